@@ -1,6 +1,6 @@
 //! Build script to generate manifests for WASM builds
 //!
-//! Scans assets/textures/ and assets/levels/ and creates manifests
+//! Scans assets/textures/, assets/levels/ and assets/sounds/ and creates manifests
 //! listing all files, since WASM can't enumerate directories at runtime.
 
 use std::fs;
@@ -10,9 +10,11 @@ use std::path::Path;
 fn main() {
     println!("cargo:rerun-if-changed=assets/textures");
     println!("cargo:rerun-if-changed=assets/levels");
+    println!("cargo:rerun-if-changed=assets/sounds");
 
     generate_texture_manifest();
     generate_levels_manifest();
+    generate_sounds_manifest();
 }
 
 /// Generate manifest for texture packs
@@ -101,3 +103,36 @@ fn generate_levels_manifest() {
     let mut file = fs::File::create(manifest_path).unwrap();
     file.write_all(manifest.as_bytes()).unwrap();
 }
+
+/// Generate manifest for ambient sounds (for WASM builds)
+fn generate_sounds_manifest() {
+    let sounds_dir = Path::new("assets/sounds");
+    let manifest_path = Path::new("assets/sounds/manifest.txt");
+
+    let mut manifest = String::new();
+
+    if sounds_dir.exists() {
+        let mut sounds: Vec<_> = fs::read_dir(sounds_dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let path = e.path();
+                path.is_file() && path
+                    .extension()
+                    .map(|ext| ext.eq_ignore_ascii_case("wav"))
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        sounds.sort_by_key(|e| e.file_name());
+
+        for sound_entry in sounds {
+            let sound_name = sound_entry.file_name().to_string_lossy().to_string();
+            manifest.push_str(&format!("{}\n", sound_name));
+        }
+    }
+
+    // Write manifest file
+    let mut file = fs::File::create(manifest_path).unwrap();
+    file.write_all(manifest.as_bytes()).unwrap();
+}
@@ -0,0 +1,49 @@
+//! Render a level to a PNG thumbnail without opening a window
+//!
+//! Usage:
+//!   cargo run --example render_thumbnail --no-default-features -- <level.ron> <out.png> [width height]
+//!
+//! Textures are loaded from assets/textures (run from the repository root).
+
+use bonnie_engine::headless::{overview_camera, render_level_frame, TextureProvider};
+use bonnie_engine::rasterizer::{RasterSettings, HEIGHT, WIDTH};
+use bonnie_engine::world::load_level;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        eprintln!("Usage: {} <level.ron> <out.png> [width height]", args[0]);
+        std::process::exit(1);
+    }
+
+    let width = args.get(3).and_then(|s| s.parse().ok()).unwrap_or(WIDTH);
+    let height = args.get(4).and_then(|s| s.parse().ok()).unwrap_or(HEIGHT);
+
+    let level = match load_level(&args[1]) {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("Failed to load {}: {}", args[1], e);
+            std::process::exit(1);
+        }
+    };
+
+    let textures = TextureProvider::from_directory("assets/textures");
+    let camera = overview_camera(&level);
+    let settings = RasterSettings::default();
+    let fb = render_level_frame(&level, &textures, &camera, width, height, &settings);
+
+    let image = match image::RgbaImage::from_raw(fb.width as u32, fb.height as u32, fb.pixels) {
+        Some(img) => img,
+        None => {
+            eprintln!("Framebuffer size mismatch");
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = image.save(&args[2]) {
+        eprintln!("Failed to write {}: {}", args[2], e);
+        std::process::exit(1);
+    }
+
+    println!("Wrote {} ({}x{})", args[2], width, height);
+}
@@ -3,7 +3,9 @@
 //! Fixed set of tools, each with its own persistent state.
 //! Switch between tools via the tab bar - all tools stay alive in background.
 
-use crate::editor::{EditorState, EditorLayout, ExampleBrowser};
+use crate::editor::{AmbientPlayer, CrashRecovery, EditorState, EditorLayout, ExampleBrowser, PauseMenu, TemplateChooser};
+use crate::game::GameSession;
+use crate::help::HelpOverlay;
 use crate::landing::LandingState;
 use crate::modeler::{ModelerState, ModelerLayout};
 use crate::tracker::TrackerState;
@@ -56,6 +58,15 @@ pub struct WorldEditorState {
     pub editor_state: EditorState,
     pub editor_layout: EditorLayout,
     pub example_browser: ExampleBrowser,
+    pub template_chooser: TemplateChooser,
+    /// Running playtest (None while editing)
+    pub game: Option<GameSession>,
+    /// Pause menu of the running playtest
+    pub pause_menu: PauseMenu,
+    /// Voices of the playtest's ambient sounds
+    pub ambient: AmbientPlayer,
+    /// Emergency save left by a crash (restore dialog open while Some)
+    pub crash_recovery: Option<CrashRecovery>,
 }
 
 /// State for the Modeler tool
@@ -81,6 +92,9 @@ pub struct AppState {
     /// Music Editor state
     pub tracker: TrackerState,
 
+    /// Help overlay (F1), shared by all tools
+    pub help: HelpOverlay,
+
     /// Icon font (Lucide)
     pub icon_font: Option<Font>,
 }
@@ -101,18 +115,31 @@ impl AppState {
                 editor_state,
                 editor_layout: EditorLayout::new(),
                 example_browser: ExampleBrowser::default(),
+                template_chooser: TemplateChooser::default(),
+                game: None,
+                pause_menu: PauseMenu::default(),
+                ambient: AmbientPlayer::default(),
+                crash_recovery: CrashRecovery::find(),
             },
             modeler: ModelerToolState {
                 modeler_state: ModelerState::new(),
                 modeler_layout: ModelerLayout::new(),
             },
             tracker: TrackerState::new(),
+            help: HelpOverlay::default(),
             icon_font,
         }
     }
 
     /// Switch to a different tool
     pub fn set_active_tool(&mut self, tool: Tool) {
+        if self.active_tool == Tool::Tracker && tool != Tool::Tracker {
+            self.tracker.on_leave();
+        }
+        if self.active_tool == Tool::WorldEditor && tool != Tool::WorldEditor {
+            // A playtest left in the background goes quiet until it's back on screen
+            self.world_editor.ambient.stop(&self.tracker.audio);
+        }
         self.active_tool = tool;
     }
 
@@ -0,0 +1,130 @@
+//! Ambient Sound Playback
+//!
+//! `SoundLibrary` holds the sounds emitters can play: the WAV files in
+//! `assets/sounds` (read from the directory natively, from its `manifest.txt` on
+//! the web). `AmbientPlayer` turns a play session's audible emitters into looping
+//! voices on the audio engine each frame: it starts voices for emitters coming into
+//! range, updates their gains and stops the ones that fell out of range.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::game::GameSession;
+use crate::tracker::{AudioEngine, PcmSound, VoiceId};
+
+/// Where sound assets live
+const SOUNDS_DIR: &str = "assets/sounds";
+
+/// Decoded sounds by name (file name without the extension), sorted by name
+#[derive(Default)]
+pub struct SoundLibrary {
+    sounds: Vec<(String, Arc<PcmSound>)>,
+}
+
+impl SoundLibrary {
+    /// Decode `bytes` and add them as `name`, reporting files that don't decode
+    fn add(&mut self, name: &str, bytes: &[u8]) {
+        match PcmSound::from_wav(bytes) {
+            Ok(sound) => self.sounds.push((name.to_string(), Arc::new(sound))),
+            Err(e) => eprintln!("Skipping sound {}: {}", name, e),
+        }
+    }
+
+    /// Load every WAV file in the sounds directory (native)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn discover() -> Self {
+        let mut library = Self::default();
+        if let Ok(entries) = std::fs::read_dir(SOUNDS_DIR) {
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                let is_wav = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("wav"));
+                let (true, Some(stem)) = (is_wav, path.file_stem()) else { continue };
+                if let Ok(bytes) = std::fs::read(&path) {
+                    library.add(&stem.to_string_lossy(), &bytes);
+                }
+            }
+        }
+        library.sounds.sort_by(|a, b| a.0.cmp(&b.0));
+        library
+    }
+
+    /// No sounds yet on the web: they're loaded async from the manifest
+    #[cfg(target_arch = "wasm32")]
+    pub fn discover() -> Self {
+        Self::default()
+    }
+
+    /// Load the sounds listed in the sounds manifest (one file name per line)
+    pub async fn load_from_manifest() -> Self {
+        use macroquad::prelude::*;
+
+        let mut library = Self::default();
+        let manifest = match load_string(&format!("{}/manifest.txt", SOUNDS_DIR)).await {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Failed to load sounds manifest: {}", e);
+                return library;
+            }
+        };
+        for line in manifest.lines().map(str::trim) {
+            let Some(name) = line.strip_suffix(".wav") else { continue };
+            match load_file(&format!("{}/{}", SOUNDS_DIR, line)).await {
+                Ok(bytes) => library.add(name, &bytes),
+                Err(e) => eprintln!("Failed to load sound {}: {}", line, e),
+            }
+        }
+        library.sounds.sort_by(|a, b| a.0.cmp(&b.0));
+        library
+    }
+
+    /// Sound names, in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sounds.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<PcmSound>> {
+        self.sounds.iter().find(|(n, _)| n == name).map(|(_, sound)| sound)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sounds.is_empty()
+    }
+}
+
+/// Voices of the emitters sounding in a play session
+#[derive(Default)]
+pub struct AmbientPlayer {
+    /// Voice per emitter, by (room index, emitter index)
+    voices: HashMap<(usize, usize), VoiceId>,
+}
+
+impl AmbientPlayer {
+    /// Bring the voices in line with what the player hears now. One-shot emitters
+    /// keep their (finished) voice while in range, so they play once per visit.
+    pub fn update(&mut self, audio: &AudioEngine, sounds: &SoundLibrary, session: &GameSession) {
+        let gains = session.ambient_gains();
+        self.voices.retain(|&(room, index), id| {
+            let audible = gains.iter().any(|g| g.room == room && g.index == index);
+            if !audible {
+                audio.stop_voice(*id);
+            }
+            audible
+        });
+        for gain in gains {
+            if let Some(&id) = self.voices.get(&(gain.room, gain.index)) {
+                audio.set_voice_gain(id, gain.gain);
+                continue;
+            }
+            let emitter = &session.level.rooms[gain.room].ambients[gain.index];
+            if let Some(sound) = sounds.get(&emitter.sound) {
+                let id = audio.play_voice(Arc::clone(sound), gain.gain, emitter.looping);
+                self.voices.insert((gain.room, gain.index), id);
+            }
+        }
+    }
+
+    /// Silence every emitter (pausing or leaving play)
+    pub fn stop(&mut self, audio: &AudioEngine) {
+        for (_, id) in self.voices.drain() {
+            audio.stop_voice(id);
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! Emergency Saves
+//!
+//! A panic in the frame loop would otherwise take every unsaved change with it.
+//! While the level has unsaved changes, `track_crash_snapshot` keeps a serialized
+//! copy of it (refreshed at most once a second, and only when the level changed;
+//! serialized on a worker thread so big levels don't hitch). The panic hook writes that copy
+//! to `emergency_save.ron` in the config directory (localStorage on the web) and
+//! appends the panic to `crash_log.txt` before the default hook runs. Serializing
+//! ahead of time keeps the hook trivial: a second panic inside a panic hook aborts
+//! instead of unwinding. On the next start the editor offers to restore the save.
+
+use std::panic;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use macroquad::prelude::*;
+use crate::ui::{draw_button, Rect, UiContext, ACCENT_COLOR};
+use crate::world::{load_level_from_str, Level, LevelError};
+use super::EditorState;
+
+/// Seconds between snapshots of a level that keeps changing
+const SNAPSHOT_INTERVAL: f64 = 1.0;
+/// Backtrace lines kept in the crash log
+#[cfg(not(target_arch = "wasm32"))]
+const BACKTRACE_LINES: usize = 40;
+/// First line of a save of a level with a file, followed by its path (RON comment)
+const HEADER: &str = "// Emergency save of ";
+/// First line of a save of a level that was never saved
+const HEADER_UNTITLED: &str = "// Emergency save\n";
+
+/// Serialized level waiting for a crash
+struct Snapshot {
+    generation: u64,
+    text: String,
+}
+
+/// Copy of the level on its way to becoming a snapshot
+struct SnapshotJob {
+    generation: u64,
+    header: String,
+    level: Level,
+}
+
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+/// Generation and time of the last copy handed to the worker
+static LAST_JOB: Mutex<Option<(u64, f64)>> = Mutex::new(None);
+/// Generation the level was last saved at: jobs from before it are stale
+static CLEAN_AT: AtomicU64 = AtomicU64::new(0);
+/// The worker is serializing a copy (don't make another yet)
+static BUSY: AtomicBool = AtomicBool::new(false);
+/// The hook is running on some thread (a second panic only logs)
+static IN_HOOK: AtomicBool = AtomicBool::new(false);
+/// The hook stored a save this run (a caught panic: drop it once the level is saved)
+static STORED: AtomicBool = AtomicBool::new(false);
+
+/// Keep the crash snapshot in step with the level (call once per frame)
+pub fn track_crash_snapshot(state: &EditorState) {
+    let mut last = LAST_JOB.lock().unwrap_or_else(|e| e.into_inner());
+    let generation = state.generation();
+    if !state.is_dirty() {
+        CLEAN_AT.store(generation, Ordering::SeqCst);
+        *last = None;
+        *SNAPSHOT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        if STORED.swap(false, Ordering::SeqCst) {
+            clear_stored();
+        }
+        return;
+    }
+    let now = get_time();
+    if last.is_some_and(|(g, time)| g == generation || now - time < SNAPSHOT_INTERVAL) {
+        return;
+    }
+    if BUSY.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let header = match &state.current_file {
+        Some(path) => format!("{}{}\n", HEADER, path.display()),
+        None => HEADER_UNTITLED.to_string(),
+    };
+    *last = Some((generation, now));
+    submit(SnapshotJob { generation, header, level: state.level.clone() });
+}
+
+/// Serialize a copy of the level into the snapshot, unless a newer snapshot or a
+/// save overtook it. A level that fails to serialize keeps the previous snapshot
+/// rather than taking the editor down.
+fn take_snapshot(job: SnapshotJob) {
+    let SnapshotJob { generation, header, level } = job;
+    if let Ok(Ok(body)) = panic::catch_unwind(panic::AssertUnwindSafe(|| ron::to_string(&level))) {
+        let mut snapshot = SNAPSHOT.lock().unwrap_or_else(|e| e.into_inner());
+        let newer = snapshot.as_ref().is_none_or(|s| s.generation < generation);
+        if newer && generation > CLEAN_AT.load(Ordering::SeqCst) {
+            *snapshot = Some(Snapshot { generation, text: header + &body });
+        }
+    }
+    BUSY.store(false, Ordering::SeqCst);
+}
+
+/// Hand a copy to the snapshot worker thread (started on first use; serialized
+/// right here if it can't be)
+#[cfg(not(target_arch = "wasm32"))]
+fn submit(job: SnapshotJob) {
+    use std::sync::mpsc::{channel, SendError, Sender};
+    use std::sync::OnceLock;
+    static WORKER: OnceLock<Option<Sender<SnapshotJob>>> = OnceLock::new();
+    let worker = WORKER.get_or_init(|| {
+        let (sender, receiver) = channel::<SnapshotJob>();
+        std::thread::Builder::new()
+            .name("crash snapshot".to_string())
+            .spawn(move || receiver.into_iter().for_each(take_snapshot))
+            .ok()
+            .map(|_| sender)
+    });
+    match worker {
+        Some(sender) => {
+            if let Err(SendError(job)) = sender.send(job) {
+                take_snapshot(job);
+            }
+        }
+        None => take_snapshot(job),
+    }
+}
+
+/// No threads on the web: serialize right away
+#[cfg(target_arch = "wasm32")]
+fn submit(job: SnapshotJob) {
+    take_snapshot(job);
+}
+
+/// Install the panic hook that writes the emergency save and the crash log, then
+/// runs the default hook (call once at startup)
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !IN_HOOK.swap(true, Ordering::SeqCst) {
+            // try_lock: the panic may come from a thread holding the snapshot lock
+            let saved = match SNAPSHOT.try_lock() {
+                Ok(snapshot) => snapshot.as_ref().map(|s| store(&s.text)),
+                Err(_) => Some(false),
+            };
+            if saved == Some(true) {
+                STORED.store(true, Ordering::SeqCst);
+            }
+            log_crash(info, saved);
+            IN_HOOK.store(false, Ordering::SeqCst);
+        }
+        default_hook(info);
+    }));
+}
+
+/// Where the emergency save goes (None without a config directory)
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path() -> Option<PathBuf> {
+    Some(super::prefs::config_dir()?.join("emergency_save.ron"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store(text: &str) -> bool {
+    let Some(path) = save_path() else { return false };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    std::fs::write(path, text).is_ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_stored() -> Option<String> {
+    std::fs::read_to_string(save_path()?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn clear_stored() {
+    if let Some(path) = save_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Rename the save to `emergency_save_<unix time>.ron` (left in place if that fails)
+#[cfg(not(target_arch = "wasm32"))]
+fn set_aside_stored() -> String {
+    let Some(path) = save_path() else { return "emergency_save.ron".to_string() };
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let kept = path.with_file_name(format!("emergency_save_{}.ron", time));
+    let kept = if std::fs::rename(&path, &kept).is_ok() { kept } else { path };
+    kept.display().to_string()
+}
+
+/// Append the panic, whether the level was saved and the start of a backtrace to
+/// `crash_log.txt` next to the emergency save
+#[cfg(not(target_arch = "wasm32"))]
+fn log_crash(info: &panic::PanicHookInfo, saved: Option<bool>) {
+    use std::io::Write;
+    let Some(path) = super::prefs::config_dir().map(|dir| dir.join("crash_log.txt")) else { return };
+    let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(&path) else { return };
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let save = match saved {
+        Some(true) => "level written to emergency_save.ron",
+        Some(false) => "FAILED to write the level",
+        None => "no unsaved changes",
+    };
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    let backtrace: Vec<&str> = backtrace.lines().take(BACKTRACE_LINES).collect();
+    let _ = writeln!(file, "=== Bonnie Engine v{} crashed (unix time {}) ===\n{}\nEmergency save: {}\n{}\n",
+        crate::VERSION, time, info, save, backtrace.join("\n"));
+    eprintln!("Crash logged to {}", path.display());
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn bonnie_store_emergency_save(ptr: *const u8, len: usize);
+    fn bonnie_get_emergency_save_len() -> usize;
+    fn bonnie_copy_emergency_save(ptr: *mut u8, max_len: usize) -> usize;
+    fn bonnie_clear_emergency_save();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn store(text: &str) -> bool {
+    unsafe { bonnie_store_emergency_save(text.as_ptr(), text.len()) };
+    true
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_stored() -> Option<String> {
+    let len = unsafe { bonnie_get_emergency_save_len() };
+    if len == 0 {
+        return None;
+    }
+    let mut bytes = vec![0u8; len];
+    let copied = unsafe { bonnie_copy_emergency_save(bytes.as_mut_ptr(), len) };
+    bytes.truncate(copied);
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn clear_stored() {
+    unsafe { bonnie_clear_emergency_save() };
+}
+
+/// Left in local storage (offered again on the next start)
+#[cfg(target_arch = "wasm32")]
+fn set_aside_stored() -> String {
+    "the browser's local storage".to_string()
+}
+
+/// The browser console already shows the panic; there's no file to log to
+#[cfg(target_arch = "wasm32")]
+fn log_crash(_info: &panic::PanicHookInfo, _saved: Option<bool>) {}
+
+/// Emergency save left by a crash, found at startup
+pub struct CrashRecovery {
+    text: String,
+    /// File the level was last saved to (None if it never was)
+    pub path: Option<PathBuf>,
+}
+
+impl CrashRecovery {
+    /// The emergency save, if the last run crashed with unsaved changes
+    pub fn find() -> Option<Self> {
+        load_stored().map(Self::from_text)
+    }
+
+    fn from_text(text: String) -> Self {
+        let path = text.lines().next().and_then(|line| line.strip_prefix(HEADER)).map(PathBuf::from);
+        Self { text, path }
+    }
+
+    /// File name of the level, or "an untitled level"
+    pub fn name(&self) -> String {
+        self.path.as_ref()
+            .and_then(|p| p.file_name())
+            .map_or("an untitled level".to_string(), |name| name.to_string_lossy().into_owned())
+    }
+
+    pub fn restore(&self) -> Result<Level, LevelError> {
+        load_level_from_str(&self.text)
+    }
+
+    /// Delete the emergency save (after restoring it or declining to)
+    pub fn discard(self) {
+        clear_stored();
+    }
+
+    /// Keep an emergency save that failed to restore, out of the way of the next
+    /// crash's save. Returns where it is now.
+    pub fn set_aside(self) -> String {
+        set_aside_stored()
+    }
+}
+
+impl EditorState {
+    /// Open a restored emergency save: the level comes back under its old file
+    /// name, with its changes still unsaved
+    pub fn open_emergency_save(&mut self, level: Level, path: Option<PathBuf>) {
+        self.load_level(level, path.clone().unwrap_or_default());
+        self.current_file = path;
+        self.mark_level_dirty();
+        self.set_status("Restored unsaved changes from the emergency save", 4.0);
+    }
+}
+
+/// Result from drawing the crash recovery dialog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecoveryAction {
+    None,
+    Restore,
+    Discard,
+}
+
+/// Draw the crash recovery modal dialog
+pub fn draw_crash_recovery(ctx: &mut UiContext, recovery: &CrashRecovery) -> RecoveryAction {
+    let mut action = RecoveryAction::None;
+    let theme = ctx.theme;
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let dialog_w = 460.0;
+    let dialog_h = 160.0;
+    let dialog_x = ((screen_width() - dialog_w) / 2.0).floor();
+    let dialog_y = ((screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(dialog_x, dialog_y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(dialog_x, dialog_y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    let header_h = 40.0;
+    draw_rectangle(dialog_x, dialog_y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    draw_text("Restore Unsaved Work", dialog_x + 16.0, dialog_y + 26.0, 20.0, WHITE);
+
+    let y = dialog_y + header_h + 24.0;
+    draw_text(&format!("The editor crashed with unsaved changes to {}.", recovery.name()),
+        dialog_x + 16.0, y, 14.0, theme.warning);
+    draw_text("Restore opens them as unsaved changes; Discard deletes the emergency save.",
+        dialog_x + 16.0, y + 22.0, 13.0, theme.text_dim);
+
+    // Footer buttons
+    let footer_y = dialog_y + dialog_h - 40.0;
+    let discard_rect = Rect::new(dialog_x + dialog_w - 236.0, footer_y + 6.0, 100.0, 28.0);
+    if draw_button(ctx, discard_rect, "Discard", Color::from_rgba(60, 60, 70, 255)) {
+        action = RecoveryAction::Discard;
+    }
+    let restore_rect = Rect::new(dialog_x + dialog_w - 116.0, footer_y + 6.0, 100.0, 28.0);
+    if draw_button(ctx, restore_rect, "Restore", ACCENT_COLOR) {
+        action = RecoveryAction::Restore;
+    }
+
+    // Keyboard: Enter restores (discarding takes a click)
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+        action = RecoveryAction::Restore;
+    }
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_empty_level;
+
+    #[test]
+    fn emergency_saves_load_with_their_header() {
+        let body = ron::to_string(&create_empty_level()).unwrap();
+
+        let saved = CrashRecovery::from_text(format!("{}levels/tomb.ron\n{}", HEADER, body));
+        assert_eq!(saved.path, Some(PathBuf::from("levels/tomb.ron")));
+        assert_eq!(saved.name(), "tomb.ron");
+        assert!(saved.restore().is_ok());
+
+        let untitled = CrashRecovery::from_text(format!("{}{}", HEADER_UNTITLED, body));
+        assert_eq!(untitled.path, None);
+        assert_eq!(untitled.name(), "an untitled level");
+        assert!(untitled.restore().is_ok());
+    }
+
+    #[test]
+    fn late_snapshots_dont_replace_newer_ones() {
+        let job = |generation| SnapshotJob { generation, header: HEADER_UNTITLED.to_string(), level: create_empty_level() };
+        let generation = || SNAPSHOT.lock().unwrap().as_ref().map(|s| s.generation);
+        CLEAN_AT.store(10, Ordering::SeqCst);
+        take_snapshot(job(12));
+        take_snapshot(job(11));
+        assert_eq!(generation(), Some(12));
+
+        // A copy from before the last save is stale
+        *SNAPSHOT.lock().unwrap() = None;
+        take_snapshot(job(9));
+        assert_eq!(generation(), None);
+    }
+}
@@ -1,20 +1,36 @@
 //! Example Level Browser
 //!
-//! Modal dialog for browsing and previewing bundled example levels.
+//! Modal dialog for browsing and previewing bundled example levels. The same dialog
+//! lists room prefabs (with thumbnails) for Insert Prefab.
 
 use macroquad::prelude::*;
 use crate::ui::{Rect, UiContext, draw_icon_centered, draw_scrollable_list, ACCENT_COLOR};
-use crate::world::Level;
+use crate::world::{Level, RoomPrefab};
 use crate::rasterizer::{Framebuffer, Texture as RasterTexture, Camera, render_mesh, Color as RasterColor, Vec3, RasterSettings};
 use super::example_levels::{ExampleLevelInfo, LevelStats, get_level_stats};
-use super::TexturePack;
+use super::room_thumbnails::RoomThumbnails;
+use super::{texture_index, TexturePack};
+
+/// What the browser lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserKind {
+    Levels,
+    Prefabs,
+}
 
 /// State for the example browser dialog
 pub struct ExampleBrowser {
     /// Whether the browser is open
     pub open: bool,
-    /// List of available example levels
+    /// Levels or prefabs
+    pub kind: BrowserKind,
+    /// List of available example levels (or prefab files)
     pub examples: Vec<ExampleLevelInfo>,
+    /// Loaded prefabs, in list order (prefab browser only)
+    pub prefabs: Vec<RoomPrefab>,
+    /// The prefab rooms as one level (room id = list index), for the thumbnails
+    prefab_library: Level,
+    prefab_thumbnails: RoomThumbnails,
     /// Currently selected index
     pub selected_index: Option<usize>,
     /// Currently loaded preview level
@@ -37,7 +53,11 @@ impl Default for ExampleBrowser {
     fn default() -> Self {
         Self {
             open: false,
+            kind: BrowserKind::Levels,
             examples: Vec::new(),
+            prefabs: Vec::new(),
+            prefab_library: Level::new(),
+            prefab_thumbnails: RoomThumbnails::new(),
             selected_index: None,
             preview_level: None,
             preview_stats: None,
@@ -56,13 +76,39 @@ impl ExampleBrowser {
     /// Open the browser with the given list of examples
     pub fn open(&mut self, examples: Vec<ExampleLevelInfo>) {
         self.open = true;
+        self.kind = BrowserKind::Levels;
         self.examples = examples;
+        self.prefabs.clear();
         self.selected_index = None;
         self.preview_level = None;
         self.preview_stats = None;
         self.scroll_offset = 0.0;
     }
 
+    /// Open the browser on the prefab library (already loaded, so previews are instant)
+    pub fn open_prefabs(&mut self, prefabs: Vec<(ExampleLevelInfo, RoomPrefab)>) {
+        let (infos, prefabs): (Vec<_>, Vec<_>) = prefabs.into_iter().unzip();
+        self.open(infos);
+        self.kind = BrowserKind::Prefabs;
+        self.prefab_library = Level::new();
+        for (i, prefab) in prefabs.iter().enumerate() {
+            let mut room = prefab.room.clone();
+            room.id = i;
+            self.prefab_library.rooms.push(room);
+        }
+        self.prefab_thumbnails = RoomThumbnails::new();
+        self.prefabs = prefabs;
+    }
+
+    /// Show a listed prefab in the preview
+    pub fn preview_prefab(&mut self, index: usize) {
+        if let Some(room) = self.prefab_library.rooms.get(index) {
+            let mut level = Level::new();
+            level.rooms.push(room.clone());
+            self.set_preview(level);
+        }
+    }
+
     /// Close the browser
     pub fn close(&mut self) {
         self.open = false;
@@ -144,6 +190,12 @@ impl ExampleBrowser {
     pub fn selected_example(&self) -> Option<&ExampleLevelInfo> {
         self.selected_index.and_then(|i| self.examples.get(i))
     }
+
+    /// Name and prefab of the selected prefab
+    pub fn selected_prefab(&self) -> Option<(&str, &RoomPrefab)> {
+        let i = self.selected_index?;
+        Some((self.examples.get(i)?.name.as_str(), self.prefabs.get(i)?))
+    }
 }
 
 /// Result from drawing the example browser
@@ -156,6 +208,10 @@ pub enum BrowserAction {
     OpenLevel,
     /// User wants to start with a new empty level
     NewLevel,
+    /// Stamp the selected prefab next to the current room
+    InsertPrefab,
+    /// Stamp the selected prefab where the user clicks in the 2D grid
+    PlacePrefab,
     /// User cancelled
     Cancel,
 }
@@ -173,6 +229,7 @@ pub fn draw_example_browser(
     }
 
     let mut action = BrowserAction::None;
+    let prefabs = browser.kind == BrowserKind::Prefabs;
 
     // Darken background
     draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 180));
@@ -191,7 +248,8 @@ pub fn draw_example_browser(
     // Header
     let header_h = 40.0;
     draw_rectangle(dialog_x, dialog_y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
-    draw_text("Browse Levels", dialog_x + 16.0, dialog_y + 26.0, 20.0, WHITE);
+    let title = if prefabs { "Insert Prefab" } else { "Browse Levels" };
+    draw_text(title, dialog_x + 16.0, dialog_y + 26.0, 20.0, WHITE);
 
     // Close button
     let close_rect = Rect::new(dialog_x + dialog_w - 36.0, dialog_y + 4.0, 32.0, 32.0);
@@ -206,7 +264,7 @@ pub fn draw_example_browser(
 
     // List panel (left) - use reusable scrollable list widget
     let list_rect = Rect::new(dialog_x + 8.0, content_y, list_w, content_h);
-    let item_h = 28.0;
+    let item_h = if prefabs { THUMBNAIL_ROW_HEIGHT } else { 28.0 };
 
     // Convert examples to string labels
     let items: Vec<String> = browser.examples.iter().map(|e| e.name.clone()).collect();
@@ -221,6 +279,10 @@ pub fn draw_example_browser(
         None, // Use default colors
     );
 
+    if prefabs {
+        draw_prefab_thumbnails(browser, list_rect, item_h, texture_packs);
+    }
+
     // Handle list click
     if let Some(clicked_idx) = list_result.clicked {
         if browser.selected_index != Some(clicked_idx) {
@@ -254,6 +316,8 @@ pub fn draw_example_browser(
             );
             draw_text(&stats_text, preview_rect.x + 8.0, stats_y + 17.0, 14.0, Color::from_rgba(180, 180, 180, 255));
         }
+    } else if prefabs && browser.examples.is_empty() {
+        draw_text("No prefabs yet: use Save as Prefab on a room", preview_rect.x + 20.0, preview_rect.y + 40.0, 16.0, Color::from_rgba(100, 100, 100, 255));
     } else if has_selection {
         // Loading indicator
         draw_text("Loading preview...", preview_rect.x + 20.0, preview_rect.y + 40.0, 16.0, Color::from_rgba(150, 150, 150, 255));
@@ -267,13 +331,16 @@ pub fn draw_example_browser(
     draw_rectangle(dialog_x, footer_y, dialog_w, 44.0, Color::from_rgba(40, 40, 48, 255));
 
     // New button (left side) - start with empty level
-    let new_rect = Rect::new(dialog_x + 10.0, footer_y + 8.0, 80.0, 28.0);
-    if draw_text_button(ctx, new_rect, "New", Color::from_rgba(60, 60, 70, 255)) {
-        action = BrowserAction::NewLevel;
+    if !prefabs {
+        let new_rect = Rect::new(dialog_x + 10.0, footer_y + 8.0, 80.0, 28.0);
+        if draw_text_button(ctx, new_rect, "New", Color::from_rgba(60, 60, 70, 255)) {
+            action = BrowserAction::NewLevel;
+        }
     }
 
     // Cancel button
-    let cancel_rect = Rect::new(dialog_x + dialog_w - 180.0, footer_y + 8.0, 80.0, 28.0);
+    let cancel_x = if prefabs { dialog_x + dialog_w - 270.0 } else { dialog_x + dialog_w - 180.0 };
+    let cancel_rect = Rect::new(cancel_x, footer_y + 8.0, 80.0, 28.0);
     if draw_text_button(ctx, cancel_rect, "Cancel", Color::from_rgba(60, 60, 70, 255)) {
         action = BrowserAction::Cancel;
     }
@@ -281,7 +348,16 @@ pub fn draw_example_browser(
     // Open button (only enabled if something is selected)
     let open_rect = Rect::new(dialog_x + dialog_w - 90.0, footer_y + 8.0, 80.0, 28.0);
     let open_enabled = browser.preview_level.is_some();
-    if draw_text_button_enabled(ctx, open_rect, "Open", ACCENT_COLOR, open_enabled) {
+    if prefabs {
+        // Place: pick the spot in the 2D grid; Insert: next to the current room
+        let place_rect = Rect::new(dialog_x + dialog_w - 180.0, footer_y + 8.0, 80.0, 28.0);
+        if draw_text_button_enabled(ctx, place_rect, "Place...", Color::from_rgba(60, 60, 70, 255), open_enabled) {
+            action = BrowserAction::PlacePrefab;
+        }
+        if draw_text_button_enabled(ctx, open_rect, "Insert", ACCENT_COLOR, open_enabled) {
+            action = BrowserAction::InsertPrefab;
+        }
+    } else if draw_text_button_enabled(ctx, open_rect, "Open", ACCENT_COLOR, open_enabled) {
         action = BrowserAction::OpenLevel;
     }
 
@@ -293,6 +369,32 @@ pub fn draw_example_browser(
     action
 }
 
+/// Row height of the prefab list (fits a thumbnail)
+const THUMBNAIL_ROW_HEIGHT: f32 = 44.0;
+
+/// Draw a thumbnail at the right end of each fully visible prefab row
+fn draw_prefab_thumbnails(browser: &mut ExampleBrowser, list_rect: Rect, item_h: f32, texture_packs: &[TexturePack]) {
+    let library = &browser.prefab_library;
+    if library.rooms.is_empty() {
+        return;
+    }
+    browser.prefab_thumbnails.begin_frame(library, browser.selected_index.unwrap_or(0));
+    let textures: Vec<RasterTexture> = texture_packs.iter().flat_map(|pack| &pack.textures).cloned().collect();
+    let settings = RasterSettings::default();
+    let size = item_h - 4.0;
+    for i in 0..library.rooms.len() {
+        let y = list_rect.y + i as f32 * item_h - browser.scroll_offset + 2.0;
+        if y < list_rect.y || y + size > list_rect.bottom() {
+            continue;
+        }
+        let Some(texture) = browser.prefab_thumbnails.get(library, i, texture_packs, &textures, &settings) else {
+            continue;
+        };
+        let x = list_rect.right() - size - 10.0;
+        draw_texture_ex(&texture, x, y, WHITE, DrawTextureParams { dest_size: Some(vec2(size, size)), ..Default::default() });
+    }
+}
+
 /// Draw the orbit preview of a level
 fn draw_orbit_preview(
     ctx: &mut UiContext,
@@ -324,10 +426,10 @@ fn draw_orbit_preview(
             browser.dragging = false;
         }
 
-        // Scroll to zoom
+        // Scroll to zoom, a fixed step per notch (wheel deltas differ between native and web)
         let scroll = mouse_wheel().1;
         if scroll != 0.0 {
-            browser.orbit_distance = (browser.orbit_distance - scroll * 100.0).clamp(500.0, 20000.0);
+            browser.orbit_distance = (browser.orbit_distance - scroll.signum() * 400.0).clamp(500.0, 20000.0);
         }
     } else {
         browser.dragging = false;
@@ -394,7 +496,7 @@ fn draw_orbit_preview(
     fb.clear(RasterColor::new(15, 15, 20));
 
     // Render settings
-    let settings = RasterSettings::default();
+    let mut settings = RasterSettings::default();
 
     // Build flattened textures array and texture map (same as main viewport)
     let textures: Vec<RasterTexture> = texture_packs
@@ -403,24 +505,12 @@ fn draw_orbit_preview(
         .cloned()
         .collect();
 
-    let mut texture_map: std::collections::HashMap<(String, String), usize> = std::collections::HashMap::new();
-    let mut texture_idx = 0;
-    for pack in texture_packs {
-        for tex in &pack.textures {
-            texture_map.insert((pack.name.clone(), tex.name.clone()), texture_idx);
-            texture_idx += 1;
-        }
-    }
-
-    let resolve_texture = |tex_ref: &crate::world::TextureRef| -> Option<usize> {
-        if !tex_ref.is_valid() {
-            return Some(0); // Fallback to first texture
-        }
-        texture_map.get(&(tex_ref.pack.clone(), tex_ref.name.clone())).copied()
-    };
+    let index = texture_index(texture_packs);
+    let resolve_texture = |tex_ref: &crate::world::TextureRef| index.resolve(tex_ref, &level.pack_aliases);
 
     // Render each room using the same method as the main viewport
-    for room in &level.rooms {
+    for (i, room) in level.rooms.iter().enumerate() {
+        settings.light_dir = level.room_light_dir(i);
         let (vertices, faces) = room.to_render_data_with_textures(&resolve_texture);
         if !vertices.is_empty() {
             render_mesh(fb, &vertices, &faces, &textures, &camera, &settings);
@@ -476,7 +566,7 @@ fn draw_text_button_enabled(ctx: &mut UiContext, rect: Rect, text: &str, bg_colo
     let color = if !enabled {
         Color::from_rgba(50, 50, 55, 255)
     } else if hovered {
-        Color::new(bg_color.r * 1.2, bg_color.g * 1.2, bg_color.b * 1.2, bg_color.a)
+        RasterColor::from(bg_color).scale_rgb(1.2).to_mq()
     } else {
         bg_color
     };
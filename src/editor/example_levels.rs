@@ -3,7 +3,7 @@
 //! Handles loading bundled levels from disk (native) or via manifest (WASM).
 
 use std::path::PathBuf;
-use crate::world::{Level, load_level};
+use crate::world::{Level, Room, load_level};
 
 #[cfg(target_arch = "wasm32")]
 use crate::world::load_level_from_str;
@@ -104,41 +104,48 @@ pub async fn load_example_level(path: &PathBuf) -> Option<Level> {
 
 /// Get level statistics without fully loading (for preview info)
 pub fn get_level_stats(level: &Level) -> LevelStats {
-    let room_count = level.rooms.len();
-    let mut sector_count = 0;
-    let mut floor_count = 0;
-    let mut wall_count = 0;
-
+    let mut stats = LevelStats::default();
     for room in &level.rooms {
-        for row in &room.sectors {
-            for sector_opt in row {
-                if let Some(sector) = sector_opt {
-                    sector_count += 1;
-                    if sector.floor.is_some() {
-                        floor_count += 1;
-                    }
-                    wall_count += sector.walls_north.len();
-                    wall_count += sector.walls_east.len();
-                    wall_count += sector.walls_south.len();
-                    wall_count += sector.walls_west.len();
+        stats.add(&get_room_stats(room));
+    }
+    stats
+}
+
+/// Statistics for a single room (`room_count` is 1)
+pub fn get_room_stats(room: &Room) -> LevelStats {
+    let mut stats = LevelStats { room_count: 1, ..LevelStats::default() };
+    for row in &room.sectors {
+        for sector_opt in row {
+            if let Some(sector) = sector_opt {
+                stats.sector_count += 1;
+                if sector.floor.is_some() {
+                    stats.floor_count += 1;
                 }
+                stats.wall_count += sector.walls_north.len();
+                stats.wall_count += sector.walls_east.len();
+                stats.wall_count += sector.walls_south.len();
+                stats.wall_count += sector.walls_west.len();
             }
         }
     }
-
-    LevelStats {
-        room_count,
-        sector_count,
-        floor_count,
-        wall_count,
-    }
+    stats
 }
 
 /// Statistics about a level
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct LevelStats {
     pub room_count: usize,
     pub sector_count: usize,
     pub floor_count: usize,
     pub wall_count: usize,
 }
+
+impl LevelStats {
+    /// Add another room's (or level's) counts to these
+    pub fn add(&mut self, other: &LevelStats) {
+        self.room_count += other.room_count;
+        self.sector_count += other.sector_count;
+        self.floor_count += other.floor_count;
+        self.wall_count += other.wall_count;
+    }
+}
@@ -0,0 +1,151 @@
+//! Find Faces
+//!
+//! State for the Find popup: the query, the faces found so far and the one picked
+//! last. The scan covers a slice of rooms per frame, so a big level fills the list
+//! over a few frames instead of stalling one; an edit to the level while it runs
+//! starts it over.
+
+use crate::world::{FaceLocation, FaceQuery, HitFace};
+use super::{EditorState, SectorFace, Selection};
+
+/// Sectors scanned per frame (a few faces each)
+const SCAN_SECTORS_PER_FRAME: usize = 8192;
+
+/// Find popup state
+#[derive(Debug, Clone)]
+pub struct FindFaces {
+    pub query: FaceQuery,
+    /// Floor height range (world units), kept while that criterion is off
+    pub height_range: (f32, f32),
+    pub results: Vec<FaceLocation>,
+    /// List text of each result
+    pub labels: Vec<String>,
+    /// Result picked last (the arrow keys step from it)
+    pub current: Option<usize>,
+    pub scroll: f32,
+    /// Scan in progress: next room, and the level generation it started from
+    scan: Option<(usize, u64)>,
+}
+
+impl Default for FindFaces {
+    fn default() -> Self {
+        Self {
+            query: FaceQuery::default(),
+            height_range: (-2048.0, 0.0),
+            results: Vec::new(),
+            labels: Vec::new(),
+            current: None,
+            scroll: 0.0,
+            scan: None,
+        }
+    }
+}
+
+impl FindFaces {
+    /// Scan still running?
+    pub fn scanning(&self) -> bool {
+        self.scan.is_some()
+    }
+}
+
+/// List text of a result: "Room 2 (3, 4) north wall 1"
+fn result_label(location: &FaceLocation) -> String {
+    let face = match location.face {
+        HitFace::Floor => "floor".to_string(),
+        HitFace::Ceiling => "ceiling".to_string(),
+        HitFace::Wall(dir, i) => format!("{} wall {}", format!("{:?}", dir).to_lowercase(), i),
+    };
+    format!("Room {} ({}, {}) {}", location.room, location.x, location.z, face)
+}
+
+impl EditorState {
+    /// Start a Find scan with the popup's query (results come in over the next frames)
+    pub fn run_find(&mut self) {
+        let generation = self.generation();
+        let Some(find) = &mut self.find_faces else { return };
+        if find.query.is_empty() {
+            self.set_status("Pick something to find", 2.0);
+            return;
+        }
+        find.results.clear();
+        find.labels.clear();
+        find.current = None;
+        find.scroll = 0.0;
+        find.scan = Some((0, generation));
+    }
+
+    /// Scan the next slice of rooms for the running Find (call once per frame)
+    pub fn step_find(&mut self) {
+        let generation = self.generation();
+        let Some(find) = &mut self.find_faces else { return };
+        let Some((mut next, started)) = find.scan else { return };
+        if started != generation {
+            next = 0;
+            find.results.clear();
+            find.labels.clear();
+            find.current = None;
+        }
+
+        let rooms = &self.level.rooms;
+        let first = next;
+        let mut budget = SCAN_SECTORS_PER_FRAME;
+        while next < rooms.len() && budget > 0 {
+            budget = budget.saturating_sub(rooms[next].width * rooms[next].depth);
+            next += 1;
+        }
+        let found = self.level.find_faces_in(first..next, &find.query);
+        find.labels.extend(found.iter().map(result_label));
+        find.results.extend(found);
+
+        if next < rooms.len() {
+            find.scan = Some((next, generation));
+        } else {
+            find.scan = None;
+            let count = find.results.len();
+            self.set_status(&format!("Found {} face(s)", count), 2.0);
+        }
+    }
+
+    /// Select a Find result's face (framing it with Sync Views on)
+    pub fn select_find_result(&mut self, index: usize) {
+        let Some(find) = &mut self.find_faces else { return };
+        let Some(&location) = find.results.get(index) else { return };
+        find.current = Some(index);
+
+        let FaceLocation { room, x, z, face } = location;
+        let Some(sector) = self.level.rooms.get(room).and_then(|r| r.get_sector(x, z)) else {
+            self.set_warning("That face is gone (the level changed since the search)");
+            return;
+        };
+        let face = match face {
+            HitFace::Floor => sector.floor.as_ref().map(|_| SectorFace::Floor),
+            HitFace::Ceiling => sector.ceiling.as_ref().map(|_| SectorFace::Ceiling),
+            HitFace::Wall(dir, i) => SectorFace::wall_at(sector, dir, i),
+        };
+        let Some(face) = face else {
+            self.set_warning("That face is gone (the level changed since the search)");
+            return;
+        };
+        self.current_room = room;
+        self.selection = Selection::SectorFace { room, x, z, face };
+        if self.sync_views {
+            self.center_grid_on_selection();
+            self.frame_selection();
+        }
+    }
+
+    /// Select the Find result `delta` rows from the current one (arrow keys)
+    pub fn step_find_result(&mut self, delta: isize) {
+        let Some(find) = &self.find_faces else { return };
+        if find.results.is_empty() {
+            return;
+        }
+        let last = find.results.len() - 1;
+        let index = match find.current {
+            Some(current) => current.saturating_add_signed(delta).min(last),
+            None if delta < 0 => last,
+            None => 0,
+        };
+        self.select_find_result(index);
+    }
+}
@@ -0,0 +1,258 @@
+//! Game View - Playtest the level being edited
+//!
+//! Reads keyboard/mouse into a `GameInput`, steps the `GameSession` and renders it
+//! full-size. The session is built fresh for every Play and owns everything the run
+//! changes, so the editor's level is never touched. Escape pauses the run and opens
+//! a small menu over the last frame: Resume, Restart Level or Exit to Editor (not
+//! when playing standalone, with no editor to go back to).
+
+use macroquad::prelude::*;
+use crate::game::{GameInput, GameSession};
+use crate::rasterizer::{
+    Framebuffer, Texture as RasterTexture, render_mesh, Color as RasterColor,
+    WIDTH, HEIGHT, WIDTH_HI, HEIGHT_HI,
+};
+use crate::ui::{Rect, UiContext, ACCENT_COLOR};
+use crate::world::TextureRef;
+use super::{texture_index, TexturePack};
+
+/// Mouse look sensitivity (radians per pixel, right button held)
+const MOUSE_LOOK_SPEED: f32 = 0.005;
+
+/// Longest step taken in one frame (keeps physics sane after a hitch)
+const MAX_STEP: f32 = 0.1;
+
+/// What the pause menu offers, top to bottom
+const PAUSE_ITEMS: [&str; 3] = ["Resume", "Restart Level", "Exit to Editor"];
+
+/// Pause menu state for the running playtest
+#[derive(Default)]
+pub struct PauseMenu {
+    /// Paused with the menu showing (the session isn't stepped)
+    pub open: bool,
+    /// Highlighted item in `PAUSE_ITEMS`
+    pub selected: usize,
+    /// Playing without the editor (no Exit to Editor)
+    pub standalone: bool,
+}
+
+impl PauseMenu {
+    /// Pause and highlight Resume
+    pub fn open(&mut self) {
+        self.open = true;
+        self.selected = 0;
+    }
+}
+
+/// Result from drawing the game view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameViewAction {
+    None,
+    /// The game was just paused (silence the audio)
+    Paused,
+    /// Start over from the spawn point with a fresh session
+    Restart,
+    /// Leave play and go back to the editor
+    Exit,
+}
+
+/// Read this frame's game input from the keyboard and mouse
+fn read_input(ctx: &UiContext, session: &GameSession, dt: f32) -> GameInput {
+    let axis = |pos: KeyCode, neg: KeyCode| -> f32 {
+        (is_key_down(pos) as i32 - is_key_down(neg) as i32) as f32
+    };
+    let mut input = GameInput {
+        forward: axis(KeyCode::W, KeyCode::S),
+        strafe: axis(KeyCode::D, KeyCode::A),
+        yaw: axis(KeyCode::Right, KeyCode::Left) * session.tuning.turn_speed * dt,
+        pitch: axis(KeyCode::Down, KeyCode::Up) * session.tuning.turn_speed * dt,
+        jump: is_key_pressed(KeyCode::Space),
+        interact: is_key_pressed(KeyCode::E),
+    };
+    if ctx.mouse.right_down {
+        let (dx, dy) = mouse_delta_position().into();
+        // Delta is in normalized window units (-1..1 across the window)
+        input.yaw -= dx * screen_width() * 0.5 * MOUSE_LOOK_SPEED;
+        input.pitch -= dy * screen_height() * 0.5 * MOUSE_LOOK_SPEED;
+    }
+    input
+}
+
+/// Step and draw the play session. While paused the session is frozen and the last
+/// frame stays on screen under the pause menu.
+pub fn draw_game_view(
+    ctx: &mut UiContext,
+    rect: Rect,
+    session: &mut GameSession,
+    pause: &mut PauseMenu,
+    textures: &[RasterTexture],
+    texture_packs: &[TexturePack],
+    fb: &mut Framebuffer,
+) -> GameViewAction {
+    let mut action = GameViewAction::None;
+    if pause.open {
+        blit_framebuffer(rect, fb);
+        return draw_pause_menu(ctx, rect, pause);
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        pause.open();
+        action = GameViewAction::Paused;
+    } else {
+        let dt = get_frame_time().min(MAX_STEP);
+        let input = read_input(ctx, session, dt);
+        session.step(&input, dt);
+    }
+
+    let (target_w, target_h) = if session.raster_settings.low_resolution {
+        (WIDTH, HEIGHT)
+    } else {
+        (WIDTH_HI, HEIGHT_HI)
+    };
+    fb.resize(target_w, target_h);
+    fb.clear(RasterColor::new(0, 0, 0));
+
+    // Same texture indexing as the editor's texture list (packs flattened in order)
+    let index = texture_index(texture_packs);
+    let resolve_texture = |tex_ref: &TextureRef| index.resolve(tex_ref, &session.level.pack_aliases);
+
+    let camera = session.camera();
+    let mut room_settings = session.raster_settings.clone();
+    for (i, room) in session.level.rooms.iter().enumerate() {
+        room_settings.light_dir = session.level.room_light_dir(i);
+        let (vertices, faces) = room.to_render_data_for(&room_settings, resolve_texture);
+        render_mesh(fb, &vertices, &faces, textures, &camera, &room_settings);
+    }
+
+    // Pickups that haven't been collected yet
+    let mut pickup_vertices = Vec::new();
+    let mut pickup_faces = Vec::new();
+    for (room_idx, room) in session.level.rooms.iter().enumerate() {
+        for (i, pickup) in room.pickups.iter().enumerate() {
+            if session.pickups.is_collected(room_idx, i) {
+                continue;
+            }
+            let texture_id = resolve_texture(&pickup.sprite).unwrap_or(0);
+            pickup.add_billboard_to_render_data(room, session.time, &camera, texture_id, &mut pickup_vertices, &mut pickup_faces);
+        }
+    }
+    if !pickup_faces.is_empty() {
+        render_mesh(fb, &pickup_vertices, &pickup_faces, textures, &camera, &session.raster_settings);
+    }
+
+    blit_framebuffer(rect, fb);
+
+    // HUD: pickup counters and controls
+    let mut hud_y = rect.y + 20.0;
+    for (kind, count) in session.pickups.counters() {
+        draw_text(&format!("{}: {}", kind, count), rect.x + 10.0, hud_y, 16.0, WHITE);
+        hud_y += 18.0;
+    }
+    draw_text(
+        "WASD: Move  Arrows/RMB: Look  Space: Jump  E: Use  Esc: Pause",
+        rect.x + 10.0,
+        rect.bottom() - 10.0,
+        14.0,
+        Color::from_rgba(200, 200, 200, 255),
+    );
+
+    action
+}
+
+/// Draw the framebuffer scaled to fit `rect`, keeping its aspect ratio
+fn blit_framebuffer(rect: Rect, fb: &Framebuffer) {
+    let fb_aspect = fb.width as f32 / fb.height as f32;
+    let (draw_w, draw_h) = if fb_aspect > rect.w / rect.h {
+        (rect.w, rect.w / fb_aspect)
+    } else {
+        (rect.h * fb_aspect, rect.h)
+    };
+    let draw_x = rect.x + (rect.w - draw_w) * 0.5;
+    let draw_y = rect.y + (rect.h - draw_h) * 0.5;
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, BLACK);
+    let texture = Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &fb.pixels);
+    texture.set_filter(FilterMode::Nearest);
+    draw_texture_ex(
+        &texture,
+        draw_x,
+        draw_y,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(Vec2::new(draw_w, draw_h)),
+            ..Default::default()
+        },
+    );
+}
+
+/// Pause menu over the frozen frame. Up/Down pick, Enter chooses, Escape resumes.
+fn draw_pause_menu(ctx: &mut UiContext, rect: Rect, pause: &mut PauseMenu) -> GameViewAction {
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(0, 0, 0, 140));
+
+    let items = if pause.standalone { &PAUSE_ITEMS[..2] } else { &PAUSE_ITEMS[..] };
+    let item_w = 220.0;
+    let item_h = 32.0;
+    let gap = 6.0;
+    let x = (rect.x + (rect.w - item_w) / 2.0).floor();
+    let top = (rect.y + (rect.h - (item_h + gap) * items.len() as f32) / 2.0).floor();
+
+    let title = "Paused";
+    let dims = measure_text(title, None, 24, 1.0);
+    draw_text(title, (rect.x + (rect.w - dims.width) / 2.0).floor(), top - 20.0, 24.0, WHITE);
+
+    let mut chosen = None;
+    for (i, label) in items.iter().enumerate() {
+        let item = Rect::new(x, top + i as f32 * (item_h + gap), item_w, item_h);
+        let hovered = ctx.mouse.inside(&item);
+        if hovered {
+            pause.selected = i;
+        }
+        let selected = pause.selected == i;
+
+        let bg = if selected {
+            Color::from_rgba(55, 60, 75, 230)
+        } else {
+            Color::from_rgba(35, 35, 40, 200)
+        };
+        draw_rectangle(item.x, item.y, item.w, item.h, bg);
+        if selected {
+            draw_rectangle_lines(item.x, item.y, item.w, item.h, 2.0, ACCENT_COLOR);
+        }
+        let dims = measure_text(label, None, 16, 1.0);
+        let color = if selected { WHITE } else { Color::from_rgba(180, 180, 180, 255) };
+        draw_text(label, (item.x + (item.w - dims.width) / 2.0).floor(), (item.y + item.h / 2.0 + 5.0).floor(), 16.0, color);
+
+        if hovered && ctx.mouse.left_pressed {
+            chosen = Some(i);
+        }
+    }
+
+    let count = items.len();
+    if is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W) {
+        pause.selected = (pause.selected + count - 1) % count;
+    }
+    if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S) {
+        pause.selected = (pause.selected + 1) % count;
+    }
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || is_key_pressed(KeyCode::Space) {
+        chosen = Some(pause.selected);
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        chosen = Some(0);
+    }
+
+    match chosen {
+        Some(0) => {
+            pause.open = false;
+            GameViewAction::None
+        }
+        Some(1) => {
+            pause.open = false;
+            GameViewAction::Restart
+        }
+        Some(_) => {
+            pause.open = false;
+            GameViewAction::Exit
+        }
+        None => GameViewAction::None,
+    }
+}
@@ -0,0 +1,223 @@
+//! Isometric Grid Preview
+//!
+//! The 2D grid panel can show the current room as an isometric projection instead
+//! of top-down, so heights and slopes read at a glance: floors are drawn as
+//! parallelograms raised by their corner heights, walls as upright faces, ceilings
+//! as outlines. It pans and zooms like the top-down grid and clicking picks sectors
+//! through the projection, but it's view-only: editing tools don't act on it.
+
+use macroquad::prelude::*;
+use crate::rasterizer::Vec3;
+use crate::ui::{Rect, UiContext};
+use crate::world::{Direction, Room, SECTOR_SIZE};
+use super::{EditorState, EditorTool, Selection};
+
+/// Screen spread of the ground axes (X runs up-right, Z up-left; 45° rotation)
+const ISO_SPREAD: f32 = std::f32::consts::FRAC_1_SQRT_2;
+/// Ground depth is halved on screen (the 2:1 isometric look)
+const ISO_DEPTH: f32 = 0.5;
+/// Screen length of a vertical world unit (cos 35.26°, true isometric)
+const ISO_RISE: f32 = 0.8165;
+
+/// Screen offset from the view center of a world point, at `scale` pixels per unit
+pub(super) fn iso_project(point: Vec3, scale: f32) -> (f32, f32) {
+    let x = (point.x - point.z) * ISO_SPREAD * scale;
+    let y = -(point.x + point.z) * ISO_SPREAD * ISO_DEPTH * scale - point.y * ISO_RISE * scale;
+    (x, y)
+}
+
+/// Is `p` inside the (convex or not, non-self-intersecting) quad?
+fn point_in_quad(p: (f32, f32), quad: &[(f32, f32); 4]) -> bool {
+    let in_triangle = |a: (f32, f32), b: (f32, f32), c: (f32, f32)| {
+        let side = |u: (f32, f32), v: (f32, f32)| (v.0 - u.0) * (p.1 - u.1) - (v.1 - u.1) * (p.0 - u.0);
+        let (d0, d1, d2) = (side(a, b), side(b, c), side(c, a));
+        let has_neg = d0 < 0.0 || d1 < 0.0 || d2 < 0.0;
+        let has_pos = d0 > 0.0 || d1 > 0.0 || d2 > 0.0;
+        !(has_neg && has_pos)
+    };
+    in_triangle(quad[0], quad[1], quad[2]) || in_triangle(quad[0], quad[2], quad[3])
+}
+
+/// World corners [NW, NE, SE, SW] of a sector's top surface: its floor, else its
+/// ceiling, else flat at the room's base
+fn sector_top(room: &Room, gx: usize, gz: usize) -> Option<[Vec3; 4]> {
+    let sector = room.get_sector(gx, gz)?;
+    let heights = sector.floor.as_ref().or(sector.ceiling.as_ref()).map_or([0.0; 4], |f| f.heights);
+    let x0 = room.position.x + gx as f32 * SECTOR_SIZE;
+    let z0 = room.position.z + gz as f32 * SECTOR_SIZE;
+    let y = room.position.y;
+    Some([
+        Vec3::new(x0, y + heights[0], z0),
+        Vec3::new(x0 + SECTOR_SIZE, y + heights[1], z0),
+        Vec3::new(x0 + SECTOR_SIZE, y + heights[2], z0 + SECTOR_SIZE),
+        Vec3::new(x0, y + heights[3], z0 + SECTOR_SIZE),
+    ])
+}
+
+/// Sectors of a room back to front (drawing order; picking walks it backwards)
+fn draw_order(room: &Room) -> Vec<(usize, usize)> {
+    let mut order: Vec<(usize, usize)> = room.iter_sectors().map(|(x, z, _)| (x, z)).collect();
+    order.sort_by_key(|&(x, z)| std::cmp::Reverse(x + z));
+    order
+}
+
+/// Front-most sector whose top surface is under the screen offset `at`
+fn pick_sector(room: &Room, at: (f32, f32), scale: f32) -> Option<(usize, usize)> {
+    draw_order(room).into_iter().rev().find(|&(gx, gz)| {
+        sector_top(room, gx, gz).is_some_and(|top| point_in_quad(at, &top.map(|p| iso_project(p, scale))))
+    })
+}
+
+/// Fill a projected quad and outline it
+fn draw_quad(corners: &[(f32, f32); 4], fill: Color, outline: Color) {
+    let v = corners.map(|(x, y)| Vec2::new(x, y));
+    draw_triangle(v[0], v[1], v[2], fill);
+    draw_triangle(v[0], v[2], v[3], fill);
+    for i in 0..4 {
+        let (a, b) = (v[i], v[(i + 1) % 4]);
+        draw_line(a.x, a.y, b.x, b.y, 1.0, outline);
+    }
+}
+
+/// Draw the isometric preview of the current room in `view` (scissor already set)
+/// and pick sectors with the Select tool. `center` is the screen position of the
+/// world origin.
+pub(super) fn draw_iso_view(ctx: &mut UiContext, view: Rect, state: &mut EditorState, room: &Room, center: (f32, f32), scale: f32) {
+    let theme = ctx.theme;
+    let room_idx = state.current_room;
+    let inside = ctx.mouse.inside(&view);
+    let mouse = (ctx.mouse.x - center.0, ctx.mouse.y - center.1);
+    let to_screen = |p: Vec3| {
+        let (x, y) = iso_project(p, scale);
+        (center.0 + x, center.1 + y)
+    };
+
+    let hovered = if inside { pick_sector(room, mouse, scale) } else { None };
+    if let Some((gx, gz)) = hovered {
+        state.grid_cursor = Some((room_idx, gx, gz));
+        state.sector_hover = Some((room_idx, gx, gz));
+    }
+
+    // Back walls (east and south edges) go behind the floor, front walls over it
+    let back = [Direction::East, Direction::South];
+    let front = [Direction::North, Direction::West];
+    for (gx, gz) in draw_order(room) {
+        let Some(sector) = room.get_sector(gx, gz) else { continue };
+        let base_x = room.position.x + gx as f32 * SECTOR_SIZE;
+        let base_z = room.position.z + gz as f32 * SECTOR_SIZE;
+        let draw_walls = |dirs: [Direction; 2]| {
+            for dir in dirs {
+                for wall in sector.walls(dir) {
+                    let corners = wall.corners(base_x, base_z, dir)
+                        .map(|c| to_screen(c + Vec3::new(0.0, room.position.y, 0.0)));
+                    let color = super::grid_view::wall_direction_color(dir);
+                    draw_quad(&corners, color.with_alpha(0.35), color);
+                }
+            }
+        };
+
+        draw_walls(back);
+        if let Some(top) = sector_top(room, gx, gz) {
+            let selected = state.selection.includes_sector(room_idx, gx, gz)
+                || state.multi_selection.iter().any(|s| s.includes_sector(room_idx, gx, gz));
+            let fill = if selected {
+                theme.highlight.with_alpha(0.6)
+            } else if hovered == Some((gx, gz)) {
+                theme.hover.with_alpha(0.47)
+            } else if sector.floor.is_some() {
+                Color::from_rgba(60, 100, 120, 160)
+            } else {
+                Color::from_rgba(100, 60, 120, 100)
+            };
+            let outline = if selected || hovered == Some((gx, gz)) { theme.sector_edge_active } else { theme.sector_edge };
+            draw_quad(&top.map(to_screen), fill, outline);
+        }
+        draw_walls(front);
+        if let (Some(_), Some(ceiling)) = (&sector.floor, &sector.ceiling) {
+            let h = ceiling.heights;
+            let y = room.position.y;
+            let corners = [
+                Vec3::new(base_x, y + h[0], base_z),
+                Vec3::new(base_x + SECTOR_SIZE, y + h[1], base_z),
+                Vec3::new(base_x + SECTOR_SIZE, y + h[2], base_z + SECTOR_SIZE),
+                Vec3::new(base_x, y + h[3], base_z + SECTOR_SIZE),
+            ].map(to_screen);
+            for i in 0..4 {
+                let (a, b) = (corners[i], corners[(i + 1) % 4]);
+                draw_line(a.0, a.1, b.0, b.1, 1.0, Color::from_rgba(140, 100, 170, 120));
+            }
+        }
+    }
+
+    draw_text("Isometric preview (view only)", (view.x + 6.0).floor(), (view.bottom() - 6.0).floor(), 12.0, theme.text_faint);
+
+    // Select picks sectors like the top-down grid; everything else waits for it
+    if !inside || state.grid_panning || !ctx.mouse.left_pressed {
+        return;
+    }
+    if state.tool != EditorTool::Select {
+        state.set_status("The isometric preview is view-only: switch the grid to top-down to edit", 2.5);
+        return;
+    }
+    let shift_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+    match hovered {
+        Some((gx, gz)) => {
+            let selection = Selection::Sector { room: room_idx, x: gx, z: gz };
+            if shift_down {
+                state.toggle_multi_selection(selection.clone());
+            } else {
+                state.clear_multi_selection();
+            }
+            state.selection = selection;
+        }
+        None if !shift_down => {
+            state.selection = Selection::None;
+            state.clear_multi_selection();
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::TextureRef;
+
+    #[test]
+    fn projection_raises_heights_and_turns_the_ground() {
+        let scale = 0.1;
+        assert_eq!(iso_project(Vec3::ZERO, scale), (0.0, 0.0));
+        // +X runs right and up the screen, +Z left and up, +Y straight up
+        let (x, y) = iso_project(Vec3::new(1024.0, 0.0, 0.0), scale);
+        assert!(x > 0.0 && y < 0.0);
+        let (x, y) = iso_project(Vec3::new(0.0, 0.0, 1024.0), scale);
+        assert!(x < 0.0 && y < 0.0);
+        let (x, y) = iso_project(Vec3::new(0.0, 1024.0, 0.0), scale);
+        assert!(x == 0.0 && y < 0.0);
+        // Ground is squashed to half height (2:1)
+        let (dx, _) = iso_project(Vec3::new(1024.0, 0.0, -1024.0), scale);
+        let (_, dy) = iso_project(Vec3::new(1024.0, 0.0, 1024.0), scale);
+        assert!((dx + dy * 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn picking_goes_through_raised_floors() {
+        let mut room = Room::new(0, Vec3::ZERO, 2, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.set_floor(1, 0, 0.0, TextureRef::none());
+        let scale = 0.1;
+        let center_of = |room: &Room, gx| {
+            let top = sector_top(room, gx, 0).unwrap();
+            iso_project((top[0] + top[2]) * 0.5, scale)
+        };
+        assert_eq!(pick_sector(&room, center_of(&room, 1), scale), Some((1, 0)));
+
+        // Raised a long way, sector 1 is drawn (and picked) where it appears, not
+        // where its ground footprint is
+        let ground = center_of(&room, 1);
+        room.set_floor(1, 0, 4096.0, TextureRef::none());
+        assert_eq!(pick_sector(&room, center_of(&room, 1), scale), Some((1, 0)));
+        assert_ne!(pick_sector(&room, ground, scale), Some((1, 0)));
+        assert_eq!(pick_sector(&room, (1.0e4, 1.0e4), scale), None);
+    }
+}
@@ -3,48 +3,532 @@
 //! Sector-based geometry system - selection and editing works on sectors.
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext};
-use crate::world::SECTOR_SIZE;
+use crate::ui::{Rect, UiContext, ACCENT_COLOR};
+use crate::world::{aliased_pack, AmbientEmitter, Direction, Pickup, PointLight, Room, PICKUP_KINDS, SECTOR_SIZE};
 use super::{EditorState, Selection, CEILING_HEIGHT};
+use super::measure::{grid_label, MEASURE_SNAP_PX};
+use super::wall_run::{edge_wall, RunCorner};
+use super::view_pan::{edge_scroll, edge_scroll_step, pan_held, zoom_about, ZOOM_STEP};
+
+/// Screen-space distance (pixels) within which the Wall tool snaps to a sector edge
+const EDGE_PICK_THRESHOLD: f32 = 8.0;
+
+/// Screen-space radius (pixels) of a pickup marker, also used for picking
+const PICKUP_MARKER_RADIUS: f32 = 6.0;
+
+/// Height of a new ambient sound above the sector's floor
+const AMBIENT_PLACE_HEIGHT: f32 = SECTOR_SIZE * 0.5;
+
+/// Height of a new point light above the sector's floor
+const LIGHT_PLACE_HEIGHT: f32 = SECTOR_SIZE;
+
+/// Grid zoom range (pixels per world unit)
+const MIN_GRID_ZOOM: f32 = 0.01;
+const MAX_GRID_ZOOM: f32 = 2.0;
+
+/// Thickness (pixels) of the top ruler and width of the left ruler
+const RULER_TOP: f32 = 16.0;
+const RULER_LEFT: f32 = 40.0;
+
+/// Minimum screen distance (pixels) between labeled ruler ticks
+const RULER_MIN_SPACING: f32 = 64.0;
+
+/// Ruler tick steps in world units: sub-sector steps first (labeled in world
+/// units), then whole sectors (labeled in sectors)
+const RULER_STEPS: [f32; 12] = [
+    64.0, 128.0, 256.0, 512.0,
+    SECTOR_SIZE, 2.0 * SECTOR_SIZE, 5.0 * SECTOR_SIZE, 10.0 * SECTOR_SIZE,
+    20.0 * SECTOR_SIZE, 50.0 * SECTOR_SIZE, 100.0 * SECTOR_SIZE, 200.0 * SECTOR_SIZE,
+];
+
+/// Smallest ruler step whose labels don't overlap at this zoom (pixels per world unit)
+fn ruler_step(scale: f32) -> f32 {
+    RULER_STEPS.iter()
+        .copied()
+        .find(|step| step * scale >= RULER_MIN_SPACING)
+        .unwrap_or(RULER_STEPS[RULER_STEPS.len() - 1])
+}
+
+/// Ruler label for a coordinate: sectors for whole-sector steps, world units below that
+fn ruler_label(value: f32, step: f32) -> String {
+    if step >= SECTOR_SIZE {
+        format!("{}", (value / SECTOR_SIZE).round() as i32)
+    } else {
+        format!("{}", value.round() as i32)
+    }
+}
+
+/// Snap a room's minimum coordinate on one axis while it is being moved.
+/// Either edge (min or max) snaps to another room's edge within half a sector;
+/// otherwise the position snaps to the sector grid.
+fn snap_room_axis(min: f32, size: f32, edges: &[f32]) -> f32 {
+    let threshold = SECTOR_SIZE * 0.5;
+    let mut best: Option<(f32, f32)> = None; // (distance, snapped min)
+    for &edge in edges {
+        for candidate in [edge, edge - size] {
+            let dist = (candidate - min).abs();
+            let closer = match best {
+                Some((best_dist, _)) => dist < best_dist,
+                None => true,
+            };
+            if dist < threshold && closer {
+                best = Some((dist, candidate));
+            }
+        }
+    }
+    match best {
+        Some((_, snapped)) => snapped,
+        None => (min / SECTOR_SIZE).round() * SECTOR_SIZE,
+    }
+}
+
+/// Remove a pickup from a room (records undo, clears a selection pointing at it)
+pub(super) fn remove_pickup(state: &mut EditorState, room_idx: usize, index: usize) {
+    let exists = state.level.rooms.get(room_idx).is_some_and(|r| index < r.pickups.len());
+    if !exists {
+        return;
+    }
+    state.save_undo("Remove pickup");
+    if let Some(room) = state.level.rooms.get_mut(room_idx) {
+        room.pickups.remove(index);
+    }
+    state.mark_room_dirty(room_idx);
+    if matches!(state.selection, Selection::Pickup { room, .. } if room == room_idx) {
+        state.selection = Selection::None;
+    }
+    state.set_status("Removed pickup", 2.0);
+}
+
+/// Remove an ambient sound from a room (records undo, clears a selection pointing at it)
+pub(super) fn remove_ambient(state: &mut EditorState, room_idx: usize, index: usize) {
+    let exists = state.level.rooms.get(room_idx).is_some_and(|r| index < r.ambients.len());
+    if !exists {
+        return;
+    }
+    state.save_undo("Remove ambient sound");
+    if let Some(room) = state.level.rooms.get_mut(room_idx) {
+        room.ambients.remove(index);
+    }
+    state.mark_room_dirty(room_idx);
+    if matches!(state.selection, Selection::Ambient { room, .. } if room == room_idx) {
+        state.selection = Selection::None;
+    }
+    state.set_status("Removed ambient sound", 2.0);
+}
+
+/// Remove a point light from a room (records undo, clears a selection pointing at it)
+pub(super) fn remove_light(state: &mut EditorState, room_idx: usize, index: usize) {
+    let exists = state.level.rooms.get(room_idx).is_some_and(|r| index < r.lights.len());
+    if !exists {
+        return;
+    }
+    state.save_undo("Remove light");
+    if let Some(room) = state.level.rooms.get_mut(room_idx) {
+        room.lights.remove(index);
+    }
+    state.mark_lights_dirty(room_idx);
+    if matches!(state.selection, Selection::Light { room, .. } if room == room_idx) {
+        state.selection = Selection::None;
+    }
+    state.set_status("Removed light", 2.0);
+}
+
+/// Report a finished room move, warning if any connected portals no longer line up
+pub(super) fn report_room_move(state: &mut EditorState, room_idx: usize) {
+    let position = match state.level.rooms.get(room_idx) {
+        Some(room) => room.position,
+        None => return,
+    };
+    let misaligned = state.level.misaligned_portals(room_idx);
+    let misplaced = state.level.misplaced_openings();
+    if misaligned > 0 {
+        state.set_warning(&format!("{} portal(s) no longer line up after moving room {}", misaligned, room_idx));
+    } else if misplaced > 0 {
+        state.set_warning(&format!("{} floor/ceiling opening(s) no longer sit over their room after moving room {}", misplaced, room_idx));
+    } else {
+        state.set_status(&format!("Moved room {} to ({:.0}, {:.0}, {:.0})", room_idx, position.x, position.y, position.z), 2.0);
+    }
+}
+
+/// Report a finished move of several rooms (see `report_room_move`)
+pub(super) fn report_rooms_move(state: &mut EditorState, rooms: &[usize]) {
+    if let [room_idx] = rooms[..] {
+        report_room_move(state, room_idx);
+        return;
+    }
+    let misaligned: usize = rooms.iter().map(|&idx| state.level.misaligned_portals(idx)).sum();
+    let misplaced = state.level.misplaced_openings();
+    if misaligned > 0 {
+        state.set_warning(&format!("{} portal(s) no longer line up after moving {} rooms", misaligned, rooms.len()));
+    } else if misplaced > 0 {
+        state.set_warning(&format!("{} floor/ceiling opening(s) no longer sit over their room after moving {} rooms", misplaced, rooms.len()));
+    } else {
+        state.set_status(&format!("Moved {} rooms", rooms.len()), 2.0);
+    }
+}
+
+/// Rooms a Move Room drag carries: the whole room selection if the current room is
+/// part of it, otherwise just the current room
+fn moving_rooms(state: &EditorState) -> Vec<usize> {
+    if state.room_selection.contains(&state.current_room) {
+        state.room_selection.clone()
+    } else {
+        vec![state.current_room]
+    }
+}
+
+/// Color used to draw walls on a sector edge, by direction
+pub(super) fn wall_direction_color(dir: Direction) -> Color {
+    match dir {
+        Direction::North => Color::from_rgba(220, 120, 100, 255),
+        Direction::East => Color::from_rgba(220, 200, 100, 255),
+        Direction::South => Color::from_rgba(100, 200, 140, 255),
+        Direction::West => Color::from_rgba(110, 160, 230, 255),
+    }
+}
+
+/// Find the sector edge nearest to a world position (X-Z plane).
+/// Prefers the sector under the cursor; falls back to the neighbor across the edge.
+/// Returns (grid_x, grid_z, direction) of the owning sector.
+fn find_hovered_edge(room: &Room, wx: f32, wz: f32, scale: f32) -> Option<(usize, usize, Direction)> {
+    let fx = (wx - room.position.x) / SECTOR_SIZE;
+    let fz = (wz - room.position.z) / SECTOR_SIZE;
+    let cell_x = fx.floor() as i32;
+    let cell_z = fz.floor() as i32;
+    let frac_x = fx - fx.floor();
+    let frac_z = fz - fz.floor();
+
+    // Distance (in world units) to each edge of the cell under the cursor
+    let candidates = [
+        (Direction::North, frac_z * SECTOR_SIZE),
+        (Direction::East, (1.0 - frac_x) * SECTOR_SIZE),
+        (Direction::South, (1.0 - frac_z) * SECTOR_SIZE),
+        (Direction::West, frac_x * SECTOR_SIZE),
+    ];
+    let (dir, dist) = candidates
+        .iter()
+        .copied()
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+
+    if dist * scale > EDGE_PICK_THRESHOLD {
+        return None;
+    }
+
+    let sector_at = |x: i32, z: i32| -> bool {
+        x >= 0 && z >= 0 && room.get_sector(x as usize, z as usize).is_some()
+    };
+
+    if sector_at(cell_x, cell_z) {
+        return Some((cell_x as usize, cell_z as usize, dir));
+    }
+
+    let (ox, oz) = dir.offset();
+    let (nx, nz) = (cell_x + ox, cell_z + oz);
+    if sector_at(nx, nz) {
+        return Some((nx as usize, nz as usize, dir.opposite()));
+    }
+
+    None
+}
+
+/// Paint the selected texture onto a sector's floor or ceiling (paint stroke).
+/// Heights are untouched and already-matching faces are skipped.
+/// The stroke is one undo transaction, begun when it started.
+fn paint_sector_face(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, ceiling: bool) {
+    let texture = state.selected_texture.clone();
+    let needs_paint = state.level.rooms.get(room_idx)
+        .and_then(|r| r.get_sector(gx, gz))
+        .and_then(|s| if ceiling { s.ceiling.as_ref() } else { s.floor.as_ref() })
+        .map(|face| face.texture != texture)
+        .unwrap_or(false);
+
+    if !needs_paint {
+        return;
+    }
+
+    let face_name = if ceiling { "ceiling" } else { "floor" };
+    state.save_undo(&format!("Paint {} texture", face_name));
+
+    if let Some(sector) = state.level.rooms.get_mut(room_idx).and_then(|r| r.get_sector_mut(gx, gz)) {
+        let face = if ceiling { sector.ceiling.as_mut() } else { sector.floor.as_mut() };
+        if let Some(face) = face {
+            face.texture = texture;
+            state.grid_paint_count += 1;
+        }
+    }
+    state.mark_room_dirty(room_idx);
+
+    let label = format!("Paint {} {} face(s)", state.grid_paint_count, face_name);
+    state.set_undo_label(&label);
+    state.set_status(&format!("Painted {} {} face(s)", state.grid_paint_count, face_name), 2.0);
+}
+
+/// Start a Paint Flags stroke painting `value` (walkable floors, or solid walls with Alt)
+fn begin_flag_stroke(state: &mut EditorState, value: bool) {
+    state.flag_paint = Some(value);
+    state.grid_paint_count = 0;
+    state.begin_transaction("Paint flags");
+}
+
+/// Count one flag change of the current stroke (one undo transaction)
+fn note_flag_change(state: &mut EditorState, what: &str) {
+    state.save_undo(&format!("Paint {} flags", what));
+    state.grid_paint_count += 1;
+    state.set_undo_label(&format!("Paint {} {} flag(s)", state.grid_paint_count, what));
+    state.set_status(&format!("Changed {} {} flag(s)", state.grid_paint_count, what), 2.0);
+}
+
+/// Set a sector floor's walkable flag (Paint Flags stroke)
+fn paint_walkable(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, walkable: bool) {
+    let needs_paint = state.level.rooms.get(room_idx)
+        .and_then(|r| r.get_sector(gx, gz))
+        .and_then(|s| s.floor.as_ref())
+        .is_some_and(|f| f.walkable != walkable);
+    if !needs_paint {
+        return;
+    }
+    note_flag_change(state, "walkable");
+    if let Some(floor) = state.level.rooms.get_mut(room_idx)
+        .and_then(|r| r.get_sector_mut(gx, gz))
+        .and_then(|s| s.floor.as_mut())
+    {
+        floor.walkable = walkable;
+    }
+    state.mark_room_dirty(room_idx);
+}
+
+/// Set the solid flag of every wall on a sector edge (Paint Flags stroke with Alt)
+fn paint_wall_solid(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, dir: Direction, solid: bool) {
+    let needs_paint = state.level.rooms.get(room_idx)
+        .and_then(|r| r.get_sector(gx, gz))
+        .is_some_and(|s| s.walls(dir).iter().any(|w| w.solid != solid));
+    if !needs_paint {
+        return;
+    }
+    note_flag_change(state, "solid");
+    if let Some(sector) = state.level.rooms.get_mut(room_idx).and_then(|r| r.get_sector_mut(gx, gz)) {
+        for wall in sector.walls_mut(dir) {
+            wall.solid = solid;
+        }
+    }
+    state.mark_room_dirty(room_idx);
+}
+
+/// Diagonal hatching across a screen rectangle
+fn draw_hatch(x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+    const SPACING: f32 = 8.0;
+    let (x0, x1, y0, y1) = (x0.min(x1), x0.max(x1), y0.min(y1), y0.max(y1));
+    let (w, h) = (x1 - x0, y1 - y0);
+    // Lines u + v = t in rectangle-local coordinates, clipped to the rectangle
+    let mut t = SPACING * 0.5;
+    while t < w + h {
+        let (v0, v1) = ((t - w).max(0.0), t.min(h));
+        draw_line(x0 + t - v0, y0 + v0, x0 + t - v1, y0 + v1, 1.0, color);
+        t += SPACING;
+    }
+}
+
+/// Sample (eyedrop) the texture of a sector's floor or ceiling into the selected texture
+fn sample_sector_face(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, ceiling: bool) {
+    let texture = state.level.rooms.get(room_idx)
+        .and_then(|r| r.get_sector(gx, gz))
+        .and_then(|s| if ceiling { s.ceiling.as_ref() } else { s.floor.as_ref() })
+        .map(|face| face.texture.clone());
+
+    if let Some(texture) = texture {
+        if texture == state.selected_texture {
+            return;
+        }
+        // Switch the palette to the sampled texture's pack (or its substitute)
+        let pack = aliased_pack(&state.level.pack_aliases, &texture.pack);
+        if let Some(pack_idx) = state.texture_packs.iter().position(|p| p.name == pack) {
+            state.selected_pack = pack_idx;
+        }
+        state.set_status(&format!("Sampled texture {}/{}", texture.pack, texture.name), 2.0);
+        state.selected_texture = texture;
+    }
+}
+
+/// Edge of sector (gx, gz) whose wall is selected by `sel` (a wall face, or one of its edges)
+fn selected_wall_edge(sel: &Selection, room_idx: usize, gx: usize, gz: usize) -> Option<Direction> {
+    let face = match *sel {
+        Selection::SectorFace { room, x, z, face } if (room, x, z) == (room_idx, gx, gz) => face,
+        Selection::Edge { room, x, z, wall_face: Some(face), .. } if (room, x, z) == (room_idx, gx, gz) => face,
+        _ => return None,
+    };
+    face.wall().map(|(dir, _)| dir)
+}
+
+/// Screen-space endpoints of a sector edge, inset slightly toward the sector center
+/// so walls on both sides of a shared edge stay visible
+fn edge_screen_segment(corners: &[(f32, f32); 4], dir: Direction, inset: f32) -> (f32, f32, f32, f32) {
+    // corners: [(x, z), (x+1, z), (x+1, z+1), (x, z+1)] in screen space
+    let (a, b) = match dir {
+        Direction::North => (corners[0], corners[1]),
+        Direction::East => (corners[1], corners[2]),
+        Direction::South => (corners[2], corners[3]),
+        Direction::West => (corners[3], corners[0]),
+    };
+    let cx = (corners[0].0 + corners[2].0) * 0.5;
+    let cy = (corners[0].1 + corners[2].1) * 0.5;
+    let mx = (a.0 + b.0) * 0.5;
+    let my = (a.1 + b.1) * 0.5;
+    let len = ((cx - mx).powi(2) + (cy - my).powi(2)).sqrt().max(0.001);
+    let ox = (cx - mx) / len * inset;
+    let oy = (cy - my) / len * inset;
+    (a.0 + ox, a.1 + oy, b.0 + ox, b.1 + oy)
+}
+
+/// Draw the rulers along the top (X) and left (Z) edges, the coordinate readout for
+/// the cursor, and the local/world toggle in the corner where the rulers meet.
+/// `room_origin` is the current room's world XZ, used for room-local coordinates.
+fn draw_rulers(
+    ctx: &mut UiContext,
+    rect: Rect,
+    state: &mut EditorState,
+    room_origin: (f32, f32),
+    center: (f32, f32),
+    scale: f32,
+    cursor: Option<(f32, f32)>,
+) {
+    let bg = Color::from_rgba(30, 30, 36, 255);
+    let tick_color = Color::from_rgba(110, 110, 120, 255);
+    let text_color = Color::from_rgba(160, 160, 170, 255);
+    let origin = if state.grid_local_coords { room_origin } else { (0.0, 0.0) };
+    let step = ruler_step(scale);
+
+    draw_rectangle(rect.x, rect.y, rect.w, RULER_TOP, bg);
+    draw_rectangle(rect.x, rect.y, RULER_LEFT, rect.h, bg);
+    draw_line(rect.x + RULER_LEFT, rect.y + RULER_TOP, rect.right(), rect.y + RULER_TOP, 1.0, tick_color);
+    draw_line(rect.x + RULER_LEFT, rect.y + RULER_TOP, rect.x + RULER_LEFT, rect.bottom(), 1.0, tick_color);
+
+    // X ruler: screen x = center + world x * scale
+    let first = ((rect.x + RULER_LEFT - center.0) / scale - origin.0) / step;
+    let last = ((rect.right() - center.0) / scale - origin.0) / step;
+    for i in (first.floor() as i64)..=(last.ceil() as i64) {
+        let value = i as f32 * step;
+        let sx = center.0 + (value + origin.0) * scale;
+        let half = sx + step * scale * 0.5;
+        if half > rect.x + RULER_LEFT && half < rect.right() {
+            draw_line(half, rect.y + RULER_TOP - 3.0, half, rect.y + RULER_TOP, 1.0, tick_color);
+        }
+        if sx < rect.x + RULER_LEFT || sx > rect.right() {
+            continue;
+        }
+        draw_line(sx, rect.y + RULER_TOP - 7.0, sx, rect.y + RULER_TOP, 1.0, tick_color);
+        draw_text(&ruler_label(value, step), sx + 2.0, rect.y + 10.0, 11.0, text_color);
+    }
+
+    // Z ruler: screen y = center - world z * scale (Z grows upward)
+    let first = (-(rect.bottom() - center.1) / scale - origin.1) / step;
+    let last = (-(rect.y + RULER_TOP - center.1) / scale - origin.1) / step;
+    for i in (first.floor() as i64)..=(last.ceil() as i64) {
+        let value = i as f32 * step;
+        let sy = center.1 - (value + origin.1) * scale;
+        let half = sy - step * scale * 0.5;
+        if half > rect.y + RULER_TOP && half < rect.bottom() {
+            draw_line(rect.x + RULER_LEFT - 3.0, half, rect.x + RULER_LEFT, half, 1.0, tick_color);
+        }
+        if sy < rect.y + RULER_TOP || sy > rect.bottom() {
+            continue;
+        }
+        draw_line(rect.x + RULER_LEFT - 7.0, sy, rect.x + RULER_LEFT, sy, 1.0, tick_color);
+        draw_text(&ruler_label(value, step), rect.x + 3.0, sy - 2.0, 11.0, text_color);
+    }
+
+    // Cursor position on both rulers, plus the readout in the bottom-left corner
+    if let Some((wx, wz)) = cursor {
+        let sx = center.0 + wx * scale;
+        let sy = center.1 - wz * scale;
+        draw_line(sx, rect.y, sx, rect.y + RULER_TOP, 1.0, ACCENT_COLOR);
+        draw_line(rect.x, sy, rect.x + RULER_LEFT, sy, 1.0, ACCENT_COLOR);
+
+        let (x, z) = (wx - origin.0, wz - origin.1);
+        let text = format!(
+            "{}  Sector ({}, {})  X {:.0}  Z {:.0}",
+            if state.grid_local_coords { "Room" } else { "World" },
+            (x / SECTOR_SIZE).floor() as i32,
+            (z / SECTOR_SIZE).floor() as i32,
+            x,
+            z,
+        );
+        let dims = measure_text(&text, None, 12, 1.0);
+        let (bx, by) = (rect.x + RULER_LEFT + 4.0, rect.bottom() - 20.0);
+        draw_rectangle(bx, by, dims.width + 10.0, 16.0, Color::from_rgba(20, 20, 25, 220));
+        draw_text(&text, bx + 5.0, by + 12.0, 12.0, text_color);
+    }
+
+    // Corner: toggle between room-local and world coordinates
+    let corner = Rect::new(rect.x, rect.y, RULER_LEFT, RULER_TOP);
+    let hovered = ctx.mouse.inside(&corner);
+    if hovered {
+        draw_rectangle(corner.x, corner.y, corner.w, corner.h, Color::from_rgba(50, 50, 60, 255));
+        ctx.set_tooltip("Rulers: toggle room-local / world coordinates", ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.grid_local_coords = !state.grid_local_coords;
+        }
+    }
+    let label = if state.grid_local_coords { "ROOM" } else { "WORLD" };
+    let color = if state.grid_local_coords { ACCENT_COLOR } else { text_color };
+    draw_text(label, corner.x + 3.0, corner.y + 11.0, 10.0, color);
+}
 
 /// Draw the 2D grid view (top-down view of current room)
 pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
     // Background
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(20, 20, 25, 255));
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.grid_bg);
 
     let mouse_pos = (ctx.mouse.x, ctx.mouse.y);
-    let inside = ctx.mouse.inside(&rect);
+    // The rulers along the top and left edges aren't part of the editable area
+    let view = Rect::new(rect.x + RULER_LEFT, rect.y + RULER_TOP, rect.w - RULER_LEFT, rect.h - RULER_TOP);
+    let inside = ctx.mouse.inside(&view);
 
-    // Handle pan and zoom
-    if inside {
-        // Zoom with scroll wheel
-        if ctx.mouse.scroll != 0.0 {
-            let zoom_factor = 1.0 + ctx.mouse.scroll * 0.02;
-            state.grid_zoom = (state.grid_zoom * zoom_factor).clamp(0.01, 2.0);
-        }
-
-        // Pan with right mouse button
-        if ctx.mouse.right_down {
-            if state.grid_panning {
-                let dx = mouse_pos.0 - state.grid_last_mouse.0;
-                let dy = mouse_pos.1 - state.grid_last_mouse.1;
-                state.grid_offset_x += dx;
-                state.grid_offset_y += dy;
-            }
-            state.grid_panning = true;
-        } else {
-            state.grid_panning = false;
+    // Zoom with scroll wheel, about the cursor (top-down and isometric both scale
+    // about the view's origin)
+    if inside && ctx.mouse.scroll != 0.0 {
+        let zoom = (state.grid_zoom * ZOOM_STEP.powf(ctx.mouse.scroll.signum())).clamp(MIN_GRID_ZOOM, MAX_GRID_ZOOM);
+        let anchor = (mouse_pos.0 - (rect.x + rect.w * 0.5), mouse_pos.1 - (rect.y + rect.h * 0.5));
+        (state.grid_offset_x, state.grid_offset_y) =
+            zoom_about((state.grid_offset_x, state.grid_offset_y), anchor, zoom / state.grid_zoom);
+        state.grid_zoom = zoom;
+    }
+
+    // Pan with the middle mouse button or Space+drag, like the 3D view; the right
+    // button pans too (Paint Flags paints with it instead). A pan keeps going when
+    // the mouse leaves the view.
+    let right_pan = ctx.mouse.right_down && state.tool != super::EditorTool::PaintFlags;
+    if (inside || state.grid_panning) && (pan_held(ctx) || right_pan) {
+        if state.grid_panning {
+            let dx = mouse_pos.0 - state.grid_last_mouse.0;
+            let dy = mouse_pos.1 - state.grid_last_mouse.1;
+            state.grid_offset_x += dx;
+            state.grid_offset_y += dy;
         }
+        state.grid_panning = true;
     } else {
         state.grid_panning = false;
     }
     state.grid_last_mouse = mouse_pos;
 
+    // Edge scroll while dragging (or placing a wall run) near the view's edge
+    let dragging = state.room_move_start.is_some()
+        || state.grid_painting
+        || state.flag_paint.is_some()
+        || (state.measure.pressed() && !state.measure.in_3d);
+    let placing_run = inside && state.tool == super::EditorTool::DrawWall && state.wall_run.start().is_some();
+    if (dragging || placing_run) && !state.grid_panning {
+        let (ex, ey) = edge_scroll(&view, mouse_pos.0, mouse_pos.1);
+        let step = edge_scroll_step();
+        state.grid_offset_x -= ex * step;
+        state.grid_offset_y -= ey * step;
+    }
+
     // Clone room for read-only access
     let room = match state.level.rooms.get(state.current_room) {
         Some(r) => r.clone(),
         None => {
-            draw_text("No room", rect.x + 10.0, rect.y + 20.0, 14.0, Color::from_rgba(100, 100, 100, 255));
+            draw_text("No room", rect.x + 10.0, rect.y + 20.0, 14.0, theme.text_faint);
             return;
         }
     };
@@ -82,9 +566,18 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         );
     }
 
+    // Isometric preview: its own drawing and picking, none of the editing below
+    if state.grid_isometric {
+        super::grid_iso::draw_iso_view(ctx, rect, state, &room, (center_x, center_y), scale);
+        unsafe {
+            get_internal_gl().quad_gl.scissor(None);
+        }
+        return;
+    }
+
     // Draw grid lines
     if state.show_grid {
-        let grid_color = Color::from_rgba(40, 40, 45, 255);
+        let grid_color = theme.grid_line;
         let grid_step = state.grid_size;
 
         // Calculate visible grid range
@@ -100,7 +593,7 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
             let (sx, _) = world_to_screen(x, 0.0);
             if sx >= rect.x && sx <= rect.right() {
                 let line_color = if (x / grid_step).abs() < 0.01 {
-                    Color::from_rgba(80, 40, 40, 255)
+                    theme.axis_x
                 } else {
                     grid_color
                 };
@@ -116,7 +609,7 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
             let (_, sy) = world_to_screen(0.0, z);
             if sy >= rect.y && sy <= rect.bottom() {
                 let line_color = if (z / grid_step).abs() < 0.01 {
-                    Color::from_rgba(40, 80, 40, 255)
+                    theme.axis_z
                 } else {
                     grid_color
                 };
@@ -140,13 +633,50 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
             let gx = (local_x / SECTOR_SIZE) as usize;
             let gz = (local_z / SECTOR_SIZE) as usize;
             if gx < room.width && gz < room.depth {
+                state.grid_cursor = Some((current_room_idx, gx, gz));
                 if room.get_sector(gx, gz).is_some() {
                     hovered_sector = Some((gx, gz));
+                    state.sector_hover = Some((current_room_idx, gx, gz));
                 }
             }
         }
     }
 
+    // Find hovered sector edge (Wall tool, or Alt in Paint Flags)
+    let alt_down = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+    let picks_edges = state.tool == super::EditorTool::DrawWall
+        || (state.tool == super::EditorTool::PaintFlags && alt_down);
+    let hovered_edge = if inside && picks_edges {
+        let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+        find_hovered_edge(&room, wx, wz, scale)
+    } else {
+        None
+    };
+
+    // Move Room tool: outline the other rooms so there's something to line up against.
+    // Rooms picked for a batch operation get a highlighted outline in every tool.
+    let move_tool = state.tool == super::EditorTool::MoveRoom;
+    if move_tool || !state.room_selection.is_empty() {
+        for (idx, other) in state.level.rooms.iter().enumerate() {
+            let picked = state.room_selection.contains(&idx);
+            if !picked && (idx == current_room_idx || !move_tool) {
+                continue;
+            }
+            let (x0, y0) = world_to_screen(other.position.x, other.position.z);
+            let (x1, y1) = world_to_screen(
+                other.position.x + other.width as f32 * SECTOR_SIZE,
+                other.position.z + other.depth as f32 * SECTOR_SIZE,
+            );
+            let (outline, thickness) = if picked {
+                (ctx.theme.highlight, 2.0)
+            } else {
+                (Color::from_rgba(120, 120, 140, 160), 1.0)
+            };
+            draw_rectangle_lines(x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs(), thickness, outline);
+            draw_text(&format!("Room {}", idx), x0.min(x1) + 4.0, y0.max(y1) - 4.0, 12.0, outline);
+        }
+    }
+
     // Draw sectors
     for (gx, gz, sector) in room.iter_sectors() {
         let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
@@ -158,10 +688,11 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         let (sx3, sy3) = world_to_screen(base_x, base_z + SECTOR_SIZE);
 
         let is_hovered = hovered_sector == Some((gx, gz));
-        let is_selected = matches!(state.selection, Selection::Sector { x, z, .. } if x == gx && z == gz);
-        let is_multi_selected = state.multi_selection.iter().any(|sel| {
-            matches!(sel, Selection::Sector { x, z, .. } if *x == gx && *z == gz)
-        });
+        // Wall faces only light up their edge (below); whole sectors and floors/ceilings fill the cell
+        let fills_cell = |sel: &Selection| sel.includes_sector(current_room_idx, gx, gz)
+            && !matches!(sel, Selection::SectorFace { face, .. } if face.wall().is_some());
+        let is_selected = fills_cell(&state.selection);
+        let is_multi_selected = state.multi_selection.iter().any(fills_cell);
 
         // Determine fill color based on sector contents
         let has_floor = sector.floor.is_some();
@@ -170,9 +701,9 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
             || !sector.walls_south.is_empty() || !sector.walls_west.is_empty();
 
         let fill_color = if is_selected || is_multi_selected {
-            Color::from_rgba(255, 200, 100, 150)
+            theme.highlight.with_alpha(0.6)
         } else if is_hovered {
-            Color::from_rgba(150, 200, 255, 120)
+            theme.hover.with_alpha(0.47)
         } else if has_floor && has_ceiling {
             Color::from_rgba(60, 120, 100, 100) // Full sector
         } else if has_floor {
@@ -199,28 +730,58 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
 
         // Draw sector edges
         let edge_color = if is_selected || is_multi_selected || is_hovered {
-            Color::from_rgba(200, 200, 220, 255)
+            theme.sector_edge_active
         } else {
-            Color::from_rgba(100, 100, 110, 255)
+            theme.sector_edge
         };
         draw_line(sx0, sy0, sx1, sy1, 1.0, edge_color);
         draw_line(sx1, sy1, sx2, sy2, 1.0, edge_color);
         draw_line(sx2, sy2, sx3, sy3, 1.0, edge_color);
         draw_line(sx3, sy3, sx0, sy0, 1.0, edge_color);
 
-        // Draw wall indicators on edges that have walls
-        let wall_color = Color::from_rgba(200, 150, 100, 255);
-        if !sector.walls_north.is_empty() {
-            draw_line(sx0, sy0, sx1, sy1, 3.0, wall_color);
+        // Paint Flags: hatch floors that aren't walkable
+        let painting_flags = state.tool == super::EditorTool::PaintFlags;
+        if painting_flags && sector.floor.as_ref().is_some_and(|f| !f.walkable) {
+            draw_hatch(sx0, sy0, sx2, sy2, Color::from_rgba(230, 80, 60, 200));
         }
-        if !sector.walls_east.is_empty() {
-            draw_line(sx1, sy1, sx2, sy2, 3.0, wall_color);
+
+        // Draw walls as thick segments on their edges (colored by direction;
+        // with Paint Flags, edges with non-solid walls are pale and thin)
+        let corners = [(sx0, sy0), (sx1, sy1), (sx2, sy2), (sx3, sy3)];
+        for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+            let walls = sector.walls(dir);
+            if walls.is_empty() {
+                continue;
+            }
+            let (ax, ay, bx, by) = edge_screen_segment(&corners, dir, 2.0);
+            let pale = painting_flags && walls.iter().any(|w| !w.solid);
+            // Partial walls (doorway pillars, lintels) only cover part of the edge
+            for wall in walls {
+                let lerp = |t: f32| (ax + (bx - ax) * t, ay + (by - ay) * t);
+                let ((sx, sy), (ex, ey)) = (lerp(wall.x_start), lerp(wall.x_end));
+                if pale {
+                    draw_line(sx, sy, ex, ey, 1.0, Color::from_rgba(200, 230, 255, 255));
+                } else {
+                    draw_line(sx, sy, ex, ey, 3.0, wall_direction_color(dir));
+                }
+            }
         }
-        if !sector.walls_south.is_empty() {
-            draw_line(sx2, sy2, sx3, sy3, 3.0, wall_color);
+
+        // Selected walls (also when picked in the 3D view)
+        for sel in std::iter::once(&state.selection).chain(&state.multi_selection) {
+            if let Some(dir) = selected_wall_edge(sel, current_room_idx, gx, gz) {
+                let (ax, ay, bx, by) = edge_screen_segment(&corners, dir, 2.0);
+                draw_line(ax, ay, bx, by, 5.0, theme.highlight);
+            }
         }
-        if !sector.walls_west.is_empty() {
-            draw_line(sx3, sy3, sx0, sy0, 3.0, wall_color);
+
+        // Wall tool placement preview
+        if let Some((ex, ez, dir)) = hovered_edge {
+            if ex == gx && ez == gz {
+                let (ax, ay, bx, by) = edge_screen_segment(&corners, dir, 2.0);
+                draw_line(ax, ay, bx, by, 5.0, Color::from_rgba(255, 255, 255, 140));
+                draw_line(ax, ay, bx, by, 2.0, wall_direction_color(dir));
+            }
         }
     }
 
@@ -257,14 +818,176 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         draw_line(sx3, sy3, sx0, sy0, 2.0, Color::from_rgba(255, 100, 255, 255));
     }
 
-    // Draw room origin marker
+    // Suggest Portals popup: outline the hovered suggestion's rooms and shared edge
+    if let Some(suggestion) = &state.hovered_portal_suggestion {
+        let highlight = crate::ui::ACCENT_COLOR;
+        for idx in [suggestion.room_a, suggestion.room_b] {
+            let Some(other) = state.level.rooms.get(idx) else { continue };
+            let (x0, y0) = world_to_screen(other.position.x, other.position.z);
+            let (x1, y1) = world_to_screen(
+                other.position.x + other.width as f32 * SECTOR_SIZE,
+                other.position.z + other.depth as f32 * SECTOR_SIZE,
+            );
+            draw_rectangle_lines(x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs(), 2.0, highlight);
+            draw_text(&format!("Room {}", idx), x0.min(x1) + 4.0, y0.max(y1) - 4.0, 12.0, highlight);
+        }
+        let [a, b, ..] = suggestion.corners;
+        let (sx0, sy0) = world_to_screen(a.x, a.z);
+        let (sx1, sy1) = world_to_screen(b.x, b.z);
+        draw_line(sx0, sy0, sx1, sy1, 4.0, Color::from_rgba(255, 100, 255, 255));
+    }
+
+    // Draw pickups as diamonds
+    let mut hovered_pickup: Option<usize> = None;
+    for (i, pickup) in room.pickups.iter().enumerate() {
+        let (px, py) = world_to_screen(room.position.x + pickup.position.x, room.position.z + pickup.position.z);
+        let dist = ((mouse_pos.0 - px).powi(2) + (mouse_pos.1 - py).powi(2)).sqrt();
+        if inside && dist <= PICKUP_MARKER_RADIUS + 2.0 {
+            hovered_pickup = Some(i);
+        }
+        let selected = state.selection == Selection::Pickup { room: current_room_idx, index: i };
+        let r = PICKUP_MARKER_RADIUS;
+        let fill = if selected {
+            theme.highlight
+        } else if hovered_pickup == Some(i) {
+            Color::from_rgba(255, 240, 150, 255)
+        } else {
+            Color::from_rgba(230, 200, 60, 255)
+        };
+        draw_triangle(Vec2::new(px, py - r), Vec2::new(px + r, py), Vec2::new(px, py + r), fill);
+        draw_triangle(Vec2::new(px, py - r), Vec2::new(px, py + r), Vec2::new(px - r, py), fill);
+        if selected {
+            draw_circle_lines(px, py, r + 3.0, 1.0, WHITE);
+        }
+    }
+
+    // Draw ambient sounds as circles, with their falloff radius when hovered or selected
+    let mut hovered_ambient: Option<usize> = None;
+    for (i, ambient) in room.ambients.iter().enumerate() {
+        let (wx, wz) = (room.position.x + ambient.position.x, room.position.z + ambient.position.z);
+        let (px, py) = world_to_screen(wx, wz);
+        let dist = ((mouse_pos.0 - px).powi(2) + (mouse_pos.1 - py).powi(2)).sqrt();
+        if inside && dist <= PICKUP_MARKER_RADIUS + 2.0 {
+            hovered_ambient = Some(i);
+        }
+        let selected = state.selection == Selection::Ambient { room: current_room_idx, index: i };
+        let fill = if selected {
+            theme.highlight
+        } else if hovered_ambient == Some(i) {
+            Color::from_rgba(150, 230, 255, 255)
+        } else {
+            Color::from_rgba(80, 180, 230, 255)
+        };
+        if selected || hovered_ambient == Some(i) {
+            let radius = world_to_screen(wx + ambient.radius, wz).0 - px;
+            draw_circle_lines(px, py, radius, 1.0, Color::new(fill.r, fill.g, fill.b, 0.6));
+        }
+        draw_circle(px, py, PICKUP_MARKER_RADIUS, fill);
+        if selected {
+            draw_circle_lines(px, py, PICKUP_MARKER_RADIUS + 3.0, 1.0, WHITE);
+        }
+    }
+
+    // Draw point lights as squares, with their radius when hovered or selected
+    let mut hovered_light: Option<usize> = None;
+    for (i, light) in room.lights.iter().enumerate() {
+        let (wx, wz) = (room.position.x + light.position.x, room.position.z + light.position.z);
+        let (px, py) = world_to_screen(wx, wz);
+        let dist = ((mouse_pos.0 - px).powi(2) + (mouse_pos.1 - py).powi(2)).sqrt();
+        if inside && dist <= PICKUP_MARKER_RADIUS + 2.0 {
+            hovered_light = Some(i);
+        }
+        let selected = state.selection == Selection::Light { room: current_room_idx, index: i };
+        let fill = if selected {
+            theme.highlight
+        } else if hovered_light == Some(i) {
+            Color::from_rgba(255, 250, 170, 255)
+        } else {
+            Color::from_rgba(250, 220, 90, 255)
+        };
+        if selected || hovered_light == Some(i) {
+            let radius = world_to_screen(wx + light.radius, wz).0 - px;
+            draw_circle_lines(px, py, radius, 1.0, Color::new(fill.r, fill.g, fill.b, 0.6));
+        }
+        let r = PICKUP_MARKER_RADIUS;
+        draw_rectangle(px - r, py - r, r * 2.0, r * 2.0, fill);
+        if selected {
+            draw_rectangle_lines(px - r - 3.0, py - r - 3.0, r * 2.0 + 6.0, r * 2.0 + 6.0, 1.0, WHITE);
+        }
+    }
+
+    // Draw world origin marker
     let (ox, oy) = world_to_screen(0.0, 0.0);
     if ox >= rect.x && ox <= rect.right() && oy >= rect.y && oy <= rect.bottom() {
-        draw_circle(ox, oy, 5.0, Color::from_rgba(255, 100, 100, 255));
+        draw_line(ox - 10.0, oy, ox + 10.0, oy, 1.0, Color::from_rgba(255, 100, 100, 255));
+        draw_line(ox, oy - 10.0, ox, oy + 10.0, 1.0, Color::from_rgba(100, 255, 100, 255));
+        draw_circle(ox, oy, 4.0, Color::from_rgba(255, 100, 100, 255));
+        draw_text("0,0", ox + 6.0, oy - 6.0, 12.0, Color::from_rgba(255, 140, 140, 255));
+    }
+
+    // Rulers and cursor readout (pinned to the view edges)
+    let cursor = inside.then(|| screen_to_world(mouse_pos.0, mouse_pos.1));
+    draw_rulers(ctx, rect, state, (room.position.x, room.position.z), (center_x, center_y), scale, cursor);
+
+    // Measure tool: ground point under the mouse, snapped to a sector corner when close
+    let measure_point = {
+        let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+        let corner = |v: f32, origin: f32| ((v - origin) / SECTOR_SIZE).round() * SECTOR_SIZE + origin;
+        let (cx, cz) = (corner(wx, room.position.x), corner(wz, room.position.z));
+        let (sx, sy) = world_to_screen(cx, cz);
+        if (sx - mouse_pos.0).hypot(sy - mouse_pos.1) <= MEASURE_SNAP_PX {
+            crate::rasterizer::Vec3::new(cx, 0.0, cz)
+        } else {
+            crate::rasterizer::Vec3::new(wx, 0.0, wz)
+        }
+    };
+
+    // Wall tool: grid corner nearest the mouse (clamped to the room's grid) and
+    // whether it's close enough to start a wall run
+    let run_corner: RunCorner = {
+        let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+        let corner = |v: f32, origin: f32, max: usize| (((v - origin) / SECTOR_SIZE).round().max(0.0) as usize).min(max);
+        (current_room_idx, corner(wx, room.position.x, room.width), corner(wz, room.position.z, room.depth))
+    };
+    let corner_screen = |(_, cx, cz): RunCorner| {
+        world_to_screen(room.position.x + cx as f32 * SECTOR_SIZE, room.position.z + cz as f32 * SECTOR_SIZE)
+    };
+    let near_run_corner = {
+        let (sx, sy) = corner_screen(run_corner);
+        (sx - mouse_pos.0).hypot(sy - mouse_pos.1) <= MEASURE_SNAP_PX
+    };
+    if state.wall_run.start().is_some_and(|(r, _, _)| r != current_room_idx) {
+        state.wall_run.cancel();
+    }
+
+    // Prefab placement: the prefab's footprint follows the mouse in whole sectors
+    // (on the current room's grid) until a click stamps it there; Escape cancels
+    let placing_prefab = state.prefab_placement.is_some();
+    if let Some((name, prefab)) = &state.prefab_placement {
+        let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+        let snap = |v: f32, origin: f32| ((v - origin) / SECTOR_SIZE).floor() * SECTOR_SIZE + origin;
+        let spot = crate::rasterizer::Vec3::new(snap(wx, room.position.x), room.position.y, snap(wz, room.position.z));
+        if inside {
+            let (x0, y0) = world_to_screen(spot.x, spot.z);
+            let (x1, y1) = world_to_screen(
+                spot.x + prefab.room.width as f32 * SECTOR_SIZE,
+                spot.z + prefab.room.depth as f32 * SECTOR_SIZE,
+            );
+            let (left, top, w, h) = (x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs());
+            draw_rectangle(left, top, w, h, theme.highlight.with_alpha(0.2));
+            draw_rectangle_lines(left, top, w, h, 2.0, theme.highlight);
+            draw_text(name, left + 4.0, top + h - 4.0, 12.0, theme.highlight);
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            state.prefab_placement = None;
+            state.set_status("Prefab placement cancelled", 1.5);
+        } else if inside && ctx.mouse.left_pressed && !state.grid_panning {
+            state.place_prefab(spot);
+        }
     }
 
     // Handle selection and interaction
-    if inside && !state.grid_panning {
+    if inside && !state.grid_panning && !placing_prefab {
         if ctx.mouse.left_pressed {
             use super::EditorTool;
 
@@ -304,10 +1027,13 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
                         .map(|s| s.floor.is_some())
                         .unwrap_or(false);
 
-                    if has_floor {
-                        state.set_status("Sector already has a floor", 2.0);
+                    if has_floor || alt_down {
+                        // Start a paint (or Alt: sample) stroke
+                        state.grid_painting = true;
+                        state.grid_paint_count = 0;
+                        state.begin_transaction("Paint texture");
                     } else {
-                        state.save_undo();
+                        state.save_undo(&format!("Create floor ({}, {})", gx, gz));
 
                         if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
                             // Expand room grid if needed
@@ -326,6 +1052,7 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
                             room.recalculate_bounds();
                             state.set_status("Created floor sector", 2.0);
                         }
+                        state.mark_room_dirty(current_room_idx);
                     }
                 }
 
@@ -341,10 +1068,13 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
                         .map(|s| s.ceiling.is_some())
                         .unwrap_or(false);
 
-                    if has_ceiling {
-                        state.set_status("Sector already has a ceiling", 2.0);
+                    if has_ceiling || alt_down {
+                        // Start a paint (or Alt: sample) stroke
+                        state.grid_painting = true;
+                        state.grid_paint_count = 0;
+                        state.begin_transaction("Paint texture");
                     } else {
-                        state.save_undo();
+                        state.save_undo(&format!("Create ceiling ({}, {})", gx, gz));
 
                         if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
                             // Expand room grid if needed
@@ -363,16 +1093,312 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
                             room.recalculate_bounds();
                             state.set_status("Created ceiling sector", 2.0);
                         }
+                        state.mark_room_dirty(current_room_idx);
                     }
                 }
 
                 EditorTool::DrawWall => {
-                    state.set_status("Wall tool: not yet implemented", 3.0);
+                    if let Some((start, end)) = state.wall_run.finish(run_corner) {
+                        // Second corner: wall the run (a diagonal keeps the start)
+                        if let Err(e) = state.place_wall_run(start, end) {
+                            state.wall_run.anchor(start);
+                            state.set_warning(&e);
+                        }
+                    } else if near_run_corner {
+                        state.wall_run.anchor(run_corner);
+                        state.set_status("Wall run: click a second corner in the same row or column (Esc cancels)", 3.0);
+                    } else if let Some((gx, gz, dir)) = hovered_edge {
+                        // Span the sector's floor to its ceiling along this edge
+                        state.save_undo(&format!("Create {:?} wall ({}, {})", dir, gx, gz));
+                        let wall = edge_wall(&room, gx, gz, dir, state.selected_texture.clone());
+                        if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
+                            room.push_wall(gx, gz, dir, wall);
+                            room.recalculate_bounds();
+                        }
+                        state.mark_room_dirty(current_room_idx);
+                        state.set_status(&format!("Created {:?} wall", dir), 2.0);
+                    } else {
+                        state.set_status("Click a sector edge to place a wall", 2.0);
+                    }
+                }
+
+                EditorTool::MoveRoom => {
+                    let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+                    let local_x = wx - room.position.x;
+                    let local_z = wz - room.position.z;
+                    let in_room = local_x >= 0.0 && local_z >= 0.0
+                        && local_x < room.width as f32 * SECTOR_SIZE
+                        && local_z < room.depth as f32 * SECTOR_SIZE;
+
+                    if in_room {
+                        state.room_move_start = Some(((wx, wz), room.position));
+                        let moving = moving_rooms(state);
+                        let label = if moving.len() > 1 {
+                            format!("Move {} rooms", moving.len())
+                        } else {
+                            format!("Move room {}", current_room_idx)
+                        };
+                        state.begin_transaction(&label);
+                    } else {
+                        state.set_status("Click inside the current room to move it", 2.0);
+                    }
+                }
+
+                EditorTool::PlaceObject => {
+                    if let Some(index) = hovered_pickup {
+                        state.selection = Selection::Pickup { room: current_room_idx, index };
+                    } else if let Some((gx, gz)) = hovered_sector {
+                        // Stand the pickup on the sector's floor, centered
+                        let floor_y = room.get_sector(gx, gz)
+                            .and_then(|s| s.floor.as_ref())
+                            .map(|f| f.avg_height())
+                            .unwrap_or(0.0);
+                        let position = crate::rasterizer::Vec3::new(
+                            (gx as f32 + 0.5) * SECTOR_SIZE,
+                            floor_y,
+                            (gz as f32 + 0.5) * SECTOR_SIZE,
+                        );
+
+                        state.save_undo(&format!("Place pickup ({}, {})", gx, gz));
+                        let pickup = Pickup::new(position, PICKUP_KINDS[0], state.selected_texture.clone());
+                        if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
+                            room.pickups.push(pickup);
+                            let index = room.pickups.len() - 1;
+                            state.selection = Selection::Pickup { room: current_room_idx, index };
+                        }
+                        state.mark_room_dirty(current_room_idx);
+                        state.set_status("Placed pickup", 2.0);
+                    } else {
+                        state.set_status("Click a sector to place a pickup", 2.0);
+                    }
+                }
+
+                EditorTool::PlaceAmbient => {
+                    if let Some(index) = hovered_ambient {
+                        state.selection = Selection::Ambient { room: current_room_idx, index };
+                    } else if let Some((gx, gz)) = hovered_sector {
+                        // Centered over the sector, around head height
+                        let floor_y = room.get_sector(gx, gz)
+                            .and_then(|s| s.floor.as_ref())
+                            .map(|f| f.avg_height())
+                            .unwrap_or(0.0);
+                        let position = crate::rasterizer::Vec3::new(
+                            (gx as f32 + 0.5) * SECTOR_SIZE,
+                            floor_y + AMBIENT_PLACE_HEIGHT,
+                            (gz as f32 + 0.5) * SECTOR_SIZE,
+                        );
+
+                        state.save_undo(&format!("Place ambient sound ({}, {})", gx, gz));
+                        let sound = state.sounds.names().next().unwrap_or_default().to_string();
+                        let ambient = AmbientEmitter::new(position, sound);
+                        if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
+                            room.ambients.push(ambient);
+                            let index = room.ambients.len() - 1;
+                            state.selection = Selection::Ambient { room: current_room_idx, index };
+                        }
+                        state.mark_room_dirty(current_room_idx);
+                        if state.sounds.is_empty() {
+                            state.set_warning("Placed ambient sound, but there are no sounds in assets/sounds to play");
+                        } else {
+                            state.set_status("Placed ambient sound", 2.0);
+                        }
+                    } else {
+                        state.set_status("Click a sector to place an ambient sound", 2.0);
+                    }
+                }
+
+                EditorTool::PlaceLight => {
+                    if let Some(index) = hovered_light {
+                        state.selection = Selection::Light { room: current_room_idx, index };
+                    } else if let Some((gx, gz)) = hovered_sector {
+                        // Centered over the sector, a sector above its floor
+                        let floor_y = room.get_sector(gx, gz)
+                            .and_then(|s| s.floor.as_ref())
+                            .map(|f| f.avg_height())
+                            .unwrap_or(0.0);
+                        let position = crate::rasterizer::Vec3::new(
+                            (gx as f32 + 0.5) * SECTOR_SIZE,
+                            floor_y + LIGHT_PLACE_HEIGHT,
+                            (gz as f32 + 0.5) * SECTOR_SIZE,
+                        );
+
+                        state.save_undo(&format!("Place light ({}, {})", gx, gz));
+                        if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
+                            room.lights.push(PointLight::new(position));
+                            let index = room.lights.len() - 1;
+                            state.selection = Selection::Light { room: current_room_idx, index };
+                        }
+                        state.mark_lights_dirty(current_room_idx);
+                        state.set_status("Placed light", 2.0);
+                    } else {
+                        state.set_status("Click a sector to place a light", 2.0);
+                    }
+                }
+
+                EditorTool::Measure => {
+                    state.measure.press(measure_point, false, mouse_pos);
+                }
+
+                EditorTool::PaintFlags => {
+                    begin_flag_stroke(state, false);
                 }
 
                 _ => {}
             }
         }
+
+        // Continue paint stroke: paint (or Alt: sample) every sector passed over
+        if state.grid_painting && ctx.mouse.left_down {
+            if let Some((gx, gz)) = hovered_sector {
+                let ceiling = state.tool == super::EditorTool::DrawCeiling;
+                if alt_down {
+                    // Alt samples textures instead of painting
+                    sample_sector_face(state, current_room_idx, gx, gz, ceiling);
+                } else {
+                    paint_sector_face(state, current_room_idx, gx, gz, ceiling);
+                }
+            }
+        }
+    }
+
+    // Right-click on an edge removes the topmost wall there (Wall tool).
+    // Right-click actions live outside the block above, since a right press also starts panning.
+    if state.tool == super::EditorTool::DrawWall && is_mouse_button_pressed(MouseButton::Right) {
+        if let Some((gx, gz, dir)) = hovered_edge {
+            let topmost = room.get_sector(gx, gz).and_then(|sector| {
+                sector.walls(dir)
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.y_top().partial_cmp(&b.1.y_top()).unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(i, _)| i)
+            });
+
+            if let Some(wall_idx) = topmost {
+                state.save_undo(&format!("Remove {:?} wall ({}, {})", dir, gx, gz));
+                if let Some(room) = state.level.rooms.get_mut(current_room_idx) {
+                    room.remove_wall(gx, gz, dir, wall_idx);
+                    room.recalculate_bounds();
+                }
+                state.mark_room_dirty(current_room_idx);
+                state.selection = Selection::None;
+                state.set_status(&format!("Removed {:?} wall", dir), 2.0);
+            }
+        }
+    }
+
+    // Right-click removes a pickup (Pickup tool)
+    if inside && state.tool == super::EditorTool::PlaceObject && is_mouse_button_pressed(MouseButton::Right) {
+        if let Some(index) = hovered_pickup {
+            remove_pickup(state, current_room_idx, index);
+        }
+    }
+
+    // Right-click removes an ambient sound (Ambient Sound tool)
+    if inside && state.tool == super::EditorTool::PlaceAmbient && is_mouse_button_pressed(MouseButton::Right) {
+        if let Some(index) = hovered_ambient {
+            remove_ambient(state, current_room_idx, index);
+        }
+    }
+
+    // Right-click removes a light (Point Light tool)
+    if inside && state.tool == super::EditorTool::PlaceLight && is_mouse_button_pressed(MouseButton::Right) {
+        if let Some(index) = hovered_light {
+            remove_light(state, current_room_idx, index);
+        }
+    }
+
+    // Paint Flags: a right press starts a stroke that sets the flags back
+    if inside && state.tool == super::EditorTool::PaintFlags && is_mouse_button_pressed(MouseButton::Right) {
+        begin_flag_stroke(state, true);
+    }
+    if let Some(value) = state.flag_paint {
+        if ctx.mouse.left_down || ctx.mouse.right_down {
+            if let Some((gx, gz, dir)) = hovered_edge {
+                paint_wall_solid(state, current_room_idx, gx, gz, dir, value);
+            } else if let (false, Some((gx, gz))) = (alt_down, hovered_sector) {
+                paint_walkable(state, current_room_idx, gx, gz, value);
+            }
+        } else {
+            state.flag_paint = None;
+            state.end_transaction();
+        }
+    }
+
+    // Move Room drag: translate the room in whole sectors, snapping to other rooms' edges.
+    // Picked rooms move along with it by the same offset.
+    if state.tool == super::EditorTool::MoveRoom && ctx.mouse.left_down {
+        if let Some(((start_x, start_z), start_pos)) = state.room_move_start {
+            let (wx, wz) = screen_to_world(mouse_pos.0, mouse_pos.1);
+            let size_x = room.width as f32 * SECTOR_SIZE;
+            let size_z = room.depth as f32 * SECTOR_SIZE;
+            let moving = moving_rooms(state);
+
+            let mut edges_x = Vec::new();
+            let mut edges_z = Vec::new();
+            for (idx, other) in state.level.rooms.iter().enumerate() {
+                if !moving.contains(&idx) {
+                    edges_x.extend([other.position.x, other.position.x + other.width as f32 * SECTOR_SIZE]);
+                    edges_z.extend([other.position.z, other.position.z + other.depth as f32 * SECTOR_SIZE]);
+                }
+            }
+
+            let new_x = snap_room_axis(start_pos.x + (wx - start_x), size_x, &edges_x);
+            let new_z = snap_room_axis(start_pos.z + (wz - start_z), size_z, &edges_z);
+
+            if new_x != room.position.x || new_z != room.position.z {
+                state.save_undo("Move room");
+                let dx = ((new_x - room.position.x) / SECTOR_SIZE).round() as i32;
+                let dz = ((new_z - room.position.z) / SECTOR_SIZE).round() as i32;
+                state.translate_rooms(&moving, dx, dz);
+            }
+        }
+    }
+
+    // End room move on release and check that its portals still connect
+    if state.room_move_start.is_some() && !ctx.mouse.left_down {
+        state.room_move_start = None;
+        if state.end_transaction() {
+            let moving = moving_rooms(state);
+            report_rooms_move(state, &moving);
+        }
+    }
+
+    // End paint stroke on release (even if the mouse left the view)
+    if state.grid_painting && !ctx.mouse.left_down {
+        state.grid_painting = false;
+        state.end_transaction();
+    }
+
+    // Wall run: the start corner and a preview line to the corner under the mouse
+    // (red while it's diagonal)
+    if state.tool == super::EditorTool::DrawWall {
+        let dot = |corner: RunCorner, color: Color| {
+            let (sx, sy) = corner_screen(corner);
+            draw_circle(sx, sy, 4.0, color);
+        };
+        match state.wall_run.start() {
+            Some(start) => {
+                let (ax, ay) = corner_screen(start);
+                let (bx, by) = corner_screen(run_corner);
+                let straight = start.1 == run_corner.1 || start.2 == run_corner.2;
+                let color = if straight { ACCENT_COLOR } else { Color::from_rgba(230, 80, 80, 255) };
+                draw_line(ax, ay, bx, by, 3.0, color);
+                dot(start, color);
+                if inside {
+                    dot(run_corner, color);
+                }
+            }
+            None if inside && near_run_corner => dot(run_corner, Color::from_rgba(255, 255, 255, 160)),
+            None => {}
+        }
+    }
+
+    // Measure: placed segments, then the rubber band to the mouse
+    if state.measure.pressed() && !state.measure.in_3d && !ctx.mouse.left_down {
+        state.measure.release(Some(measure_point), mouse_pos);
+    }
+    if !state.measure.in_3d && !state.measure.points.is_empty() {
+        draw_measure(state, world_to_screen, (state.tool == super::EditorTool::Measure && inside).then_some(measure_point));
     }
 
     // Disable scissor rectangle
@@ -380,3 +1406,54 @@ pub fn draw_grid_view(ctx: &mut UiContext, rect: Rect, state: &mut EditorState)
         get_internal_gl().quad_gl.scissor(None);
     }
 }
+
+/// Draw the grid's measure chain, with the rubber band to `cursor` (ground point) if given
+fn draw_measure(state: &EditorState, world_to_screen: impl Fn(f32, f32) -> (f32, f32), cursor: Option<crate::rasterizer::Vec3>) {
+    let color = Color::from_rgba(255, 220, 80, 255);
+    let label_color = Color::from_rgba(255, 240, 180, 255);
+    let measure = &state.measure;
+    let band = measure.points.last().copied().zip(cursor);
+    for (i, (a, b)) in measure.segments().chain(band).enumerate() {
+        let (ax, ay) = world_to_screen(a.x, a.z);
+        let (bx, by) = world_to_screen(b.x, b.z);
+        let is_band = i == measure.points.len() - 1;
+        draw_line(ax, ay, bx, by, if is_band { 1.0 } else { 2.0 }, color);
+        let label = grid_label(a, b);
+        draw_text(&label, ((ax + bx) * 0.5 + 6.0).floor(), ((ay + by) * 0.5 - 6.0).floor(), 13.0, label_color);
+    }
+    for p in &measure.points {
+        let (x, y) = world_to_screen(p.x, p.z);
+        draw_circle(x, y, 3.0, color);
+    }
+    let segments = measure.points.len().saturating_sub(1) + band.is_some() as usize;
+    if segments > 1 {
+        let total = measure.total(cursor);
+        // Under the end of the chain
+        let end = band.map_or(measure.points[measure.points.len() - 1], |(_, c)| c);
+        let (x, y) = world_to_screen(end.x, end.z);
+        let text = format!("Total {:.2} sec ({:.0})", total / SECTOR_SIZE, total);
+        draw_text(&text, (x + 6.0).floor(), (y + 16.0).floor(), 13.0, label_color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ruler_step_keeps_labels_apart() {
+        // Zoomed out: whole sectors, labeled in sectors
+        let step = ruler_step(0.1);
+        assert_eq!(step, SECTOR_SIZE);
+        assert_eq!(ruler_label(-3.0 * SECTOR_SIZE, step), "-3");
+
+        // Zoomed in: sub-sector steps, labeled in world units
+        let step = ruler_step(1.0);
+        assert_eq!(step, 64.0);
+        assert_eq!(ruler_label(1536.0, step), "1536");
+
+        for scale in [0.01, 0.03, 0.1, 0.3, 1.0, 2.0] {
+            assert!(ruler_step(scale) * scale >= RULER_MIN_SPACING);
+        }
+    }
+}
@@ -1,26 +1,41 @@
 //! Editor layout - TRLE-inspired panel arrangement
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext, SplitPanel, draw_panel, panel_content_rect, Toolbar, icon};
-use crate::rasterizer::{Framebuffer, Texture as RasterTexture};
-use super::{EditorState, EditorTool};
-use super::grid_view::draw_grid_view;
+use crate::ui::{Rect, UiContext, SplitPanel, CollapseSide, draw_panel, panel_content_rect, draw_scrollable_list, Toolbar, icon, text_input, TextInputEvent};
+use crate::ui::{drag_value, DragValue, ValueUnit};
+use crate::rasterizer::{Color as RasterColor, Framebuffer, Texture as RasterTexture};
+use crate::world::{light_dir_angles, light_dir_from_angles, LevelRenderSettings, MAX_UNDO};
+use super::{EditorState, EditorTool, LightControl, CLICK_HEIGHT, SECTOR_SIZE};
+use super::grid_view::{draw_grid_view, remove_ambient, remove_light, remove_pickup, report_room_move, report_rooms_move};
 use super::viewport_3d::draw_viewport_3d;
 use super::texture_palette::draw_texture_palette;
+use super::uv_preview::{draw_uv_preview, UV_PREVIEW_SIZE};
+use super::profiler::{self, profile_scope};
+use super::texture_replace::{ReplaceScope, TextureReplace};
+use super::stairs::{StairsOptions, MAX_STEP_CLICKS};
+use super::find_faces::FindFaces;
+
+/// Vertical mouse travel (pixels) per click when dragging a room's Y position
+const ROOM_Y_DRAG_PIXELS_PER_CLICK: f32 = 8.0;
 
 /// Actions that can be triggered by the editor UI
 #[derive(Debug, Clone, PartialEq)]
 pub enum EditorAction {
     None,
     Play,
+    PlayFrom { room: usize, x: usize, z: usize }, // Play From Here: start on this sector
     New,
     Save,
     SaveAs,
     Load(String),   // Path to load
     PromptLoad,     // Show file prompt
     Export,         // Browser: download as file
-    Import,         // Browser: upload file
+    Import,         // Browser: upload file; native: import an OBJ blockout
+    Compare,        // Native: pick a level file to compare against
     BrowseExamples, // Open example browser
+    SavePrefab,     // Native: save the current room to the prefab library
+    ExportWeb,      // Native: export a playable web bundle of the level
+    BrowsePrefabs,  // Open the prefab browser (Insert Prefab)
     Exit,           // Close/quit
 }
 
@@ -39,10 +54,10 @@ pub struct EditorLayout {
 impl EditorLayout {
     pub fn new() -> Self {
         Self {
-            main_split: SplitPanel::horizontal(1).with_ratio(0.25).with_min_size(150.0),
-            right_split: SplitPanel::horizontal(2).with_ratio(0.75).with_min_size(150.0),
-            left_split: SplitPanel::vertical(3).with_ratio(0.6).with_min_size(100.0),
-            right_panel_split: SplitPanel::vertical(4).with_ratio(0.6).with_min_size(100.0),
+            main_split: SplitPanel::horizontal(1).with_ratio(0.25).with_min_fraction(0.15).with_collapse(CollapseSide::First),
+            right_split: SplitPanel::horizontal(2).with_ratio(0.75).with_min_fraction(0.2).with_collapse(CollapseSide::Second),
+            left_split: SplitPanel::vertical(3).with_ratio(0.6).with_min_size(100.0).with_collapse(CollapseSide::Second),
+            right_panel_split: SplitPanel::vertical(4).with_ratio(0.6).with_min_size(100.0).with_collapse(CollapseSide::Second),
         }
     }
 
@@ -52,6 +67,10 @@ impl EditorLayout {
         self.right_split.ratio = config.right_split;
         self.left_split.ratio = config.left_split;
         self.right_panel_split.ratio = config.right_panel_split;
+        self.main_split.collapsed = config.main_collapsed;
+        self.right_split.collapsed = config.right_collapsed;
+        self.left_split.collapsed = config.left_collapsed;
+        self.right_panel_split.collapsed = config.right_panel_collapsed;
     }
 
     /// Extract current layout as a config (for saving with level)
@@ -61,6 +80,10 @@ impl EditorLayout {
             right_split: self.right_split.ratio,
             left_split: self.left_split.ratio,
             right_panel_split: self.right_panel_split.ratio,
+            main_collapsed: self.main_split.collapsed,
+            right_collapsed: self.right_split.collapsed,
+            left_collapsed: self.left_split.collapsed,
+            right_panel_collapsed: self.right_panel_split.collapsed,
         }
     }
 }
@@ -75,8 +98,14 @@ pub fn draw_editor(
     bounds: Rect,
     icon_font: Option<&Font>,
 ) -> EditorAction {
+    let theme = ctx.theme;
     let screen = bounds;
 
+    profiler::begin_frame();
+    if is_key_pressed(KeyCode::F11) && !ctx.text_input_focused() {
+        profiler::toggle();
+    }
+
     // Single unified toolbar at top
     let toolbar_height = 36.0;
     let toolbar_rect = screen.slice_top(toolbar_height);
@@ -88,7 +117,9 @@ pub fn draw_editor(
     let panels_rect = main_rect.remaining_after_bottom(status_height);
 
     // Draw unified toolbar
-    let action = draw_unified_toolbar(ctx, toolbar_rect, state, icon_font);
+    let mut action = draw_unified_toolbar(ctx, toolbar_rect, state, icon_font);
+    // The views find the sector under the mouse again below
+    state.sector_hover = None;
 
     // Main split: left panels | rest
     let (left_rect, rest_rect) = layout.main_split.update(ctx, panels_rect);
@@ -102,30 +133,130 @@ pub fn draw_editor(
     // Right split: texture palette | face properties
     let (texture_rect, props_rect) = layout.right_panel_split.update(ctx, right_rect);
 
-    // Draw panels
-    draw_panel(grid_rect, Some("2D Grid"), Color::from_rgba(35, 35, 40, 255));
-    draw_grid_view(ctx, panel_content_rect(grid_rect, true), state);
+    // History and texture report popups float over the 3D viewport; block input to
+    // the panels underneath them
+    let history_rect = history_popup_rect(center_rect, state);
+    let layers_rect = layers_popup_rect(center_rect);
+    let report_rect = texture_report_rect(center_rect);
+    let real_mouse = ctx.mouse;
+    let over_popup = (state.show_history && ctx.mouse.inside(&history_rect))
+        || (state.show_layers && ctx.mouse.inside(&layers_rect))
+        || (state.texture_report.is_some() && ctx.mouse.inside(&report_rect))
+        || (state.texture_replace.is_some() && ctx.mouse.inside(&report_rect))
+        || (state.stairs.is_some() && ctx.mouse.inside(&report_rect))
+        || (state.show_portal_suggestions && ctx.mouse.inside(&report_rect))
+        || (state.level_diff.is_some() && ctx.mouse.inside(&report_rect))
+        || (state.find_faces.is_some() && ctx.mouse.inside(&report_rect))
+        || (state.show_pack_load_report && ctx.mouse.inside(&report_rect))
+        || material_menu_rect(state).is_some_and(|r| ctx.mouse.inside(&r));
+    if over_popup {
+        ctx.begin_modal();
+    }
+
+    // Draw panels (collapsed panels have no area and are skipped)
+    let visible = |r: Rect| r.w > 1.0 && r.h > 1.0;
+
+    if visible(grid_rect) {
+        draw_panel(grid_rect, Some("2D Grid"), theme.panel_bg);
+        profile_scope!("grid");
+        // Top-down / isometric switch at the right of the title bar
+        let iso_rect = Rect::new(grid_rect.right() - 20.0, grid_rect.y + 1.0, 18.0, 18.0);
+        let iso_tip = if state.grid_isometric { "Top-down grid" } else { "Isometric preview (view only)" };
+        if crate::ui::icon_button_active(ctx, iso_rect, icon::AXIS_3D, icon_font, iso_tip, state.grid_isometric) {
+            state.toggle_grid_isometric();
+        }
+        let before = state.selection.clone();
+        draw_grid_view(ctx, panel_content_rect(grid_rect, true), state);
+        if state.sync_views && state.selection != before {
+            state.frame_selection();
+        }
+    }
+
+    if visible(room_props_rect) {
+        draw_panel(room_props_rect, Some("Room"), theme.panel_bg);
+        let room_action = draw_room_properties(ctx, panel_content_rect(room_props_rect, true), state, textures);
+        if room_action != EditorAction::None {
+            action = room_action;
+        }
+    }
+
+    if visible(center_rect) {
+        draw_panel(center_rect, Some("3D Viewport"), theme.inset_bg);
+        let before = state.selection.clone();
+        draw_viewport_3d(ctx, panel_content_rect(center_rect, true), state, textures, fb);
+        if state.sync_views && state.selection != before {
+            state.center_grid_on_selection();
+        }
+    }
 
-    draw_panel(room_props_rect, Some("Room"), Color::from_rgba(35, 35, 40, 255));
-    draw_room_properties(ctx, panel_content_rect(room_props_rect, true), state);
+    if visible(texture_rect) {
+        draw_panel(texture_rect, Some("Textures"), theme.panel_bg);
+        profile_scope!("textures");
+        draw_texture_palette(ctx, panel_content_rect(texture_rect, true), state, icon_font);
+    }
 
-    draw_panel(center_rect, Some("3D Viewport"), Color::from_rgba(25, 25, 30, 255));
-    draw_viewport_3d(ctx, panel_content_rect(center_rect, true), state, textures, fb);
+    if visible(props_rect) {
+        draw_panel(props_rect, Some("Properties"), theme.panel_bg);
+        profile_scope!("properties");
+        draw_properties(ctx, panel_content_rect(props_rect, true), state, icon_font);
+    }
 
-    draw_panel(texture_rect, Some("Textures"), Color::from_rgba(35, 35, 40, 255));
-    draw_texture_palette(ctx, panel_content_rect(texture_rect, true), state, icon_font);
+    if over_popup {
+        ctx.end_modal(real_mouse);
+    }
+    if state.show_history {
+        draw_history_popup(ctx, history_rect, state);
+    }
+    if state.show_layers {
+        draw_layers_popup(ctx, layers_rect, state);
+    }
+    if state.texture_report.is_some() {
+        draw_texture_report(ctx, report_rect, state);
+    }
+    if state.texture_replace.is_some() {
+        draw_texture_replace(ctx, report_rect, state);
+    }
+    if state.stairs.is_some() {
+        draw_stairs_popup(ctx, report_rect, state);
+    }
+    if state.show_portal_suggestions {
+        draw_portal_suggestions(ctx, report_rect, state);
+    }
+    if state.level_diff.is_some() {
+        draw_level_diff(ctx, report_rect, state);
+    }
+    if state.find_faces.is_some() {
+        draw_find_faces(ctx, report_rect, state);
+    }
+    if state.show_pack_load_report {
+        draw_pack_load_report(ctx, report_rect, state);
+    }
+    draw_material_menu(ctx, state);
 
-    draw_panel(props_rect, Some("Properties"), Color::from_rgba(35, 35, 40, 255));
-    draw_properties(ctx, panel_content_rect(props_rect, true), state, icon_font);
+    // Drags close their undo transaction on release; this catches one whose panel
+    // wasn't drawn on the release frame (collapsed, or under a popup)
+    if !ctx.mouse.left_down && !ctx.mouse.right_down {
+        state.end_transaction();
+    }
 
     // Draw status bar
-    draw_status_bar(status_rect, state);
+    draw_status_bar(ctx, status_rect, state);
+
+    if profiler::is_enabled() {
+        let stats = state.level_stats();
+        profiler::set_note(format!(
+            "rooms rebuilt {}/{}  {} sectors  {} walls",
+            state.room_meshes.rebuilt(), stats.room_count, stats.sector_count, stats.wall_count,
+        ));
+    }
+    profiler::draw_overlay(panels_rect);
 
     action
 }
 
 fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, icon_font: Option<&Font>) -> EditorAction {
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(40, 40, 45, 255));
+    let theme = ctx.theme;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.bar_bg);
 
     let mut action = EditorAction::None;
     let mut toolbar = Toolbar::new(rect);
@@ -140,17 +271,30 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, "Open") {
             action = EditorAction::PromptLoad;
         }
+        if toolbar.icon_button(ctx, icon::IMPORT, icon_font, "Import OBJ Blockout (new room)") {
+            action = EditorAction::Import;
+        }
         if toolbar.icon_button(ctx, icon::SAVE, icon_font, "Save") {
             action = EditorAction::Save;
         }
         if toolbar.icon_button(ctx, icon::SAVE_AS, icon_font, "Save As") {
             action = EditorAction::SaveAs;
         }
+        if toolbar.icon_button_active(ctx, icon::FILE_DIFF, icon_font, "Compare with File...", state.level_diff.is_some()) {
+            if state.level_diff.is_some() {
+                state.level_diff = None;
+            } else {
+                action = EditorAction::Compare;
+            }
+        }
+        if toolbar.icon_button(ctx, icon::GLOBE, icon_font, "Export Playable Web Bundle...") {
+            action = EditorAction::ExportWeb;
+        }
     }
 
     #[cfg(target_arch = "wasm32")]
     {
-        if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, "Upload") {
+        if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, "Upload level or texture pack (.ron, .zip)") {
             action = EditorAction::Import;
         }
         if toolbar.icon_button(ctx, icon::SAVE, icon_font, "Download") {
@@ -162,6 +306,55 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     if toolbar.icon_button(ctx, icon::BOOK_OPEN, icon_font, "Browse") {
         action = EditorAction::BrowseExamples;
     }
+    if toolbar.icon_button_active(ctx, icon::BRUSH_CLEANING, icon_font, "Clean Level (check texture references)", state.texture_report.is_some()) {
+        if state.texture_report.is_some() {
+            state.texture_report = None;
+        } else {
+            close_level_popups(state);
+            state.show_texture_report();
+        }
+    }
+    if toolbar.icon_button_active(ctx, icon::IMAGE_OFF, icon_font, "Highlight Missing Textures", state.raster_settings.highlight_missing_textures) {
+        state.raster_settings.highlight_missing_textures = !state.raster_settings.highlight_missing_textures;
+        let mode = if state.raster_settings.highlight_missing_textures { "ON" } else { "OFF" };
+        state.set_status(&format!("Highlight missing textures: {}", mode), 2.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::REPLACE, icon_font, "Replace Texture", state.texture_replace.is_some()) {
+        if state.texture_replace.is_some() {
+            state.texture_replace = None;
+        } else {
+            close_level_popups(state);
+            state.texture_replace = Some(TextureReplace::default());
+        }
+    }
+    if toolbar.icon_button_active(ctx, icon::SEARCH, icon_font, "Find Faces (by texture, flags, height...)", state.find_faces.is_some()) {
+        if state.find_faces.is_some() {
+            state.find_faces = None;
+        } else {
+            close_level_popups(state);
+            state.find_faces = Some(FindFaces::default());
+        }
+    }
+    if toolbar.icon_button(ctx, icon::CLIPBOARD_COPY, icon_font, "Copy Selection as Text (Ctrl+Shift+C)") {
+        state.copy_selection_as_text();
+    }
+    if toolbar.icon_button(ctx, icon::CLIPBOARD_PASTE, icon_font, "Paste Sectors from Text (Ctrl+Shift+V, at the grid cursor)") {
+        state.paste_from_text();
+    }
+    if toolbar.icon_button_active(ctx, icon::CHART_NO_AXES_COLUMN_INCREASING, icon_font, "Stairs Generator", state.stairs.is_some()) {
+        if state.stairs.is_some() {
+            state.stairs = None;
+        } else {
+            close_level_popups(state);
+            state.stairs = Some(StairsOptions::default());
+        }
+    }
+    if toolbar.icon_button_active(ctx, icon::PLUG, icon_font, "Suggest Portals (connect touching rooms)", state.show_portal_suggestions) {
+        let open = !state.show_portal_suggestions;
+        close_level_popups(state);
+        state.show_portal_suggestions = open;
+        state.portal_suggestions_scroll = 0.0;
+    }
 
     toolbar.separator();
 
@@ -172,6 +365,10 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     if toolbar.icon_button(ctx, icon::REDO, icon_font, "Redo") {
         state.redo();
     }
+    if toolbar.icon_button_active(ctx, icon::HISTORY, icon_font, "Undo History", state.show_history) {
+        state.show_history = !state.show_history;
+        state.show_layers = false;
+    }
 
     toolbar.separator();
 
@@ -186,14 +383,24 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     let tools = [
         (icon::MOVE, "Select", EditorTool::Select),
         (icon::SQUARE, "Floor", EditorTool::DrawFloor),
-        (icon::BOX, "Wall", EditorTool::DrawWall),
+        (icon::BOX, "Wall (click an edge, or two grid corners for a run)", EditorTool::DrawWall),
         (icon::LAYERS, "Ceiling", EditorTool::DrawCeiling),
         (icon::DOOR_CLOSED, "Portal", EditorTool::PlacePortal),
+        (icon::HAND_GRAB, "Move Room", EditorTool::MoveRoom),
+        (icon::GEM, "Pickup", EditorTool::PlaceObject),
+        (icon::VOLUME_2, "Ambient Sound", EditorTool::PlaceAmbient),
+        (icon::LIGHTBULB, "Point Light", EditorTool::PlaceLight),
+        (icon::RULER, "Measure (Esc clears)", EditorTool::Measure),
+        (icon::FOOTPRINTS, "Paint Flags (drag: LMB not walkable, RMB walkable; Alt+drag walls: solid)", EditorTool::PaintFlags),
     ];
 
     for (icon_char, tooltip, tool) in tools {
         let is_active = state.tool == tool;
         if toolbar.icon_button_active(ctx, icon_char, icon_font, tooltip, is_active) {
+            if tool != state.tool {
+                state.measure.clear();
+                state.wall_run.cancel();
+            }
             state.tool = tool;
         }
     }
@@ -209,6 +416,37 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         state.set_status(&format!("Vertex mode: {}", mode), 2.0);
     }
 
+    // Selecting in the grid frames it in 3D and vice versa
+    if toolbar.icon_button_active(ctx, icon::CROSSHAIR, icon_font, "Sync Views", state.sync_views) {
+        state.sync_views = !state.sync_views;
+        let mode = if state.sync_views { "on" } else { "off" };
+        state.set_status(&format!("Sync views: {}", mode), 2.0);
+    }
+
+    if toolbar.icon_button_active(ctx, icon::TAG, icon_font, "Show Labels", state.show_labels) {
+        state.show_labels = !state.show_labels;
+    }
+    let layers_tooltip = if state.isolate_geometry { "Viewport Layers (isolating geometry, H)" } else { "Viewport Layers" };
+    if toolbar.icon_button_active(ctx, icon::EYE, icon_font, layers_tooltip, state.show_layers || state.isolate_geometry) {
+        state.show_layers = !state.show_layers;
+        state.show_history = false;
+    }
+
+    // Height snap for vertex drags (cycles Click/Half/Quarter/Free)
+    let snap_tooltip = format!("Height Snap: {} (G, hold Ctrl while dragging for free)", state.height_snap.label());
+    if toolbar.icon_button_active(ctx, icon::MOVE_VERTICAL, icon_font, &snap_tooltip, state.height_snap != super::HeightSnap::Free) {
+        state.cycle_height_snap();
+    }
+
+    if toolbar.icon_button(ctx, icon::SWATCH_BOOK, icon_font, &format!("Theme: {}", state.theme.label())) {
+        state.cycle_theme();
+        ctx.theme = state.theme.theme();
+    }
+    if toolbar.icon_button_active(ctx, icon::TYPE, icon_font, "Text Labels: show toolbar buttons as text instead of icons", state.text_icons) {
+        state.toggle_text_icons();
+        ctx.text_icons = state.text_icons;
+    }
+
     toolbar.separator();
 
     // Room navigation
@@ -216,12 +454,12 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
 
     if toolbar.icon_button(ctx, icon::CIRCLE_CHEVRON_LEFT, icon_font, "Previous Room") {
         if state.current_room > 0 {
-            state.current_room -= 1;
+            state.go_to_room(state.current_room - 1);
         }
     }
     if toolbar.icon_button(ctx, icon::CIRCLE_CHEVRON_RIGHT, icon_font, "Next Room") {
         if state.current_room + 1 < state.level.rooms.len() {
-            state.current_room += 1;
+            state.go_to_room(state.current_room + 1);
         }
     }
     if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Add Room") {
@@ -262,6 +500,65 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         let mode = if state.raster_settings.dithering { "ON" } else { "OFF" };
         state.set_status(&format!("Dithering: {}", mode), 2.0);
     }
+    let bit15 = state.raster_settings.color_depth == crate::rasterizer::ColorDepth::Bit15;
+    if toolbar.icon_button_active(ctx, icon::PALETTE, icon_font, "15-bit Color (PS1 frame buffer)", bit15) {
+        use crate::rasterizer::ColorDepth;
+        state.raster_settings.color_depth = if bit15 { ColorDepth::Full } else { ColorDepth::Bit15 };
+        let mode = if bit15 { "24-bit" } else { "15-bit" };
+        state.set_status(&format!("Color depth: {}", mode), 2.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::TABLE_CELLS_MERGE, icon_font, "Merge Coplanar Faces (fewer triangles)", state.raster_settings.merge_coplanar) {
+        state.raster_settings.merge_coplanar = !state.raster_settings.merge_coplanar;
+        let mode = if state.raster_settings.merge_coplanar { "ON" } else { "OFF" };
+        state.set_status(&format!("Merge coplanar faces: {}", mode), 2.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::SUN, icon_font, "Smooth Normals (no seams between sloped tiles)", state.raster_settings.smooth_normals) {
+        state.raster_settings.smooth_normals = !state.raster_settings.smooth_normals;
+        let mode = if state.raster_settings.smooth_normals { "ON" } else { "OFF" };
+        state.set_status(&format!("Smooth normals: {}", mode), 2.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::IMAGE_DOWN, icon_font, "Mipmapping (less shimmer on distant surfaces)", state.raster_settings.mipmapping) {
+        state.raster_settings.mipmapping = !state.raster_settings.mipmapping;
+        let mode = if state.raster_settings.mipmapping { "ON" } else { "OFF" };
+        state.set_status(&format!("Mipmapping: {}", mode), 2.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::LAMP, icon_font, "Dynamic Lights (relight every frame while moving lights)", state.raster_settings.dynamic_lights) {
+        state.toggle_dynamic_lights();
+    }
+    let debug_view = state.raster_settings.debug_view;
+    let debug_tooltip = format!("Debug View: {} (overdraw, depth, triangle density)", debug_view.label());
+    if toolbar.icon_button_active(ctx, icon::BUG, icon_font, &debug_tooltip, debug_view != crate::rasterizer::DebugView::Off) {
+        state.raster_settings.debug_view = debug_view.next();
+        state.set_status(&format!("Debug view: {}", state.raster_settings.debug_view.label()), 2.0);
+    }
+
+    // Store the toggles above with the level (they're applied when it's opened or played)
+    let stored = state.level.render_settings.is_some();
+    if toolbar.icon_button_active(ctx, icon::FILE_COG, icon_font, "Store Render Settings with Level", stored) {
+        state.level.render_settings = (!stored).then(|| LevelRenderSettings::from_raster(&state.raster_settings));
+        state.mark_level_dirty();
+        let mode = if stored { "OFF" } else { "ON" };
+        state.set_status(&format!("Store render settings with level: {}", mode), 2.0);
+    }
+    state.sync_level_render_settings();
+
+    toolbar.separator();
+
+    // Ambient occlusion bake: button + strength drag value
+    if toolbar.icon_button(ctx, icon::CONTRAST, icon_font, "Bake AO (darken corners)") {
+        let strength = state.ao_strength;
+        state.save_undo(&format!("Bake AO ({:.0}%)", strength * 100.0));
+        let count = state.level.bake_corner_ao(strength);
+        state.mark_all_rooms_dirty();
+        state.set_status(&format!("Baked AO: darkened {} corners", count), 3.0);
+    }
+    let strength_rect = toolbar.next_rect(AO_STRENGTH_FIELD_WIDTH);
+    draw_ao_strength_field(ctx, strength_rect, state);
+
+    toolbar.separator();
+
+    let fov_rect = toolbar.next_rect(FOV_FIELD_WIDTH);
+    draw_viewport_fov_field(ctx, fov_rect, state);
 
     toolbar.separator();
 
@@ -271,14 +568,14 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
             let name = path.file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_else(|| "untitled".to_string());
-            if state.dirty {
+            if state.is_dirty() {
                 format!("{}*", name)
             } else {
                 name
             }
         }
         None => {
-            if state.dirty {
+            if state.is_dirty() {
                 "untitled*".to_string()
             } else {
                 "untitled".to_string()
@@ -287,7 +584,10 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     };
     toolbar.label(&file_label);
 
-    // Keyboard shortcuts
+    // Keyboard shortcuts (the focused text field gets the keys while typing)
+    if ctx.text_input_focused() {
+        return action;
+    }
     let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
              || is_key_down(KeyCode::LeftSuper) || is_key_down(KeyCode::RightSuper);
     let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
@@ -295,6 +595,12 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     if ctrl && is_key_pressed(KeyCode::N) {
         action = EditorAction::New;
     }
+    if ctrl && is_key_pressed(KeyCode::P) {
+        match state.play_from_target() {
+            Some((room, x, z)) => action = EditorAction::PlayFrom { room, x, z },
+            None => state.set_warning("Point at or select a sector to play from"),
+        }
+    }
     #[cfg(not(target_arch = "wasm32"))]
     {
         if ctrl && is_key_pressed(KeyCode::O) {
@@ -315,6 +621,21 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
             action = EditorAction::Export;
         }
     }
+    if !ctrl && is_key_pressed(KeyCode::G) {
+        state.cycle_height_snap();
+    }
+    if !ctrl && is_key_pressed(KeyCode::H) {
+        state.toggle_isolate();
+    }
+    if is_key_pressed(KeyCode::Escape) && !state.measure.points.is_empty() {
+        state.measure.clear();
+    }
+    if is_key_pressed(KeyCode::Escape) && state.wall_run.cancel() {
+        state.set_status("Wall run cancelled", 1.5);
+    }
+    if is_key_pressed(KeyCode::Escape) && state.cancel_drag() {
+        state.set_status("Drag cancelled", 1.5);
+    }
     if ctrl && is_key_pressed(KeyCode::Z) {
         if shift {
             state.redo();
@@ -322,11 +643,31 @@ fn draw_unified_toolbar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
             state.undo();
         }
     }
+    if ctrl && shift && is_key_pressed(KeyCode::C) {
+        state.copy_selection_as_text();
+    }
+    if ctrl && shift && is_key_pressed(KeyCode::V) {
+        state.paste_from_text();
+    }
+
+    // Camera bookmarks: Ctrl+1..5 stores the 3D view, 1..5 flies back to it
+    let bookmark_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5];
+    for (slot, key) in bookmark_keys.into_iter().enumerate() {
+        if is_key_pressed(key) {
+            if ctrl {
+                state.set_camera_bookmark(slot);
+            } else {
+                state.recall_camera_bookmark(slot);
+            }
+        }
+    }
 
     action
 }
 
-fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, textures: &[RasterTexture]) -> EditorAction {
+    let theme = ctx.theme;
+    let mut action = EditorAction::None;
     let mut y = rect.y.floor();
     let x = rect.x.floor();
     let line_height = 20.0;
@@ -335,12 +676,17 @@ fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         draw_text(&format!("ID: {}", room.id), x, (y + 14.0).floor(), 16.0, WHITE);
         y += line_height;
 
-        draw_text(
-            &format!("Pos: ({:.1}, {:.1}, {:.1})", room.position.x, room.position.y, room.position.z),
-            x, (y + 14.0).floor(), 16.0, WHITE,
-        );
+        let name_rect = Rect::new(x, y, rect.w - 4.0, line_height - 2.0);
+        draw_room_name_field(ctx, name_rect, state);
         y += line_height;
 
+        y = draw_room_position_fields(ctx, Rect::new(x, y, rect.w - 4.0, line_height - 2.0), state);
+
+        let room = match state.current_room() {
+            Some(room) => room,
+            None => return action,
+        };
+
         // Count sectors
         let sector_count = room.iter_sectors().count();
         draw_text(&format!("Size: {}x{}", room.width, room.depth), x, (y + 14.0).floor(), 16.0, WHITE);
@@ -352,99 +698,1849 @@ fn draw_room_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         draw_text(&format!("Portals: {}", room.portals.len()), x, (y + 14.0).floor(), 16.0, WHITE);
         y += line_height;
 
+        // Triangles as the 3D viewport last built them (merge setting applied)
+        let triangles = state.room_meshes.get(state.current_room, room).map_or("-".to_string(), |m| m.triangles().to_string());
+        draw_text(&format!("Triangles: {}", triangles), x, (y + 14.0).floor(), 16.0, WHITE);
+        y += line_height;
+
+        y += 4.0;
+        let half_w = ((rect.w - 8.0) * 0.5).floor();
+        let dup_rect = Rect::new(x, y, half_w, line_height - 2.0);
+        if draw_text_button(ctx, dup_rect, "Duplicate", "Copy this room one room-width along +X") {
+            duplicate_current_room(state);
+        }
+        let del_rect = Rect::new(x + half_w + 4.0, y, half_w, line_height - 2.0);
+        let picked = state.selected_rooms();
+        if picked.len() > 1 {
+            let tooltip = format!("Delete the {} picked rooms (portals into them are removed)", picked.len());
+            if draw_text_button(ctx, del_rect, &format!("Delete {}", picked.len()), &tooltip) {
+                state.delete_rooms(&picked);
+            }
+        } else if draw_text_button(ctx, del_rect, "Delete", "Delete this room (portals into it are removed)") {
+            let room_idx = state.current_room;
+            state.delete_room(room_idx);
+        }
+        y += line_height;
+
+        // Prefab library
+        let save_rect = Rect::new(x, y, half_w, line_height - 2.0);
+        if draw_text_button(ctx, save_rect, "Save Prefab", "Save this room's geometry to assets/prefabs (named after the room)") {
+            action = EditorAction::SavePrefab;
+        }
+        let insert_rect = Rect::new(x + half_w + 4.0, y, half_w, line_height - 2.0);
+        if draw_text_button(ctx, insert_rect, "Insert Prefab", "Stamp a saved prefab into this level as a new room") {
+            action = EditorAction::BrowsePrefabs;
+        }
+        y += line_height;
+
+        // Ambient light (every picked room)
+        draw_room_ambient_field(ctx, Rect::new(x, y, rect.w - 4.0, line_height - 2.0), state);
+        y += line_height;
+
+        // Batch move of the picked rooms, one sector per click
+        let picked = state.selected_rooms();
+        if picked.len() > 1 {
+            draw_text(&format!("{} rooms picked (Ctrl/Shift+click)", picked.len()), x, (y + 14.0).floor(), 14.0, theme.text_dim);
+            y += line_height;
+            let nudge_w = ((rect.w - 4.0 - 3.0 * 4.0) / 4.0).floor();
+            let nudges = [("-X", -1, 0), ("+X", 1, 0), ("-Z", 0, -1), ("+Z", 0, 1)];
+            for (i, (label, dx, dz)) in nudges.into_iter().enumerate() {
+                let nudge_rect = Rect::new(x + i as f32 * (nudge_w + 4.0), y, nudge_w, line_height - 2.0);
+                if draw_text_button(ctx, nudge_rect, label, &format!("Move the picked rooms one sector along {}", label)) {
+                    state.save_undo(&format!("Move {} rooms", picked.len()));
+                    state.translate_rooms(&picked, dx, dz);
+                    report_rooms_move(state, &picked);
+                }
+            }
+            y += line_height;
+        }
+
+        // Light direction (room override or level sun)
+        y += 6.0;
+        y = draw_light_controls(ctx, Rect::new(x, y, rect.w - 4.0, 0.0), state);
+        y = draw_game_fov_row(ctx, Rect::new(x, y, rect.w - 4.0, line_height - 2.0), state);
+
+        // Camera bookmarks (level-wide)
+        y += 10.0;
+        y = draw_camera_bookmarks(ctx, Rect::new(x, y, rect.w - 4.0, 0.0), state);
+
         // Room list
         y += 10.0;
-        draw_text("Rooms:", x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+        draw_text("Rooms:", x, (y + 14.0).floor(), 16.0, theme.text_dim);
         y += line_height;
 
-        for (i, room) in state.level.rooms.iter().enumerate() {
+        // Click to select, drag onto another row to reorder
+        let rows: Vec<(String, String)> = state.level.rooms.iter().enumerate()
+            .map(|(i, room)| {
+                let mut counts = if room.is_empty() {
+                    "empty room".to_string()
+                } else {
+                    format!("{} sectors", room.iter_sectors().count())
+                };
+                if let Some(mesh) = state.room_meshes.get(i, room) {
+                    counts.push_str(&format!(", {} tris", mesh.triangles()));
+                }
+                (room.display_name(), counts)
+            })
+            .collect();
+        state.room_thumbnails.begin_frame(&state.level, state.current_room);
+        let row_height = ROOM_THUMBNAIL_PX + 4.0;
+        let mut drop_target = None;
+        for (i, (name, counts)) in rows.iter().enumerate() {
             let is_selected = i == state.current_room;
             let color = if is_selected {
-                Color::from_rgba(100, 200, 100, 255)
+                theme.selection
             } else {
                 WHITE
             };
 
-            let room_btn_rect = Rect::new(x, y, rect.w - 4.0, line_height);
+            let room_btn_rect = Rect::new(x, y, rect.w - 4.0, row_height);
             if ctx.mouse.clicked(&room_btn_rect) {
-                state.current_room = i;
+                let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+                let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+                state.click_room(i, ctrl, shift);
+                if !ctrl && !shift {
+                    state.room_list_drag = Some(i);
+                }
             }
 
             if is_selected {
-                draw_rectangle(room_btn_rect.x.floor(), room_btn_rect.y.floor(), room_btn_rect.w, room_btn_rect.h, Color::from_rgba(60, 80, 60, 255));
+                draw_rectangle(room_btn_rect.x.floor(), room_btn_rect.y.floor(), room_btn_rect.w, room_btn_rect.h, theme.selection_bg);
+            }
+            if state.room_selection.contains(&i) {
+                draw_rectangle_lines(room_btn_rect.x.floor(), room_btn_rect.y.floor(), room_btn_rect.w, room_btn_rect.h, 1.0, theme.highlight);
             }
 
-            let sector_count = room.iter_sectors().count();
-            draw_text(&format!("  Room {} ({} sectors)", room.id, sector_count), x, (y + 14.0).floor(), 16.0, color);
-            y += line_height;
+            let thumb_rect = Rect::new(x + 2.0, y + 2.0, ROOM_THUMBNAIL_PX, ROOM_THUMBNAIL_PX);
+            let thumbnail = state.room_thumbnails.get(&state.level, i, &state.texture_packs, textures, &state.raster_settings);
+            if let Some(texture) = thumbnail {
+                draw_texture_ex(&texture, thumb_rect.x, thumb_rect.y, WHITE, DrawTextureParams {
+                    dest_size: Some(Vec2::new(thumb_rect.w, thumb_rect.h)),
+                    ..Default::default()
+                });
+            } else {
+                draw_rectangle(thumb_rect.x, thumb_rect.y, thumb_rect.w, thumb_rect.h, Color::from_rgba(30, 30, 40, 255));
+            }
+            if is_selected {
+                draw_rectangle_lines(thumb_rect.x - 1.0, thumb_rect.y - 1.0, thumb_rect.w + 2.0, thumb_rect.h + 2.0, 2.0, color);
+            }
 
-            if y > rect.bottom() - line_height {
+            let text_y = (y + (row_height + 12.0) * 0.5).floor();
+            draw_text(&format!("{} ({})", name, counts), thumb_rect.right() + 6.0, text_y, 16.0, color);
+
+            if state.room_list_drag.is_some_and(|from| from != i) && ctx.mouse.inside(&room_btn_rect) {
+                drop_target = Some(i);
+                // Insertion marker on the side the dragged room will land
+                let marker_y = if state.room_list_drag.is_some_and(|from| from < i) { room_btn_rect.bottom() } else { room_btn_rect.y };
+                draw_line(room_btn_rect.x, marker_y.floor(), room_btn_rect.right(), marker_y.floor(), 2.0, theme.selection);
+            }
+            y += row_height;
+
+            if y > rect.bottom() - row_height {
                 break;
             }
         }
+
+        if let Some(from) = state.room_list_drag {
+            if !ctx.mouse.left_down {
+                state.room_list_drag = None;
+                if let Some(to) = drop_target {
+                    state.move_room(from, to);
+                }
+            }
+        }
     } else {
-        draw_text("No room selected", x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+        draw_text("No room selected", x, (y + 14.0).floor(), 16.0, theme.text_dim);
     }
+    action
 }
 
-/// Container configuration
-const CONTAINER_PADDING: f32 = 8.0;
-const CONTAINER_MARGIN: f32 = 6.0;
+/// Drag value IDs of the room position (plus the axis: X, Y, Z)
+const ROOM_POSITION_FIELD: u64 = 0x504f_5300;
 
-/// Draw a container box with a colored header
-fn draw_container_start(
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
-    header_text: &str,
-    header_color: Color,
-) {
-    let header_height = 22.0;
+/// Draw the room position as drag values, one row per axis: X and Z in sectors
+/// (snapped to the sector grid), Y in clicks. A drag or a typed value is one undo
+/// step. Returns the new y.
+fn draw_room_position_fields(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let Some(position) = state.current_room().map(|room| room.position) else {
+        return rect.y;
+    };
+    let room_idx = state.current_room;
+    let axes = [("X", position.x, SECTORS), ("Y", position.y, CLICKS), ("Z", position.z, SECTORS)];
+    let mut y = rect.y;
+    for (axis, (label, value, unit)) in axes.into_iter().enumerate() {
+        let vertical = axis == 1;
+        let spec = DragValue {
+            tooltip: if vertical { "Room height" } else { "Room position on the sector grid" },
+            pixels_per_step: if vertical { ROOM_Y_DRAG_PIXELS_PER_CLICK } else { 16.0 },
+            vertical,
+            snap: !vertical,
+            unit: Some(unit),
+            ..DragValue::new(label, unit.size)
+        };
+        let field = Rect::new(rect.x, y, rect.w, rect.h);
+        if let Some(change) = drag_value(ctx, field, ROOM_POSITION_FIELD + axis as u64, value, &spec) {
+            if change.value != value {
+                if change.first {
+                    state.save_undo(&format!("Move room {} along {}", room_idx, label));
+                }
+                if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                    match axis {
+                        0 => room.position.x = change.value,
+                        1 => room.position.y = change.value,
+                        _ => room.position.z = change.value,
+                    }
+                    room.recalculate_bounds();
+                }
+                state.mark_room_dirty(room_idx);
+            }
+            if change.last && (change.value != value || !change.first) {
+                report_room_move(state, room_idx);
+            }
+        }
+        y += rect.h + 2.0;
+    }
+    y
+}
 
-    // Container background
-    draw_rectangle(
-        x.floor(), y.floor(),
-        width, height,
-        Color::from_rgba(30, 30, 35, 255)
-    );
+/// Draw the room ambient drag value (drag horizontally, 1% per pixel). Shows the
+/// current room's value and sets every picked room; one drag is one undo step.
+fn draw_room_ambient_field(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let Some(ambient) = state.current_room().map(|room| room.ambient) else {
+        return;
+    };
+    let rooms = state.selected_rooms();
+    let hovered = ctx.mouse.inside(&rect);
+    if hovered {
+        ctx.set_tooltip("Ambient light (drag left/right)", ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.room_ambient_drag = Some((ctx.mouse.x, ambient));
+            state.room_ambient_started = false;
+        }
+    }
+    if let Some((anchor_x, start)) = state.room_ambient_drag {
+        if ctx.mouse.left_down {
+            let value = ((start + (ctx.mouse.x - anchor_x) * 0.01).clamp(0.0, 1.0) * 100.0).round() / 100.0;
+            let changed = rooms.iter().any(|&i| state.level.rooms.get(i).is_some_and(|r| r.ambient != value));
+            if changed {
+                if !state.room_ambient_started {
+                    let label = if rooms.len() > 1 { format!("Ambient of {} rooms", rooms.len()) } else { String::from("Room ambient") };
+                    state.save_undo(&label);
+                    state.room_ambient_started = true;
+                }
+                state.set_rooms_ambient(&rooms, value);
+            }
+        } else {
+            state.room_ambient_drag = None;
+        }
+    }
 
-    // Container border
-    draw_rectangle_lines(
-        x.floor(), y.floor(),
-        width, height,
-        1.0,
-        Color::from_rgba(60, 60, 70, 255)
-    );
+    let active = hovered || state.room_ambient_drag.is_some();
+    let bg = if active { theme.button_hover } else { theme.button_bg };
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, bg);
+    draw_rectangle(rect.x, rect.y, rect.w * ambient, rect.h, theme.fill);
+    let label = if rooms.len() > 1 {
+        format!("Ambient: {:.0}% ({} rooms)", ambient * 100.0, rooms.len())
+    } else {
+        format!("Ambient: {:.0}%", ambient * 100.0)
+    };
+    draw_text(&label, (rect.x + 4.0).floor(), (rect.y + 13.0).floor(), 14.0, WHITE);
+}
 
-    // Header background
-    draw_rectangle(
-        x.floor(), y.floor(),
-        width, header_height,
-        Color::from_rgba(header_color.r as u8 / 4, header_color.g as u8 / 4, header_color.b as u8 / 4, 200)
+/// Text field IDs of the camera bookmark names (one per slot, stable across frames)
+const BOOKMARK_NAME_FIELD: u64 = 0x424f_4f4b;
+
+/// Draw the camera bookmark list: one row per stored slot with a rename field,
+/// a recall button and a delete button. Returns the new y.
+fn draw_camera_bookmarks(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let theme = ctx.theme;
+    let line_height = 20.0;
+    let mut y = rect.y;
+    draw_text("Camera bookmarks:", rect.x, (y + 14.0).floor(), 16.0, theme.text_dim);
+    y += line_height;
+
+    if state.level.camera_bookmarks.iter().all(Option::is_none) {
+        draw_text("Ctrl+1..5 to store a view", (rect.x + 4.0).floor(), (y + 13.0).floor(), 14.0, theme.text_faint);
+        return y + line_height;
+    }
+
+    let button_w = 26.0;
+    for slot in 0..crate::world::CAMERA_BOOKMARK_SLOTS {
+        let Some(name) = state.level.camera_bookmarks[slot].as_ref().map(|b| b.name.clone()) else {
+            continue;
+        };
+        let row = Rect::new(rect.x, y, rect.w, line_height - 2.0);
+        let go_rect = Rect::new(row.x, row.y, button_w, row.h);
+        if draw_text_button(ctx, go_rect, &format!("{}", slot + 1), &format!("Fly to this view (key {})", slot + 1)) {
+            state.recall_camera_bookmark(slot);
+        }
+
+        let field_id = BOOKMARK_NAME_FIELD + slot as u64;
+        let field = Rect::new(go_rect.right() + 4.0, row.y, row.w - (button_w + 4.0) * 2.0, row.h);
+        let focused = ctx.is_text_focused(field_id);
+        if !focused {
+            state.bookmark_name_edits[slot].clone_from(&name);
+        }
+        if text_input(ctx, field, field_id, &mut state.bookmark_name_edits[slot]) == TextInputEvent::Submitted {
+            let new_name = state.bookmark_name_edits[slot].trim().to_string();
+            if let Some(bookmark) = state.level.camera_bookmarks[slot].as_mut() {
+                if bookmark.name != new_name {
+                    bookmark.name = new_name;
+                    state.mark_level_dirty();
+                }
+            }
+        }
+        if !focused && name.is_empty() {
+            draw_text("(unnamed)", (field.x + 4.0).floor(), (row.y + 13.0).floor(), 14.0, theme.text_dim);
+        }
+
+        let del_rect = Rect::new(row.right() - button_w, row.y, button_w, row.h);
+        if draw_text_button(ctx, del_rect, "x", "Delete this bookmark") {
+            state.delete_camera_bookmark(slot);
+        }
+        y += line_height;
+    }
+    y
+}
+
+/// Displayed size of the room list thumbnails
+const ROOM_THUMBNAIL_PX: f32 = 32.0;
+
+/// Text field ID of the room name (stable across frames)
+const ROOM_NAME_FIELD: u64 = 0x524f_4f4d;
+
+/// Draw the room name field. Click to type, Enter (or clicking elsewhere) renames,
+/// Escape cancels. One undo step per rename.
+fn draw_room_name_field(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let room_idx = state.current_room;
+    let Some(current_name) = state.level.rooms.get(room_idx).map(|r| r.name.clone()) else {
+        return;
+    };
+
+    let label_w = 42.0;
+    draw_text("Name:", (rect.x + 4.0).floor(), (rect.y + 13.0).floor(), 14.0, WHITE);
+    let field = Rect::new(rect.x + label_w, rect.y, rect.w - label_w, rect.h);
+    let focused = ctx.is_text_focused(ROOM_NAME_FIELD);
+    if !focused {
+        // Follow the room (undo, switching rooms) while not being edited
+        state.room_name_edit.clone_from(&current_name);
+    }
+
+    if text_input(ctx, field, ROOM_NAME_FIELD, &mut state.room_name_edit) == TextInputEvent::Submitted {
+        let new_name = state.room_name_edit.trim().to_string();
+        if new_name != current_name {
+            state.save_undo("Rename room");
+            if let Some(room) = state.level.rooms.get_mut(room_idx) {
+                room.name = new_name;
+            }
+            state.mark_room_dirty(room_idx);
+        }
+    }
+
+    if !focused && current_name.is_empty() {
+        draw_text("(unnamed)", (field.x + 4.0).floor(), (rect.y + 13.0).floor(), 14.0, theme.text_dim);
+    }
+    if !focused && ctx.mouse.inside(&field) {
+        ctx.set_tooltip("Click to rename this room", ctx.mouse.x, ctx.mouse.y);
+    }
+}
+
+/// Diameter of the sun-angle compass in the room properties
+const LIGHT_COMPASS_SIZE: f32 = 56.0;
+
+/// Draw the light section: override toggle, azimuth compass and elevation slider.
+/// Edits the room's override if it has one, otherwise the level light. Returns the new y.
+fn draw_light_controls(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    let theme = ctx.theme;
+    let line_height = 20.0;
+    let room_idx = state.current_room;
+    let Some(room) = state.level.rooms.get(room_idx) else {
+        return rect.y;
+    };
+    let overridden = room.light_dir.is_some();
+    let dir = state.level.room_light_dir(room_idx);
+    let (azimuth, elevation) = light_dir_angles(dir);
+    let mut y = rect.y;
+
+    let toggle_rect = Rect::new(rect.x, y, rect.w, line_height - 2.0);
+    let toggle_label = if overridden { "Light: Room override" } else { "Light: Level" };
+    let toggle_tip = if overridden {
+        "Click to use the level light for this room"
+    } else {
+        "Click to give this room its own light direction"
+    };
+    if draw_text_button(ctx, toggle_rect, toggle_label, toggle_tip) {
+        let label = if overridden { "Remove room light override" } else { "Add room light override" };
+        state.save_undo(label);
+        let level_dir = state.level.light_dir;
+        if let Some(room) = state.level.rooms.get_mut(room_idx) {
+            room.light_dir = if overridden { None } else { Some(level_dir) };
+        }
+        state.mark_room_dirty(room_idx);
+    }
+    y += line_height + 2.0;
+
+    // Compass: seen from above like the grid view (+Z up, +X right)
+    let size = LIGHT_COMPASS_SIZE;
+    let compass = Rect::new(rect.x, y, size, size);
+    let (cx, cy) = (compass.x + size * 0.5, compass.y + size * 0.5);
+    let radius = size * 0.5 - 2.0;
+    let compass_hovered = ctx.mouse.inside(&compass);
+    draw_circle(cx, cy, radius, Color::from_rgba(40, 40, 48, 255));
+    draw_circle_lines(cx, cy, radius, 1.0, theme.border);
+    // Dot moves toward the center as the sun rises
+    let dot_r = radius * elevation.cos().clamp(0.0, 1.0);
+    let (dot_x, dot_y) = (cx + azimuth.sin() * dot_r, cy - azimuth.cos() * dot_r);
+    draw_line(cx, cy, dot_x, dot_y, 1.0, Color::from_rgba(120, 120, 140, 255));
+    draw_circle(dot_x, dot_y, 4.0, Color::from_rgba(255, 210, 90, 255));
+    if compass_hovered {
+        ctx.set_tooltip("Drag to set the light's compass direction (+Z up)", ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.light_drag = Some(LightControl::Azimuth);
+            state.light_drag_started = false;
+        }
+    }
+
+    // Elevation slider to the right of the compass
+    let slider_x = compass.right() + 8.0;
+    let slider = Rect::new(slider_x, y + size * 0.5 - 2.0, (rect.right() - slider_x).max(16.0), line_height - 2.0);
+    let t = (elevation / std::f32::consts::FRAC_PI_2).clamp(0.0, 1.0);
+    let slider_hovered = ctx.mouse.inside(&slider);
+    let slider_bg = if slider_hovered || state.light_drag == Some(LightControl::Elevation) {
+        theme.button_hover
+    } else {
+        theme.button_bg
+    };
+    draw_rectangle(slider.x, slider.y, slider.w, slider.h, slider_bg);
+    draw_rectangle(slider.x, slider.y, slider.w * t, slider.h, theme.fill);
+    draw_text(&format!("Elev {:.0}", elevation.to_degrees()), (slider.x + 4.0).floor(), (slider.y + 13.0).floor(), 14.0, WHITE);
+    draw_text(
+        &format!("Az {:.0}", azimuth.to_degrees().rem_euclid(360.0)),
+        (slider_x + 4.0).floor(), (slider.y - 6.0).floor(), 14.0, theme.text_secondary,
     );
+    if slider_hovered {
+        ctx.set_tooltip("Drag to set the light's height above the horizon", ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.light_drag = Some(LightControl::Elevation);
+            state.light_drag_started = false;
+        }
+    }
+
+    if let Some(control) = state.light_drag {
+        if ctx.mouse.left_down {
+            let (new_az, new_el) = match control {
+                LightControl::Azimuth => ((ctx.mouse.x - cx).atan2(cy - ctx.mouse.y), elevation),
+                LightControl::Elevation => {
+                    let t = ((ctx.mouse.x - slider.x) / slider.w).clamp(0.0, 1.0);
+                    (azimuth, t * std::f32::consts::FRAC_PI_2)
+                }
+            };
+            let new_dir = light_dir_from_angles(new_az, new_el);
+            if (new_dir - dir).len() > 1e-4 {
+                if !state.light_drag_started {
+                    state.save_undo("Change light direction");
+                    state.light_drag_started = true;
+                }
+                match state.level.rooms.get_mut(room_idx) {
+                    Some(room) if room.light_dir.is_some() => {
+                        room.light_dir = Some(new_dir);
+                        state.mark_room_dirty(room_idx);
+                    }
+                    _ => {
+                        state.level.light_dir = new_dir;
+                        state.mark_level_dirty();
+                    }
+                }
+            }
+        } else {
+            state.light_drag = None;
+        }
+    }
+
+    y + size + 4.0
+}
+
+/// Draw a simple full-width text button. Returns true when clicked.
+fn draw_text_button(ctx: &mut UiContext, rect: Rect, label: &str, tooltip: &str) -> bool {
+    let theme = ctx.theme;
+    let hovered = ctx.mouse.inside(&rect);
+    let bg = if hovered {
+        theme.button_hover
+    } else {
+        theme.button_bg
+    };
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, bg);
+    draw_rectangle_lines(rect.x.floor(), rect.y.floor(), rect.w, rect.h, 1.0, theme.border);
+    draw_text(label, (rect.x + 4.0).floor(), (rect.y + 13.0).floor(), 14.0, WHITE);
+
+    if hovered {
+        ctx.set_tooltip(tooltip, ctx.mouse.x, ctx.mouse.y);
+    }
+
+    ctx.mouse.clicked(&rect)
+}
+
+/// Duplicate the current room (single undo step) and select the copy
+fn duplicate_current_room(state: &mut EditorState) {
+    let room_idx = state.current_room;
+    if room_idx >= state.level.rooms.len() {
+        return;
+    }
+
+    state.save_undo(&format!("Duplicate room {}", room_idx));
+    if let Some((new_idx, dropped)) = state.level.duplicate_room(room_idx) {
+        state.mark_room_dirty(new_idx);
+        state.current_room = new_idx;
+        state.selection = super::Selection::None;
+        state.clear_multi_selection();
+        if dropped > 0 {
+            state.set_status(&format!("Duplicated room {} as room {} ({} portal(s) to other rooms dropped)", room_idx, new_idx, dropped), 4.0);
+        } else {
+            state.set_status(&format!("Duplicated room {} as room {}", room_idx, new_idx), 2.0);
+        }
+    }
+}
+
+/// Max rows shown in the history popup
+const HISTORY_ROWS: usize = 20;
+const HISTORY_ROW_HEIGHT: f32 = 18.0;
+const HISTORY_WIDTH: f32 = 280.0;
+
+/// Rows in the history popup: initial state, done actions, then undone (redoable) actions
+fn history_row_count(state: &EditorState) -> usize {
+    1 + state.history.undo_stack.len() + state.history.redo_stack.len()
+}
+
+/// Rectangle of the history popup (top-left corner of the 3D viewport)
+fn history_popup_rect(center_rect: Rect, state: &EditorState) -> Rect {
+    let rows = history_row_count(state).min(HISTORY_ROWS);
+    Rect::new(
+        center_rect.x + 8.0,
+        center_rect.y + 28.0,
+        HISTORY_WIDTH,
+        24.0 + rows as f32 * HISTORY_ROW_HEIGHT + 4.0,
+    )
+}
+
+/// Layers popover rows: the layer toggles, then Isolate
+const LAYER_ROWS: usize = 6;
+
+/// Layers popover: top-left corner of the 3D viewport, like the history popup
+fn layers_popup_rect(center_rect: Rect) -> Rect {
+    Rect::new(center_rect.x + 8.0, center_rect.y + 28.0, 200.0, 24.0 + LAYER_ROWS as f32 * HISTORY_ROW_HEIGHT + 4.0)
+}
+
+/// Draw the viewport layers popover: a checkbox per overlay (saved in prefs), and
+/// Isolate, which hides them all and the labels until turned off
+fn draw_layers_popup(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text("Viewport Layers", (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, theme.text_dim);
+
+    let isolate = state.isolate_geometry;
+    let mut layers = state.viewport_layers;
+    let mut rows: Vec<(&str, &mut bool)> = layers.toggles().into_iter().collect();
+    let mut isolate_flag = isolate;
+    rows.push(("Isolate geometry (H)", &mut isolate_flag));
+
+    let mut changed = false;
+    for (i, (label, flag)) in rows.into_iter().enumerate() {
+        let row_rect = Rect::new(rect.x + 2.0, rect.y + 24.0 + i as f32 * HISTORY_ROW_HEIGHT, rect.w - 4.0, HISTORY_ROW_HEIGHT);
+        if ctx.mouse.inside(&row_rect) {
+            draw_rectangle(row_rect.x, row_rect.y, row_rect.w, row_rect.h, Color::from_rgba(50, 50, 62, 255));
+        }
+        // Layer rows are dimmed while Isolate overrides them
+        let is_layer = i + 1 < LAYER_ROWS;
+        let color = if is_layer && isolate { theme.text_dim } else { WHITE };
+        let box_rect = Rect::new(row_rect.x + 4.0, row_rect.y + 4.0, 10.0, 10.0);
+        draw_rectangle_lines(box_rect.x, box_rect.y, box_rect.w, box_rect.h, 1.0, color);
+        if *flag {
+            draw_rectangle(box_rect.x + 2.0, box_rect.y + 2.0, box_rect.w - 4.0, box_rect.h - 4.0, color);
+        }
+        draw_text(label, (row_rect.x + 20.0).floor(), (row_rect.y + 13.0).floor(), 13.0, color);
+        if ctx.mouse.clicked(&row_rect) {
+            *flag = !*flag;
+            changed = true;
+        }
+    }
+
+    if isolate_flag != isolate {
+        state.toggle_isolate();
+    } else if changed {
+        state.viewport_layers = layers;
+        state.save_prefs();
+    }
+}
+
+/// Draw the undo history list. Clicking a row undoes/redoes to the state after that action.
+fn draw_history_popup(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text(
+        &format!("History ({}/{})", state.history.undo_stack.len(), MAX_UNDO),
+        (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, theme.text_dim,
+    );
+
+    let total = history_row_count(state);
+    let position = state.history.undo_stack.len(); // Row of the current state
+    let start = position
+        .saturating_sub(HISTORY_ROWS - 5)
+        .min(total.saturating_sub(HISTORY_ROWS));
+
+    let mut clicked_row = None;
+    for (screen_row, row) in (start..total).take(HISTORY_ROWS).enumerate() {
+        let row_rect = Rect::new(rect.x + 2.0, rect.y + 24.0 + screen_row as f32 * HISTORY_ROW_HEIGHT, rect.w - 4.0, HISTORY_ROW_HEIGHT);
+        let label = if row == 0 {
+            "(initial state)"
+        } else if row <= state.history.undo_stack.len() {
+            state.history.undo_stack[row - 1].label.as_str()
+        } else {
+            // Redo stack top is the next action to redo
+            let redo_idx = state.history.redo_stack.len() - (row - state.history.undo_stack.len());
+            state.history.redo_stack[redo_idx].label.as_str()
+        };
+
+        let is_current = row == position;
+        if is_current {
+            draw_rectangle(row_rect.x, row_rect.y, row_rect.w, row_rect.h, theme.selection_bg);
+        } else if ctx.mouse.inside(&row_rect) {
+            draw_rectangle(row_rect.x, row_rect.y, row_rect.w, row_rect.h, Color::from_rgba(50, 50, 62, 255));
+        }
+
+        let color = if row > position {
+            Color::from_rgba(110, 110, 120, 255) // Undone (redoable)
+        } else if is_current {
+            theme.selection
+        } else {
+            WHITE
+        };
+        let marker = if is_current { ">" } else { " " };
+        draw_text(&format!("{} {}", marker, label), (row_rect.x + 4.0).floor(), (row_rect.y + 13.0).floor(), 13.0, color);
+
+        if ctx.mouse.clicked(&row_rect) {
+            clicked_row = Some(row);
+        }
+    }
+
+    if let Some(row) = clicked_row {
+        state.jump_history(row as isize - position as isize);
+    }
+}
+
+/// Texture report popup sizing
+const REPORT_WIDTH: f32 = 300.0;
+const REPORT_ROWS: usize = 12;
+const REPORT_ROW_HEIGHT: f32 = 18.0;
+
+/// Texture report popup: top-right corner of the 3D viewport
+fn texture_report_rect(center_rect: Rect) -> Rect {
+    Rect::new(
+        center_rect.right() - REPORT_WIDTH - 8.0,
+        center_rect.y + 28.0,
+        REPORT_WIDTH,
+        60.0 + REPORT_ROWS as f32 * REPORT_ROW_HEIGHT + 28.0,
+    )
+}
+
+/// Close the popups that share the texture report's spot over the viewport
+fn close_level_popups(state: &mut EditorState) {
+    state.texture_report = None;
+    state.texture_replace = None;
+    state.stairs = None;
+    state.show_portal_suggestions = false;
+    state.hovered_portal_suggestion = None;
+    state.level_diff = None;
+    state.find_faces = None;
+    state.show_pack_load_report = false;
+}
+
+/// Draw the Suggest Portals popup: rooms touching on a wall plane without a portal
+/// between them, each with Accept (or a "blocked" note when solid walls cover the
+/// shared face), and Accept All. Hovering a row highlights its rooms in the grid view.
+fn draw_portal_suggestions(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let suggestions = state.level.suggest_portals();
+    let dim = theme.text_dim;
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text("Suggest Portals", (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, dim);
+    let open = suggestions.iter().filter(|s| !s.blocked).count();
+    draw_text(
+        &format!("{} touching room pair(s) without portals, {} blocked", suggestions.len(), suggestions.len() - open),
+        (rect.x + 6.0).floor(), (rect.y + 34.0).floor(), 13.0, WHITE,
+    );
+
+    // One row per suggestion, scrolled a whole row at a time
+    let list_rect = Rect::new(rect.x + 4.0, rect.y + 44.0, rect.w - 8.0, REPORT_ROWS as f32 * REPORT_ROW_HEIGHT);
+    let max_scroll = suggestions.len().saturating_sub(REPORT_ROWS) as f32;
+    if ctx.mouse.inside(&list_rect) {
+        state.portal_suggestions_scroll -= ctx.mouse.scroll.signum();
+    }
+    state.portal_suggestions_scroll = state.portal_suggestions_scroll.clamp(0.0, max_scroll);
+    let first = state.portal_suggestions_scroll as usize;
+
+    let mut hovered = None;
+    let mut accept = None;
+    for (row, (i, s)) in suggestions.iter().enumerate().skip(first).take(REPORT_ROWS).enumerate() {
+        let row_rect = Rect::new(list_rect.x, list_rect.y + row as f32 * REPORT_ROW_HEIGHT, list_rect.w, REPORT_ROW_HEIGHT);
+        if ctx.mouse.inside(&row_rect) {
+            hovered = Some(s.clone());
+            draw_rectangle(row_rect.x, row_rect.y, row_rect.w, row_rect.h, Color::from_rgba(50, 50, 60, 255));
+        }
+        let side = if s.direction == crate::world::Direction::East { "X" } else { "Z" };
+        let color = if s.blocked { dim } else { WHITE };
+        draw_text(
+            &format!("Room {} - Room {}  ({} side)", s.room_a, s.room_b, side),
+            (row_rect.x + 4.0).floor(), (row_rect.y + 13.0).floor(), 13.0, color,
+        );
+        let button = Rect::new(row_rect.right() - 58.0, row_rect.y + 1.0, 56.0, row_rect.h - 2.0);
+        if s.blocked {
+            draw_text("blocked", (button.x + 6.0).floor(), (row_rect.y + 13.0).floor(), 13.0, theme.warning);
+            if ctx.mouse.inside(&row_rect) {
+                ctx.set_tooltip("Solid walls cover the whole shared face", ctx.mouse.x, ctx.mouse.y);
+            }
+        } else if draw_text_button(ctx, button, "Accept", "Create the portal pair for these rooms (undoable)") {
+            accept = Some(i);
+        }
+    }
+    state.hovered_portal_suggestion = hovered;
+
+    let button_y = rect.bottom() - 24.0;
+    let all_rect = Rect::new(rect.x + 6.0, button_y, 90.0, 18.0);
+    if open > 0 && draw_text_button(ctx, all_rect, "Accept All", "Create every unblocked portal pair as one undo step") {
+        state.accept_portal_suggestions(&suggestions);
+    } else if let Some(i) = accept {
+        state.accept_portal_suggestions(std::slice::from_ref(&suggestions[i]));
+    }
+    let close_rect = Rect::new(rect.right() - 56.0, button_y, 50.0, 18.0);
+    if draw_text_button(ctx, close_rect, "Close", "Close the portal suggestions") {
+        close_level_popups(state);
+    }
+}
+
+/// Draw the Compare with File popup: one row per difference between the file and
+/// the level being edited. Clicking a row selects its room or sector.
+fn draw_level_diff(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let Some(view) = &state.level_diff else { return };
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text(&format!("Compare with {}", view.file_name), (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, theme.text_dim);
+    let summary = if view.diff.is_empty() {
+        "No differences".to_string()
+    } else {
+        format!("{} difference(s), file -> current level", view.diff.len())
+    };
+    draw_text(&summary, (rect.x + 6.0).floor(), (rect.y + 34.0).floor(), 13.0, WHITE);
+
+    let list_rect = Rect::new(rect.x + 4.0, rect.y + 44.0, rect.w - 8.0, REPORT_ROWS as f32 * REPORT_ROW_HEIGHT);
+    let items: Vec<String> = view.diff.entries.iter().map(|e| e.to_string()).collect();
+    let mut scroll = view.scroll;
+    let result = draw_scrollable_list(ctx, list_rect, &items, None, &mut scroll, REPORT_ROW_HEIGHT, None);
+    let clicked = result.clicked.and_then(|i| view.diff.entries.get(i).cloned());
+    if let Some(view) = &mut state.level_diff {
+        view.scroll = scroll;
+    }
+    if let Some(entry) = clicked {
+        state.select_diff_entry(&entry);
+    }
+
+    let close_rect = Rect::new(rect.right() - 56.0, rect.bottom() - 24.0, 50.0, 18.0);
+    if draw_text_button(ctx, close_rect, "Close", "Close the comparison") {
+        state.level_diff = None;
+    }
+}
+
+/// Draw the texture usage report: summary, scrollable list of missing textures,
+/// and the cleanup / close buttons
+fn draw_texture_report(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let Some(report) = &state.texture_report else { return };
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text("Texture Report", (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, theme.text_dim);
+
+    let summary_color = if report.missing_refs > 0 {
+        theme.warning
+    } else {
+        theme.info
+    };
+    draw_text(
+        &format!("{} textures used, {} untextured faces", report.distinct_used, report.untextured),
+        (rect.x + 6.0).floor(), (rect.y + 34.0).floor(), 13.0, WHITE,
+    );
+    draw_text(
+        &format!("{} references to {} missing textures", report.missing_refs, report.missing.len()),
+        (rect.x + 6.0).floor(), (rect.y + 50.0).floor(), 13.0, summary_color,
+    );
+
+    // Missing textures, by name
+    let list_rect = Rect::new(rect.x + 4.0, rect.y + 58.0, rect.w - 8.0, REPORT_ROWS as f32 * REPORT_ROW_HEIGHT);
+    let items: Vec<String> = report.missing.iter()
+        .map(|(name, count)| format!("{}  ({} faces)", name, count))
+        .collect();
+    let has_missing = report.missing_refs > 0;
+    draw_scrollable_list(ctx, list_rect, &items, None, &mut state.texture_report_scroll, REPORT_ROW_HEIGHT, None);
+
+    let button_y = rect.bottom() - 24.0;
+    if has_missing {
+        let strip_rect = Rect::new(rect.x + 6.0, button_y, 110.0, 18.0);
+        if draw_text_button(ctx, strip_rect, "Strip missing", "Point every missing texture reference at no texture (undoable)") {
+            state.strip_missing_textures();
+        }
+    }
+    let close_rect = Rect::new(rect.right() - 56.0, button_y, 50.0, 18.0);
+    if draw_text_button(ctx, close_rect, "Close", "Close the texture report") {
+        state.texture_report = None;
+    }
+}
+
+/// Draw the texture pack loading details: per-pack counts, the textures that failed
+/// to load and why, and manifest warnings
+fn draw_pack_load_report(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let Some(report) = &state.pack_load_report else {
+        state.show_pack_load_report = false;
+        return;
+    };
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text("Texture Pack Loading", (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, theme.text_dim);
+    draw_text(&report.summary(), (rect.x + 6.0).floor(), (rect.y + 34.0).floor(), 13.0, theme.warning);
+
+    let list_rect = Rect::new(rect.x + 4.0, rect.y + 44.0, rect.w - 8.0, REPORT_ROWS as f32 * REPORT_ROW_HEIGHT);
+    let items = report.detail_lines();
+    draw_scrollable_list(ctx, list_rect, &items, None, &mut state.pack_load_report_scroll, REPORT_ROW_HEIGHT, None);
+
+    let close_rect = Rect::new(rect.right() - 56.0, rect.bottom() - 24.0, 50.0, 18.0);
+    if draw_text_button(ctx, close_rect, "Close", "Close the loading details") {
+        state.show_pack_load_report = false;
+    }
+}
+
+/// Draw the Replace Texture popup: scope, textures used in scope, the palette
+/// selection as the replacement, and a preview of how many faces will change
+fn draw_texture_replace(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let Some(replace) = state.texture_replace.clone() else { return };
+    let dim = theme.text_dim;
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text("Replace Texture", (rect.x + 6.0).floor(), (rect.y + 16.0).floor(), 14.0, dim);
+
+    // Scope
+    let mut scope = replace.scope;
+    let scope_w = 70.0;
+    for (i, option) in ReplaceScope::ALL.iter().enumerate() {
+        let button = Rect::new(rect.x + 6.0 + i as f32 * (scope_w + 4.0), rect.y + 24.0, scope_w, 18.0);
+        if draw_text_button(ctx, button, option.label(), "Faces to replace in") {
+            scope = *option;
+        }
+        if *option == scope {
+            draw_rectangle_lines(button.x.floor(), button.y.floor(), button.w, button.h, 1.0, crate::ui::ACCENT_COLOR);
+        }
+    }
+
+    // Textures used in scope
+    let usage = state.level.texture_usage(&state.texture_scope(scope));
+    let list_rect = Rect::new(rect.x + 4.0, rect.y + 46.0, rect.w - 8.0, (REPORT_ROWS - 2) as f32 * REPORT_ROW_HEIGHT);
+    let items: Vec<String> = usage.iter()
+        .map(|(tex, count)| {
+            let name = if tex.is_valid() { format!("{}/{}", tex.pack, tex.name) } else { "(none)".to_string() };
+            format!("{}  ({} faces)", name, count)
+        })
+        .collect();
+    let selected = replace.from.as_ref().and_then(|from| usage.iter().position(|(t, _)| t == from));
+    let mut scroll = replace.scroll;
+    let result = draw_scrollable_list(ctx, list_rect, &items, selected, &mut scroll, REPORT_ROW_HEIGHT, None);
+    let from = match result.clicked {
+        Some(i) => Some(usage[i].0.clone()),
+        None => replace.from.clone(),
+    };
+
+    // Replacement and preview
+    let to = &state.selected_texture;
+    let text_y = list_rect.bottom() + 14.0;
+    draw_text(&format!("With: {}/{} (palette)", to.pack, to.name), (rect.x + 6.0).floor(), text_y.floor(), 13.0, WHITE);
+    let changes = from.as_ref()
+        .filter(|from| *from != to)
+        .and_then(|from| usage.iter().find(|(t, _)| t == from))
+        .map_or(0, |(_, count)| *count);
+    let preview = match &from {
+        Some(_) => format!("{} face(s) will change", changes),
+        None => "Pick a texture to replace".to_string(),
+    };
+    draw_text(&preview, (rect.x + 6.0).floor(), (text_y + 16.0).floor(), 13.0, dim);
+
+    state.texture_replace = Some(TextureReplace { from, scope, scroll });
+
+    let button_y = rect.bottom() - 24.0;
+    let apply_rect = Rect::new(rect.x + 6.0, button_y, 70.0, 18.0);
+    if changes > 0 && draw_text_button(ctx, apply_rect, "Replace", "Replace the texture on these faces (undoable)") {
+        state.apply_texture_replace();
+    }
+    let close_rect = Rect::new(rect.right() - 56.0, button_y, 50.0, 18.0);
+    if draw_text_button(ctx, close_rect, "Close", "Close the Replace Texture popup") {
+        state.texture_replace = None;
+    }
+}
+
+/// Drag value IDs of the Find popup's floor height range (plus 0 = min, 1 = max)
+const FIND_HEIGHT_FIELD: u64 = 0x4649_4e00;
+
+/// Draw the Find popup: criteria toggles (all set ones must match), the floor
+/// height range, and the faces found. Clicking a result or stepping with the
+/// arrow keys selects its face.
+fn draw_find_faces(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    state.step_find();
+    let Some(mut find) = state.find_faces.take() else { return };
+    let x = rect.x + 6.0;
+    let row_w = rect.w - 12.0;
+    let half_w = (row_w - 4.0) / 2.0;
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text("Find Faces", x.floor(), (rect.y + 16.0).floor(), 14.0, theme.text_dim);
+
+    let toggle = |ctx: &mut UiContext, button: Rect, label: &str, tooltip: &str, on: bool| -> bool {
+        let clicked = draw_text_button(ctx, button, label, tooltip);
+        if on {
+            draw_rectangle_lines(button.x.floor(), button.y.floor(), button.w, button.h, 1.0, theme.accent);
+        }
+        clicked
+    };
+
+    // Texture: the palette selection when turned on
+    let query = &mut find.query;
+    let palette = &state.selected_texture;
+    let texture_label = match &query.texture {
+        Some(tex) => format!("Texture: {}/{}", tex.pack, tex.name),
+        None => format!("Texture (palette: {}/{})", palette.pack, palette.name),
+    };
+    let mut y = rect.y + 24.0;
+    if toggle(ctx, Rect::new(x, y, row_w, 18.0), &texture_label, "Faces using the palette's texture", query.texture.is_some()) {
+        query.texture = match query.texture {
+            Some(_) => None,
+            None => Some(palette.clone()),
+        };
+    }
+    y += 22.0;
+    let flags: [[(&str, &str, &mut bool); 2]; 2] = [
+        [
+            ("Not walkable", "Floors and ceilings that can't be walked on", &mut query.not_walkable),
+            ("Blended", "Faces with a blend mode other than Opaque", &mut query.blended),
+        ],
+        [
+            ("Triggers", "Faces of sectors with a pickup or a door", &mut query.triggers),
+            ("Custom UVs", "Faces with their own UVs", &mut query.custom_uv),
+        ],
+    ];
+    for row in flags {
+        for (column, (label, tooltip, value)) in row.into_iter().enumerate() {
+            let button = Rect::new(x + column as f32 * (half_w + 4.0), y, half_w, 18.0);
+            if toggle(ctx, button, label, tooltip, *value) {
+                *value = !*value;
+            }
+        }
+        y += 22.0;
+    }
+
+    // Floor height range
+    if toggle(ctx, Rect::new(x, y, row_w, 18.0), "Floor height in range", "Floors with a corner between Min and Max (world height)", query.floor_height.is_some()) {
+        query.floor_height = match query.floor_height {
+            Some(_) => None,
+            None => Some(find.height_range),
+        };
+    }
+    y += 22.0;
+    let (min, max) = find.height_range;
+    for (i, (label, value)) in [("Min", min), ("Max", max)].into_iter().enumerate() {
+        let spec = DragValue { tooltip: "Floor height range", unit: Some(CLICKS), snap: true, ..DragValue::new(label, CLICK_HEIGHT) };
+        let field = Rect::new(x + i as f32 * (half_w + 4.0), y, half_w, 18.0);
+        if let Some(change) = drag_value(ctx, field, FIND_HEIGHT_FIELD + i as u64, value, &spec) {
+            if i == 0 {
+                find.height_range.0 = change.value;
+            } else {
+                find.height_range.1 = change.value;
+            }
+        }
+    }
+    if find.query.floor_height.is_some() {
+        let (min, max) = find.height_range;
+        find.query.floor_height = Some((min.min(max), min.max(max)));
+    }
+    y += 24.0;
+
+    // Results (arrow keys step through them, keeping the current one in view)
+    let list_rect = Rect::new(rect.x + 4.0, y, rect.w - 8.0, rect.bottom() - 30.0 - y);
+    let step = if ctx.text_input_focused() {
+        0
+    } else {
+        is_key_pressed(KeyCode::Down) as isize - is_key_pressed(KeyCode::Up) as isize
+    };
+    let result = draw_scrollable_list(ctx, list_rect, &find.labels, find.current, &mut find.scroll, REPORT_ROW_HEIGHT, None);
+
+    let button_y = rect.bottom() - 24.0;
+    let find_rect = Rect::new(x, button_y, 50.0, 18.0);
+    let run = draw_text_button(ctx, find_rect, "Find", "Search the level for faces matching every criterion that is on");
+    let summary = if find.scanning() {
+        format!("Searching... {} so far", find.results.len())
+    } else if let Some(current) = find.current {
+        format!("{} / {} face(s)", current + 1, find.results.len())
+    } else {
+        format!("{} face(s)", find.results.len())
+    };
+    draw_text(&summary, (find_rect.right() + 8.0).floor(), (button_y + 13.0).floor(), 13.0, theme.text_secondary);
+    let close_rect = Rect::new(rect.right() - 56.0, button_y, 50.0, 18.0);
+    let close = draw_text_button(ctx, close_rect, "Close", "Close the Find popup");
+
+    state.find_faces = Some(find);
+    if close {
+        state.find_faces = None;
+    } else if run {
+        state.run_find();
+    } else if let Some(index) = result.clicked {
+        state.select_find_result(index);
+    } else if step != 0 {
+        state.step_find_result(step);
+        if let Some(find) = &mut state.find_faces {
+            let top = find.current.unwrap_or(0) as f32 * REPORT_ROW_HEIGHT;
+            find.scroll = find.scroll.clamp(top + REPORT_ROW_HEIGHT - list_rect.h, top).max(0.0);
+        }
+    }
+}
+
+/// Draw the Stairs Generator popup: step height, side walls / ceiling / ramp
+/// toggles, and Generate for the two selected sectors
+fn draw_stairs_popup(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let Some(mut options) = state.stairs else { return };
+    let dim = theme.text_dim;
+    let x = rect.x + 6.0;
+    let row_w = rect.w - 12.0;
+
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.popup_bg);
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.popup_border);
+    draw_text("Stairs Generator", x.floor(), (rect.y + 16.0).floor(), 14.0, dim);
+
+    let lines = [
+        "Click the start sector, Shift+click the end sector",
+        "(same row or column). An end floor at another",
+        "height is bridged; otherwise the stairs climb",
+        "by the step height. Uses the palette texture.",
+    ];
+    let mut y = rect.y + 36.0;
+    for line in lines {
+        draw_text(line, x.floor(), y.floor(), 13.0, theme.text_secondary);
+        y += 15.0;
+    }
+    y += 6.0;
+
+    let step_label = format!("Step: {} click{}", options.step_clicks, if options.step_clicks == 1 { "" } else { "s" });
+    if draw_text_button(ctx, Rect::new(x, y, row_w, 18.0), &step_label, "Click to cycle the rise per step (1-4 clicks)") {
+        options.step_clicks = options.step_clicks % MAX_STEP_CLICKS + 1;
+    }
+    y += 22.0;
+    let toggles: [(&str, &str, &mut bool); 3] = [
+        ("Side walls", "Walls along both sides, floor to ceiling", &mut options.side_walls),
+        ("Sloped ceiling", "A ceiling following the stairs, at the start sector's headroom", &mut options.ceiling),
+        ("Smooth ramp", "Sloped floors instead of steps", &mut options.ramp),
+    ];
+    for (label, tooltip, value) in toggles {
+        let text = format!("{}: {}", label, if *value { "ON" } else { "OFF" });
+        let button = Rect::new(x, y, row_w, 18.0);
+        if draw_text_button(ctx, button, &text, tooltip) {
+            *value = !*value;
+        }
+        if *value {
+            draw_rectangle_lines(button.x.floor(), button.y.floor(), button.w, button.h, 1.0, theme.accent);
+        }
+        y += 22.0;
+    }
+    state.stairs = Some(options);
+
+    let button_y = rect.bottom() - 24.0;
+    let apply_rect = Rect::new(x, button_y, 70.0, 18.0);
+    if draw_text_button(ctx, apply_rect, "Generate", "Build the stairs between the selected sectors (undoable)") {
+        state.apply_stairs();
+    }
+    let close_rect = Rect::new(rect.right() - 56.0, button_y, 50.0, 18.0);
+    if draw_text_button(ctx, close_rect, "Close", "Close the Stairs Generator") {
+        state.stairs = None;
+    }
+}
+
+/// Container configuration
+const CONTAINER_PADDING: f32 = 8.0;
+const CONTAINER_MARGIN: f32 = 6.0;
+
+/// Draw a container box with a colored header
+fn draw_container_start(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    header_text: &str,
+    header_color: Color,
+) {
+    let header_height = 22.0;
+
+    // Container background
+    draw_rectangle(
+        x.floor(), y.floor(),
+        width, height,
+        Color::from_rgba(30, 30, 35, 255)
+    );
+
+    // Container border
+    draw_rectangle_lines(
+        x.floor(), y.floor(),
+        width, height,
+        1.0,
+        Color::from_rgba(60, 60, 70, 255)
+    );
+
+    // Header background
+    draw_rectangle(
+        x.floor(), y.floor(),
+        width, header_height,
+        RasterColor::from(header_color).scale_rgb(0.25).with_alpha_f32(0.8).to_mq()
+    );
+
+    // Header text
+    draw_text(header_text, (x + CONTAINER_PADDING).floor(), (y + 15.0).floor(), 14.0, header_color);
+}
+
+/// Height of the UV preview row in the face containers
+const UV_ROW_HEIGHT: f32 = UV_PREVIEW_SIZE + 4.0;
+
+/// The face a UV preview row shows
+struct FaceUvRow<'a> {
+    /// Room index, sector grid coordinates and face
+    target: (usize, usize, usize, super::SectorFace),
+    uvs: [crate::rasterizer::Vec2; 4],
+    /// Face has its own UVs (not the default mapping)
+    custom: bool,
+    texture: &'a crate::world::TextureRef,
+}
+
+/// Draw a face's UV preview row and write back any edit it makes
+fn draw_face_uv_row(ctx: &mut UiContext, x: f32, y: f32, row: FaceUvRow, state: &mut EditorState) {
+    let FaceUvRow { target, uvs, custom, texture } = row;
+    let rect = Rect::new(x, y, UV_PREVIEW_SIZE, UV_PREVIEW_SIZE);
+    if let Some(edit) = draw_uv_preview(ctx, rect, target, uvs, texture, state) {
+        let (room_idx, gx, gz, face) = target;
+        state.apply_uv_edit(room_idx, gx, gz, face, edit);
+    }
+    let hint_x = (x + UV_PREVIEW_SIZE + 6.0).floor();
+    let hint_color = Color::from_rgba(140, 140, 140, 255);
+    draw_text(if custom { "UV: custom" } else { "UV: default" }, hint_x, (y + 12.0).floor(), 13.0, WHITE);
+    for (i, hint) in ["Drag: pan", "Corner: move", "Right-click: reset"].iter().enumerate() {
+        draw_text(hint, hint_x, (y + 30.0 + i as f32 * 14.0).floor(), 12.0, hint_color);
+    }
+}
+
+/// Calculate height needed for a horizontal face container
+fn horizontal_face_container_height(face: &crate::world::HorizontalFace) -> f32 {
+    let line_height = 18.0;
+    let header_height = 22.0;
+    let mut lines = 5; // texture, height, walkable, blend, opening
+    if !face.is_flat() {
+        lines += 1; // extra line for individual heights
+    }
+    header_height + CONTAINER_PADDING * 2.0 + (lines as f32) * line_height + UV_ROW_HEIGHT
+}
+
+/// Calculate height needed for a wall face container
+fn wall_face_container_height(_wall: &crate::world::VerticalFace, is_door: bool) -> f32 {
+    let line_height = 18.0;
+    let header_height = 22.0;
+    let mut lines = 7; // texture, y range, 2 corner rows, adapt, blend, make door
+    if is_door {
+        lines += 1; // door open height + timing rows instead of the button
+    } else {
+        lines += 2; // doorway opening width and height
+    }
+    header_height + CONTAINER_PADDING * 2.0 + (lines as f32) * line_height + UV_ROW_HEIGHT
+}
+
+/// Edge and current index in its sector of a wall face (None if the wall is gone)
+fn wall_index(state: &EditorState, room_idx: usize, gx: usize, gz: usize, face: super::SectorFace) -> Option<(crate::world::Direction, usize)> {
+    face.wall_index_in(state.level.rooms.get(room_idx)?.get_sector(gx, gz)?)
+}
+
+/// Door index (in its room) for a wall face, if the wall is a door
+fn wall_door(state: &EditorState, room_idx: usize, gx: usize, gz: usize, face: super::SectorFace) -> Option<usize> {
+    let (dir, id) = face.wall()?;
+    state.level.rooms.get(room_idx)?.door_at(gx, gz, dir, id)
+}
+
+/// Width of the AO strength drag field in the toolbar
+const AO_STRENGTH_FIELD_WIDTH: f32 = 64.0;
+
+/// Draw the AO strength drag value (drag horizontally, 1% per pixel)
+fn draw_ao_strength_field(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let hovered = ctx.mouse.inside(&rect);
+    if hovered {
+        ctx.set_tooltip("AO strength (drag left/right)", ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.ao_strength_drag = Some((ctx.mouse.x, state.ao_strength));
+        }
+    }
+    if let Some((anchor_x, start)) = state.ao_strength_drag {
+        if ctx.mouse.left_down {
+            state.ao_strength = (start + (ctx.mouse.x - anchor_x) * 0.01).clamp(0.0, 1.0);
+        } else {
+            state.ao_strength_drag = None;
+        }
+    }
+
+    let active = hovered || state.ao_strength_drag.is_some();
+    let bg = if active { theme.button_hover } else { theme.button_bg };
+    draw_rectangle(rect.x, rect.y + 4.0, rect.w, rect.h - 8.0, bg);
+    // Fill proportional to strength
+    draw_rectangle(rect.x, rect.y + 4.0, rect.w * state.ao_strength, rect.h - 8.0, theme.fill);
+    let label = format!("AO {:.0}%", state.ao_strength * 100.0);
+    let dims = measure_text(&label, None, 13, 1.0);
+    draw_text(&label, (rect.x + (rect.w - dims.width) * 0.5).floor(), (rect.y + (rect.h + dims.height) * 0.5).floor(), 13.0, WHITE);
+}
+
+/// Width of the viewport FOV drag field in the toolbar
+const FOV_FIELD_WIDTH: f32 = 64.0;
+
+/// Game FOV adjustment step (degrees)
+const GAME_FOV_STEP: f32 = 5.0;
+
+/// Draw the 3D viewport FOV drag value (drag horizontally, 0.5 degrees per pixel).
+/// Saved in editor prefs when the drag ends.
+fn draw_viewport_fov_field(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    use crate::rasterizer::{DEFAULT_FOV_DEGREES, MAX_FOV_DEGREES, MIN_FOV_DEGREES};
+
+    let hovered = ctx.mouse.inside(&rect);
+    if hovered {
+        ctx.set_tooltip("3D viewport field of view (drag left/right, right-click to reset)", ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.fov_drag = Some((ctx.mouse.x, state.camera_3d.fov_degrees));
+        }
+        if is_mouse_button_pressed(MouseButton::Right) {
+            state.camera_3d.fov_degrees = DEFAULT_FOV_DEGREES;
+            state.save_prefs();
+        }
+    }
+    if let Some((anchor_x, start)) = state.fov_drag {
+        if ctx.mouse.left_down {
+            state.camera_3d.fov_degrees = (start + (ctx.mouse.x - anchor_x) * 0.5).round()
+                .clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES);
+        } else {
+            state.fov_drag = None;
+            state.save_prefs();
+        }
+    }
+
+    let fov = state.camera_3d.fov_degrees;
+    let t = (fov - MIN_FOV_DEGREES) / (MAX_FOV_DEGREES - MIN_FOV_DEGREES);
+    let active = hovered || state.fov_drag.is_some();
+    let bg = if active { theme.button_hover } else { theme.button_bg };
+    draw_rectangle(rect.x, rect.y + 4.0, rect.w, rect.h - 8.0, bg);
+    draw_rectangle(rect.x, rect.y + 4.0, rect.w * t, rect.h - 8.0, theme.fill);
+    let label = format!("FOV {:.0}", fov);
+    let dims = measure_text(&label, None, 13, 1.0);
+    draw_text(&label, (rect.x + (rect.w - dims.width) * 0.5).floor(), (rect.y + (rect.h + dims.height) * 0.5).floor(), 13.0, WHITE);
+}
+
+/// Draw the level's game-mode FOV with -/+ buttons. Returns the new y.
+fn draw_game_fov_row(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) -> f32 {
+    use crate::rasterizer::{MAX_FOV_DEGREES, MIN_FOV_DEGREES};
+
+    let fov = state.level.game_fov;
+    draw_text(&format!("Game FOV: {:.0}", fov), (rect.x + 4.0).floor(), (rect.y + 13.0).floor(), 14.0, WHITE);
+
+    let button_w = 18.0;
+    let minus = Rect::new(rect.right() - button_w * 2.0 - 2.0, rect.y, button_w, rect.h);
+    let plus = Rect::new(rect.right() - button_w, rect.y, button_w, rect.h);
+    let mut step = 0.0;
+    if draw_text_button(ctx, minus, "-", "Narrower field of view in game mode") {
+        step = -GAME_FOV_STEP;
+    }
+    if draw_text_button(ctx, plus, "+", "Wider field of view in game mode") {
+        step = GAME_FOV_STEP;
+    }
+    let new_fov = (fov + step).clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES);
+    if new_fov != fov {
+        state.save_undo("Change game FOV");
+        state.level.game_fov = new_fov;
+    }
+
+    rect.y + rect.h + 2.0
+}
+
+/// Face height step for dragging and PageUp/PageDown (quarter click)
+const HEIGHT_STEP: f32 = CLICK_HEIGHT / 4.0;
+
+/// Mouse travel (pixels) per step when dragging a height field
+const HEIGHT_DRAG_PIXELS: f32 = 6.0;
+
+/// Heights are shown and typed in clicks as well as world units
+const CLICKS: ValueUnit = ValueUnit { size: CLICK_HEIGHT, suffix: 'c', name: "clk" };
+
+/// Room X/Z positions are shown and typed in sectors as well as world units
+const SECTORS: ValueUnit = ValueUnit { size: SECTOR_SIZE, suffix: 's', name: "sec" };
+
+/// Drag value IDs of a face's height fields (plus `face_field_index`)
+const FACE_BASE_FIELD: u64 = 0x4241_5345_0000;
+const WALL_BOTTOM_FIELD: u64 = 0x5742_4f54_0000;
+const WALL_TOP_FIELD: u64 = 0x5754_4f50_0000;
+
+/// Distinct number for each face of a sector, to tell their fields apart
+fn face_field_index(face: super::SectorFace) -> u64 {
+    use super::SectorFace::*;
+    match face {
+        Floor => 0,
+        Ceiling => 1,
+        WallNorth(id) => 2 + id.0 as u64 * 4,
+        WallEast(id) => 3 + id.0 as u64 * 4,
+        WallSouth(id) => 4 + id.0 as u64 * 4,
+        WallWest(id) => 5 + id.0 as u64 * 4,
+    }
+}
+
+/// Short names for VerticalFace::heights indices
+const WALL_CORNER_NAMES: [&str; 4] = ["BL", "BR", "TR", "TL"];
+
+/// Door open/close time adjustment step (seconds)
+const DOOR_TIME_STEP: f32 = 0.25;
+
+/// Cut Doorway opening width step (an eighth of the edge)
+const DOORWAY_WIDTH_STEP: f32 = SECTOR_SIZE / 8.0;
+
+/// Draw a wall corner height field. Clicking selects the corner, dragging up/down changes
+/// its height in quarter clicks (one undo step per drag). Returns the new height while dragging.
+fn draw_wall_corner_field(
+    ctx: &mut UiContext,
+    rect: Rect,
+    (room_idx, gx, gz, face): (usize, usize, usize, super::SectorFace),
+    corner: usize,
+    height: f32,
+    state: &mut EditorState,
+) -> Option<f32> {
+    let theme = ctx.theme;
+    let highlighted = state.selected_wall_corner == Some(corner) && matches!(state.selection,
+        super::Selection::SectorFace { room, x, z, face: f } if room == room_idx && x == gx && z == gz && f == face);
+    let hovered = ctx.mouse.inside(&rect);
+    let dragging = state.wall_corner_drag.filter(|d| {
+        d.room == room_idx && d.x == gx && d.z == gz && d.face == face && d.corner == corner
+    });
+
+    let bg = if dragging.is_some() {
+        theme.button_active
+    } else if highlighted {
+        theme.selection_bg
+    } else if hovered {
+        theme.button_hover
+    } else {
+        theme.button_bg
+    };
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, bg);
+    draw_rectangle_lines(rect.x.floor(), rect.y.floor(), rect.w, rect.h, 1.0, theme.border);
+    draw_text(&format!("{} {:.0}", WALL_CORNER_NAMES[corner], height), (rect.x + 4.0).floor(), (rect.y + 12.0).floor(), 13.0, WHITE);
+
+    if hovered {
+        ctx.set_tooltip("Drag to adjust height, PgUp/PgDn to nudge", ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.selected_wall_corner = Some(corner);
+            state.selection = super::Selection::SectorFace { room: room_idx, x: gx, z: gz, face };
+            state.wall_corner_drag = Some(super::WallCornerDrag {
+                room: room_idx,
+                x: gx,
+                z: gz,
+                face,
+                corner,
+                anchor_y: ctx.mouse.y,
+                start_height: height,
+                started: false,
+            });
+            return None;
+        }
+    }
+
+    let drag = dragging?;
+    if !ctx.mouse.left_down {
+        state.wall_corner_drag = None;
+        return None;
+    }
+
+    let steps = ((drag.anchor_y - ctx.mouse.y) / HEIGHT_DRAG_PIXELS).round();
+    let new_height = drag.start_height + steps * HEIGHT_STEP;
+    if new_height == height {
+        return None;
+    }
+    if !drag.started {
+        state.save_undo(&format!("Edit {} corner ({}, {})", WALL_CORNER_NAMES[corner], gx, gz));
+        state.wall_corner_drag = Some(super::WallCornerDrag { started: true, ..drag });
+    }
+    Some(new_height)
+}
+
+/// Set one corner height of a wall (caller records undo)
+fn set_wall_corner_height(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, face: super::SectorFace, corner: usize, height: f32) {
+    let (dir, wall_idx) = match wall_index(state, room_idx, gx, gz, face) {
+        Some(w) => w,
+        None => return,
+    };
+    let door = wall_door(state, room_idx, gx, gz, face);
+    if let Some(room) = state.level.rooms.get_mut(room_idx) {
+        if let Some(wall) = room.get_sector_mut(gx, gz).and_then(|s| s.walls_mut(dir).get_mut(wall_idx)) {
+            wall.heights[corner] = height;
+        }
+        // A door's closed state is the wall as edited
+        if let Some(door_idx) = door {
+            room.doors[door_idx].closed[corner] = height;
+        }
+        room.recalculate_bounds();
+    }
+    state.mark_room_dirty(room_idx);
+}
+
+/// Turn a wall into a door (closed = current heights, open = sunk to its bottom edge)
+fn make_door(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, face: super::SectorFace) {
+    let Some((dir, wall_idx)) = wall_index(state, room_idx, gx, gz, face) else { return };
+    let Some(wall) = state.level.rooms.get(room_idx)
+        .and_then(|r| r.get_sector(gx, gz))
+        .and_then(|s| s.walls(dir).get(wall_idx))
+        .cloned()
+    else {
+        return;
+    };
+    state.save_undo(&format!("Make door ({}, {})", gx, gz));
+    state.level.rooms[room_idx].doors.push(crate::world::Door::from_wall(gx, gz, dir, wall_idx, &wall));
+    state.mark_room_dirty(room_idx);
+    state.set_status("Door created: opens in game mode", 2.0);
+}
+
+/// Split a wall around an opening of `state.doorway_size`, centered on the wall
+fn cut_doorway(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, face: super::SectorFace) {
+    let Some((dir, wall_idx)) = wall_index(state, room_idx, gx, gz, face) else { return };
+    let (width, height) = state.doorway_size;
+    // Cut a copy first, so a refused cut doesn't leave an undo step behind
+    let Some(mut room) = state.level.rooms.get(room_idx).cloned() else { return };
+    match room.cut_doorway(gx, gz, dir, wall_idx, width, height) {
+        Ok(pieces) => {
+            state.save_undo(&format!("Cut doorway ({}, {})", gx, gz));
+            room.recalculate_bounds();
+            state.level.rooms[room_idx] = room;
+            state.mark_room_dirty(room_idx);
+            state.selection = super::Selection::None;
+            state.set_status(&format!("Cut {:.0}x{:.0} doorway ({} pieces)", width, height, pieces), 2.0);
+        }
+        Err(msg) => state.set_warning(&msg),
+    }
+}
+
+/// Opening width and height rows for Cut Doorway
+fn draw_doorway_size_controls(ctx: &mut UiContext, x: f32, y: f32, width: f32, state: &mut EditorState) {
+    let line_height = 18.0;
+    let button_w = 18.0;
+    let rows = [
+        ("Opening width", DOORWAY_WIDTH_STEP, SECTOR_SIZE),
+        ("Opening height", CLICK_HEIGHT, CLICK_HEIGHT * 16.0),
+    ];
+    for (row, (label, step, max)) in rows.into_iter().enumerate() {
+        let y = y + row as f32 * line_height;
+        let value = if row == 0 { &mut state.doorway_size.0 } else { &mut state.doorway_size.1 };
+        draw_text(&format!("{}: {:.0}", label, value), x.floor(), (y + 12.0).floor(), 13.0, WHITE);
+        let minus = Rect::new(x + width - button_w * 2.0 - 2.0, y - 2.0, button_w, 16.0);
+        let plus = Rect::new(x + width - button_w, y - 2.0, button_w, 16.0);
+        if draw_text_button(ctx, minus, "-", "Smaller opening") {
+            *value = (*value - step).max(step);
+        }
+        if draw_text_button(ctx, plus, "+", "Larger opening") {
+            *value = (*value + step).min(max);
+        }
+    }
+}
+
+/// Open height and timing rows for a door wall, plus removing the door
+fn draw_door_controls(ctx: &mut UiContext, x: f32, y: f32, width: f32, state: &mut EditorState, room_idx: usize, door_idx: usize) {
+    let Some(door) = state.level.rooms.get(room_idx).and_then(|r| r.doors.get(door_idx)).cloned() else {
+        return;
+    };
+    let line_height = 18.0;
+    let button_w = 18.0;
+    let open_top = (door.open[2] + door.open[3]) * 0.5;
+
+    // Open height: top edge of the wall when fully open
+    draw_text(&format!("Door open top: {:.0}", open_top), x.floor(), (y + 12.0).floor(), 13.0, WHITE);
+    let minus = Rect::new(x + width - button_w * 2.0 - 2.0, y - 2.0, button_w, 16.0);
+    let plus = Rect::new(x + width - button_w, y - 2.0, button_w, 16.0);
+    let mut open_step = 0.0;
+    if draw_text_button(ctx, minus, "-", "Lower the open position") {
+        open_step = -HEIGHT_STEP;
+    }
+    if draw_text_button(ctx, plus, "+", "Raise the open position") {
+        open_step = HEIGHT_STEP;
+    }
+    if open_step != 0.0 {
+        state.save_undo(&format!("Door open height ({}, {})", door.x, door.z));
+        let door = &mut state.level.rooms[room_idx].doors[door_idx];
+        // Top corners move; never below the bottom corners
+        door.open[2] = (door.open[2] + open_step).max(door.open[1]);
+        door.open[3] = (door.open[3] + open_step).max(door.open[0]);
+        state.mark_room_dirty(room_idx);
+    }
+
+    // Open/close time, and removing the door
+    let y = y + line_height;
+    draw_text(&format!("Time: {:.2}s", door.duration), x.floor(), (y + 12.0).floor(), 13.0, WHITE);
+    let remove = Rect::new(x + width - button_w * 2.0 - 70.0, y - 2.0, 64.0, 16.0);
+    let minus = Rect::new(x + width - button_w * 2.0 - 2.0, y - 2.0, button_w, 16.0);
+    let plus = Rect::new(x + width - button_w, y - 2.0, button_w, 16.0);
+    let mut time_step = 0.0;
+    if draw_text_button(ctx, minus, "-", "Open/close faster") {
+        time_step = -DOOR_TIME_STEP;
+    }
+    if draw_text_button(ctx, plus, "+", "Open/close slower") {
+        time_step = DOOR_TIME_STEP;
+    }
+    if time_step != 0.0 {
+        state.save_undo(&format!("Door time ({}, {})", door.x, door.z));
+        let door = &mut state.level.rooms[room_idx].doors[door_idx];
+        door.duration = (door.duration + time_step).max(DOOR_TIME_STEP);
+        state.mark_room_dirty(room_idx);
+    }
+    if draw_text_button(ctx, remove, "No door", "Make this a plain wall again") {
+        state.save_undo(&format!("Remove door ({}, {})", door.x, door.z));
+        state.level.rooms[room_idx].doors.remove(door_idx);
+        state.mark_room_dirty(room_idx);
+    }
+}
+
+/// Fit a wall to its sector: bottom corners follow the floor edge, top corners the ceiling edge
+fn adapt_wall_to_sector(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, face: super::SectorFace) {
+    let (dir, wall_idx) = match wall_index(state, room_idx, gx, gz, face) {
+        Some(w) => w,
+        None => return,
+    };
+    let edges = state.level.rooms.get(room_idx)
+        .and_then(|r| r.get_sector(gx, gz))
+        .map(|s| (
+            s.floor.as_ref().map(|f| f.edge_heights(dir)),
+            s.ceiling.as_ref().map(|c| c.edge_heights(dir)),
+        ));
+    let (floor_edge, ceiling_edge) = match edges {
+        Some((None, None)) | None => {
+            state.set_warning("Sector has no floor or ceiling to adapt to");
+            return;
+        }
+        Some(edges) => edges,
+    };
+
+    state.save_undo(&format!("Adapt {:?} wall ({}, {})", dir, gx, gz));
+    if let Some(room) = state.level.rooms.get_mut(room_idx) {
+        if let Some(wall) = room.get_sector_mut(gx, gz).and_then(|s| s.walls_mut(dir).get_mut(wall_idx)) {
+            if let Some((left, right)) = floor_edge {
+                wall.heights[0] = left;
+                wall.heights[1] = right;
+            }
+            if let Some((left, right)) = ceiling_edge {
+                wall.heights[2] = right;
+                wall.heights[3] = left;
+            }
+        }
+        room.recalculate_bounds();
+    }
+    state.mark_room_dirty(room_idx);
+    state.set_status("Adapted wall to floor/ceiling", 2.0);
+}
+
+/// Draw a "Blend: <mode>" cycle button. Returns the next mode when clicked.
+fn draw_blend_mode_button(ctx: &mut UiContext, rect: Rect, mode: crate::rasterizer::BlendMode) -> Option<crate::rasterizer::BlendMode> {
+    let theme = ctx.theme;
+    let hovered = ctx.mouse.inside(&rect);
+    let bg = if hovered {
+        theme.button_hover
+    } else {
+        theme.button_bg
+    };
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, bg);
+    draw_rectangle_lines(rect.x.floor(), rect.y.floor(), rect.w, rect.h, 1.0, theme.border);
+
+    let text_color = if mode == crate::rasterizer::BlendMode::Opaque {
+        theme.text_dim
+    } else {
+        WHITE
+    };
+    draw_text(&format!("Blend: {}", mode.label()), (rect.x + 4.0).floor(), (rect.y + 12.0).floor(), 13.0, text_color);
+
+    if hovered {
+        ctx.set_tooltip("Click to cycle PS1 blend mode", ctx.mouse.x, ctx.mouse.y);
+    }
+
+    if ctx.mouse.clicked(&rect) {
+        Some(mode.next())
+    } else {
+        None
+    }
+}
+
+/// Set the blend mode of a face, and of the same face on every other selected sector.
+/// Records a single undo step.
+fn apply_blend_mode(
+    state: &mut EditorState,
+    room_idx: usize,
+    gx: usize,
+    gz: usize,
+    face: super::SectorFace,
+    mode: crate::rasterizer::BlendMode,
+) {
+    // Primary face plus all sectors in the multi-selection
+    let mut targets = vec![(room_idx, gx, gz)];
+    for sel in &state.multi_selection {
+        if let Some(coords) = sel.sector_coords() {
+            if !targets.contains(&coords) {
+                targets.push(coords);
+            }
+        }
+    }
+
+    // Other sectors' walls by their place on the edge (ids are per wall)
+    let wall_slot = wall_index(state, room_idx, gx, gz, face);
+
+    let label = format!("Set blend mode {} on {} face(s)", mode.label(), targets.len());
+    state.save_undo(&label);
+
+    let mut count = 0;
+    for (r, x, z) in targets {
+        state.mark_room_dirty(r);
+        let sector = match state.level.rooms.get_mut(r).and_then(|room| room.get_sector_mut(x, z)) {
+            Some(s) => s,
+            None => continue,
+        };
+        let blend = match face {
+            super::SectorFace::Floor => sector.floor.as_mut().map(|f| &mut f.blend_mode),
+            super::SectorFace::Ceiling => sector.ceiling.as_mut().map(|c| &mut c.blend_mode),
+            _ => wall_slot.and_then(|(dir, i)| sector.walls_mut(dir).get_mut(i)).map(|w| &mut w.blend_mode),
+        };
+        if let Some(blend) = blend {
+            *blend = mode;
+            count += 1;
+        }
+    }
+
+    if count > 1 {
+        state.set_status(&format!("Blend mode: {} ({} faces)", mode.label(), count), 2.0);
+    } else {
+        state.set_status(&format!("Blend mode: {}", mode.label()), 2.0);
+    }
+}
+
+/// Per-face flag toggled from the properties panel
+#[derive(Clone, Copy)]
+enum FaceFlag {
+    /// Drawn in game
+    Visible,
+    /// Solid wall (walls only)
+    Collides,
+}
+
+/// Toggle a flag of one face (one undo step)
+fn toggle_face_flag(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, face: super::SectorFace, flag: FaceFlag) {
+    let current = state.level.rooms.get(room_idx).and_then(|r| r.get_sector(gx, gz)).and_then(|sector| match face {
+        super::SectorFace::Floor => sector.floor.as_ref().map(|f| f.visible),
+        super::SectorFace::Ceiling => sector.ceiling.as_ref().map(|c| c.visible),
+        wall => wall.wall_in(sector).map(|(_, w)| match flag {
+            FaceFlag::Visible => w.visible,
+            FaceFlag::Collides => w.solid,
+        }),
+    });
+    let Some(current) = current else { return };
+    let label = match (flag, current) {
+        (FaceFlag::Visible, true) => "Hide face",
+        (FaceFlag::Visible, false) => "Show face",
+        (FaceFlag::Collides, true) => "Make wall non-solid",
+        (FaceFlag::Collides, false) => "Make wall solid",
+    };
+    state.save_undo(&format!("{} ({}, {})", label, gx, gz));
+    state.mark_room_dirty(room_idx);
+    let Some(sector) = state.level.rooms.get_mut(room_idx).and_then(|r| r.get_sector_mut(gx, gz)) else { return };
+    let target = match face {
+        super::SectorFace::Floor => sector.floor.as_mut().map(|f| &mut f.visible),
+        super::SectorFace::Ceiling => sector.ceiling.as_mut().map(|c| &mut c.visible),
+        wall => wall.wall_in_mut(sector).map(|(_, w)| match flag {
+            FaceFlag::Visible => &mut w.visible,
+            FaceFlag::Collides => &mut w.solid,
+        }),
+    };
+    if let Some(target) = target {
+        *target = !current;
+    }
+}
+
+/// Height of a row in the floor material dropdown
+const MATERIAL_MENU_ROW: f32 = 16.0;
+
+/// Draw a "Material: <name>" dropdown button for a floor; a click opens (or closes)
+/// the material list below it
+fn draw_material_button(ctx: &mut UiContext, rect: Rect, target: (usize, usize, usize), material: crate::world::SurfaceMaterial, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let (room, x, z) = target;
+    let open = state.material_menu.is_some_and(|m| (m.room, m.x, m.z) == target);
+    let hovered = ctx.mouse.inside(&rect);
+    let bg = if open {
+        theme.button_active
+    } else if hovered {
+        theme.button_hover
+    } else {
+        theme.button_bg
+    };
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, bg);
+    draw_rectangle_lines(rect.x.floor(), rect.y.floor(), rect.w, rect.h, 1.0, theme.border);
+
+    let text_color = if material == crate::world::SurfaceMaterial::Stone {
+        theme.text_dim
+    } else {
+        WHITE
+    };
+    draw_text(&format!("Material: {}", material.label()), (rect.x + 4.0).floor(), (rect.y + 12.0).floor(), 13.0, text_color);
+    draw_text("v", (rect.right() - 10.0).floor(), (rect.y + 12.0).floor(), 13.0, theme.text_dim);
+
+    if hovered {
+        ctx.set_tooltip("Floor material (footstep sounds)", ctx.mouse.x, ctx.mouse.y);
+    }
+
+    if ctx.mouse.clicked(&rect) {
+        state.material_menu = (!open).then_some(super::MaterialMenu { room, x, z, anchor: rect });
+    } else if open {
+        // Keep the list under the button as the panel scrolls
+        state.material_menu = Some(super::MaterialMenu { room, x, z, anchor: rect });
+    }
+}
+
+/// Materials the dropdown lists: the built-in ones, the Custom ones already used in
+/// the level, and the next unused Custom id (flagged new)
+fn material_menu_options(level: &crate::world::Level) -> Vec<(crate::world::SurfaceMaterial, bool)> {
+    use crate::world::SurfaceMaterial;
+    let mut used = [false; 256];
+    for room in &level.rooms {
+        for (_, _, sector) in room.iter_sectors() {
+            if let Some(SurfaceMaterial::Custom(id)) = sector.floor.as_ref().map(|f| f.material) {
+                used[id as usize] = true;
+            }
+        }
+    }
+    let mut options: Vec<_> = SurfaceMaterial::ALL.iter().map(|&m| (m, false)).collect();
+    options.extend((0..=255u8).filter(|&id| used[id as usize]).map(|id| (SurfaceMaterial::Custom(id), false)));
+    if let Some(free) = (0..=255u8).find(|&id| !used[id as usize]) {
+        options.push((SurfaceMaterial::Custom(free), true));
+    }
+    options
+}
 
-    // Header text
-    draw_text(header_text, (x + CONTAINER_PADDING).floor(), (y + 15.0).floor(), 14.0, header_color);
+/// Screen rect of the open material dropdown: below its button, or above it when
+/// there's no room below
+fn material_menu_rect(state: &EditorState) -> Option<Rect> {
+    let menu = state.material_menu?;
+    let height = material_menu_options(&state.level).len() as f32 * MATERIAL_MENU_ROW;
+    let below = menu.anchor.bottom();
+    let y = if below + height > screen_height() { menu.anchor.y - height } else { below };
+    Some(Rect::new(menu.anchor.x, y, menu.anchor.w, height))
 }
 
-/// Calculate height needed for a horizontal face container
-fn horizontal_face_container_height(face: &crate::world::HorizontalFace) -> f32 {
-    let line_height = 18.0;
-    let header_height = 22.0;
-    let mut lines = 3; // texture, height, walkable
-    if !face.is_flat() {
-        lines += 1; // extra line for individual heights
+/// Draw the open material dropdown on top of the panels and apply a pick. It closes
+/// on a pick, a click elsewhere, Escape, or once its floor isn't selected anymore.
+fn draw_material_menu(ctx: &mut UiContext, state: &mut EditorState) {
+    let (Some(menu), Some(rect)) = (state.material_menu, material_menu_rect(state)) else { return };
+    let selected = state.selection.sector_coords() == Some((menu.room, menu.x, menu.z));
+    let current = state.level.rooms.get(menu.room)
+        .and_then(|r| r.get_sector(menu.x, menu.z))
+        .and_then(|s| s.floor.as_ref())
+        .map(|f| f.material);
+    let Some(current) = current.filter(|_| selected) else {
+        state.material_menu = None;
+        return;
+    };
+
+    let theme = ctx.theme;
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, theme.popup_bg);
+    let mut picked = None;
+    for (i, (material, is_new)) in material_menu_options(&state.level).into_iter().enumerate() {
+        let row = Rect::new(rect.x, rect.y + i as f32 * MATERIAL_MENU_ROW, rect.w, MATERIAL_MENU_ROW);
+        let hovered = ctx.mouse.inside(&row);
+        if material == current {
+            draw_rectangle(row.x.floor(), row.y.floor(), row.w, row.h, theme.selection_bg);
+        } else if hovered {
+            draw_rectangle(row.x.floor(), row.y.floor(), row.w, row.h, theme.button_hover);
+        }
+        let label = if is_new { format!("{} (new)", material.label()) } else { material.label() };
+        let color = if is_new { theme.text_dim } else { WHITE };
+        draw_text(&label, (row.x + 4.0).floor(), (row.y + 12.0).floor(), 13.0, color);
+        if ctx.mouse.clicked(&row) {
+            picked = Some(material);
+        }
+    }
+    draw_rectangle_lines(rect.x.floor(), rect.y.floor(), rect.w, rect.h, 1.0, theme.popup_border);
+
+    if let Some(material) = picked {
+        state.material_menu = None;
+        if material != current {
+            apply_floor_material(state, menu.room, menu.x, menu.z, material);
+        }
+    } else if is_key_pressed(KeyCode::Escape)
+        || (ctx.mouse.left_pressed && !ctx.mouse.inside(&rect) && !ctx.mouse.inside(&menu.anchor))
+    {
+        state.material_menu = None;
     }
-    header_height + CONTAINER_PADDING * 2.0 + (lines as f32) * line_height
 }
 
-/// Calculate height needed for a wall face container
-fn wall_face_container_height(_wall: &crate::world::VerticalFace) -> f32 {
-    let line_height = 18.0;
-    let header_height = 22.0;
-    let lines = 3; // texture, y range, blend
-    header_height + CONTAINER_PADDING * 2.0 + (lines as f32) * line_height
+/// Set the floor material of a sector, and of every other selected sector.
+/// Records a single undo step.
+fn apply_floor_material(
+    state: &mut EditorState,
+    room_idx: usize,
+    gx: usize,
+    gz: usize,
+    material: crate::world::SurfaceMaterial,
+) {
+    let mut targets = vec![(room_idx, gx, gz)];
+    for sel in &state.multi_selection {
+        if let Some(coords) = sel.sector_coords() {
+            if !targets.contains(&coords) {
+                targets.push(coords);
+            }
+        }
+    }
+
+    let label = format!("Set material {} on {} floor(s)", material.label(), targets.len());
+    state.save_undo(&label);
+
+    let mut count = 0;
+    for (r, x, z) in targets {
+        state.mark_room_dirty(r);
+        let floor = state.level.rooms.get_mut(r)
+            .and_then(|room| room.get_sector_mut(x, z))
+            .and_then(|sector| sector.floor.as_mut());
+        if let Some(floor) = floor {
+            floor.material = material;
+            count += 1;
+        }
+    }
+
+    if count > 1 {
+        state.set_status(&format!("Material: {} ({} floors)", material.label(), count), 2.0);
+    } else {
+        state.set_status(&format!("Material: {}", material.label()), 2.0);
+    }
 }
 
 /// Draw properties for a horizontal face inside a container
@@ -483,6 +2579,12 @@ fn draw_horizontal_face_container(
     draw_text(&tex_display, content_x.floor(), (content_y + 12.0).floor(), 13.0, WHITE);
     content_y += line_height;
 
+    // UV preview
+    let uv_face = if is_floor { super::SectorFace::Floor } else { super::SectorFace::Ceiling };
+    let row = FaceUvRow { target: (room_idx, gx, gz, uv_face), uvs: face.uvs(), custom: face.uv.is_some(), texture: &face.texture };
+    draw_face_uv_row(ctx, content_x, content_y, row, state);
+    content_y += UV_ROW_HEIGHT;
+
     // Heights
     if !face.is_flat() {
         draw_text(&format!("Heights: [{:.0}, {:.0}, {:.0}, {:.0}]",
@@ -490,7 +2592,24 @@ fn draw_horizontal_face_container(
             content_x.floor(), (content_y + 12.0).floor(), 13.0, WHITE);
         content_y += line_height;
     }
-    draw_text(&format!("Base: {:.0}", face.heights[0]), content_x.floor(), (content_y + 12.0).floor(), 13.0, WHITE);
+    let base_rect = Rect::new(content_x, content_y - 2.0, (width - CONTAINER_PADDING * 2.0).min(180.0), 16.0);
+    let base_face = if is_floor { super::SectorFace::Floor } else { super::SectorFace::Ceiling };
+    let base_spec = DragValue {
+        tooltip: "Height of the first corner (the whole face moves, slope kept)",
+        pixels_per_step: HEIGHT_DRAG_PIXELS,
+        vertical: true,
+        unit: Some(CLICKS),
+        ..DragValue::new("Base", HEIGHT_STEP)
+    };
+    if let Some(change) = drag_value(ctx, base_rect, FACE_BASE_FIELD + face_field_index(base_face), face.heights[0], &base_spec) {
+        if change.value != face.heights[0] {
+            if change.first {
+                let which = if is_floor { "floor" } else { "ceiling" };
+                state.save_undo(&format!("Move {} ({}, {})", which, gx, gz));
+            }
+            set_face_base(state, room_idx, gx, gz, is_floor, change.value);
+        }
+    }
     content_y += line_height;
 
     // Walkable icon button
@@ -511,23 +2630,143 @@ fn draw_horizontal_face_container(
                 }
             }
         }
+        state.mark_room_dirty(room_idx);
+    }
+
+    // Visible toggle: invisible faces still collide if walkable
+    let face_id = if is_floor { super::SectorFace::Floor } else { super::SectorFace::Ceiling };
+    let visible_rect = Rect::new(content_x + icon_size + 4.0, content_y - 2.0, icon_size, icon_size);
+    if crate::ui::icon_button_active(ctx, visible_rect, icon::EYE, icon_font, "Visible (drawn in game)", face.visible) {
+        toggle_face_flag(state, room_idx, gx, gz, face_id, FaceFlag::Visible);
+    }
+
+    // Floor material, next to the toggles
+    if is_floor {
+        let toggles_w = (icon_size + 4.0) * 2.0 + 2.0;
+        let material_rect = Rect::new(content_x + toggles_w, content_y - 2.0, (width - CONTAINER_PADDING * 2.0 - toggles_w).min(140.0), 16.0);
+        draw_material_button(ctx, material_rect, (room_idx, gx, gz), face.material, state);
+    }
+    content_y += line_height;
+
+    // Blend mode
+    let blend_rect = Rect::new(content_x, content_y - 2.0, (width - CONTAINER_PADDING * 2.0).min(140.0), 16.0);
+    if let Some(mode) = draw_blend_mode_button(ctx, blend_rect, face.blend_mode) {
+        apply_blend_mode(state, room_idx, gx, gz, face_id, mode);
+    }
+
+    // Subdivision (detail grid), next to the blend mode
+    let subdiv_x = blend_rect.right() + 6.0;
+    let subdiv_w = (content_x + width - CONTAINER_PADDING * 2.0 - subdiv_x).min(80.0);
+    if subdiv_w > 40.0 {
+        let n = face.cells();
+        let subdiv_rect = Rect::new(subdiv_x, content_y - 2.0, subdiv_w, 16.0);
+        let label = format!("Subdiv: {}x{}", n, n);
+        if draw_text_button(ctx, subdiv_rect, &label, "Split into a grid of cells with their own vertex heights (drag the extra vertices in the 3D view). Resets detail heights") {
+            let levels = crate::world::SUBDIVISION_LEVELS;
+            let next = levels.iter().position(|&l| l as usize == n)
+                .map(|i| levels[(i + 1) % levels.len()])
+                .unwrap_or(1);
+            let which = if is_floor { "floor" } else { "ceiling" };
+            state.save_undo(&format!("Subdivide {} {}x{} ({}, {})", which, next, next, gx, gz));
+            let target = state.level.rooms.get_mut(room_idx)
+                .and_then(|r| r.get_sector_mut(gx, gz))
+                .and_then(|s| if is_floor { s.floor.as_mut() } else { s.ceiling.as_mut() });
+            if let Some(target) = target {
+                target.set_subdivision(next);
+            }
+            state.mark_room_dirty(room_idx);
+            state.set_status(&format!("Subdivision: {}x{}", next, next), 2.0);
+        }
+    }
+    content_y += line_height;
+
+    // Opening into the room below (floor) or above (ceiling)
+    let inner_w = width - CONTAINER_PADDING * 2.0;
+    match face.opening {
+        Some(target) => {
+            let name = state.level.room_index(target)
+                .map(|i| state.level.rooms[i].display_name())
+                .unwrap_or_else(|| "missing room".to_string());
+            draw_text(&format!("Opens to {}", name), content_x.floor(), (content_y + 12.0).floor(), 13.0, WHITE);
+            let close_rect = Rect::new(content_x + inner_w - 50.0, content_y - 2.0, 50.0, 16.0);
+            if draw_text_button(ctx, close_rect, "Close", "Restore this face and remove the portal pair") {
+                state.save_undo(&format!("Close opening ({}, {})", gx, gz));
+                state.level.remove_vertical_portal(room_idx, gx, gz, !is_floor);
+                state.mark_room_dirty(room_idx);
+                if let Some(target_idx) = state.level.room_index(target) {
+                    state.mark_room_dirty(target_idx);
+                }
+                state.set_status("Closed opening", 2.0);
+            }
+        }
+        None => {
+            let create_rect = Rect::new(content_x, content_y - 2.0, inner_w.min(150.0), 16.0);
+            let tooltip = if is_floor {
+                "Open this floor into the room directly below"
+            } else {
+                "Open this ceiling into the room directly above"
+            };
+            if draw_text_button(ctx, create_rect, "Create vertical portal", tooltip) {
+                create_vertical_portal(state, room_idx, gx, gz, is_floor);
+            }
+        }
     }
 
     container_height
 }
 
+/// Move a floor (or ceiling) so its first corner is at `base`, keeping its slope
+/// (caller records undo)
+fn set_face_base(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, is_floor: bool, base: f32) {
+    if let Some(room) = state.level.rooms.get_mut(room_idx) {
+        let face = room.get_sector_mut(gx, gz)
+            .and_then(|s| if is_floor { s.floor.as_mut() } else { s.ceiling.as_mut() });
+        if let Some(face) = face {
+            let delta = base - face.heights[0];
+            for height in &mut face.heights {
+                *height += delta;
+            }
+        }
+        room.recalculate_bounds();
+    }
+    state.mark_room_dirty(room_idx);
+}
+
+/// Open a floor (or ceiling) into the room below (above) it, if there is one
+fn create_vertical_portal(state: &mut EditorState, room_idx: usize, gx: usize, gz: usize, is_floor: bool) {
+    let Some(target) = state.level.vertical_neighbor(room_idx, gx, gz, !is_floor) else {
+        let side = if is_floor { "below this floor" } else { "above this ceiling" };
+        state.set_warning(&format!("No room {} covers the whole sector", side));
+        return;
+    };
+    state.save_undo(&format!("Create vertical portal ({}, {})", gx, gz));
+    state.level.create_vertical_portal(room_idx, gx, gz, !is_floor);
+    state.mark_room_dirty(room_idx);
+    state.mark_room_dirty(target);
+    let name = state.level.rooms[target].display_name();
+    state.set_status(&format!("Opened into {}", name), 2.0);
+}
+
 /// Draw properties for a wall face inside a container
 fn draw_wall_face_container(
+    ctx: &mut UiContext,
     x: f32,
     y: f32,
     width: f32,
     wall: &crate::world::VerticalFace,
     label: &str,
     label_color: Color,
+    room_idx: usize,
+    gx: usize,
+    gz: usize,
+    face: super::SectorFace,
+    state: &mut EditorState,
+    icon_font: Option<&Font>,
 ) -> f32 {
     let line_height = 18.0;
     let header_height = 22.0;
-    let container_height = wall_face_container_height(wall);
+    let door_idx = wall_door(state, room_idx, gx, gz, face);
+    let container_height = wall_face_container_height(wall, door_idx.is_some());
 
     // Draw container
     draw_container_start(x, y, width, container_height, label, label_color);
@@ -545,17 +2784,120 @@ fn draw_wall_face_container(
     draw_text(&tex_display, content_x.floor(), (content_y + 12.0).floor(), 13.0, WHITE);
     content_y += line_height;
 
-    // Height range
-    draw_text(&format!("Y Range: {:.0} - {:.0}", wall.y_bottom(), wall.y_top()), content_x.floor(), (content_y + 12.0).floor(), 13.0, WHITE);
+    // UV preview
+    let row = FaceUvRow { target: (room_idx, gx, gz, face), uvs: wall.uvs(), custom: wall.uv.is_some(), texture: &wall.texture };
+    draw_face_uv_row(ctx, content_x, content_y, row, state);
+    content_y += UV_ROW_HEIGHT;
+
+    // Height range: bottom and top edges, each moving its two corners (never past
+    // the other edge)
+    let inner_w = width - CONTAINER_PADDING * 2.0;
+    let field_w = ((inner_w - 4.0) / 2.0).floor();
+    let gap = (wall.heights[3] - wall.heights[0]).min(wall.heights[2] - wall.heights[1]).max(0.0);
+    let edges = [
+        ("Bottom", WALL_BOTTOM_FIELD, wall.y_bottom(), [0usize, 1usize], f32::MIN, wall.y_bottom() + gap),
+        ("Top", WALL_TOP_FIELD, wall.y_top(), [3, 2], wall.y_top() - gap, f32::MAX),
+    ];
+    for (col, (label, field_id, value, corners, min, max)) in edges.into_iter().enumerate() {
+        let spec = DragValue {
+            tooltip: if col == 0 { "Bottom edge of the wall" } else { "Top edge of the wall" },
+            pixels_per_step: HEIGHT_DRAG_PIXELS,
+            vertical: true,
+            min,
+            max,
+            unit: Some(CLICKS),
+            ..DragValue::new(label, HEIGHT_STEP)
+        };
+        let field_rect = Rect::new(content_x + col as f32 * (field_w + 4.0), content_y - 2.0, field_w, 16.0);
+        let Some(change) = drag_value(ctx, field_rect, field_id + face_field_index(face), value, &spec) else {
+            continue;
+        };
+        if change.value == value {
+            continue;
+        }
+        if change.first {
+            state.save_undo(&format!("Move wall {} ({}, {})", label.to_lowercase(), gx, gz));
+        }
+        for corner in corners {
+            set_wall_corner_height(state, room_idx, gx, gz, face, corner, wall.heights[corner] + change.value - value);
+        }
+    }
     content_y += line_height;
 
-    // Blend mode
-    draw_text(&format!("Blend: {:?}", wall.blend_mode), content_x.floor(), (content_y + 12.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
+    // Corner heights: top row TL/TR, bottom row BL/BR (click selects, drag adjusts)
+    let is_selected_face = matches!(state.selection,
+        super::Selection::SectorFace { room, x, z, face: f } if room == room_idx && x == gx && z == gz && f == face);
+    for (row, corners) in [[3usize, 2usize], [0, 1]].iter().enumerate() {
+        for (col, &corner) in corners.iter().enumerate() {
+            let field_rect = Rect::new(content_x + col as f32 * (field_w + 4.0), content_y - 2.0 + row as f32 * line_height, field_w, 16.0);
+            if let Some(height) = draw_wall_corner_field(ctx, field_rect, (room_idx, gx, gz, face), corner, wall.heights[corner], state) {
+                set_wall_corner_height(state, room_idx, gx, gz, face, corner, height);
+            }
+        }
+    }
+    content_y += line_height * 2.0;
+
+    // PageUp/PageDown nudge the selected corner of the selected wall
+    if is_selected_face && !ctx.text_input_focused() {
+        if let Some(corner) = state.selected_wall_corner {
+            let step = if is_key_pressed(KeyCode::PageUp) {
+                HEIGHT_STEP
+            } else if is_key_pressed(KeyCode::PageDown) {
+                -HEIGHT_STEP
+            } else {
+                0.0
+            };
+            if step != 0.0 {
+                state.save_undo(&format!("Nudge {} corner ({}, {})", WALL_CORNER_NAMES[corner], gx, gz));
+                set_wall_corner_height(state, room_idx, gx, gz, face, corner, wall.heights[corner] + step);
+            }
+        }
+    }
+
+    // Adapt to the sector's floor/ceiling slope along this edge
+    let adapt_rect = Rect::new(content_x, content_y - 2.0, inner_w.min(180.0), 16.0);
+    if draw_text_button(ctx, adapt_rect, "Adapt to floor/ceiling", "Match bottom corners to the floor and top corners to the ceiling along this edge") {
+        adapt_wall_to_sector(state, room_idx, gx, gz, face);
+    }
+    content_y += line_height;
+
+    // Blend mode, then the solid (collision) and visible (drawn) toggles
+    let icon_size = 18.0;
+    let blend_rect = Rect::new(content_x, content_y - 2.0, (inner_w - (icon_size + 4.0) * 2.0).min(140.0), 16.0);
+    if let Some(mode) = draw_blend_mode_button(ctx, blend_rect, wall.blend_mode) {
+        apply_blend_mode(state, room_idx, gx, gz, face, mode);
+    }
+    let solid_rect = Rect::new(blend_rect.right() + 4.0, content_y - 2.0, icon_size, icon_size);
+    if crate::ui::icon_button_active(ctx, solid_rect, icon::BRICK_WALL, icon_font, "Solid (blocks movement)", wall.solid) {
+        toggle_face_flag(state, room_idx, gx, gz, face, FaceFlag::Collides);
+    }
+    let visible_rect = Rect::new(solid_rect.right() + 4.0, content_y - 2.0, icon_size, icon_size);
+    if crate::ui::icon_button_active(ctx, visible_rect, icon::EYE, icon_font, "Visible (drawn in game)", wall.visible) {
+        toggle_face_flag(state, room_idx, gx, gz, face, FaceFlag::Visible);
+    }
+    content_y += line_height;
+
+    // Door
+    match door_idx {
+        Some(door_idx) => draw_door_controls(ctx, content_x, content_y, inner_w, state, room_idx, door_idx),
+        None => {
+            let door_rect = Rect::new(content_x, content_y - 2.0, inner_w.min(100.0), 16.0);
+            if draw_text_button(ctx, door_rect, "Make Door", "Turn this wall into a door: closed as it is now, opens by sinking to its bottom edge") {
+                make_door(state, room_idx, gx, gz, face);
+            }
+            let cut_rect = Rect::new(content_x + door_rect.w + 4.0, content_y - 2.0, inner_w.min(100.0), 16.0);
+            if draw_text_button(ctx, cut_rect, "Cut Doorway", "Split this wall around an opening: left and right pillars and a lintel") {
+                cut_doorway(state, room_idx, gx, gz, face);
+            }
+            draw_doorway_size_controls(ctx, content_x, content_y + line_height, inner_w, state);
+        }
+    }
 
     container_height
 }
 
 fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, icon_font: Option<&Font>) {
+    let theme = ctx.theme;
     let x = rect.x.floor();
     let container_width = rect.w - 4.0;
 
@@ -568,6 +2910,17 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
     // Clone selection to avoid borrow issues
     let selection = state.selection.clone();
 
+    // Collapsed containers are only remembered while the same sector stays selected
+    let sector_key = match selection {
+        super::Selection::Sector { room, x, z } => Some((room, x, z)),
+        _ => None,
+    };
+    if state.collapsed_sector != sector_key {
+        state.collapsed_sector = sector_key;
+        state.collapsed_faces.clear();
+        state.properties_flash = None;
+    }
+
     // Calculate total content height first
     let total_height = calculate_properties_content_height(&selection, state);
 
@@ -594,14 +2947,14 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
 
     match &selection {
         super::Selection::None => {
-            draw_text("Nothing selected", x, (y + 14.0).floor(), 16.0, Color::from_rgba(150, 150, 150, 255));
+            draw_text("Nothing selected", x, (y + 14.0).floor(), 16.0, theme.text_dim);
         }
         super::Selection::Room(idx) => {
             draw_text(&format!("Room {}", idx), x, (y + 14.0).floor(), 16.0, WHITE);
         }
         super::Selection::SectorFace { room, x: gx, z: gz, face } => {
             // Single face selected (from 3D view click)
-            draw_text(&format!("Sector ({}, {})", gx, gz), x, (y + 14.0).floor(), 14.0, Color::from_rgba(150, 150, 150, 255));
+            draw_text(&format!("Sector ({}, {})", gx, gz), x, (y + 14.0).floor(), 14.0, theme.text_dim);
             y += 24.0;
 
             // Get sector data
@@ -620,7 +2973,7 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                             );
                             y += h + CONTAINER_MARGIN;
                         } else {
-                            draw_text("(no floor)", x, (y + 14.0).floor(), 14.0, Color::from_rgba(100, 100, 100, 255));
+                            draw_text("(no floor)", x, (y + 14.0).floor(), 14.0, theme.text_faint);
                         }
                     }
                     super::SectorFace::Ceiling => {
@@ -632,36 +2985,28 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                             );
                             y += h + CONTAINER_MARGIN;
                         } else {
-                            draw_text("(no ceiling)", x, (y + 14.0).floor(), 14.0, Color::from_rgba(100, 100, 100, 255));
-                        }
-                    }
-                    super::SectorFace::WallNorth(i) => {
-                        if let Some(wall) = sector.walls_north.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (North)", Color::from_rgba(255, 180, 120, 255));
-                            y += h + CONTAINER_MARGIN;
-                        }
-                    }
-                    super::SectorFace::WallEast(i) => {
-                        if let Some(wall) = sector.walls_east.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (East)", Color::from_rgba(255, 180, 120, 255));
-                            y += h + CONTAINER_MARGIN;
-                        }
-                    }
-                    super::SectorFace::WallSouth(i) => {
-                        if let Some(wall) = sector.walls_south.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (South)", Color::from_rgba(255, 180, 120, 255));
-                            y += h + CONTAINER_MARGIN;
+                            draw_text("(no ceiling)", x, (y + 14.0).floor(), 14.0, theme.text_faint);
                         }
                     }
-                    super::SectorFace::WallWest(i) => {
-                        if let Some(wall) = sector.walls_west.get(*i) {
-                            let h = draw_wall_face_container(x, y, container_width, wall, "Wall (West)", Color::from_rgba(255, 180, 120, 255));
+                    wall_face => {
+                        if let Some((dir, wall)) = wall_face.wall_in(&sector) {
+                            let title = match dir {
+                                crate::world::Direction::North => "Wall (North)",
+                                crate::world::Direction::East => "Wall (East)",
+                                crate::world::Direction::South => "Wall (South)",
+                                crate::world::Direction::West => "Wall (West)",
+                            };
+                            let h = draw_wall_face_container(
+                                ctx, x, y, container_width, wall, title,
+                                Color::from_rgba(255, 180, 120, 255),
+                                *room, *gx, *gz, *face, state, icon_font
+                            );
                             y += h + CONTAINER_MARGIN;
                         }
                     }
                 }
             } else {
-                draw_text("Sector not found", x, (y + 14.0).floor(), 14.0, Color::from_rgba(255, 100, 100, 255));
+                draw_text("Sector not found", x, (y + 14.0).floor(), 14.0, theme.error);
             }
         }
         super::Selection::Sector { room, x: gx, z: gz } => {
@@ -675,52 +3020,72 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                 .cloned();
 
             if let Some(sector) = sector_data {
-                // === FLOOR ===
-                if let Some(floor) = &sector.floor {
-                    let h = draw_horizontal_face_container(
-                        ctx, x, y, container_width, floor, "Floor",
-                        Color::from_rgba(150, 200, 255, 255),
-                        *room, *gx, *gz, true, state, icon_font
-                    );
-                    y += h + CONTAINER_MARGIN;
-                }
+                let containers = sector_containers(state, *room, *gx, *gz);
+                draw_face_navigator(ctx, x, y, container_width, &sector, &containers, state);
+                y += FACE_NAV_HEIGHT;
+
+                for (face, height) in containers {
+                    let (label, color) = sector_container_label(&sector, face);
+                    let collapsed = state.collapsed_faces.contains(&face);
+                    if collapsed {
+                        draw_container_start(x, y, container_width, height, &label, color);
+                    } else {
+                        match face {
+                            super::SectorFace::Floor | super::SectorFace::Ceiling => {
+                                let is_floor = face == super::SectorFace::Floor;
+                                let Some(horizontal) = (if is_floor { &sector.floor } else { &sector.ceiling }) else { continue };
+                                draw_horizontal_face_container(
+                                    ctx, x, y, container_width, horizontal, &label, color,
+                                    *room, *gx, *gz, is_floor, state, icon_font
+                                );
+                            }
+                            wall_face => {
+                                let Some((_, wall)) = wall_face.wall_in(&sector) else { continue };
+                                draw_wall_face_container(
+                                    ctx, x, y, container_width, wall, &label, color,
+                                    *room, *gx, *gz, wall_face, state, icon_font
+                                );
+                            }
+                        }
+                    }
 
-                // === CEILING ===
-                if let Some(ceiling) = &sector.ceiling {
-                    let h = draw_horizontal_face_container(
-                        ctx, x, y, container_width, ceiling, "Ceiling",
-                        Color::from_rgba(200, 150, 255, 255),
-                        *room, *gx, *gz, false, state, icon_font
-                    );
-                    y += h + CONTAINER_MARGIN;
-                }
+                    // Header flash after jumping here from the navigator
+                    if let Some((flashed, at)) = state.properties_flash {
+                        let t = (get_time() - at) / FACE_NAV_FLASH;
+                        if flashed == face && t < 1.0 {
+                            let alpha = (1.0 - t as f32) * 0.5;
+                            draw_rectangle(x.floor(), y.floor(), container_width, 22.0, Color::new(1.0, 1.0, 1.0, alpha));
+                        }
+                    }
 
-                // === WALLS ===
-                let wall_dirs: [(&str, &Vec<crate::world::VerticalFace>); 4] = [
-                    ("North", &sector.walls_north),
-                    ("East", &sector.walls_east),
-                    ("South", &sector.walls_south),
-                    ("West", &sector.walls_west),
-                ];
-
-                for (dir_name, walls) in wall_dirs {
-                    for (i, wall) in walls.iter().enumerate() {
-                        let label = if walls.len() == 1 {
-                            format!("Wall ({})", dir_name)
+                    // Collapse/expand toggle on the right of the header
+                    let toggle = Rect::new(x + container_width - 21.0, y + 3.0, 18.0, 16.0);
+                    let (symbol, tip) = if collapsed { ("+", "Expand") } else { ("-", "Collapse") };
+                    if draw_text_button(ctx, toggle, symbol, tip) {
+                        if collapsed {
+                            state.collapsed_faces.retain(|f| *f != face);
                         } else {
-                            format!("Wall ({}) [{}]", dir_name, i)
-                        };
-                        let h = draw_wall_face_container(x, y, container_width, wall, &label, Color::from_rgba(255, 180, 120, 255));
-                        y += h + CONTAINER_MARGIN;
+                            state.collapsed_faces.push(face);
+                        }
                     }
+                    y += height + CONTAINER_MARGIN;
                 }
             } else {
-                draw_text("Sector not found", x, (y + 14.0).floor(), 14.0, Color::from_rgba(255, 100, 100, 255));
+                draw_text("Sector not found", x, (y + 14.0).floor(), 14.0, theme.error);
             }
         }
         super::Selection::Portal { room, portal } => {
             draw_text(&format!("Portal {} in Room {}", portal, room), x, (y + 14.0).floor(), 16.0, WHITE);
         }
+        super::Selection::Pickup { room, index } => {
+            draw_pickup_properties(ctx, x, y, container_width, *room, *index, state);
+        }
+        super::Selection::Ambient { room, index } => {
+            draw_ambient_properties(ctx, x, y, container_width, *room, *index, state);
+        }
+        super::Selection::Light { room, index } => {
+            draw_light_properties(ctx, x, y, container_width, *room, *index, state);
+        }
         super::Selection::Edge { room, x: gx, z: gz, face_idx, edge_idx, wall_face } => {
             // Determine face name based on type
             let face_name = if *face_idx == 0 {
@@ -773,13 +3138,7 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                         sector.ceiling.as_ref().map(|c| c.heights)
                     } else if let Some(wf) = wall_face {
                         // Get wall heights
-                        match wf {
-                            super::SectorFace::WallNorth(i) => sector.walls_north.get(*i).map(|w| w.heights),
-                            super::SectorFace::WallEast(i) => sector.walls_east.get(*i).map(|w| w.heights),
-                            super::SectorFace::WallSouth(i) => sector.walls_south.get(*i).map(|w| w.heights),
-                            super::SectorFace::WallWest(i) => sector.walls_west.get(*i).map(|w| w.heights),
-                            _ => None,
-                        }
+                        wf.wall_in(sector).map(|(_, w)| w.heights)
                     } else {
                         None
                     };
@@ -791,13 +3150,13 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                         // Get corner positions - for walls these are different
                         if *face_idx == 2 {
                             // Wall corners: heights are [bottom-left, bottom-right, top-right, top-left]
-                            draw_text("Vertex 1:", x, (y + 12.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
+                            draw_text("Vertex 1:", x, (y + 12.0).floor(), 13.0, theme.text_dim);
                             y += 18.0;
                             draw_text(&format!("  Height: {:.0}", h[corner0]),
                                 x, (y + 12.0).floor(), 13.0, WHITE);
                             y += 18.0;
 
-                            draw_text("Vertex 2:", x, (y + 12.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
+                            draw_text("Vertex 2:", x, (y + 12.0).floor(), 13.0, theme.text_dim);
                             y += 18.0;
                             draw_text(&format!("  Height: {:.0}", h[corner1]),
                                 x, (y + 12.0).floor(), 13.0, WHITE);
@@ -810,13 +3169,13 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
                                 (base_x, base_z + crate::world::SECTOR_SIZE),               // SW - 3
                             ];
 
-                            draw_text("Vertex 1:", x, (y + 12.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
+                            draw_text("Vertex 1:", x, (y + 12.0).floor(), 13.0, theme.text_dim);
                             y += 18.0;
                             draw_text(&format!("  X: {:.0}  Z: {:.0}  Y: {:.0}", corners[corner0].0, corners[corner0].1, h[corner0]),
                                 x, (y + 12.0).floor(), 13.0, WHITE);
                             y += 18.0;
 
-                            draw_text("Vertex 2:", x, (y + 12.0).floor(), 13.0, Color::from_rgba(150, 150, 150, 255));
+                            draw_text("Vertex 2:", x, (y + 12.0).floor(), 13.0, theme.text_dim);
                             y += 18.0;
                             draw_text(&format!("  X: {:.0}  Z: {:.0}  Y: {:.0}", corners[corner1].0, corners[corner1].1, h[corner1]),
                                 x, (y + 12.0).floor(), 13.0, WHITE);
@@ -845,7 +3204,372 @@ fn draw_properties(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, ico
     }
 }
 
+/// Draw the properties of a pickup (kind, sprite, respawn)
+fn draw_pickup_properties(ctx: &mut UiContext, x: f32, mut y: f32, width: f32, room_idx: usize, index: usize, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let pickup = match state.level.rooms.get(room_idx).and_then(|r| r.pickups.get(index)) {
+        Some(p) => p.clone(),
+        None => {
+            draw_text("Pickup not found", x, (y + 14.0).floor(), 14.0, theme.error);
+            return;
+        }
+    };
+
+    draw_text(&format!("Pickup {} in Room {}", index, room_idx), x, (y + 14.0).floor(), 16.0, WHITE);
+    y += 24.0;
+
+    let line_height = 22.0;
+    let button_w = (width - 8.0).min(180.0);
+
+    // Kind: cycles through the suggested kinds
+    let kind_rect = Rect::new(x, y, button_w, 18.0);
+    if draw_text_button(ctx, kind_rect, &format!("Kind: {}", pickup.kind), "Cycle pickup kind") {
+        let next = crate::world::PICKUP_KINDS.iter()
+            .position(|k| *k == pickup.kind)
+            .map(|i| (i + 1) % crate::world::PICKUP_KINDS.len())
+            .unwrap_or(0);
+        let kind = crate::world::PICKUP_KINDS[next].to_string();
+        state.save_undo(&format!("Set pickup kind to {}", kind));
+        if let Some(p) = state.level.rooms.get_mut(room_idx).and_then(|r| r.pickups.get_mut(index)) {
+            p.kind = kind;
+        }
+        state.mark_room_dirty(room_idx);
+    }
+    y += line_height;
+
+    // Sprite
+    let sprite_display = if pickup.sprite.is_valid() {
+        format!("Sprite: {}", pickup.sprite.name)
+    } else {
+        "Sprite: (none)".to_string()
+    };
+    draw_text(&sprite_display, x.floor(), (y + 13.0).floor(), 13.0, WHITE);
+    y += line_height;
+
+    let sprite_rect = Rect::new(x, y, button_w, 18.0);
+    if draw_text_button(ctx, sprite_rect, "Use selected texture", "Set the sprite to the texture selected in the palette") {
+        let texture = state.selected_texture.clone();
+        state.save_undo("Set pickup sprite");
+        if let Some(p) = state.level.rooms.get_mut(room_idx).and_then(|r| r.pickups.get_mut(index)) {
+            p.sprite = texture;
+        }
+        state.mark_room_dirty(room_idx);
+    }
+    y += line_height;
+
+    // Respawn toggle
+    let respawn_rect = Rect::new(x, y, button_w, 18.0);
+    let respawn_label = if pickup.respawn { "Respawn: On" } else { "Respawn: Off" };
+    if draw_text_button(ctx, respawn_rect, respawn_label, "Come back some time after being collected") {
+        state.save_undo("Toggle pickup respawn");
+        if let Some(p) = state.level.rooms.get_mut(room_idx).and_then(|r| r.pickups.get_mut(index)) {
+            p.respawn = !p.respawn;
+        }
+        state.mark_room_dirty(room_idx);
+    }
+    y += line_height;
+
+    let delete_rect = Rect::new(x, y, button_w, 18.0);
+    if draw_text_button(ctx, delete_rect, "Delete", "Remove this pickup") {
+        remove_pickup(state, room_idx, index);
+    }
+}
+
+/// Drag value IDs of an ambient sound's fields
+const AMBIENT_VOLUME_FIELD: u64 = 0x564f_4c00;
+const AMBIENT_RADIUS_FIELD: u64 = 0x5241_4400;
+
+/// Draw the properties of an ambient sound (sound, volume, radius, falloff, looping)
+fn draw_ambient_properties(ctx: &mut UiContext, x: f32, mut y: f32, width: f32, room_idx: usize, index: usize, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let ambient = match state.level.rooms.get(room_idx).and_then(|r| r.ambients.get(index)) {
+        Some(a) => a.clone(),
+        None => {
+            draw_text("Ambient sound not found", x, (y + 14.0).floor(), 14.0, theme.error);
+            return;
+        }
+    };
+
+    draw_text(&format!("Ambient Sound {} in Room {}", index, room_idx), x, (y + 14.0).floor(), 16.0, WHITE);
+    y += 24.0;
+
+    let line_height = 22.0;
+    let button_w = (width - 8.0).min(180.0);
+
+    // Sound: cycles through the sound library
+    let sound_rect = Rect::new(x, y, button_w, 18.0);
+    let sound_label = match (ambient.sound.is_empty(), state.sounds.get(&ambient.sound).is_some()) {
+        (true, _) => "Sound: (none)".to_string(),
+        (false, true) => format!("Sound: {}", ambient.sound),
+        (false, false) => format!("Sound: {} (missing)", ambient.sound),
+    };
+    if draw_text_button(ctx, sound_rect, &sound_label, "Cycle through the sounds in assets/sounds") {
+        let names: Vec<String> = state.sounds.names().map(str::to_string).collect();
+        if names.is_empty() {
+            state.set_warning("No sounds in assets/sounds");
+        } else {
+            let next = names.iter()
+                .position(|n| *n == ambient.sound)
+                .map(|i| (i + 1) % names.len())
+                .unwrap_or(0);
+            state.save_undo(&format!("Set ambient sound to {}", names[next]));
+            if let Some(a) = state.level.rooms.get_mut(room_idx).and_then(|r| r.ambients.get_mut(index)) {
+                a.sound = names[next].clone();
+            }
+            state.mark_room_dirty(room_idx);
+        }
+    }
+    y += line_height;
+
+    // Volume and falloff radius
+    let fields = [
+        (AMBIENT_VOLUME_FIELD, ambient.volume, DragValue {
+            tooltip: "Gain at the emitter (0-1)",
+            min: 0.0,
+            max: 1.0,
+            ..DragValue::new("Volume", 0.05)
+        }),
+        (AMBIENT_RADIUS_FIELD, ambient.radius, DragValue {
+            tooltip: "Distance at which the sound fades out",
+            min: SECTOR_SIZE / 4.0,
+            max: SECTOR_SIZE * 32.0,
+            unit: Some(SECTORS),
+            ..DragValue::new("Radius", SECTOR_SIZE / 4.0)
+        }),
+    ];
+    for (id, value, spec) in fields {
+        let rect = Rect::new(x, y, button_w, 18.0);
+        if let Some(change) = drag_value(ctx, rect, id, value, &spec) {
+            if change.value != value {
+                if change.first {
+                    state.save_undo(&format!("Set ambient sound {}", spec.label.to_lowercase()));
+                }
+                if let Some(a) = state.level.rooms.get_mut(room_idx).and_then(|r| r.ambients.get_mut(index)) {
+                    match id {
+                        AMBIENT_VOLUME_FIELD => a.volume = change.value,
+                        _ => a.radius = change.value,
+                    }
+                }
+                state.mark_room_dirty(room_idx);
+            }
+        }
+        y += line_height;
+    }
+
+    // Falloff curve
+    let falloff_rect = Rect::new(x, y, button_w, 18.0);
+    if draw_text_button(ctx, falloff_rect, &format!("Falloff: {}", ambient.falloff.label()), "Cycle how the volume falls off with distance") {
+        let all = crate::world::Falloff::ALL;
+        let next = all[(all.iter().position(|f| *f == ambient.falloff).unwrap_or(0) + 1) % all.len()];
+        state.save_undo(&format!("Set ambient falloff to {}", next.label()));
+        if let Some(a) = state.level.rooms.get_mut(room_idx).and_then(|r| r.ambients.get_mut(index)) {
+            a.falloff = next;
+        }
+        state.mark_room_dirty(room_idx);
+    }
+    y += line_height;
+
+    // Looping toggle
+    let looping_rect = Rect::new(x, y, button_w, 18.0);
+    let looping_label = if ambient.looping { "Loop: On" } else { "Loop: Off" };
+    if draw_text_button(ctx, looping_rect, looping_label, "Play on a loop, or once each time the player comes into range") {
+        state.save_undo("Toggle ambient sound loop");
+        if let Some(a) = state.level.rooms.get_mut(room_idx).and_then(|r| r.ambients.get_mut(index)) {
+            a.looping = !a.looping;
+        }
+        state.mark_room_dirty(room_idx);
+    }
+    y += line_height;
+
+    let delete_rect = Rect::new(x, y, button_w, 18.0);
+    if draw_text_button(ctx, delete_rect, "Delete", "Remove this ambient sound") {
+        remove_ambient(state, room_idx, index);
+    }
+}
+
+/// Drag value IDs of a point light's fields
+const LIGHT_X_FIELD: u64 = 0x4c58_0000;
+const LIGHT_Y_FIELD: u64 = 0x4c59_0000;
+const LIGHT_Z_FIELD: u64 = 0x4c5a_0000;
+const LIGHT_INTENSITY_FIELD: u64 = 0x4c49_0000;
+const LIGHT_RADIUS_FIELD: u64 = 0x4c52_0000;
+
+/// Draw the properties of a point light (position, intensity, radius, falloff)
+fn draw_light_properties(ctx: &mut UiContext, x: f32, mut y: f32, width: f32, room_idx: usize, index: usize, state: &mut EditorState) {
+    let theme = ctx.theme;
+    let light = match state.level.rooms.get(room_idx).and_then(|r| r.lights.get(index)) {
+        Some(l) => l.clone(),
+        None => {
+            draw_text("Light not found", x, (y + 14.0).floor(), 14.0, theme.error);
+            return;
+        }
+    };
+
+    draw_text(&format!("Light {} in Room {}", index, room_idx), x, (y + 14.0).floor(), 16.0, WHITE);
+    y += 24.0;
+
+    let line_height = 22.0;
+    let button_w = (width - 8.0).min(180.0);
+
+    // Position (room-relative), intensity and falloff radius
+    let axis = |label, unit, tooltip| DragValue { tooltip, unit: Some(unit), ..DragValue::new(label, SECTOR_SIZE / 8.0) };
+    let fields = [
+        (LIGHT_X_FIELD, light.position.x, axis("X", SECTORS, "Position along X from the room origin")),
+        (LIGHT_Y_FIELD, light.position.y, axis("Y", CLICKS, "Height")),
+        (LIGHT_Z_FIELD, light.position.z, axis("Z", SECTORS, "Position along Z from the room origin")),
+        (LIGHT_INTENSITY_FIELD, light.intensity, DragValue {
+            tooltip: "How much the light brightens a face right at it (1 = twice as bright)",
+            min: 0.0,
+            max: 4.0,
+            ..DragValue::new("Intensity", 0.05)
+        }),
+        (LIGHT_RADIUS_FIELD, light.radius, DragValue {
+            tooltip: "Distance at which the light fades out",
+            min: SECTOR_SIZE / 4.0,
+            max: SECTOR_SIZE * 32.0,
+            unit: Some(SECTORS),
+            ..DragValue::new("Radius", SECTOR_SIZE / 4.0)
+        }),
+    ];
+    for (id, value, spec) in fields {
+        let rect = Rect::new(x, y, button_w, 18.0);
+        if let Some(change) = drag_value(ctx, rect, id, value, &spec) {
+            if change.value != value {
+                if change.first {
+                    state.save_undo(&format!("Set light {}", spec.label.to_lowercase()));
+                }
+                if let Some(l) = state.level.rooms.get_mut(room_idx).and_then(|r| r.lights.get_mut(index)) {
+                    match id {
+                        LIGHT_X_FIELD => l.position.x = change.value,
+                        LIGHT_Y_FIELD => l.position.y = change.value,
+                        LIGHT_Z_FIELD => l.position.z = change.value,
+                        LIGHT_INTENSITY_FIELD => l.intensity = change.value,
+                        _ => l.radius = change.value,
+                    }
+                }
+                state.mark_lights_dirty(room_idx);
+            }
+        }
+        y += line_height;
+    }
+
+    // Falloff curve
+    let falloff_rect = Rect::new(x, y, button_w, 18.0);
+    if draw_text_button(ctx, falloff_rect, &format!("Falloff: {}", light.falloff.label()), "Cycle how the light fades with distance") {
+        let all = crate::world::Falloff::ALL;
+        let next = all[(all.iter().position(|f| *f == light.falloff).unwrap_or(0) + 1) % all.len()];
+        state.save_undo(&format!("Set light falloff to {}", next.label()));
+        if let Some(l) = state.level.rooms.get_mut(room_idx).and_then(|r| r.lights.get_mut(index)) {
+            l.falloff = next;
+        }
+        state.mark_lights_dirty(room_idx);
+    }
+    y += line_height;
+
+    let delete_rect = Rect::new(x, y, button_w, 18.0);
+    if draw_text_button(ctx, delete_rect, "Delete", "Remove this light") {
+        remove_light(state, room_idx, index);
+    }
+}
+
 /// Calculate total content height for properties panel (for scroll bounds)
+/// Height of the face navigator row above a whole sector's containers
+const FACE_NAV_HEIGHT: f32 = 24.0;
+
+/// Height of a collapsed container (just its header)
+const COLLAPSED_CONTAINER_HEIGHT: f32 = 22.0;
+
+/// Seconds a container header flashes after jumping to it
+const FACE_NAV_FLASH: f64 = 0.6;
+
+/// Containers shown for a whole-sector selection, in panel order, with their heights
+/// (collapsed ones are just a header)
+fn sector_containers(state: &EditorState, room: usize, gx: usize, gz: usize) -> Vec<(super::SectorFace, f32)> {
+    use super::SectorFace;
+    use crate::world::Direction;
+    let Some(sector) = state.level.rooms.get(room).and_then(|r| r.get_sector(gx, gz)) else {
+        return Vec::new();
+    };
+    let mut containers = Vec::new();
+    let mut push = |face: SectorFace, height: f32| {
+        let collapsed = state.collapsed_faces.contains(&face);
+        containers.push((face, if collapsed { COLLAPSED_CONTAINER_HEIGHT } else { height }));
+    };
+    if let Some(floor) = &sector.floor {
+        push(SectorFace::Floor, horizontal_face_container_height(floor));
+    }
+    if let Some(ceiling) = &sector.ceiling {
+        push(SectorFace::Ceiling, horizontal_face_container_height(ceiling));
+    }
+    for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+        for wall in sector.walls(dir) {
+            let face = SectorFace::for_wall(dir, wall.id);
+            let is_door = wall_door(state, room, gx, gz, face).is_some();
+            push(face, wall_face_container_height(wall, is_door));
+        }
+    }
+    containers
+}
+
+/// Header label and color of a whole-sector container
+fn sector_container_label(sector: &crate::world::Sector, face: super::SectorFace) -> (String, Color) {
+    match face.wall() {
+        None if face == super::SectorFace::Floor => ("Floor".to_string(), Color::from_rgba(150, 200, 255, 255)),
+        None => ("Ceiling".to_string(), Color::from_rgba(200, 150, 255, 255)),
+        Some((dir, _)) => {
+            let label = match face.wall_index_in(sector) {
+                Some((_, i)) if sector.walls(dir).len() > 1 => format!("Wall ({:?}) [{}]", dir, i),
+                _ => format!("Wall ({:?})", dir),
+            };
+            (label, Color::from_rgba(255, 180, 120, 255))
+        }
+    }
+}
+
+/// Row of jump buttons (Floor, Ceil, N, E, S, W) above a whole sector's containers:
+/// scrolls to the first container of that kind and flashes its header
+fn draw_face_navigator(
+    ctx: &mut UiContext,
+    x: f32,
+    y: f32,
+    width: f32,
+    sector: &crate::world::Sector,
+    containers: &[(super::SectorFace, f32)],
+    state: &mut EditorState,
+) {
+    use super::SectorFace;
+    use crate::world::Direction;
+    let targets = [
+        ("Floor", sector.floor.is_some().then_some(SectorFace::Floor), 0),
+        ("Ceil", sector.ceiling.is_some().then_some(SectorFace::Ceiling), 0),
+        ("N", SectorFace::wall_at(sector, Direction::North, 0), sector.walls_north.len()),
+        ("E", SectorFace::wall_at(sector, Direction::East, 0), sector.walls_east.len()),
+        ("S", SectorFace::wall_at(sector, Direction::South, 0), sector.walls_south.len()),
+        ("W", SectorFace::wall_at(sector, Direction::West, 0), sector.walls_west.len()),
+    ];
+    let button_w = ((width - 2.0 * (targets.len() - 1) as f32) / targets.len() as f32).floor();
+    for (i, (name, face, walls)) in targets.into_iter().enumerate() {
+        let rect = Rect::new(x + i as f32 * (button_w + 2.0), y, button_w, 18.0);
+        // Walls are there only if the edge has any; stacked walls show their count
+        let face = face.filter(|f| f.wall().is_none() || walls > 0);
+        let label = if walls > 1 { format!("{}{}", name, walls) } else { name.to_string() };
+        let Some(face) = face else {
+            draw_rectangle_lines(rect.x.floor(), rect.y.floor(), rect.w, rect.h, 1.0, ctx.theme.border);
+            draw_text(&label, (rect.x + 4.0).floor(), (rect.y + 13.0).floor(), 14.0, ctx.theme.text_faint);
+            continue;
+        };
+        if draw_text_button(ctx, rect, &label, "Jump to this face") {
+            // Offset of the container below the sector header and this row
+            let offset: f32 = containers.iter()
+                .take_while(|(f, _)| *f != face)
+                .map(|(_, h)| h + CONTAINER_MARGIN)
+                .sum();
+            state.properties_scroll = 24.0 + FACE_NAV_HEIGHT + offset;
+            state.properties_flash = Some((face, get_time()));
+        }
+    }
+}
+
 fn calculate_properties_content_height(selection: &super::Selection, state: &EditorState) -> f32 {
     let header_height = 24.0;
 
@@ -854,6 +3578,12 @@ fn calculate_properties_content_height(selection: &super::Selection, state: &Edi
 
         super::Selection::Edge { .. } => 120.0, // Edge header + 2 vertex coords
 
+        super::Selection::Pickup { .. } => 24.0 + 5.0 * 22.0, // Header + kind, sprite, 2 buttons, delete
+
+        super::Selection::Ambient { .. } => 24.0 + 6.0 * 22.0, // Header + sound, 2 fields, falloff, loop, delete
+
+        super::Selection::Light { .. } => 24.0 + 7.0 * 22.0, // Header + 5 fields, falloff, delete
+
         super::Selection::SectorFace { room, x: gx, z: gz, face } => {
             let sector_data = state.level.rooms.get(*room)
                 .and_then(|r| r.get_sector(*gx, *gz));
@@ -872,24 +3602,10 @@ fn calculate_properties_content_height(selection: &super::Selection, state: &Edi
                             height += horizontal_face_container_height(ceiling) + CONTAINER_MARGIN;
                         }
                     }
-                    super::SectorFace::WallNorth(i) => {
-                        if let Some(wall) = sector.walls_north.get(*i) {
-                            height += wall_face_container_height(wall) + CONTAINER_MARGIN;
-                        }
-                    }
-                    super::SectorFace::WallEast(i) => {
-                        if let Some(wall) = sector.walls_east.get(*i) {
-                            height += wall_face_container_height(wall) + CONTAINER_MARGIN;
-                        }
-                    }
-                    super::SectorFace::WallSouth(i) => {
-                        if let Some(wall) = sector.walls_south.get(*i) {
-                            height += wall_face_container_height(wall) + CONTAINER_MARGIN;
-                        }
-                    }
-                    super::SectorFace::WallWest(i) => {
-                        if let Some(wall) = sector.walls_west.get(*i) {
-                            height += wall_face_container_height(wall) + CONTAINER_MARGIN;
+                    wall_face => {
+                        if let Some((_, wall)) = wall_face.wall_in(sector) {
+                            let is_door = wall_door(state, *room, *gx, *gz, *face).is_some();
+                            height += wall_face_container_height(wall, is_door) + CONTAINER_MARGIN;
                         }
                     }
                 }
@@ -898,49 +3614,26 @@ fn calculate_properties_content_height(selection: &super::Selection, state: &Edi
         }
 
         super::Selection::Sector { room, x: gx, z: gz } => {
-            let sector_data = state.level.rooms.get(*room)
-                .and_then(|r| r.get_sector(*gx, *gz));
-
-            let mut height = header_height;
-
-            if let Some(sector) = sector_data {
-                if let Some(floor) = &sector.floor {
-                    height += horizontal_face_container_height(floor) + CONTAINER_MARGIN;
-                }
-                if let Some(ceiling) = &sector.ceiling {
-                    height += horizontal_face_container_height(ceiling) + CONTAINER_MARGIN;
-                }
-                for wall in &sector.walls_north {
-                    height += wall_face_container_height(wall) + CONTAINER_MARGIN;
-                }
-                for wall in &sector.walls_east {
-                    height += wall_face_container_height(wall) + CONTAINER_MARGIN;
-                }
-                for wall in &sector.walls_south {
-                    height += wall_face_container_height(wall) + CONTAINER_MARGIN;
-                }
-                for wall in &sector.walls_west {
-                    height += wall_face_container_height(wall) + CONTAINER_MARGIN;
-                }
-            }
-            height
+            let containers = sector_containers(state, *room, *gx, *gz);
+            header_height + FACE_NAV_HEIGHT + containers.iter().map(|(_, h)| h + CONTAINER_MARGIN).sum::<f32>()
         }
     }
 }
 
-fn draw_status_bar(rect: Rect, state: &EditorState) {
-    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, Color::from_rgba(40, 40, 45, 255));
+fn draw_status_bar(ctx: &mut UiContext, rect: Rect, state: &mut EditorState) {
+    let theme = ctx.theme;
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, theme.bar_bg);
 
     // Show status message on the left if available
-    if let Some(msg) = state.get_status() {
-        draw_text(&msg, (rect.x + 10.0).floor(), (rect.y + 15.0).floor(), 16.0, Color::from_rgba(100, 255, 100, 255));
+    if let Some((msg, severity)) = state.get_status() {
+        draw_text(msg, (rect.x + 10.0).floor(), (rect.y + 15.0).floor(), 16.0, theme.severity(severity));
     }
 
     // Show keyboard shortcuts hint on the right (platform-specific)
     #[cfg(not(target_arch = "wasm32"))]
-    let hints = "Ctrl+S: Save | Ctrl+Shift+S: Save As | Ctrl+O: Open | Ctrl+N: New";
+    let hints = "Ctrl+S: Save | Ctrl+Shift+S: Save As | Ctrl+O: Open | Ctrl+N: New | F1: Help";
     #[cfg(target_arch = "wasm32")]
-    let hints = "Ctrl+S: Download | Ctrl+O: Upload | Ctrl+N: New";
+    let hints = "Ctrl+S: Download | Ctrl+O: Upload | Ctrl+N: New | F1: Help";
 
     let hint_width = hints.len() as f32 * 6.0; // Approximate width
     draw_text(
@@ -948,6 +3641,44 @@ fn draw_status_bar(rect: Rect, state: &EditorState) {
         (rect.right() - hint_width - 8.0).floor(),
         (rect.y + 15.0).floor(),
         14.0,
-        Color::from_rgba(100, 100, 100, 255),
+        theme.text_faint,
     );
+
+    // Broken texture references stay visible until they're fixed (see Clean Level)
+    let mut right = rect.right() - hint_width - 24.0;
+    if state.missing_texture_faces > 0 {
+        let text = format!("{} faces missing textures", state.missing_texture_faces);
+        let width = measure_text(&text, None, 14, 1.0).width;
+        draw_text(
+            &text,
+            (right - width).floor(),
+            (rect.y + 15.0).floor(),
+            14.0,
+            theme.warning,
+        );
+        right -= width + 16.0;
+    }
+
+    // Texture pack loading problems, for the whole session: click for the details
+    if let Some(report) = &state.pack_load_report {
+        let failures = report.failure_count();
+        let text = if failures > 0 {
+            format!("{} textures failed to load", failures)
+        } else {
+            "Texture pack warnings".to_string()
+        };
+        let width = measure_text(&text, None, 14, 1.0).width;
+        let link = Rect::new(right - width - 4.0, rect.y, width + 8.0, rect.h);
+        let hovered = ctx.mouse.inside(&link);
+        let color = if hovered { WHITE } else { theme.warning };
+        draw_text(&text, (right - width).floor(), (rect.y + 15.0).floor(), 14.0, color);
+        if hovered {
+            ctx.set_tooltip("Show what went wrong loading the texture packs", ctx.mouse.x, ctx.mouse.y);
+        }
+        if ctx.mouse.clicked(&link) {
+            let show = !state.show_pack_load_report;
+            close_level_popups(state);
+            state.show_pack_load_report = show;
+        }
+    }
 }
@@ -0,0 +1,49 @@
+//! Compare with File
+//!
+//! State for the Compare popup: the differences between a level file and the level
+//! being edited, listed so that clicking one selects its room or sector.
+
+use crate::world::{Level, LevelChange, LevelDiff, LevelDiffEntry};
+use super::{EditorState, Selection};
+
+/// Compare popup state
+#[derive(Debug, Clone)]
+pub struct LevelDiffView {
+    /// File compared against (shown in the popup title)
+    pub file_name: String,
+    /// From the file to the level being edited
+    pub diff: LevelDiff,
+    pub scroll: f32,
+}
+
+impl EditorState {
+    /// Compare the level being edited against `file_level` and open the popup
+    pub fn open_level_diff(&mut self, file_name: &str, file_level: &Level) {
+        let diff = file_level.diff(&self.level);
+        if diff.is_empty() {
+            self.set_status(&format!("No differences from {}", file_name), 3.0);
+        } else {
+            self.set_status(&format!("{} difference(s) from {}", diff.len(), file_name), 3.0);
+        }
+        self.level_diff = Some(LevelDiffView { file_name: file_name.to_string(), diff, scroll: 0.0 });
+    }
+
+    /// Select the room or sector a difference refers to in the level being edited
+    pub fn select_diff_entry(&mut self, entry: &LevelDiffEntry) {
+        let Some(id) = entry.room else { return };
+        let room = match self.level.room_index(id) {
+            Some(room) if entry.change != LevelChange::RoomRemoved => room,
+            _ => {
+                self.set_warning(&format!("Room {} is only in the file", id));
+                return;
+            }
+        };
+        self.current_room = room;
+        self.selection = match entry.sector {
+            Some((x, z)) if self.level.rooms[room].get_sector(x, z).is_some() => Selection::Sector { room, x, z },
+            _ => Selection::Room(room),
+        };
+        self.center_grid_on_selection();
+        self.frame_selection();
+    }
+}
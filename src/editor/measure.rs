@@ -0,0 +1,110 @@
+//! Measure tool
+//!
+//! Click to drop points, or drag to measure a single segment; every further click
+//! chains another segment from the last point. The grid measures on the ground
+//! plane, the 3D view between points picked on faces. Points snap to nearby vertex
+//! corners. The chain stays up until Escape or another tool is picked.
+
+use crate::rasterizer::Vec3;
+use crate::world::{CLICK_HEIGHT, SECTOR_SIZE};
+
+/// Screen distance (pixels) within which a point snaps to a vertex corner
+pub const MEASURE_SNAP_PX: f32 = 8.0;
+/// Mouse travel (pixels) between press and release that makes a drag
+const DRAG_PX: f32 = 4.0;
+
+/// Measure tool state
+#[derive(Debug, Clone, Default)]
+pub struct Measure {
+    /// Placed points (world space); consecutive points form segments
+    pub points: Vec<Vec3>,
+    /// Points were picked in the 3D view (else on the grid, where Y is unused)
+    pub in_3d: bool,
+    /// Screen position of the last press, until release
+    press: Option<(f32, f32)>,
+}
+
+impl Measure {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Mouse press: start a chain or extend it. A press in the other view starts over.
+    pub fn press(&mut self, point: Vec3, in_3d: bool, screen: (f32, f32)) {
+        if self.in_3d != in_3d {
+            self.points.clear();
+            self.in_3d = in_3d;
+        }
+        self.points.push(point);
+        self.press = Some(screen);
+    }
+
+    /// Mouse release: if the mouse moved since the press, the drag ends a segment at
+    /// `point` (None when there's nothing under the mouse to measure to)
+    pub fn release(&mut self, point: Option<Vec3>, screen: (f32, f32)) {
+        if let (Some((x, y)), Some(point)) = (self.press.take(), point) {
+            if (screen.0 - x).hypot(screen.1 - y) > DRAG_PX {
+                self.points.push(point);
+            }
+        }
+    }
+
+    /// Is a press waiting for its release?
+    pub fn pressed(&self) -> bool {
+        self.press.is_some()
+    }
+
+    /// Placed segments as (start, end)
+    pub fn segments(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        self.points.windows(2).map(|w| (w[0], w[1]))
+    }
+
+    /// Length of the whole chain, plus the rubber band from the last point to `cursor`
+    pub fn total(&self, cursor: Option<Vec3>) -> f32 {
+        let length = |a: Vec3, b: Vec3| if self.in_3d { (b - a).len() } else { (b.x - a.x).hypot(b.z - a.z) };
+        let placed: f32 = self.segments().map(|(a, b)| length(a, b)).sum();
+        let band = self.points.last().zip(cursor).map_or(0.0, |(&a, b)| length(a, b));
+        placed + band
+    }
+}
+
+/// Grid label: length in sectors and world units, then the X/Z extents
+pub fn grid_label(a: Vec3, b: Vec3) -> String {
+    let (dx, dz) = (b.x - a.x, b.z - a.z);
+    let length = dx.hypot(dz);
+    format!("{:.2} sec ({:.0})  dX {:.0}  dZ {:.0}", length / SECTOR_SIZE, length, dx, dz)
+}
+
+/// 3D label: straight-line distance, height difference in clicks, horizontal distance
+pub fn label_3d(a: Vec3, b: Vec3) -> String {
+    let d = b - a;
+    format!(
+        "{:.0} ({:.2} sec)  dY {:+.2} clicks  horiz {:.0}",
+        d.len(), d.len() / SECTOR_SIZE, d.y / CLICK_HEIGHT, d.x.hypot(d.z)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_chain_and_drags_close_a_segment() {
+        let mut m = Measure::default();
+        // Drag: press and release far apart make one segment
+        m.press(Vec3::ZERO, false, (0.0, 0.0));
+        m.release(Some(Vec3::new(SECTOR_SIZE, 0.0, 0.0)), (50.0, 0.0));
+        // Click: extends the chain
+        m.press(Vec3::new(SECTOR_SIZE, 0.0, SECTOR_SIZE), false, (50.0, 50.0));
+        m.release(Some(Vec3::new(SECTOR_SIZE, 0.0, SECTOR_SIZE)), (51.0, 50.0));
+        assert_eq!(m.segments().count(), 2);
+        assert_eq!(m.total(None), 2.0 * SECTOR_SIZE);
+        assert_eq!(m.total(Some(Vec3::new(SECTOR_SIZE, 0.0, 3.0 * SECTOR_SIZE))), 4.0 * SECTOR_SIZE);
+
+        // A press in the 3D view starts a new chain; there Y counts
+        m.press(Vec3::ZERO, true, (0.0, 0.0));
+        assert_eq!(m.points.len(), 1);
+        assert_eq!(m.total(Some(Vec3::new(0.0, 2.0 * CLICK_HEIGHT, 0.0))), 2.0 * CLICK_HEIGHT);
+        assert_eq!(label_3d(Vec3::ZERO, Vec3::new(0.0, -CLICK_HEIGHT, 0.0)), "256 (0.25 sec)  dY -1.00 clicks  horiz 0");
+    }
+}
@@ -0,0 +1,176 @@
+//! Missing Texture Packs
+//!
+//! When a level is loaded, the packs its faces use are checked against the loaded
+//! ones. Missing packs are listed with the faces they affect; each can keep its
+//! "MISSING" placeholders or borrow a loaded pack's textures of the same name. A
+//! substitute is a pack alias on the level (`Level::pack_aliases`), so it's undoable
+//! and saved with the level.
+
+use macroquad::prelude::*;
+use crate::ui::{draw_button, Rect, UiContext, ACCENT_COLOR};
+use super::{EditorState, TexturePack};
+
+/// Most missing packs listed in the dialog (the summary still counts all of them)
+const MAX_ROWS: usize = 10;
+
+/// Packs the loaded level uses that no loaded pack matches
+pub struct MissingPacks {
+    /// Pack name and faces using it, by name
+    pub packs: Vec<(String, usize)>,
+    /// Substitute picked for each pack (index into the loaded packs)
+    pub substitutes: Vec<Option<usize>>,
+}
+
+impl MissingPacks {
+    pub fn new(packs: Vec<(String, usize)>) -> Self {
+        Self { substitutes: vec![None; packs.len()], packs }
+    }
+
+    /// e.g. "Missing packs: GOTHIC, 42 faces affected"
+    pub fn summary(&self) -> String {
+        let names: Vec<&str> = self.packs.iter().map(|(name, _)| name.as_str()).collect();
+        let faces: usize = self.packs.iter().map(|(_, faces)| faces).sum();
+        let plural = if faces == 1 { "" } else { "s" };
+        format!("Missing packs: {}, {} face{} affected", names.join(", "), faces, plural)
+    }
+}
+
+/// Next choice when the substitute button is clicked: placeholders, then each loaded pack
+fn next_substitute(current: Option<usize>, pack_count: usize) -> Option<usize> {
+    match current {
+        None if pack_count > 0 => Some(0),
+        Some(i) if i + 1 < pack_count => Some(i + 1),
+        _ => None,
+    }
+}
+
+impl EditorState {
+    /// Check the level's texture packs against the loaded ones and open the dialog
+    /// if any are missing (called when a level is loaded)
+    pub fn check_texture_packs(&mut self) {
+        let packs = &self.texture_packs;
+        let missing = self.level.missing_packs(|name| packs.iter().any(|p| p.name == name));
+        self.missing_packs = (!missing.is_empty()).then(|| MissingPacks::new(missing));
+    }
+
+    /// Close the dialog, aliasing each missing pack to its picked substitute (one
+    /// undo step). Packs without one keep their placeholders.
+    pub fn apply_pack_substitutes(&mut self) {
+        let Some(dialog) = self.missing_packs.take() else { return };
+        let aliases: Vec<(String, String)> = dialog.packs.iter().zip(&dialog.substitutes)
+            .filter_map(|((pack, _), sub)| Some((pack.clone(), self.texture_packs.get((*sub)?)?.name.clone())))
+            .collect();
+        if aliases.is_empty() {
+            return;
+        }
+        self.save_undo("Substitute texture packs");
+        let summary: Vec<String> = aliases.iter().map(|(pack, sub)| format!("{} -> {}", pack, sub)).collect();
+        self.level.pack_aliases.extend(aliases);
+        self.mark_all_rooms_dirty();
+        self.set_status(&format!("Texture packs substituted: {} (saved with the level)", summary.join(", ")), 4.0);
+    }
+}
+
+/// Result from drawing the missing packs dialog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingPacksAction {
+    None,
+    /// Keep the placeholders for every pack
+    Continue,
+    /// Apply the picked substitutes
+    Substitute,
+}
+
+/// Draw the missing packs modal dialog
+pub fn draw_missing_packs(ctx: &mut UiContext, dialog: &mut MissingPacks, packs: &[TexturePack]) -> MissingPacksAction {
+    let mut action = MissingPacksAction::None;
+    let theme = ctx.theme;
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    let row_h = 28.0;
+    let rows = dialog.packs.len().min(MAX_ROWS);
+    let more = dialog.packs.len() - rows;
+    let dialog_w = 520.0;
+    let dialog_h = 40.0 + 56.0 + rows as f32 * row_h + if more > 0 { 20.0 } else { 0.0 } + 48.0;
+    let dialog_x = ((screen_width() - dialog_w) / 2.0).floor();
+    let dialog_y = ((screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(dialog_x, dialog_y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(dialog_x, dialog_y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    let header_h = 40.0;
+    draw_rectangle(dialog_x, dialog_y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    draw_text("Missing Texture Packs", dialog_x + 16.0, dialog_y + 26.0, 20.0, WHITE);
+
+    let mut y = dialog_y + header_h + 20.0;
+    draw_text(&dialog.summary(), dialog_x + 16.0, y, 14.0, theme.warning);
+    y += 20.0;
+    draw_text("Pick a loaded pack to borrow same-named textures from, or keep the placeholders.",
+        dialog_x + 16.0, y, 13.0, theme.text_dim);
+    y += 16.0;
+
+    // One row per pack: name, faces, substitute (click to cycle)
+    for i in 0..rows {
+        let (name, faces) = &dialog.packs[i];
+        let text_y = (y + 18.0).floor();
+        draw_text(name, dialog_x + 16.0, text_y, 14.0, WHITE);
+        draw_text(&format!("{} faces", faces), dialog_x + 190.0, text_y, 14.0, theme.text_dim);
+
+        let button = Rect::new(dialog_x + dialog_w - 216.0, y + 3.0, 200.0, row_h - 6.0);
+        let label = match dialog.substitutes[i].and_then(|s| packs.get(s)) {
+            Some(pack) => format!("Use {}", pack.name),
+            None => "Keep placeholders".to_string(),
+        };
+        let active = dialog.substitutes[i].is_some();
+        if draw_button(ctx, button, &label, if active { ACCENT_COLOR } else { Color::from_rgba(60, 60, 70, 255) }) {
+            dialog.substitutes[i] = next_substitute(dialog.substitutes[i], packs.len());
+        }
+        if ctx.mouse.inside(&button) {
+            ctx.set_tooltip("Click to cycle through the loaded packs", ctx.mouse.x, ctx.mouse.y);
+        }
+        y += row_h;
+    }
+    if more > 0 {
+        draw_text(&format!("...and {} more (keeping placeholders)", more), dialog_x + 16.0, y + 14.0, 13.0, theme.text_dim);
+    }
+
+    // Footer buttons
+    let footer_y = dialog_y + dialog_h - 40.0;
+    let continue_rect = Rect::new(dialog_x + dialog_w - 316.0, footer_y + 6.0, 170.0, 28.0);
+    if draw_button(ctx, continue_rect, "Continue with Fallbacks", Color::from_rgba(60, 60, 70, 255)) {
+        action = MissingPacksAction::Continue;
+    }
+    let apply_rect = Rect::new(dialog_x + dialog_w - 136.0, footer_y + 6.0, 120.0, 28.0);
+    if draw_button(ctx, apply_rect, "Use Substitutes", ACCENT_COLOR) {
+        action = MissingPacksAction::Substitute;
+    }
+
+    // Keyboard: Enter applies, Escape keeps the placeholders
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+        action = MissingPacksAction::Substitute;
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        action = MissingPacksAction::Continue;
+    }
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_and_substitute_cycle() {
+        let dialog = MissingPacks::new(vec![("GOTHIC".to_string(), 41), ("TOMB".to_string(), 1)]);
+        assert_eq!(dialog.summary(), "Missing packs: GOTHIC, TOMB, 42 faces affected");
+        assert_eq!(dialog.substitutes, vec![None, None]);
+
+        let picks: Vec<_> = std::iter::successors(Some(None), |&s| Some(next_substitute(s, 2))).take(4).collect();
+        assert_eq!(picks, vec![None, Some(0), Some(1), None]);
+        assert_eq!(next_substitute(None, 0), None);
+    }
+}
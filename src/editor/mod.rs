@@ -5,18 +5,56 @@
 //! - 3D viewport (software rendered preview)
 //! - Texture palette
 //! - Properties panel
+//! - Frame profiler overlay (F11)
+//! - Playtest view (Play)
+//! - Ambient sound playback in playtests
 
 mod state;
 mod layout;
 mod grid_view;
+mod grid_iso;
 mod viewport_3d;
 mod texture_palette;
 mod texture_pack;
 mod example_levels;
 mod example_browser;
+mod template_chooser;
+mod game_view;
+mod profiler;
+mod zip;
+mod prefs;
+mod texture_report;
+mod texture_replace;
+mod level_diff;
+mod find_faces;
+mod stairs;
+mod measure;
+mod two_click;
+mod wall_run;
+mod room_thumbnails;
+mod room_cache;
+mod uv_preview;
+mod prefabs;
+mod missing_packs;
+mod crash_save;
+mod view_pan;
+mod view_axes;
+mod ambient_audio;
+#[cfg(not(target_arch = "wasm32"))]
+mod web_export;
 
 pub use state::*;
+pub use prefs::HeightSnap;
 pub use layout::*;
-pub use texture_pack::TexturePack;
+pub use texture_pack::{memory_summary, texture_index, PaletteMode, TexturePack, MAX_UPLOAD_RGBA_BYTES};
+pub use zip::read_zip;
+#[cfg(not(target_arch = "wasm32"))]
+pub use web_export::WebBundle;
 pub use example_levels::*;
 pub use example_browser::*;
+pub use prefabs::discover_prefabs;
+pub use missing_packs::{draw_missing_packs, MissingPacksAction};
+pub use crash_save::{draw_crash_recovery, install_panic_hook, track_crash_snapshot, CrashRecovery, RecoveryAction};
+pub use template_chooser::*;
+pub use game_view::{draw_game_view, GameViewAction, PauseMenu};
+pub use ambient_audio::{AmbientPlayer, SoundLibrary};
@@ -0,0 +1,109 @@
+//! Room prefabs
+//!
+//! A room saved on its own in `assets/prefabs/<name>.ron` (geometry only, see
+//! `RoomPrefab`) and stamped into other levels as a new room: next to the current
+//! room, or where the 2D grid is clicked. Prefab files are loaded with the same
+//! limits as level files.
+
+use std::path::PathBuf;
+use crate::rasterizer::Vec3;
+use crate::world::{RoomPrefab, SECTOR_SIZE};
+use super::example_levels::ExampleLevelInfo;
+use super::EditorState;
+
+/// Directory the prefab library lives in
+pub const PREFAB_DIR: &str = "assets/prefabs";
+
+/// File name (without extension) for a prefab called `name`: lowercase letters,
+/// digits, '-' and '_', everything else becomes '_'
+pub fn prefab_file_stem(name: &str) -> String {
+    let stem: String = name.trim().chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if stem.is_empty() { "prefab".to_string() } else { stem }
+}
+
+/// Load every prefab in the library, sorted by name. Files that fail to load are
+/// skipped (and reported on stderr).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn discover_prefabs() -> Vec<(ExampleLevelInfo, RoomPrefab)> {
+    let mut prefabs = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(PREFAB_DIR) {
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if !path.is_file() || path.extension().is_none_or(|e| e != "ron") {
+                continue;
+            }
+            let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            match crate::world::load_prefab(&path) {
+                Ok(prefab) => prefabs.push((ExampleLevelInfo { name, path }, prefab)),
+                Err(e) => eprintln!("Skipping prefab {}: {}", path.display(), e),
+            }
+        }
+    }
+    prefabs.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+    prefabs
+}
+
+/// No prefab library in the browser
+#[cfg(target_arch = "wasm32")]
+pub fn discover_prefabs() -> Vec<(ExampleLevelInfo, RoomPrefab)> {
+    Vec::new()
+}
+
+impl EditorState {
+    /// Save the current room to the prefab library, named after the room
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_room_as_prefab(&mut self) {
+        let Some(room) = self.current_room() else { return };
+        let stem = prefab_file_stem(&room.display_name());
+        let path = PathBuf::from(PREFAB_DIR).join(format!("{}.ron", stem));
+        let prefab = RoomPrefab::from_room(room);
+        let replaced = path.exists();
+        match crate::world::save_prefab(&prefab, &path) {
+            Ok(()) if replaced => self.set_status(&format!("Replaced prefab {}", path.display()), 3.0),
+            Ok(()) => self.set_status(&format!("Saved prefab {}", path.display()), 3.0),
+            Err(e) => self.set_error(&format!("Save prefab failed: {}", e)),
+        }
+    }
+
+    /// Where Insert Prefab puts a room: one room-width along +X from the current
+    /// room, like Duplicate (the origin if there's no room)
+    pub fn prefab_spot(&self) -> Vec3 {
+        match self.current_room() {
+            Some(room) => Vec3::new(room.position.x + room.width as f32 * SECTOR_SIZE, room.position.y, room.position.z),
+            None => Vec3::ZERO,
+        }
+    }
+
+    /// Stamp a prefab into the level as a new room with its grid origin at
+    /// `position` and make it the current room. One undo step.
+    pub fn insert_prefab(&mut self, name: &str, prefab: &RoomPrefab, position: Vec3) {
+        self.save_undo(&format!("Insert prefab {}", name));
+        let idx = prefab.stamp(&mut self.level, position);
+        self.mark_room_dirty(idx);
+        self.current_room = idx;
+        self.selection = super::Selection::None;
+        self.clear_multi_selection();
+        self.room_selection.clear();
+        self.set_status(&format!("Inserted prefab {} as room {}", name, idx), 3.0);
+    }
+
+    /// Stamp the prefab waiting for a grid click at `position`
+    pub fn place_prefab(&mut self, position: Vec3) {
+        if let Some((name, prefab)) = self.prefab_placement.take() {
+            self.insert_prefab(&name, &prefab, position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stems_are_safe() {
+        assert_eq!(prefab_file_stem("Save Room"), "save_room");
+        assert_eq!(prefab_file_stem("../alcove-2"), "___alcove-2");
+        assert_eq!(prefab_file_stem("  "), "prefab");
+    }
+}
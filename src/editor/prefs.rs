@@ -0,0 +1,200 @@
+//! Editor Preferences - per-user settings that outlive a level
+//!
+//! Stored as RON in the user's config directory (native). The web build has no
+//! file system, so it always starts from the defaults.
+
+use serde::{Serialize, Deserialize};
+use crate::ui::ThemeKind;
+use super::CLICK_HEIGHT;
+
+/// Height increment for vertex drags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HeightSnap {
+    #[default]
+    Click,
+    Half,
+    Quarter,
+    Free,
+}
+
+impl HeightSnap {
+    /// Snap increment in world units (None = free)
+    pub fn increment(self) -> Option<f32> {
+        match self {
+            HeightSnap::Click => Some(CLICK_HEIGHT),
+            HeightSnap::Half => Some(CLICK_HEIGHT / 2.0),
+            HeightSnap::Quarter => Some(CLICK_HEIGHT / 4.0),
+            HeightSnap::Free => None,
+        }
+    }
+
+    /// Round a height to this increment
+    pub fn apply(self, height: f32) -> f32 {
+        match self.increment() {
+            Some(step) => (height / step).round() * step,
+            None => height.round(),
+        }
+    }
+
+    /// Next setting when cycling (toolbar button / G)
+    pub fn next(self) -> Self {
+        match self {
+            HeightSnap::Click => HeightSnap::Half,
+            HeightSnap::Half => HeightSnap::Quarter,
+            HeightSnap::Quarter => HeightSnap::Free,
+            HeightSnap::Free => HeightSnap::Click,
+        }
+    }
+
+    /// Display name, e.g. "Half (128)"
+    pub fn label(self) -> String {
+        match self {
+            HeightSnap::Click => format!("Click ({})", CLICK_HEIGHT as i32),
+            HeightSnap::Half => format!("Half ({})", (CLICK_HEIGHT / 2.0) as i32),
+            HeightSnap::Quarter => format!("Quarter ({})", (CLICK_HEIGHT / 4.0) as i32),
+            HeightSnap::Free => "Free".to_string(),
+        }
+    }
+}
+
+/// Overlays drawn over the level in the 3D viewport (toolbar eye popover)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewportLayers {
+    /// Vertex dots and hover/selection outlines
+    pub wireframe: bool,
+    /// Portal outlines (and their labels)
+    pub portals: bool,
+    /// Pickup sprites
+    pub objects: bool,
+    /// Bounding box of the current room
+    pub room_bounds: bool,
+    /// Invisible faces as translucent ghosts, so they can still be picked and edited
+    pub invisible: bool,
+}
+
+impl Default for ViewportLayers {
+    fn default() -> Self {
+        Self { wireframe: true, portals: true, objects: true, room_bounds: true, invisible: true }
+    }
+}
+
+impl ViewportLayers {
+    /// Geometry only (Isolate)
+    pub const NONE: ViewportLayers = ViewportLayers { wireframe: false, portals: false, objects: false, room_bounds: false, invisible: false };
+
+    /// Popover rows: label and flag
+    pub fn toggles(&mut self) -> [(&'static str, &mut bool); 5] {
+        [
+            ("Wireframe overlays", &mut self.wireframe),
+            ("Portal markers", &mut self.portals),
+            ("Object sprites", &mut self.objects),
+            ("Room bounds", &mut self.room_bounds),
+            ("Invisible geometry", &mut self.invisible),
+        ]
+    }
+}
+
+/// Saved editor preferences (missing fields fall back to defaults)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorPrefs {
+    pub height_snap: HeightSnap,
+    /// 3D viewport field of view in degrees
+    pub viewport_fov: f32,
+    /// UI color theme
+    pub theme: ThemeKind,
+    /// Texture palette thumbnail size in pixels
+    pub palette_thumb_size: f32,
+    /// 3D viewport overlays
+    pub viewport_layers: ViewportLayers,
+    /// 3D camera WASD speed in units per second
+    pub camera_fly_speed: f32,
+    /// Toolbar buttons show text labels instead of icons
+    pub text_icons: bool,
+    /// 2D grid panel shows the isometric preview instead of top-down
+    pub grid_isometric: bool,
+}
+
+impl Default for EditorPrefs {
+    fn default() -> Self {
+        Self {
+            height_snap: HeightSnap::default(),
+            viewport_fov: crate::rasterizer::DEFAULT_FOV_DEGREES,
+            theme: ThemeKind::default(),
+            palette_thumb_size: super::texture_palette::DEFAULT_THUMB_SIZE,
+            viewport_layers: ViewportLayers::default(),
+            camera_fly_speed: super::state::DEFAULT_FLY_SPEED,
+            text_icons: false,
+            grid_isometric: false,
+        }
+    }
+}
+
+/// The editor's config directory (None if the platform has none)
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn config_dir() -> Option<std::path::PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| std::env::var_os("APPDATA"))
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| std::path::PathBuf::from(home).join(".config")))?;
+    Some(base.join("bonnie-engine"))
+}
+
+impl EditorPrefs {
+    /// Location of the prefs file (None if there's no config directory)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn path() -> Option<std::path::PathBuf> {
+        Some(config_dir()?.join("editor_prefs.ron"))
+    }
+
+    /// Load saved prefs (defaults if missing or unreadable)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|s| ron::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        Self::default()
+    }
+
+    /// Write prefs to disk (failures are reported but not fatal)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|s| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&path, s).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!("Failed to save editor prefs to {}: {}", path.display(), e);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_rounds_to_increment() {
+        assert_eq!(HeightSnap::Click.apply(300.0), 256.0);
+        assert_eq!(HeightSnap::Half.apply(300.0), 256.0);
+        assert_eq!(HeightSnap::Quarter.apply(300.0), 320.0);
+        assert_eq!(HeightSnap::Free.apply(300.4), 300.0);
+        assert_eq!(HeightSnap::Free.next(), HeightSnap::Click);
+    }
+}
@@ -0,0 +1,252 @@
+//! Frame Profiler - Per-phase timings for the editor
+//!
+//! `profile_scope!("name")` times the rest of the enclosing block and adds it to the
+//! current frame. Finished frames go into a ring buffer that the overlay (F11) draws
+//! as a stacked ms bar graph. While the overlay is hidden the timers only read a bool.
+
+use std::cell::{Cell, RefCell};
+use macroquad::prelude::*;
+use crate::ui::Rect;
+
+/// Number of frames kept for the bar graph
+pub const HISTORY_FRAMES: usize = 120;
+
+/// Maximum number of distinct phases (extra phases are ignored)
+const MAX_PHASES: usize = 8;
+
+/// Frame time mapped to the full graph height (30 fps)
+const GRAPH_MAX_MS: f32 = 1000.0 / 30.0;
+
+/// Phase colors, in registration order
+const PHASE_COLORS: [Color; MAX_PHASES] = [
+    Color::new(0.35, 0.75, 1.0, 1.0),
+    Color::new(1.0, 0.55, 0.25, 1.0),
+    Color::new(0.45, 0.9, 0.45, 1.0),
+    Color::new(0.9, 0.4, 0.85, 1.0),
+    Color::new(1.0, 0.85, 0.3, 1.0),
+    Color::new(0.4, 0.9, 0.85, 1.0),
+    Color::new(0.95, 0.35, 0.4, 1.0),
+    Color::new(0.65, 0.6, 1.0, 1.0),
+];
+
+/// Time spent outside any profiled phase
+const OTHER_COLOR: Color = Color::new(0.35, 0.35, 0.4, 1.0);
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
+}
+
+/// One recorded frame: ms per phase plus the whole frame time
+#[derive(Clone, Copy, Default)]
+struct FrameSample {
+    phases: [f32; MAX_PHASES],
+    total: f32,
+}
+
+struct Profiler {
+    /// Phase names in the order they were first seen (index = color/stack slot)
+    phases: Vec<&'static str>,
+    /// Accumulated ms for the frame in progress
+    current: [f32; MAX_PHASES],
+    /// Ring buffer of finished frames
+    history: Vec<FrameSample>,
+    /// Next slot to write once the ring buffer is full
+    head: usize,
+    /// Extra line under the legend (room rebuilds, level stats)
+    note: String,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Self {
+            phases: Vec::with_capacity(MAX_PHASES),
+            current: [0.0; MAX_PHASES],
+            history: Vec::with_capacity(HISTORY_FRAMES),
+            head: 0,
+            note: String::new(),
+        }
+    }
+
+    fn record(&mut self, name: &'static str, ms: f32) {
+        let slot = match self.phases.iter().position(|&p| p == name) {
+            Some(i) => i,
+            None if self.phases.len() < MAX_PHASES => {
+                self.phases.push(name);
+                self.phases.len() - 1
+            }
+            None => return,
+        };
+        self.current[slot] += ms;
+    }
+
+    fn push_frame(&mut self, total: f32) {
+        let sample = FrameSample { phases: self.current, total };
+        if self.history.len() < HISTORY_FRAMES {
+            self.history.push(sample);
+        } else {
+            self.history[self.head] = sample;
+            self.head = (self.head + 1) % HISTORY_FRAMES;
+        }
+        self.current = [0.0; MAX_PHASES];
+    }
+
+    fn clear(&mut self) {
+        self.current = [0.0; MAX_PHASES];
+        self.history.clear();
+        self.head = 0;
+    }
+
+    /// Frames from oldest to newest
+    fn frames(&self) -> impl Iterator<Item = &FrameSample> {
+        let (newer, older) = self.history.split_at(self.head);
+        older.iter().chain(newer.iter())
+    }
+}
+
+/// Is the profiler collecting timings?
+pub fn is_enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+/// Show/hide the overlay. History starts fresh each time it's shown.
+pub fn toggle() {
+    let enabled = !is_enabled();
+    ENABLED.with(|e| e.set(enabled));
+    if enabled {
+        PROFILER.with(|p| p.borrow_mut().clear());
+    }
+}
+
+/// Close the previous frame (call once per frame, before any profiled scope)
+pub fn begin_frame() {
+    if !is_enabled() {
+        return;
+    }
+    let total = get_frame_time() * 1000.0;
+    PROFILER.with(|p| p.borrow_mut().push_frame(total));
+}
+
+/// Set the line shown under the legend for this frame
+pub fn set_note(note: String) {
+    PROFILER.with(|p| p.borrow_mut().note = note);
+}
+
+/// Timer created by `profile_scope!`; records its lifetime on drop
+pub struct ScopeTimer {
+    name: &'static str,
+    start: Option<f64>,
+}
+
+impl ScopeTimer {
+    #[inline]
+    pub fn new(name: &'static str) -> Self {
+        let start = if is_enabled() { Some(get_time()) } else { None };
+        Self { name, start }
+    }
+}
+
+impl Drop for ScopeTimer {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            let ms = ((get_time() - start) * 1000.0) as f32;
+            PROFILER.with(|p| p.borrow_mut().record(self.name, ms));
+        }
+    }
+}
+
+/// Time the rest of the enclosing block under the given phase name
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::editor::profiler::ScopeTimer::new($name);
+    };
+}
+pub(crate) use profile_scope;
+
+/// Draw the profiler overlay in the top-right corner of `bounds`
+pub fn draw_overlay(bounds: Rect) {
+    if !is_enabled() {
+        return;
+    }
+
+    PROFILER.with(|p| {
+        let profiler = p.borrow();
+
+        let bar_w = 2.0;
+        let graph_w = HISTORY_FRAMES as f32 * bar_w;
+        let graph_h = 80.0;
+        let line_h = 14.0;
+        let padding = 6.0;
+        let legend_h = (profiler.phases.len() + 3) as f32 * line_h;
+        let w = graph_w + padding * 2.0;
+        let h = graph_h + legend_h + padding * 3.0;
+        let x = (bounds.right() - w - 8.0).floor();
+        let y = (bounds.y + 8.0).floor();
+
+        draw_rectangle(x, y, w, h, Color::from_rgba(15, 15, 20, 220));
+        draw_rectangle_lines(x, y, w, h, 1.0, Color::from_rgba(70, 70, 85, 255));
+
+        // Stacked bars, oldest on the left
+        let graph_x = x + padding;
+        let graph_bottom = y + padding + graph_h;
+        let scale = graph_h / GRAPH_MAX_MS;
+        for (i, frame) in profiler.frames().enumerate() {
+            let bar_x = graph_x + i as f32 * bar_w;
+            let mut top = graph_bottom;
+            let mut accounted = 0.0;
+            for (slot, &ms) in frame.phases.iter().enumerate().take(profiler.phases.len()) {
+                let seg = (ms * scale).min(top - (graph_bottom - graph_h));
+                if seg > 0.0 {
+                    top -= seg;
+                    draw_rectangle(bar_x, top, bar_w, seg, PHASE_COLORS[slot]);
+                }
+                accounted += ms;
+            }
+            let other = ((frame.total - accounted).max(0.0) * scale).min(top - (graph_bottom - graph_h));
+            if other > 0.0 {
+                draw_rectangle(bar_x, top - other, bar_w, other, OTHER_COLOR);
+            }
+        }
+
+        // 60 fps budget line
+        let budget_y = (graph_bottom - 1000.0 / 60.0 * scale).floor();
+        draw_line(graph_x, budget_y, graph_x + graph_w, budget_y, 1.0, Color::from_rgba(255, 255, 255, 80));
+
+        // Legend: last frame and average over the history
+        let count = profiler.history.len().max(1) as f32;
+        let last = profiler.frames().last().copied().unwrap_or_default();
+
+        let mut text_y = graph_bottom + padding + 11.0;
+        let avg_total = profiler.frames().map(|f| f.total).sum::<f32>() / count;
+        draw_text(
+            &format!("frame  {:5.2} ms  avg {:5.2} ms", last.total, avg_total),
+            graph_x.floor(), text_y.floor(), 13.0, WHITE,
+        );
+        text_y += line_h;
+
+        let mut last_accounted = 0.0;
+        let mut avg_accounted = 0.0;
+        for (slot, name) in profiler.phases.iter().enumerate() {
+            let avg = profiler.frames().map(|f| f.phases[slot]).sum::<f32>() / count;
+            last_accounted += last.phases[slot];
+            avg_accounted += avg;
+            draw_rectangle(graph_x, text_y - 8.0, 8.0, 8.0, PHASE_COLORS[slot]);
+            draw_text(
+                &format!("{:<10} {:5.2} ms  avg {:5.2} ms", name, last.phases[slot], avg),
+                (graph_x + 12.0).floor(), text_y.floor(), 13.0, Color::from_rgba(220, 220, 220, 255),
+            );
+            text_y += line_h;
+        }
+
+        draw_rectangle(graph_x, text_y - 8.0, 8.0, 8.0, OTHER_COLOR);
+        draw_text(
+            &format!("{:<10} {:5.2} ms  avg {:5.2} ms", "other",
+                (last.total - last_accounted).max(0.0), (avg_total - avg_accounted).max(0.0)),
+            (graph_x + 12.0).floor(), text_y.floor(), 13.0, Color::from_rgba(160, 160, 160, 255),
+        );
+        text_y += line_h;
+
+        draw_text(&profiler.note, graph_x.floor(), text_y.floor(), 13.0, Color::from_rgba(160, 160, 160, 255));
+    });
+}
@@ -0,0 +1,114 @@
+//! Per-room caches keyed by edit generation
+//!
+//! Every change to a room stamps it with a fresh generation (see
+//! `EditorState::mark_room_dirty`), unique across the level. A `RoomCache` keeps one
+//! derived value per room index and rebuilds only the rooms whose generation moved,
+//! so editing one room leaves the others' render data and stats alone. Undo/redo
+//! swap in rooms with their old generations, which also differ from the cached ones.
+
+use crate::world::Room;
+
+/// Derived data per room index, rebuilt when the room's generation changes
+pub struct RoomCache<T> {
+    /// (generation built from, value) per room index
+    entries: Vec<Option<(u64, T)>>,
+    /// Everything besides the room the values depend on (texture ids, settings)
+    key: u64,
+    /// Rooms built since the last `begin_frame`
+    rebuilt: usize,
+}
+
+impl<T> RoomCache<T> {
+    pub fn new() -> Self {
+        Self { entries: Vec::new(), key: 0, rebuilt: 0 }
+    }
+
+    /// Start a frame: follow the room count, drop everything if `key` changed
+    /// and reset the rebuild count
+    pub fn begin_frame(&mut self, room_count: usize, key: u64) {
+        if key != self.key {
+            self.entries.clear();
+            self.key = key;
+        }
+        self.entries.resize_with(room_count, || None);
+        self.rebuilt = 0;
+    }
+
+    /// Cached value for a room, built first if the room changed since
+    pub fn get_or_build(&mut self, room_idx: usize, room: &Room, build: impl FnOnce(&Room) -> T) -> &T {
+        if room_idx >= self.entries.len() {
+            self.entries.resize_with(room_idx + 1, || None);
+        }
+        let slot = &mut self.entries[room_idx];
+        if slot.as_ref().is_some_and(|(generation, _)| *generation != room.generation) {
+            *slot = None;
+        }
+        let rebuilt = &mut self.rebuilt;
+        &slot.get_or_insert_with(|| {
+            *rebuilt += 1;
+            (room.generation, build(room))
+        }).1
+    }
+
+    /// Cached value for a room, if it's up to date
+    pub fn get(&self, room_idx: usize, room: &Room) -> Option<&T> {
+        self.entries.get(room_idx)?.as_ref()
+            .filter(|(generation, _)| *generation == room.generation)
+            .map(|(_, value)| value)
+    }
+
+    /// Rooms built since the last `begin_frame`
+    pub fn rebuilt(&self) -> usize {
+        self.rebuilt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+
+    fn rooms(count: usize) -> Vec<Room> {
+        (0..count).map(|i| {
+            let mut room = Room::new(i, Vec3::ZERO, 1, 1);
+            room.generation = i as u64 + 1;
+            room
+        }).collect()
+    }
+
+    fn build_all(cache: &mut RoomCache<usize>, rooms: &[Room]) -> usize {
+        cache.begin_frame(rooms.len(), 0);
+        for (i, room) in rooms.iter().enumerate() {
+            cache.get_or_build(i, room, |r| r.id);
+        }
+        cache.rebuilt()
+    }
+
+    #[test]
+    fn rebuilds_only_changed_rooms() {
+        let mut rooms = rooms(20);
+        let mut cache = RoomCache::new();
+        assert_eq!(build_all(&mut cache, &rooms), 20);
+        assert_eq!(build_all(&mut cache, &rooms), 0);
+
+        rooms[7].generation = 100;
+        assert_eq!(cache.get(7, &rooms[7]), None);
+        assert_eq!(cache.get(6, &rooms[6]), Some(&6));
+        assert_eq!(build_all(&mut cache, &rooms), 1);
+    }
+
+    #[test]
+    fn reorder_and_key_change_rebuild() {
+        let mut rooms = rooms(3);
+        let mut cache = RoomCache::new();
+        build_all(&mut cache, &rooms);
+
+        rooms.swap(0, 2);
+        assert_eq!(build_all(&mut cache, &rooms), 2);
+        assert_eq!(*cache.get_or_build(0, &rooms[0], |_| 99), 2);
+
+        cache.begin_frame(rooms.len(), 1);
+        cache.get_or_build(1, &rooms[1], |r| r.id);
+        assert_eq!(cache.rebuilt(), 1);
+    }
+}
@@ -0,0 +1,173 @@
+//! Room list thumbnails
+//!
+//! Small software-rendered views of each room, cached as textures by room id. At most
+//! one thumbnail is rendered per frame so a long room list doesn't hitch. Each frame the
+//! current room and one other (in turn) are checked for geometry changes, and changed
+//! rooms are re-rendered when next shown. The cache evicts the least recently shown.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use macroquad::prelude::*;
+use crate::rasterizer::{render_mesh_with_scratch, Camera, Framebuffer, RasterSettings, RenderScratch, Texture as RasterTexture, Vec3};
+use crate::world::{Level, Room, TextureRef, SECTOR_SIZE};
+use super::texture_pack::{texture_index, TexturePack};
+
+/// Thumbnail resolution (square)
+pub const THUMBNAIL_SIZE: usize = 64;
+/// Thumbnails kept before the least recently shown is dropped
+const MAX_THUMBNAILS: usize = 64;
+
+struct Thumbnail {
+    texture: Texture2D,
+    /// Geometry fingerprint the thumbnail was rendered from
+    fingerprint: u64,
+    /// Out of date, re-render when there's time
+    stale: bool,
+    /// Frame it was last shown
+    last_used: u64,
+}
+
+/// Thumbnail cache for the room list (keyed by `Room::id`)
+pub struct RoomThumbnails {
+    entries: HashMap<usize, Thumbnail>,
+    fb: Framebuffer,
+    scratch: RenderScratch,
+    frame: u64,
+    rendered_this_frame: bool,
+    /// Room index checked for changes next (besides the current room)
+    check_cursor: usize,
+}
+
+fn hash_face(hasher: &mut DefaultHasher, heights: &[f32; 4], texture: &TextureRef) {
+    heights.iter().for_each(|h| h.to_bits().hash(hasher));
+    (&texture.pack, &texture.name).hash(hasher);
+}
+
+/// Hash of everything that shows up in a thumbnail
+fn fingerprint(room: &Room) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (room.width, room.depth).hash(&mut hasher);
+    for (x, z, sector) in room.iter_sectors() {
+        (x, z).hash(&mut hasher);
+        for face in sector.floor.iter().chain(&sector.ceiling).filter(|f| f.opening.is_none()) {
+            hash_face(&mut hasher, &face.heights, &face.texture);
+        }
+        for wall in sector.walls_north.iter().chain(&sector.walls_east).chain(&sector.walls_south).chain(&sector.walls_west) {
+            hash_face(&mut hasher, &wall.heights, &wall.texture);
+        }
+    }
+    hasher.finish()
+}
+
+/// Camera looking down at a room from above one corner
+fn thumbnail_camera(room: &Room) -> Camera {
+    let bounds = room.world_bounds();
+    let center = room.world_center();
+    let extent = if bounds.is_empty() { SECTOR_SIZE } else { (bounds.max - bounds.min).len().max(SECTOR_SIZE) };
+    let eye = center + Vec3::new(extent, extent, extent).scale(0.55);
+    let dir = (center - eye).normalize();
+
+    let mut camera = Camera::new();
+    camera.position = eye;
+    camera.rotation_x = (-dir.y).asin();
+    camera.rotation_y = dir.x.atan2(dir.z);
+    camera.update_basis();
+    camera
+}
+
+impl RoomThumbnails {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            fb: Framebuffer::new(THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+            scratch: RenderScratch::new(),
+            frame: 0,
+            rendered_this_frame: false,
+            check_cursor: 0,
+        }
+    }
+
+    /// Start a frame: look for geometry changes in the current room and one other
+    pub fn begin_frame(&mut self, level: &Level, current_room: usize) {
+        self.frame += 1;
+        self.rendered_this_frame = false;
+        if level.rooms.is_empty() {
+            return;
+        }
+        self.check_cursor = (self.check_cursor + 1) % level.rooms.len();
+        for idx in [current_room, self.check_cursor] {
+            let Some(room) = level.rooms.get(idx) else { continue };
+            if let Some(entry) = self.entries.get_mut(&room.id) {
+                if !entry.stale && entry.fingerprint != fingerprint(room) {
+                    entry.stale = true;
+                }
+            }
+        }
+    }
+
+    /// Thumbnail of a room, rendering it if it's missing or out of date and nothing
+    /// else was rendered this frame. A stale thumbnail is returned until then.
+    pub fn get(
+        &mut self,
+        level: &Level,
+        room_idx: usize,
+        packs: &[TexturePack],
+        textures: &[RasterTexture],
+        settings: &RasterSettings,
+    ) -> Option<Texture2D> {
+        let room = level.rooms.get(room_idx)?;
+        let needs_render = self.entries.get(&room.id).is_none_or(|e| e.stale);
+        if needs_render && !self.rendered_this_frame {
+            self.rendered_this_frame = true;
+            let texture = self.render(level, room_idx, packs, textures, settings);
+            self.entries.insert(room.id, Thumbnail {
+                texture,
+                fingerprint: fingerprint(room),
+                stale: false,
+                last_used: self.frame,
+            });
+            self.evict();
+        }
+        let entry = self.entries.get_mut(&room.id)?;
+        entry.last_used = self.frame;
+        Some(entry.texture.clone())
+    }
+
+    fn render(
+        &mut self,
+        level: &Level,
+        room_idx: usize,
+        packs: &[TexturePack],
+        textures: &[RasterTexture],
+        settings: &RasterSettings,
+    ) -> Texture2D {
+        let room = &level.rooms[room_idx];
+
+        // Same texture lookup as the 3D viewport
+        let index = texture_index(packs);
+        let resolve_texture = |tex_ref: &TextureRef| index.resolve(tex_ref, &level.pack_aliases);
+
+        let mut settings = settings.clone();
+        settings.light_dir = level.room_light_dir(room_idx);
+        settings.debug_view = crate::rasterizer::DebugView::Off;
+        self.fb.clear(crate::rasterizer::Color::new(30, 30, 40));
+        let (vertices, faces) = room.to_render_data_with_textures(resolve_texture);
+        render_mesh_with_scratch(&mut self.fb, &vertices, &faces, textures, &thumbnail_camera(room), &settings, &mut self.scratch);
+
+        let texture = Texture2D::from_rgba8(THUMBNAIL_SIZE as u16, THUMBNAIL_SIZE as u16, &self.fb.pixels);
+        texture.set_filter(FilterMode::Nearest);
+        texture
+    }
+
+    /// Drop the least recently shown thumbnails beyond the cap
+    fn evict(&mut self) {
+        while self.entries.len() > MAX_THUMBNAILS {
+            let oldest = self.entries.iter().min_by_key(|(_, e)| e.last_used).map(|(&id, _)| id);
+            match oldest {
+                Some(id) => { self.entries.remove(&id); }
+                None => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Stairs Generator
+//!
+//! Fills the sectors between a start and an end sector (same row or column) with
+//! a staircase or a ramp: stepped (or sloped) floors, risers between the steps,
+//! and optionally side walls and a sloped ceiling. One undo step.
+
+use crate::world::{Direction, HorizontalFace, Room, TextureRef, VerticalFace};
+use super::{EditorState, CEILING_HEIGHT, CLICK_HEIGHT};
+
+/// Steepest step the generator will build, in clicks
+pub const MAX_STEP_CLICKS: u8 = 4;
+
+/// Stairs popup state (the generator options)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StairsOptions {
+    /// Rise per step when the end sector doesn't set the height (clicks)
+    pub step_clicks: u8,
+    /// Walls along both sides, floor to ceiling
+    pub side_walls: bool,
+    /// Ceiling following the stairs, at the start sector's headroom
+    pub ceiling: bool,
+    /// Sloped floors instead of steps
+    pub ramp: bool,
+}
+
+impl Default for StairsOptions {
+    fn default() -> Self {
+        Self { step_clicks: 1, side_walls: true, ceiling: false, ramp: false }
+    }
+}
+
+/// Floor corners [NW, NE, SE, SW] rising from `near` to `far` going `dir`
+fn sloped_heights(dir: Direction, near: f32, far: f32) -> [f32; 4] {
+    match dir {
+        Direction::East => [near, far, far, near],
+        Direction::West => [far, near, near, far],
+        Direction::South => [near, near, far, far],
+        Direction::North => [far, far, near, near],
+    }
+}
+
+/// Average height of a face along one sector edge
+fn edge_height(face: &HorizontalFace, dir: Direction) -> f32 {
+    let (left, right) = face.edge_heights(dir);
+    (left + right) * 0.5
+}
+
+/// Build stairs from `start` to `end` (sector coordinates in `room`).
+///
+/// If the end sector has a floor at a different height the stairs bridge the two
+/// floors; otherwise they climb `step_clicks` per sector from the start floor,
+/// ending on the end sector. Returns the number of sectors generated.
+pub fn build_stairs(
+    room: &mut Room,
+    start: (usize, usize),
+    end: (usize, usize),
+    options: &StairsOptions,
+    texture: &TextureRef,
+) -> Result<usize, String> {
+    let (dx, dz) = (end.0 as i32 - start.0 as i32, end.1 as i32 - start.1 as i32);
+    let dir = match (dx.signum(), dz.signum()) {
+        (1, 0) => Direction::East,
+        (-1, 0) => Direction::West,
+        (0, 1) => Direction::South,
+        (0, -1) => Direction::North,
+        _ => return Err("Stairs need a start and end sector in the same row or column".to_string()),
+    };
+    let n = dx.unsigned_abs().max(dz.unsigned_abs()) as usize;
+    let at = |i: usize| {
+        let (sx, sz) = dir.offset();
+        ((start.0 as i32 + sx * i as i32) as usize, (start.1 as i32 + sz * i as i32) as usize)
+    };
+
+    let start_sector = room.get_sector(start.0, start.1);
+    let Some(start_floor) = start_sector.and_then(|s| s.floor.as_ref()) else {
+        return Err("The start sector needs a floor".to_string());
+    };
+    let h0 = edge_height(start_floor, dir);
+    let headroom = start_sector
+        .and_then(|s| s.ceiling.as_ref())
+        .map_or(CEILING_HEIGHT, |c| edge_height(c, dir) - h0);
+
+    // Bridge to the end floor, or climb by the step height onto the end sector
+    let end_height = room.get_sector(end.0, end.1)
+        .and_then(|s| s.floor.as_ref())
+        .map(|f| edge_height(f, dir.opposite()))
+        .filter(|h| (h - h0).abs() > 0.5);
+    let (count, rise) = match end_height {
+        Some(he) if options.ramp => (n - 1, (he - h0) / (n as f32 - 1.0)),
+        Some(he) => (n - 1, (he - h0) / n as f32),
+        None => (n, options.step_clicks as f32 * CLICK_HEIGHT),
+    };
+    if count == 0 {
+        return Err("No sectors between the start and end to fill".to_string());
+    }
+    if rise.abs() > MAX_STEP_CLICKS as f32 * CLICK_HEIGHT + 0.5 {
+        return Err(format!(
+            "Each step would rise {:.1} clicks (max {})", rise.abs() / CLICK_HEIGHT, MAX_STEP_CLICKS
+        ));
+    }
+
+    // Clear the way: walls between consecutive sectors along the stairs (through
+    // remove_wall, so doors on them go too and doors further up the stack follow)
+    for i in 1..=n {
+        let (px, pz) = at(i - 1);
+        let (cx, cz) = at(i);
+        for (x, z, edge) in [(px, pz, dir), (cx, cz, dir.opposite())] {
+            while room.remove_wall(x, z, edge, 0).is_some() {}
+        }
+    }
+
+    // Floors (and ceilings) of the generated sectors
+    for i in 1..=count {
+        let (x, z) = at(i);
+        let (near, far) = (h0 + (i - 1) as f32 * rise, h0 + i as f32 * rise);
+        room.set_floor(x, z, far, texture.clone());
+        let Some(sector) = room.get_sector_mut(x, z) else { continue };
+        if options.ramp {
+            sector.floor = Some(HorizontalFace::sloped(sloped_heights(dir, near, far), texture.clone()));
+        }
+        if options.ceiling {
+            let heights = sloped_heights(dir, near + headroom, far + headroom);
+            sector.ceiling = Some(HorizontalFace::sloped(heights, texture.clone()));
+        }
+    }
+
+    // Risers between steps, facing down the stairs
+    if !options.ramp {
+        for i in 1..=n {
+            let (px, pz) = at(i - 1);
+            let (cx, cz) = at(i);
+            let lower = room.get_sector(px, pz).and_then(|s| s.floor.as_ref()).map(|f| edge_height(f, dir));
+            let upper = room.get_sector(cx, cz).and_then(|s| s.floor.as_ref()).map(|f| edge_height(f, dir.opposite()));
+            let (Some(a), Some(b)) = (lower, upper) else { continue };
+            if (b - a).abs() < 0.5 {
+                continue;
+            }
+            if b > a {
+                room.add_wall(px, pz, dir, a, b, texture.clone());
+            } else {
+                room.add_wall(cx, cz, dir.opposite(), b, a, texture.clone());
+            }
+        }
+    }
+
+    // Side walls, floor to ceiling (or to the start sector's headroom)
+    if options.side_walls {
+        let sides = match dir {
+            Direction::East | Direction::West => [Direction::North, Direction::South],
+            Direction::North | Direction::South => [Direction::East, Direction::West],
+        };
+        for i in 1..=count {
+            let (x, z) = at(i);
+            let Some(sector) = room.get_sector_mut(x, z) else { continue };
+            let Some(floor) = sector.floor.as_ref() else { continue };
+            let heights = sides.map(|side| {
+                let (bottom_left, bottom_right) = floor.edge_heights(side);
+                let (top_left, top_right) = match &sector.ceiling {
+                    Some(ceiling) => ceiling.edge_heights(side),
+                    None => (bottom_left + headroom, bottom_right + headroom),
+                };
+                [bottom_left, bottom_right, top_right, top_left]
+            });
+            for (side, heights) in sides.into_iter().zip(heights) {
+                let mut wall = VerticalFace::new(0.0, 0.0, texture.clone());
+                wall.heights = heights;
+                let walls = sector.walls_mut(side);
+                walls.clear();
+                walls.push(wall);
+            }
+        }
+    }
+
+    room.recalculate_bounds();
+    Ok(count)
+}
+
+impl EditorState {
+    /// Start and end sector of the stairs: the first two sectors selected, in
+    /// click order (the multi-selection, then the primary selection)
+    fn stairs_endpoints(&self) -> Result<[(usize, usize, usize); 2], String> {
+        let mut sectors: Vec<(usize, usize, usize)> = Vec::new();
+        for coords in self.multi_selection.iter().chain(std::iter::once(&self.selection)).filter_map(|s| s.sector_coords()) {
+            if !sectors.contains(&coords) {
+                sectors.push(coords);
+            }
+        }
+        match sectors.as_slice() {
+            [a, b] if a.0 == b.0 => Ok([*a, *b]),
+            [_, _] => Err("Stairs need a start and end sector in the same room".to_string()),
+            _ => Err("Select a start sector, then Shift+click the end sector".to_string()),
+        }
+    }
+
+    /// Generate stairs between the selected sectors with the popup's options
+    /// (one undo step), using the palette texture
+    pub fn apply_stairs(&mut self) {
+        let Some(options) = self.stairs else { return };
+        let [(room_idx, sx, sz), (_, ex, ez)] = match self.stairs_endpoints() {
+            Ok(endpoints) => endpoints,
+            Err(msg) => {
+                self.set_error(&msg);
+                return;
+            }
+        };
+        let texture = self.selected_texture.clone();
+        let Some(room) = self.level.rooms.get(room_idx) else { return };
+
+        // Build on a copy so a refusal leaves the level (and undo stack) alone
+        let mut built = room.clone();
+        match build_stairs(&mut built, (sx, sz), (ex, ez), &options, &texture) {
+            Ok(count) => {
+                let kind = if options.ramp { "ramp" } else { "stairs" };
+                self.save_undo(&format!("Generate {} ({} sectors)", kind, count));
+                self.level.rooms[room_idx] = built;
+                self.mark_room_dirty(room_idx);
+                self.set_status(&format!("Generated {} over {} sector(s)", kind, count), 3.0);
+            }
+            Err(msg) => self.set_error(&msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+
+    fn corridor(length: usize) -> Room {
+        let mut room = Room::new(0, Vec3::ZERO, length, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.set_ceiling(0, 0, 2048.0, TextureRef::none());
+        room
+    }
+
+    #[test]
+    fn steps_climb_and_bridge() {
+        let options = StairsOptions::default();
+        let mut room = corridor(4);
+        assert_eq!(build_stairs(&mut room, (0, 0), (3, 0), &options, &TextureRef::none()), Ok(3));
+        let floor_at = |room: &Room, x| room.get_sector(x, 0).unwrap().floor.as_ref().unwrap().heights[0];
+        assert_eq!([1, 2, 3].map(|x| floor_at(&room, x)), [256.0, 512.0, 768.0]);
+        // Riser on the lower step's east edge, side walls to the start's headroom
+        assert_eq!(room.get_sector(1, 0).unwrap().walls_east[0].heights, [256.0, 256.0, 512.0, 512.0]);
+        assert_eq!(room.get_sector(1, 0).unwrap().walls_north[0].heights[2], 256.0 + 2048.0);
+
+        // Bridging a 2048 rise is 2.7 clicks a step over 3 sectors; 3072 over 2 is too steep
+        let mut room = corridor(4);
+        room.set_floor(3, 0, 2048.0, TextureRef::none());
+        assert_eq!(build_stairs(&mut room, (0, 0), (3, 0), &options, &TextureRef::none()), Ok(2));
+        let mut room = corridor(3);
+        room.set_floor(2, 0, 3072.0, TextureRef::none());
+        assert!(build_stairs(&mut room, (0, 0), (2, 0), &options, &TextureRef::none()).is_err());
+        assert!(room.get_sector(1, 0).is_none());
+
+        assert!(build_stairs(&mut corridor(4), (0, 0), (0, 0), &options, &TextureRef::none()).is_err());
+    }
+
+    #[test]
+    fn cleared_walls_take_their_doors() {
+        let mut room = corridor(3);
+        room.set_floor(1, 0, 0.0, TextureRef::none());
+        let wall = VerticalFace::new(0.0, 1024.0, TextureRef::none());
+        room.get_sector_mut(0, 0).unwrap().walls_east.push(wall.clone());
+        room.doors.push(crate::world::Door::from_wall(0, 0, Direction::East, 0, &wall));
+        build_stairs(&mut room, (0, 0), (2, 0), &StairsOptions::default(), &TextureRef::none()).unwrap();
+        // Only the new riser is left on that edge, and the door went with the old wall
+        assert_eq!(room.get_sector(0, 0).unwrap().walls_east.len(), 1);
+        assert_eq!(room.get_sector(0, 0).unwrap().walls_east[0].heights, [0.0, 0.0, 256.0, 256.0]);
+        assert!(room.doors.is_empty());
+    }
+}
@@ -1,9 +1,27 @@
 //! Editor state and data
 
 use std::path::PathBuf;
-use crate::world::Level;
-use crate::rasterizer::{Camera, Vec3, Texture, RasterSettings};
-use super::texture_pack::TexturePack;
+use crate::world::{CameraBookmark, Level, LevelHistory, RoomPrefab, SectorSnippet, LevelRenderSettings, PortalSuggestion, MAX_DYNAMIC_LIGHTS};
+pub use crate::world::SectorFace;
+use crate::rasterizer::{Camera, Vec3, Texture, RasterSettings, RenderScratch};
+use super::texture_pack::{TexturePack, TexturePackLoadReport};
+use super::ambient_audio::SoundLibrary;
+use super::room_thumbnails::RoomThumbnails;
+use super::prefs::{EditorPrefs, HeightSnap, ViewportLayers};
+use super::texture_report::{is_missing, MissingTextures, TextureReport};
+use super::texture_replace::TextureReplace;
+use super::missing_packs::MissingPacks;
+use super::level_diff::LevelDiffView;
+use super::find_faces::FindFaces;
+use super::texture_palette::{MAX_THUMB_SIZE, MIN_THUMB_SIZE};
+use super::stairs::StairsOptions;
+use super::measure::Measure;
+use super::two_click::TwoClick;
+use super::wall_run::RunCorner;
+use super::room_cache::RoomCache;
+use super::viewport_3d::RoomMesh;
+use super::example_levels::{get_room_stats, LevelStats};
+use crate::ui::{Severity, StatusQueue, ThemeKind};
 
 /// TRLE grid constraints
 /// Sector size in world units (X-Z plane)
@@ -22,17 +40,62 @@ pub enum EditorTool {
     DrawCeiling,
     PlacePortal,
     PlaceObject,
+    PlaceAmbient,
+    PlaceLight,
+    MoveRoom,
+    Measure,
+    PaintFlags,
 }
 
-/// Which face within a sector is selected
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SectorFace {
-    Floor,
-    Ceiling,
-    WallNorth(usize),  // Index into walls array
-    WallEast(usize),
-    WallSouth(usize),
-    WallWest(usize),
+/// Height drag on a wall corner field in the properties panel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WallCornerDrag {
+    pub room: usize,
+    pub x: usize,
+    pub z: usize,
+    pub face: SectorFace,
+    pub corner: usize,      // Index into VerticalFace::heights [BL, BR, TR, TL]
+    pub anchor_y: f32,      // Mouse Y at drag start
+    pub start_height: f32,
+    pub started: bool,      // True once the height changed (undo saved)
+}
+
+/// Open floor material dropdown (properties panel)
+#[derive(Debug, Clone, Copy)]
+pub struct MaterialMenu {
+    /// The floor it sets (with the rest of the multi-selection)
+    pub room: usize,
+    pub x: usize,
+    pub z: usize,
+    /// Button it drops from, in screen space (follows the panel's scroll)
+    pub anchor: crate::ui::Rect,
+}
+
+/// Which part of the sun-angle widget is being dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightControl {
+    Azimuth,
+    Elevation,
+}
+
+/// Length of the camera fly-over when switching rooms or recalling a bookmark (seconds)
+const CAMERA_TRANSITION_SECS: f32 = 0.3;
+
+/// 3D camera WASD speed in world units per second (adjusted with right-drag + scroll,
+/// saved in prefs)
+pub const DEFAULT_FLY_SPEED: f32 = 6000.0;
+pub const MIN_FLY_SPEED: f32 = 250.0;
+pub const MAX_FLY_SPEED: f32 = 64000.0;
+
+/// 3D camera moving over to frame a room or to a bookmarked view
+#[derive(Debug, Clone, Copy)]
+pub struct CameraTransition {
+    from: Vec3,
+    to: Vec3,
+    /// (pitch, yaw) at the start and end
+    from_rotation: (f32, f32),
+    to_rotation: (f32, f32),
+    elapsed: f32,
 }
 
 /// What is currently selected in the editor
@@ -51,6 +114,12 @@ pub enum Selection {
     /// wall_face: Some(SectorFace::WallXxx) when face_idx=2
     Edge { room: usize, x: usize, z: usize, face_idx: usize, edge_idx: usize, wall_face: Option<SectorFace> },
     Portal { room: usize, portal: usize },
+    /// Collectible pickup (index into Room::pickups)
+    Pickup { room: usize, index: usize },
+    /// Ambient sound emitter (index into Room::ambients)
+    Ambient { room: usize, index: usize },
+    /// Point light (index into Room::lights)
+    Light { room: usize, index: usize },
 }
 
 impl Selection {
@@ -84,6 +153,23 @@ impl Selection {
             _ => false,
         }
     }
+
+    /// Rewrite the room index with `map` (None = room is gone, so is the selection)
+    pub fn remap_room(&self, map: impl Fn(usize) -> Option<usize>) -> Option<Selection> {
+        Some(match *self {
+            Selection::None => Selection::None,
+            Selection::Room(room) => Selection::Room(map(room)?),
+            Selection::Sector { room, x, z } => Selection::Sector { room: map(room)?, x, z },
+            Selection::SectorFace { room, x, z, face } => Selection::SectorFace { room: map(room)?, x, z, face },
+            Selection::Edge { room, x, z, face_idx, edge_idx, wall_face } => {
+                Selection::Edge { room: map(room)?, x, z, face_idx, edge_idx, wall_face }
+            }
+            Selection::Portal { room, portal } => Selection::Portal { room: map(room)?, portal },
+            Selection::Pickup { room, index } => Selection::Pickup { room: map(room)?, index },
+            Selection::Ambient { room, index } => Selection::Ambient { room: map(room)?, index },
+            Selection::Light { room, index } => Selection::Light { room: map(room)?, index },
+        })
+    }
 }
 
 /// Editor state
@@ -115,6 +201,12 @@ pub struct EditorState {
 
     /// 3D viewport camera
     pub camera_3d: Camera,
+    /// Camera fly-over in progress after a room switch or bookmark recall (cancelled by camera input)
+    pub camera_transition: Option<CameraTransition>,
+    /// Base WASD speed of the 3D camera in units per second (saved in prefs)
+    pub camera_fly_speed: f32,
+    /// Bookmark name field text, per slot (follows the level until focused)
+    pub bookmark_name_edits: [String; crate::world::CAMERA_BOOKMARK_SLOTS],
 
     /// 2D grid view camera (pan and zoom)
     pub grid_offset_x: f32,
@@ -124,34 +216,107 @@ pub struct EditorState {
     /// Grid settings
     pub grid_size: f32, // World units per grid cell
     pub show_grid: bool,
+    /// Grid view rulers and readout use room-local coordinates (false = world)
+    pub grid_local_coords: bool,
+    /// Grid panel shows the room isometric, view-only (saved in prefs)
+    pub grid_isometric: bool,
 
     /// Vertex editing mode
     pub link_coincident_vertices: bool, // When true, moving a vertex moves all vertices at same position
+    pub sync_views: bool,               // Selecting in one view centers the other on it
+    pub show_labels: bool,              // Room ids and portal targets floating in the 3D view
+    pub viewport_layers: ViewportLayers, // 3D overlays shown (saved in prefs)
+    pub isolate_geometry: bool,         // Hide every overlay and label (H), for clean screenshots
+    pub show_layers: bool,              // Layers popover open
+    pub height_snap: HeightSnap,        // Increment for vertex height drags (saved in prefs)
+    pub theme: ThemeKind,               // UI color theme (saved in prefs)
+    pub text_icons: bool,               // Text labels instead of toolbar icons (saved in prefs)
 
     /// Undo/redo (simple version - just level snapshots)
-    pub undo_stack: Vec<Level>,
-    pub redo_stack: Vec<Level>,
+    pub history: LevelHistory,
+    /// Show the undo history popup
+    pub show_history: bool,
+    /// Texture usage report popup (None = closed)
+    pub texture_report: Option<TextureReport>,
+    /// Texture packs the loaded level uses that aren't loaded (dialog open while Some)
+    pub missing_packs: Option<MissingPacks>,
+    pub texture_report_scroll: f32,
+    /// What went wrong loading the texture packs (None if nothing did); the status
+    /// bar links to it
+    pub pack_load_report: Option<TexturePackLoadReport>,
+    /// Show the texture pack loading details popup
+    pub show_pack_load_report: bool,
+    pub pack_load_report_scroll: f32,
+    /// Replace Texture popup (None = closed)
+    pub texture_replace: Option<TextureReplace>,
+    /// Stairs generator popup (None = closed)
+    pub stairs: Option<StairsOptions>,
+    /// Compare with File popup (None = closed)
+    pub level_diff: Option<LevelDiffView>,
+    /// Find popup (None = closed)
+    pub find_faces: Option<FindFaces>,
+    /// Suggest Portals popup (suggestions are recomputed while it's open)
+    pub show_portal_suggestions: bool,
+    pub portal_suggestions_scroll: f32,
+    /// Suggestion under the mouse in the popup (its rooms are highlighted in the grid view)
+    pub hovered_portal_suggestion: Option<PortalSuggestion>,
+    /// Measure tool points (cleared on Escape or when another tool is picked)
+    pub measure: Measure,
+    /// Wall tool: start corner of a wall run, while the end is picked
+    pub wall_run: TwoClick<RunCorner>,
+
+    /// Edit counter: bumped by every change, and a changed room takes the new value
+    /// as its `generation`. The level has unsaved changes while it differs from
+    /// `saved_generation`.
+    generation: u64,
+    saved_generation: u64,
 
-    /// Dirty flag (unsaved changes)
-    pub dirty: bool,
+    /// Status messages (shown in status bar, one after another)
+    pub status: StatusQueue,
 
-    /// Status message (shown in status bar)
-    pub status_message: Option<(String, f64)>, // (message, expiry_time)
+    /// Reused mesh rendering buffers for the 3D viewport
+    pub render_scratch: RenderScratch,
+    /// 3D viewport meshes per room, rebuilt when a room's generation moves
+    pub room_meshes: RoomCache<RoomMesh>,
+    /// Sector and face counts per room (profiler overlay)
+    pub room_stats: RoomCache<LevelStats>,
+    /// Room list thumbnails
+    pub room_thumbnails: RoomThumbnails,
+    /// Placeholders drawn for textures no loaded pack has
+    pub missing_textures: MissingTextures,
+    /// Faces drawn with a missing texture in the last 3D viewport frame
+    pub missing_texture_faces: usize,
 
     /// 3D viewport mouse state (for camera control)
     pub viewport_last_mouse: (f32, f32),
     pub viewport_mouse_captured: bool,
+    /// Unsnapped (pitch, yaw) while looking around, for Ctrl's 45° snapping
+    pub viewport_free_rotation: (f32, f32),
+    /// Middle (or Space+left) drag panning the 3D camera
+    pub viewport_panning: bool,
 
     /// 2D grid view mouse state
     pub grid_last_mouse: (f32, f32),
     pub grid_panning: bool,
+    /// Last grid cell under the mouse in the current room (room, x, z): where pastes land
+    pub grid_cursor: Option<(usize, usize, usize)>,
+    /// Sector under the mouse this frame (room, x, z): a grid cell, or a floor in the
+    /// 3D view. Play From Here starts there.
+    pub sector_hover: Option<(usize, usize, usize)>,
     pub grid_dragging_vertex: Option<usize>, // Primary dragged vertex (for backward compat)
     pub grid_dragging_vertices: Vec<usize>,   // All vertices being dragged (for linking)
     pub grid_drag_started: bool, // True if we've started dragging (for undo)
 
+    /// 2D grid texture paint stroke (Floor/Ceiling tool drag)
+    pub grid_painting: bool,
+    pub grid_paint_count: usize,      // Faces painted during the current stroke
+    pub flag_paint: Option<bool>,     // Paint Flags stroke in progress: the value being painted
+
+    /// Room move drag (Move Room tool in the 2D grid)
+    pub room_move_start: Option<((f32, f32), Vec3)>, // (world X-Z anchor, room position at drag start)
+
     /// 3D viewport vertex dragging state (legacy - kept for compatibility)
     pub viewport_dragging_vertices: Vec<(usize, usize)>, // List of (room_idx, vertex_idx)
-    pub viewport_drag_started: bool,
     pub viewport_drag_plane_y: f32, // Y height of the drag plane (reference point for delta)
     pub viewport_drag_initial_y: Vec<f32>, // Initial Y positions of each dragged vertex
 
@@ -166,9 +331,46 @@ pub struct EditorState {
     pub texture_packs: Vec<TexturePack>,
     pub selected_pack: usize,
     pub texture_scroll: f32,
+    /// Thumbnail size in pixels (Ctrl+scroll or the header -/+, saved in prefs)
+    pub palette_thumb_size: f32,
+
+    /// Sounds ambient emitters can play
+    pub sounds: SoundLibrary,
 
     /// Properties panel scroll offset
     pub properties_scroll: f32,
+    /// Whole-sector properties: collapsed containers (kept while `collapsed_sector`
+    /// stays selected) and the container header last jumped to, with the time
+    pub collapsed_faces: Vec<SectorFace>,
+    pub collapsed_sector: Option<(usize, usize, usize)>,
+    pub properties_flash: Option<(SectorFace, f64)>,
+
+    /// Wall corner editing (properties panel)
+    pub selected_wall_corner: Option<usize>, // Corner nudged by PageUp/PageDown [BL, BR, TR, TL]
+    pub wall_corner_drag: Option<WallCornerDrag>,
+    pub material_menu: Option<MaterialMenu>,
+    /// UV preview drag (properties panel face containers)
+    pub uv_drag: Option<super::uv_preview::UvDrag>,
+    pub doorway_size: (f32, f32),                 // Cut Doorway opening (width, height)
+
+    /// Ambient occlusion bake (toolbar)
+    pub ao_strength: f32,                          // Darkening of a fully enclosed corner (0-1)
+    pub ao_strength_drag: Option<(f32, f32)>,      // (mouse X anchor, strength at drag start)
+
+    /// Viewport FOV drag (toolbar)
+    pub fov_drag: Option<(f32, f32)>,              // (mouse X anchor, FOV at drag start)
+
+    /// Room list (room properties)
+    pub room_name_edit: String,                    // Room name field text (follows the room until focused)
+    pub room_list_drag: Option<usize>,             // Room index being dragged to reorder
+    pub room_selection: Vec<usize>,                // Rooms picked with Ctrl/Shift+click (sorted; empty = current room only)
+    pub room_ambient_drag: Option<(f32, f32)>,     // (mouse X anchor, ambient at drag start)
+    pub room_ambient_started: bool,                // True once the ambient changed (undo saved)
+    pub prefab_placement: Option<(String, RoomPrefab)>, // Prefab (name) to stamp where the 2D grid is clicked
+
+    /// Sun-angle widget (room properties)
+    pub light_drag: Option<LightControl>,
+    pub light_drag_started: bool,                  // True once the direction changed (undo saved)
 
     /// Placement height adjustment (for DrawFloor/DrawCeiling/DrawWall modes)
     pub placement_target_y: f32,           // Current Y height for new placements
@@ -183,6 +385,13 @@ pub struct EditorState {
 
 impl EditorState {
     pub fn new(level: Level) -> Self {
+        // backface_cull=true shows backfaces as wireframe; mipmaps keep distant
+        // floors readable while editing
+        let mut raster_settings = RasterSettings { mipmapping: true, ..RasterSettings::default() };
+        if let Some(stored) = &level.render_settings {
+            stored.apply(&mut raster_settings);
+        }
+
         let mut camera_3d = Camera::new();
         // Position camera far away from origin to get good view of sector
         // Single 1024×1024 sector is at origin (0,0,0) to (1024,0,1024)
@@ -192,8 +401,11 @@ impl EditorState {
         camera_3d.rotation_y = 4.02;
         camera_3d.update_basis();
 
+        let prefs = EditorPrefs::load();
+        camera_3d.fov_degrees = prefs.viewport_fov;
+
         // Discover all texture packs
-        let texture_packs = TexturePack::discover_all();
+        let (texture_packs, pack_load_report) = TexturePack::discover_all();
         println!("Discovered {} texture packs", texture_packs.len());
         for pack in &texture_packs {
             println!("  - {} ({} textures)", pack.name, pack.textures.len());
@@ -206,7 +418,7 @@ impl EditorState {
             }))
             .unwrap_or_else(crate::world::TextureRef::none);
 
-        Self {
+        let mut state = Self {
             level,
             current_file: None,
             tool: EditorTool::Select,
@@ -217,105 +429,766 @@ impl EditorState {
             current_room: 0,
             selected_texture,
             camera_3d,
+            camera_transition: None,
+            camera_fly_speed: prefs.camera_fly_speed.clamp(MIN_FLY_SPEED, MAX_FLY_SPEED),
+            bookmark_name_edits: Default::default(),
             grid_offset_x: 0.0,
             grid_offset_y: 0.0,
             grid_zoom: 0.1, // Pixels per world unit (very zoomed out for TRLE 1024-unit sectors)
             grid_size: SECTOR_SIZE, // TRLE sector size
             show_grid: true,
+            grid_local_coords: false,
+            grid_isometric: prefs.grid_isometric,
             link_coincident_vertices: true, // Default to linked mode
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
-            dirty: false,
-            status_message: None,
+            sync_views: false,
+            show_labels: false,
+            viewport_layers: prefs.viewport_layers,
+            isolate_geometry: false,
+            show_layers: false,
+            height_snap: prefs.height_snap,
+            theme: prefs.theme,
+            text_icons: prefs.text_icons,
+            history: LevelHistory::new(),
+            show_history: false,
+            texture_report: None,
+            missing_packs: None,
+            texture_report_scroll: 0.0,
+            pack_load_report: None,
+            show_pack_load_report: false,
+            pack_load_report_scroll: 0.0,
+            texture_replace: None,
+            stairs: None,
+            level_diff: None,
+            find_faces: None,
+            show_portal_suggestions: false,
+            portal_suggestions_scroll: 0.0,
+            hovered_portal_suggestion: None,
+            measure: Measure::default(),
+            wall_run: TwoClick::default(),
+            generation: 0,
+            saved_generation: 0,
+            status: StatusQueue::new(),
+            render_scratch: RenderScratch::new(),
+            room_meshes: RoomCache::new(),
+            room_stats: RoomCache::new(),
+            room_thumbnails: RoomThumbnails::new(),
+            missing_textures: MissingTextures::new(),
+            missing_texture_faces: 0,
             viewport_last_mouse: (0.0, 0.0),
             viewport_mouse_captured: false,
+            viewport_free_rotation: (0.0, 0.0),
+            viewport_panning: false,
             grid_last_mouse: (0.0, 0.0),
+            grid_cursor: None,
+            sector_hover: None,
             grid_panning: false,
             grid_dragging_vertex: None,
             grid_dragging_vertices: Vec::new(),
             grid_drag_started: false,
+            grid_painting: false,
+            grid_paint_count: 0,
+            flag_paint: None,
+            room_move_start: None,
             viewport_dragging_vertices: Vec::new(),
-            viewport_drag_started: false,
             viewport_drag_plane_y: 0.0,
             viewport_drag_initial_y: Vec::new(),
             dragging_sector_vertices: Vec::new(),
             drag_initial_heights: Vec::new(),
             texture_packs,
+            sounds: SoundLibrary::discover(),
             selected_pack: 0,
             texture_scroll: 0.0,
+            palette_thumb_size: prefs.palette_thumb_size.clamp(MIN_THUMB_SIZE, MAX_THUMB_SIZE),
             properties_scroll: 0.0,
+            collapsed_faces: Vec::new(),
+            collapsed_sector: None,
+            properties_flash: None,
+            selected_wall_corner: None,
+            wall_corner_drag: None,
+            material_menu: None,
+            uv_drag: None,
+            doorway_size: (SECTOR_SIZE / 2.0, CLICK_HEIGHT * 3.0),
+            ao_strength: 0.5,
+            ao_strength_drag: None,
+            fov_drag: None,
+            room_name_edit: String::new(),
+            room_list_drag: None,
+            room_selection: Vec::new(),
+            room_ambient_drag: None,
+            room_ambient_started: false,
+            prefab_placement: None,
+            light_drag: None,
+            light_drag_started: false,
             placement_target_y: 0.0,
             height_adjust_mode: false,
             height_adjust_start_mouse_y: 0.0,
             height_adjust_start_y: 0.0,
             height_adjust_locked_pos: None,
-            raster_settings: RasterSettings::default(), // backface_cull=true shows backfaces as wireframe
-        }
+            raster_settings,
+        };
+        state.mark_all_rooms_dirty();
+        state.mark_saved();
+        state.set_pack_load_report(pack_load_report);
+        state
     }
 
     /// Create editor state with a file path
     pub fn with_file(level: Level, path: PathBuf) -> Self {
         let mut state = Self::new(level);
         state.current_file = Some(path);
+        state.check_texture_packs();
         state
     }
 
+    /// Unsaved changes?
+    pub fn is_dirty(&self) -> bool {
+        self.generation != self.saved_generation
+    }
+
+    /// Counter bumped by every change to the level (equal counters, same level)
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The level as it is now has been saved
+    pub fn mark_saved(&mut self) {
+        self.saved_generation = self.generation;
+    }
+
+    /// Record a change outside room geometry (bookmarks, settings, room order)
+    pub fn mark_level_dirty(&mut self) {
+        self.generation += 1;
+    }
+
+    /// Record a change to one room, so the caches built from it are rebuilt
+    pub fn mark_room_dirty(&mut self, room_idx: usize) {
+        self.generation += 1;
+        if let Some(room) = self.level.rooms.get_mut(room_idx) {
+            room.generation = self.generation;
+            room.assign_wall_ids();
+            room.sync_door_walls();
+        }
+        self.check_wall_selection();
+    }
+
+    /// Record a change to a room's point lights. With dynamic lights on the room
+    /// meshes are built without them (they're lit every frame), so nothing is rebuilt.
+    pub fn mark_lights_dirty(&mut self, room_idx: usize) {
+        if self.raster_settings.dynamic_lights {
+            self.mark_level_dirty();
+        } else {
+            self.mark_room_dirty(room_idx);
+        }
+    }
+
+    /// Record a change that may touch every room (AO bake, texture replace). Each
+    /// room gets its own generation, so a cache can't mistake one room for another
+    /// after a reorder.
+    pub fn mark_all_rooms_dirty(&mut self) {
+        for room in &mut self.level.rooms {
+            self.generation += 1;
+            room.generation = self.generation;
+            room.assign_wall_ids();
+            room.sync_door_walls();
+        }
+        self.check_wall_selection();
+    }
+
+    /// Does a wall selection's wall still exist? (Other selections always do here.)
+    fn wall_selection_exists(&self, selection: &Selection) -> bool {
+        let (room, x, z, face) = match *selection {
+            Selection::SectorFace { room, x, z, face } => (room, x, z, face),
+            Selection::Edge { room, x, z, wall_face: Some(face), .. } => (room, x, z, face),
+            _ => return true,
+        };
+        face.wall().is_none() || self.level.rooms.get(room)
+            .and_then(|r| r.get_sector(x, z))
+            .is_some_and(|sector| face.wall_in(sector).is_some())
+    }
+
+    /// Drop selections of walls that were removed, rather than leave them to
+    /// point at nothing (or let another wall take their place)
+    fn check_wall_selection(&mut self) {
+        let mut removed = !self.wall_selection_exists(&self.selection);
+        if removed {
+            self.selection = Selection::None;
+        }
+        let count = self.multi_selection.len();
+        let multi = std::mem::take(&mut self.multi_selection);
+        self.multi_selection = multi.into_iter().filter(|s| self.wall_selection_exists(s)).collect();
+        removed |= self.multi_selection.len() != count;
+        if removed {
+            self.set_status("Selected wall was removed: selection cleared", 2.0);
+        }
+    }
+
+    /// Sector and face counts for the whole level, recounting only changed rooms
+    pub fn level_stats(&mut self) -> LevelStats {
+        self.room_stats.begin_frame(self.level.rooms.len(), 0);
+        let mut total = LevelStats::default();
+        for (i, room) in self.level.rooms.iter().enumerate() {
+            total.add(self.room_stats.get_or_build(i, room, get_room_stats));
+        }
+        total
+    }
+
+    /// Keep the level's stored render settings (if it stores them) in step with the
+    /// toolbar toggles. A change marks the level dirty.
+    pub fn sync_level_render_settings(&mut self) {
+        let current = LevelRenderSettings::from_raster(&self.raster_settings);
+        if let Some(stored) = self.level.render_settings.as_mut().filter(|s| **s != current) {
+            *stored = current;
+            self.mark_level_dirty();
+        }
+    }
+
+    /// Toolbar toggle: light rooms every frame instead of into their meshes
+    pub fn toggle_dynamic_lights(&mut self) {
+        self.raster_settings.dynamic_lights = !self.raster_settings.dynamic_lights;
+        let mode = if self.raster_settings.dynamic_lights { "ON" } else { "OFF" };
+        self.set_status(&format!("Dynamic lights: {}", mode), 2.0);
+        self.limit_dynamic_lights();
+    }
+
+    /// Switch dynamic lights off (with a warning) once the level has more lights than
+    /// relighting every frame can keep up with. Call once per frame.
+    pub fn limit_dynamic_lights(&mut self) {
+        let count = self.level.light_count();
+        if self.raster_settings.dynamic_lights && count > MAX_DYNAMIC_LIGHTS {
+            self.raster_settings.dynamic_lights = false;
+            self.set_warning(&format!(
+                "Dynamic lights off: {} lights (max {}), lighting into the room meshes instead",
+                count, MAX_DYNAMIC_LIGHTS,
+            ));
+        }
+    }
+
     /// Load a new level, preserving view state (camera, zoom, etc.)
     pub fn load_level(&mut self, level: Level, path: PathBuf) {
+        // Render settings stored with the level win over the current toolbar state
+        if let Some(stored) = &level.render_settings {
+            stored.apply(&mut self.raster_settings);
+        }
         self.level = level;
         self.current_file = Some(path);
-        self.dirty = false;
-        self.undo_stack.clear();
-        self.redo_stack.clear();
         self.selection = Selection::None;
+        self.multi_selection.clear();
+        self.mark_all_rooms_dirty();
+        self.mark_saved();
+        self.history.clear();
+        self.room_selection.clear();
+        self.prefab_placement = None;
+        self.camera_transition = None;
+
         // Clamp current_room to valid range
         if self.current_room >= self.level.rooms.len() {
             self.current_room = 0;
         }
+        self.check_texture_packs();
     }
 
-    /// Set a status message that will be displayed for a duration
-    pub fn set_status(&mut self, message: &str, duration_secs: f64) {
-        let expiry = macroquad::time::get_time() + duration_secs;
-        self.status_message = Some((message.to_string(), expiry));
+    /// Point the 3D camera at the whole level (keeping its viewing angle), far
+    /// enough back to see all of it
+    pub fn frame_level(&mut self) {
+        let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+        for room in self.level.rooms.iter().filter(|room| !room.is_empty()) {
+            let (lo, hi) = (room.bounds.min + room.position, room.bounds.max + room.position);
+            min = Vec3::new(min.x.min(lo.x), min.y.min(lo.y), min.z.min(lo.z));
+            max = Vec3::new(max.x.max(hi.x), max.y.max(hi.y), max.z.max(hi.z));
+        }
+        if min.x > max.x {
+            return;
+        }
+
+        let center = (min + max) * 0.5;
+        let distance = (max - min).len().max(2048.0) * 1.2;
+        self.camera_transition = None;
+        self.camera_3d.update_basis();
+        self.camera_3d.position = center - self.camera_3d.basis_z * distance;
+        if let Some(ortho_distance) = &mut self.camera_3d.ortho_distance {
+            *ortho_distance = distance;
+        }
+    }
+
+    /// Switch to a room and fly the 3D camera over to frame it: same view
+    /// direction, backed off from the room's center to outside its bounds
+    pub fn go_to_room(&mut self, idx: usize) {
+        let Some(room) = self.level.rooms.get(idx) else { return };
+        self.current_room = idx;
+        if room.is_empty() {
+            return;
+        }
+        let bounds = room.world_bounds();
+        let distance = (bounds.max - bounds.min).len() * 0.75 + SECTOR_SIZE;
+        self.camera_3d.update_basis();
+        let rotation = (self.camera_3d.rotation_x, self.camera_3d.rotation_y);
+        if let Some(ortho_distance) = &mut self.camera_3d.ortho_distance {
+            *ortho_distance = distance;
+        }
+        self.camera_transition = Some(CameraTransition {
+            from: self.camera_3d.position,
+            to: bounds.center() - self.camera_3d.basis_z * distance,
+            from_rotation: rotation,
+            to_rotation: rotation,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Advance the camera fly-over by `dt` seconds
+    pub fn step_camera_transition(&mut self, dt: f32) {
+        let Some(transition) = &mut self.camera_transition else { return };
+        transition.elapsed += dt;
+        let t = (transition.elapsed / CAMERA_TRANSITION_SECS).min(1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        let (pitch0, yaw0) = transition.from_rotation;
+        let (pitch1, yaw1) = transition.to_rotation;
+        // Turn the short way round
+        let yaw_delta = (yaw1 - yaw0 + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+        self.camera_3d.position = transition.from + (transition.to - transition.from) * eased;
+        self.camera_3d.rotation_x = pitch0 + (pitch1 - pitch0) * eased;
+        self.camera_3d.rotation_y = if t >= 1.0 { yaw1 } else { yaw0 + yaw_delta * eased };
+        self.camera_3d.update_basis();
+        if t >= 1.0 {
+            self.camera_transition = None;
+        }
+    }
+
+    /// Set the base fly speed (clamped, saved in prefs) and show it in the status bar
+    pub fn set_camera_fly_speed(&mut self, speed: f32) {
+        let speed = speed.clamp(MIN_FLY_SPEED, MAX_FLY_SPEED);
+        if speed != self.camera_fly_speed {
+            self.camera_fly_speed = speed;
+            self.save_prefs();
+        }
+        self.set_status(&format!("Fly speed: {:.0} units/s", self.camera_fly_speed), 1.5);
+    }
+
+    /// Store the current 3D camera view in a bookmark slot, keeping its name
+    pub fn set_camera_bookmark(&mut self, slot: usize) {
+        let Some(bookmark) = self.level.camera_bookmarks.get_mut(slot) else { return };
+        let name = bookmark.take().map(|b| b.name).unwrap_or_default();
+        *bookmark = Some(CameraBookmark {
+            name,
+            position: self.camera_3d.position,
+            rotation_x: self.camera_3d.rotation_x,
+            rotation_y: self.camera_3d.rotation_y,
+        });
+        self.mark_level_dirty();
+        self.set_status(&format!("Saved camera bookmark {}", slot + 1), 2.0);
+    }
+
+    /// Fly the 3D camera to a bookmarked view
+    pub fn recall_camera_bookmark(&mut self, slot: usize) {
+        let Some(Some(bookmark)) = self.level.camera_bookmarks.get(slot).cloned() else {
+            self.set_status(&format!("Camera bookmark {} is empty (Ctrl+{} to set)", slot + 1, slot + 1), 2.0);
+            return;
+        };
+        self.camera_3d.ortho_distance = None;
+        self.camera_transition = Some(CameraTransition {
+            from: self.camera_3d.position,
+            to: bookmark.position,
+            from_rotation: (self.camera_3d.rotation_x, self.camera_3d.rotation_y),
+            to_rotation: (bookmark.rotation_x, bookmark.rotation_y),
+            elapsed: 0.0,
+        });
+        let label = if bookmark.name.is_empty() {
+            format!("Camera bookmark {}", slot + 1)
+        } else {
+            format!("Camera bookmark {}: {}", slot + 1, bookmark.name)
+        };
+        self.set_status(&label, 2.0);
     }
 
-    /// Get current status message if not expired
-    pub fn get_status(&self) -> Option<&str> {
-        if let Some((msg, expiry)) = &self.status_message {
-            if macroquad::time::get_time() < *expiry {
-                return Some(msg);
+    /// Clear a bookmark slot
+    pub fn delete_camera_bookmark(&mut self, slot: usize) {
+        if let Some(bookmark) = self.level.camera_bookmarks.get_mut(slot) {
+            if bookmark.take().is_some() {
+                self.mark_level_dirty();
             }
         }
-        None
     }
 
-    /// Save current state for undo
-    pub fn save_undo(&mut self) {
-        self.undo_stack.push(self.level.clone());
-        self.redo_stack.clear();
-        self.dirty = true;
+    /// Room and world-space point of a selection: the middle of a sector's floor (or
+    /// of the wall, for a wall face), a portal's center, a pickup or ambient sound, a
+    /// room's center
+    pub fn selection_point(&self, selection: &Selection) -> Option<(usize, Vec3)> {
+        let (room_idx, x, z, face) = match *selection {
+            Selection::None => return None,
+            Selection::Room(room) => {
+                return Some((room, self.level.rooms.get(room)?.world_center()));
+            }
+            Selection::Portal { room, portal } => {
+                let r = self.level.rooms.get(room)?;
+                return Some((room, r.portals.get(portal)?.center() + r.position));
+            }
+            Selection::Pickup { room, index } => {
+                let r = self.level.rooms.get(room)?;
+                return Some((room, r.pickups.get(index)?.position + r.position));
+            }
+            Selection::Ambient { room, index } => {
+                let r = self.level.rooms.get(room)?;
+                return Some((room, r.ambients.get(index)?.position + r.position));
+            }
+            Selection::Light { room, index } => {
+                let r = self.level.rooms.get(room)?;
+                return Some((room, r.lights.get(index)?.position + r.position));
+            }
+            Selection::Sector { room, x, z } => (room, x, z, None),
+            Selection::SectorFace { room, x, z, face } => (room, x, z, Some(face)),
+            Selection::Edge { room, x, z, wall_face, .. } => (room, x, z, wall_face),
+        };
+        let room = self.level.rooms.get(room_idx)?;
+        let sector = room.get_sector(x, z)?;
+        // Heights are room-relative
+        let floor = sector.floor.as_ref().map(|f| f.height_at(0.5, 0.5));
+        let ceiling = sector.ceiling.as_ref().map(|c| c.height_at(0.5, 0.5));
+        let mut point = Vec3::new(
+            (x as f32 + 0.5) * SECTOR_SIZE,
+            floor.or(ceiling).unwrap_or(0.0),
+            (z as f32 + 0.5) * SECTOR_SIZE,
+        );
+        if face == Some(SectorFace::Ceiling) {
+            point.y = ceiling.unwrap_or(point.y);
+        } else if let Some(face) = face.filter(|f| f.wall().is_some()) {
+            let (dir, wall) = face.wall_in(sector)?;
+            let (dx, dz) = dir.offset();
+            point.x += dx as f32 * SECTOR_SIZE * 0.5;
+            point.z += dz as f32 * SECTOR_SIZE * 0.5;
+            point.y = wall.heights.iter().sum::<f32>() * 0.25;
+        }
+        Some((room_idx, point + room.position))
+    }
+
+    /// Sync Views, after selecting in the 3D viewport: switch the grid to the
+    /// selection's room and center it there
+    pub fn center_grid_on_selection(&mut self) {
+        let Some((room, point)) = self.selection_point(&self.selection) else { return };
+        self.current_room = room;
+        if self.grid_isometric {
+            let (x, y) = super::grid_iso::iso_project(point, self.grid_zoom);
+            self.grid_offset_x = -x;
+            self.grid_offset_y = -y;
+        } else {
+            self.grid_offset_x = -point.x * self.grid_zoom;
+            self.grid_offset_y = point.z * self.grid_zoom;
+        }
+    }
+
+    /// Sync Views, after selecting in the grid: turn the 3D camera toward the
+    /// selection, then pull it in to within a few sectors of it
+    pub fn frame_selection(&mut self) {
+        let Some((_, point)) = self.selection_point(&self.selection) else { return };
+        let to = point - self.camera_3d.position;
+        if to.len() > 1.0 {
+            // Inverse of `Camera::update_basis`
+            self.camera_3d.rotation_y = to.x.atan2(to.z);
+            self.camera_3d.rotation_x = (-to.y).atan2(to.x.hypot(to.z));
+        }
+        self.camera_3d.update_basis();
+        let distance = to.len().clamp(SECTOR_SIZE, SECTOR_SIZE * 4.0);
+        self.camera_transition = None;
+        self.camera_3d.ortho_distance = None;
+        self.camera_3d.position = point - self.camera_3d.basis_z * distance;
+    }
+
+    /// Scan texture references and open the report popup (Clean Level)
+    pub fn show_texture_report(&mut self) {
+        let report = TextureReport::scan(&self.level, &self.texture_packs);
+        self.set_status(&report.summary(), 3.0);
+        self.texture_report = Some(report);
+        self.texture_report_scroll = 0.0;
+    }
+
+    /// After saving: warn about openings that lost their room and faces that do
+    /// nothing, and open the texture report if the level references missing textures
+    pub fn check_level_on_save(&mut self) {
+        let misplaced = self.level.misplaced_openings();
+        if misplaced > 0 {
+            self.set_warning(&format!("{} floor/ceiling opening(s) don't sit over their target room", misplaced));
+        }
+        let empty = self.level.empty_rooms();
+        if !empty.is_empty() {
+            let list = empty.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+            self.set_warning(&format!("{} empty room(s) without geometry: {}", empty.len(), list));
+        }
+        let inert = self.level.inert_faces();
+        if inert > 0 {
+            self.set_warning(&format!("{} face(s) are invisible and don't collide", inert));
+        }
+        let report = TextureReport::scan(&self.level, &self.texture_packs);
+        if report.missing_refs > 0 {
+            self.set_warning(&report.summary());
+            self.texture_report = Some(report);
+            self.texture_report_scroll = 0.0;
+        }
+    }
+
+    /// Point every reference to a missing texture at no texture (one undo step)
+    pub fn strip_missing_textures(&mut self) {
+        let packs = &self.texture_packs;
+        let aliases = self.level.pack_aliases.clone();
+        let missing = self.level.texture_refs().filter(|t| is_missing(t, packs, &aliases)).count();
+        if missing == 0 {
+            return;
+        }
+        self.save_undo(&format!("Strip {} missing textures", missing));
+        let packs = &self.texture_packs;
+        self.level.retarget_textures(|t| is_missing(t, packs, &aliases).then(crate::world::TextureRef::none));
+        self.mark_all_rooms_dirty();
+        self.set_status(&format!("Cleared {} references to missing textures", missing), 3.0);
+        self.texture_report = Some(TextureReport::scan(&self.level, &self.texture_packs));
+    }
+
+    /// Create the portal pairs for suggestions (one undo step). Blocked ones are skipped.
+    pub fn accept_portal_suggestions(&mut self, suggestions: &[PortalSuggestion]) {
+        let open: Vec<_> = suggestions.iter().filter(|s| !s.blocked).collect();
+        if open.is_empty() {
+            return;
+        }
+        let label = match open.as_slice() {
+            [s] => format!("Connect rooms {} and {}", s.room_a, s.room_b),
+            _ => format!("Connect {} room pairs", open.len()),
+        };
+        self.save_undo(&label);
+        for suggestion in &open {
+            self.level.accept_portal_suggestion(suggestion);
+            self.mark_room_dirty(suggestion.room_a);
+            self.mark_room_dirty(suggestion.room_b);
+        }
+        self.set_status(&format!("Created {} portal pair(s)", open.len()), 2.0);
+    }
+
+    /// Save the settings that live in editor prefs
+    pub fn save_prefs(&self) {
+        EditorPrefs {
+            height_snap: self.height_snap,
+            viewport_fov: self.camera_3d.fov_degrees,
+            theme: self.theme,
+            palette_thumb_size: self.palette_thumb_size,
+            viewport_layers: self.viewport_layers,
+            camera_fly_speed: self.camera_fly_speed,
+            text_icons: self.text_icons,
+            grid_isometric: self.grid_isometric,
+        }.save();
+    }
+
+    /// Overlays to draw in the 3D viewport this frame (none while isolating geometry)
+    pub fn visible_layers(&self) -> ViewportLayers {
+        if self.isolate_geometry {
+            ViewportLayers::NONE
+        } else {
+            self.viewport_layers
+        }
+    }
+
+    /// Toggle Isolate: geometry only, no overlays or labels (H)
+    pub fn toggle_isolate(&mut self) {
+        self.isolate_geometry = !self.isolate_geometry;
+        let mode = if self.isolate_geometry { "geometry only" } else { "off" };
+        self.set_status(&format!("Isolate: {}", mode), 2.0);
+    }
+
+    /// Switch to the next built-in UI theme (saved in prefs)
+    pub fn cycle_theme(&mut self) {
+        self.theme = self.theme.next();
+        self.save_prefs();
+        self.set_status(&format!("Theme: {}", self.theme.label()), 2.0);
+    }
+
+    /// Switch toolbar buttons between icons and text labels (saved in prefs)
+    pub fn toggle_text_icons(&mut self) {
+        self.text_icons = !self.text_icons;
+        self.save_prefs();
+        let mode = if self.text_icons { "text labels" } else { "icons" };
+        self.set_status(&format!("Toolbar buttons show {}", mode), 2.0);
+    }
+
+    /// Keep the texture pack loading report if anything went wrong, announcing it
+    /// once in the status bar
+    pub fn set_pack_load_report(&mut self, report: TexturePackLoadReport) {
+        if report.has_problems() {
+            self.set_warning(&format!("{} (click the status bar for details)", report.summary()));
+            self.pack_load_report = Some(report);
+        } else {
+            self.pack_load_report = None;
+        }
+        self.show_pack_load_report = false;
+    }
+
+    /// Switch the grid panel between top-down and the isometric preview (saved in prefs)
+    pub fn toggle_grid_isometric(&mut self) {
+        self.grid_isometric = !self.grid_isometric;
+        self.save_prefs();
+        let mode = if self.grid_isometric { "isometric preview (view only)" } else { "top-down" };
+        self.set_status(&format!("2D grid: {}", mode), 2.0);
+    }
+
+    /// Cycle the vertex height snap increment (toolbar button / G)
+    pub fn cycle_height_snap(&mut self) {
+        self.height_snap = self.height_snap.next();
+        self.save_prefs();
+        self.set_status(&format!("Height snap: {}", self.height_snap.label()), 2.0);
+    }
+
+    /// Queue a status message that will be displayed for a duration
+    pub fn set_status(&mut self, message: &str, duration_secs: f64) {
+        self.status.push(message, Severity::Info, duration_secs);
+    }
+
+    /// Queue a warning (highlighted, stays up longer)
+    pub fn set_warning(&mut self, message: &str) {
+        self.status.push(message, Severity::Warning, Severity::Warning.default_duration());
+    }
+
+    /// Queue an error (highlighted and logged)
+    pub fn set_error(&mut self, message: &str) {
+        self.status.push(message, Severity::Error, Severity::Error.default_duration());
+    }
+
+    /// Status message to display right now, if any
+    pub fn get_status(&self) -> Option<(&str, Severity)> {
+        self.status.current()
+    }
 
-        // Limit undo stack size
-        if self.undo_stack.len() > 50 {
-            self.undo_stack.remove(0);
+    /// Save current state for undo. Inside a transaction only the first call
+    /// snapshots the level (and `label` is ignored): `end_transaction` pushes the step.
+    pub fn save_undo(&mut self, label: &str) {
+        if self.history.save(&self.level, label) {
+            self.mark_level_dirty();
         }
     }
 
+    /// Rename the most recent undo step (e.g. once a paint stroke knows its face
+    /// count), or the open transaction's
+    pub fn set_undo_label(&mut self, label: &str) {
+        self.history.set_label(label);
+    }
+
+    /// Start collecting edits into one undo step (a drag or stroke): `save_undo`
+    /// calls until `end_transaction` share the snapshot taken by the first one.
+    /// Ends a transaction that is still open.
+    pub fn begin_transaction(&mut self, label: &str) {
+        self.history.begin_transaction(&self.level, label);
+    }
+
+    /// Close the open transaction, pushing its undo step unless the level ended up
+    /// unchanged. Returns true if a step was pushed.
+    pub fn end_transaction(&mut self) -> bool {
+        self.history.end_transaction(&self.level)
+    }
+
+    /// Close the open transaction and put the level back as it was before it.
+    /// Returns true if there were edits to revert.
+    pub fn cancel_transaction(&mut self) -> bool {
+        let Some(before) = self.history.cancel_transaction() else {
+            return false;
+        };
+        self.level = before;
+        self.mark_all_rooms_dirty();
+        self.clamp_current_room();
+        true
+    }
+
+    /// Is a drag or stroke collecting its edits?
+    pub fn in_transaction(&self) -> bool {
+        self.history.in_transaction()
+    }
+
+    /// Escape mid-drag: revert the drag's edits and stop it. Returns true if a drag
+    /// was in progress.
+    pub fn cancel_drag(&mut self) -> bool {
+        if !self.in_transaction() {
+            return false;
+        }
+        self.cancel_transaction();
+        self.stop_drags();
+        true
+    }
+
+    /// Let go of every drag and stroke (their edits stay)
+    fn stop_drags(&mut self) {
+        self.dragging_sector_vertices.clear();
+        self.drag_initial_heights.clear();
+        self.uv_drag = None;
+        self.grid_painting = false;
+        self.flag_paint = None;
+        self.room_move_start = None;
+    }
+
     /// Undo last action
     pub fn undo(&mut self) {
-        if let Some(prev) = self.undo_stack.pop() {
-            self.redo_stack.push(self.level.clone());
-            self.level = prev;
+        if self.step_undo() {
+            if let Some(entry) = self.history.redo_stack.last() {
+                let status = format!("Undo: {}", entry.label);
+                self.set_status(&status, 2.0);
+            }
+        } else {
+            self.set_status("Nothing to undo", 1.5);
         }
     }
 
     /// Redo last undone action
     pub fn redo(&mut self) {
-        if let Some(next) = self.redo_stack.pop() {
-            self.undo_stack.push(self.level.clone());
-            self.level = next;
+        if self.step_redo() {
+            if let Some(entry) = self.history.undo_stack.last() {
+                let status = format!("Redo: {}", entry.label);
+                self.set_status(&status, 2.0);
+            }
+        } else {
+            self.set_status("Nothing to redo", 1.5);
+        }
+    }
+
+    /// Jump through history: negative steps undo, positive steps redo
+    pub fn jump_history(&mut self, steps: isize) {
+        let count = steps.unsigned_abs();
+        let moved = (0..count)
+            .take_while(|_| if steps < 0 { self.step_undo() } else { self.step_redo() })
+            .count();
+        if moved > 0 {
+            let label = self.history.undo_stack.last().map(|e| e.label.as_str()).unwrap_or("initial state");
+            let status = format!("History: {}", label);
+            self.set_status(&status, 2.0);
+        }
+    }
+
+    /// Keep current_room valid after the level was swapped (e.g. undoing a room duplicate)
+    fn clamp_current_room(&mut self) {
+        if self.current_room >= self.level.rooms.len() {
+            self.current_room = self.level.rooms.len().saturating_sub(1);
+            self.selection = Selection::None;
+        }
+    }
+
+    /// Pop one undo step without status feedback. Returns false if the stack is empty.
+    /// A drag in progress is let go first, its edits becoming their own step.
+    fn step_undo(&mut self) -> bool {
+        self.stop_drags();
+        if !self.history.undo(&mut self.level) {
+            return false;
         }
+        self.mark_level_dirty();
+        self.clamp_current_room();
+        self.check_wall_selection();
+        true
+    }
+
+    /// Pop one redo step without status feedback. Returns false if the stack is empty.
+    fn step_redo(&mut self) -> bool {
+        self.stop_drags();
+        if !self.history.redo(&mut self.level) {
+            return false;
+        }
+        self.mark_level_dirty();
+        self.clamp_current_room();
+        self.check_wall_selection();
+        true
+    }
+
+    /// Sector Play From Here starts on: the one under the mouse, else the selected one
+    pub fn play_from_target(&self) -> Option<(usize, usize, usize)> {
+        self.sector_hover.or_else(|| self.selection.sector_coords())
     }
 
     /// Get current room being edited
@@ -328,6 +1201,159 @@ impl EditorState {
         self.level.rooms.get_mut(self.current_room)
     }
 
+    /// Update every stored room index after rooms were removed or reordered.
+    /// `map` gives the new index of an old one (None = removed).
+    pub fn remap_rooms(&mut self, map: impl Fn(usize) -> Option<usize>) {
+        self.current_room = map(self.current_room)
+            .unwrap_or(self.current_room)
+            .min(self.level.rooms.len().saturating_sub(1));
+        self.selection = self.selection.remap_room(&map).unwrap_or(Selection::None);
+        self.multi_selection = self.multi_selection.iter()
+            .filter_map(|sel| sel.remap_room(&map))
+            .collect();
+        // In-flight drags hold room indices; drop them rather than remap
+        self.viewport_dragging_vertices.clear();
+        self.dragging_sector_vertices.clear();
+        self.wall_corner_drag = None;
+        self.uv_drag = None;
+        self.room_list_drag = None;
+        self.room_ambient_drag = None;
+        let mut room_selection: Vec<usize> = self.room_selection.iter().filter_map(|&i| map(i)).collect();
+        room_selection.sort_unstable();
+        room_selection.dedup();
+        self.room_selection = room_selection;
+    }
+
+    /// Rooms the batch operations act on: the picked rooms, or just the current one
+    pub fn selected_rooms(&self) -> Vec<usize> {
+        if self.room_selection.is_empty() {
+            vec![self.current_room]
+        } else {
+            self.room_selection.clone()
+        }
+    }
+
+    /// Room list click: plain selects one room, Ctrl toggles a room in the selection,
+    /// Shift picks the range from the current room
+    pub fn click_room(&mut self, idx: usize, ctrl: bool, shift: bool) {
+        if idx >= self.level.rooms.len() {
+            return;
+        }
+        if shift {
+            let (lo, hi) = (self.current_room.min(idx), self.current_room.max(idx));
+            self.room_selection = (lo..=hi).collect();
+        } else if ctrl {
+            if self.room_selection.is_empty() {
+                self.room_selection.push(self.current_room);
+            }
+            match self.room_selection.binary_search(&idx) {
+                Ok(pos) => {
+                    self.room_selection.remove(pos);
+                }
+                Err(pos) => self.room_selection.insert(pos, idx),
+            }
+        } else {
+            self.room_selection.clear();
+            if idx != self.current_room {
+                self.go_to_room(idx);
+            }
+        }
+    }
+
+    /// Delete several rooms as one undo step; portals into them are dropped and the
+    /// room indices remapped once. Keeps at least one room.
+    pub fn delete_rooms(&mut self, rooms: &[usize]) {
+        let mut rooms: Vec<usize> = rooms.iter().copied().filter(|&i| i < self.level.rooms.len()).collect();
+        rooms.sort_unstable();
+        rooms.dedup();
+        if let [room_idx] = rooms[..] {
+            self.delete_room(room_idx);
+            return;
+        }
+        if rooms.is_empty() {
+            return;
+        }
+        if rooms.len() >= self.level.rooms.len() {
+            self.set_warning("Can't delete every room");
+            return;
+        }
+        self.save_undo(&format!("Delete {} rooms", rooms.len()));
+        let (removed, dropped) = self.level.remove_rooms(&rooms);
+        self.remap_rooms(|i| match rooms.binary_search(&i) {
+            Ok(_) => None,
+            Err(below) => Some(i - below),
+        });
+        self.mark_all_rooms_dirty();
+        if dropped > 0 {
+            self.set_status(&format!("Deleted {} rooms ({} portal(s) into them removed)", removed, dropped), 3.0);
+        } else {
+            self.set_status(&format!("Deleted {} rooms", removed), 2.0);
+        }
+    }
+
+    /// Move several rooms by whole sectors along X/Z. Caller records undo.
+    pub fn translate_rooms(&mut self, rooms: &[usize], dx: i32, dz: i32) {
+        for &room_idx in rooms {
+            if let Some(room) = self.level.rooms.get_mut(room_idx) {
+                room.position.x += dx as f32 * SECTOR_SIZE;
+                room.position.z += dz as f32 * SECTOR_SIZE;
+                room.recalculate_bounds();
+            }
+            self.mark_room_dirty(room_idx);
+        }
+    }
+
+    /// Set the ambient light of several rooms. Caller records undo.
+    pub fn set_rooms_ambient(&mut self, rooms: &[usize], ambient: f32) {
+        for &room_idx in rooms {
+            if let Some(room) = self.level.rooms.get_mut(room_idx) {
+                room.ambient = ambient.clamp(0.0, 1.0);
+            }
+            self.mark_room_dirty(room_idx);
+        }
+    }
+
+    /// Delete a room (portals into it are dropped). Keeps at least one room.
+    pub fn delete_room(&mut self, room_idx: usize) {
+        if self.level.rooms.len() <= 1 || room_idx >= self.level.rooms.len() {
+            self.set_warning("Can't delete the only room");
+            return;
+        }
+        let name = self.level.rooms[room_idx].display_name();
+        self.save_undo(&format!("Delete {}", name));
+        if let Some((_, dropped)) = self.level.remove_room(room_idx) {
+            self.remap_rooms(|i| match i.cmp(&room_idx) {
+                std::cmp::Ordering::Less => Some(i),
+                std::cmp::Ordering::Equal => None,
+                std::cmp::Ordering::Greater => Some(i - 1),
+            });
+            if dropped > 0 {
+                self.set_status(&format!("Deleted {} ({} portal(s) into it removed)", name, dropped), 3.0);
+            } else {
+                self.set_status(&format!("Deleted {}", name), 2.0);
+            }
+        }
+    }
+
+    /// Move a room within the room list (organization only, portals are unaffected)
+    pub fn move_room(&mut self, from: usize, to: usize) {
+        if from == to || from >= self.level.rooms.len() || to >= self.level.rooms.len() {
+            return;
+        }
+        let name = self.level.rooms[from].display_name();
+        self.save_undo(&format!("Reorder {}", name));
+        self.level.move_room(from, to);
+        self.remap_rooms(|i| Some(if i == from {
+            to
+        } else if from < to && i > from && i <= to {
+            i - 1
+        } else if from > to && i >= to && i < from {
+            i + 1
+        } else {
+            i
+        }));
+    }
+
     /// Get textures from the currently selected pack
     pub fn current_textures(&self) -> &[Texture] {
         self.texture_packs
@@ -336,6 +1362,22 @@ impl EditorState {
             .unwrap_or(&[])
     }
 
+    /// Add a pack (replacing one with the same name) and show it in the palette
+    pub fn add_texture_pack(&mut self, pack: TexturePack) {
+        let idx = match self.texture_packs.iter().position(|p| p.name == pack.name) {
+            Some(idx) => {
+                self.texture_packs[idx] = pack;
+                idx
+            }
+            None => {
+                self.texture_packs.push(pack);
+                self.texture_packs.len() - 1
+            }
+        };
+        self.selected_pack = idx;
+        self.texture_scroll = 0.0;
+    }
+
     /// Get the name of the currently selected pack
     pub fn current_pack_name(&self) -> &str {
         self.texture_packs
@@ -349,6 +1391,79 @@ impl EditorState {
         self.multi_selection.iter().any(|s| s == selection)
     }
 
+    /// Copy the selected sectors (those in the primary selection's room) to the
+    /// clipboard as snippet text
+    pub fn copy_selection_as_text(&mut self) {
+        let primary = self.selection.sector_coords()
+            .or_else(|| self.multi_selection.iter().find_map(|sel| sel.sector_coords()));
+        let Some((room_idx, _, _)) = primary else {
+            self.set_warning("Select sectors to copy");
+            return;
+        };
+        let coords: Vec<(usize, usize)> = self.multi_selection.iter()
+            .chain(std::iter::once(&self.selection))
+            .filter_map(|sel| sel.sector_coords())
+            .filter(|(r, _, _)| *r == room_idx)
+            .map(|(_, x, z)| (x, z))
+            .collect();
+        let Some(snippet) = self.level.rooms.get(room_idx).and_then(|room| SectorSnippet::copy(room, &coords)) else {
+            self.set_warning("Nothing to copy");
+            return;
+        };
+        match snippet.to_text() {
+            Ok(text) => {
+                macroquad::miniquad::window::clipboard_set(&text);
+                self.set_status(&format!("Copied {} sector(s) as text", snippet.sectors.len()), 2.0);
+            }
+            Err(e) => self.set_error(&format!("Copy failed: {}", e)),
+        }
+    }
+
+    /// Paste snippet text from the clipboard into the current room, at the last
+    /// grid cell under the mouse (else the selected sector). One undo step.
+    pub fn paste_from_text(&mut self) {
+        let Some(text) = macroquad::miniquad::window::clipboard_get() else {
+            self.set_error("Clipboard is empty or unavailable");
+            return;
+        };
+        let snippet = match SectorSnippet::from_text(&text) {
+            Ok(snippet) => snippet,
+            Err(e) => {
+                self.set_error(&format!("Paste failed: {}", e));
+                return;
+            }
+        };
+        let room_idx = self.current_room;
+        let (x, z) = match (self.grid_cursor, self.selection.sector_coords()) {
+            (Some((r, x, z)), _) if r == room_idx => (x, z),
+            (_, Some((r, x, z))) if r == room_idx => (x, z),
+            _ => (0, 0),
+        };
+        let Some(room) = self.level.rooms.get(room_idx) else { return };
+        if snippet.sectors.iter().all(|(dx, dz, _)| x + dx >= room.width || z + dz >= room.depth) {
+            self.set_error("Pasted sectors don't fit in this room there");
+            return;
+        }
+
+        self.save_undo(&format!("Paste {} sector(s) at ({}, {})", snippet.sectors.len(), x, z));
+        let Some(room) = self.level.rooms.get_mut(room_idx) else { return };
+        let placed = snippet.paste(room, x, z);
+        self.mark_room_dirty(room_idx);
+        self.clear_multi_selection();
+        for &(px, pz) in &placed {
+            self.multi_selection.push(Selection::Sector { room: room_idx, x: px, z: pz });
+        }
+        if let Some(&(px, pz)) = placed.first() {
+            self.selection = Selection::Sector { room: room_idx, x: px, z: pz };
+        }
+        let skipped = snippet.sectors.len() - placed.len();
+        if skipped > 0 {
+            self.set_warning(&format!("Pasted {} sector(s); {} didn't fit in the room", placed.len(), skipped));
+        } else {
+            self.set_status(&format!("Pasted {} sector(s)", placed.len()), 2.0);
+        }
+    }
+
     /// Add a selection to the multi-selection list (if not already present)
     pub fn add_to_multi_selection(&mut self, selection: Selection) {
         if !matches!(selection, Selection::None) && !self.is_multi_selected(&selection) {
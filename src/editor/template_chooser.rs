@@ -0,0 +1,244 @@
+//! New Level Template Chooser
+//!
+//! Modal dialog shown for File > New: pick a starting level from a few templates,
+//! each with a top-down thumbnail drawn from the template itself.
+
+use macroquad::prelude::*;
+use crate::ui::{draw_button, Rect, UiContext, ACCENT_COLOR};
+use crate::world::{
+    create_corridor_level, create_empty_level, create_terrain_level, create_test_level,
+    create_two_room_level, Level, SECTOR_SIZE,
+};
+
+/// A starting point for a new level
+pub struct LevelTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub build: fn() -> Level,
+}
+
+/// Templates offered by the chooser, in display order
+pub const LEVEL_TEMPLATES: [LevelTemplate; 5] = [
+    LevelTemplate {
+        name: "Empty",
+        description: "A single floor sector. Start from scratch.",
+        build: create_empty_level,
+    },
+    LevelTemplate {
+        name: "Enclosed Room",
+        description: "One sector with floor, ceiling and four walls.",
+        build: create_test_level,
+    },
+    LevelTemplate {
+        name: "Corridor",
+        description: "A 1x8 walled corridor.",
+        build: create_corridor_level,
+    },
+    LevelTemplate {
+        name: "Two Rooms + Portal",
+        description: "Two rooms joined by a portal on their shared edge.",
+        build: create_two_room_level,
+    },
+    LevelTemplate {
+        name: "Terrain 8x8",
+        description: "Open ground with gently sloped floors.",
+        build: create_terrain_level,
+    },
+];
+
+/// State for the template chooser dialog
+#[derive(Default)]
+pub struct TemplateChooser {
+    /// Whether the chooser is open
+    pub open: bool,
+    /// Highlighted template index
+    pub selected: usize,
+    /// Built templates, used for the thumbnails (filled on open)
+    previews: Vec<Level>,
+}
+
+impl TemplateChooser {
+    /// Open the chooser (builds the template levels for the thumbnails)
+    pub fn open(&mut self) {
+        self.open = true;
+        self.selected = 0;
+        self.previews = LEVEL_TEMPLATES.iter().map(|t| (t.build)()).collect();
+    }
+
+    /// Close the chooser
+    pub fn close(&mut self) {
+        self.open = false;
+        self.previews.clear();
+    }
+}
+
+/// Result from drawing the template chooser
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateAction {
+    None,
+    /// Create a new level from the template at this index
+    Create(usize),
+    /// User cancelled (keep the current level)
+    Cancel,
+}
+
+/// Draw the template chooser modal dialog
+pub fn draw_template_chooser(ctx: &mut UiContext, chooser: &mut TemplateChooser) -> TemplateAction {
+    if !chooser.open {
+        return TemplateAction::None;
+    }
+
+    let mut action = TemplateAction::None;
+    let count = LEVEL_TEMPLATES.len();
+
+    // Darken background
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+    // Dialog sized to fit one row of cards
+    let card_w = 140.0;
+    let card_h = 170.0;
+    let gap = 10.0;
+    let dialog_w = (card_w + gap) * count as f32 + gap;
+    let dialog_h = 40.0 + card_h + 80.0;
+    let dialog_x = ((screen_width() - dialog_w) / 2.0).floor();
+    let dialog_y = ((screen_height() - dialog_h) / 2.0).floor();
+
+    draw_rectangle(dialog_x, dialog_y, dialog_w, dialog_h, Color::from_rgba(35, 35, 40, 255));
+    draw_rectangle_lines(dialog_x, dialog_y, dialog_w, dialog_h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header
+    let header_h = 40.0;
+    draw_rectangle(dialog_x, dialog_y, dialog_w, header_h, Color::from_rgba(45, 45, 55, 255));
+    draw_text("New Level", dialog_x + 16.0, dialog_y + 26.0, 20.0, WHITE);
+
+    // Template cards
+    let cards_y = dialog_y + header_h + gap;
+    for (i, template) in LEVEL_TEMPLATES.iter().enumerate() {
+        let card = Rect::new(dialog_x + gap + i as f32 * (card_w + gap), cards_y, card_w, card_h);
+        let hovered = ctx.mouse.inside(&card);
+        let selected = chooser.selected == i;
+
+        let bg = if selected {
+            Color::from_rgba(55, 60, 75, 255)
+        } else if hovered {
+            Color::from_rgba(48, 48, 58, 255)
+        } else {
+            Color::from_rgba(40, 40, 48, 255)
+        };
+        draw_rectangle(card.x, card.y, card.w, card.h, bg);
+        if selected {
+            draw_rectangle_lines(card.x, card.y, card.w, card.h, 2.0, ACCENT_COLOR);
+        }
+
+        let thumb = Rect::new(card.x + 6.0, card.y + 6.0, card.w - 12.0, card.w - 12.0);
+        draw_rectangle(thumb.x, thumb.y, thumb.w, thumb.h, Color::from_rgba(20, 20, 25, 255));
+        if let Some(level) = chooser.previews.get(i) {
+            draw_template_thumbnail(level, thumb);
+        }
+
+        let dims = measure_text(template.name, None, 14, 1.0);
+        draw_text(template.name, (card.x + (card.w - dims.width) / 2.0).floor(), card.bottom() - 10.0, 14.0, WHITE);
+
+        if hovered && ctx.mouse.left_pressed {
+            if selected {
+                // Second click on the highlighted card creates it
+                action = TemplateAction::Create(i);
+            }
+            chooser.selected = i;
+        }
+    }
+
+    // Description of the highlighted template
+    let desc = LEVEL_TEMPLATES[chooser.selected].description;
+    draw_text(desc, dialog_x + gap, cards_y + card_h + 22.0, 14.0, Color::from_rgba(180, 180, 180, 255));
+
+    // Footer buttons
+    let footer_y = dialog_y + dialog_h - 40.0;
+    let cancel_rect = Rect::new(dialog_x + dialog_w - 180.0, footer_y + 6.0, 80.0, 28.0);
+    if draw_button(ctx, cancel_rect, "Cancel", Color::from_rgba(60, 60, 70, 255)) {
+        action = TemplateAction::Cancel;
+    }
+    let create_rect = Rect::new(dialog_x + dialog_w - 90.0, footer_y + 6.0, 80.0, 28.0);
+    if draw_button(ctx, create_rect, "Create", ACCENT_COLOR) {
+        action = TemplateAction::Create(chooser.selected);
+    }
+
+    // Keyboard: arrows pick, Enter creates, Escape cancels
+    if is_key_pressed(KeyCode::Left) {
+        chooser.selected = chooser.selected.saturating_sub(1);
+    }
+    if is_key_pressed(KeyCode::Right) {
+        chooser.selected = (chooser.selected + 1).min(count - 1);
+    }
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+        action = TemplateAction::Create(chooser.selected);
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        action = TemplateAction::Cancel;
+    }
+
+    action
+}
+
+/// Top-down map of a level: floors shaded by height, walls and portals as lines
+fn draw_template_thumbnail(level: &Level, rect: Rect) {
+    // World-space XZ extent of all rooms
+    let mut min = (f32::MAX, f32::MAX);
+    let mut max = (f32::MIN, f32::MIN);
+    let (mut low, mut high) = (f32::MAX, f32::MIN);
+    for room in &level.rooms {
+        min = (min.0.min(room.position.x), min.1.min(room.position.z));
+        max = (
+            max.0.max(room.position.x + room.width as f32 * SECTOR_SIZE),
+            max.1.max(room.position.z + room.depth as f32 * SECTOR_SIZE),
+        );
+        for (_, _, sector) in room.iter_sectors() {
+            for &h in sector.floor.iter().flat_map(|f| &f.heights) {
+                low = low.min(h);
+                high = high.max(h);
+            }
+        }
+    }
+    if min.0 >= max.0 || min.1 >= max.1 {
+        return;
+    }
+
+    // Fit the extent into the thumbnail (square sectors, centered, with a margin)
+    let margin = 8.0;
+    let scale = ((rect.w - margin * 2.0) / (max.0 - min.0)).min((rect.h - margin * 2.0) / (max.1 - min.1));
+    let origin_x = rect.x + (rect.w - (max.0 - min.0) * scale) / 2.0;
+    let origin_y = rect.y + (rect.h - (max.1 - min.1) * scale) / 2.0;
+    let to_screen = |x: f32, z: f32| (origin_x + (x - min.0) * scale, origin_y + (z - min.1) * scale);
+    let cell = SECTOR_SIZE * scale;
+
+    let wall_color = Color::from_rgba(220, 220, 230, 255);
+    for room in &level.rooms {
+        for (x, z, sector) in room.iter_sectors() {
+            let (sx, sy) = to_screen(
+                room.position.x + x as f32 * SECTOR_SIZE,
+                room.position.z + z as f32 * SECTOR_SIZE,
+            );
+            if let Some(floor) = &sector.floor {
+                let t = if high > low { (floor.avg_height() - low) / (high - low) } else { 0.5 };
+                let v = (70.0 + t * 90.0) as u8;
+                draw_rectangle(sx, sy, cell, cell, Color::from_rgba(v / 2, v, v / 2 + 20, 255));
+                draw_rectangle_lines(sx, sy, cell, cell, 1.0, Color::from_rgba(30, 35, 40, 255));
+            }
+
+            let (x0, y0, x1, y1) = (sx, sy, sx + cell, sy + cell);
+            if !sector.walls_north.is_empty() { draw_line(x0, y0, x1, y0, 2.0, wall_color); }
+            if !sector.walls_east.is_empty() { draw_line(x1, y0, x1, y1, 2.0, wall_color); }
+            if !sector.walls_south.is_empty() { draw_line(x0, y1, x1, y1, 2.0, wall_color); }
+            if !sector.walls_west.is_empty() { draw_line(x0, y0, x0, y1, 2.0, wall_color); }
+        }
+
+        // Portals as an accent line along their floor edge
+        for portal in &room.portals {
+            let a = portal.vertices[0] + room.position;
+            let b = portal.vertices[1] + room.position;
+            let (ax, ay) = to_screen(a.x, a.z);
+            let (bx, by) = to_screen(b.x, b.z);
+            draw_line(ax, ay, bx, by, 3.0, ACCENT_COLOR);
+        }
+    }
+}
@@ -1,152 +1,513 @@
 //! Texture pack loading for the editor
 //!
-//! Handles loading texture packs from disk (native) or via JavaScript cache (WASM).
+//! Handles loading texture packs from disk (native) or via JavaScript cache (WASM),
+//! and building in-memory packs from uploaded zips. An optional `pack.ron` next to
+//! the textures holds pack settings, e.g. `(palette: Some(Clut4))` to quantize the
+//! pack to 16-color CLUTs on load.
+//!
+//! Loading never stops at a bad texture: failures (files the JS cache doesn't have,
+//! PNGs that don't decode) and manifest problems are collected into a
+//! `TexturePackLoadReport` returned alongside the packs, so the editor can say why
+//! a level shows checkerboards.
 
 use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
 use crate::rasterizer::Texture;
+use crate::world::TextureIndex;
+use super::zip::ZipEntry;
+
+/// File name of the pack settings, next to the textures
+pub const PACK_SETTINGS_FILE: &str = "pack.ron";
+
+/// Largest pack accepted from a zip upload, in decoded RGBA bytes
+pub const MAX_UPLOAD_RGBA_BYTES: usize = 128 * 1024 * 1024;
+
+/// Image size from a PNG's IHDR chunk (without decoding the image)
+fn png_dimensions(bytes: &[u8]) -> Option<(usize, usize)> {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    if bytes.get(..8)? != SIGNATURE || bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?);
+    Some((width as usize, height as usize))
+}
+
+/// PS1 CLUT depth a pack's textures are limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaletteMode {
+    /// 4-bit indices, 16 colors
+    Clut4,
+    /// 8-bit indices, 256 colors
+    Clut8,
+}
+
+impl PaletteMode {
+    pub fn max_colors(self) -> usize {
+        match self {
+            PaletteMode::Clut4 => 16,
+            PaletteMode::Clut8 => 256,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PaletteMode::Clut4 => "4-bit CLUT",
+            PaletteMode::Clut8 => "8-bit CLUT",
+        }
+    }
+}
+
+/// Pack-level settings read from `pack.ron` (all optional)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackSettings {
+    /// Quantize the textures to this CLUT depth on load
+    pub palette: Option<PaletteMode>,
+}
+
+impl PackSettings {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| format!("Invalid {}: {}", PACK_SETTINGS_FILE, e))
+    }
+
+    /// Settings from `pack.ron` in `dir` (defaults if there is none or it doesn't
+    /// parse; parse errors go to `warnings`)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_directory(dir: &std::path::Path, warnings: &mut Vec<String>) -> Self {
+        let Ok(text) = std::fs::read_to_string(dir.join(PACK_SETTINGS_FILE)) else {
+            return Self::default();
+        };
+        Self::parse(&text).unwrap_or_else(|e| {
+            eprintln!("{}: {}", dir.display(), e);
+            warnings.push(format!("{}: {}", dir.display(), e));
+            Self::default()
+        })
+    }
+}
+
+/// A texture file that was asked for but didn't load
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureLoadFailure {
+    /// File name, relative to the pack
+    pub file: String,
+    pub reason: String,
+}
+
+/// How loading one pack went
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackLoadStats {
+    pub pack: String,
+    /// Texture files listed in the manifest (WASM) or found in the directory (native)
+    pub requested: usize,
+    pub loaded: usize,
+    pub failures: Vec<TextureLoadFailure>,
+}
+
+/// Diagnostics from loading the texture packs: per-pack counts, the textures that
+/// failed and why, and warnings about the manifest or pack settings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TexturePackLoadReport {
+    pub packs: Vec<PackLoadStats>,
+    /// Manifest problems (empty sections, duplicate names, files outside a section)
+    /// and unreadable manifests, directories or pack settings
+    pub warnings: Vec<String>,
+}
+
+impl TexturePackLoadReport {
+    /// Textures that failed to load, across all packs
+    pub fn failure_count(&self) -> usize {
+        self.packs.iter().map(|p| p.failures.len()).sum()
+    }
+
+    pub fn has_problems(&self) -> bool {
+        self.failure_count() > 0 || !self.warnings.is_empty()
+    }
+
+    /// One line for the status bar
+    pub fn summary(&self) -> String {
+        let requested: usize = self.packs.iter().map(|p| p.requested).sum();
+        let loaded: usize = self.packs.iter().map(|p| p.loaded).sum();
+        let failures = self.failure_count();
+        let mut parts = Vec::new();
+        if failures > 0 {
+            let packs = self.packs.iter().filter(|p| !p.failures.is_empty()).count();
+            parts.push(format!("{} of {} textures failed to load in {} pack(s)", failures, requested, packs));
+        }
+        if !self.warnings.is_empty() {
+            parts.push(format!("{} warning(s)", self.warnings.len()));
+        }
+        if parts.is_empty() {
+            format!("Loaded {} textures in {} pack(s)", loaded, self.packs.len())
+        } else {
+            format!("Texture packs: {}", parts.join(", "))
+        }
+    }
+
+    /// Lines for the details view: each pack's counts and failures, then the warnings
+    pub fn detail_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for pack in &self.packs {
+            lines.push(format!("{}: {} of {} loaded", pack.pack, pack.loaded, pack.requested));
+            for failure in &pack.failures {
+                lines.push(format!("  {}: {}", failure.file, failure.reason));
+            }
+        }
+        lines.extend(self.warnings.iter().map(|w| format!("Warning: {}", w)));
+        lines
+    }
+}
 
 /// A texture pack loaded from a folder
 pub struct TexturePack {
     pub name: String,
     pub path: PathBuf,
     pub textures: Vec<Texture>,
+    pub settings: PackSettings,
+    /// Textures are currently stored quantized to `settings.palette`
+    pub quantized: bool,
+}
+
+/// Texture memory of `packs`, e.g. "12.0 MB + 4.0 MB mips"
+pub fn memory_summary(packs: &[TexturePack]) -> String {
+    let (base, mips) = packs.iter()
+        .flat_map(|p| &p.textures)
+        .map(Texture::memory_bytes)
+        .fold((0, 0), |(b, m), (tb, tm)| (b + tb, m + tm));
+    let mb = |bytes: usize| bytes as f32 / (1024.0 * 1024.0);
+    format!("{:.1} MB + {:.1} MB mips", mb(base), mb(mips))
+}
+
+/// Ids for every texture of `packs`, flattened in order (the editor's texture list)
+pub fn texture_index(packs: &[TexturePack]) -> TextureIndex {
+    let mut index = TextureIndex::new();
+    for pack in packs {
+        for tex in &pack.textures {
+            index.push(&pack.name, &tex.name);
+        }
+    }
+    index
 }
 
 impl TexturePack {
-    /// Load a texture pack from a directory (native only)
+    /// Pack with its textures quantized to the settings' palette mode (if any) and
+    /// mip levels generated (for `RasterSettings::mipmapping`)
+    fn new(name: String, path: PathBuf, textures: Vec<Texture>, settings: PackSettings) -> Self {
+        let mut pack = Self { name, path, textures: Vec::new(), settings, quantized: false };
+        pack.set_textures(textures, true);
+        pack
+    }
+
+    /// Replace the textures, quantizing them if `quantize` and the pack has a palette mode
+    fn set_textures(&mut self, textures: Vec<Texture>, quantize: bool) {
+        let mode = self.settings.palette.filter(|_| quantize);
+        self.quantized = mode.is_some();
+        self.textures = textures.into_iter()
+            .map(|tex| match mode {
+                Some(mode) => tex.quantized(mode.max_colors()),
+                None => tex,
+            })
+            .map(Texture::with_mips)
+            .collect();
+    }
+
+    /// Reload the textures from their source with quantization on or off, to compare
+    /// the pack's CLUT look with the true-color images. Packs without a source to
+    /// reload from (zip uploads) are left alone; returns false for those.
+    pub fn reload(&mut self, quantize: bool) -> bool {
+        match self.load_source() {
+            Some(textures) => {
+                self.set_textures(textures, quantize);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The pack's textures as stored on disk (native)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_source(&self) -> Option<Vec<Texture>> {
+        if !self.path.is_dir() {
+            return None;
+        }
+        let (textures, _) = load_directory_textures(&self.path);
+        (!textures.is_empty()).then_some(textures)
+    }
+
+    /// The pack's textures as decoded by JavaScript (WASM)
+    #[cfg(target_arch = "wasm32")]
+    fn load_source(&self) -> Option<Vec<Texture>> {
+        let textures: Vec<Texture> = self.textures.iter()
+            .filter_map(|tex| {
+                let path = format!("{}/{}.png", self.path.display(), tex.name);
+                wasm::load_cached_texture(&path, tex.name.clone()).ok()
+            })
+            .collect();
+        (textures.len() == self.textures.len()).then_some(textures)
+    }
+
+    /// Load a texture pack from a directory (native only), noting in `report` how
+    /// it went. None if no texture loaded.
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn from_directory(path: PathBuf) -> Option<Self> {
+    pub fn from_directory(path: PathBuf, report: &mut TexturePackLoadReport) -> Option<Self> {
         let name = path.file_name()?.to_string_lossy().to_string();
-        let textures = Texture::load_directory(&path);
+        let (textures, failures) = load_directory_textures(&path);
+        if textures.is_empty() && failures.is_empty() {
+            report.warnings.push(format!("{} has no PNG textures", path.display()));
+            return None;
+        }
+        for failure in &failures {
+            eprintln!("{}/{}: {}", name, failure.file, failure.reason);
+        }
+        report.packs.push(PackLoadStats {
+            pack: name.clone(),
+            requested: textures.len() + failures.len(),
+            loaded: textures.len(),
+            failures,
+        });
+        if textures.is_empty() {
+            return None;
+        }
+        let settings = PackSettings::from_directory(&path, &mut report.warnings);
+        Some(Self::new(name, path, textures, settings))
+    }
+
+    /// Build an in-memory pack from the PNGs in an uploaded zip (folders inside are flattened).
+    /// Image headers are checked first so oversized packs are rejected before decoding.
+    pub fn from_zip_entries(name: String, entries: &[ZipEntry]) -> Result<Self, String> {
+        let pngs: Vec<&ZipEntry> = entries.iter().filter(|e| e.has_extension("png")).collect();
+        if pngs.is_empty() {
+            return Err(format!("No PNG textures in {}", name));
+        }
+
+        let rgba_bytes: usize = pngs.iter()
+            .filter_map(|e| png_dimensions(&e.data))
+            .map(|(w, h)| w * h * 4)
+            .sum();
+        if rgba_bytes > MAX_UPLOAD_RGBA_BYTES {
+            return Err(format!(
+                "{} is too large: {} MB of texture data (limit {} MB)",
+                name,
+                rgba_bytes / (1024 * 1024),
+                MAX_UPLOAD_RGBA_BYTES / (1024 * 1024),
+            ));
+        }
 
+        let mut textures: Vec<Texture> = pngs.iter()
+            .filter_map(|e| {
+                let file_name = e.file_name();
+                let tex_name = file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem);
+                Texture::from_bytes(&e.data, tex_name.to_string()).ok()
+            })
+            .collect();
         if textures.is_empty() {
-            // Try loading from subdirectories (some packs have nested folders)
-            let mut all_textures = Vec::new();
-            if let Ok(entries) = std::fs::read_dir(&path) {
-                for entry in entries.filter_map(|e| e.ok()) {
-                    let entry_path = entry.path();
-                    if entry_path.is_dir() {
-                        all_textures.extend(Texture::load_directory(&entry_path));
-                    }
-                }
-            }
-            if all_textures.is_empty() {
-                return None;
-            }
-            Some(Self { name, path, textures: all_textures })
-        } else {
-            Some(Self { name, path, textures })
+            return Err(format!("No readable PNG textures in {}", name));
         }
+        textures.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let settings = match entries.iter().find(|e| e.file_name() == PACK_SETTINGS_FILE) {
+            Some(entry) => PackSettings::parse(&String::from_utf8_lossy(&entry.data))?,
+            None => PackSettings::default(),
+        };
+
+        Ok(Self::new(name.clone(), PathBuf::from(&name), textures, settings))
     }
 
     /// Discover all texture packs in the assets/textures directory (native only)
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn discover_all() -> Vec<Self> {
+    pub fn discover_all() -> (Vec<Self>, TexturePackLoadReport) {
         let textures_dir = PathBuf::from("assets/textures");
         let mut packs = Vec::new();
+        let mut report = TexturePackLoadReport::default();
 
-        if let Ok(entries) = std::fs::read_dir(&textures_dir) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let path = entry.path();
-                if path.is_dir() {
-                    if let Some(pack) = Self::from_directory(path) {
-                        packs.push(pack);
+        match std::fs::read_dir(&textures_dir) {
+            Ok(entries) => {
+                for entry in entries.filter_map(|e| e.ok()) {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        if let Some(pack) = Self::from_directory(path, &mut report) {
+                            packs.push(pack);
+                        }
                     }
                 }
             }
+            // No textures directory at all is a valid (untextured) setup
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => report.warnings.push(format!("Can't read {}: {}", textures_dir.display(), e)),
         }
 
         packs.sort_by(|a, b| a.name.cmp(&b.name));
-        packs
+        report.packs.sort_by(|a, b| a.pack.cmp(&b.pack));
+        println!("Loaded {} texture packs ({})", packs.len(), memory_summary(&packs));
+        (packs, report)
     }
 
     /// Discover all texture packs from manifest (WASM stub - returns empty, loaded async later)
     #[cfg(target_arch = "wasm32")]
-    pub fn discover_all() -> Vec<Self> {
-        Vec::new()
+    pub fn discover_all() -> (Vec<Self>, TexturePackLoadReport) {
+        (Vec::new(), TexturePackLoadReport::default())
     }
 
     /// Load texture packs from manifest asynchronously.
     /// On WASM: JavaScript prefetches and decodes PNGs in parallel, Rust just copies raw RGBA.
     /// On native: Falls back to load_file + PNG decoding.
-    pub async fn load_from_manifest() -> Vec<Self> {
+    pub async fn load_from_manifest() -> (Vec<Self>, TexturePackLoadReport) {
         use macroquad::prelude::*;
 
+        let mut report = TexturePackLoadReport::default();
+
         // Load and parse manifest
         let manifest = match load_string("assets/textures/manifest.txt").await {
             Ok(s) => s,
             Err(e) => {
                 eprintln!("Failed to load texture manifest: {}", e);
+                report.warnings.push(format!("Failed to load texture manifest: {}", e));
                 wasm::hide_loading();
-                return Vec::new();
+                return (Vec::new(), report);
             }
         };
 
-        let pack_files = parse_manifest(&manifest);
+        let (pack_files, warnings) = parse_manifest(&manifest);
+        report.warnings = warnings;
         let mut packs = Vec::new();
 
         for (pack_name, files) in pack_files {
             wasm::set_status(&format!("Loading {}...", pack_name));
 
             let mut textures = Vec::with_capacity(files.len());
+            let mut failures = Vec::new();
             for filename in &files {
-                if let Some(tex) = load_single_texture(&pack_name, filename).await {
-                    textures.push(tex);
+                match load_single_texture(&pack_name, filename).await {
+                    Ok(tex) => textures.push(tex),
+                    Err(reason) => {
+                        eprintln!("{}/{}: {}", pack_name, filename, reason);
+                        failures.push(TextureLoadFailure { file: filename.clone(), reason });
+                    }
                 }
             }
+            report.packs.push(PackLoadStats {
+                pack: pack_name.clone(),
+                requested: files.len(),
+                loaded: textures.len(),
+                failures,
+            });
 
             if !textures.is_empty() {
-                packs.push(TexturePack {
-                    name: pack_name.clone(),
-                    path: PathBuf::from(format!("assets/textures/{}", pack_name)),
+                // Optional, so a missing file is not an error
+                let settings_path = format!("assets/textures/{}/{}", pack_name, PACK_SETTINGS_FILE);
+                let settings = match load_string(&settings_path).await {
+                    Ok(text) => PackSettings::parse(&text).unwrap_or_else(|e| {
+                        eprintln!("{}: {}", pack_name, e);
+                        report.warnings.push(format!("{}: {}", pack_name, e));
+                        PackSettings::default()
+                    }),
+                    Err(_) => PackSettings::default(),
+                };
+                packs.push(TexturePack::new(
+                    pack_name.clone(),
+                    PathBuf::from(format!("assets/textures/{}", pack_name)),
                     textures,
-                });
+                    settings,
+                ));
             }
         }
 
-        println!("Loaded {} texture packs from manifest", packs.len());
+        println!("Loaded {} texture packs from manifest ({})", packs.len(), memory_summary(&packs));
         wasm::hide_loading();
-        packs
+        (packs, report)
     }
 }
 
-/// Parse manifest file into (pack_name, filenames) pairs
-fn parse_manifest(manifest: &str) -> Vec<(String, Vec<String>)> {
-    let mut result = Vec::new();
-    let mut current_pack: Option<String> = None;
-    let mut current_files = Vec::new();
+/// Textures in a pack directory, or in its subdirectories if it has none itself
+/// (some packs have nested folders), with the files that didn't load
+#[cfg(not(target_arch = "wasm32"))]
+fn load_directory_textures(path: &std::path::Path) -> (Vec<Texture>, Vec<TextureLoadFailure>) {
+    let mut failures = Vec::new();
+    let load = |dir: &std::path::Path, prefix: &str, failures: &mut Vec<TextureLoadFailure>| {
+        match Texture::load_directory_checked(dir) {
+            Ok((textures, failed)) => {
+                failures.extend(failed.into_iter().map(|(file, reason)| TextureLoadFailure {
+                    file: format!("{}{}", prefix, file),
+                    reason,
+                }));
+                textures
+            }
+            Err(reason) => {
+                failures.push(TextureLoadFailure { file: prefix.to_string(), reason });
+                Vec::new()
+            }
+        }
+    };
+    let textures = load(path, "", &mut failures);
+    if !textures.is_empty() {
+        return (textures, failures);
+    }
+    let mut all_textures = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(path) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let entry_path = entry.path();
+            if entry_path.is_dir() {
+                let prefix = format!("{}/", entry.file_name().to_string_lossy());
+                all_textures.extend(load(&entry_path, &prefix, &mut failures));
+            }
+        }
+    }
+    (all_textures, failures)
+}
 
-    for line in manifest.lines() {
+/// Parse manifest file into (pack_name, filenames) pairs, with warnings about
+/// anything odd in it. Sections listed twice are merged; files listed twice (or
+/// naming the same texture) are kept once.
+fn parse_manifest(manifest: &str) -> (Vec<(String, Vec<String>)>, Vec<String>) {
+    let mut result: Vec<(String, Vec<String>)> = Vec::new();
+    let mut warnings = Vec::new();
+    let mut current: Option<usize> = None;
+    // Files naming the same texture (a.png, a.PNG) clash too
+    let texture_name = |file: &str| file.strip_suffix(".png").or_else(|| file.strip_suffix(".PNG")).unwrap_or(file).to_string();
+
+    for (line_no, line) in manifest.lines().enumerate() {
         let line = line.trim();
         if line.is_empty() {
             continue;
         }
 
         if line.starts_with('[') && line.ends_with(']') {
-            // Save previous pack
-            if let Some(name) = current_pack.take() {
-                if !current_files.is_empty() {
-                    result.push((name, std::mem::take(&mut current_files)));
+            let name = &line[1..line.len() - 1];
+            current = match result.iter().position(|(pack, _)| pack == name) {
+                Some(i) => {
+                    warnings.push(format!("Manifest: [{}] appears more than once (merged)", name));
+                    Some(i)
+                }
+                None => {
+                    result.push((name.to_string(), Vec::new()));
+                    Some(result.len() - 1)
                 }
+            };
+        } else if let Some(i) = current {
+            let (pack, files) = &mut result[i];
+            if files.iter().any(|f| texture_name(f) == texture_name(line)) {
+                warnings.push(format!("Manifest: [{}] lists {} more than once", pack, texture_name(line)));
+            } else {
+                files.push(line.to_string());
             }
-            current_pack = Some(line[1..line.len() - 1].to_string());
-        } else if current_pack.is_some() {
-            current_files.push(line.to_string());
+        } else {
+            warnings.push(format!("Manifest line {}: {} is outside any [pack] section", line_no + 1, line));
         }
     }
 
-    // Don't forget last pack
-    if let Some(name) = current_pack {
-        if !current_files.is_empty() {
-            result.push((name, current_files));
+    // Sections without files don't become packs
+    result.retain(|(pack, files)| {
+        if files.is_empty() {
+            warnings.push(format!("Manifest: [{}] lists no files", pack));
         }
-    }
+        !files.is_empty()
+    });
 
-    result
+    (result, warnings)
 }
 
-/// Load a single texture from pack
-async fn load_single_texture(pack_name: &str, filename: &str) -> Option<Texture> {
+/// Load a single texture from pack, or why it didn't load
+async fn load_single_texture(pack_name: &str, filename: &str) -> Result<Texture, String> {
     let tex_path = format!("assets/textures/{}/{}", pack_name, filename);
     let tex_name = filename
         .strip_suffix(".png")
@@ -162,10 +523,8 @@ async fn load_single_texture(pack_name: &str, filename: &str) -> Option<Texture>
     #[cfg(not(target_arch = "wasm32"))]
     {
         use macroquad::prelude::load_file;
-        match load_file(&tex_path).await {
-            Ok(bytes) => Texture::from_bytes(&bytes, tex_name).ok(),
-            Err(_) => None,
-        }
+        let bytes = load_file(&tex_path).await.map_err(|e| e.to_string())?;
+        Texture::from_bytes(&bytes, tex_name)
     }
 }
 
@@ -207,11 +566,11 @@ mod wasm {
 
     /// Load texture from JavaScript cache (pre-decoded RGBA)
     #[cfg(target_arch = "wasm32")]
-    pub fn load_cached_texture(path: &str, name: String) -> Option<Texture> {
+    pub fn load_cached_texture(path: &str, name: String) -> Result<Texture, String> {
         unsafe {
             let info = bonnie_get_cached_texture_info(path.as_ptr(), path.len());
             if info == 0 {
-                return None;
+                return Err("Not in the browser's texture cache (missing file or stale manifest?)".to_string());
             }
 
             let width = (info >> 16) as usize;
@@ -227,7 +586,7 @@ mod wasm {
             );
 
             if copied != rgba_size {
-                return None;
+                return Err(format!("Cached image data is truncated ({} of {} bytes)", copied, rgba_size));
             }
 
             let pixels: Vec<Color> = rgba_buffer
@@ -235,12 +594,67 @@ mod wasm {
                 .map(|c| Color::with_alpha(c[0], c[1], c[2], c[3]))
                 .collect();
 
-            Some(Texture {
+            Ok(Texture {
                 width,
                 height,
                 pixels,
                 name,
+                mips: Vec::new(),
+                clut: None,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_settings_parse() {
+        assert_eq!(PackSettings::parse("()").unwrap(), PackSettings::default());
+        let settings = PackSettings::parse("(palette: Some(Clut4))").unwrap();
+        assert_eq!(settings.palette.map(PaletteMode::max_colors), Some(16));
+        assert!(PackSettings::parse("(palette: Some(Clut2))").is_err());
+    }
+
+    #[test]
+    fn manifest_problems_become_warnings() {
+        let manifest = "stray.png\n[bricks]\na.png\nb.png\na.PNG\n[empty]\n\n[bricks]\nc.png\n";
+        let (packs, warnings) = parse_manifest(manifest);
+        assert_eq!(packs, [("bricks".to_string(), vec!["a.png".to_string(), "b.png".to_string(), "c.png".to_string()])]);
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+        assert!(warnings[0].contains("line 1") && warnings[0].contains("stray.png"));
+        assert!(warnings[1].contains("[bricks]") && warnings[1].contains(" a "));
+        assert!(warnings[2].contains("more than once (merged)"));
+        assert!(warnings[3].contains("[empty] lists no files"));
+
+        let (packs, warnings) = parse_manifest("[a]\nx.png\n[b]\ny.png\n");
+        assert_eq!(packs.len(), 2);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn load_report_counts_failures_per_pack() {
+        let mut report = TexturePackLoadReport::default();
+        report.packs.push(PackLoadStats { pack: "stone".to_string(), requested: 3, loaded: 3, failures: Vec::new() });
+        assert!(!report.has_problems());
+        assert_eq!(report.summary(), "Loaded 3 textures in 1 pack(s)");
+
+        report.packs.push(PackLoadStats {
+            pack: "wood".to_string(),
+            requested: 2,
+            loaded: 1,
+            failures: vec![TextureLoadFailure { file: "plank.png".to_string(), reason: "Not in the cache".to_string() }],
+        });
+        report.warnings.push("Manifest: [empty] lists no files".to_string());
+        assert!(report.has_problems());
+        assert_eq!(report.summary(), "Texture packs: 1 of 5 textures failed to load in 1 pack(s), 1 warning(s)");
+        assert_eq!(report.detail_lines(), [
+            "stone: 3 of 3 loaded",
+            "wood: 1 of 2 loaded",
+            "  plank.png: Not in the cache",
+            "Warning: Manifest: [empty] lists no files",
+        ]);
+    }
+}
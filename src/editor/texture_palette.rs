@@ -2,13 +2,97 @@
 
 use macroquad::prelude::*;
 use crate::ui::{Rect, UiContext, icon, draw_icon_centered};
-use crate::rasterizer::Texture as RasterTexture;
-use super::EditorState;
-
-/// Size of texture thumbnails in the palette
-const THUMB_SIZE: f32 = 48.0;
+use crate::rasterizer::{Clut, Texture as RasterTexture};
+use super::{EditorState, PaletteMode};
+use super::view_pan::ZOOM_STEP;
+
+/// Size of texture thumbnails in the palette (zoomable, saved in prefs)
+pub const DEFAULT_THUMB_SIZE: f32 = 48.0;
+pub const MIN_THUMB_SIZE: f32 = 32.0;
+pub const MAX_THUMB_SIZE: f32 = 128.0;
+/// Zoom step for the header buttons (Ctrl+scroll zooms by `ZOOM_STEP` ratios)
+const THUMB_SIZE_STEP: f32 = 16.0;
+/// Thumbnails at least this big show the texture name beneath them
+const NAMED_THUMB_SIZE: f32 = 64.0;
+const NAME_HEIGHT: f32 = 14.0;
 const THUMB_PADDING: f32 = 4.0;
 const HEADER_HEIGHT: f32 = 28.0;
+/// CLUT inspector: colors per row, largest swatch and caption height
+const CLUT_COLUMNS: usize = 16;
+const CLUT_MAX_SWATCH: f32 = 14.0;
+const CLUT_CAPTION_HEIGHT: f32 = 18.0;
+
+/// Grid layout for a thumbnail size: (columns, cell width, cell height, total height)
+fn grid_layout(width: f32, thumb_size: f32, texture_count: usize) -> (usize, f32, f32, f32) {
+    let cell_w = thumb_size + THUMB_PADDING;
+    let cell_h = cell_w + if thumb_size >= NAMED_THUMB_SIZE { NAME_HEIGHT } else { 0.0 };
+    let cols = (((width - THUMB_PADDING) / cell_w).floor() as usize).max(1);
+    let rows = texture_count.div_ceil(cols);
+    (cols, cell_w, cell_h, rows as f32 * cell_h + THUMB_PADDING)
+}
+
+/// Scroll that keeps thumbnail `index` at the same height on screen after a zoom:
+/// `offset_y` is the cursor's height below the content top, `frac` how far down
+/// the thumbnail's cell it was (0-1)
+fn scroll_to_keep(index: usize, frac: f32, offset_y: f32, cols: usize, cell_h: f32) -> f32 {
+    let row = (index / cols) as f32;
+    THUMB_PADDING + (row + frac) * cell_h - offset_y
+}
+
+/// Shorten `text` with a trailing ".." until it fits `max_width`
+fn elide(text: &str, max_width: f32, font_size: u16) -> String {
+    if measure_text(text, None, font_size, 1.0).width <= max_width {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate: String = chars.iter().collect::<String>() + "..";
+        if measure_text(&candidate, None, font_size, 1.0).width <= max_width {
+            return candidate;
+        }
+    }
+    String::new()
+}
+
+impl EditorState {
+    /// Zoom the palette thumbnails (clamped, saved in prefs)
+    pub fn set_palette_thumb_size(&mut self, size: f32) {
+        let size = size.clamp(MIN_THUMB_SIZE, MAX_THUMB_SIZE);
+        if size != self.palette_thumb_size {
+            self.palette_thumb_size = size;
+            self.save_prefs();
+        }
+    }
+
+    /// Any pack declares a palette mode in its `pack.ron`
+    pub fn has_palette_packs(&self) -> bool {
+        self.texture_packs.iter().any(|p| p.settings.palette.is_some())
+    }
+
+    /// Reload the packs that declare a palette mode with CLUT quantization on or off,
+    /// to compare their PS1 look with the true-color images
+    pub fn set_texture_quantization(&mut self, quantize: bool) {
+        let mut reloaded = 0;
+        let mut skipped = 0;
+        for pack in self.texture_packs.iter_mut().filter(|p| p.settings.palette.is_some()) {
+            if pack.reload(quantize) {
+                reloaded += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+        let state = if quantize { "on" } else { "off" };
+        if skipped > 0 {
+            self.set_warning(&format!(
+                "CLUT quantization {} for {} pack(s); {} uploaded pack(s) can't be reloaded",
+                state, reloaded, skipped,
+            ));
+        } else {
+            self.set_status(&format!("CLUT quantization {}", state), 2.0);
+        }
+    }
+}
 
 /// Draw the texture palette
 pub fn draw_texture_palette(
@@ -17,15 +101,27 @@ pub fn draw_texture_palette(
     state: &mut EditorState,
     icon_font: Option<&Font>,
 ) {
+    let theme = ctx.theme;
     // Background
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(25, 25, 30, 255));
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.inset_bg);
 
     // Draw folder selector header
+    let old_thumb_size = state.palette_thumb_size;
     let header_rect = Rect::new(rect.x, rect.y, rect.w, HEADER_HEIGHT);
     draw_folder_selector(ctx, header_rect, state, icon_font);
 
-    // Content area (below header)
-    let content_rect = Rect::new(rect.x, rect.y + HEADER_HEIGHT, rect.w, rect.h - HEADER_HEIGHT);
+    // Content area (below header), with the CLUT of an indexed selected texture at the bottom
+    let mut content_rect = Rect::new(rect.x, rect.y + HEADER_HEIGHT, rect.w, rect.h - HEADER_HEIGHT);
+    let mut inspector = None;
+    if let Some((clut, mode)) = selected_clut(state) {
+        let swatch = ((rect.w - THUMB_PADDING * 2.0) / CLUT_COLUMNS as f32).min(CLUT_MAX_SWATCH).floor();
+        let rows = clut.colors.len().div_ceil(CLUT_COLUMNS);
+        let inspector_h = CLUT_CAPTION_HEIGHT + rows as f32 * swatch + THUMB_PADDING;
+        if inspector_h < content_rect.h / 2.0 {
+            content_rect.h -= inspector_h;
+            inspector = Some((Rect::new(rect.x, content_rect.bottom(), rect.w, inspector_h), clut, mode, swatch));
+        }
+    }
 
     // Get texture count without borrowing state
     let texture_count = state.texture_packs
@@ -33,25 +129,52 @@ pub fn draw_texture_palette(
         .map(|p| p.textures.len())
         .unwrap_or(0);
 
+    // Ctrl+scroll zooms the thumbnails instead of scrolling, keeping the one under
+    // the cursor (its index and how far down its cell the cursor is) in place
+    let zooming = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+        || is_key_down(KeyCode::LeftSuper) || is_key_down(KeyCode::RightSuper);
+    let mut zoom_anchor = None;
+    if zooming && ctx.mouse.inside(&content_rect) && ctx.mouse.scroll != 0.0 && texture_count > 0 {
+        let (cols, cell_w, cell_h, _) = grid_layout(content_rect.w, old_thumb_size, texture_count);
+        let offset_y = ctx.mouse.y - content_rect.y;
+        let y = (offset_y + state.texture_scroll - THUMB_PADDING).max(0.0);
+        let col = (((ctx.mouse.x - content_rect.x - THUMB_PADDING).max(0.0) / cell_w) as usize).min(cols - 1);
+        let index = ((y / cell_h) as usize * cols + col).min(texture_count - 1);
+        zoom_anchor = Some((index, (y / cell_h).fract(), offset_y));
+        let size = (state.palette_thumb_size * ZOOM_STEP.powf(ctx.mouse.scroll.signum())).round();
+        state.set_palette_thumb_size(size);
+    }
+
     if texture_count == 0 {
         draw_text(
             "No textures in this pack",
             (content_rect.x + 10.0).floor(),
             (content_rect.y + 20.0).floor(),
             16.0,
-            Color::from_rgba(100, 100, 100, 255),
+            theme.text_faint,
         );
         return;
     }
 
     // Calculate grid layout
-    let cols = ((content_rect.w - THUMB_PADDING) / (THUMB_SIZE + THUMB_PADDING)).floor() as usize;
-    let cols = cols.max(1);
-    let rows = (texture_count + cols - 1) / cols;
-    let total_height = rows as f32 * (THUMB_SIZE + THUMB_PADDING) + THUMB_PADDING;
+    let thumb_size = state.palette_thumb_size;
+    let (cols, cell_w, cell_h, total_height) = grid_layout(content_rect.w, thumb_size, texture_count);
+
+    // Keep the same place in the list after a zoom: the thumbnail under the cursor,
+    // or for the header buttons the same fraction of the list
+    if thumb_size != old_thumb_size {
+        state.texture_scroll = match zoom_anchor {
+            Some((index, frac, offset_y)) => scroll_to_keep(index, frac, offset_y, cols, cell_h),
+            None => {
+                let (_, _, _, old_height) = grid_layout(content_rect.w, old_thumb_size, texture_count);
+                state.texture_scroll * total_height / old_height
+            }
+        };
+        state.texture_scroll = state.texture_scroll.clamp(0.0, (total_height - content_rect.h).max(0.0));
+    }
 
     // Handle scrolling
-    if ctx.mouse.inside(&content_rect) {
+    if ctx.mouse.inside(&content_rect) && !zooming {
         state.texture_scroll -= ctx.mouse.scroll * 30.0;
         // Clamp scroll
         let max_scroll = (total_height - content_rect.h).max(0.0);
@@ -96,15 +219,15 @@ pub fn draw_texture_palette(
         let col = i % cols;
         let row = i / cols;
 
-        let x = content_rect.x + THUMB_PADDING + col as f32 * (THUMB_SIZE + THUMB_PADDING);
-        let y = content_rect.y + THUMB_PADDING + row as f32 * (THUMB_SIZE + THUMB_PADDING) - texture_scroll;
+        let x = content_rect.x + THUMB_PADDING + col as f32 * cell_w;
+        let y = content_rect.y + THUMB_PADDING + row as f32 * cell_h - texture_scroll;
 
         // Skip if outside visible area
-        if y + THUMB_SIZE < content_rect.y || y > content_rect.bottom() {
+        if y + thumb_size < content_rect.y || y > content_rect.bottom() {
             continue;
         }
 
-        let thumb_rect = Rect::new(x, y, THUMB_SIZE, THUMB_SIZE);
+        let thumb_rect = Rect::new(x, y, thumb_size, thumb_size);
 
         // Clip drawing to content area
         if y < content_rect.y {
@@ -121,7 +244,7 @@ pub fn draw_texture_palette(
         };
 
         // Check for click (only if fully visible)
-        if y >= content_rect.y && y + THUMB_SIZE <= content_rect.bottom() {
+        if y >= content_rect.y && y + thumb_size <= content_rect.bottom() {
             if ctx.mouse.clicked(&thumb_rect) {
                 clicked_texture = Some(crate::world::TextureRef::new(pack_name.clone(), texture.name.clone()));
             }
@@ -135,7 +258,7 @@ pub fn draw_texture_palette(
             y,
             WHITE,
             DrawTextureParams {
-                dest_size: Some(Vec2::new(THUMB_SIZE, THUMB_SIZE)),
+                dest_size: Some(Vec2::new(thumb_size, thumb_size)),
                 ..Default::default()
             },
         );
@@ -150,10 +273,10 @@ pub fn draw_texture_palette(
             draw_rectangle_lines(
                 x - 2.0,
                 y - 2.0,
-                THUMB_SIZE + 4.0,
-                THUMB_SIZE + 4.0,
+                thumb_size + 4.0,
+                thumb_size + 4.0,
                 2.0,
-                Color::from_rgba(255, 200, 50, 255),
+                theme.highlight,
             );
         }
 
@@ -162,10 +285,10 @@ pub fn draw_texture_palette(
             draw_rectangle_lines(
                 x - 1.0,
                 y - 1.0,
-                THUMB_SIZE + 2.0,
-                THUMB_SIZE + 2.0,
+                thumb_size + 2.0,
+                thumb_size + 2.0,
                 1.0,
-                Color::from_rgba(150, 150, 200, 255),
+                theme.hover,
             );
         }
 
@@ -173,10 +296,21 @@ pub fn draw_texture_palette(
         draw_text(
             &format!("{}", i),
             (x + 2.0).floor(),
-            (y + THUMB_SIZE - 2.0).floor(),
+            (y + thumb_size - 2.0).floor(),
             12.0,
             Color::from_rgba(255, 255, 255, 200),
         );
+
+        // Name beneath the bigger thumbnails
+        if thumb_size >= NAMED_THUMB_SIZE && y + thumb_size + NAME_HEIGHT <= content_rect.bottom() {
+            let color = if is_selected { theme.highlight } else { theme.text_dim };
+            draw_text(&elide(&texture.name, thumb_size, 12), x.floor(), (y + thumb_size + 11.0).floor(), 12.0, color);
+        }
+    }
+
+    // Drawn after the grid so it covers thumbnails cut off at the bottom
+    if let Some((inspector_rect, clut, mode, swatch)) = inspector {
+        draw_clut_inspector(ctx, inspector_rect, &clut, mode, swatch);
     }
 
     // Apply clicked texture after loop
@@ -188,19 +322,73 @@ pub fn draw_texture_palette(
         all_selections.extend(state.multi_selection.clone());
 
         // Check if we have any valid selections
-        let has_valid_selection = all_selections.iter().any(|sel| !matches!(sel, super::Selection::None));
+        let valid_count = all_selections.iter().filter(|sel| !matches!(sel, super::Selection::None)).count();
 
-        if has_valid_selection {
-            state.save_undo();
+        if valid_count > 0 {
+            state.save_undo(&format!("Apply texture {} to {} selection(s)", tex_ref.name, valid_count));
 
             // Apply texture to all selections
             for sel in all_selections {
+                if let Some((room, _, _)) = sel.sector_coords() {
+                    state.mark_room_dirty(room);
+                }
                 apply_texture_to_selection(&mut state.level, sel, tex_ref.clone());
             }
         }
     }
 }
 
+/// CLUT of the selected texture and its pack's palette mode, if the texture is in
+/// the shown pack and indexed
+fn selected_clut(state: &EditorState) -> Option<(Clut, PaletteMode)> {
+    let pack = state.texture_packs.get(state.selected_pack)?;
+    if pack.name != state.selected_texture.pack {
+        return None;
+    }
+    let clut = pack.textures.iter()
+        .find(|tex| tex.name == state.selected_texture.name)?
+        .clut.clone()?;
+    Some((clut, pack.settings.palette?))
+}
+
+/// Palette of an indexed texture: swatches in index order, hover for the values
+fn draw_clut_inspector(ctx: &mut UiContext, rect: Rect, clut: &Clut, mode: PaletteMode, swatch: f32) {
+    let theme = ctx.theme;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.bar_bg);
+    draw_text(
+        &format!("{}: {}/{} colors", mode.label(), clut.colors.len(), mode.max_colors()),
+        (rect.x + THUMB_PADDING).floor(),
+        (rect.y + 13.0).floor(),
+        12.0,
+        theme.text_dim,
+    );
+
+    let origin_y = rect.y + CLUT_CAPTION_HEIGHT;
+    for (i, color) in clut.colors.iter().enumerate() {
+        let cell = Rect::new(
+            rect.x + THUMB_PADDING + (i % CLUT_COLUMNS) as f32 * swatch,
+            origin_y + (i / CLUT_COLUMNS) as f32 * swatch,
+            swatch,
+            swatch,
+        );
+        // Checker under translucent entries so alpha reads
+        if color.a < 255 {
+            draw_rectangle(cell.x, cell.y, cell.w, cell.h, Color::from_rgba(90, 90, 90, 255));
+            draw_rectangle(cell.x, cell.y, cell.w / 2.0, cell.h / 2.0, Color::from_rgba(160, 160, 160, 255));
+            draw_rectangle(cell.x + cell.w / 2.0, cell.y + cell.h / 2.0, cell.w / 2.0, cell.h / 2.0, Color::from_rgba(160, 160, 160, 255));
+        }
+        draw_rectangle(cell.x, cell.y, cell.w - 1.0, cell.h - 1.0, Color::from_rgba(color.r, color.g, color.b, color.a));
+        if ctx.mouse.inside(&cell) {
+            draw_rectangle_lines(cell.x, cell.y, cell.w, cell.h, 1.0, theme.hover);
+            ctx.set_tooltip(
+                &format!("#{}  {}, {}, {}, {}", i, color.r, color.g, color.b, color.a),
+                ctx.mouse.x,
+                ctx.mouse.y,
+            );
+        }
+    }
+}
+
 /// Apply a texture to a single selection
 fn apply_texture_to_selection(level: &mut crate::world::Level, selection: super::Selection, tex_ref: crate::world::TextureRef) {
     match selection {
@@ -219,23 +407,8 @@ fn apply_texture_to_selection(level: &mut crate::world::Level, selection: super:
                                 ceiling.texture = tex_ref;
                             }
                         }
-                        super::SectorFace::WallNorth(i) => {
-                            if let Some(wall) = sector.walls_north.get_mut(i) {
-                                wall.texture = tex_ref;
-                            }
-                        }
-                        super::SectorFace::WallEast(i) => {
-                            if let Some(wall) = sector.walls_east.get_mut(i) {
-                                wall.texture = tex_ref;
-                            }
-                        }
-                        super::SectorFace::WallSouth(i) => {
-                            if let Some(wall) = sector.walls_south.get_mut(i) {
-                                wall.texture = tex_ref;
-                            }
-                        }
-                        super::SectorFace::WallWest(i) => {
-                            if let Some(wall) = sector.walls_west.get_mut(i) {
+                        wall => {
+                            if let Some((_, wall)) = wall.wall_in_mut(sector) {
                                 wall.texture = tex_ref;
                             }
                         }
@@ -277,11 +450,12 @@ fn apply_texture_to_selection(level: &mut crate::world::Level, selection: super:
 
 /// Draw the folder selector dropdown
 fn draw_folder_selector(ctx: &mut UiContext, rect: Rect, state: &mut EditorState, icon_font: Option<&Font>) {
+    let theme = ctx.theme;
     // Background
-    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, Color::from_rgba(40, 40, 45, 255));
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, theme.bar_bg);
 
     if state.texture_packs.is_empty() {
-        draw_text("No texture packs found", (rect.x + 5.0).floor(), (rect.y + 18.0).floor(), 14.0, Color::from_rgba(150, 150, 150, 255));
+        draw_text("No texture packs found", (rect.x + 5.0).floor(), (rect.y + 18.0).floor(), 14.0, theme.text_dim);
         return;
     }
 
@@ -291,9 +465,9 @@ fn draw_folder_selector(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     let prev_rect = Rect::new((rect.x + 4.0).round(), (rect.y + 4.0).round(), btn_size, btn_size);
     let prev_hovered = ctx.mouse.inside(&prev_rect);
     if prev_hovered {
-        draw_rectangle(prev_rect.x, prev_rect.y, prev_rect.w, prev_rect.h, Color::from_rgba(60, 60, 70, 255));
+        draw_rectangle(prev_rect.x, prev_rect.y, prev_rect.w, prev_rect.h, theme.button_hover);
     }
-    let prev_color = if prev_hovered { WHITE } else { Color::from_rgba(180, 180, 180, 255) };
+    let prev_color = if prev_hovered { WHITE } else { theme.text_secondary };
     draw_icon_centered(icon_font, icon::CIRCLE_CHEVRON_LEFT, &prev_rect, 14.0, prev_color);
     if ctx.mouse.clicked(&prev_rect) && state.selected_pack > 0 {
         state.selected_pack -= 1;
@@ -305,9 +479,9 @@ fn draw_folder_selector(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
     let next_rect = Rect::new((rect.right() - btn_size - 4.0).round(), (rect.y + 4.0).round(), btn_size, btn_size);
     let next_hovered = ctx.mouse.inside(&next_rect);
     if next_hovered {
-        draw_rectangle(next_rect.x, next_rect.y, next_rect.w, next_rect.h, Color::from_rgba(60, 60, 70, 255));
+        draw_rectangle(next_rect.x, next_rect.y, next_rect.w, next_rect.h, theme.button_hover);
     }
-    let next_color = if next_hovered { WHITE } else { Color::from_rgba(180, 180, 180, 255) };
+    let next_color = if next_hovered { WHITE } else { theme.text_secondary };
     draw_icon_centered(icon_font, icon::CIRCLE_CHEVRON_RIGHT, &next_rect, 14.0, next_color);
     if ctx.mouse.clicked(&next_rect) && state.selected_pack < state.texture_packs.len() - 1 {
         state.selected_pack += 1;
@@ -315,28 +489,65 @@ fn draw_folder_selector(ctx: &mut UiContext, rect: Rect, state: &mut EditorState
         state.texture_scroll = 0.0;
     }
 
-    // Pack name in center - properly centered vertically
+    // Thumbnail zoom, left of the next button
+    let zoom_in_rect = Rect::new(next_rect.x - btn_size - 2.0, next_rect.y, btn_size, btn_size);
+    let zoom_out_rect = Rect::new(zoom_in_rect.x - btn_size, next_rect.y, btn_size, btn_size);
+    if header_button(ctx, zoom_out_rect, icon_font, icon::MINUS) {
+        state.set_palette_thumb_size(state.palette_thumb_size - THUMB_SIZE_STEP);
+    }
+    if header_button(ctx, zoom_in_rect, icon_font, icon::PLUS) {
+        state.set_palette_thumb_size(state.palette_thumb_size + THUMB_SIZE_STEP);
+    }
+
+    // CLUT quantization on/off, when a pack declares a palette mode
+    let mut label_right = zoom_out_rect.x;
+    if state.has_palette_packs() {
+        let clut_rect = Rect::new(zoom_out_rect.x - btn_size - 2.0, next_rect.y, btn_size, btn_size);
+        let quantized = state.texture_packs.iter().any(|p| p.quantized);
+        if quantized {
+            draw_rectangle(clut_rect.x, clut_rect.y, clut_rect.w, clut_rect.h, theme.button_active);
+        }
+        if header_button(ctx, clut_rect, icon_font, icon::PALETTE) {
+            state.set_texture_quantization(!quantized);
+        }
+        if ctx.mouse.inside(&clut_rect) {
+            let mode = state.texture_packs.get(state.selected_pack)
+                .and_then(|p| p.settings.palette)
+                .map_or(String::new(), |m| format!(" (this pack: {})", m.label()));
+            ctx.set_tooltip(&format!("Quantize textures to their pack's CLUT{}", mode), ctx.mouse.x, ctx.mouse.y);
+        }
+        label_right = clut_rect.x;
+    }
+
+    // Pack name centered between the buttons - properly centered vertically
     let name = state.current_pack_name();
     let pack_count = state.texture_packs.len();
     let label = format!("{} ({}/{})", name, state.selected_pack + 1, pack_count);
     let font_size = 14.0;
     let text_dims = measure_text(&label, None, font_size as u16, 1.0);
-    let text_x = (rect.x + (rect.w - text_dims.width) * 0.5).round();
+    let text_x = (prev_rect.right() + (label_right - prev_rect.right() - text_dims.width) * 0.5).round();
     let text_y = (rect.y + (rect.h + text_dims.height) * 0.5).round();
     draw_text(&label, text_x, text_y, font_size, WHITE);
 }
 
+/// Flat icon button in the palette header
+fn header_button(ctx: &mut UiContext, rect: Rect, icon_font: Option<&Font>, icon_char: char) -> bool {
+    let hovered = ctx.mouse.inside(&rect);
+    if hovered {
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, ctx.theme.button_hover);
+    }
+    let color = if hovered { WHITE } else { ctx.theme.text_secondary };
+    draw_icon_centered(icon_font, icon_char, &rect, 12.0, color);
+    ctx.mouse.clicked(&rect)
+}
+
 /// Convert a raster texture to a macroquad texture
-fn raster_to_mq_texture(texture: &RasterTexture) -> Texture2D {
+pub(super) fn raster_to_mq_texture(texture: &RasterTexture) -> Texture2D {
     // Convert RGBA pixels
     let mut pixels = Vec::with_capacity(texture.width * texture.height * 4);
     for y in 0..texture.height {
         for x in 0..texture.width {
-            let color = texture.get_pixel(x, y);
-            pixels.push(color.r);
-            pixels.push(color.g);
-            pixels.push(color.b);
-            pixels.push(color.a);
+            pixels.extend_from_slice(&texture.get_pixel(x, y).to_bytes());
         }
     }
 
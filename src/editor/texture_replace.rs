@@ -0,0 +1,82 @@
+//! Batch Texture Replace
+//!
+//! State for the Replace Texture popup: retarget every face using one texture to
+//! the palette selection, across the level, the current room or the selection.
+
+use crate::world::{TextureRef, TextureScope};
+use super::EditorState;
+
+/// Where a texture replace applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplaceScope {
+    #[default]
+    Level,
+    CurrentRoom,
+    Selection,
+}
+
+impl ReplaceScope {
+    pub const ALL: [ReplaceScope; 3] = [ReplaceScope::Level, ReplaceScope::CurrentRoom, ReplaceScope::Selection];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReplaceScope::Level => "Level",
+            ReplaceScope::CurrentRoom => "Room",
+            ReplaceScope::Selection => "Selection",
+        }
+    }
+}
+
+/// Replace Texture popup state
+#[derive(Debug, Clone, Default)]
+pub struct TextureReplace {
+    /// Texture to replace (picked from the textures used in scope)
+    pub from: Option<TextureRef>,
+    pub scope: ReplaceScope,
+    pub scroll: f32,
+}
+
+impl EditorState {
+    /// Faces covered by a replace scope, right now
+    pub fn texture_scope(&self, scope: ReplaceScope) -> TextureScope {
+        match scope {
+            ReplaceScope::Level => TextureScope::Level,
+            ReplaceScope::CurrentRoom => TextureScope::Room(self.current_room),
+            ReplaceScope::Selection => {
+                let mut sectors: Vec<_> = self.multi_selection.iter()
+                    .chain(std::iter::once(&self.selection))
+                    .filter_map(|sel| sel.sector_coords())
+                    .collect();
+                sectors.sort_unstable();
+                sectors.dedup();
+                TextureScope::Sectors(sectors)
+            }
+        }
+    }
+
+    /// Replace the popup's source texture with the palette selection (one undo step)
+    pub fn apply_texture_replace(&mut self) {
+        let Some(replace) = &self.texture_replace else { return };
+        let Some(from) = replace.from.clone() else {
+            self.set_status("Pick a texture to replace first", 2.0);
+            return;
+        };
+        let to = self.selected_texture.clone();
+        let scope = self.texture_scope(replace.scope);
+        let count = self.level.texture_usage(&scope).iter()
+            .find(|(t, _)| *t == from)
+            .map_or(0, |(_, n)| *n);
+        if count == 0 || from == to {
+            self.set_status("Nothing to replace", 2.0);
+            return;
+        }
+
+        self.save_undo(&format!("Replace {} with {} ({} faces)", from.name, to.name, count));
+        let replaced = self.level.replace_texture(&from, &to, &scope);
+        self.mark_all_rooms_dirty();
+        if let Some(replace) = &mut self.texture_replace {
+            replace.from = None;
+        }
+        self.set_status(&format!("Replaced {}/{} with {}/{} on {} face(s)", from.pack, from.name, to.pack, to.name, replaced), 3.0);
+    }
+}
@@ -0,0 +1,153 @@
+//! Texture Usage Report
+//!
+//! Cross-checks every face's texture reference against the loaded texture packs,
+//! so references to deleted textures show up before they render as "MISSING"
+//! placeholders.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use crate::rasterizer::Texture;
+use crate::world::{aliased_pack, Level, PackAliases, TextureRef};
+use super::TexturePack;
+
+/// Result of scanning a level's texture references
+#[derive(Debug, Clone, Default)]
+pub struct TextureReport {
+    /// Distinct textures referenced (resolved or not)
+    pub distinct_used: usize,
+    /// Missing textures as "pack/name" with how many faces use each
+    pub missing: Vec<(String, usize)>,
+    /// Faces referencing a missing texture
+    pub missing_refs: usize,
+    /// Faces with no texture at all (explicit fallback)
+    pub untextured: usize,
+}
+
+impl TextureReport {
+    /// Scan all faces in `level` against `packs` (with the level's pack substitutes)
+    pub fn scan(level: &Level, packs: &[TexturePack]) -> Self {
+        let mut used = HashSet::new();
+        let mut missing: BTreeMap<String, usize> = BTreeMap::new();
+        let mut report = Self::default();
+
+        for tex in level.texture_refs() {
+            if !tex.is_valid() {
+                report.untextured += 1;
+                continue;
+            }
+            used.insert((tex.pack.as_str(), tex.name.as_str()));
+            if is_missing(tex, packs, &level.pack_aliases) {
+                *missing.entry(format!("{}/{}", tex.pack, tex.name)).or_default() += 1;
+                report.missing_refs += 1;
+            }
+        }
+
+        report.distinct_used = used.len();
+        report.missing = missing.into_iter().collect();
+        report
+    }
+
+    /// One-line summary for the status bar
+    pub fn summary(&self) -> String {
+        if self.missing_refs == 0 {
+            format!("{} textures used, none missing", self.distinct_used)
+        } else {
+            format!(
+                "{} references to {} missing textures ({} textures used)",
+                self.missing_refs, self.missing.len(), self.distinct_used
+            )
+        }
+    }
+}
+
+/// Does this reference name a texture that no loaded pack has, with its pack
+/// substituted per `aliases`? (Empty references are an explicit fallback, not missing.)
+pub fn is_missing(tex: &TextureRef, packs: &[TexturePack], aliases: &PackAliases) -> bool {
+    let pack = aliased_pack(aliases, &tex.pack);
+    tex.is_valid() && !packs.iter()
+        .filter(|p| p.name == pack)
+        .any(|p| p.textures.iter().any(|t| t.name == tex.name))
+}
+
+/// Cache of "MISSING" placeholder textures, keyed by (pack, name)
+#[derive(Default)]
+pub struct MissingTextures {
+    cache: HashMap<(String, String), Texture>,
+}
+
+impl MissingTextures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Placeholders for every texture `level` references that `is_loaded` rejects,
+    /// sorted by (pack, name) so indices stay put from frame to frame
+    pub fn placeholders(&mut self, level: &Level, is_loaded: impl Fn(&TextureRef) -> bool) -> Vec<(&(String, String), &Texture)> {
+        let missing: BTreeSet<(&str, &str)> = level.texture_refs()
+            .filter(|t| t.is_valid() && !is_loaded(t))
+            .map(|t| (t.pack.as_str(), t.name.as_str()))
+            .collect();
+        for &(pack, name) in &missing {
+            self.cache.entry((pack.to_string(), name.to_string())).or_insert_with(|| Texture::missing(name));
+        }
+        let mut placeholders: Vec<_> = self.cache.iter()
+            .filter(|((pack, name), _)| missing.contains(&(pack.as_str(), name.as_str())))
+            .collect();
+        placeholders.sort_by(|a, b| a.0.cmp(b.0));
+        placeholders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::{Texture, Vec3};
+    use crate::world::{Direction, Room};
+
+    #[test]
+    fn finds_and_strips_missing_textures() {
+        let mut texture = Texture::new(4, 4);
+        texture.name = "FLOOR".to_string();
+        let packs = [TexturePack {
+            name: "pack".to_string(),
+            path: Default::default(),
+            textures: vec![texture],
+            settings: Default::default(),
+            quantized: false,
+        }];
+
+        let mut room = Room::new(0, Vec3::ZERO, 2, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::new("pack", "FLOOR"));
+        room.set_floor(1, 0, 0.0, TextureRef::new("pack", "GONE"));
+        room.add_wall(1, 0, Direction::East, 0.0, 1024.0, TextureRef::new("old", "WALL"));
+        room.add_wall(0, 0, Direction::West, 0.0, 1024.0, TextureRef::new("old", "WALL"));
+        let mut level = Level::new();
+        level.add_room(room);
+
+        let report = TextureReport::scan(&level, &packs);
+        assert_eq!(report.distinct_used, 3);
+        assert_eq!(report.missing, vec![("old/WALL".to_string(), 2), ("pack/GONE".to_string(), 1)]);
+        assert_eq!(report.missing_refs, 3);
+
+        let stripped = level.retarget_textures(|t| is_missing(t, &packs, &PackAliases::new()).then(TextureRef::none));
+        assert_eq!(stripped, 3);
+        let report = TextureReport::scan(&level, &packs);
+        assert_eq!(report.missing_refs, 0);
+        assert_eq!(report.untextured, 3);
+    }
+
+    #[test]
+    fn placeholders_cover_only_missing_textures() {
+        let mut room = Room::new(0, Vec3::ZERO, 3, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::new("pack", "FLOOR"));
+        room.set_floor(1, 0, 0.0, TextureRef::new("pack", "GONE"));
+        room.set_floor(2, 0, 0.0, TextureRef::new("old", "WALL"));
+        let mut level = Level::new();
+        level.add_room(room);
+
+        let mut missing = MissingTextures::new();
+        let placeholders = missing.placeholders(&level, |t| t.name == "FLOOR");
+        let keys: Vec<_> = placeholders.iter().map(|(key, tex)| (key.1.as_str(), tex.name.as_str())).collect();
+        assert_eq!(keys, vec![("WALL", "WALL"), ("GONE", "GONE")]);
+        assert!(placeholders.iter().all(|(_, tex)| tex.pixels.len() == tex.width * tex.height));
+    }
+}
@@ -0,0 +1,40 @@
+//! Two-click picks
+//!
+//! Tools that take a start and an end point click once to anchor the start, show a
+//! live preview while the second point is chosen, and finish on the second click.
+//! Escape (or picking another tool) cancels. Used by the Wall tool's wall runs.
+
+/// Pending start point of a two-click pick
+#[derive(Debug, Clone, Copy)]
+pub struct TwoClick<P> {
+    start: Option<P>,
+}
+
+impl<P> Default for TwoClick<P> {
+    fn default() -> Self {
+        Self { start: None }
+    }
+}
+
+impl<P: Copy> TwoClick<P> {
+    /// First click: remember the start point
+    pub fn anchor(&mut self, point: P) {
+        self.start = Some(point);
+    }
+
+    /// Start point, while waiting for the second click
+    pub fn start(&self) -> Option<P> {
+        self.start
+    }
+
+    /// Second click: the (start, end) pair, if a start was anchored. The pick is
+    /// over either way; anchor again to keep going from the same start.
+    pub fn finish(&mut self, end: P) -> Option<(P, P)> {
+        self.start.take().map(|start| (start, end))
+    }
+
+    /// Drop the start point. Returns true if one was anchored.
+    pub fn cancel(&mut self) -> bool {
+        self.start.take().is_some()
+    }
+}
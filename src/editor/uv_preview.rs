@@ -0,0 +1,253 @@
+//! UV Preview - the face's texture with its UV quad over it, edited by dragging
+//!
+//! Shown in the face containers of the properties panel. Drag inside the preview to
+//! pan all four UVs, drag a corner handle to move that corner alone (written to the
+//! face's raw `uv` array) and right-click to go back to the default mapping. Moves
+//! snap to whole texels; one drag is one undo step, like the wall corner fields.
+
+use macroquad::prelude::*;
+use crate::rasterizer::{Texture as RasterTexture, Vec2};
+use crate::ui::{Rect, UiContext};
+use crate::world::{aliased_pack, TextureRef};
+use super::texture_palette::raster_to_mq_texture;
+use super::{EditorState, SectorFace};
+
+/// Side of the preview square in pixels
+pub const UV_PREVIEW_SIZE: f32 = 88.0;
+
+/// Most texture tiles shown per side when the UVs reach past 0-1
+const MAX_TILES: f32 = 4.0;
+
+/// Pick radius of a corner handle in pixels
+const HANDLE_PICK: f32 = 6.0;
+
+/// What a UV drag moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UvHandle {
+    /// All four corners together
+    Pan,
+    /// One corner, by index into the face's UVs
+    Corner(usize),
+}
+
+/// UV drag in progress in a preview
+#[derive(Debug, Clone, Copy)]
+pub struct UvDrag {
+    pub room: usize,
+    pub x: usize,
+    pub z: usize,
+    pub face: SectorFace,
+    pub handle: UvHandle,
+    /// Mouse position at drag start
+    pub anchor: (f32, f32),
+    /// UVs at drag start
+    pub start: [Vec2; 4],
+}
+
+/// A change made in the preview
+#[derive(Debug, Clone, Copy)]
+pub enum UvEdit {
+    Set([Vec2; 4]),
+    /// Back to the default mapping (`uv = None`)
+    Reset,
+}
+
+/// Area of UV space the preview shows: (top-left corner, side). Whole texture tiles,
+/// covering 0-1 and the UVs.
+fn uv_view(uvs: &[Vec2; 4]) -> (Vec2, f32) {
+    let min_u = uvs.iter().map(|uv| uv.x).fold(0.0, f32::min).floor();
+    let min_v = uvs.iter().map(|uv| uv.y).fold(0.0, f32::min).floor();
+    let max_u = uvs.iter().map(|uv| uv.x).fold(1.0, f32::max).ceil();
+    let max_v = uvs.iter().map(|uv| uv.y).fold(1.0, f32::max).ceil();
+    let side = (max_u - min_u).max(max_v - min_v).clamp(1.0, MAX_TILES);
+    (Vec2::new(min_u, min_v), side)
+}
+
+/// UVs after dragging `handle` by `delta` (in UV units), snapped to `snap` steps
+fn dragged_uvs(start: [Vec2; 4], handle: UvHandle, delta: Vec2, snap: Vec2) -> [Vec2; 4] {
+    let delta = Vec2::new((delta.x / snap.x).round() * snap.x, (delta.y / snap.y).round() * snap.y);
+    let mut uvs = start;
+    for (i, uv) in uvs.iter_mut().enumerate() {
+        if handle == UvHandle::Pan || handle == UvHandle::Corner(i) {
+            *uv = Vec2::new(uv.x + delta.x, uv.y + delta.y);
+        }
+    }
+    uvs
+}
+
+fn same_uvs(a: &[Vec2; 4], b: &[Vec2; 4]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a.x == b.x && a.y == b.y)
+}
+
+/// Texture a reference resolves to in the loaded packs
+fn find_texture<'a>(state: &'a EditorState, texture: &TextureRef) -> Option<&'a RasterTexture> {
+    let pack = aliased_pack(&state.level.pack_aliases, &texture.pack);
+    state.texture_packs.iter()
+        .find(|p| p.name == pack)?
+        .textures.iter()
+        .find(|tex| tex.name == texture.name)
+}
+
+/// Draw the preview for a face with `uvs` and handle dragging. Returns the edit to
+/// write back with `EditorState::set_face_uv` (undo is already recorded).
+pub fn draw_uv_preview(
+    ctx: &mut UiContext,
+    rect: Rect,
+    (room, gx, gz, face): (usize, usize, usize, SectorFace),
+    uvs: [Vec2; 4],
+    texture: &TextureRef,
+    state: &mut EditorState,
+) -> Option<UvEdit> {
+    let theme = ctx.theme;
+    let drag = state.uv_drag.filter(|d| d.room == room && d.x == gx && d.z == gz && d.face == face);
+
+    // Keep the view still while dragging so the handle stays under the mouse
+    let (origin, side) = uv_view(&drag.map_or(uvs, |d| d.start));
+    let scale = rect.w / side;
+    let to_screen = |uv: Vec2| ((rect.x + (uv.x - origin.x) * scale).floor(), (rect.y + (uv.y - origin.y) * scale).floor());
+
+    // Texture tiles (checker where the texture is missing)
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.inset_bg);
+    let raster = find_texture(state, texture);
+    let texel_snap = raster.map_or(Vec2::new(1.0 / 64.0, 1.0 / 64.0), |tex| {
+        Vec2::new(1.0 / tex.width.max(1) as f32, 1.0 / tex.height.max(1) as f32)
+    });
+    match raster {
+        Some(raster) => {
+            let tile = raster_to_mq_texture(raster);
+            let tiles = side as usize;
+            for ty in 0..tiles {
+                for tx in 0..tiles {
+                    draw_texture_ex(
+                        &tile,
+                        rect.x + tx as f32 * scale,
+                        rect.y + ty as f32 * scale,
+                        if tx as f32 + origin.x == 0.0 && ty as f32 + origin.y == 0.0 { WHITE } else { Color::new(0.7, 0.7, 0.7, 1.0) },
+                        DrawTextureParams { dest_size: Some(vec2(scale, scale)), ..Default::default() },
+                    );
+                }
+            }
+        }
+        None => {
+            draw_text("no texture", (rect.x + 4.0).floor(), (rect.y + 14.0).floor(), 12.0, theme.text_faint);
+        }
+    }
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.border);
+
+    // UV quad and corner handles
+    let corners = uvs.map(to_screen);
+    let fill = Color::new(theme.highlight.r, theme.highlight.g, theme.highlight.b, 0.15);
+    for (a, b, c) in [(0, 1, 2), (0, 2, 3)] {
+        draw_triangle(corners[a].into(), corners[b].into(), corners[c].into(), fill);
+    }
+    for i in 0..4 {
+        let (a, b) = (corners[i], corners[(i + 1) % 4]);
+        draw_line(a.0, a.1, b.0, b.1, 1.0, theme.highlight);
+    }
+    let mouse = (ctx.mouse.x, ctx.mouse.y);
+    let hovered_corner = (0..4).find(|&i| {
+        (corners[i].0 - mouse.0).abs() <= HANDLE_PICK && (corners[i].1 - mouse.1).abs() <= HANDLE_PICK
+    });
+    for (i, &(cx, cy)) in corners.iter().enumerate() {
+        let active = drag.is_some_and(|d| d.handle == UvHandle::Corner(i)) || hovered_corner == Some(i);
+        let size = if active { 6.0 } else { 4.0 };
+        draw_rectangle(cx - size / 2.0, cy - size / 2.0, size, size, if active { WHITE } else { theme.highlight });
+    }
+
+    let hovered = ctx.mouse.inside(&rect);
+    if hovered && drag.is_none() {
+        ctx.set_tooltip("Drag to pan the UVs, drag a corner to move it, right-click to reset", mouse.0, mouse.1);
+        if ctx.mouse.left_pressed {
+            state.uv_drag = Some(UvDrag {
+                room,
+                x: gx,
+                z: gz,
+                face,
+                handle: hovered_corner.map_or(UvHandle::Pan, UvHandle::Corner),
+                anchor: mouse,
+                start: uvs,
+            });
+            state.begin_transaction(&format!("Edit UVs ({}, {})", gx, gz));
+            return None;
+        }
+        if is_mouse_button_pressed(MouseButton::Right) {
+            state.save_undo(&format!("Reset UVs ({}, {})", gx, gz));
+            return Some(UvEdit::Reset);
+        }
+    }
+
+    let drag = drag?;
+    if !ctx.mouse.left_down {
+        state.uv_drag = None;
+        state.end_transaction();
+        return None;
+    }
+    let delta = Vec2::new((mouse.0 - drag.anchor.0) / scale, (mouse.1 - drag.anchor.1) / scale);
+    let new_uvs = dragged_uvs(drag.start, drag.handle, delta, texel_snap);
+    if same_uvs(&new_uvs, &uvs) {
+        return None;
+    }
+    state.save_undo(&format!("Edit UVs ({}, {})", gx, gz));
+    Some(UvEdit::Set(new_uvs))
+}
+
+impl EditorState {
+    /// Write a face's UVs (None = default mapping). Caller records undo.
+    pub fn set_face_uv(&mut self, room_idx: usize, gx: usize, gz: usize, face: SectorFace, uv: Option<[Vec2; 4]>) {
+        let Some(sector) = self.level.rooms.get_mut(room_idx).and_then(|r| r.get_sector_mut(gx, gz)) else {
+            return;
+        };
+        let slot = match face {
+            SectorFace::Floor => sector.floor.as_mut().map(|f| &mut f.uv),
+            SectorFace::Ceiling => sector.ceiling.as_mut().map(|c| &mut c.uv),
+            wall => wall.wall_in_mut(sector).map(|(_, w)| &mut w.uv),
+        };
+        if let Some(slot) = slot {
+            *slot = uv;
+            self.mark_room_dirty(room_idx);
+        }
+    }
+
+    /// Apply an edit from `draw_uv_preview`
+    pub fn apply_uv_edit(&mut self, room_idx: usize, gx: usize, gz: usize, face: SectorFace, edit: UvEdit) {
+        match edit {
+            UvEdit::Set(uvs) => self.set_face_uv(room_idx, gx, gz, face, Some(uvs)),
+            UvEdit::Reset => {
+                self.set_face_uv(room_idx, gx, gz, face, None);
+                self.set_status("UVs reset", 1.5);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit() -> [Vec2; 4] {
+        [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)]
+    }
+
+    #[test]
+    fn view_covers_uvs_in_whole_tiles() {
+        let (origin, side) = uv_view(&unit());
+        assert_eq!((origin.x, origin.y, side), (0.0, 0.0, 1.0));
+
+        let mut uvs = unit();
+        uvs[2] = Vec2::new(2.5, 1.0);
+        uvs[0] = Vec2::new(-0.25, 0.0);
+        let (origin, side) = uv_view(&uvs);
+        assert_eq!((origin.x, origin.y, side), (-1.0, 0.0, 4.0));
+    }
+
+    #[test]
+    fn drags_snap_to_texels() {
+        let snap = Vec2::new(1.0 / 16.0, 1.0 / 16.0);
+        let panned = dragged_uvs(unit(), UvHandle::Pan, Vec2::new(0.26, -0.01), snap);
+        assert!(panned.iter().zip(unit()).all(|(a, b)| a.x == b.x + 0.25 && a.y == b.y));
+
+        let moved = dragged_uvs(unit(), UvHandle::Corner(2), Vec2::new(-0.5, 0.1), snap);
+        assert!(same_uvs(&[moved[0], moved[1], moved[3], moved[3]], &[unit()[0], unit()[1], unit()[3], unit()[3]]));
+        assert_eq!((moved[2].x, moved[2].y), (0.5, 1.125));
+    }
+}
@@ -0,0 +1,236 @@
+//! View Axes
+//!
+//! Predictable camera angles for alignment work in the 3D viewport. Holding Ctrl
+//! while looking around (right drag) snaps pitch and yaw to 45° steps. The
+//! canonical Top, Front and Right views (numpad 7, 1 and 3, or a click on the axis
+//! gizmo) also switch to an orthographic projection, scaled to match what the
+//! perspective view showed at the point under the view center. Looking around
+//! again, or numpad 5, returns to perspective.
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+use macroquad::prelude::*;
+use crate::rasterizer::{Camera, Vec3};
+use crate::ui::{Rect, UiContext};
+use crate::world::SECTOR_SIZE;
+use super::EditorState;
+
+/// Free-look pitch limit (the canonical Top view alone looks straight down)
+const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+
+/// Rotation step while Ctrl is held
+const SNAP_STEP: f32 = FRAC_PI_4;
+
+/// Orthographic framing distance when nothing is under the view center
+const DEFAULT_VIEW_DISTANCE: f32 = SECTOR_SIZE * 8.0;
+
+/// Orthographic framing distance range (zooming moves within it)
+const MIN_ORTHO_DISTANCE: f32 = SECTOR_SIZE * 0.5;
+const MAX_ORTHO_DISTANCE: f32 = SECTOR_SIZE * 64.0;
+
+/// Axis gizmo radius (pixels) and its margin from the viewport corner
+const GIZMO_RADIUS: f32 = 26.0;
+const GIZMO_MARGIN: f32 = 8.0;
+
+/// Axis-aligned orthographic views
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanonicalView {
+    /// Looking straight down, +X to the right and north (-Z) up
+    Top,
+    /// Looking north (-Z), +X to the right
+    Front,
+    /// Looking west (-X) from the east side, north to the right
+    Right,
+}
+
+impl CanonicalView {
+    pub const ALL: [CanonicalView; 3] = [CanonicalView::Top, CanonicalView::Front, CanonicalView::Right];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CanonicalView::Top => "Top",
+            CanonicalView::Front => "Front",
+            CanonicalView::Right => "Right",
+        }
+    }
+
+    /// Camera (pitch, yaw) of the view
+    pub fn rotation(self) -> (f32, f32) {
+        match self {
+            CanonicalView::Top => (FRAC_PI_2, PI),
+            CanonicalView::Front => (0.0, PI),
+            CanonicalView::Right => (0.0, -FRAC_PI_2),
+        }
+    }
+
+    /// Direction the view looks in (as `Camera::update_basis` computes it)
+    fn forward(self) -> Vec3 {
+        let (pitch, yaw) = self.rotation();
+        Vec3::new(pitch.cos() * yaw.sin(), -pitch.sin(), pitch.cos() * yaw.cos())
+    }
+
+    /// The view whose axis is closest to where `camera` looks (either way along it)
+    pub fn nearest(camera: &Camera) -> Self {
+        let alignment = |view: &CanonicalView| view.forward().dot(camera.basis_z).abs();
+        Self::ALL.into_iter()
+            .max_by(|a, b| alignment(a).total_cmp(&alignment(b)))
+            .unwrap_or(CanonicalView::Top)
+    }
+}
+
+/// Free-look (pitch, yaw) snapped to 45° steps
+pub(super) fn snap_rotation(pitch: f32, yaw: f32) -> (f32, f32) {
+    let snap = |angle: f32| (angle / SNAP_STEP).round() * SNAP_STEP;
+    (snap(pitch).clamp(-PITCH_LIMIT, PITCH_LIMIT), snap(yaw))
+}
+
+impl EditorState {
+    /// Switch the 3D camera to a canonical orthographic view, keeping the point
+    /// under the view center (or the current orthographic framing) in view
+    pub fn set_canonical_view(&mut self, view: CanonicalView) {
+        let camera = &self.camera_3d;
+        let distance = camera.ortho_distance.unwrap_or_else(|| {
+            let reach = camera.basis_z * (MAX_ORTHO_DISTANCE * 2.0);
+            self.level.pick(camera.position, camera.position + reach)
+                .map_or(DEFAULT_VIEW_DISTANCE, |hit| (hit.point - camera.position).len())
+        }).clamp(MIN_ORTHO_DISTANCE, MAX_ORTHO_DISTANCE);
+        let target = camera.position + camera.basis_z * distance;
+
+        let (pitch, yaw) = view.rotation();
+        self.camera_transition = None;
+        self.camera_3d.rotation_x = pitch;
+        self.camera_3d.rotation_y = yaw;
+        self.camera_3d.update_basis();
+        self.camera_3d.position = target - self.camera_3d.basis_z * distance;
+        self.camera_3d.ortho_distance = Some(distance);
+        self.set_status(&format!("{} view (orthographic): right-drag or numpad 5 for perspective", view.label()), 3.0);
+    }
+
+    /// Look around by (pitch, yaw) deltas, snapping the shown rotation to 45° steps
+    /// if `snap` is set
+    pub(super) fn rotate_viewport_camera(&mut self, d_pitch: f32, d_yaw: f32, snap: bool) {
+        let (pitch, yaw) = self.viewport_free_rotation;
+        let free = ((pitch + d_pitch).clamp(-PITCH_LIMIT, PITCH_LIMIT), yaw + d_yaw);
+        self.viewport_free_rotation = free;
+        let (pitch, yaw) = if snap { snap_rotation(free.0, free.1) } else { free };
+        self.camera_3d.rotation_x = pitch;
+        self.camera_3d.rotation_y = yaw;
+        self.camera_3d.update_basis();
+    }
+
+    /// Back to the perspective projection (no-op if already there)
+    pub fn leave_ortho_view(&mut self) {
+        if self.camera_3d.ortho_distance.take().is_some() {
+            self.set_status("Perspective view", 1.5);
+        }
+    }
+
+    /// Zoom an orthographic view: move toward the framed point by `amount` world
+    /// units (negative backs off), keeping the scale tied to the distance
+    pub(super) fn zoom_ortho_view(&mut self, amount: f32) {
+        let camera = &mut self.camera_3d;
+        let Some(distance) = camera.ortho_distance else { return };
+        let new_distance = (distance - amount).clamp(MIN_ORTHO_DISTANCE, MAX_ORTHO_DISTANCE);
+        camera.position = camera.position + camera.basis_z * (distance - new_distance);
+        camera.ortho_distance = Some(new_distance);
+    }
+}
+
+/// Screen rect of the axis gizmo, in the bottom-right corner of the viewport
+pub(super) fn gizmo_rect(viewport: &Rect) -> Rect {
+    let size = GIZMO_RADIUS * 2.0;
+    Rect::new(viewport.right() - size - GIZMO_MARGIN, viewport.bottom() - size - GIZMO_MARGIN, size, size)
+}
+
+/// Draw the axis gizmo: world X, Y and Z as seen by the camera (positive ends
+/// labeled, negative ends dimmed). Clicking it jumps to the nearest canonical view.
+pub(super) fn draw_axis_gizmo(ctx: &mut UiContext, viewport: &Rect, state: &mut EditorState) {
+    let rect = gizmo_rect(viewport);
+    let (cx, cy) = (rect.x + GIZMO_RADIUS, rect.y + GIZMO_RADIUS);
+    let hovered = ctx.mouse.inside(&rect);
+    let camera = &state.camera_3d;
+
+    let bg = if hovered { Color::from_rgba(60, 60, 70, 200) } else { Color::from_rgba(30, 30, 35, 160) };
+    draw_circle(cx, cy, GIZMO_RADIUS, bg);
+
+    // Back-facing axis ends first, so the ones pointing at the viewer draw on top
+    let reach = GIZMO_RADIUS - 7.0;
+    let axes = [
+        (Vec3::new(1.0, 0.0, 0.0), "X", Color::from_rgba(230, 90, 90, 255)),
+        (Vec3::new(0.0, 1.0, 0.0), "Y", Color::from_rgba(120, 210, 100, 255)),
+        (Vec3::new(0.0, 0.0, 1.0), "Z", Color::from_rgba(100, 150, 240, 255)),
+    ];
+    let mut ends: Vec<(f32, f32, f32, Option<&str>, Color)> = axes.iter()
+        .flat_map(|&(axis, label, color)| {
+            let screen = |dir: Vec3| (dir.dot(camera.basis_x) * reach, dir.dot(camera.basis_y) * reach, dir.dot(camera.basis_z));
+            let (px, py, pz) = screen(axis);
+            let (nx, ny, nz) = screen(axis * -1.0);
+            [(px, py, pz, Some(label), color), (nx, ny, nz, None, color.with_alpha(0.45))]
+        })
+        .collect();
+    ends.sort_by(|a, b| b.2.total_cmp(&a.2));
+    for (x, y, _, label, color) in ends {
+        if let Some(label) = label {
+            draw_line(cx, cy, cx + x, cy + y, 2.0, color);
+            draw_circle(cx + x, cy + y, 6.0, color);
+            let width = measure_text(label, None, 11, 1.0).width;
+            draw_text(label, (cx + x - width * 0.5).floor(), (cy + y + 4.0).floor(), 11.0, BLACK);
+        } else {
+            draw_circle(cx + x, cy + y, 4.0, color);
+        }
+    }
+
+    if camera.ortho_distance.is_some() {
+        let label = CanonicalView::nearest(camera).label();
+        let width = measure_text(label, None, 12, 1.0).width;
+        draw_text(label, (cx - width * 0.5).floor(), (rect.y - 4.0).floor(), 12.0, WHITE);
+    }
+
+    if hovered {
+        let nearest = CanonicalView::nearest(camera);
+        ctx.set_tooltip(&format!("{} view (orthographic). Numpad 7 / 1 / 3: Top / Front / Right", nearest.label()), ctx.mouse.x, ctx.mouse.y);
+        if ctx.mouse.left_pressed {
+            state.set_canonical_view(nearest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapped_rotation_lands_on_45_degree_steps() {
+        let (pitch, yaw) = snap_rotation(0.5, 2.5);
+        assert_eq!(pitch, FRAC_PI_4);
+        assert_eq!(yaw, 3.0 * FRAC_PI_4);
+        // Pitch stays short of straight up or down
+        assert_eq!(snap_rotation(1.5, 0.0).0, PITCH_LIMIT);
+        assert_eq!(snap_rotation(-0.3, -0.3), (0.0, 0.0));
+    }
+
+    #[test]
+    fn canonical_views_look_along_the_axes() {
+        let mut camera = Camera::new();
+        for view in CanonicalView::ALL {
+            let (pitch, yaw) = view.rotation();
+            camera.rotation_x = pitch;
+            camera.rotation_y = yaw;
+            camera.update_basis();
+            let expected = match view {
+                CanonicalView::Top => Vec3::new(0.0, -1.0, 0.0),
+                CanonicalView::Front => Vec3::new(0.0, 0.0, -1.0),
+                CanonicalView::Right => Vec3::new(-1.0, 0.0, 0.0),
+            };
+            assert!((camera.basis_z - expected).len() < 1e-5, "{:?}", view);
+            // Straight down still has a proper screen basis
+            assert!((camera.basis_x.len() - 1.0).abs() < 1e-5 && (camera.basis_y.len() - 1.0).abs() < 1e-5);
+            assert_eq!(CanonicalView::nearest(&camera), view);
+        }
+
+        // A slightly tilted look north is nearest to Front
+        camera.rotation_x = 0.3;
+        camera.rotation_y = PI + 0.2;
+        camera.update_basis();
+        assert_eq!(CanonicalView::nearest(&camera), CanonicalView::Front);
+    }
+}
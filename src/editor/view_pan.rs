@@ -0,0 +1,85 @@
+//! View Panning
+//!
+//! The grid view and the 3D viewport both pan with a middle-mouse drag, or a left
+//! drag while Space is held. While a drag operation runs (moving a room or vertex
+//! heights, painting, a wall run or measure) and the cursor nears a view's edge,
+//! the view scrolls that way, faster the closer the cursor gets. Drags recompute
+//! their target from world coordinates every frame, so they follow the scroll.
+//!
+//! Wheel zoom (grid view, texture palette thumbnails) goes in equal ratio steps
+//! and keeps what's under the cursor in place.
+
+use macroquad::prelude::*;
+use crate::ui::{Rect, UiContext};
+
+/// Distance (pixels) from a view's edge where edge scrolling starts
+const EDGE_SCROLL_MARGIN: f32 = 20.0;
+/// Edge scroll speed with the cursor on (or past) the edge (pixels per second)
+const EDGE_SCROLL_SPEED: f32 = 600.0;
+
+/// Zoom factor per wheel notch
+pub(super) const ZOOM_STEP: f32 = 1.15;
+
+/// Pan offset that keeps the point under the cursor in place when the zoom
+/// scales by `factor`. `anchor` is the cursor relative to where the view's origin
+/// would be without panning (a view whose screen positions are the origin plus
+/// `offset` plus world coordinates times the zoom).
+pub(super) fn zoom_about(offset: (f32, f32), anchor: (f32, f32), factor: f32) -> (f32, f32) {
+    (
+        offset.0 + (anchor.0 - offset.0) * (1.0 - factor),
+        offset.1 + (anchor.1 - offset.1) * (1.0 - factor),
+    )
+}
+
+/// Is a pan drag held: middle mouse, or left mouse with Space (not while typing)?
+pub(super) fn pan_held(ctx: &UiContext) -> bool {
+    ctx.mouse.middle_down
+        || (ctx.mouse.left_down && is_key_down(KeyCode::Space) && !ctx.text_input_focused())
+}
+
+/// Edge scroll for the cursor at (`x`, `y`) over `rect`: per axis from -1 (on or
+/// past the left/top edge) to 1 (right/bottom), 0 away from the edges
+pub(super) fn edge_scroll(rect: &Rect, x: f32, y: f32) -> (f32, f32) {
+    let axis = |pos: f32, min: f32, max: f32| {
+        let near_min = (1.0 - (pos - min) / EDGE_SCROLL_MARGIN).clamp(0.0, 1.0);
+        let near_max = (1.0 - (max - pos) / EDGE_SCROLL_MARGIN).clamp(0.0, 1.0);
+        near_max - near_min
+    };
+    (axis(x, rect.x, rect.right()), axis(y, rect.y, rect.bottom()))
+}
+
+/// Edge scroll distance (pixels) this frame, frame time capped like camera moves
+pub(super) fn edge_scroll_step() -> f32 {
+    EDGE_SCROLL_SPEED * get_frame_time().min(0.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_scroll_grows_toward_the_edge() {
+        let rect = Rect::new(100.0, 100.0, 400.0, 300.0);
+        assert_eq!(edge_scroll(&rect, 300.0, 250.0), (0.0, 0.0));
+        assert_eq!(edge_scroll(&rect, 120.0, 380.0), (0.0, 0.0));
+        assert_eq!(edge_scroll(&rect, 110.0, 250.0), (-0.5, 0.0));
+        assert_eq!(edge_scroll(&rect, 500.0, 100.0), (1.0, -1.0));
+        // Past the edge (a drag that left the view) scrolls at full speed
+        assert_eq!(edge_scroll(&rect, 700.0, 450.0), (1.0, 1.0));
+    }
+
+    #[test]
+    fn zoom_keeps_the_point_under_the_cursor() {
+        let (offset, zoom) = ((30.0, -20.0), 0.1);
+        let anchor = (130.0, 80.0);
+        // World point under the cursor before and after
+        let world = |offset: (f32, f32), zoom: f32| ((anchor.0 - offset.0) / zoom, (anchor.1 - offset.1) / zoom);
+        let before = world(offset, zoom);
+        for factor in [ZOOM_STEP, 1.0 / ZOOM_STEP, 4.0] {
+            let after = world(zoom_about(offset, anchor, factor), zoom * factor);
+            assert!((after.0 - before.0).abs() < 1e-3 && (after.1 - before.1).abs() < 1e-3);
+        }
+        // Zooming at the origin itself doesn't pan
+        assert_eq!(zoom_about(offset, offset, 2.0), offset);
+    }
+}
@@ -2,50 +2,113 @@
 //!
 //! Sector-based geometry system - selection works on faces within sectors.
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use macroquad::prelude::*;
 use crate::ui::{Rect, UiContext};
 use crate::rasterizer::{
-    Framebuffer, Texture as RasterTexture, render_mesh, Color as RasterColor, Vec3,
-    WIDTH, HEIGHT, WIDTH_HI, HEIGHT_HI,
-    perspective_transform,
+    Framebuffer, Texture as RasterTexture, render_mesh_with_scratch, render_world_label, Color as RasterColor, Vec3,
+    Vertex, Face as RasterFace, WIDTH, HEIGHT, WIDTH_HI, HEIGHT_HI,
 };
-use crate::world::SECTOR_SIZE;
-use super::{EditorState, EditorTool, Selection, SectorFace};
-
-/// Project a world-space point to framebuffer coordinates
-fn world_to_screen(
-    world_pos: Vec3,
-    camera_pos: Vec3,
-    basis_x: Vec3,
-    basis_y: Vec3,
-    basis_z: Vec3,
-    fb_width: usize,
-    fb_height: usize,
-) -> Option<(f32, f32)> {
-    let rel = world_pos - camera_pos;
-    let cam_z = rel.dot(basis_z);
-
-    // Behind camera
-    if cam_z <= 0.1 {
-        return None;
-    }
+use crate::world::{Direction, Room, TextureIndex, SECTOR_SIZE};
+use super::{EditorState, EditorTool, HeightSnap, Selection, SectorFace};
+use super::profiler::profile_scope;
+use super::measure::{label_3d, MEASURE_SNAP_PX};
+use super::view_pan::{edge_scroll, edge_scroll_step, pan_held};
+use super::view_axes::{draw_axis_gizmo, gizmo_rect, CanonicalView};
+
+/// Length (world units) of the Measure tool's pick ray
+const MEASURE_PICK_DISTANCE: f32 = 256.0 * SECTOR_SIZE;
+
+/// Fly speed factor per scroll notch
+const FLY_SPEED_STEP: f32 = 1.25;
 
-    let cam_x = rel.dot(basis_x);
-    let cam_y = rel.dot(basis_y);
+/// Orthographic view distance factor per scroll notch (zooming in)
+const ORTHO_ZOOM_STEP: f32 = 0.85;
 
-    // Same projection as the rasterizer
-    const SCALE: f32 = 0.75;
-    let vs = (fb_width.min(fb_height) as f32 / 2.0) * SCALE;
-    let ud = 5.0;
-    let us = ud - 1.0;
+/// Longest frame time applied to camera movement (no jump after a hitch)
+const MAX_CAMERA_STEP: f32 = 0.1;
 
-    let denom = cam_z + ud;
-    let sx = (cam_x * us / denom) * vs + (fb_width as f32 / 2.0);
-    let sy = (cam_y * us / denom) * vs + (fb_height as f32 / 2.0);
+/// Camera pan (world units) per pixel of mouse travel, as a fraction of the fly speed
+const PAN_PER_PIXEL: f32 = 1.0 / 1500.0;
+
+const DIRECTIONS: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+/// A room's render data, cached until the room changes (see `RoomCache`)
+pub struct RoomMesh {
+    vertices: Vec<Vertex>,
+    faces: Vec<RasterFace>,
+    /// Faces using a MISSING placeholder
+    missing_faces: usize,
+}
 
-    Some((sx, sy))
+impl RoomMesh {
+    pub fn triangles(&self) -> usize {
+        self.faces.len()
+    }
+}
+
+/// Copy of `room` with non-walkable floors tinted red and non-solid walls blue, for
+/// the Paint Flags tool (None if every flag is at its default)
+fn flag_tinted(room: &Room) -> Option<Room> {
+    let flagged = room.iter_sectors().any(|(_, _, s)| {
+        s.floor.as_ref().is_some_and(|f| !f.walkable)
+            || DIRECTIONS.iter().any(|&d| s.walls(d).iter().any(|w| !w.solid))
+    });
+    if !flagged {
+        return None;
+    }
+    let mut tinted = room.clone();
+    for column in &mut tinted.sectors {
+        for sector in column.iter_mut().flatten() {
+            if let Some(floor) = sector.floor.as_mut().filter(|f| !f.walkable) {
+                floor.colors = [RasterColor::new(255, 70, 60); 4];
+            }
+            for dir in DIRECTIONS {
+                for wall in sector.walls_mut(dir).iter_mut().filter(|w| !w.solid) {
+                    wall.colors = [RasterColor::new(70, 120, 255); 4];
+                }
+            }
+        }
+    }
+    Some(tinted)
 }
 
+/// Copy of `room` holding only its invisible faces, made visible as translucent
+/// tinted ghosts so they can still be seen and edited (None if there are none)
+fn invisible_ghosts(room: &Room) -> Option<Room> {
+    let any = room.iter_sectors().any(|(_, _, s)| {
+        s.floor.iter().chain(&s.ceiling).any(|f| !f.visible)
+            || DIRECTIONS.iter().any(|&d| s.walls(d).iter().any(|w| !w.visible))
+    });
+    if !any {
+        return None;
+    }
+    let ghost = |visible: &mut bool, blend: &mut crate::rasterizer::BlendMode, colors: &mut [RasterColor; 4]| {
+        *visible = true;
+        *blend = crate::rasterizer::BlendMode::Average;
+        *colors = [RasterColor::new(120, 200, 255); 4];
+    };
+    let mut ghosts = room.clone();
+    for column in &mut ghosts.sectors {
+        for sector in column.iter_mut().flatten() {
+            for face in [&mut sector.floor, &mut sector.ceiling] {
+                match face.as_mut().filter(|f| !f.visible) {
+                    Some(f) => ghost(&mut f.visible, &mut f.blend_mode, &mut f.colors),
+                    None => *face = None,
+                }
+            }
+            for dir in DIRECTIONS {
+                let walls = sector.walls_mut(dir);
+                walls.retain(|w| !w.visible);
+                for wall in walls.iter_mut() {
+                    ghost(&mut wall.visible, &mut wall.blend_mode, &mut wall.colors);
+                }
+            }
+        }
+    }
+    Some(ghosts)
+}
 /// Calculate distance from point to line segment in 2D screen space
 fn point_to_segment_distance(
     px: f32, py: f32,      // Point
@@ -118,7 +181,8 @@ pub fn draw_viewport_3d(
     fb.resize(target_w, target_h);
 
     let mouse_pos = (ctx.mouse.x, ctx.mouse.y);
-    let inside_viewport = ctx.mouse.inside(&rect);
+    // The axis gizmo in the corner takes its own clicks
+    let inside_viewport = ctx.mouse.inside(&rect) && !ctx.mouse.inside(&gizmo_rect(&rect));
 
     // Pre-calculate viewport scaling (used multiple times)
     let fb_width = fb.width;
@@ -146,6 +210,19 @@ pub fn draw_viewport_3d(
         }
     };
 
+    // Room switch fly-over; any manual camera input takes over from it
+    let typing = ctx.text_input_focused();
+    if state.camera_transition.is_some() {
+        let move_keys = [KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D, KeyCode::Q, KeyCode::E];
+        let steering = (ctx.mouse.right_down && inside_viewport)
+            || (inside_viewport && !typing && move_keys.iter().any(|&k| is_key_down(k)));
+        if steering {
+            state.camera_transition = None;
+        } else {
+            state.step_camera_transition(get_frame_time());
+        }
+    }
+
     // Camera rotation with right mouse button (same as game mode)
     // Only rotate camera when not dragging a vertex
     if ctx.mouse.right_down && inside_viewport && state.dragging_sector_vertices.is_empty() {
@@ -153,45 +230,109 @@ pub fn draw_viewport_3d(
             // Inverted to match Y-down coordinate system
             let dx = (mouse_pos.1 - state.viewport_last_mouse.1) * 0.005;
             let dy = -(mouse_pos.0 - state.viewport_last_mouse.0) * 0.005;
-            state.camera_3d.rotate(dx, dy);
+            if dx != 0.0 || dy != 0.0 {
+                state.leave_ortho_view();
+                // Ctrl snaps to 45° steps; the unsnapped rotation keeps accumulating
+                // so releasing Ctrl picks up where the mouse really is
+                let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+                state.rotate_viewport_camera(dx, dy, ctrl);
+            }
+        } else {
+            state.viewport_free_rotation = (state.camera_3d.rotation_x, state.camera_3d.rotation_y);
         }
         state.viewport_mouse_captured = true;
     } else if !ctx.mouse.right_down {
         state.viewport_mouse_captured = false;
     }
 
-    // Keyboard camera movement (WASD + Q/E) - only when viewport focused and not dragging
-    let move_speed = 100.0; // Scaled for TRLE units (1024 per sector)
-    if (inside_viewport || state.viewport_mouse_captured) && state.dragging_sector_vertices.is_empty() {
-        if is_key_down(KeyCode::W) {
-            state.camera_3d.position = state.camera_3d.position + state.camera_3d.basis_z * move_speed;
+    // Pan with the middle mouse button or Space+drag (like the grid view), not
+    // while dragging vertices. A pan keeps going when the mouse leaves the view.
+    let pan_scale = state.camera_fly_speed * PAN_PER_PIXEL;
+    if (inside_viewport || state.viewport_panning) && pan_held(ctx) && state.dragging_sector_vertices.is_empty() {
+        if state.viewport_panning {
+            let dx = (mouse_pos.0 - state.viewport_last_mouse.0) * pan_scale;
+            let dy = (mouse_pos.1 - state.viewport_last_mouse.1) * pan_scale;
+            let camera = &mut state.camera_3d;
+            camera.position = camera.position - camera.basis_x * dx - camera.basis_y * dy;
         }
-        if is_key_down(KeyCode::S) {
-            state.camera_3d.position = state.camera_3d.position - state.camera_3d.basis_z * move_speed;
-        }
-        if is_key_down(KeyCode::A) {
-            state.camera_3d.position = state.camera_3d.position - state.camera_3d.basis_x * move_speed;
+        state.viewport_panning = true;
+        state.camera_transition = None;
+    } else {
+        state.viewport_panning = false;
+    }
+
+    // Edge scroll while dragging vertex heights (or measuring) near the view's edge.
+    // The drag height moves with the camera, so the vertices stay under the cursor.
+    let dragging = (ctx.mouse.left_down && !state.dragging_sector_vertices.is_empty())
+        || (state.measure.pressed() && state.measure.in_3d);
+    if dragging && inside_viewport {
+        let (ex, ey) = edge_scroll(&rect, mouse_pos.0, mouse_pos.1);
+        let step = edge_scroll_step() * pan_scale;
+        let camera = &mut state.camera_3d;
+        let shift = camera.basis_x * (ex * step) + camera.basis_y * (ey * step);
+        camera.position = camera.position + shift;
+        if !state.dragging_sector_vertices.is_empty() {
+            state.viewport_drag_plane_y += shift.y;
         }
-        if is_key_down(KeyCode::D) {
-            state.camera_3d.position = state.camera_3d.position + state.camera_3d.basis_x * move_speed;
+    }
+
+    // Scroll while looking around (right mouse held) changes the fly speed
+    if state.viewport_mouse_captured && ctx.mouse.scroll != 0.0 {
+        let speed = state.camera_fly_speed * FLY_SPEED_STEP.powf(ctx.mouse.scroll.signum());
+        state.set_camera_fly_speed(speed);
+    } else if inside_viewport && ctx.mouse.scroll != 0.0 {
+        // Otherwise scroll zooms an orthographic view (perspective has W/S for that)
+        if let Some(distance) = state.camera_3d.ortho_distance {
+            let zoomed = distance * ORTHO_ZOOM_STEP.powf(ctx.mouse.scroll.signum());
+            state.zoom_ortho_view(distance - zoomed);
         }
-        if is_key_down(KeyCode::Q) {
-            state.camera_3d.position = state.camera_3d.position - state.camera_3d.basis_y * move_speed;
+    }
+
+    // Canonical views: numpad 7 / 1 / 3 for orthographic Top / Front / Right, 5 back
+    // to perspective
+    if inside_viewport && !typing {
+        let views = [(KeyCode::Kp7, CanonicalView::Top), (KeyCode::Kp1, CanonicalView::Front), (KeyCode::Kp3, CanonicalView::Right)];
+        if let Some(&(_, view)) = views.iter().find(|(key, _)| is_key_pressed(*key)) {
+            state.set_canonical_view(view);
+        } else if is_key_pressed(KeyCode::Kp5) {
+            state.leave_ortho_view();
         }
-        if is_key_down(KeyCode::E) {
-            state.camera_3d.position = state.camera_3d.position + state.camera_3d.basis_y * move_speed;
+    }
+
+    // Keyboard camera movement (WASD + Q/E) - only when viewport focused and not dragging or typing.
+    // Units per second, so the speed is the same at any frame rate; Shift 4x, Ctrl 0.25x
+    if (inside_viewport || state.viewport_mouse_captured) && state.dragging_sector_vertices.is_empty() && !typing {
+        let axis = |pos: KeyCode, neg: KeyCode| (is_key_down(pos) as i32 - is_key_down(neg) as i32) as f32;
+        let boost = if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+            4.0
+        } else if is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl) {
+            0.25
+        } else {
+            1.0
+        };
+        let step = state.camera_fly_speed * boost * get_frame_time().min(MAX_CAMERA_STEP);
+        // Moving forward doesn't show in an orthographic view, so W/S zoom it instead
+        let mut forward = axis(KeyCode::W, KeyCode::S);
+        if state.camera_3d.ortho_distance.is_some() && forward != 0.0 {
+            state.zoom_ortho_view(forward * step);
+            forward = 0.0;
         }
+        let camera = &mut state.camera_3d;
+        let direction = camera.basis_z * forward
+            + camera.basis_x * axis(KeyCode::D, KeyCode::A)
+            + camera.basis_y * axis(KeyCode::E, KeyCode::Q);
+        camera.position = camera.position + direction * step;
     }
 
     // Toggle link coincident vertices mode with L key
-    if inside_viewport && is_key_pressed(KeyCode::L) {
+    if inside_viewport && !typing && is_key_pressed(KeyCode::L) {
         state.link_coincident_vertices = !state.link_coincident_vertices;
         let mode = if state.link_coincident_vertices { "Linked" } else { "Independent" };
         state.set_status(&format!("Vertex mode: {}", mode), 2.0);
     }
 
     // Delete selected face with Delete or Backspace key
-    if inside_viewport && (is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace)) {
+    if inside_viewport && !typing && (is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace)) {
         if let Selection::SectorFace { room, x, z, face } = &state.selection {
             let (room_idx, gx, gz, face) = (*room, *x, *z, *face);
 
@@ -205,26 +346,21 @@ pub fn draw_viewport_3d(
                     .and_then(|r| r.get_sector(gx, gz))
                     .map(|s| s.ceiling.is_some())
                     .unwrap_or(false),
-                SectorFace::WallNorth(i) => state.level.rooms.get(room_idx)
+                wall => state.level.rooms.get(room_idx)
                     .and_then(|r| r.get_sector(gx, gz))
-                    .map(|s| s.walls_north.get(i).is_some())
-                    .unwrap_or(false),
-                SectorFace::WallEast(i) => state.level.rooms.get(room_idx)
-                    .and_then(|r| r.get_sector(gx, gz))
-                    .map(|s| s.walls_east.get(i).is_some())
-                    .unwrap_or(false),
-                SectorFace::WallSouth(i) => state.level.rooms.get(room_idx)
-                    .and_then(|r| r.get_sector(gx, gz))
-                    .map(|s| s.walls_south.get(i).is_some())
-                    .unwrap_or(false),
-                SectorFace::WallWest(i) => state.level.rooms.get(room_idx)
-                    .and_then(|r| r.get_sector(gx, gz))
-                    .map(|s| s.walls_west.get(i).is_some())
-                    .unwrap_or(false),
+                    .is_some_and(|s| wall.wall_in(s).is_some()),
             };
 
             if has_face {
-                state.save_undo();
+                let face_name = match face {
+                    SectorFace::Floor => "floor",
+                    SectorFace::Ceiling => "ceiling",
+                    SectorFace::WallNorth(_) => "north wall",
+                    SectorFace::WallEast(_) => "east wall",
+                    SectorFace::WallSouth(_) => "south wall",
+                    SectorFace::WallWest(_) => "west wall",
+                };
+                state.save_undo(&format!("Delete {} ({}, {})", face_name, gx, gz));
 
                 let deleted = match face {
                     SectorFace::Floor => {
@@ -245,53 +381,23 @@ pub fn draw_viewport_3d(
                             Some("ceiling")
                         } else { None }
                     }
-                    SectorFace::WallNorth(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
-                            if let Some(sector) = room.get_sector_mut(gx, gz) {
-                                if i < sector.walls_north.len() {
-                                    sector.walls_north.remove(i);
-                                }
-                            }
-                            room.recalculate_bounds();
-                            Some("north wall")
-                        } else { None }
-                    }
-                    SectorFace::WallEast(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
-                            if let Some(sector) = room.get_sector_mut(gx, gz) {
-                                if i < sector.walls_east.len() {
-                                    sector.walls_east.remove(i);
-                                }
-                            }
-                            room.recalculate_bounds();
-                            Some("east wall")
-                        } else { None }
-                    }
-                    SectorFace::WallSouth(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
-                            if let Some(sector) = room.get_sector_mut(gx, gz) {
-                                if i < sector.walls_south.len() {
-                                    sector.walls_south.remove(i);
-                                }
+                    wall => {
+                        let slot = state.level.rooms.get(room_idx)
+                            .and_then(|r| r.get_sector(gx, gz))
+                            .and_then(|s| wall.wall_index_in(s));
+                        match (state.level.rooms.get_mut(room_idx), slot) {
+                            (Some(room), Some((dir, i))) => {
+                                room.remove_wall(gx, gz, dir, i);
+                                room.recalculate_bounds();
+                                Some(face_name)
                             }
-                            room.recalculate_bounds();
-                            Some("south wall")
-                        } else { None }
-                    }
-                    SectorFace::WallWest(i) => {
-                        if let Some(room) = state.level.rooms.get_mut(room_idx) {
-                            if let Some(sector) = room.get_sector_mut(gx, gz) {
-                                if i < sector.walls_west.len() {
-                                    sector.walls_west.remove(i);
-                                }
-                            }
-                            room.recalculate_bounds();
-                            Some("west wall")
-                        } else { None }
+                            _ => None,
+                        }
                     }
                 };
 
                 if let Some(type_name) = deleted {
+                    state.mark_room_dirty(room_idx);
                     state.selection = Selection::None;
                     state.set_status(&format!("Deleted {}", type_name), 2.0);
                 }
@@ -309,7 +415,7 @@ pub fn draw_viewport_3d(
 
     // Collect all vertex positions for the current room (for drawing and selection)
     // Each vertex is (world_pos, room_idx, gx, gz, corner_idx, face_type)
-    // corner_idx: 0=NW, 1=NE, 2=SE, 3=SW for horizontal faces
+    // corner_idx: 0=NW, 1=NE, 2=SE, 3=SW for horizontal faces, 4+ for subdivision points
     let mut all_vertices: Vec<(Vec3, usize, usize, usize, usize, SectorFace)> = Vec::new();
 
     if let Some(room) = state.level.rooms.get(state.current_room) {
@@ -323,6 +429,10 @@ pub fn draw_viewport_3d(
                 all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, floor.heights[1], base_z), state.current_room, gx, gz, 1, SectorFace::Floor));
                 all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, floor.heights[2], base_z + SECTOR_SIZE), state.current_room, gx, gz, 2, SectorFace::Floor));
                 all_vertices.push((Vec3::new(base_x, floor.heights[3], base_z + SECTOR_SIZE), state.current_room, gx, gz, 3, SectorFace::Floor));
+                for (vi, u, v) in floor.detail_vertices() {
+                    let h = floor.vertex_height(vi).unwrap_or(0.0);
+                    all_vertices.push((Vec3::new(base_x + u * SECTOR_SIZE, h, base_z + v * SECTOR_SIZE), state.current_room, gx, gz, vi, SectorFace::Floor));
+                }
             }
 
             // Ceiling vertices
@@ -331,46 +441,33 @@ pub fn draw_viewport_3d(
                 all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[1], base_z), state.current_room, gx, gz, 1, SectorFace::Ceiling));
                 all_vertices.push((Vec3::new(base_x + SECTOR_SIZE, ceiling.heights[2], base_z + SECTOR_SIZE), state.current_room, gx, gz, 2, SectorFace::Ceiling));
                 all_vertices.push((Vec3::new(base_x, ceiling.heights[3], base_z + SECTOR_SIZE), state.current_room, gx, gz, 3, SectorFace::Ceiling));
+                for (vi, u, v) in ceiling.detail_vertices() {
+                    let h = ceiling.vertex_height(vi).unwrap_or(0.0);
+                    all_vertices.push((Vec3::new(base_x + u * SECTOR_SIZE, h, base_z + v * SECTOR_SIZE), state.current_room, gx, gz, vi, SectorFace::Ceiling));
+                }
             }
 
             // Wall vertices
-            let wall_configs: [(&Vec<crate::world::VerticalFace>, f32, f32, f32, f32, fn(usize) -> SectorFace); 4] = [
-                (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z, |i| SectorFace::WallNorth(i)),
-                (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, |i| SectorFace::WallEast(i)),
-                (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE, |i| SectorFace::WallSouth(i)),
-                (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z, |i| SectorFace::WallWest(i)),
-            ];
-
-            for (walls, x0, z0, x1, z1, make_face) in wall_configs {
-                for (i, wall) in walls.iter().enumerate() {
+            for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                for wall in sector.walls(dir) {
                     // 4 corners of wall: bottom-left, bottom-right, top-right, top-left
-                    // wall.heights = [bottom-left, bottom-right, top-right, top-left]
-                    all_vertices.push((Vec3::new(x0, wall.heights[0], z0), state.current_room, gx, gz, 0, make_face(i)));
-                    all_vertices.push((Vec3::new(x1, wall.heights[1], z1), state.current_room, gx, gz, 1, make_face(i)));
-                    all_vertices.push((Vec3::new(x1, wall.heights[2], z1), state.current_room, gx, gz, 2, make_face(i)));
-                    all_vertices.push((Vec3::new(x0, wall.heights[3], z0), state.current_room, gx, gz, 3, make_face(i)));
+                    for (corner, pos) in wall.corners(base_x, base_z, dir).into_iter().enumerate() {
+                        all_vertices.push((pos, state.current_room, gx, gz, corner, SectorFace::for_wall(dir, wall.id)));
+                    }
                 }
             }
         }
     }
 
     // In Select mode, find hovered vertex/edge/face using 2D screen projection
-    if inside_viewport && !ctx.mouse.right_down && state.tool == EditorTool::Select {
+    if inside_viewport && !ctx.mouse.right_down && !state.viewport_panning && state.tool == EditorTool::Select {
         if let Some((mouse_fb_x, mouse_fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
             const VERTEX_THRESHOLD: f32 = 10.0;
             const EDGE_THRESHOLD: f32 = 8.0;
 
             // Check vertices first (highest priority)
             for (world_pos, room_idx, gx, gz, corner_idx, face) in &all_vertices {
-                if let Some((sx, sy)) = world_to_screen(
-                    *world_pos,
-                    state.camera_3d.position,
-                    state.camera_3d.basis_x,
-                    state.camera_3d.basis_y,
-                    state.camera_3d.basis_z,
-                    fb.width,
-                    fb.height,
-                ) {
+                if let Some((sx, sy)) = state.camera_3d.world_to_screen(*world_pos, fb.width, fb.height) {
                     let dist = ((mouse_fb_x - sx).powi(2) + (mouse_fb_y - sy).powi(2)).sqrt();
                     if dist < VERTEX_THRESHOLD {
                         if hovered_vertex.map_or(true, |(_, _, _, _, _, best_dist)| dist < best_dist) {
@@ -399,10 +496,8 @@ pub fn draw_viewport_3d(
                                 let v0 = corners[edge_idx];
                                 let v1 = corners[(edge_idx + 1) % 4];
                                 if let (Some((sx0, sy0)), Some((sx1, sy1))) = (
-                                    world_to_screen(v0, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(v1, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height)
+                                    state.camera_3d.world_to_screen(v0, fb.width, fb.height),
+                                    state.camera_3d.world_to_screen(v1, fb.width, fb.height)
                                 ) {
                                     let dist = point_to_segment_distance(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1);
                                     if dist < EDGE_THRESHOLD {
@@ -426,10 +521,8 @@ pub fn draw_viewport_3d(
                                 let v0 = corners[edge_idx];
                                 let v1 = corners[(edge_idx + 1) % 4];
                                 if let (Some((sx0, sy0)), Some((sx1, sy1))) = (
-                                    world_to_screen(v0, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(v1, state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height)
+                                    state.camera_3d.world_to_screen(v0, fb.width, fb.height),
+                                    state.camera_3d.world_to_screen(v1, fb.width, fb.height)
                                 ) {
                                     let dist = point_to_segment_distance(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1);
                                     if dist < EDGE_THRESHOLD {
@@ -442,34 +535,20 @@ pub fn draw_viewport_3d(
                         }
 
                         // Check wall edges
-                        let wall_configs: [(&Vec<crate::world::VerticalFace>, f32, f32, f32, f32, fn(usize) -> SectorFace); 4] = [
-                            (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z, |i| SectorFace::WallNorth(i)),
-                            (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, |i| SectorFace::WallEast(i)),
-                            (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE, |i| SectorFace::WallSouth(i)),
-                            (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z, |i| SectorFace::WallWest(i)),
-                        ];
-
-                        for (walls, x0, z0, x1, z1, make_face) in wall_configs {
-                            for (i, wall) in walls.iter().enumerate() {
-                                let wall_corners = [
-                                    Vec3::new(x0, wall.heights[0], z0),
-                                    Vec3::new(x1, wall.heights[1], z1),
-                                    Vec3::new(x1, wall.heights[2], z1),
-                                    Vec3::new(x0, wall.heights[3], z0),
-                                ];
+                        for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                            for wall in sector.walls(dir) {
+                                let wall_corners = wall.corners(base_x, base_z, dir);
                                 for edge_idx in 0..4 {
                                     let v0 = wall_corners[edge_idx];
                                     let v1 = wall_corners[(edge_idx + 1) % 4];
                                     if let (Some((sx0, sy0)), Some((sx1, sy1))) = (
-                                        world_to_screen(v0, state.camera_3d.position, state.camera_3d.basis_x,
-                                            state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                        world_to_screen(v1, state.camera_3d.position, state.camera_3d.basis_x,
-                                            state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height)
+                                        state.camera_3d.world_to_screen(v0, fb.width, fb.height),
+                                        state.camera_3d.world_to_screen(v1, fb.width, fb.height)
                                     ) {
                                         let dist = point_to_segment_distance(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1);
                                         if dist < EDGE_THRESHOLD {
                                             if hovered_edge.map_or(true, |(_, _, _, _, _, _, best_dist)| dist < best_dist) {
-                                                let wall_face = make_face(i);
+                                                let wall_face = SectorFace::for_wall(dir, wall.id);
                                                 hovered_edge = Some((state.current_room, gx, gz, 2, edge_idx, Some(wall_face), dist)); // face_idx=2 for walls
                                             }
                                         }
@@ -498,14 +577,10 @@ pub fn draw_viewport_3d(
                             ];
 
                             if let (Some((sx0, sy0)), Some((sx1, sy1)), Some((sx2, sy2)), Some((sx3, sy3))) = (
-                                world_to_screen(corners[0], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[1], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[2], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[3], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[0], fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[1], fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[2], fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[3], fb.width, fb.height),
                             ) {
                                 // Test both triangles that make up the quad (0-1-2 and 0-2-3)
                                 if point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1, sx2, sy2) ||
@@ -526,14 +601,10 @@ pub fn draw_viewport_3d(
                             ];
 
                             if let (Some((sx0, sy0)), Some((sx1, sy1)), Some((sx2, sy2)), Some((sx3, sy3))) = (
-                                world_to_screen(corners[0], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[1], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[2], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                world_to_screen(corners[3], state.camera_3d.position, state.camera_3d.basis_x,
-                                    state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[0], fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[1], fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[2], fb.width, fb.height),
+                                state.camera_3d.world_to_screen(corners[3], fb.width, fb.height),
                             ) {
                                 if point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1, sx2, sy2) ||
                                    point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx2, sy2, sx3, sy3) {
@@ -544,35 +615,19 @@ pub fn draw_viewport_3d(
                         }
 
                         // Check walls
-                        let wall_configs: [(&Vec<crate::world::VerticalFace>, f32, f32, f32, f32, fn(usize) -> SectorFace); 4] = [
-                            (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z, |i| SectorFace::WallNorth(i)),
-                            (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, |i| SectorFace::WallEast(i)),
-                            (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE, |i| SectorFace::WallSouth(i)),
-                            (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z, |i| SectorFace::WallWest(i)),
-                        ];
-
-                        for (walls, x0, z0, x1, z1, make_face) in wall_configs {
-                            for (i, wall) in walls.iter().enumerate() {
-                                let wall_corners = [
-                                    Vec3::new(x0, wall.heights[0], z0),
-                                    Vec3::new(x1, wall.heights[1], z1),
-                                    Vec3::new(x1, wall.heights[2], z1),
-                                    Vec3::new(x0, wall.heights[3], z0),
-                                ];
+                        for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                            for wall in sector.walls(dir) {
+                                let wall_corners = wall.corners(base_x, base_z, dir);
 
                                 if let (Some((sx0, sy0)), Some((sx1, sy1)), Some((sx2, sy2)), Some((sx3, sy3))) = (
-                                    world_to_screen(wall_corners[0], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(wall_corners[1], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(wall_corners[2], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                                    world_to_screen(wall_corners[3], state.camera_3d.position, state.camera_3d.basis_x,
-                                        state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
+                                    state.camera_3d.world_to_screen(wall_corners[0], fb.width, fb.height),
+                                    state.camera_3d.world_to_screen(wall_corners[1], fb.width, fb.height),
+                                    state.camera_3d.world_to_screen(wall_corners[2], fb.width, fb.height),
+                                    state.camera_3d.world_to_screen(wall_corners[3], fb.width, fb.height),
                                 ) {
                                     if point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx1, sy1, sx2, sy2) ||
                                        point_in_triangle_2d(mouse_fb_x, mouse_fb_y, sx0, sy0, sx2, sy2, sx3, sy3) {
-                                        hovered_face = Some((state.current_room, gx, gz, make_face(i)));
+                                        hovered_face = Some((state.current_room, gx, gz, SectorFace::for_wall(dir, wall.id)));
                                         break 'face_loop;
                                     }
                                 }
@@ -584,6 +639,10 @@ pub fn draw_viewport_3d(
         }
     }
 
+    if let Some((room_idx, gx, gz, SectorFace::Floor)) = hovered_face {
+        state.sector_hover = Some((room_idx, gx, gz));
+    }
+
     // In drawing modes, find preview sector position
     if inside_viewport && (state.tool == EditorTool::DrawFloor || state.tool == EditorTool::DrawCeiling) {
         if let Some((mouse_fb_x, mouse_fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
@@ -618,9 +677,7 @@ pub fn draw_viewport_3d(
                         let grid_z = start_z + (iz as f32 * SECTOR_SIZE);
                         let test_pos = Vec3::new(grid_x + SECTOR_SIZE / 2.0, target_y, grid_z + SECTOR_SIZE / 2.0);
 
-                        if let Some((sx, sy)) = world_to_screen(test_pos, state.camera_3d.position,
-                            state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z,
-                            fb.width, fb.height)
+                        if let Some((sx, sy)) = state.camera_3d.world_to_screen(test_pos, fb.width, fb.height)
                         {
                             let dist = ((mouse_fb_x - sx).powi(2) + (mouse_fb_y - sy).powi(2)).sqrt();
                             if closest.map_or(true, |(_, _, best_dist)| dist < best_dist) {
@@ -741,9 +798,7 @@ pub fn draw_viewport_3d(
                     ];
 
                     for (edge_dir, center) in edges {
-                        if let Some((sx, sy)) = world_to_screen(center, state.camera_3d.position,
-                            state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z,
-                            fb.width, fb.height)
+                        if let Some((sx, sy)) = state.camera_3d.world_to_screen(center, fb.width, fb.height)
                         {
                             let dist = ((mouse_fb_x - sx).powi(2) + (mouse_fb_y - sy).powi(2)).sqrt();
                             if closest_edge.map_or(true, |(_, _, _, best_dist)| dist < best_dist) {
@@ -827,8 +882,35 @@ pub fn draw_viewport_3d(
         }
     }
 
+    // Measure tool: point on a face under the mouse, snapped to a vertex corner when close
+    let mut measure_hover: Option<Vec3> = None;
+    if inside_viewport && state.tool == EditorTool::Measure {
+        if let Some((mouse_fb_x, mouse_fb_y)) = screen_to_fb(mouse_pos.0, mouse_pos.1) {
+            measure_hover = all_vertices.iter()
+                .filter_map(|(world_pos, ..)| {
+                    let (sx, sy) = state.camera_3d.world_to_screen(*world_pos, fb.width, fb.height)?;
+                    let dist = (mouse_fb_x - sx).hypot(mouse_fb_y - sy);
+                    (dist <= MEASURE_SNAP_PX).then_some((*world_pos, dist))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(world_pos, _)| world_pos)
+                .or_else(|| {
+                    let (origin, dir) = state.camera_3d.screen_to_ray(mouse_fb_x, mouse_fb_y, fb.width, fb.height);
+                    state.level.pick(origin, origin + dir * MEASURE_PICK_DISTANCE).map(|hit| hit.point)
+                });
+        }
+        if ctx.mouse.left_pressed && !ctx.mouse.right_down && !state.viewport_panning {
+            if let Some(point) = measure_hover {
+                state.measure.press(point, true, mouse_pos);
+            }
+        }
+    }
+    if state.measure.pressed() && state.measure.in_3d && !ctx.mouse.left_down {
+        state.measure.release(measure_hover, mouse_pos);
+    }
+
     // Handle clicks and dragging in 3D viewport
-    if inside_viewport && !ctx.mouse.right_down {
+    if inside_viewport && !ctx.mouse.right_down && !state.viewport_panning {
         // Detect Shift key for multi-select
         let shift_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
 
@@ -840,7 +922,7 @@ pub fn draw_viewport_3d(
                     // Start dragging vertex
                     state.dragging_sector_vertices.clear();
                     state.drag_initial_heights.clear();
-                    state.viewport_drag_started = false;
+                    state.begin_transaction("Move vertex heights");
 
                     // Store the vertex to drag
                     state.dragging_sector_vertices.push((room_idx, gx, gz, face, corner_idx));
@@ -849,12 +931,9 @@ pub fn draw_viewport_3d(
                     if let Some(room) = state.level.rooms.get(room_idx) {
                         if let Some(sector) = room.get_sector(gx, gz) {
                             let height = match face {
-                                SectorFace::Floor => sector.floor.as_ref().map(|f| f.heights[corner_idx]),
-                                SectorFace::Ceiling => sector.ceiling.as_ref().map(|c| c.heights[corner_idx]),
-                                SectorFace::WallNorth(i) => sector.walls_north.get(i).map(|w| w.heights[corner_idx]),
-                                SectorFace::WallEast(i) => sector.walls_east.get(i).map(|w| w.heights[corner_idx]),
-                                SectorFace::WallSouth(i) => sector.walls_south.get(i).map(|w| w.heights[corner_idx]),
-                                SectorFace::WallWest(i) => sector.walls_west.get(i).map(|w| w.heights[corner_idx]),
+                                SectorFace::Floor => sector.floor.as_ref().and_then(|f| f.vertex_height(corner_idx)),
+                                SectorFace::Ceiling => sector.ceiling.as_ref().and_then(|c| c.vertex_height(corner_idx)),
+                                wall => wall.wall_in(sector).map(|(_, w)| w.heights[corner_idx]),
                             };
                             if let Some(h) = height {
                                 state.drag_initial_heights.push(h);
@@ -888,7 +967,7 @@ pub fn draw_viewport_3d(
                     // Start dragging edge (both vertices)
                     state.dragging_sector_vertices.clear();
                     state.drag_initial_heights.clear();
-                    state.viewport_drag_started = false;
+                    state.begin_transaction("Move vertex heights");
 
                     // Handle selection first (Shift = toggle multi-select)
                     let new_selection = Selection::Edge {
@@ -1015,13 +1094,7 @@ pub fn draw_viewport_3d(
                                         let corner1 = (*edge_idx + 1) % 4;
 
                                         // Get wall heights based on wall direction
-                                        let wall_heights = match wall_face {
-                                            SectorFace::WallNorth(i) => sector.walls_north.get(*i).map(|w| w.heights),
-                                            SectorFace::WallEast(i) => sector.walls_east.get(*i).map(|w| w.heights),
-                                            SectorFace::WallSouth(i) => sector.walls_south.get(*i).map(|w| w.heights),
-                                            SectorFace::WallWest(i) => sector.walls_west.get(*i).map(|w| w.heights),
-                                            _ => None,
-                                        };
+                                        let wall_heights = wall_face.wall_in(sector).map(|(_, w)| w.heights);
 
                                         if let Some(h) = wall_heights {
                                             // Add both edge vertices
@@ -1054,7 +1127,7 @@ pub fn draw_viewport_3d(
                     // Start dragging face (all 4 vertices)
                     state.dragging_sector_vertices.clear();
                     state.drag_initial_heights.clear();
-                    state.viewport_drag_started = false;
+                    state.begin_transaction("Move vertex heights");
 
                     // Handle selection first (Shift = toggle multi-select)
                     let new_selection = Selection::SectorFace { room: room_idx, x: gx, z: gz, face };
@@ -1143,29 +1216,16 @@ pub fn draw_viewport_3d(
                                 }
 
                                 // Handle wall dragging
-                                match face {
-                                    SectorFace::WallNorth(i) | SectorFace::WallEast(i) |
-                                    SectorFace::WallSouth(i) | SectorFace::WallWest(i) => {
-                                        let walls = match face {
-                                            SectorFace::WallNorth(_) => &sector.walls_north,
-                                            SectorFace::WallEast(_) => &sector.walls_east,
-                                            SectorFace::WallSouth(_) => &sector.walls_south,
-                                            SectorFace::WallWest(_) => &sector.walls_west,
-                                            _ => unreachable!(),
-                                        };
-                                        if let Some(wall) = walls.get(*i) {
-                                            for corner in 0..4 {
-                                                let key = (*r_idx, *gx, *gz, *face, corner);
-                                                if !state.dragging_sector_vertices.contains(&key) {
-                                                    state.dragging_sector_vertices.push(key);
-                                                    state.drag_initial_heights.push(wall.heights[corner]);
-                                                    avg_height += wall.heights[corner];
-                                                    height_count += 1;
-                                                }
-                                            }
+                                if let Some((_, wall)) = face.wall_in(sector) {
+                                    for corner in 0..4 {
+                                        let key = (*r_idx, *gx, *gz, *face, corner);
+                                        if !state.dragging_sector_vertices.contains(&key) {
+                                            state.dragging_sector_vertices.push(key);
+                                            state.drag_initial_heights.push(wall.heights[corner]);
+                                            avg_height += wall.heights[corner];
+                                            height_count += 1;
                                         }
                                     }
-                                    _ => {}
                                 }
                             }
                         }
@@ -1192,7 +1252,8 @@ pub fn draw_viewport_3d(
                         let type_name = if is_floor { "floor" } else { "ceiling" };
                         state.set_status(&format!("Sector already has a {}", type_name), 2.0);
                     } else {
-                        state.save_undo();
+                        let type_name = if is_floor { "floor" } else { "ceiling" };
+                        state.save_undo(&format!("Create {}", type_name));
 
                         // Get texture and room position before borrowing mutably
                         let texture = state.selected_texture.clone();
@@ -1254,6 +1315,7 @@ pub fn draw_viewport_3d(
                             }
                             room.recalculate_bounds();
                         }
+                        state.mark_room_dirty(state.current_room);
 
                         let status = if is_floor { "Created floor sector" } else { "Created ceiling sector" };
                         state.set_status(status, 2.0);
@@ -1263,12 +1325,12 @@ pub fn draw_viewport_3d(
             // DrawWall mode - place wall on sector edge
             else if state.tool == EditorTool::DrawWall {
                 if let Some((grid_x, grid_z, dir, y_bottom, y_top, occupied)) = preview_wall {
-                    use crate::world::{Direction, VerticalFace};
+                    use crate::world::Direction;
 
                     if occupied {
                         state.set_status("Edge already has a wall", 2.0);
                     } else {
-                        state.save_undo();
+                        state.save_undo(&format!("Create {:?} wall", dir));
 
                         let texture = state.selected_texture.clone();
                         let room_pos = state.level.rooms.get(state.current_room)
@@ -1316,19 +1378,10 @@ pub fn draw_viewport_3d(
                                 }
                             }
 
-                            // Create the wall
-                            let wall = VerticalFace::new(y_bottom, y_top, texture);
-
-                            // Ensure sector exists and add wall
-                            let sector = room.ensure_sector(gx, gz);
-                            match dir {
-                                Direction::North => sector.walls_north.push(wall),
-                                Direction::East => sector.walls_east.push(wall),
-                                Direction::South => sector.walls_south.push(wall),
-                                Direction::West => sector.walls_west.push(wall),
-                            }
+                            room.add_wall(gx, gz, dir, y_bottom, y_top, texture);
                             room.recalculate_bounds();
                         }
+                        state.mark_room_dirty(state.current_room);
 
                         let dir_name = match dir {
                             Direction::North => "north",
@@ -1344,12 +1397,12 @@ pub fn draw_viewport_3d(
 
         // Continue dragging (Y-axis only - TRLE constraint)
         if ctx.mouse.left_down && !state.dragging_sector_vertices.is_empty() {
-            use super::CLICK_HEIGHT;
+            // Ctrl drags freely regardless of the snap setting
+            let ctrl_down = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+            let snap = if ctrl_down { HeightSnap::Free } else { state.height_snap };
+            state.set_status(&format!("Height snap: {}", snap.label()), 0.5);
 
-            if !state.viewport_drag_started {
-                state.save_undo();
-                state.viewport_drag_started = true;
-            }
+            state.save_undo("Move vertex heights");
 
             // Calculate Y delta from mouse movement (inverted: mouse up = positive Y)
             let mouse_delta_y = state.viewport_last_mouse.1 - mouse_pos.1;
@@ -1368,31 +1421,24 @@ pub fn draw_viewport_3d(
             for (i, &(room_idx, gx, gz, face, corner_idx)) in state.dragging_sector_vertices.clone().iter().enumerate() {
                 if let Some(initial_h) = state.drag_initial_heights.get(i) {
                     let new_h = initial_h + delta_from_initial;
-                    let snapped_h = (new_h / CLICK_HEIGHT).round() * CLICK_HEIGHT;
+                    let snapped_h = snap.apply(new_h);
 
+                    state.mark_room_dirty(room_idx);
                     if let Some(room) = state.level.rooms.get_mut(room_idx) {
                         if let Some(sector) = room.get_sector_mut(gx, gz) {
                             match face {
                                 SectorFace::Floor => {
                                     if let Some(floor) = &mut sector.floor {
-                                        floor.heights[corner_idx] = snapped_h;
+                                        floor.set_vertex_height(corner_idx, snapped_h);
                                     }
                                 }
                                 SectorFace::Ceiling => {
                                     if let Some(ceiling) = &mut sector.ceiling {
-                                        ceiling.heights[corner_idx] = snapped_h;
+                                        ceiling.set_vertex_height(corner_idx, snapped_h);
                                     }
                                 }
-                                SectorFace::WallNorth(wi) | SectorFace::WallEast(wi) |
-                                SectorFace::WallSouth(wi) | SectorFace::WallWest(wi) => {
-                                    let walls = match face {
-                                        SectorFace::WallNorth(_) => &mut sector.walls_north,
-                                        SectorFace::WallEast(_) => &mut sector.walls_east,
-                                        SectorFace::WallSouth(_) => &mut sector.walls_south,
-                                        SectorFace::WallWest(_) => &mut sector.walls_west,
-                                        _ => unreachable!(),
-                                    };
-                                    if let Some(wall) = walls.get_mut(wi) {
+                                wall => {
+                                    if let Some((_, wall)) = wall.wall_in_mut(sector) {
                                         // Update individual corner height
                                         wall.heights[corner_idx] = snapped_h;
                                     }
@@ -1408,26 +1454,44 @@ pub fn draw_viewport_3d(
                 room.recalculate_bounds();
             }
         }
+    }
 
-        // End dragging on release
-        if ctx.mouse.left_released {
-            // If we actually dragged geometry, recalculate room bounds
-            if state.viewport_drag_started {
-                if let Some(room) = state.level.rooms.get_mut(state.current_room) {
-                    room.recalculate_bounds();
-                }
+    // End dragging on release (even outside the viewport): label and close the undo step
+    if !ctx.mouse.left_down && !state.dragging_sector_vertices.is_empty() {
+        if state.in_transaction() {
+            if let Some(room) = state.level.rooms.get_mut(state.current_room) {
+                room.recalculate_bounds();
+            }
+            let initial_avg: f32 = state.drag_initial_heights.iter().sum::<f32>()
+                / state.drag_initial_heights.len().max(1) as f32;
+            let delta = state.height_snap.apply(state.viewport_drag_plane_y - initial_avg);
+            if let Some(&(_, gx, gz, face, _)) = state.dragging_sector_vertices.first() {
+                let face_name = match face {
+                    SectorFace::Floor => "floor",
+                    SectorFace::Ceiling => "ceiling",
+                    _ => "wall",
+                };
+                let verb = if delta >= 0.0 { "Raise" } else { "Lower" };
+                let clicks = delta.abs() / super::CLICK_HEIGHT;
+                let amount = if clicks.fract() == 0.0 {
+                    format!("{} click(s)", clicks)
+                } else {
+                    format!("{} units", delta.abs())
+                };
+                let label = format!("{} {} ({}, {}) by {}", verb, face_name, gx, gz, amount);
+                state.set_undo_label(&label);
             }
-            state.dragging_sector_vertices.clear();
-            state.drag_initial_heights.clear();
-            state.viewport_drag_started = false;
         }
+        state.end_transaction();
+        state.dragging_sector_vertices.clear();
+        state.drag_initial_heights.clear();
     }
 
     // Update mouse position for next frame
     state.viewport_last_mouse = mouse_pos;
 
-    // Clear framebuffer
-    fb.clear(RasterColor::new(30, 30, 40));
+    // Clear framebuffer (only the area drawn last frame, when the size hasn't changed)
+    fb.clear_dirty(RasterColor::new(30, 30, 40));
 
     // Draw main floor grid (large, fixed extent)
     if state.show_grid {
@@ -1631,33 +1695,133 @@ pub fn draw_viewport_3d(
         }
     }
 
-    // Build texture map from texture packs
-    let mut texture_map: std::collections::HashMap<(String, String), usize> = std::collections::HashMap::new();
-    let mut texture_idx = 0;
+    // Too many lights to relight every frame: back to lighting into the meshes
+    state.limit_dynamic_lights();
+
+    // Build texture map from texture packs, hashing the id assignment: cached room
+    // meshes hold texture ids, so they're rebuilt when it changes (as is the lighting
+    // mode: dynamic lights leave the point lights out of them)
+    let mut index = TextureIndex::new();
+    let aliases = &state.level.pack_aliases;
+    let mut mesh_key = DefaultHasher::new();
+    let mesh_settings = &state.raster_settings;
+    (mesh_settings.merge_coplanar, mesh_settings.smooth_normals, mesh_settings.dynamic_lights, textures.len(), aliases).hash(&mut mesh_key);
     for pack in &state.texture_packs {
         for tex in &pack.textures {
-            texture_map.insert((pack.name.clone(), tex.name.clone()), texture_idx);
-            texture_idx += 1;
+            index.push(&pack.name, &tex.name);
+            (&pack.name, &tex.name).hash(&mut mesh_key);
         }
     }
 
+    // References no pack has get "MISSING" placeholders after the loaded textures
+    // (pulsing when highlighted, so they stand out in screenshots too)
+    let placeholders = state.missing_textures.placeholders(&state.level, |t| index.get(t, aliases).is_some());
+    let first_placeholder = textures.len();
+    let with_placeholders: Vec<RasterTexture>;
+    let textures = if placeholders.is_empty() {
+        textures
+    } else {
+        let pulse = if state.raster_settings.highlight_missing_textures {
+            0.65 + 0.35 * (get_time() * 6.0).sin() as f32
+        } else {
+            1.0
+        };
+        let mut all = textures.to_vec();
+        for (i, (key, placeholder)) in placeholders.into_iter().enumerate() {
+            index.insert(&key.0, &key.1, textures.len() + i);
+            key.hash(&mut mesh_key);
+            let mut placeholder = placeholder.clone();
+            if pulse != 1.0 {
+                placeholder.pixels.iter_mut().for_each(|p| *p = p.shade(pulse));
+            }
+            all.push(placeholder);
+        }
+        with_placeholders = all;
+        &with_placeholders
+    };
+
     // Texture resolver closure
+    let missing_faces = std::cell::Cell::new(0);
     let resolve_texture = |tex_ref: &crate::world::TextureRef| -> Option<usize> {
-        if !tex_ref.is_valid() {
-            return Some(0); // Fallback to first texture
+        let id = index.resolve(tex_ref, aliases);
+        if id.is_some_and(|id| id >= first_placeholder) {
+            missing_faces.set(missing_faces.get() + 1);
         }
-        texture_map.get(&(tex_ref.pack.clone(), tex_ref.name.clone())).copied()
+        id
     };
 
-    // Render all rooms
+    // Render all rooms, rebuilding render data only for rooms that changed
+    let layers = state.visible_layers();
+    let mut room_settings = state.raster_settings.clone();
     let settings = &state.raster_settings;
-    for room in &state.level.rooms {
-        let (vertices, faces) = room.to_render_data_with_textures(&resolve_texture);
-        render_mesh(fb, &vertices, &faces, textures, &state.camera_3d, settings);
+    let painting_flags = state.tool == EditorTool::PaintFlags;
+    state.room_meshes.begin_frame(state.level.rooms.len(), mesh_key.finish());
+    // Dynamic lights: cached meshes are unlit, so each lit room renders a relit copy
+    let mut lit_vertices = Vec::new();
+    for (i, room) in state.level.rooms.iter().enumerate() {
+        room_settings.light_dir = state.level.room_light_dir(i);
+        if let Some(tinted) = painting_flags.then(|| flag_tinted(room)).flatten() {
+            let (mut vertices, faces) = {
+                profile_scope!("render_data");
+                tinted.to_render_data_for(settings, resolve_texture)
+            };
+            if settings.dynamic_lights {
+                room.light_vertices(&mut vertices);
+            }
+            profile_scope!("raster");
+            render_mesh_with_scratch(fb, &vertices, &faces, textures, &state.camera_3d, &room_settings, &mut state.render_scratch);
+            continue;
+        }
+        let mesh = {
+            profile_scope!("render_data");
+            state.room_meshes.get_or_build(i, room, |room| {
+                // Count this room's missing faces on their own; cached meshes add theirs below
+                let before = missing_faces.get();
+                let (vertices, faces) = room.to_render_data_for(settings, resolve_texture);
+                let missing = missing_faces.replace(before) - before;
+                RoomMesh { vertices, faces, missing_faces: missing }
+            })
+        };
+        missing_faces.set(missing_faces.get() + mesh.missing_faces);
+        let vertices = if settings.dynamic_lights && !room.lights.is_empty() {
+            profile_scope!("lights");
+            lit_vertices.clear();
+            lit_vertices.extend_from_slice(&mesh.vertices);
+            room.light_vertices(&mut lit_vertices);
+            &lit_vertices
+        } else {
+            &mesh.vertices
+        };
+        profile_scope!("raster");
+        render_mesh_with_scratch(fb, vertices, &mesh.faces, textures, &state.camera_3d, &room_settings, &mut state.render_scratch);
+    }
+
+    // Invisible faces as ghosts, after all opaque geometry so they blend over it
+    for (i, room) in state.level.rooms.iter().enumerate().filter(|_| layers.invisible) {
+        let Some(ghosts) = invisible_ghosts(room) else { continue };
+        room_settings.light_dir = state.level.room_light_dir(i);
+        let (vertices, faces) = ghosts.to_render_data_with_textures(resolve_texture);
+        render_mesh_with_scratch(fb, &vertices, &faces, textures, &state.camera_3d, &room_settings, &mut state.render_scratch);
     }
 
+    // Pickups as bobbing billboards
+    let time = get_time();
+    let mut pickup_vertices = Vec::new();
+    let mut pickup_faces = Vec::new();
+    for room in state.level.rooms.iter().filter(|_| layers.objects) {
+        for pickup in &room.pickups {
+            let texture_id = resolve_texture(&pickup.sprite).unwrap_or(0);
+            pickup.add_billboard_to_render_data(room, time, &state.camera_3d, texture_id, &mut pickup_vertices, &mut pickup_faces);
+        }
+    }
+    if !pickup_faces.is_empty() {
+        render_mesh_with_scratch(fb, &pickup_vertices, &pickup_faces, textures, &state.camera_3d, settings, &mut state.render_scratch);
+    }
+    state.missing_texture_faces = missing_faces.get();
+    fb.apply_debug_view(state.raster_settings.debug_view);
+
     // Draw room boundary wireframe for the current room
-    if let Some(room) = state.level.rooms.get(state.current_room) {
+    if let Some(room) = state.level.rooms.get(state.current_room).filter(|_| layers.room_bounds) {
         let room_color = RasterColor::new(80, 120, 200); // Blue for room boundary
 
         // Room grid extents in world space
@@ -1667,9 +1831,12 @@ pub fn draw_viewport_3d(
         let max_z = room.position.z + (room.depth as f32) * SECTOR_SIZE;
 
         // Use Y range from room's actual geometry bounds
-        // bounds are room-relative, so add room.position.y
-        let min_y = room.position.y + room.bounds.min.y;
-        let max_y = room.position.y + room.bounds.max.y;
+        // bounds are room-relative, so add room.position.y (an empty room is flat at it)
+        let (min_y, max_y) = if room.is_empty() {
+            (room.position.y, room.position.y)
+        } else {
+            (room.position.y + room.bounds.min.y, room.position.y + room.bounds.max.y)
+        };
 
         // 8 corners of the room bounding box
         let corners = [
@@ -1685,9 +1852,7 @@ pub fn draw_viewport_3d(
 
         // Project corners to screen
         let screen_corners: Vec<Option<(i32, i32)>> = corners.iter()
-            .map(|c| world_to_screen(*c, state.camera_3d.position,
-                state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z,
-                fb.width, fb.height)
+            .map(|c| state.camera_3d.world_to_screen(*c, fb.width, fb.height)
                 .map(|(x, y)| (x as i32, y as i32)))
             .collect();
 
@@ -1708,18 +1873,23 @@ pub fn draw_viewport_3d(
         }
     }
 
+    // Portal outlines (magenta, like their labels)
+    if layers.portals {
+        let portal_color = RasterColor::new(230, 100, 230);
+        for room in &state.level.rooms {
+            for portal in &room.portals {
+                let v = portal.vertices.map(|v| v + room.position);
+                for i in 0..4 {
+                    draw_3d_line(fb, v[i], v[(i + 1) % 4], &state.camera_3d, portal_color);
+                }
+            }
+        }
+    }
+
     // Draw vertex overlays directly into framebuffer (only in Select mode)
-    if state.tool == EditorTool::Select {
+    if layers.wireframe && state.tool == EditorTool::Select {
         for (world_pos, room_idx, gx, gz, corner_idx, face) in &all_vertices {
-            if let Some((fb_x, fb_y)) = world_to_screen(
-                *world_pos,
-                state.camera_3d.position,
-                state.camera_3d.basis_x,
-                state.camera_3d.basis_y,
-                state.camera_3d.basis_z,
-                fb.width,
-                fb.height,
-            ) {
+            if let Some((fb_x, fb_y)) = state.camera_3d.world_to_screen(*world_pos, fb.width, fb.height) {
                 // Check if this specific vertex is hovered (match room, sector coords, corner index, and face)
                 let is_hovered = hovered_vertex.map_or(false, |(hr, hgx, hgz, hci, hface, _)|
                     hr == *room_idx && hgx == *gx && hgz == *gz && hci == *corner_idx && hface == *face);
@@ -1741,7 +1911,7 @@ pub fn draw_viewport_3d(
     }
 
     // Draw hovered edge highlight directly into framebuffer
-    if let Some((room_idx, gx, gz, face_idx, edge_idx, wall_face_opt, _)) = hovered_edge {
+    if let Some((room_idx, gx, gz, face_idx, edge_idx, wall_face_opt, _)) = hovered_edge.filter(|_| layers.wireframe) {
         if let Some(room) = state.level.rooms.get(room_idx) {
             if let Some(sector) = room.get_sector(gx, gz) {
                 let base_x = room.position.x + (gx as f32) * SECTOR_SIZE;
@@ -1766,26 +1936,7 @@ pub fn draw_viewport_3d(
                     2 => {
                         // Wall edge - get corners from the specific wall
                         if let Some(wf) = &wall_face_opt {
-                            let (x0, z0, x1, z1) = match wf {
-                                SectorFace::WallNorth(_) => (base_x, base_z, base_x + SECTOR_SIZE, base_z),
-                                SectorFace::WallEast(_) => (base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE),
-                                SectorFace::WallSouth(_) => (base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE),
-                                SectorFace::WallWest(_) => (base_x, base_z + SECTOR_SIZE, base_x, base_z),
-                                _ => (0.0, 0.0, 0.0, 0.0),
-                            };
-                            let wall_heights = match wf {
-                                SectorFace::WallNorth(i) => sector.walls_north.get(*i).map(|w| w.heights),
-                                SectorFace::WallEast(i) => sector.walls_east.get(*i).map(|w| w.heights),
-                                SectorFace::WallSouth(i) => sector.walls_south.get(*i).map(|w| w.heights),
-                                SectorFace::WallWest(i) => sector.walls_west.get(*i).map(|w| w.heights),
-                                _ => None,
-                            };
-                            wall_heights.map(|h| [
-                                Vec3::new(x0, h[0], z0),
-                                Vec3::new(x1, h[1], z1),
-                                Vec3::new(x1, h[2], z1),
-                                Vec3::new(x0, h[3], z0),
-                            ])
+                            wf.wall_in(sector).map(|(dir, w)| w.corners(base_x, base_z, dir))
                         } else {
                             None
                         }
@@ -1798,10 +1949,8 @@ pub fn draw_viewport_3d(
                     let v1 = corners[(edge_idx + 1) % 4];
 
                     if let (Some((sx0, sy0)), Some((sx1, sy1))) = (
-                        world_to_screen(v0, state.camera_3d.position, state.camera_3d.basis_x,
-                            state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height),
-                        world_to_screen(v1, state.camera_3d.position, state.camera_3d.basis_x,
-                            state.camera_3d.basis_y, state.camera_3d.basis_z, fb.width, fb.height)
+                        state.camera_3d.world_to_screen(v0, fb.width, fb.height),
+                        state.camera_3d.world_to_screen(v1, fb.width, fb.height)
                     ) {
                         fb.draw_thick_line(sx0 as i32, sy0 as i32, sx1 as i32, sy1 as i32, 3, edge_color);
                     }
@@ -1811,7 +1960,7 @@ pub fn draw_viewport_3d(
     }
 
     // Draw hover highlight for hovered face (in Select mode)
-    if let Some((room_idx, gx, gz, face)) = hovered_face {
+    if let Some((room_idx, gx, gz, face)) = hovered_face.filter(|_| layers.wireframe) {
         // Don't draw hover if this face is already selected
         let is_selected = state.selection.includes_face(room_idx, gx, gz, face);
         if !is_selected {
@@ -1852,51 +2001,10 @@ pub fn draw_viewport_3d(
                                 draw_3d_line(fb, corners[0], corners[2], &state.camera_3d, hover_color);
                             }
                         }
-                        SectorFace::WallNorth(i) => {
-                            if let Some(wall) = sector.walls_north.get(i) {
-                                let p0 = Vec3::new(base_x, wall.heights[0], base_z);
-                                let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z);
-                                let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z);
-                                let p3 = Vec3::new(base_x, wall.heights[3], base_z);
-                                draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p2, p3, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p3, p0, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p0, p2, &state.camera_3d, hover_color);
-                            }
-                        }
-                        SectorFace::WallEast(i) => {
-                            if let Some(wall) = sector.walls_east.get(i) {
-                                let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z);
-                                let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z + SECTOR_SIZE);
-                                let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z + SECTOR_SIZE);
-                                let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z);
-                                draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p2, p3, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p3, p0, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p0, p2, &state.camera_3d, hover_color);
-                            }
-                        }
-                        SectorFace::WallSouth(i) => {
-                            if let Some(wall) = sector.walls_south.get(i) {
-                                let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z + SECTOR_SIZE);
-                                let p1 = Vec3::new(base_x, wall.heights[1], base_z + SECTOR_SIZE);
-                                let p2 = Vec3::new(base_x, wall.heights[2], base_z + SECTOR_SIZE);
-                                let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z + SECTOR_SIZE);
-                                draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p2, p3, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p3, p0, &state.camera_3d, hover_color);
-                                draw_3d_line(fb, p0, p2, &state.camera_3d, hover_color);
-                            }
-                        }
-                        SectorFace::WallWest(i) => {
-                            if let Some(wall) = sector.walls_west.get(i) {
-                                let p0 = Vec3::new(base_x, wall.heights[0], base_z + SECTOR_SIZE);
-                                let p1 = Vec3::new(base_x, wall.heights[1], base_z);
-                                let p2 = Vec3::new(base_x, wall.heights[2], base_z);
-                                let p3 = Vec3::new(base_x, wall.heights[3], base_z + SECTOR_SIZE);
+                        wall_face => {
+                            let wall = wall_face.wall_in(sector);
+                            if let Some((dir, wall)) = wall {
+                                let [p0, p1, p2, p3] = wall.corners(base_x, base_z, dir);
                                 draw_3d_line(fb, p0, p1, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p1, p2, &state.camera_3d, hover_color);
                                 draw_3d_line(fb, p2, p3, &state.camera_3d, hover_color);
@@ -1951,51 +2059,10 @@ pub fn draw_viewport_3d(
                                     draw_3d_line(fb, corners[0], corners[2], &state.camera_3d, select_color);
                                 }
                             }
-                            SectorFace::WallNorth(i) => {
-                                if let Some(wall) = sector.walls_north.get(*i) {
-                                    let p0 = Vec3::new(base_x, wall.heights[0], base_z);
-                                    let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z);
-                                    let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z);
-                                    let p3 = Vec3::new(base_x, wall.heights[3], base_z);
-                                    draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p3, p0, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p0, p2, &state.camera_3d, select_color);
-                                }
-                            }
-                            SectorFace::WallEast(i) => {
-                                if let Some(wall) = sector.walls_east.get(*i) {
-                                    let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z);
-                                    let p1 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z + SECTOR_SIZE);
-                                    let p2 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z + SECTOR_SIZE);
-                                    let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z);
-                                    draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p3, p0, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p0, p2, &state.camera_3d, select_color);
-                                }
-                            }
-                            SectorFace::WallSouth(i) => {
-                                if let Some(wall) = sector.walls_south.get(*i) {
-                                    let p0 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z + SECTOR_SIZE);
-                                    let p1 = Vec3::new(base_x, wall.heights[1], base_z + SECTOR_SIZE);
-                                    let p2 = Vec3::new(base_x, wall.heights[2], base_z + SECTOR_SIZE);
-                                    let p3 = Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z + SECTOR_SIZE);
-                                    draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p3, p0, &state.camera_3d, select_color);
-                                    draw_3d_line(fb, p0, p2, &state.camera_3d, select_color);
-                                }
-                            }
-                            SectorFace::WallWest(i) => {
-                                if let Some(wall) = sector.walls_west.get(*i) {
-                                    let p0 = Vec3::new(base_x, wall.heights[0], base_z + SECTOR_SIZE);
-                                    let p1 = Vec3::new(base_x, wall.heights[1], base_z);
-                                    let p2 = Vec3::new(base_x, wall.heights[2], base_z);
-                                    let p3 = Vec3::new(base_x, wall.heights[3], base_z + SECTOR_SIZE);
+                            wall_face => {
+                                let wall = wall_face.wall_in(sector);
+                                if let Some((dir, wall)) = wall {
+                                    let [p0, p1, p2, p3] = wall.corners(base_x, base_z, dir);
                                     draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
                                     draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
@@ -2066,19 +2133,9 @@ pub fn draw_viewport_3d(
                         }
 
                         // Draw wall outlines
-                        let wall_sets = [
-                            (&sector.walls_north, base_x, base_z, base_x + SECTOR_SIZE, base_z),
-                            (&sector.walls_east, base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE),
-                            (&sector.walls_south, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE),
-                            (&sector.walls_west, base_x, base_z + SECTOR_SIZE, base_x, base_z),
-                        ];
-
-                        for (walls, x0, z0, x1, z1) in wall_sets {
-                            for wall in walls {
-                                let p0 = Vec3::new(x0, wall.heights[0], z0);
-                                let p1 = Vec3::new(x1, wall.heights[1], z1);
-                                let p2 = Vec3::new(x1, wall.heights[2], z1);
-                                let p3 = Vec3::new(x0, wall.heights[3], z0);
+                        for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                            for wall in sector.walls(dir) {
+                                let [p0, p1, p2, p3] = wall.corners(base_x, base_z, dir);
                                 draw_3d_line(fb, p0, p1, &state.camera_3d, select_color);
                                 draw_3d_line(fb, p1, p2, &state.camera_3d, select_color);
                                 draw_3d_line(fb, p2, p3, &state.camera_3d, select_color);
@@ -2111,26 +2168,7 @@ pub fn draw_viewport_3d(
                         } else if *face_idx == 2 {
                             // Wall edge
                             if let Some(wf) = wall_face {
-                                let (x0, z0, x1, z1) = match wf {
-                                    SectorFace::WallNorth(_) => (base_x, base_z, base_x + SECTOR_SIZE, base_z),
-                                    SectorFace::WallEast(_) => (base_x + SECTOR_SIZE, base_z, base_x + SECTOR_SIZE, base_z + SECTOR_SIZE),
-                                    SectorFace::WallSouth(_) => (base_x + SECTOR_SIZE, base_z + SECTOR_SIZE, base_x, base_z + SECTOR_SIZE),
-                                    SectorFace::WallWest(_) => (base_x, base_z + SECTOR_SIZE, base_x, base_z),
-                                    _ => (0.0, 0.0, 0.0, 0.0),
-                                };
-                                let wall_heights = match wf {
-                                    SectorFace::WallNorth(i) => sector.walls_north.get(*i).map(|w| w.heights),
-                                    SectorFace::WallEast(i) => sector.walls_east.get(*i).map(|w| w.heights),
-                                    SectorFace::WallSouth(i) => sector.walls_south.get(*i).map(|w| w.heights),
-                                    SectorFace::WallWest(i) => sector.walls_west.get(*i).map(|w| w.heights),
-                                    _ => None,
-                                };
-                                wall_heights.map(|h| [
-                                    Vec3::new(x0, h[0], z0),
-                                    Vec3::new(x1, h[1], z1),
-                                    Vec3::new(x1, h[2], z1),
-                                    Vec3::new(x0, h[3], z0),
-                                ])
+                                wf.wall_in(sector).map(|(dir, w)| w.corners(base_x, base_z, dir))
                             } else {
                                 None
                             }
@@ -2150,12 +2188,12 @@ pub fn draw_viewport_3d(
         }
     };
 
-    // Draw primary selection
-    draw_selection(fb, &state.selection);
-
-    // Draw all multi-selections
-    for sel in &state.multi_selection {
-        draw_selection(fb, sel);
+    // Draw primary selection, then all multi-selections
+    if layers.wireframe {
+        draw_selection(fb, &state.selection);
+        for sel in &state.multi_selection {
+            draw_selection(fb, sel);
+        }
     }
 
     // Draw floor/ceiling placement preview wireframe with vertical sector boundaries
@@ -2191,27 +2229,21 @@ pub fn draw_viewport_3d(
         let mut screen_ceiling = Vec::new();
 
         for corner in &corners {
-            if let Some((sx, sy)) = world_to_screen(*corner, state.camera_3d.position,
-                state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z,
-                fb.width, fb.height)
+            if let Some((sx, sy)) = state.camera_3d.world_to_screen(*corner, fb.width, fb.height)
             {
                 screen_corners.push((sx as i32, sy as i32));
             }
         }
 
         for corner in &floor_corners {
-            if let Some((sx, sy)) = world_to_screen(*corner, state.camera_3d.position,
-                state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z,
-                fb.width, fb.height)
+            if let Some((sx, sy)) = state.camera_3d.world_to_screen(*corner, fb.width, fb.height)
             {
                 screen_floor.push((sx as i32, sy as i32));
             }
         }
 
         for corner in &ceiling_corners {
-            if let Some((sx, sy)) = world_to_screen(*corner, state.camera_3d.position,
-                state.camera_3d.basis_x, state.camera_3d.basis_y, state.camera_3d.basis_z,
-                fb.width, fb.height)
+            if let Some((sx, sy)) = state.camera_3d.world_to_screen(*corner, fb.width, fb.height)
             {
                 screen_ceiling.push((sx as i32, sy as i32));
             }
@@ -2264,6 +2296,35 @@ pub fn draw_viewport_3d(
         }
     }
 
+    // Measure chain (and rubber band to the mouse)
+    let measure_band = state.measure.points.last().copied().zip(measure_hover);
+    if state.measure.in_3d {
+        let color = RasterColor::new(255, 220, 80);
+        for (a, b) in state.measure.segments().chain(measure_band) {
+            draw_3d_line(fb, a, b, &state.camera_3d, color);
+        }
+        for p in state.measure.points.iter().chain(measure_hover.as_ref()) {
+            if let Some((x, y)) = state.camera_3d.world_to_screen(*p, fb.width, fb.height) {
+                fb.draw_circle(x as i32, y as i32, 2, color);
+            }
+        }
+    }
+
+    // Room and portal labels (numbered by room index, as in the grid view)
+    if state.show_labels && !state.isolate_geometry {
+        let settings = &state.raster_settings;
+        for (idx, room) in state.level.rooms.iter().enumerate() {
+            let center = room.world_center();
+            render_world_label(fb, &state.camera_3d, center, &format!("Room {}", idx), RasterColor::new(220, 220, 220), settings);
+            for portal in room.portals.iter().filter(|_| layers.portals) {
+                let target = state.level.room_index(portal.target_room)
+                    .map_or("?".to_string(), |i| i.to_string());
+                let pos = portal.center() + room.position;
+                render_world_label(fb, &state.camera_3d, pos, &format!("> {}", target), RasterColor::new(230, 100, 230), settings);
+            }
+        }
+    }
+
     // Convert framebuffer to texture and draw to viewport
     let texture = Texture2D::from_rgba8(fb.width as u16, fb.height as u16, &fb.pixels);
     texture.set_filter(FilterMode::Nearest);
@@ -2282,6 +2343,26 @@ pub fn draw_viewport_3d(
     // Draw viewport border
     draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, Color::from_rgba(60, 60, 60, 255));
 
+    // Measure labels at segment midpoints, total under the end of a longer chain
+    if state.measure.in_3d && !state.measure.points.is_empty() {
+        let to_screen = |p: Vec3| state.camera_3d.world_to_screen(p, fb_width, fb_height)
+            .map(|(x, y)| (draw_x + x / fb_width as f32 * draw_w, draw_y + y / fb_height as f32 * draw_h));
+        let label_color = Color::from_rgba(255, 240, 180, 255);
+        let mut segments = 0;
+        for (a, b) in state.measure.segments().chain(measure_band) {
+            segments += 1;
+            if let Some((x, y)) = to_screen((a + b) * 0.5) {
+                draw_text(&label_3d(a, b), (x + 6.0).floor(), (y - 6.0).floor(), 13.0, label_color);
+            }
+        }
+        let end = measure_band.map_or(state.measure.points[state.measure.points.len() - 1], |(_, c)| c);
+        if let Some((x, y)) = to_screen(end).filter(|_| segments > 1) {
+            let total = state.measure.total(measure_hover);
+            let text = format!("Total {:.0} ({:.2} sec)", total, total / SECTOR_SIZE);
+            draw_text(&text, (x + 6.0).floor(), (y + 16.0).floor(), 13.0, label_color);
+        }
+    }
+
     // Draw camera info (position and rotation)
     draw_text(
         &format!(
@@ -2297,6 +2378,8 @@ pub fn draw_viewport_3d(
         14.0,
         Color::from_rgba(200, 200, 200, 255),
     );
+
+    draw_axis_gizmo(ctx, &rect, state);
 }
 
 /// Draw a 3D line into the framebuffer using Bresenham's algorithm
@@ -2307,7 +2390,7 @@ fn draw_3d_line(
     camera: &crate::rasterizer::Camera,
     color: RasterColor,
 ) {
-    const NEAR_PLANE: f32 = 0.1;
+    let near = camera.near_plane;
 
     // Transform to camera space
     let rel0 = p0 - camera.position;
@@ -2317,17 +2400,17 @@ fn draw_3d_line(
     let z1 = rel1.dot(camera.basis_z);
 
     // Both behind camera - skip entirely
-    if z0 <= NEAR_PLANE && z1 <= NEAR_PLANE {
+    if z0 <= near && z1 <= near {
         return;
     }
 
     // Clip line to near plane if needed
-    let (clipped_p0, clipped_p1) = if z0 <= NEAR_PLANE {
-        let t = (NEAR_PLANE - z0) / (z1 - z0);
+    let (clipped_p0, clipped_p1) = if z0 <= near {
+        let t = (near - z0) / (z1 - z0);
         let new_p0 = p0 + (p1 - p0) * t;
         (new_p0, p1)
-    } else if z1 <= NEAR_PLANE {
-        let t = (NEAR_PLANE - z0) / (z1 - z0);
+    } else if z1 <= near {
+        let t = (near - z0) / (z1 - z0);
         let new_p1 = p0 + (p1 - p0) * t;
         (p0, new_p1)
     } else {
@@ -2335,8 +2418,8 @@ fn draw_3d_line(
     };
 
     // Project clipped endpoints
-    let s0 = world_to_screen(clipped_p0, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height);
-    let s1 = world_to_screen(clipped_p1, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height);
+    let s0 = camera.world_to_screen(clipped_p0, fb.width, fb.height);
+    let s1 = camera.world_to_screen(clipped_p1, fb.width, fb.height);
 
     let (Some((x0f, y0f)), Some((x1f, y1f))) = (s0, s1) else {
         return;
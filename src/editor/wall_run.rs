@@ -0,0 +1,134 @@
+//! Wall runs
+//!
+//! With the Wall tool, clicking a grid corner in the 2D view starts a run and
+//! clicking a second corner in the same row or column places a wall on every
+//! sector edge between them, each spanning its sector's floor to its ceiling.
+//! One undo step.
+
+use crate::world::{Direction, Room, TextureRef, VerticalFace};
+use super::{EditorState, CEILING_HEIGHT, CLICK_HEIGHT};
+
+/// Default wall height (in clicks) when the sector has no ceiling
+const DEFAULT_WALL_CLICKS: f32 = 4.0;
+
+/// Grid corner of a room: (room index, corner x, corner z), 0..=width / 0..=depth
+pub type RunCorner = (usize, usize, usize);
+
+/// Wall along the `dir` edge of sector (gx, gz), from the sector's floor to its
+/// ceiling along that edge (or a few clicks above the floor when it has no ceiling)
+pub fn edge_wall(room: &Room, gx: usize, gz: usize, dir: Direction, texture: TextureRef) -> VerticalFace {
+    let (bottom, top) = match room.get_sector(gx, gz) {
+        Some(sector) => {
+            let (bl, br) = sector.floor.as_ref()
+                .map(|f| f.edge_heights(dir))
+                .unwrap_or((0.0, 0.0));
+            let (tl, tr) = sector.ceiling.as_ref()
+                .map(|c| c.edge_heights(dir))
+                .unwrap_or((
+                    bl + DEFAULT_WALL_CLICKS * CLICK_HEIGHT,
+                    br + DEFAULT_WALL_CLICKS * CLICK_HEIGHT,
+                ));
+            ((bl, br), (tl, tr))
+        }
+        None => ((0.0, 0.0), (CEILING_HEIGHT, CEILING_HEIGHT)),
+    };
+    let mut wall = VerticalFace::new(bottom.0, top.0, texture);
+    wall.heights = [bottom.0, bottom.1, top.1, top.0];
+    wall
+}
+
+/// Sector edges (x, z, side) between two grid corners in the same row or column.
+/// Each edge goes on the sector that exists on either side of it (the south or
+/// east one when both do, or when neither does but it's inside the grid).
+pub fn wall_run_edges(room: &Room, a: (usize, usize), b: (usize, usize)) -> Result<Vec<(usize, usize, Direction)>, String> {
+    if a == b {
+        return Err("Wall run needs two different corners".to_string());
+    }
+    if a.0 != b.0 && a.1 != b.1 {
+        return Err("Wall runs must be straight along X or Z".to_string());
+    }
+
+    let in_grid = |x: usize, z: usize, dir: Direction| (x < room.width && z < room.depth).then_some((x, z, dir));
+    let mut edges = Vec::new();
+    if a.1 == b.1 {
+        // Along X: sectors south (facing north) and north (facing south) of the line
+        let z = a.1;
+        for x in a.0.min(b.0)..a.0.max(b.0) {
+            let north = z.checked_sub(1).and_then(|nz| in_grid(x, nz, Direction::South));
+            edges.push([in_grid(x, z, Direction::North), north]);
+        }
+    } else {
+        // Along Z: sectors east (facing west) and west (facing east) of the line
+        let x = a.0;
+        for z in a.1.min(b.1)..a.1.max(b.1) {
+            let west = x.checked_sub(1).and_then(|wx| in_grid(wx, z, Direction::East));
+            edges.push([in_grid(x, z, Direction::West), west]);
+        }
+    }
+
+    Ok(edges.into_iter()
+        .filter_map(|sides| {
+            let mut sides = sides.into_iter().flatten();
+            let first = sides.clone().next();
+            sides.find(|&(x, z, _)| room.get_sector(x, z).is_some()).or(first)
+        })
+        .collect())
+}
+
+impl EditorState {
+    /// Place walls with the selected texture along the run from `a` to `b` (one undo
+    /// step). Edges that already have a full-width wall are skipped.
+    pub fn place_wall_run(&mut self, a: RunCorner, b: RunCorner) -> Result<(), String> {
+        let room_idx = a.0;
+        let room = self.level.rooms.get(room_idx).ok_or("Room is gone")?;
+        let edges = wall_run_edges(room, (a.1, a.2), (b.1, b.2))?;
+        let walled = |&(x, z, dir): &(usize, usize, Direction)| {
+            room.get_sector(x, z).is_some_and(|s| s.walls(dir).iter().any(|w| w.is_full_width()))
+        };
+        let (existing, new): (Vec<_>, Vec<_>) = edges.into_iter().partition(walled);
+        if new.is_empty() {
+            self.set_status(&format!("All {} edges already have walls", existing.len()), 2.0);
+            return Ok(());
+        }
+
+        self.save_undo(&format!("Wall run ({}, {}) to ({}, {})", a.1, a.2, b.1, b.2));
+        let texture = self.selected_texture.clone();
+        let room = &mut self.level.rooms[room_idx];
+        for &(x, z, dir) in &new {
+            let wall = edge_wall(room, x, z, dir, texture.clone());
+            room.push_wall(x, z, dir, wall);
+        }
+        room.recalculate_bounds();
+        self.mark_room_dirty(room_idx);
+
+        let skipped = if existing.is_empty() { String::new() } else { format!(" ({} already walled)", existing.len()) };
+        self.set_status(&format!("Created {} walls{}", new.len(), skipped), 2.0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+
+    #[test]
+    fn run_walls_the_existing_side_of_each_edge() {
+        let mut room = Room::new(0, Vec3::ZERO, 3, 2);
+        for x in 0..3 {
+            room.set_floor(x, 1, 0.0, TextureRef::none());
+        }
+
+        // Row z = 1 has the floors: they get the walls, facing north
+        let edges = wall_run_edges(&room, (3, 1), (0, 1)).unwrap();
+        assert_eq!(edges, vec![(0, 1, Direction::North), (1, 1, Direction::North), (2, 1, Direction::North)]);
+        // Along the room's west side, only the in-grid sectors east of the line
+        let edges = wall_run_edges(&room, (0, 0), (0, 2)).unwrap();
+        assert_eq!(edges, vec![(0, 0, Direction::West), (0, 1, Direction::West)]);
+        // Bottom edge of the grid: the sectors north of it
+        assert_eq!(wall_run_edges(&room, (0, 2), (1, 2)).unwrap(), vec![(0, 1, Direction::South)]);
+
+        assert!(wall_run_edges(&room, (0, 0), (2, 1)).is_err());
+        assert!(wall_run_edges(&room, (1, 1), (1, 1)).is_err());
+    }
+}
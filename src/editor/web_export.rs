@@ -0,0 +1,186 @@
+//! Playable Web Bundles
+//!
+//! Export a level as a folder that plays it in a browser and nothing else: the
+//! prebuilt web build (`docs/`, as the deploy workflow publishes it), the level as
+//! `level.ron`, and optionally the textures it uses (just those files, with their
+//! own texture manifest). The bundle's `index.html` sets `BONNIE_PLAY_LEVEL`, which
+//! boots the engine straight into play mode for that level instead of the editor
+//! (`?play=<file>` does the same on any web build, `--play <file>` natively).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use crate::world::{aliased_pack, level_to_string, Level};
+use super::texture_pack::PACK_SETTINGS_FILE;
+use super::zip::write_zip;
+use super::TexturePack;
+
+/// Prebuilt web build the bundle is made from
+const WEB_DIR: &str = "docs";
+/// Files of the web build the bundle copies as they are (plus `index.html`)
+const WEB_FILES: [&str; 6] = [
+    "bonnie-engine.wasm", "mq_js_bundle.js", "audio-processor.js",
+    "favicon-16.png", "favicon-32.png", "apple-touch-icon.png",
+];
+/// The level inside the bundle
+const BUNDLE_LEVEL: &str = "level.ron";
+/// The page's boot script goes right before the engine's JS
+const BOOT_MARKER: &str = "<script src=\"mq_js_bundle.js\"></script>";
+
+/// Files of a bundle, by path inside it
+pub struct WebBundle {
+    pub files: Vec<(String, Vec<u8>)>,
+    /// Texture files included
+    pub textures: usize,
+    /// Textures the level uses that weren't found (drawn as placeholders)
+    pub missing: usize,
+}
+
+/// `index.html` of the web build, set to play `level_file` on load
+fn boot_page(index_html: &str, level_file: &str) -> Result<String, String> {
+    if !index_html.contains(BOOT_MARKER) {
+        return Err(format!("{}/index.html doesn't load mq_js_bundle.js", WEB_DIR));
+    }
+    let boot = format!("<script>window.BONNIE_PLAY_LEVEL = '{}';</script>\n    {}", level_file, BOOT_MARKER);
+    Ok(index_html.replacen(BOOT_MARKER, &boot, 1))
+}
+
+/// Texture names the level's faces and pickups use, by the pack they're looked up
+/// in (pack aliases applied)
+fn used_textures(level: &Level) -> BTreeMap<String, BTreeSet<String>> {
+    let pickups = level.rooms.iter().flat_map(|room| &room.pickups).map(|p| &p.sprite);
+    let mut used: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for tex in level.texture_refs().chain(pickups).filter(|t| t.is_valid()) {
+        let pack = aliased_pack(&level.pack_aliases, &tex.pack);
+        used.entry(pack.to_string()).or_default().insert(tex.name.clone());
+    }
+    used
+}
+
+/// PNG files of a pack directory (and its subdirectories, like pack loading), by
+/// texture name
+fn pack_files(dir: &Path) -> BTreeMap<String, PathBuf> {
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        dirs.extend(entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()));
+    }
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let is_png = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png"));
+            if let (true, Some(stem)) = (is_png, path.file_stem()) {
+                files.entry(stem.to_string_lossy().into_owned()).or_insert(path);
+            }
+        }
+    }
+    files
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))
+}
+
+impl WebBundle {
+    /// Collect a bundle playing `level`. With `textures`, the files of the textures
+    /// it uses are copied from `packs` (only packs loaded from a directory); without,
+    /// every face shows a placeholder.
+    pub fn build(level: &Level, packs: &[TexturePack], textures: bool) -> Result<Self, String> {
+        let web = Path::new(WEB_DIR);
+        let index = read_file(&web.join("index.html"))
+            .map_err(|e| format!("{} (build the web version first, see the README)", e))?;
+        let mut files = vec![("index.html".to_string(), boot_page(&String::from_utf8_lossy(&index), BUNDLE_LEVEL)?.into_bytes())];
+        for name in WEB_FILES {
+            files.push((name.to_string(), read_file(&web.join(name))?));
+        }
+        let text = level_to_string(level).map_err(|e| e.to_string())?;
+        files.push((BUNDLE_LEVEL.to_string(), text.into_bytes()));
+
+        let mut bundle = Self { files, textures: 0, missing: 0 };
+        let mut manifest = String::new();
+        if textures {
+            let used = used_textures(level);
+            bundle.missing = used.iter()
+                .filter(|(pack, _)| !packs.iter().any(|p| &p.name == *pack))
+                .map(|(_, names)| names.len())
+                .sum();
+            // Same pack order as the editor, so texture ids match
+            for (i, pack) in packs.iter().enumerate() {
+                let mut names: Vec<&str> = used.get(&pack.name).into_iter().flatten().map(String::as_str).collect();
+                // Untextured faces draw with the very first texture
+                if let (0, Some(first)) = (i, pack.textures.first()) {
+                    names.retain(|&name| name != first.name);
+                    names.insert(0, &first.name);
+                }
+                if names.is_empty() {
+                    continue;
+                }
+                let sources = pack_files(&pack.path);
+                manifest.push_str(&format!("[{}]\n", pack.name));
+                for name in names {
+                    let Some(source) = sources.get(name) else {
+                        bundle.missing += 1;
+                        continue;
+                    };
+                    bundle.files.push((format!("assets/textures/{}/{}.png", pack.name, name), read_file(source)?));
+                    manifest.push_str(&format!("{}.png\n", name));
+                    bundle.textures += 1;
+                }
+                if let Ok(settings) = std::fs::read(pack.path.join(PACK_SETTINGS_FILE)) {
+                    bundle.files.push((format!("assets/textures/{}/{}", pack.name, PACK_SETTINGS_FILE), settings));
+                }
+            }
+        }
+        bundle.files.push(("assets/textures/manifest.txt".to_string(), manifest.into_bytes()));
+        Ok(bundle)
+    }
+
+    /// Write the bundle's files under `dir`
+    pub fn write(&self, dir: &Path) -> Result<(), String> {
+        for (name, data) in &self.files {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| format!("Can't create {}: {}", parent.display(), e))?;
+            }
+            std::fs::write(&path, data).map_err(|e| format!("Can't write {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// The bundle as a zip archive (the same files, for sharing)
+    pub fn to_zip(&self) -> Vec<u8> {
+        write_zip(&self.files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::{Pickup, Room, TextureRef, PICKUP_KINDS};
+
+    #[test]
+    fn bundle_page_boots_into_the_level() {
+        let page = format!("<body>\n    {}\n</body>", BOOT_MARKER);
+        let boot = boot_page(&page, "level.ron").unwrap();
+        assert!(boot.contains("window.BONNIE_PLAY_LEVEL = 'level.ron';</script>\n    <script src=\"mq_js_bundle.js\">"));
+        assert_eq!(boot.matches(BOOT_MARKER).count(), 1);
+        assert!(boot_page("<body></body>", "level.ron").is_err());
+    }
+
+    #[test]
+    fn used_textures_follow_aliases_and_include_pickups() {
+        let mut room = Room::new(0, Vec3::ZERO, 2, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::new("GOTHIC", "FLOOR"));
+        room.set_floor(1, 0, 0.0, TextureRef::new("retro", "WALL"));
+        room.set_ceiling(1, 0, 1024.0, TextureRef::none());
+        room.pickups.push(Pickup::new(Vec3::ZERO, PICKUP_KINDS[0], TextureRef::new("retro", "GEM")));
+        let mut level = Level::new();
+        level.add_room(room);
+        level.pack_aliases.insert("GOTHIC".to_string(), "retro".to_string());
+
+        let used = used_textures(&level);
+        assert_eq!(used.len(), 1);
+        let names: Vec<&str> = used["retro"].iter().map(String::as_str).collect();
+        assert_eq!(names, ["FLOOR", "GEM", "WALL"]);
+    }
+}
@@ -0,0 +1,260 @@
+//! Minimal ZIP reader for uploads (and writer for exports)
+//!
+//! Handles what browsers and OS "compress folder" tools produce: stored and deflate
+//! entries listed in the central directory. No ZIP64, encryption or multi-disk archives.
+
+/// A decompressed file from an archive
+pub struct ZipEntry {
+    /// Path inside the archive (forward slashes)
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl ZipEntry {
+    /// File name without directories
+    pub fn file_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+
+    /// Case-insensitive extension check (e.g. "png")
+    pub fn has_extension(&self, ext: &str) -> bool {
+        self.name.rsplit_once('.').is_some_and(|(_, e)| e.eq_ignore_ascii_case(ext))
+    }
+}
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+const EOCD_SIZE: usize = 22;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16, String> {
+    bytes.get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| "Truncated zip archive".to_string())
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32, String> {
+    bytes.get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Truncated zip archive".to_string())
+}
+
+/// Read every file in a zip archive (directories are skipped).
+/// Fails if the files would decompress to more than `max_total` bytes.
+pub fn read_zip(bytes: &[u8], max_total: usize) -> Result<Vec<ZipEntry>, String> {
+    // End of central directory record: scan back over a possible trailing comment
+    let search_start = bytes.len().saturating_sub(EOCD_SIZE + u16::MAX as usize);
+    let eocd = (search_start..=bytes.len().saturating_sub(EOCD_SIZE))
+        .rev()
+        .find(|&i| read_u32(bytes, i) == Ok(EOCD_SIGNATURE))
+        .ok_or_else(|| "Not a zip archive".to_string())?;
+
+    let entry_count = read_u16(bytes, eocd + 10)? as usize;
+    let mut offset = read_u32(bytes, eocd + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(entry_count);
+    let mut total = 0usize;
+    for _ in 0..entry_count {
+        if read_u32(bytes, offset)? != CENTRAL_SIGNATURE {
+            return Err("Corrupt zip central directory".to_string());
+        }
+        let method = read_u16(bytes, offset + 10)?;
+        let compressed_size = read_u32(bytes, offset + 20)? as usize;
+        let size = read_u32(bytes, offset + 24)? as usize;
+        let name_len = read_u16(bytes, offset + 28)? as usize;
+        let extra_len = read_u16(bytes, offset + 30)? as usize;
+        let comment_len = read_u16(bytes, offset + 32)? as usize;
+        let local_offset = read_u32(bytes, offset + 42)? as usize;
+        let name_bytes = bytes.get(offset + 46..offset + 46 + name_len)
+            .ok_or_else(|| "Truncated zip archive".to_string())?;
+        let name = String::from_utf8_lossy(name_bytes).replace('\\', "/");
+        offset += 46 + name_len + extra_len + comment_len;
+
+        if name.ends_with('/') {
+            continue;
+        }
+
+        total += size;
+        if total > max_total {
+            return Err(format!(
+                "Archive too large: over {} MB uncompressed",
+                max_total / (1024 * 1024)
+            ));
+        }
+
+        // Local header has its own name/extra lengths before the data
+        if read_u32(bytes, local_offset)? != LOCAL_SIGNATURE {
+            return Err(format!("Corrupt zip entry: {}", name));
+        }
+        let data_start = local_offset + 30
+            + read_u16(bytes, local_offset + 26)? as usize
+            + read_u16(bytes, local_offset + 28)? as usize;
+        let raw = bytes.get(data_start..data_start + compressed_size)
+            .ok_or_else(|| "Truncated zip archive".to_string())?;
+
+        let data = match method {
+            METHOD_STORED => raw.to_vec(),
+            METHOD_DEFLATE => miniz_oxide::inflate::decompress_to_vec_with_limit(raw, size)
+                .map_err(|e| format!("Failed to inflate {}: {:?}", name, e.status))?,
+            other => return Err(format!("Unsupported compression ({}) for {}", other, name)),
+        };
+        entries.push(ZipEntry { name, data });
+    }
+
+    Ok(entries)
+}
+
+/// CRC-32 (IEEE) of `data`, as zip headers store it
+#[cfg(not(target_arch = "wasm32"))]
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Build a zip archive of `files` (path inside the archive, contents). Entries are
+/// deflated unless that doesn't make them smaller (PNGs are stored).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_zip(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let deflated = miniz_oxide::deflate::compress_to_vec(data, 6);
+        let (method, payload) = if deflated.len() < data.len() {
+            (METHOD_DEFLATE, deflated.as_slice())
+        } else {
+            (METHOD_STORED, data.as_slice())
+        };
+        let crc = crc32(data);
+        let local_offset = out.len() as u32;
+        // Shared by the local and central headers: method, time/date, crc, sizes, name length
+        let mut fields = Vec::with_capacity(24);
+        fields.extend_from_slice(&method.to_le_bytes());
+        fields.extend_from_slice(&[0; 4]);
+        fields.extend_from_slice(&crc.to_le_bytes());
+        fields.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+
+        out.extend_from_slice(&LOCAL_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&[20, 0, 0, 0]); // version needed, flags
+        out.extend_from_slice(&fields);
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(payload);
+
+        central.extend_from_slice(&CENTRAL_SIGNATURE.to_le_bytes());
+        central.extend_from_slice(&[20, 0, 20, 0, 0, 0]); // versions, flags
+        central.extend_from_slice(&fields);
+        central.extend_from_slice(&[0; 12]); // extra, comment, disk, attributes
+        central.extend_from_slice(&local_offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // disk numbers
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an archive in memory (method 0 = stored, 8 = deflate)
+    fn build_zip(files: &[(&str, &[u8], u16)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for &(name, data, method) in files {
+            let payload = if method == METHOD_DEFLATE {
+                miniz_oxide::deflate::compress_to_vec(data, 6)
+            } else {
+                data.to_vec()
+            };
+            let local_offset = out.len() as u32;
+
+            out.extend_from_slice(&LOCAL_SIGNATURE.to_le_bytes());
+            out.extend_from_slice(&[20, 0, 0, 0]);
+            out.extend_from_slice(&method.to_le_bytes());
+            out.extend_from_slice(&[0; 8]); // time, date, crc
+            out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&payload);
+
+            central.extend_from_slice(&CENTRAL_SIGNATURE.to_le_bytes());
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0]);
+            central.extend_from_slice(&method.to_le_bytes());
+            central.extend_from_slice(&[0; 8]);
+            central.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0; 12]); // extra, comment, disk, attributes
+            central.extend_from_slice(&local_offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let central_offset = out.len() as u32;
+        out.extend_from_slice(&central);
+        out.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        out.extend_from_slice(&central_offset.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_entries() {
+        let text = b"hello hello hello hello".repeat(20);
+        let zip = build_zip(&[
+            ("pack/", b"", METHOD_STORED),
+            ("pack/A.PNG", b"raw", METHOD_STORED),
+            ("pack/level.ron", &text, METHOD_DEFLATE),
+        ]);
+        let entries = read_zip(&zip, 1 << 20).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].file_name(), "A.PNG");
+        assert!(entries[0].has_extension("png"));
+        assert_eq!(entries[0].data, b"raw");
+        assert_eq!(entries[1].data, text);
+    }
+
+    #[test]
+    fn written_archives_read_back() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        let text = b"level level level level".repeat(20);
+        let files = vec![
+            ("index.html".to_string(), text.clone()),
+            ("assets/textures/pack/A.png".to_string(), vec![1, 2, 3]),
+        ];
+        let entries = read_zip(&write_zip(&files), 1 << 20).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].data, text);
+        assert_eq!(entries[1].name, "assets/textures/pack/A.png");
+        assert_eq!(entries[1].data, [1, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_oversized_and_invalid_archives() {
+        let zip = build_zip(&[("big.bin", &[0u8; 4096], METHOD_DEFLATE)]);
+        assert!(read_zip(&zip, 1024).is_err());
+        assert!(read_zip(b"definitely not a zip", 1024).is_err());
+    }
+}
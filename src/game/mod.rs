@@ -0,0 +1,13 @@
+//! Game mode simulation
+//!
+//! Player physics and tuning, kept free of rendering and input so it can be
+//! stepped headless (tests, deterministic replays). A `GameSession` owns one
+//! playtest run: the game loop feeds input in and reads positions/events out.
+
+mod tuning;
+mod player;
+mod session;
+
+pub use tuning::*;
+pub use player::*;
+pub use session::*;
@@ -0,0 +1,352 @@
+//! Player vertical physics
+//!
+//! Gravity, jumping, landing on (possibly sloped) floors and bumping into ceilings.
+//! Heights are sampled with the same triangle split the renderer uses, so the feet
+//! sit exactly on the visible floor. Walking also drives a footstep cycle that
+//! reports the material underfoot.
+
+use crate::rasterizer::Vec3;
+use crate::world::{Direction, Level, SurfaceMaterial, SECTOR_SIZE};
+use super::GameTuning;
+
+/// What happened during one vertical step
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VerticalEvents {
+    /// Touched down this step
+    pub landed: bool,
+    /// Fall distance, when landing after a fall longer than `GameTuning::long_fall_height`
+    pub long_fall: Option<f32>,
+    /// Head hit a ceiling while moving up
+    pub hit_ceiling: bool,
+}
+
+/// A footstep, fired once per stride while walking on the ground
+#[derive(Debug, Clone, Copy)]
+pub struct Footstep {
+    /// Feet position when the foot came down
+    pub position: Vec3,
+    /// Material of the floor face underfoot
+    pub material: SurfaceMaterial,
+}
+
+/// Player body (feet position plus vertical motion state)
+#[derive(Debug, Clone)]
+pub struct Player {
+    /// Feet position in world space
+    pub position: Vec3,
+    /// Vertical speed (units/s, positive = up)
+    pub velocity_y: f32,
+    pub on_ground: bool,
+    /// Room whose floor is under the player (index into Level::rooms)
+    pub room: Option<usize>,
+    /// Highest feet position since leaving the ground (fall distance is measured from here)
+    fall_start_y: f32,
+    /// Remaining camera shake time (s)
+    shake_time: f32,
+    /// Distance walked since the last footstep
+    stride_distance: f32,
+}
+
+/// Highest floor at a world XZ that is at or below `max_y`: (room index, floor Y, ceiling Y)
+fn surfaces_at(level: &Level, x: f32, z: f32, max_y: f32) -> Option<(usize, f32, Option<f32>)> {
+    let (room, _, floor) = level.floor_at(Vec3::new(x, max_y, z))?;
+    Some((room, floor, level.rooms[room].ceiling_height_at(x, z)))
+}
+
+impl Player {
+    /// Player at `position` (feet), airborne until the first step finds the floor
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            velocity_y: 0.0,
+            on_ground: false,
+            room: None,
+            fall_start_y: position.y,
+            shake_time: 0.0,
+            stride_distance: 0.0,
+        }
+    }
+
+    /// Start a jump (only from the ground). Returns true if the player jumped.
+    pub fn jump(&mut self, tuning: &GameTuning) -> bool {
+        if !self.on_ground {
+            return false;
+        }
+        self.on_ground = false;
+        self.velocity_y = tuning.jump_velocity();
+        self.fall_start_y = self.position.y;
+        true
+    }
+
+    /// Advance vertical motion by `dt` seconds
+    pub fn step_vertical(&mut self, level: &Level, tuning: &GameTuning, dt: f32) -> VerticalEvents {
+        let mut events = VerticalEvents::default();
+        self.shake_time = (self.shake_time - dt).max(0.0);
+        let (x, z) = (self.position.x, self.position.z);
+
+        if self.on_ground {
+            // Follow the floor down slopes and small steps; anything deeper is a fall
+            match surfaces_at(level, x, z, self.position.y + tuning.step_height) {
+                Some((room, floor, _)) if floor >= self.position.y - tuning.step_height => {
+                    self.position.y = floor;
+                    self.room = Some(room);
+                    return events;
+                }
+                _ => {
+                    self.on_ground = false;
+                    self.velocity_y = 0.0;
+                    self.fall_start_y = self.position.y;
+                }
+            }
+        }
+
+        // Constant acceleration, integrated exactly (trapezoid on velocity) so the jump
+        // apex doesn't depend on the frame rate
+        let prev_y = self.position.y;
+        let v0 = self.velocity_y;
+        let v1 = (v0 - tuning.gravity * dt).max(-tuning.terminal_velocity);
+        self.position.y += (v0 + v1) * 0.5 * dt;
+        self.velocity_y = v1;
+        self.fall_start_y = self.fall_start_y.max(self.position.y);
+
+        // Surfaces of the sector we're moving through (floor at or below where we started)
+        let surfaces = surfaces_at(level, x, z, prev_y + 0.01);
+        if let Some((room, _, _)) = surfaces {
+            self.room = Some(room);
+        }
+
+        if let Some(ceiling) = surfaces.and_then(|(_, _, ceiling)| ceiling) {
+            let head = self.position.y + tuning.player_height;
+            if self.velocity_y > 0.0 && head > ceiling {
+                self.position.y = ceiling - tuning.player_height;
+                self.velocity_y = 0.0;
+                self.fall_start_y = self.position.y;
+                events.hit_ceiling = true;
+            }
+        }
+
+        if let Some((_, floor, _)) = surfaces {
+            if self.velocity_y <= 0.0 && self.position.y <= floor {
+                self.position.y = floor;
+                self.velocity_y = 0.0;
+                self.on_ground = true;
+                events.landed = true;
+
+                let fall = self.fall_start_y - floor;
+                if fall > tuning.long_fall_height {
+                    events.long_fall = Some(fall);
+                    self.shake_time = tuning.shake_duration;
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Would walking out of the current sector across its `dir` edge run into a wall?
+    /// Walls only count where they overlap the body above step height, at their current
+    /// heights, so a door blocks until it has opened far enough.
+    pub fn wall_blocks(&self, level: &Level, tuning: &GameTuning, dir: Direction) -> bool {
+        let Some(room) = self.room.and_then(|r| level.rooms.get(r)) else {
+            return false;
+        };
+        let Some((x, z)) = room.world_to_grid(self.position.x, self.position.z) else {
+            return false;
+        };
+        // Where along the edge the player crosses, from its left end seen from inside
+        let fx = (self.position.x - room.position.x) / SECTOR_SIZE - x as f32;
+        let fz = (self.position.z - room.position.z) / SECTOR_SIZE - z as f32;
+        let along = match dir {
+            Direction::North => fx,
+            Direction::East => fz,
+            Direction::South => 1.0 - fx,
+            Direction::West => 1.0 - fz,
+        };
+        room.edge_blocked(x, z, dir, along, self.position.y + tuning.step_height, self.position.y + tuning.player_height)
+    }
+
+    /// Advance the footstep cycle by a horizontal distance walked this frame.
+    /// Returns a footstep each time a stride completes on the ground.
+    pub fn advance_footsteps(&mut self, level: &Level, tuning: &GameTuning, distance: f32) -> Option<Footstep> {
+        if !self.on_ground {
+            // Next step comes as soon as we land and start walking again
+            self.stride_distance = tuning.stride_length;
+            return None;
+        }
+        if distance <= 0.0 {
+            return None;
+        }
+        self.stride_distance += distance;
+        if self.stride_distance < tuning.stride_length {
+            return None;
+        }
+        self.stride_distance %= tuning.stride_length.max(1.0);
+        let material = level.floor_material_at(self.position)?;
+        Some(Footstep { position: self.position, material })
+    }
+
+    /// Camera offset while shaking after a long fall (zero otherwise)
+    pub fn camera_shake_offset(&self, tuning: &GameTuning, time: f64) -> Vec3 {
+        if self.shake_time <= 0.0 || tuning.shake_duration <= 0.0 {
+            return Vec3::ZERO;
+        }
+        let amplitude = tuning.shake_amplitude * (self.shake_time / tuning.shake_duration);
+        let t = time as f32;
+        Vec3::new((t * 53.0).sin() * amplitude, (t * 71.0).cos() * amplitude, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{Door, DoorState, HorizontalFace, Room, TextureRef, VerticalFace, SECTOR_SIZE};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// 3x3 room with a flat floor at 0 and the given ceiling height
+    fn flat_level(ceiling: f32) -> Level {
+        let mut room = Room::new(0, Vec3::ZERO, 3, 3);
+        for x in 0..3 {
+            for z in 0..3 {
+                room.set_floor(x, z, 0.0, TextureRef::none());
+                room.set_ceiling(x, z, ceiling, TextureRef::none());
+            }
+        }
+        room.recalculate_bounds();
+        let mut level = Level::new();
+        level.add_room(room);
+        level
+    }
+
+    fn center() -> Vec3 {
+        Vec3::new(SECTOR_SIZE * 1.5, 0.0, SECTOR_SIZE * 1.5)
+    }
+
+    /// Step until landed (or give up), returning the last events and the highest feet Y
+    fn run_until_landed(player: &mut Player, level: &Level, tuning: &GameTuning) -> (VerticalEvents, f32) {
+        let mut apex = player.position.y;
+        for _ in 0..600 {
+            let events = player.step_vertical(level, tuning, DT);
+            apex = apex.max(player.position.y);
+            if events.landed {
+                return (events, apex);
+            }
+        }
+        panic!("player never landed");
+    }
+
+    #[test]
+    fn jump_apex_is_one_click() {
+        let level = flat_level(SECTOR_SIZE * 2.0);
+        let tuning = GameTuning::default();
+        let mut player = Player::new(center());
+        player.step_vertical(&level, &tuning, DT);
+        assert!(player.on_ground);
+
+        assert!(player.jump(&tuning));
+        assert!(!player.jump(&tuning), "no double jump");
+        let (events, apex) = run_until_landed(&mut player, &level, &tuning);
+        assert!((apex - tuning.jump_height).abs() < 2.0, "apex {}", apex);
+        assert_eq!(events.long_fall, None);
+        assert_eq!(player.position.y, 0.0);
+    }
+
+    #[test]
+    fn lands_on_sloped_floor() {
+        let mut level = flat_level(SECTOR_SIZE * 4.0);
+        // Middle sector ramps up along +X: 0 on the west edge, 256 on the east edge
+        let ramp = HorizontalFace::sloped([0.0, 256.0, 256.0, 0.0], TextureRef::none());
+        level.rooms[0].ensure_sector(1, 1).floor = Some(ramp);
+
+        let tuning = GameTuning::default();
+        let mut player = Player::new(Vec3::new(SECTOR_SIZE * 1.75, 1000.0, SECTOR_SIZE * 1.5));
+        let (events, _) = run_until_landed(&mut player, &level, &tuning);
+        assert!(events.landed);
+        assert!((player.position.y - 192.0).abs() < 0.01, "landed at {}", player.position.y);
+
+        // Grounded players follow the slope
+        player.position.x = SECTOR_SIZE * 1.25;
+        player.step_vertical(&level, &tuning, DT);
+        assert!(player.on_ground);
+        assert!((player.position.y - 64.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn ceiling_stops_upward_motion() {
+        let tuning = GameTuning::default();
+        let ceiling = tuning.player_height + tuning.jump_height * 0.5;
+        let level = flat_level(ceiling);
+        let mut player = Player::new(center());
+        player.step_vertical(&level, &tuning, DT);
+        player.jump(&tuning);
+
+        let mut hit = false;
+        for _ in 0..120 {
+            hit |= player.step_vertical(&level, &tuning, DT).hit_ceiling;
+            assert!(player.position.y + tuning.player_height <= ceiling + 0.01);
+        }
+        assert!(hit);
+        assert!(player.on_ground);
+    }
+
+    #[test]
+    fn long_falls_are_flagged() {
+        let level = flat_level(SECTOR_SIZE * 8.0);
+        let tuning = GameTuning::default();
+        let drop = tuning.long_fall_height + 500.0;
+        let mut player = Player::new(center() + Vec3::new(0.0, drop, 0.0));
+        let (events, _) = run_until_landed(&mut player, &level, &tuning);
+        let fall = events.long_fall.expect("long fall");
+        assert!((fall - drop).abs() < 0.01);
+        assert!(player.velocity_y.abs() < f32::EPSILON);
+        assert!(player.camera_shake_offset(&tuning, 0.1).len() > 0.0);
+    }
+
+    #[test]
+    fn doors_block_until_open_enough() {
+        let mut level = flat_level(SECTOR_SIZE * 2.0);
+        let room = &mut level.rooms[0];
+        room.push_wall(1, 1, Direction::East, VerticalFace::new(0.0, SECTOR_SIZE, TextureRef::none()));
+        let door = Door::from_wall(1, 1, Direction::East, 0, &room.get_sector(1, 1).unwrap().walls_east[0]);
+        room.doors.push(door);
+
+        let tuning = GameTuning::default();
+        let mut player = Player::new(center());
+        player.step_vertical(&level, &tuning, DT);
+        assert!(player.wall_blocks(&level, &tuning, Direction::East));
+        assert!(!player.wall_blocks(&level, &tuning, Direction::North));
+
+        // Half open still blocks; once it's sunk below step height it doesn't
+        let mut doors = DoorState::new();
+        doors.toggle(0, 0);
+        doors.update(&mut level, 0.5);
+        assert!(player.wall_blocks(&level, &tuning, Direction::East));
+        doors.update(&mut level, 0.45);
+        assert!(!player.wall_blocks(&level, &tuning, Direction::East));
+    }
+
+    #[test]
+    fn footsteps_report_material_underfoot() {
+        let mut level = flat_level(SECTOR_SIZE * 2.0);
+        let mut grate = HorizontalFace::sloped([0.0, 256.0, 256.0, 0.0], TextureRef::none());
+        grate.material = SurfaceMaterial::Metal;
+        level.rooms[0].ensure_sector(1, 1).floor = Some(grate);
+
+        let tuning = GameTuning::default();
+        let mut player = Player::new(Vec3::new(SECTOR_SIZE * 0.5, 0.0, SECTOR_SIZE * 1.5));
+        player.step_vertical(&level, &tuning, DT);
+
+        // One step per stride
+        assert!(player.advance_footsteps(&level, &tuning, tuning.stride_length * 0.5).is_none());
+        let step = player.advance_footsteps(&level, &tuning, tuning.stride_length * 0.5).expect("first step");
+        assert_eq!(step.material, SurfaceMaterial::Stone);
+
+        // Walk onto the metal ramp: the step reports the face the floor height came from
+        player.position.x = SECTOR_SIZE * 1.5;
+        player.step_vertical(&level, &tuning, DT);
+        assert!((player.position.y - 128.0).abs() < 0.01);
+        let step = player.advance_footsteps(&level, &tuning, tuning.stride_length).expect("second step");
+        assert_eq!(step.material, SurfaceMaterial::Metal);
+        assert_eq!(level.floor_material_at(Vec3::new(SECTOR_SIZE * 1.5, 0.0, SECTOR_SIZE * 1.5)), None);
+    }
+}
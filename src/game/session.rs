@@ -0,0 +1,287 @@
+//! Play session
+//!
+//! Everything a playtest changes lives in `GameSession`: its own copy of the level
+//! (doors animate in it), the player, door and pickup runtime state and the clock.
+//! Play builds a fresh session and leaving play drops it, so nothing carries over
+//! between runs, and the same level, spawn and inputs always give the same run.
+
+use crate::rasterizer::{Camera, DebugView, RasterSettings, Vec3};
+use crate::world::{ambient_gains, AmbientGain, Direction, DoorState, Level, PickupEvent, PickupState, AMBIENT_MAX_HOPS, SECTOR_SIZE};
+use super::{Footstep, GameTuning, Player, VerticalEvents};
+
+/// Eye height as a fraction of the player height
+const EYE_HEIGHT: f32 = 0.9;
+
+/// Input for one step. Analog axes are -1..=1; look deltas are radians for this step.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GameInput {
+    /// Forward (+) / back (-)
+    pub forward: f32,
+    /// Right (+) / left (-)
+    pub strafe: f32,
+    /// Yaw change (positive turns right)
+    pub yaw: f32,
+    /// Pitch change (positive looks down)
+    pub pitch: f32,
+    pub jump: bool,
+    /// Use the nearest door
+    pub interact: bool,
+}
+
+/// What happened during one step
+#[derive(Debug, Clone, Default)]
+pub struct GameEvents {
+    pub vertical: VerticalEvents,
+    pub footstep: Option<Footstep>,
+    pub pickups: Vec<PickupEvent>,
+    /// A door was used: true if it's now opening
+    pub door: Option<bool>,
+}
+
+/// One playtest run
+pub struct GameSession {
+    /// The session's own copy of the level
+    pub level: Level,
+    pub player: Player,
+    /// Where the player started (Restart Level goes back here)
+    pub spawn: Vec3,
+    /// View direction at the start (radians)
+    pub spawn_yaw: f32,
+    /// View direction (radians)
+    pub yaw: f32,
+    pub pitch: f32,
+    pub tuning: GameTuning,
+    pub raster_settings: RasterSettings,
+    pub doors: DoorState,
+    pub pickups: PickupState,
+    /// Seconds since the session started
+    pub time: f64,
+    /// Steps taken
+    pub frames: u64,
+}
+
+impl GameSession {
+    /// Fresh session with the player's feet at `spawn`. Render settings stored with
+    /// the level take precedence over `raster_settings`.
+    pub fn new(level: &Level, spawn: Vec3, mut raster_settings: RasterSettings, tuning: GameTuning) -> Self {
+        if let Some(stored) = &level.render_settings {
+            stored.apply(&mut raster_settings);
+        }
+        // Debug views and dynamic lights are for the editor viewport
+        raster_settings.debug_view = DebugView::Off;
+        raster_settings.dynamic_lights = false;
+        Self {
+            level: level.clone(),
+            player: Player::new(spawn),
+            spawn,
+            spawn_yaw: 0.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            tuning,
+            raster_settings,
+            doors: DoorState::new(),
+            pickups: PickupState::new(),
+            time: 0.0,
+            frames: 0,
+        }
+    }
+
+    /// Start looking along `yaw` (radians) instead of +Z
+    pub fn facing(mut self, yaw: f32) -> Self {
+        self.spawn_yaw = yaw;
+        self.yaw = yaw;
+        self
+    }
+
+    /// Advance the session by `dt` seconds
+    pub fn step(&mut self, input: &GameInput, dt: f32) -> GameEvents {
+        let mut events = GameEvents::default();
+        self.time += dt as f64;
+        self.frames += 1;
+
+        self.yaw += input.yaw;
+        self.pitch = (self.pitch + input.pitch).clamp(-1.4, 1.4);
+
+        // Walk on the XZ plane relative to the view direction
+        let (sin, cos) = self.yaw.sin_cos();
+        let forward = Vec3::new(sin, 0.0, cos);
+        let right = Vec3::new(cos, 0.0, -sin);
+        let mut wish = forward * input.forward.clamp(-1.0, 1.0) + right * input.strafe.clamp(-1.0, 1.0);
+        if wish.len() > 1.0 {
+            wish = wish.normalize();
+        }
+        let start = self.player.position;
+        self.walk(wish * (self.tuning.walk_speed * dt));
+        let walked = (self.player.position.x - start.x).hypot(self.player.position.z - start.z);
+
+        if input.jump {
+            self.player.jump(&self.tuning);
+        }
+        if input.interact {
+            if let Some(room) = self.player.room {
+                if let Some(door) = self.level.rooms[room].door_near(self.player.position) {
+                    events.door = Some(self.doors.toggle(room, door));
+                }
+            }
+        }
+
+        self.doors.update(&mut self.level, dt);
+        events.vertical = self.player.step_vertical(&self.level, &self.tuning, dt);
+        events.footstep = self.player.advance_footsteps(&self.level, &self.tuning, walked);
+        events.pickups = self.pickups.update(&self.level, self.player.position, self.tuning.player_height, self.time);
+        events
+    }
+
+    /// Move horizontally one axis at a time, so blocked moves slide along walls
+    fn walk(&mut self, delta: Vec3) {
+        let x = self.player.position + Vec3::new(delta.x, 0.0, 0.0);
+        if self.can_move_to(x) {
+            self.player.position = x;
+        }
+        let z = self.player.position + Vec3::new(0.0, 0.0, delta.z);
+        if self.can_move_to(z) {
+            self.player.position = z;
+        }
+    }
+
+    /// Can the player step to `target`? Leaving the current sector must not cross a
+    /// wall, and there must be a floor no higher than a step up.
+    fn can_move_to(&self, target: Vec3) -> bool {
+        let pos = self.player.position;
+        if let Some(room) = self.player.room.and_then(|r| self.level.rooms.get(r)) {
+            let from = room.world_to_grid(pos.x, pos.z);
+            let to = room.world_to_grid(target.x, target.z);
+            if from.is_some() && from != to {
+                let dir = if target.x > pos.x {
+                    Direction::East
+                } else if target.x < pos.x {
+                    Direction::West
+                } else if target.z > pos.z {
+                    Direction::South
+                } else {
+                    Direction::North
+                };
+                if self.player.wall_blocks(&self.level, &self.tuning, dir) {
+                    return false;
+                }
+            }
+        }
+        self.level.floor_at(Vec3::new(target.x, pos.y + self.tuning.step_height, target.z)).is_some()
+    }
+
+    /// First-person camera at the player's eyes
+    pub fn camera(&self) -> Camera {
+        let mut camera = Camera::new();
+        camera.position = self.player.position
+            + Vec3::new(0.0, self.tuning.player_height * EYE_HEIGHT, 0.0)
+            + self.player.camera_shake_offset(&self.tuning, self.time);
+        camera.rotation_x = self.pitch;
+        camera.rotation_y = self.yaw;
+        camera.fov_degrees = self.level.game_fov;
+        camera.update_basis();
+        camera
+    }
+
+    /// Ambient sounds the player hears now (at the camera), with their gains
+    pub fn ambient_gains(&self) -> Vec<AmbientGain> {
+        ambient_gains(&self.level, self.player.room, self.camera().position, AMBIENT_MAX_HOPS)
+    }
+}
+
+/// Where to start a playtest near `near`: on the floor below it if there is one,
+/// otherwise the center of the first floor sector in the level
+pub fn find_spawn(level: &Level, near: Vec3) -> Option<Vec3> {
+    if let Some((_, _, floor)) = level.floor_at(near) {
+        return Some(Vec3::new(near.x, floor, near.z));
+    }
+    level.rooms.iter().find_map(|room| {
+        room.iter_sectors().find_map(|(x, z, sector)| {
+            let floor = sector.floor.as_ref()?;
+            let wx = room.position.x + (x as f32 + 0.5) * SECTOR_SIZE;
+            let wz = room.position.z + (z as f32 + 0.5) * SECTOR_SIZE;
+            Some(Vec3::new(wx, floor.height_at(0.5, 0.5), wz))
+        })
+    })
+}
+
+/// Where to start a playtest on a chosen sector: the center of its floor. None if
+/// the sector has no floor to stand on.
+pub fn sector_spawn(level: &Level, room: usize, x: usize, z: usize) -> Option<Vec3> {
+    let room = level.rooms.get(room)?;
+    let wx = room.position.x + (x as f32 + 0.5) * SECTOR_SIZE;
+    let wz = room.position.z + (z as f32 + 0.5) * SECTOR_SIZE;
+    // The center must be in this sector (not past the room's edge)
+    if room.world_to_grid(wx, wz) != Some((x, z)) {
+        return None;
+    }
+    Some(Vec3::new(wx, room.floor_height_at(wx, wz)?, wz))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_two_room_level;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// Face east and walk, turn, jump and strafe for a few seconds
+    fn scripted_input(frame: u32) -> GameInput {
+        match frame {
+            0 => GameInput { yaw: std::f32::consts::FRAC_PI_2, ..Default::default() },
+            1..=59 => GameInput { forward: 1.0, ..Default::default() },
+            60..=89 => GameInput { forward: 1.0, yaw: 0.02, jump: frame == 60, ..Default::default() },
+            90..=179 => GameInput { forward: 1.0, strafe: -0.5, ..Default::default() },
+            180..=239 => GameInput { forward: -1.0, pitch: 0.01, interact: frame == 180, ..Default::default() },
+            _ => GameInput::default(),
+        }
+    }
+
+    #[test]
+    fn same_inputs_give_same_run() {
+        let level = create_two_room_level();
+        let spawn = find_spawn(&level, Vec3::new(512.0, 100.0, 1024.0)).unwrap();
+        let run = || {
+            let mut session = GameSession::new(&level, spawn, RasterSettings::default(), GameTuning::default());
+            let mut positions = Vec::new();
+            for frame in 0..300 {
+                session.step(&scripted_input(frame), DT);
+                positions.push(session.player.position);
+            }
+            (session, positions)
+        };
+
+        let (a, path_a) = run();
+        let (b, path_b) = run();
+        for (pa, pb) in path_a.iter().zip(&path_b) {
+            assert_eq!((pa.x, pa.y, pa.z), (pb.x, pb.y, pb.z));
+        }
+        assert_eq!(a.frames, 300);
+        assert_eq!(b.player.room, a.player.room);
+
+        // Walked through the portal into the second room, and the walls kept the
+        // player inside the level
+        let end = a.player.position;
+        assert_eq!(a.player.room, Some(1));
+        assert!(end.x > 0.0 && end.x < 4.0 * SECTOR_SIZE && end.z > 0.0 && end.z < 2.0 * SECTOR_SIZE);
+        assert!(a.player.on_ground);
+    }
+
+    #[test]
+    fn play_from_a_sector_starts_on_its_floor() {
+        let level = create_two_room_level();
+        let spawn = sector_spawn(&level, 1, 1, 0).unwrap();
+        let room = &level.rooms[1];
+        assert_eq!(room.world_to_grid(spawn.x, spawn.z), Some((1, 0)));
+        assert!(sector_spawn(&level, 1, room.width, 0).is_none());
+        assert!(sector_spawn(&level, 5, 0, 0).is_none());
+
+        let mut session = GameSession::new(&level, spawn, RasterSettings::default(), GameTuning::default())
+            .facing(1.0);
+        session.step(&GameInput::default(), DT);
+        assert_eq!(session.yaw, 1.0);
+        assert_eq!(session.spawn_yaw, 1.0);
+        assert_eq!(session.player.room, Some(1));
+        assert!(session.player.on_ground);
+        assert_eq!(session.player.position.y, spawn.y);
+    }
+}
@@ -0,0 +1,59 @@
+//! Game tuning constants
+//!
+//! Everything that affects how the player moves lives in `GameTuning`, so it can be
+//! adjusted in one place (and later exposed in the editor or saved per level).
+
+use crate::world::{CLICK_HEIGHT, SECTOR_SIZE};
+
+/// Player movement tuning (world units, seconds)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameTuning {
+    /// Downward acceleration (units/s²)
+    pub gravity: f32,
+    /// Apex of a standing jump above the take-off point. One click (TR style), so the
+    /// player can just get up onto a one-click step.
+    pub jump_height: f32,
+    /// Maximum falling speed (units/s)
+    pub terminal_velocity: f32,
+    /// Player height, feet to top of head
+    pub player_height: f32,
+    /// Floors this far below the feet are followed while grounded (slopes, small steps)
+    /// instead of starting a fall
+    pub step_height: f32,
+    /// Falls longer than this (apex to landing) raise the long-fall flag
+    pub long_fall_height: f32,
+    /// Camera shake after a long fall: duration (s) and peak offset (units)
+    pub shake_duration: f32,
+    pub shake_amplitude: f32,
+    /// Horizontal distance walked per footstep
+    pub stride_length: f32,
+    /// Walking speed (units/s)
+    pub walk_speed: f32,
+    /// Keyboard turning speed (radians/s)
+    pub turn_speed: f32,
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        Self {
+            gravity: 9000.0,
+            jump_height: CLICK_HEIGHT,
+            terminal_velocity: 8000.0,
+            player_height: 762.0,
+            step_height: CLICK_HEIGHT * 0.5,
+            long_fall_height: SECTOR_SIZE * 2.0,
+            shake_duration: 0.4,
+            shake_amplitude: 24.0,
+            stride_length: CLICK_HEIGHT * 1.5,
+            walk_speed: SECTOR_SIZE * 2.0,
+            turn_speed: 2.5,
+        }
+    }
+}
+
+impl GameTuning {
+    /// Upward speed that reaches exactly `jump_height` under `gravity`
+    pub fn jump_velocity(&self) -> f32 {
+        (2.0 * self.gravity * self.jump_height).sqrt()
+    }
+}
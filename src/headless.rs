@@ -0,0 +1,161 @@
+//! Headless rendering
+//!
+//! Load levels and render frames into a `Framebuffer` without macroquad.
+//! Used by external tools (thumbnail generators, screenshot diffs).
+//!
+//! ```ignore
+//! use bonnie_engine::headless::{render_level_frame, overview_camera, TextureProvider};
+//! use bonnie_engine::rasterizer::RasterSettings;
+//!
+//! let level = bonnie_engine::world::load_level("assets/levels/level_001.ron")?;
+//! let textures = TextureProvider::from_directory("assets/textures");
+//! let camera = overview_camera(&level);
+//! let fb = render_level_frame(&level, &textures, &camera, 320, 240, &RasterSettings::default());
+//! ```
+
+use crate::rasterizer::{render_mesh_with_scratch, Camera, Color, Framebuffer, RasterSettings, RenderScratch, Texture, Vec3};
+use crate::world::{Level, PackAliases, TextureIndex, TextureRef, SECTOR_SIZE};
+
+/// Background color used for headless frames (matches the editor viewport)
+pub const CLEAR_COLOR: Color = Color { r: 30, g: 30, b: 40, a: 255 };
+
+/// Resolves level texture references (pack + name) to textures
+pub struct TextureProvider {
+    textures: Vec<Texture>,
+    index: TextureIndex,
+}
+
+impl TextureProvider {
+    /// Create an empty provider (all faces render untextured)
+    pub fn new() -> Self {
+        Self {
+            textures: Vec::new(),
+            index: TextureIndex::new(),
+        }
+    }
+
+    /// Add a named pack of textures
+    pub fn add_pack(&mut self, pack_name: &str, textures: Vec<Texture>) {
+        for tex in textures {
+            self.index.push(pack_name, &tex.name);
+            self.textures.push(tex);
+        }
+    }
+
+    /// Load every subdirectory of `dir` as a texture pack (native only).
+    /// Mirrors the editor's pack discovery, including one level of nested folders.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_directory<P: AsRef<std::path::Path>>(dir: P) -> Self {
+        let mut provider = Self::new();
+
+        let mut pack_dirs: Vec<_> = match std::fs::read_dir(dir.as_ref()) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect(),
+            Err(_) => return provider,
+        };
+        pack_dirs.sort();
+
+        for pack_dir in pack_dirs {
+            let pack_name = match pack_dir.file_name() {
+                Some(n) => n.to_string_lossy().to_string(),
+                None => continue,
+            };
+
+            let mut textures = Texture::load_directory(&pack_dir);
+            if textures.is_empty() {
+                if let Ok(entries) = std::fs::read_dir(&pack_dir) {
+                    for entry in entries.filter_map(|e| e.ok()) {
+                        if entry.path().is_dir() {
+                            textures.extend(Texture::load_directory(entry.path()));
+                        }
+                    }
+                }
+            }
+
+            provider.add_pack(&pack_name, textures.into_iter().map(Texture::with_mips).collect());
+        }
+
+        provider
+    }
+
+    /// Look up the texture index for a reference.
+    /// Invalid (empty) references fall back to the first texture, like the editor.
+    pub fn resolve(&self, tex_ref: &TextureRef) -> Option<usize> {
+        self.index.resolve(tex_ref, &PackAliases::new())
+    }
+
+    /// Like `resolve`, with a level's pack substitutes applied
+    pub fn resolve_in(&self, level: &Level, tex_ref: &TextureRef) -> Option<usize> {
+        self.index.resolve(tex_ref, &level.pack_aliases)
+    }
+
+    /// All loaded textures (indexed by `resolve`)
+    pub fn textures(&self) -> &[Texture] {
+        &self.textures
+    }
+}
+
+impl Default for TextureProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render every room of a level into a new framebuffer
+pub fn render_level_frame(
+    level: &Level,
+    textures: &TextureProvider,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    settings: &RasterSettings,
+) -> Framebuffer {
+    let mut fb = Framebuffer::new(width, height);
+    fb.clear(CLEAR_COLOR);
+
+    let mut room_settings = settings.clone();
+    let mut scratch = RenderScratch::new();
+    for (i, room) in level.rooms.iter().enumerate() {
+        room_settings.light_dir = level.room_light_dir(i);
+        let (vertices, faces) = room.to_render_data_for(settings, |t| textures.resolve_in(level, t));
+        render_mesh_with_scratch(&mut fb, &vertices, &faces, textures.textures(), camera, &room_settings, &mut scratch);
+    }
+
+    fb
+}
+
+/// Build a camera looking down at the whole level from above one corner,
+/// similar to the editor's default view
+pub fn overview_camera(level: &Level) -> Camera {
+    let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+
+    for room in level.rooms.iter().filter(|room| !room.is_empty()) {
+        let bounds = room.world_bounds();
+        min = Vec3::new(min.x.min(bounds.min.x), min.y.min(bounds.min.y), min.z.min(bounds.min.z));
+        max = Vec3::new(max.x.max(bounds.max.x), max.y.max(bounds.max.y), max.z.max(bounds.max.z));
+    }
+
+    // No geometry anywhere: look at the first sector's spot
+    if min.x > max.x {
+        min = Vec3::ZERO;
+        max = Vec3::new(SECTOR_SIZE, 0.0, SECTOR_SIZE);
+    }
+
+    let center = Vec3::new((min.x + max.x) * 0.5, (min.y + max.y) * 0.5, (min.z + max.z) * 0.5);
+    let extent = (max - min).len().max(SECTOR_SIZE);
+
+    // Back off along the diagonal, above the level
+    let eye = center + Vec3::new(extent, extent, extent).scale(0.6);
+    let dir = (center - eye).normalize();
+
+    let mut camera = Camera::new();
+    camera.position = eye;
+    camera.rotation_x = (-dir.y).asin();
+    camera.rotation_y = dir.x.atan2(dir.z);
+    camera.update_basis();
+    camera
+}
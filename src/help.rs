@@ -0,0 +1,352 @@
+//! Help overlay (F1)
+//!
+//! Every tool and shortcut in one searchable list, grouped into sections. The list
+//! is a static registry kept next to the toolbars it describes. The overlay is drawn
+//! above everything; while it's open the mouse is blocked behind it and the filter
+//! field keeps keyboard focus, so tool shortcuts stay quiet.
+
+use macroquad::prelude::*;
+use crate::app::Tool;
+use crate::ui::{draw_icon_centered, draw_scrollable_list, icon, text_input, Rect, TextInputEvent, UiContext};
+
+/// One line of the help list
+pub struct HelpEntry {
+    /// Toolbar icon (None for keyboard-only actions)
+    pub icon: Option<char>,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Shortcut as shown to the user ("" when there is none)
+    pub shortcut: &'static str,
+}
+
+/// A toolbar button
+const fn tool(icon: char, name: &'static str, description: &'static str, shortcut: &'static str) -> HelpEntry {
+    HelpEntry { icon: Some(icon), name, description, shortcut }
+}
+
+/// A keyboard-only action
+const fn key(name: &'static str, description: &'static str, shortcut: &'static str) -> HelpEntry {
+    HelpEntry { icon: None, name, description, shortcut }
+}
+
+pub struct HelpSection {
+    pub name: &'static str,
+    pub entries: &'static [HelpEntry],
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const FILE: &[HelpEntry] = &[
+    tool(icon::FILE_PLUS, "New", "Start a new level from a template", "Ctrl+N"),
+    tool(icon::FOLDER_OPEN, "Open", "Open a level file", "Ctrl+O"),
+    tool(icon::IMPORT, "Import OBJ Blockout", "Add an OBJ model as a new room", ""),
+    tool(icon::SAVE, "Save", "Save the level", "Ctrl+S"),
+    tool(icon::SAVE_AS, "Save As", "Save the level to a new file", "Ctrl+Shift+S"),
+    tool(icon::FILE_DIFF, "Compare with File", "Show what changed against another level file", ""),
+    tool(icon::GLOBE, "Export Web Bundle", "A folder (and zip) that plays just this level in a browser", ""),
+    tool(icon::BOOK_OPEN, "Browse", "Open one of the example levels", ""),
+    tool(icon::BRUSH_CLEANING, "Clean Level", "Check the level's texture references", ""),
+];
+
+#[cfg(target_arch = "wasm32")]
+const FILE: &[HelpEntry] = &[
+    tool(icon::FILE_PLUS, "New", "Start a new level from a template", "Ctrl+N"),
+    tool(icon::FOLDER_OPEN, "Upload", "Open a level or texture pack (.ron, .zip)", "Ctrl+O"),
+    tool(icon::SAVE, "Download", "Download the level", "Ctrl+S"),
+    tool(icon::BOOK_OPEN, "Browse", "Open one of the example levels", ""),
+    tool(icon::BRUSH_CLEANING, "Clean Level", "Check the level's texture references", ""),
+];
+
+const EDIT: &[HelpEntry] = &[
+    tool(icon::UNDO, "Undo", "Undo the last change", "Ctrl+Z"),
+    tool(icon::REDO, "Redo", "Redo the last undone change", "Ctrl+Shift+Z"),
+    tool(icon::HISTORY, "Undo History", "List of changes; click one to go back to it", ""),
+    tool(icon::CLIPBOARD_COPY, "Copy as Text", "Copy the selected sectors to the clipboard", "Ctrl+Shift+C"),
+    tool(icon::CLIPBOARD_PASTE, "Paste from Text", "Paste sectors at the grid cursor", "Ctrl+Shift+V"),
+    tool(icon::REPLACE, "Replace Texture", "Swap one texture for another across the level", ""),
+    tool(icon::SEARCH, "Find Faces", "List faces by texture, flags, floor height, triggers or custom UVs", "Up / Down steps"),
+    tool(icon::IMAGE_OFF, "Highlight Missing Textures", "Show faces whose texture can't be found", ""),
+    tool(icon::CHART_NO_AXES_COLUMN_INCREASING, "Stairs Generator", "Build a flight of stairs between two heights", ""),
+    tool(icon::PLUG, "Suggest Portals", "Connect rooms that touch", ""),
+    tool(icon::CONTRAST, "Bake AO", "Darken vertices in corners", ""),
+    key("Delete Face", "Delete the selected face (3D view)", "Delete"),
+    key("Cancel Drag", "Put back what the drag or paint stroke changed so far", "Esc"),
+    key("Nudge Wall Corner", "Raise or lower the selected wall corner", "PageUp / PageDown"),
+    key("Type a Value", "Double-click a drag value to type it (3c = 3 clicks, 2s = 2 sectors)", "Double-click, Enter / Esc"),
+];
+
+const TOOLS: &[HelpEntry] = &[
+    tool(icon::MOVE, "Select", "Pick and drag sectors, faces and vertices", ""),
+    tool(icon::SQUARE, "Floor", "Draw floor sectors", ""),
+    tool(icon::BOX, "Wall", "Click an edge, or two grid corners for a run", ""),
+    tool(icon::LAYERS, "Ceiling", "Draw ceiling sectors", ""),
+    tool(icon::DOOR_CLOSED, "Portal", "Connect two rooms", ""),
+    tool(icon::HAND_GRAB, "Move Room", "Drag rooms in the 2D grid", ""),
+    tool(icon::GEM, "Pickup", "Place pickups", ""),
+    tool(icon::VOLUME_2, "Ambient Sound", "Place looping sounds from assets/sounds (RMB removes)", ""),
+    tool(icon::LIGHTBULB, "Point Light", "Place lights that brighten nearby faces (RMB removes)", ""),
+    tool(icon::RULER, "Measure", "Distances between points", "Esc clears"),
+    tool(icon::FOOTPRINTS, "Paint Flags", "LMB not walkable, RMB walkable, Alt+drag walls solid", ""),
+    tool(icon::PLAY, "Play", "Playtest the level", ""),
+    key("Play From Here", "Playtest from the sector under the mouse (or the selected one)", "Ctrl+P"),
+];
+
+const VIEW: &[HelpEntry] = &[
+    tool(icon::LINK, "Link Vertices", "Move coincident vertices together", "L"),
+    tool(icon::CROSSHAIR, "Sync Views", "Keep the 2D and 3D views on the same spot", ""),
+    tool(icon::AXIS_3D, "Isometric Grid", "2D grid panel: view the room isometric (click selects, no editing)", ""),
+    tool(icon::TAG, "Show Labels", "Room and portal labels in the views", ""),
+    tool(icon::EYE, "Viewport Layers", "Show or hide parts of the level; H isolates geometry", "H"),
+    tool(icon::MOVE_VERTICAL, "Height Snap", "Cycle the vertex drag snap (Ctrl while dragging: free)", "G"),
+    tool(icon::SWATCH_BOOK, "Theme", "Cycle the editor color theme", ""),
+    tool(icon::TYPE, "Text Labels", "Show toolbar buttons as text instead of icons", ""),
+    tool(icon::CIRCLE_CHEVRON_LEFT, "Previous Room", "Select the previous room", ""),
+    tool(icon::CIRCLE_CHEVRON_RIGHT, "Next Room", "Select the next room", ""),
+    tool(icon::PLUS, "Add Room", "Add an empty room", ""),
+    key("Fly Camera", "Move the 3D camera (Shift faster, Ctrl slower)", "W A S D, Q / E"),
+    key("Pan View", "Pan the grid or 3D view; drags near a view's edge scroll it", "MMB / Space+drag"),
+    key("Snap Look", "Hold while looking around: 45° steps", "Ctrl+RMB drag"),
+    key("Ortho Views", "Orthographic Top / Front / Right (or click the axis gizmo); 5 back to perspective", "Numpad 7 / 1 / 3, 5"),
+    key("Camera Bookmarks", "Ctrl stores the 3D view, the key alone flies back", "1-5, Ctrl+1-5"),
+    key("Profiler", "Frame timing overlay", "F11"),
+    key("Help", "This list", "F1"),
+];
+
+const PS1_EFFECTS: &[HelpEntry] = &[
+    tool(icon::WAVES, "Affine Textures", "PS1 texture warp", ""),
+    tool(icon::MAGNET, "Vertex Snap", "PS1 vertex jitter", ""),
+    tool(icon::SUN, "Gouraud Shading", "Smooth vertex lighting (off: unlit)", ""),
+    tool(icon::MONITOR, "Low Resolution", "Render at 320x240", ""),
+    tool(icon::BLEND, "Dithering", "PS1 color banding", ""),
+    tool(icon::PALETTE, "15-bit Color", "PS1 frame buffer color depth", ""),
+    tool(icon::TABLE_CELLS_MERGE, "Merge Coplanar Faces", "Draw flat areas with fewer triangles", ""),
+    tool(icon::SUN, "Smooth Normals", "No shading seams between sloped tiles", ""),
+    tool(icon::IMAGE_DOWN, "Mipmapping", "Less shimmer on distant surfaces", ""),
+    tool(icon::LAMP, "Dynamic Lights", "Relight every frame while moving lights (editor only)", ""),
+    tool(icon::BUG, "Debug View", "Cycle overdraw, depth and triangle density views", ""),
+    tool(icon::FILE_COG, "Store Render Settings", "Save these settings with the level", ""),
+];
+
+const TRACKER: &[HelpEntry] = &[
+    tool(icon::GRID, "Pattern Editor", "Edit the notes of a pattern", ""),
+    tool(icon::LIST_MUSIC, "Arrangement", "Order of patterns in the song (+/- add and remove)", ""),
+    tool(icon::PIANO, "Instruments", "Pick and tweak instruments", ""),
+    tool(icon::SKIP_BACK, "Stop & Rewind", "Stop playback", "Esc"),
+    tool(icon::PLAY, "Play from Start", "Play the song from the top", ""),
+    tool(icon::SKIP_FORWARD, "Play from Cursor", "Play or pause from the cursor row", "Space"),
+    tool(icon::CPU, "SPU Quantization", "PS1 15-bit sample quantization", ""),
+    tool(icon::HEADPHONES, "Background Playback", "Keep playing in other tabs", ""),
+    tool(icon::LOCATE_FIXED, "Follow Playback", "Scroll with the playing row", ""),
+    tool(icon::TIMER, "Metronome", "Click on every beat", ""),
+    tool(icon::FOLDER_OPEN, "Load Soundfont", "Play the song with another SF2 (remembered in the song)", ""),
+    key("Edit Mode", "Typing notes writes them into the pattern", "`"),
+    key("Move Cursor", "Rows and columns", "Arrows, PageUp / PageDown, Home / End"),
+    key("Next Channel", "Move to the next channel (Shift: previous)", "Tab"),
+    key("Octave", "Octave for note keys", "+ / -"),
+    key("Instrument", "Previous or next instrument", "[ / ]"),
+    key("Edit Step", "Rows the cursor moves after a note", "F9 / F10"),
+    key("Loop Region", "Loop start and end at the current pattern", "F5 / F6"),
+    key("Pattern Loop", "Loop the current pattern", "F7"),
+    key("Note Off", "Write a note off", ". or '"),
+    key("Clear", "Clear the field under the cursor", "Delete"),
+    key("Undo / Redo", "Undo and redo pattern edits", "Ctrl+Z / Ctrl+Y"),
+    key("Duplicate Pattern", "Copy the current pattern to a new one after it (Shift: in its place)", "Ctrl+D"),
+    key("Make Unique", "Give this position its own copy of a shared pattern", "Ctrl+U"),
+];
+
+/// Every section, in display order
+pub const HELP_SECTIONS: [HelpSection; 6] = [
+    HelpSection { name: "File", entries: FILE },
+    HelpSection { name: "Edit", entries: EDIT },
+    HelpSection { name: "Tools", entries: TOOLS },
+    HelpSection { name: "View", entries: VIEW },
+    HelpSection { name: "PS1 Effects", entries: PS1_EFFECTS },
+    HelpSection { name: "Tracker", entries: TRACKER },
+];
+
+/// Section shown first when help opens in `tool` (None = all sections)
+pub fn section_for(tool: Tool) -> Option<usize> {
+    let name = match tool {
+        Tool::WorldEditor => "Tools",
+        Tool::Tracker => "Tracker",
+        Tool::Home | Tool::Modeler => return None,
+    };
+    HELP_SECTIONS.iter().position(|s| s.name == name)
+}
+
+/// Entries to list as (section index, entry). A filter searches names in every
+/// section; without one, `section` picks the section (None = all).
+fn matching_entries(filter: &str, section: Option<usize>) -> Vec<(usize, &'static HelpEntry)> {
+    let filter = filter.trim().to_lowercase();
+    HELP_SECTIONS.iter().enumerate()
+        .filter(|(i, _)| !filter.is_empty() || section.is_none_or(|s| s == *i))
+        .flat_map(|(i, s)| s.entries.iter().map(move |e| (i, e)))
+        .filter(|(_, e)| e.name.to_lowercase().contains(&filter))
+        .collect()
+}
+
+/// Fixed id of the filter field
+const FILTER_ID: u64 = 0x4E1F;
+const ROW_HEIGHT: f32 = 22.0;
+
+/// State of the help overlay
+#[derive(Default)]
+pub struct HelpOverlay {
+    pub open: bool,
+    pub filter: String,
+    /// Section listed when the filter is empty (None = all)
+    pub section: Option<usize>,
+    /// First list row shown
+    scroll: usize,
+}
+
+impl HelpOverlay {
+    /// Open on `section` with an empty filter
+    pub fn open(&mut self, section: Option<usize>) {
+        self.open = true;
+        self.filter.clear();
+        self.section = section;
+        self.scroll = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// F1: open on `section`, or close
+    pub fn toggle(&mut self, section: Option<usize>) {
+        if self.open {
+            self.close();
+        } else {
+            self.open(section);
+        }
+    }
+}
+
+/// Draw the help overlay if open. Call last in the frame, with the real mouse
+/// restored (it closes on Escape, F1 is handled by the caller, and a click outside
+/// the panel closes it too).
+pub fn draw_help_overlay(ctx: &mut UiContext, help: &mut HelpOverlay, icon_font: Option<&Font>) {
+    if !help.open {
+        return;
+    }
+    let theme = ctx.theme;
+
+    // Translucent backdrop over the whole window
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 190));
+
+    let panel_w = (screen_width() - 40.0).min(960.0);
+    let panel_h = (screen_height() - 40.0).min(680.0);
+    let panel = Rect::new(
+        ((screen_width() - panel_w) / 2.0).floor(),
+        ((screen_height() - panel_h) / 2.0).floor(),
+        panel_w,
+        panel_h,
+    );
+    if ctx.mouse.left_pressed && !ctx.mouse.inside(&panel) {
+        help.close();
+        return;
+    }
+    draw_rectangle(panel.x, panel.y, panel.w, panel.h, Color::from_rgba(35, 35, 40, 240));
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 2.0, Color::from_rgba(60, 60, 70, 255));
+
+    // Header: title, filter and hint
+    let header_h = 40.0;
+    draw_rectangle(panel.x, panel.y, panel.w, header_h, Color::from_rgba(45, 45, 55, 255));
+    draw_text("Help", panel.x + 16.0, panel.y + 26.0, 20.0, WHITE);
+    let filter_rect = Rect::new(panel.x + 80.0, panel.y + 9.0, 240.0, 22.0);
+    ctx.focus_text(FILTER_ID, &help.filter);
+    match text_input(ctx, filter_rect, FILTER_ID, &mut help.filter) {
+        TextInputEvent::Cancelled => {
+            help.close();
+            return;
+        }
+        TextInputEvent::Changed => help.scroll = 0,
+        TextInputEvent::None | TextInputEvent::Submitted => {}
+    }
+    if help.filter.is_empty() {
+        draw_text("Filter by name...", filter_rect.x + 4.0, filter_rect.y + 15.0, 14.0, theme.text_faint);
+    }
+    let hint = "F1 or Esc to close";
+    let hint_w = measure_text(hint, None, 14, 1.0).width;
+    draw_text(hint, (panel.right() - hint_w - 16.0).floor(), panel.y + 25.0, 14.0, theme.text_faint);
+
+    // Sections (left): "All" then one row per section
+    let body_y = panel.y + header_h + 8.0;
+    let body_h = panel.h - header_h - 16.0;
+    let sections_rect = Rect::new(panel.x + 8.0, body_y, 140.0, body_h);
+    let mut names = vec!["All".to_string()];
+    names.extend(HELP_SECTIONS.iter().map(|s| s.name.to_string()));
+    let selected = if help.filter.trim().is_empty() { Some(help.section.map_or(0, |s| s + 1)) } else { None };
+    let mut sections_scroll = 0.0;
+    let result = draw_scrollable_list(ctx, sections_rect, &names, selected, &mut sections_scroll, 26.0, None);
+    if let Some(clicked) = result.clicked {
+        help.section = clicked.checked_sub(1);
+        help.filter.clear();
+        help.scroll = 0;
+    }
+
+    // Entries (right), with a header row where the section changes
+    let list = Rect::new(sections_rect.right() + 12.0, body_y, panel.right() - sections_rect.right() - 20.0, body_h);
+    let entries = matching_entries(&help.filter, help.section);
+    if entries.is_empty() {
+        draw_text("No matches", list.x + 8.0, list.y + 16.0, 14.0, theme.text_dim);
+        return;
+    }
+    let mut rows: Vec<(usize, Option<&HelpEntry>)> = Vec::new();
+    for (section, entry) in entries {
+        if rows.last().is_none_or(|&(s, _)| s != section) {
+            rows.push((section, None));
+        }
+        rows.push((section, Some(entry)));
+    }
+    let visible = (list.h / ROW_HEIGHT).floor() as usize;
+    if ctx.mouse.inside(&list) && ctx.mouse.scroll != 0.0 {
+        let step = if ctx.mouse.scroll > 0.0 { -3 } else { 3 };
+        help.scroll = help.scroll.saturating_add_signed(step);
+    }
+    help.scroll = help.scroll.min(rows.len().saturating_sub(visible));
+
+    let name_x = list.x + 28.0;
+    let shortcut_x = list.x + (list.w * 0.32).floor();
+    let description_x = list.x + (list.w * 0.55).floor();
+    for (i, &(section, entry)) in rows.iter().skip(help.scroll).take(visible).enumerate() {
+        let y = list.y + i as f32 * ROW_HEIGHT;
+        let text_y = (y + 15.0).floor();
+        let Some(entry) = entry else {
+            draw_text(HELP_SECTIONS[section].name, list.x + 4.0, text_y, 16.0, theme.accent);
+            draw_line(list.x, y + ROW_HEIGHT - 2.0, list.right(), y + ROW_HEIGHT - 2.0, 1.0, theme.border);
+            continue;
+        };
+        if ctx.mouse.inside(&Rect::new(list.x, y, list.w, ROW_HEIGHT)) {
+            draw_rectangle(list.x, y, list.w, ROW_HEIGHT, theme.hover);
+        }
+        if let Some(icon) = entry.icon {
+            draw_icon_centered(icon_font, icon, &Rect::new(list.x + 4.0, y + 2.0, 18.0, 18.0), 14.0, theme.text);
+        }
+        draw_text(entry.name, name_x, text_y, 14.0, WHITE);
+        draw_text(entry.shortcut, shortcut_x, text_y, 14.0, theme.highlight);
+        draw_text(entry.description, description_x, text_y, 14.0, theme.text_dim);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_searches_every_section_by_name() {
+        // No filter: just the chosen section, or everything
+        let tracker = section_for(Tool::Tracker);
+        let listed = matching_entries("", tracker);
+        assert_eq!(listed.len(), TRACKER.len());
+        assert!(listed.iter().all(|&(s, _)| Some(s) == tracker));
+        let total: usize = HELP_SECTIONS.iter().map(|s| s.entries.len()).sum();
+        assert_eq!(matching_entries("", None).len(), total);
+
+        // A filter ignores the section and case
+        let found = matching_entries("  UNDO ", tracker);
+        assert!(found.iter().any(|(_, e)| e.name == "Undo History"));
+        assert!(found.iter().any(|(_, e)| e.name == "Undo / Redo"));
+        assert!(matching_entries("no such tool", None).is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+//! Bonnie Engine library
+//!
+//! The core of the engine, usable without a window:
+//! - `rasterizer`: PS1-style software rasterizer
+//! - `world`: rooms, sectors and level IO (RON)
+//! - `headless`: render a level to a framebuffer (thumbnails, screenshot tests)
+//! - `game`: player physics and tuning for game mode
+//! - `music`: tracker songs, the row sequencer and the mixer (no audio device)
+//!
+//! The editor, tracker and other UI layers live in the `bonnie-engine` binary
+//! and need the `ui` feature (macroquad, audio, file dialogs).
+
+pub mod rasterizer;
+pub mod world;
+pub mod headless;
+pub mod game;
+pub mod music;
@@ -10,20 +10,23 @@
 /// Version from Cargo.toml
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-mod rasterizer;
-mod world;
+use bonnie_engine::{game, music, rasterizer, world};
+
 mod ui;
 mod editor;
 mod landing;
 mod modeler;
 mod tracker;
 mod app;
+mod help;
 
 use macroquad::prelude::*;
 use rasterizer::{Framebuffer, Texture, HEIGHT, WIDTH};
 use world::{create_empty_level, load_level, save_level};
 use ui::{UiContext, MouseState, Rect, draw_fixed_tabs, TabEntry, layout as tab_layout, icon};
-use editor::{EditorAction, draw_editor, draw_example_browser, BrowserAction, discover_examples};
+use editor::{EditorAction, draw_editor, draw_example_browser, BrowserAction, BrowserKind, discover_examples, discover_prefabs};
+use editor::{draw_template_chooser, TemplateAction, LEVEL_TEMPLATES, draw_game_view, GameViewAction};
+use editor::{draw_missing_packs, MissingPacksAction, draw_crash_recovery, RecoveryAction};
 use app::{AppState, Tool};
 use std::path::PathBuf;
 
@@ -45,9 +48,19 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
+    // Write an emergency save of unsaved work if anything panics
+    editor::install_panic_hook();
+
     // Initialize framebuffer (used by 3D viewport in editor)
     let mut fb = Framebuffer::new(WIDTH, HEIGHT);
 
+    // Asked to play a level: no editor at all
+    match startup_play_level() {
+        Some(Ok(level)) => return play_standalone(level, &mut fb).await,
+        Some(Err(e)) => eprintln!("Can't play level {}, opening the editor", e),
+        None => {}
+    }
+
     // Start with empty level (user can open levels via browser)
     let level = create_empty_level();
 
@@ -64,13 +77,15 @@ async fn main() {
             Some(font)
         }
         Err(e) => {
-            println!("Failed to load Lucide font: {}, icons will be missing", e);
+            println!("Failed to load Lucide font: {}, icons will show as text labels", e);
             None
         }
     };
 
     // App state with all tools
     let mut app = AppState::new(level, None, icon_font);
+    ui_ctx.theme = app.world_editor.editor_state.theme.theme();
+    ui_ctx.text_icons = app.world_editor.editor_state.text_icons;
 
     // Track if this is the first time opening World Editor (to show browser)
     let mut world_editor_first_open = true;
@@ -79,8 +94,11 @@ async fn main() {
     #[cfg(target_arch = "wasm32")]
     {
         use editor::TexturePack;
-        app.world_editor.editor_state.texture_packs = TexturePack::load_from_manifest().await;
+        let (texture_packs, report) = TexturePack::load_from_manifest().await;
+        app.world_editor.editor_state.texture_packs = texture_packs;
+        app.world_editor.editor_state.set_pack_load_report(report);
         println!("WASM: Loaded {} texture packs", app.world_editor.editor_state.texture_packs.len());
+        app.world_editor.editor_state.sounds = editor::SoundLibrary::load_from_manifest().await;
     }
 
     println!("=== Bonnie Engine ===");
@@ -94,6 +112,7 @@ async fn main() {
             y: mouse_pos.1,
             left_down,
             right_down: is_mouse_button_down(MouseButton::Right),
+            middle_down: is_mouse_button_down(MouseButton::Middle),
             left_pressed: left_down && !last_left_down,
             left_released: !left_down && last_left_down,
             scroll: mouse_wheel().1,
@@ -101,10 +120,20 @@ async fn main() {
         last_left_down = left_down;
         ui_ctx.begin_frame(mouse_state);
 
+        // F1 opens help on the active tool's section (not over a running playtest)
+        let playtesting = app.active_tool == Tool::WorldEditor && app.world_editor.game.is_some();
+        if is_key_pressed(KeyCode::F1) && !playtesting {
+            app.help.toggle(help::section_for(app.active_tool));
+        }
+
         // Block background input if example browser modal is open
         // Save the real mouse state so we can restore it for the modal
         let real_mouse = mouse_state;
-        if app.world_editor.example_browser.open {
+        let help_open = app.help.open;
+        let world_dialog = app.world_editor.example_browser.open || app.world_editor.template_chooser.open
+            || app.world_editor.editor_state.missing_packs.is_some()
+            || (app.active_tool == Tool::WorldEditor && app.world_editor.crash_recovery.is_some());
+        if world_dialog || help_open {
             ui_ctx.begin_modal();
         }
 
@@ -137,56 +166,87 @@ async fn main() {
         // Content area below tab bar
         let content_rect = Rect::new(0.0, tab_layout::BAR_HEIGHT, screen_w, screen_h - tab_layout::BAR_HEIGHT);
 
+        // Keep the song running while another tool is active (tracker background playback),
+        // frozen while a playtest is paused
+        let game_paused = app.world_editor.game.is_some() && app.world_editor.pause_menu.open;
+        app.tracker.set_playback_held(game_paused);
+        if app.active_tool != Tool::Tracker && app.tracker.background_playback && !game_paused {
+            app.tracker.update_playback(get_frame_time() as f64);
+        }
+
         // Draw active tool content
         match app.active_tool {
             Tool::Home => {
                 landing::draw_landing(content_rect, &mut app.landing);
             }
 
-            Tool::WorldEditor => {
+            // Playtest: the session replaces the editor until Exit in the pause menu
+            Tool::WorldEditor if app.world_editor.game.is_some() => {
                 let ws = &mut app.world_editor;
+                let editor_textures: Vec<Texture> = ws.editor_state.texture_packs
+                    .iter()
+                    .flat_map(|pack| &pack.textures)
+                    .cloned()
+                    .collect();
 
-                // Check for pending import from browser (WASM only)
-                #[cfg(target_arch = "wasm32")]
-                {
-                    extern "C" {
-                        fn bonnie_check_import() -> i32;
-                        fn bonnie_get_import_data_len() -> usize;
-                        fn bonnie_get_import_filename_len() -> usize;
-                        fn bonnie_copy_import_data(ptr: *mut u8, max_len: usize) -> usize;
-                        fn bonnie_copy_import_filename(ptr: *mut u8, max_len: usize) -> usize;
-                        fn bonnie_clear_import();
+                let action = match ws.game.as_mut() {
+                    Some(session) => draw_game_view(
+                        &mut ui_ctx,
+                        content_rect,
+                        session,
+                        &mut ws.pause_menu,
+                        &editor_textures,
+                        &ws.editor_state.texture_packs,
+                        &mut fb,
+                    ),
+                    None => GameViewAction::Exit,
+                };
+                match action {
+                    GameViewAction::None => {
+                        if let Some(session) = &ws.game {
+                            ws.ambient.update(&app.tracker.audio, &ws.editor_state.sounds, session);
+                        }
                     }
-
-                    let has_import = unsafe { bonnie_check_import() };
-
-                    if has_import != 0 {
-                        let data_len = unsafe { bonnie_get_import_data_len() };
-                        let filename_len = unsafe { bonnie_get_import_filename_len() };
-
-                        let mut data_buf = vec![0u8; data_len];
-                        let mut filename_buf = vec![0u8; filename_len];
-
-                        unsafe {
-                            bonnie_copy_import_data(data_buf.as_mut_ptr(), data_len);
-                            bonnie_copy_import_filename(filename_buf.as_mut_ptr(), filename_len);
-                            bonnie_clear_import();
+                    GameViewAction::Paused => {
+                        // Background song holds its position; cut the notes still sounding
+                        app.tracker.audio.all_notes_off();
+                        ws.ambient.stop(&app.tracker.audio);
+                    }
+                    GameViewAction::Restart => {
+                        // Fresh session from the same spawn, like pressing Play again
+                        if let Some(session) = &ws.game {
+                            let restarted = game::GameSession::new(
+                                &ws.editor_state.level,
+                                session.spawn,
+                                ws.editor_state.raster_settings.clone(),
+                                session.tuning.clone(),
+                            ).facing(session.spawn_yaw);
+                            ws.game = Some(restarted);
                         }
+                    }
+                    GameViewAction::Exit => {
+                        // Drop the whole session: the next Play starts from scratch
+                        ws.game = None;
+                        ws.ambient.stop(&app.tracker.audio);
+                        ws.editor_state.set_status("Stopped playtest", 2.0);
+                    }
+                }
 
-                        let data = String::from_utf8_lossy(&data_buf).to_string();
-                        let filename = String::from_utf8_lossy(&filename_buf).to_string();
+                // The web has no audio thread: voices only sound when the engine
+                // renders, which background song playback may already do this frame
+                #[cfg(target_arch = "wasm32")]
+                if !app.tracker.background_playback {
+                    app.tracker.audio.render_audio(get_frame_time() as f64);
+                }
+            }
 
-                        match ron::from_str::<world::Level>(&data) {
-                            Ok(level) => {
-                                ws.editor_layout.apply_config(&level.editor_layout);
-                                ws.editor_state.load_level(level, PathBuf::from(&filename));
-                                ws.editor_state.set_status(&format!("Uploaded {}", filename), 3.0);
-                            }
-                            Err(e) => {
-                                ws.editor_state.set_status(&format!("Upload failed: {}", e), 5.0);
-                            }
-                        }
-                    }
+            Tool::WorldEditor => {
+                let ws = &mut app.world_editor;
+
+                // Check for pending import from browser (WASM only)
+                #[cfg(target_arch = "wasm32")]
+                if let Some((filename, data)) = take_browser_import() {
+                    import_file(ws, &filename, &data);
                 }
 
                 // Build textures array from texture packs
@@ -212,8 +272,10 @@ async fn main() {
 
                 // Draw example browser overlay if open
                 if ws.example_browser.open {
-                    // End modal blocking so the browser itself can receive input
-                    ui_ctx.end_modal(real_mouse);
+                    // End modal blocking so the browser itself can receive input (unless help is on top)
+                    if !help_open {
+                        ui_ctx.end_modal(real_mouse);
+                    }
 
                     let browser_action = draw_example_browser(
                         &mut ui_ctx,
@@ -224,6 +286,10 @@ async fn main() {
                     );
 
                     match browser_action {
+                        BrowserAction::SelectPreview(index) if ws.example_browser.kind == BrowserKind::Prefabs => {
+                            // Prefabs are loaded when the browser opens
+                            ws.example_browser.preview_prefab(index);
+                        }
                         BrowserAction::SelectPreview(index) => {
                             // Load the preview synchronously
                             if let Some(example) = ws.example_browser.examples.get(index) {
@@ -237,7 +303,7 @@ async fn main() {
                                         }
                                         Err(e) => {
                                             eprintln!("Failed to load example {}: {}", path.display(), e);
-                                            ws.editor_state.set_status(&format!("Failed to load: {}", e), 3.0);
+                                            ws.editor_state.set_error(&format!("Failed to load: {}", e));
                                         }
                                     }
                                 }
@@ -257,11 +323,23 @@ async fn main() {
                             }
                         }
                         BrowserAction::NewLevel => {
-                            // Start with a fresh empty level
-                            let new_level = create_empty_level();
-                            ws.editor_state = editor::EditorState::new(new_level);
-                            ws.editor_layout.apply_config(&ws.editor_state.level.editor_layout);
-                            ws.editor_state.set_status("New level created", 3.0);
+                            // Start a new level from a template
+                            ws.example_browser.close();
+                            ws.template_chooser.open();
+                        }
+                        BrowserAction::InsertPrefab => {
+                            if let Some((name, prefab)) = ws.example_browser.selected_prefab() {
+                                let (name, prefab) = (name.to_string(), prefab.clone());
+                                let spot = ws.editor_state.prefab_spot();
+                                ws.editor_state.insert_prefab(&name, &prefab, spot);
+                            }
+                            ws.example_browser.close();
+                        }
+                        BrowserAction::PlacePrefab => {
+                            if let Some((name, prefab)) = ws.example_browser.selected_prefab() {
+                                ws.editor_state.set_status(&format!("Click in the 2D grid to place {} (Esc cancels)", name), 4.0);
+                                ws.editor_state.prefab_placement = Some((name.to_string(), prefab.clone()));
+                            }
                             ws.example_browser.close();
                         }
                         BrowserAction::Cancel => {
@@ -270,6 +348,71 @@ async fn main() {
                         BrowserAction::None => {}
                     }
                 }
+
+                // Draw new-level template chooser overlay if open
+                if ws.template_chooser.open {
+                    if !help_open {
+                        ui_ctx.end_modal(real_mouse);
+                    }
+
+                    match draw_template_chooser(&mut ui_ctx, &mut ws.template_chooser) {
+                        TemplateAction::Create(index) => {
+                            let template = &LEVEL_TEMPLATES[index];
+                            ws.editor_state = editor::EditorState::new((template.build)());
+                            ws.editor_layout.apply_config(&ws.editor_state.level.editor_layout);
+                            ws.editor_state.mark_level_dirty();
+                            ws.editor_state.frame_level();
+                            ws.editor_state.set_status(&format!("New level: {}", template.name), 3.0);
+                            ws.template_chooser.close();
+                        }
+                        TemplateAction::Cancel => {
+                            ws.template_chooser.close();
+                        }
+                        TemplateAction::None => {}
+                    }
+                }
+
+                // Missing texture packs of a freshly loaded level (on top of the browser that loaded it)
+                if let Some(dialog) = ws.editor_state.missing_packs.as_mut() {
+                    if !help_open {
+                        ui_ctx.end_modal(real_mouse);
+                    }
+
+                    match draw_missing_packs(&mut ui_ctx, dialog, &ws.editor_state.texture_packs) {
+                        MissingPacksAction::Substitute => ws.editor_state.apply_pack_substitutes(),
+                        MissingPacksAction::Continue => ws.editor_state.missing_packs = None,
+                        MissingPacksAction::None => {}
+                    }
+                }
+
+                // Emergency save left by a crash (on top of everything, including the browser)
+                if let Some(recovery) = ws.crash_recovery.as_ref() {
+                    if !help_open {
+                        ui_ctx.end_modal(real_mouse);
+                    }
+
+                    let action = draw_crash_recovery(&mut ui_ctx, recovery);
+                    if action != RecoveryAction::None {
+                        if let Some(recovery) = ws.crash_recovery.take() {
+                            match action {
+                                RecoveryAction::Restore => match recovery.restore() {
+                                    Ok(level) => {
+                                        ws.editor_layout.apply_config(&level.editor_layout);
+                                        ws.editor_state.open_emergency_save(level, recovery.path.clone());
+                                        ws.example_browser.close();
+                                        recovery.discard();
+                                    }
+                                    // Keep the file: it may be the only copy of that work
+                                    Err(e) => {
+                                        let kept = recovery.set_aside();
+                                        ws.editor_state.set_error(&format!("Emergency save unreadable ({}), kept at {}", e, kept));
+                                    }
+                                },
+                                _ => recovery.discard(),
+                            }
+                        }
+                    }
+                }
             }
 
             Tool::Modeler => {
@@ -295,14 +438,29 @@ async fn main() {
                 let delta = get_frame_time() as f64;
                 app.tracker.update_playback(delta);
 
+                // Soundfont uploaded from Load Soundfont (WASM only)
+                #[cfg(target_arch = "wasm32")]
+                if let Some((filename, data)) = take_browser_import() {
+                    app.tracker.load_soundfont_file(&filename, &data);
+                }
+
                 // Draw tracker UI
                 tracker::draw_tracker(&mut ui_ctx, content_rect, &mut app.tracker, app.icon_font.as_ref());
             }
         }
 
+        // Help overlay above every tool and dialog
+        if app.help.open {
+            ui_ctx.end_modal(real_mouse);
+            help::draw_help_overlay(&mut ui_ctx, &mut app.help, app.icon_font.as_ref());
+        }
+
         // Draw tooltips last (on top of everything)
         ui_ctx.draw_tooltip();
 
+        // Keep the emergency save current for the panic hook
+        editor::track_crash_snapshot(&app.world_editor.editor_state);
+
         next_frame().await;
     }
 }
@@ -311,13 +469,41 @@ async fn main() {
 fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
     match action {
         EditorAction::Play => {
-            ws.editor_state.set_status("Game preview coming soon", 2.0);
+            // Start on the floor under the 3D camera, or the first floor in the level
+            let state = &mut ws.editor_state;
+            match game::find_spawn(&state.level, state.camera_3d.position) {
+                Some(spawn) => {
+                    ws.pause_menu.open = false;
+                    ws.game = Some(game::GameSession::new(
+                        &state.level,
+                        spawn,
+                        state.raster_settings.clone(),
+                        game::GameTuning::default(),
+                    ));
+                }
+                None => state.set_warning("Nothing to stand on: add a floor before playing"),
+            }
+        }
+        EditorAction::PlayFrom { room, x, z } => {
+            // A spawn for this run only, looking the way the 3D camera does
+            let state = &mut ws.editor_state;
+            match game::sector_spawn(&state.level, room, x, z) {
+                Some(spawn) => {
+                    ws.pause_menu.open = false;
+                    ws.game = Some(game::GameSession::new(
+                        &state.level,
+                        spawn,
+                        state.raster_settings.clone(),
+                        game::GameTuning::default(),
+                    ).facing(state.camera_3d.rotation_y));
+                    state.set_status(&format!("Playtesting from ({}, {}) — spawn point unchanged", x, z), 3.0);
+                }
+                None => state.set_warning(&format!("Nothing to stand on at ({}, {}): pick a sector with a floor", x, z)),
+            }
         }
         EditorAction::New => {
-            let new_level = create_empty_level();
-            ws.editor_state = editor::EditorState::new(new_level);
-            ws.editor_layout.apply_config(&ws.editor_state.level.editor_layout);
-            ws.editor_state.set_status("Created new level", 3.0);
+            // Pick a template first; the current level stays until one is chosen
+            ws.template_chooser.open();
         }
         EditorAction::Save => {
             ws.editor_state.level.editor_layout = ws.editor_layout.to_config();
@@ -325,11 +511,12 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
             if let Some(path) = &ws.editor_state.current_file.clone() {
                 match save_level(&ws.editor_state.level, path) {
                     Ok(()) => {
-                        ws.editor_state.dirty = false;
+                        ws.editor_state.mark_saved();
                         ws.editor_state.set_status(&format!("Saved to {}", path.display()), 3.0);
+                        ws.editor_state.check_level_on_save();
                     }
                     Err(e) => {
-                        ws.editor_state.set_status(&format!("Save failed: {}", e), 5.0);
+                        ws.editor_state.set_error(&format!("Save failed: {}", e));
                     }
                 }
             } else {
@@ -340,11 +527,12 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                 match save_level(&ws.editor_state.level, &default_path) {
                     Ok(()) => {
                         ws.editor_state.current_file = Some(default_path.clone());
-                        ws.editor_state.dirty = false;
+                        ws.editor_state.mark_saved();
                         ws.editor_state.set_status(&format!("Saved to {}", default_path.display()), 3.0);
+                        ws.editor_state.check_level_on_save();
                     }
                     Err(e) => {
-                        ws.editor_state.set_status(&format!("Save failed: {}", e), 5.0);
+                        ws.editor_state.set_error(&format!("Save failed: {}", e));
                     }
                 }
             }
@@ -364,18 +552,19 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                 match save_level(&ws.editor_state.level, &save_path) {
                     Ok(()) => {
                         ws.editor_state.current_file = Some(save_path.clone());
-                        ws.editor_state.dirty = false;
+                        ws.editor_state.mark_saved();
                         ws.editor_state.set_status(&format!("Saved as {}", save_path.display()), 3.0);
+                        ws.editor_state.check_level_on_save();
                     }
                     Err(e) => {
-                        ws.editor_state.set_status(&format!("Save failed: {}", e), 5.0);
+                        ws.editor_state.set_error(&format!("Save failed: {}", e));
                     }
                 }
             }
         }
         #[cfg(target_arch = "wasm32")]
         EditorAction::SaveAs => {
-            ws.editor_state.set_status("Save As not available in browser", 3.0);
+            ws.editor_state.set_warning("Save As not available in browser");
         }
         #[cfg(not(target_arch = "wasm32"))]
         EditorAction::PromptLoad => {
@@ -384,24 +573,36 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
 
             let dialog = rfd::FileDialog::new()
                 .add_filter("RON Level", &["ron"])
+                .add_filter("Texture Pack / Level Zip", &["zip"])
                 .set_directory(&default_dir);
 
             if let Some(path) = dialog.pick_file() {
-                match load_level(&path) {
-                    Ok(level) => {
-                        ws.editor_layout.apply_config(&level.editor_layout);
-                        ws.editor_state.load_level(level, path.clone());
-                        ws.editor_state.set_status(&format!("Loaded {}", path.display()), 3.0);
+                let is_zip = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+                if is_zip {
+                    let filename = path.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    match std::fs::read(&path) {
+                        Ok(bytes) => import_file(ws, &filename, &bytes),
+                        Err(e) => ws.editor_state.set_error(&format!("Load failed: {}", e)),
                     }
-                    Err(e) => {
-                        ws.editor_state.set_status(&format!("Load failed: {}", e), 5.0);
+                } else {
+                    match load_level(&path) {
+                        Ok(level) => {
+                            ws.editor_layout.apply_config(&level.editor_layout);
+                            ws.editor_state.load_level(level, path.clone());
+                            ws.editor_state.set_status(&format!("Loaded {}", path.display()), 3.0);
+                        }
+                        Err(e) => {
+                            ws.editor_state.set_error(&format!("Load failed: {}", e));
+                        }
                     }
                 }
             }
         }
         #[cfg(target_arch = "wasm32")]
         EditorAction::PromptLoad => {
-            ws.editor_state.set_status("Open not available in browser - use Upload", 3.0);
+            ws.editor_state.set_warning("Open not available in browser - use Upload");
         }
         #[cfg(target_arch = "wasm32")]
         EditorAction::Export => {
@@ -426,11 +627,12 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                         bonnie_trigger_download();
                     }
 
-                    ws.editor_state.dirty = false;
+                    ws.editor_state.mark_saved();
                     ws.editor_state.set_status(&format!("Downloaded {}", filename), 3.0);
+                    ws.editor_state.check_level_on_save();
                 }
                 Err(e) => {
-                    ws.editor_state.set_status(&format!("Export failed: {}", e), 5.0);
+                    ws.editor_state.set_error(&format!("Export failed: {}", e));
                 }
             }
         }
@@ -446,11 +648,42 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
             unsafe {
                 bonnie_import_file();
             }
-            ws.editor_state.set_status("Select a .ron file to import...", 3.0);
+            ws.editor_state.set_status("Select a .ron level or .zip texture pack to import...", 3.0);
         }
         #[cfg(not(target_arch = "wasm32"))]
         EditorAction::Import => {
-            ws.editor_state.set_status("Import is for browser - use Open", 3.0);
+            let dialog = rfd::FileDialog::new()
+                .add_filter("Wavefront OBJ", &["obj"]);
+
+            if let Some(path) = dialog.pick_file() {
+                let filename = path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                match std::fs::read(&path) {
+                    Ok(bytes) => import_obj_room(ws, &filename, &bytes),
+                    Err(e) => ws.editor_state.set_error(&format!("Import failed: {}", e)),
+                }
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        EditorAction::Compare => {
+            let dialog = rfd::FileDialog::new()
+                .add_filter("RON Level", &["ron"])
+                .set_directory("assets/levels");
+
+            if let Some(path) = dialog.pick_file() {
+                let filename = path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                match load_level(&path) {
+                    Ok(level) => ws.editor_state.open_level_diff(&filename, &level),
+                    Err(e) => ws.editor_state.set_error(&format!("Compare failed: {}", e)),
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        EditorAction::Compare => {
+            ws.editor_state.set_warning("Compare not available in browser");
         }
         EditorAction::Load(path_str) => {
             let path = PathBuf::from(&path_str);
@@ -461,7 +694,7 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
                     ws.editor_state.set_status(&format!("Loaded {}", path.display()), 3.0);
                 }
                 Err(e) => {
-                    ws.editor_state.set_status(&format!("Load failed: {}", e), 5.0);
+                    ws.editor_state.set_error(&format!("Load failed: {}", e));
                 }
             }
         }
@@ -471,6 +704,271 @@ fn handle_editor_action(action: EditorAction, ws: &mut app::WorldEditorState) {
             ws.example_browser.open(levels);
             ws.editor_state.set_status("Browse levels", 2.0);
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        EditorAction::SavePrefab => {
+            ws.editor_state.save_room_as_prefab();
+        }
+        #[cfg(target_arch = "wasm32")]
+        EditorAction::SavePrefab => {
+            ws.editor_state.set_warning("Prefabs not available in browser");
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        EditorAction::ExportWeb => {
+            export_web_bundle(ws);
+        }
+        #[cfg(target_arch = "wasm32")]
+        EditorAction::ExportWeb => {
+            ws.editor_state.set_warning("Web export not available in browser");
+        }
+        EditorAction::BrowsePrefabs => {
+            ws.example_browser.open_prefabs(discover_prefabs());
+        }
         EditorAction::Exit | EditorAction::None => {}
     }
 }
+
+/// Add an OBJ blockout to the level as a new room (one undo step) and report how
+/// many of its triangles made it in
+#[cfg(not(target_arch = "wasm32"))]
+fn import_obj_room(ws: &mut app::WorldEditorState, filename: &str, data: &[u8]) {
+    let import = match ws.editor_state.level.import_obj(data, world::SECTOR_SIZE) {
+        Ok(import) => import,
+        Err(e) => {
+            ws.editor_state.set_error(&format!("Import failed: {}: {}", filename, e));
+            return;
+        }
+    };
+    let (summary, skipped) = (import.summary(), import.skipped);
+    let state = &mut ws.editor_state;
+    state.save_undo(&format!("Import {}", filename));
+    state.level.rooms.push(import.room);
+    state.current_room = state.level.rooms.len() - 1;
+    state.mark_room_dirty(state.current_room);
+    state.selection = editor::Selection::None;
+    state.clear_multi_selection();
+    let message = format!("Imported {} as room {}: {}", filename, state.current_room, summary);
+    if skipped > 0 {
+        state.set_warning(&message);
+    } else {
+        state.set_status(&message, 5.0);
+    }
+}
+
+/// Export the level as a folder that plays it in a browser, optionally with the
+/// textures it uses and a zip of the folder next to it
+#[cfg(not(target_arch = "wasm32"))]
+fn export_web_bundle(ws: &mut app::WorldEditorState) {
+    let Some(dir) = rfd::FileDialog::new().set_title("Export Playable Web Bundle").pick_folder() else {
+        return;
+    };
+    let ask = |description: &str| {
+        rfd::MessageDialog::new()
+            .set_title("Export Playable Web Bundle")
+            .set_description(description)
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show() == rfd::MessageDialogResult::Yes
+    };
+    let textures = ask("Include the textures this level uses? (Without them every face shows a placeholder.)");
+    let zip = ask("Also write a zip of the bundle next to the folder?");
+
+    let state = &mut ws.editor_state;
+    let result = editor::WebBundle::build(&state.level, &state.texture_packs, textures).and_then(|bundle| {
+        bundle.write(&dir)?;
+        if zip {
+            let zip_path = dir.with_extension("zip");
+            std::fs::write(&zip_path, bundle.to_zip()).map_err(|e| format!("Can't write {}: {}", zip_path.display(), e))?;
+        }
+        Ok(bundle)
+    });
+    match result {
+        Ok(bundle) if bundle.missing > 0 => state.set_warning(&format!(
+            "Exported web bundle to {} ({} textures; {} not found, shown as placeholders)",
+            dir.display(), bundle.textures, bundle.missing,
+        )),
+        Ok(bundle) => state.set_status(&format!(
+            "Exported web bundle to {} ({} textures): serve the folder and open index.html",
+            dir.display(), bundle.textures,
+        ), 5.0),
+        Err(e) => state.set_error(&format!("Web export failed: {}", e)),
+    }
+}
+
+/// Level to boot straight into play mode with, from `--play <level.ron>`
+#[cfg(not(target_arch = "wasm32"))]
+fn startup_play_level() -> Option<Result<world::Level, String>> {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args.iter().position(|arg| arg == "--play").and_then(|i| args.get(i + 1))?;
+    Some(load_level(path).map_err(|e| format!("{}: {}", path, e)))
+}
+
+/// Level to boot straight into play mode with, fetched by the page (an exported
+/// bundle's `BONNIE_PLAY_LEVEL`, or `?play=<file>`)
+#[cfg(target_arch = "wasm32")]
+fn startup_play_level() -> Option<Result<world::Level, String>> {
+    extern "C" {
+        fn bonnie_get_play_level_len() -> usize;
+        fn bonnie_copy_play_level(ptr: *mut u8, max_len: usize) -> usize;
+    }
+
+    let len = unsafe { bonnie_get_play_level_len() };
+    if len == 0 {
+        return None;
+    }
+    let mut bytes = vec![0u8; len];
+    let copied = unsafe { bonnie_copy_play_level(bytes.as_mut_ptr(), len) };
+    bytes.truncate(copied);
+    Some(world::load_level_from_str(&String::from_utf8_lossy(&bytes)).map_err(|e| e.to_string()))
+}
+
+/// Play one level with no editor around it (exported web bundles, `--play`). The
+/// pause menu only resumes or restarts: there's nothing to exit to.
+async fn play_standalone(level: world::Level, fb: &mut Framebuffer) {
+    #[cfg(not(target_arch = "wasm32"))]
+    let (texture_packs, _) = editor::TexturePack::discover_all();
+    #[cfg(target_arch = "wasm32")]
+    let (texture_packs, _) = editor::TexturePack::load_from_manifest().await;
+    let textures: Vec<Texture> = texture_packs.iter().flat_map(|pack| &pack.textures).cloned().collect();
+    #[cfg(not(target_arch = "wasm32"))]
+    let sounds = editor::SoundLibrary::discover();
+    #[cfg(target_arch = "wasm32")]
+    let sounds = editor::SoundLibrary::load_from_manifest().await;
+    #[allow(unused_mut)]
+    let mut audio = tracker::AudioEngine::new();
+    let mut ambient = editor::AmbientPlayer::default();
+
+    let raster_settings = rasterizer::RasterSettings { mipmapping: true, ..Default::default() };
+    let Some(spawn) = game::find_spawn(&level, rasterizer::Vec3::ZERO) else {
+        loop {
+            clear_background(BLACK);
+            draw_text("Nothing to stand on: this level has no floor", 20.0, 40.0, 20.0, WHITE);
+            next_frame().await;
+        }
+    };
+    let new_session = || game::GameSession::new(&level, spawn, raster_settings.clone(), game::GameTuning::default());
+    let mut session = new_session();
+    let mut pause = editor::PauseMenu { standalone: true, ..Default::default() };
+    let mut ui_ctx = UiContext::new();
+    let mut last_left_down = false;
+
+    loop {
+        let (x, y) = mouse_position();
+        let left_down = is_mouse_button_down(MouseButton::Left);
+        ui_ctx.begin_frame(MouseState {
+            x,
+            y,
+            left_down,
+            right_down: is_mouse_button_down(MouseButton::Right),
+            middle_down: is_mouse_button_down(MouseButton::Middle),
+            left_pressed: left_down && !last_left_down,
+            left_released: !left_down && last_left_down,
+            scroll: mouse_wheel().1,
+        });
+        last_left_down = left_down;
+
+        let rect = Rect::new(0.0, 0.0, screen_width(), screen_height());
+        let action = draw_game_view(&mut ui_ctx, rect, &mut session, &mut pause, &textures, &texture_packs, fb);
+        match action {
+            GameViewAction::None => ambient.update(&audio, &sounds, &session),
+            GameViewAction::Paused => ambient.stop(&audio),
+            GameViewAction::Restart | GameViewAction::Exit => session = new_session(),
+        }
+        #[cfg(target_arch = "wasm32")]
+        audio.render_audio(get_frame_time() as f64);
+        ui_ctx.draw_tooltip();
+        next_frame().await;
+    }
+}
+
+/// Take the file the page finished uploading, if any, as (filename, bytes)
+#[cfg(target_arch = "wasm32")]
+fn take_browser_import() -> Option<(String, Vec<u8>)> {
+    extern "C" {
+        fn bonnie_check_import() -> i32;
+        fn bonnie_get_import_data_len() -> usize;
+        fn bonnie_get_import_filename_len() -> usize;
+        fn bonnie_copy_import_data(ptr: *mut u8, max_len: usize) -> usize;
+        fn bonnie_copy_import_filename(ptr: *mut u8, max_len: usize) -> usize;
+        fn bonnie_clear_import();
+    }
+
+    if unsafe { bonnie_check_import() } == 0 {
+        return None;
+    }
+
+    let data_len = unsafe { bonnie_get_import_data_len() };
+    let filename_len = unsafe { bonnie_get_import_filename_len() };
+
+    let mut data_buf = vec![0u8; data_len];
+    let mut filename_buf = vec![0u8; filename_len];
+
+    unsafe {
+        bonnie_copy_import_data(data_buf.as_mut_ptr(), data_len);
+        bonnie_copy_import_filename(filename_buf.as_mut_ptr(), filename_len);
+        bonnie_clear_import();
+    }
+
+    Some((String::from_utf8_lossy(&filename_buf).to_string(), data_buf))
+}
+
+/// Import an uploaded/opened file: a .ron level, or a .zip containing a texture pack
+/// (PNGs, registered under the zip's name) and/or a .ron level
+fn import_file(ws: &mut app::WorldEditorState, filename: &str, data: &[u8]) {
+    let is_zip = filename.rsplit_once('.').is_some_and(|(_, ext)| ext.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        match world::load_level_from_str(&String::from_utf8_lossy(data)) {
+            Ok(level) => {
+                ws.editor_layout.apply_config(&level.editor_layout);
+                ws.editor_state.load_level(level, PathBuf::from(filename));
+                ws.editor_state.set_status(&format!("Uploaded {}", filename), 3.0);
+            }
+            Err(e) => {
+                ws.editor_state.set_error(&format!("Upload failed: {}", e));
+            }
+        }
+        return;
+    }
+
+    let entries = match editor::read_zip(data, editor::MAX_UPLOAD_RGBA_BYTES) {
+        Ok(entries) => entries,
+        Err(e) => {
+            ws.editor_state.set_error(&format!("Upload failed: {}", e));
+            return;
+        }
+    };
+
+    let mut loaded = Vec::new();
+    if entries.iter().any(|e| e.has_extension("png")) {
+        let pack_name = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem).to_string();
+        match editor::TexturePack::from_zip_entries(pack_name, &entries) {
+            Ok(pack) => {
+                let memory = editor::memory_summary(std::slice::from_ref(&pack));
+                loaded.push(format!("texture pack '{}' ({} textures, {})", pack.name, pack.textures.len(), memory));
+                ws.editor_state.add_texture_pack(pack);
+            }
+            Err(e) => {
+                ws.editor_state.set_error(&format!("Upload failed: {}", e));
+                return;
+            }
+        }
+    }
+
+    if let Some(entry) = entries.iter().find(|e| e.has_extension("ron")) {
+        match world::load_level_from_str(&String::from_utf8_lossy(&entry.data)) {
+            Ok(level) => {
+                ws.editor_layout.apply_config(&level.editor_layout);
+                ws.editor_state.load_level(level, PathBuf::from(entry.file_name()));
+                loaded.push(format!("level {}", entry.file_name()));
+            }
+            Err(e) => {
+                ws.editor_state.set_error(&format!("Upload failed: {}: {}", entry.file_name(), e));
+                return;
+            }
+        }
+    }
+
+    if loaded.is_empty() {
+        ws.editor_state.set_error(&format!("Upload failed: no .png textures or .ron level in {}", filename));
+    } else {
+        ws.editor_state.set_status(&format!("Uploaded {}", loaded.join(" and ")), 4.0);
+    }
+}
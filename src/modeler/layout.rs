@@ -130,8 +130,10 @@ pub fn draw_modeler(
     // Draw status bar
     draw_status_bar(status_rect, state);
 
-    // Handle keyboard shortcuts
-    handle_keyboard(state);
+    // Handle keyboard shortcuts (not while a text field, like the help filter, has focus)
+    if !ctx.text_input_focused() {
+        handle_keyboard(state);
+    }
 
     action
 }
@@ -503,9 +505,9 @@ fn draw_status_bar(rect: Rect, state: &ModelerState) {
     draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_rgba(40, 40, 45, 255));
 
     // Status message
-    if let Some(msg) = state.get_status() {
+    if let Some((msg, severity)) = state.get_status() {
         let center_x = rect.x + rect.w * 0.5 - (msg.len() as f32 * 4.0);
-        draw_text(msg, center_x, rect.y + 15.0, 14.0, Color::from_rgba(100, 255, 100, 255));
+        draw_text(msg, center_x, rect.y + 15.0, 14.0, severity.color());
     }
 
     // Keyboard hints
@@ -3,6 +3,7 @@
 use std::path::PathBuf;
 use crate::rasterizer::{Camera, Vec2, Vec3, Color, RasterSettings};
 use super::model::{Model, PartTransform};
+use crate::ui::{Severity, StatusQueue};
 
 /// Modeler view modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -194,7 +195,7 @@ pub struct ModelerState {
     pub undo_stack: Vec<Model>,
     pub redo_stack: Vec<Model>,
     pub dirty: bool,
-    pub status_message: Option<(String, f64)>,
+    pub status: StatusQueue,
 
     // Transform state (for mouse drag)
     pub transform_active: bool,
@@ -246,7 +247,7 @@ impl ModelerState {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             dirty: false,
-            status_message: None,
+            status: StatusQueue::new(),
 
             transform_active: false,
             transform_start_mouse: (0.0, 0.0),
@@ -258,20 +259,14 @@ impl ModelerState {
         }
     }
 
-    /// Set a status message that will be displayed for a duration
+    /// Queue a status message that will be displayed for a duration
     pub fn set_status(&mut self, message: &str, duration_secs: f64) {
-        let expiry = macroquad::time::get_time() + duration_secs;
-        self.status_message = Some((message.to_string(), expiry));
+        self.status.push(message, Severity::Info, duration_secs);
     }
 
-    /// Get current status message if not expired
-    pub fn get_status(&self) -> Option<&str> {
-        if let Some((msg, expiry)) = &self.status_message {
-            if macroquad::time::get_time() < *expiry {
-                return Some(msg);
-            }
-        }
-        None
+    /// Status message to display right now, if any
+    pub fn get_status(&self) -> Option<(&str, Severity)> {
+        self.status.current()
     }
 
     /// Save current state for undo
@@ -9,40 +9,6 @@ use crate::rasterizer::{
 use super::state::{ModelerState, ModelerSelection, SelectMode};
 use super::model::{Model, PartTransform};
 
-/// Project a world-space point to framebuffer coordinates
-fn world_to_screen(
-    world_pos: Vec3,
-    camera_pos: Vec3,
-    basis_x: Vec3,
-    basis_y: Vec3,
-    basis_z: Vec3,
-    fb_width: usize,
-    fb_height: usize,
-) -> Option<(f32, f32)> {
-    let rel = world_pos - camera_pos;
-    let cam_z = rel.dot(basis_z);
-
-    // Behind camera
-    if cam_z <= 0.1 {
-        return None;
-    }
-
-    let cam_x = rel.dot(basis_x);
-    let cam_y = rel.dot(basis_y);
-
-    // Same projection as the rasterizer
-    const SCALE: f32 = 0.75;
-    let vs = (fb_width.min(fb_height) as f32 / 2.0) * SCALE;
-    let ud = 5.0;
-    let us = ud - 1.0;
-
-    let denom = cam_z + ud;
-    let sx = (cam_x * us / denom) * vs + (fb_width as f32 / 2.0);
-    let sy = (cam_y * us / denom) * vs + (fb_height as f32 / 2.0);
-
-    Some((sx, sy))
-}
-
 /// Build a 4x4 rotation matrix from euler angles (degrees)
 fn rotation_matrix(rot: Vec3) -> [[f32; 4]; 4] {
     let (sx, cx) = rot.x.to_radians().sin_cos();
@@ -186,8 +152,8 @@ pub fn draw_modeler_viewport(
         state.viewport_mouse_captured = false;
     }
 
-    // Keyboard camera movement (WASD + Q/E)
-    let move_speed = 10.0; // Smaller scale for models
+    // Keyboard camera movement (WASD + Q/E), in units per second so it doesn't depend on frame rate
+    let move_speed = 600.0 * get_frame_time().min(0.1); // Smaller scale for models
     if inside_viewport || state.viewport_mouse_captured {
         if is_key_down(KeyCode::W) {
             state.camera.position = state.camera.position + state.camera.basis_z * move_speed;
@@ -253,11 +219,7 @@ pub fn draw_modeler_viewport(
             // Calculate normal (simplified - just use up vector for now)
             let normal = Vec3::new(0.0, 1.0, 0.0);
 
-            all_vertices.push(RasterVertex {
-                pos: world_pos,
-                uv: RasterVec2::new(vert.uv.x, vert.uv.y),
-                normal,
-            });
+            all_vertices.push(RasterVertex::new(world_pos, RasterVec2::new(vert.uv.x, vert.uv.y), normal));
         }
 
         // Add faces with offset indices
@@ -349,7 +311,7 @@ fn draw_3d_line(
     camera: &crate::rasterizer::Camera,
     color: RasterColor,
 ) {
-    const NEAR_PLANE: f32 = 0.1;
+    let near = camera.near_plane;
 
     let rel0 = p0 - camera.position;
     let rel1 = p1 - camera.position;
@@ -357,23 +319,23 @@ fn draw_3d_line(
     let z0 = rel0.dot(camera.basis_z);
     let z1 = rel1.dot(camera.basis_z);
 
-    if z0 <= NEAR_PLANE && z1 <= NEAR_PLANE {
+    if z0 <= near && z1 <= near {
         return;
     }
 
     // Clip to near plane
-    let (clipped_p0, clipped_p1) = if z0 <= NEAR_PLANE {
-        let t = (NEAR_PLANE - z0) / (z1 - z0);
+    let (clipped_p0, clipped_p1) = if z0 <= near {
+        let t = (near - z0) / (z1 - z0);
         (p0 + (p1 - p0) * t, p1)
-    } else if z1 <= NEAR_PLANE {
-        let t = (NEAR_PLANE - z0) / (z1 - z0);
+    } else if z1 <= near {
+        let t = (near - z0) / (z1 - z0);
         (p0, p0 + (p1 - p0) * t)
     } else {
         (p0, p1)
     };
 
-    let s0 = world_to_screen(clipped_p0, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height);
-    let s1 = world_to_screen(clipped_p1, camera.position, camera.basis_x, camera.basis_y, camera.basis_z, fb.width, fb.height);
+    let s0 = camera.world_to_screen(clipped_p0, fb.width, fb.height);
+    let s1 = camera.world_to_screen(clipped_p1, fb.width, fb.height);
 
     if let (Some((x0f, y0f)), Some((x1f, y1f))) = (s0, s1) {
         fb.draw_line(x0f as i32, y0f as i32, x1f as i32, y1f as i32, color);
@@ -401,15 +363,7 @@ fn draw_selection_overlays<F>(
             for (vert_idx, vert) in part.vertices.iter().enumerate() {
                 let world_pos = transform_point(world_mat, vert.position);
 
-                if let Some((sx, sy)) = world_to_screen(
-                    world_pos,
-                    state.camera.position,
-                    state.camera.basis_x,
-                    state.camera.basis_y,
-                    state.camera.basis_z,
-                    fb.width,
-                    fb.height,
-                ) {
+                if let Some((sx, sy)) = state.camera.world_to_screen(world_pos, fb.width, fb.height) {
                     // Check if selected
                     let is_selected = match &state.selection {
                         ModelerSelection::Vertices { part, verts } => {
@@ -530,15 +484,7 @@ fn handle_selection_click<F>(
                 for vert in &part.vertices {
                     let world_pos = transform_point(world_mat, vert.position);
 
-                    if let Some((sx, sy)) = world_to_screen(
-                        world_pos,
-                        state.camera.position,
-                        state.camera.basis_x,
-                        state.camera.basis_y,
-                        state.camera.basis_z,
-                        fb_width,
-                        fb_height,
-                    ) {
+                    if let Some((sx, sy)) = state.camera.world_to_screen(world_pos, fb_width, fb_height) {
                         let dist = ((fb_x - sx).powi(2) + (fb_y - sy).powi(2)).sqrt();
                         if dist < 20.0 {
                             if closest.map_or(true, |(_, best_dist)| dist < best_dist) {
@@ -571,15 +517,7 @@ fn handle_selection_click<F>(
                 for (vert_idx, vert) in part.vertices.iter().enumerate() {
                     let world_pos = transform_point(world_mat, vert.position);
 
-                    if let Some((sx, sy)) = world_to_screen(
-                        world_pos,
-                        state.camera.position,
-                        state.camera.basis_x,
-                        state.camera.basis_y,
-                        state.camera.basis_z,
-                        fb_width,
-                        fb_height,
-                    ) {
+                    if let Some((sx, sy)) = state.camera.world_to_screen(world_pos, fb_width, fb_height) {
                         let dist = ((fb_x - sx).powi(2) + (fb_y - sy).powi(2)).sqrt();
                         if dist < 10.0 {
                             if closest.map_or(true, |(_, _, best_dist)| dist < best_dist) {
@@ -0,0 +1,283 @@
+//! Mixer run by the audio callback: the SF2 synth, the metronome and PCM voices,
+//! stepped by the row sequencer and sent through the output stage
+//!
+//! No audio device here: the platform output (cpal, Web Audio) lives with the
+//! tracker's `AudioEngine` and calls `AudioState::render` for each buffer.
+
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, SyncSender};
+use rustysynth::Synthesizer;
+use super::pcm::{PcmSound, Voice, VoiceId};
+use super::pattern::Song;
+use super::sequencer::{Sequencer, SequencerCommand, SequencerEvent};
+
+/// Sample rate for audio output
+pub const SAMPLE_RATE: u32 = 44100;
+
+/// Quantization steps per unit for the PS1 SPU emulation (15-bit signed = 2^14)
+const SPU_QUANT_STEPS: f32 = 16384.0;
+
+/// Replaced songs the audio callback can queue before the UI frees them
+pub const REPLACED_SONGS: usize = 4;
+
+/// Audio engine state shared between main thread and audio callback
+pub struct AudioState {
+    /// The synthesizer
+    pub synth: Option<Synthesizer>,
+    /// Whether audio is playing
+    pub playing: bool,
+    /// Master output volume (0.0-1.0)
+    pub master_volume: f32,
+    /// Truncate mixed output to 15-bit precision like the PS1 SPU
+    pub spu_quantize: bool,
+    /// Metronome click currently sounding
+    click: Click,
+    /// PCM sounds playing
+    voices: Vec<Voice>,
+    /// Id of the next voice started
+    next_voice: u64,
+    /// Song playback, stepped by the samples rendered
+    pub sequencer: Sequencer,
+    /// Commands from the UI thread, applied at the start of each buffer
+    commands: Receiver<SequencerCommand>,
+    /// Songs the sequencer let go of, sent back for the UI thread to free
+    /// (bounded, so sending never allocates in the callback)
+    replaced_songs: SyncSender<Box<Song>>,
+    /// Scratch list of the sequencer's synth work (kept to avoid allocating per row)
+    events: Vec<SequencerEvent>,
+}
+
+impl AudioState {
+    pub fn new(commands: Receiver<SequencerCommand>, replaced_songs: SyncSender<Box<Song>>) -> Self {
+        Self {
+            synth: None,
+            playing: false,
+            master_volume: 1.0,
+            spu_quantize: false,
+            click: Click::default(),
+            voices: Vec::new(),
+            next_voice: 0,
+            sequencer: Sequencer::new(),
+            commands,
+            replaced_songs,
+            events: Vec::new(),
+        }
+    }
+
+    /// Render a buffer: synthesis is split at every row boundary inside it, so the
+    /// row's notes start on their exact sample
+    pub fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        while let Ok(command) = self.commands.try_recv() {
+            if let Some(song) = self.sequencer.apply(command, &mut self.events) {
+                // Only if the UI stopped draining them is one freed here
+                let _ = self.replaced_songs.try_send(song);
+            }
+            self.apply_events();
+        }
+
+        let len = left.len().min(right.len());
+        let mut start = 0;
+        while start < len {
+            let next_row = self.sequencer.samples_to_next_row();
+            if next_row == Some(0) {
+                self.sequencer.fire_row(&mut self.events);
+                self.apply_events();
+                continue;
+            }
+            let end = next_row.map_or(len, |samples| (start + samples).min(len));
+            let (l, r) = (&mut left[start..end], &mut right[start..end]);
+            match self.synth.as_mut() {
+                Some(synth) => synth.render(l, r),
+                None => {
+                    l.fill(0.0);
+                    r.fill(0.0);
+                }
+            }
+            self.click.mix(l, r);
+            for voice in &mut self.voices {
+                voice.mix(l, r);
+            }
+            self.sequencer.advance(end - start);
+            start = end;
+        }
+        self.voices.retain(|voice| !voice.finished());
+        apply_output_stage(&mut left[..len], &mut right[..len], self.master_volume, self.spu_quantize);
+    }
+
+    /// Start playing a PCM sound at `gain` (0.0-1.0), once or on a loop
+    pub fn play_voice(&mut self, sound: Arc<PcmSound>, gain: f32, looping: bool) -> VoiceId {
+        let id = VoiceId(self.next_voice);
+        self.next_voice += 1;
+        self.voices.push(Voice::new(id, sound, gain, looping));
+        id
+    }
+
+    /// Change a voice's gain (ramped over the next buffer). Does nothing once the
+    /// voice has stopped.
+    pub fn set_voice_gain(&mut self, id: VoiceId, gain: f32) {
+        if let Some(voice) = self.voices.iter_mut().find(|v| v.id == id) {
+            voice.target_gain = gain.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Stop a voice
+    pub fn stop_voice(&mut self, id: VoiceId) {
+        self.voices.retain(|v| v.id != id);
+    }
+
+    /// Hand the sequencer's pending events to the synth and metronome
+    fn apply_events(&mut self) {
+        for event in self.events.drain(..) {
+            if let SequencerEvent::Click { accent, volume } = event {
+                self.click = Click::metronome(accent, volume);
+                continue;
+            }
+            let Some(synth) = self.synth.as_mut() else {
+                continue;
+            };
+            match event {
+                SequencerEvent::NoteOn { channel, key, velocity, bank, program } => {
+                    set_program(synth, channel, bank, program);
+                    synth.note_on(channel, key, velocity);
+                }
+                SequencerEvent::NoteOff { channel, key } => synth.note_off(channel, key),
+                SequencerEvent::Midi { channel, command, data1, data2 } => {
+                    synth.process_midi_message(channel, command, data1, data2);
+                }
+                SequencerEvent::AllNotesOff => all_notes_off(synth),
+                SequencerEvent::Click { .. } => {}
+            }
+        }
+    }
+}
+
+/// Bank select (CC 0) then program change. The synth takes the SF2 bank number as-is,
+/// so drum kits (bank 128) work on any channel.
+pub fn set_program(synth: &mut Synthesizer, channel: i32, bank: i32, program: i32) {
+    synth.process_midi_message(channel, 0xB0, 0, bank);
+    synth.process_midi_message(channel, 0xC0, program, 0);
+}
+
+/// Release every key on every channel
+pub fn all_notes_off(synth: &mut Synthesizer) {
+    for channel in 0..16 {
+        for key in 0..128 {
+            synth.note_off(channel, key);
+        }
+    }
+}
+
+/// Length of a metronome click (25ms)
+const CLICK_SAMPLES: usize = SAMPLE_RATE as usize / 40;
+/// Per-sample amplitude decay of a click
+const CLICK_DECAY: f32 = 0.9985;
+
+/// Metronome click: a short decaying sine blip mixed over the synth output
+#[derive(Default)]
+struct Click {
+    /// Samples left to play (0 = silent)
+    remaining: usize,
+    /// Oscillator phase (radians)
+    phase: f32,
+    /// Phase increment per sample
+    step: f32,
+    amplitude: f32,
+}
+
+impl Click {
+    fn new(frequency: f32, amplitude: f32) -> Self {
+        Self {
+            remaining: CLICK_SAMPLES,
+            phase: 0.0,
+            step: std::f32::consts::TAU * frequency / SAMPLE_RATE as f32,
+            amplitude,
+        }
+    }
+
+    /// Metronome click, higher pitched on the first beat of a bar
+    fn metronome(accent: bool, volume: f32) -> Self {
+        let frequency = if accent { 1760.0 } else { 880.0 };
+        Self::new(frequency, volume.clamp(0.0, 1.0))
+    }
+
+    /// Add the click to both channels of the buffers
+    fn mix(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            if self.remaining == 0 {
+                break;
+            }
+            let sample = self.phase.sin() * self.amplitude;
+            *l += sample;
+            *r += sample;
+            self.phase += self.step;
+            self.amplitude *= CLICK_DECAY;
+            self.remaining -= 1;
+        }
+    }
+}
+
+/// Final output stage: apply master volume and optional SPU quantization
+/// to the mixed stereo buffers
+fn apply_output_stage(left: &mut [f32], right: &mut [f32], master_volume: f32, spu_quantize: bool) {
+    for sample in left.iter_mut().chain(right.iter_mut()) {
+        let mut value = *sample * master_volume;
+        if spu_quantize {
+            // Truncate (not round) like the hardware does
+            value = (value * SPU_QUANT_STEPS).trunc() / SPU_QUANT_STEPS;
+        }
+        *sample = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{channel, sync_channel};
+    use rustysynth::{SoundFont, SynthesizerSettings};
+    use super::*;
+    use super::super::pattern::Note;
+    use super::super::sequencer::SequencerSettings;
+
+    /// A second of a short song with the metronome on, rendered `chunk` samples at a time
+    fn render_song(chunk: usize) -> Vec<f32> {
+        let (commands, receiver) = channel();
+        let (returns, _replaced_songs) = sync_channel(REPLACED_SONGS);
+        let mut state = AudioState::new(receiver, returns);
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/soundfonts/TimGM6mb.sf2");
+        if let Some(soundfont) = std::fs::read(path).ok().and_then(|bytes| SoundFont::new(&mut bytes.as_slice()).ok()) {
+            let settings = SynthesizerSettings::new(SAMPLE_RATE as i32);
+            state.synth = Synthesizer::new(&Arc::new(soundfont), &settings).ok();
+        }
+
+        let mut song = Song::new();
+        song.bpm = 137; // Rows don't fall on whole samples
+        for (row, pitch) in [(0, 60), (3, 64), (6, 67), (9, 72)] {
+            song.patterns[0].channels[0][row] = Note::new(pitch, 0);
+        }
+        let settings = SequencerSettings { metronome: true, metronome_volume: 0.5, ..Default::default() };
+        for command in [
+            SequencerCommand::SetSong(Box::new(song)),
+            SequencerCommand::Settings(settings),
+            SequencerCommand::Play { pattern_idx: 0, row: 0, count_in_rows: 0 },
+        ] {
+            commands.send(command).unwrap();
+        }
+
+        let total = SAMPLE_RATE as usize;
+        let mut out = Vec::with_capacity(total * 2);
+        let (mut left, mut right) = (vec![0.0; chunk], vec![0.0; chunk]);
+        while out.len() < total * 2 {
+            let len = chunk.min(total - out.len() / 2);
+            state.render(&mut left[..len], &mut right[..len]);
+            out.extend(left[..len].iter().zip(&right[..len]).flat_map(|(&l, &r)| [l, r]));
+        }
+        out
+    }
+
+    #[test]
+    fn output_does_not_depend_on_buffer_size() {
+        let small = render_song(256);
+        let odd = render_song(1000);
+        assert!(small.iter().any(|&s| s != 0.0));
+        assert!(small == odd, "rows landed on different samples");
+    }
+}
@@ -0,0 +1,17 @@
+//! Music and sound playback without an audio device
+//!
+//! Tracker songs and their undo steps, the row sequencer, PCM sounds and the mixer
+//! the audio callback runs. The tracker UI and the device output live in the
+//! `bonnie-engine` binary.
+
+mod pattern;
+mod sequencer;
+mod pcm;
+mod mixer;
+mod undo;
+
+pub use pattern::*;
+pub use sequencer::*;
+pub use pcm::*;
+pub use mixer::*;
+pub use undo::*;
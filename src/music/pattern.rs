@@ -91,8 +91,11 @@ pub const DEFAULT_CHANNELS: usize = 4;
 /// Default pattern length (rows)
 pub const DEFAULT_PATTERN_LEN: usize = 64;
 
+/// Longest pattern (rows)
+pub const MAX_PATTERN_LEN: usize = 256;
+
 /// A pattern is a grid of notes across channels
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Pattern {
     /// Pattern length in rows
     pub length: usize,
@@ -106,7 +109,7 @@ impl Pattern {
     }
 
     pub fn with_channels(length: usize, num_channels: usize) -> Self {
-        let len = length.min(256);
+        let len = length.min(MAX_PATTERN_LEN);
         let ch_count = num_channels.clamp(1, MAX_CHANNELS);
         Self {
             length: len,
@@ -128,6 +131,15 @@ impl Pattern {
         }
     }
 
+    /// Change the number of rows (1 to `MAX_PATTERN_LEN`). New rows are empty;
+    /// rows past the new end are dropped.
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length.clamp(1, MAX_PATTERN_LEN);
+        for channel in &mut self.channels {
+            channel.resize(self.length, Note::EMPTY);
+        }
+    }
+
     /// Get the number of channels
     pub fn num_channels(&self) -> usize {
         self.channels.len()
@@ -155,7 +167,7 @@ impl Default for Pattern {
 }
 
 /// A song is a sequence of pattern indices (arrangement)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Song {
     /// Song name
     pub name: String,
@@ -169,8 +181,31 @@ pub struct Song {
     pub arrangement: Vec<usize>,
     /// Instrument names (for display)
     pub instrument_names: Vec<String>,
-    /// Per-channel instrument (GM program number 0-127)
+    /// Per-channel instrument (program number 0-127 within the channel's bank)
     pub channel_instruments: Vec<u8>,
+    /// Per-channel SF2 bank (0 = General MIDI, 128 = drum kits)
+    #[serde(default)]
+    pub channel_banks: Vec<u8>,
+    /// Starred instruments as (bank, program), for the instrument list's favorites filter
+    #[serde(default)]
+    pub favorite_instruments: Vec<(u8, u8)>,
+    /// Per-channel pan (-1.0 = left, 0.0 = center, 1.0 = right)
+    #[serde(default)]
+    pub channel_pan: Vec<f32>,
+    /// Master output volume (0.0-1.0)
+    #[serde(default = "default_master_volume")]
+    pub master_volume: f32,
+    /// Truncate output to 15-bit precision like the PS1 SPU
+    #[serde(default)]
+    pub spu_quantize: bool,
+    /// File name of the soundfont the song was written with (looked up in
+    /// `assets/soundfonts`); None for songs from before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub soundfont: Option<String>,
+}
+
+fn default_master_volume() -> f32 {
+    1.0
 }
 
 impl Song {
@@ -183,6 +218,12 @@ impl Song {
             arrangement: vec![0],
             instrument_names: Vec::new(),
             channel_instruments: vec![0; DEFAULT_CHANNELS], // Piano for all channels
+            channel_banks: vec![0; DEFAULT_CHANNELS],
+            favorite_instruments: Vec::new(),
+            channel_pan: vec![0.0; DEFAULT_CHANNELS],         // Center for all channels
+            master_volume: default_master_volume(),
+            spu_quantize: false,
+            soundfont: None,
         }
     }
 
@@ -195,6 +236,8 @@ impl Song {
     pub fn add_channel(&mut self) {
         if self.channel_instruments.len() < MAX_CHANNELS {
             self.channel_instruments.push(0); // Default to piano
+            self.channel_pan.resize(self.channel_instruments.len(), 0.0);
+            self.channel_banks.resize(self.channel_instruments.len(), 0);
             // Also add channel to all patterns
             for pattern in &mut self.patterns {
                 pattern.add_channel();
@@ -206,6 +249,8 @@ impl Song {
     pub fn remove_channel(&mut self) {
         if self.channel_instruments.len() > 1 {
             self.channel_instruments.pop();
+            self.channel_pan.truncate(self.channel_instruments.len());
+            self.channel_banks.truncate(self.channel_instruments.len());
             // Also remove channel from all patterns
             for pattern in &mut self.patterns {
                 pattern.remove_channel();
@@ -225,6 +270,53 @@ impl Song {
         self.channel_instruments.get(channel).copied().unwrap_or(0)
     }
 
+    /// Set the SF2 bank for a channel
+    pub fn set_channel_bank(&mut self, channel: usize, bank: u8) {
+        if channel < self.channel_instruments.len() {
+            // Songs saved before banks existed have an empty list
+            if self.channel_banks.len() < self.channel_instruments.len() {
+                self.channel_banks.resize(self.channel_instruments.len(), 0);
+            }
+            self.channel_banks[channel] = bank;
+        }
+    }
+
+    /// Get the SF2 bank for a channel (defaults to bank 0)
+    pub fn get_channel_bank(&self, channel: usize) -> u8 {
+        self.channel_banks.get(channel).copied().unwrap_or(0)
+    }
+
+    /// Is this instrument starred?
+    pub fn is_favorite(&self, bank: u8, program: u8) -> bool {
+        self.favorite_instruments.contains(&(bank, program))
+    }
+
+    /// Star or unstar an instrument
+    pub fn toggle_favorite(&mut self, bank: u8, program: u8) {
+        match self.favorite_instruments.iter().position(|&f| f == (bank, program)) {
+            Some(i) => {
+                self.favorite_instruments.remove(i);
+            }
+            None => self.favorite_instruments.push((bank, program)),
+        }
+    }
+
+    /// Set pan for a channel (-1.0 to 1.0)
+    pub fn set_channel_pan(&mut self, channel: usize, pan: f32) {
+        if channel < self.channel_instruments.len() {
+            // Songs saved before pan existed have an empty list
+            if self.channel_pan.len() < self.channel_instruments.len() {
+                self.channel_pan.resize(self.channel_instruments.len(), 0.0);
+            }
+            self.channel_pan[channel] = pan.clamp(-1.0, 1.0);
+        }
+    }
+
+    /// Get pan for a channel (defaults to center)
+    pub fn get_channel_pan(&self, channel: usize) -> f32 {
+        self.channel_pan.get(channel).copied().unwrap_or(0.0)
+    }
+
     /// Get the current pattern being edited
     pub fn current_pattern(&self, pattern_idx: usize) -> Option<&Pattern> {
         self.patterns.get(pattern_idx)
@@ -238,10 +330,15 @@ impl Song {
     /// Add a new pattern
     pub fn add_pattern(&mut self) -> usize {
         let idx = self.patterns.len();
-        self.patterns.push(Pattern::default());
+        self.patterns.push(Pattern::with_channels(DEFAULT_PATTERN_LEN, self.num_channels()));
         idx
     }
 
+    /// How many arrangement positions play this pattern
+    pub fn pattern_use_count(&self, pattern: usize) -> usize {
+        self.arrangement.iter().filter(|&&p| p == pattern).count()
+    }
+
     /// Calculate tick duration in seconds
     pub fn tick_duration(&self) -> f64 {
         60.0 / (self.bpm as f64 * self.rows_per_beat as f64)
@@ -269,6 +366,8 @@ pub enum Effect {
     Portamento(u8),
     /// Vibrato (4xy) - modulation wheel
     Vibrato(u8, u8),
+    /// Set panning (8xx) - 00=left, 80=center, FF=right
+    SetPanning(u8),
     /// Volume slide (Axy)
     VolumeSlide(u8, u8),
     /// Set volume (Cxx)
@@ -290,6 +389,11 @@ pub enum Effect {
 }
 
 impl Effect {
+    /// Convert an 8xx panning parameter to a normalized pan (-1.0 to 1.0)
+    pub fn panning_to_pan(param: u8) -> f32 {
+        ((param as f32 - 128.0) / 127.0).clamp(-1.0, 1.0)
+    }
+
     /// Parse effect from character and parameter
     pub fn from_char(c: char, param: u8) -> Self {
         match c.to_ascii_uppercase() {
@@ -298,6 +402,7 @@ impl Effect {
             '2' => Effect::SlideDown(param),
             '3' => Effect::Portamento(param),
             '4' => Effect::Vibrato(param >> 4, param & 0x0F),
+            '8' => Effect::SetPanning(param),
             'A' => Effect::VolumeSlide(param >> 4, param & 0x0F),
             'C' => Effect::SetVolume(param),
             'D' => Effect::PatternBreak(param),
@@ -320,6 +425,7 @@ impl Effect {
             Effect::SlideDown(_) => Some('2'),
             Effect::Portamento(_) => Some('3'),
             Effect::Vibrato(_, _) => Some('4'),
+            Effect::SetPanning(_) => Some('8'),
             Effect::VolumeSlide(_, _) => Some('A'),
             Effect::SetVolume(_) => Some('C'),
             Effect::PatternBreak(_) => Some('D'),
@@ -341,6 +447,7 @@ impl Effect {
             Effect::SlideDown(p) => *p,
             Effect::Portamento(p) => *p,
             Effect::Vibrato(x, y) => (x << 4) | y,
+            Effect::SetPanning(p) => *p,
             Effect::VolumeSlide(x, y) => (x << 4) | y,
             Effect::SetVolume(v) => *v,
             Effect::PatternBreak(r) => *r,
@@ -0,0 +1,221 @@
+//! PCM sounds and voices
+//!
+//! Sampled sounds (WAV files) played alongside the SF2 synth: ambient loops in game
+//! mode, for now. A `Voice` plays one sound at a gain that can change while it
+//! sounds; gain changes ramp over the next buffer so they don't click.
+
+use std::sync::Arc;
+use super::mixer::SAMPLE_RATE;
+
+/// Longest sound accepted (seconds at the file's own rate)
+const MAX_SOUND_SECONDS: usize = 120;
+
+/// A decoded sound: stereo frames at the sound's own sample rate
+#[derive(Debug, Clone)]
+pub struct PcmSound {
+    frames: Vec<[f32; 2]>,
+    sample_rate: u32,
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+impl PcmSound {
+    /// Decode a WAV file: PCM (8, 16 or 24-bit) or 32-bit float, mono or stereo
+    pub fn from_wav(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.get(0..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"WAVE") {
+            return Err("Not a WAV file".to_string());
+        }
+        let mut format = None;
+        let mut data = None;
+        let mut at = 12;
+        while let (Some(id), Some(len)) = (bytes.get(at..at + 4), read_u32(bytes, at + 4)) {
+            let start = at + 8;
+            let end = start.saturating_add(len as usize).min(bytes.len());
+            match id {
+                b"fmt " => {
+                    let chunk = &bytes[start..end];
+                    format = Some((
+                        read_u16(chunk, 0).ok_or("WAV format chunk is too short")?,
+                        read_u16(chunk, 2).ok_or("WAV format chunk is too short")?,
+                        read_u32(chunk, 4).ok_or("WAV format chunk is too short")?,
+                        read_u16(chunk, 14).ok_or("WAV format chunk is too short")?,
+                    ));
+                }
+                b"data" => data = Some(&bytes[start..end]),
+                _ => {}
+            }
+            // Chunks are padded to an even length
+            at = end + (len as usize & 1);
+        }
+        let (tag, channels, sample_rate, bits) = format.ok_or("WAV file has no format chunk")?;
+        let data = data.ok_or("WAV file has no data chunk")?;
+        if channels != 1 && channels != 2 {
+            return Err(format!("{} channel WAV files aren't supported (mono or stereo only)", channels));
+        }
+        if !(1000..=192_000).contains(&sample_rate) {
+            return Err(format!("Unsupported WAV sample rate {}", sample_rate));
+        }
+        // 1 = integer PCM, 3 = float; 0xFFFE (extensible) is read by its bit depth
+        let sample: fn(&[u8]) -> f32 = match (tag, bits) {
+            (1 | 0xFFFE, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+            (1 | 0xFFFE, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            (1 | 0xFFFE, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2147483648.0,
+            (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            _ => return Err(format!("Unsupported WAV encoding (format {}, {} bits)", tag, bits)),
+        };
+        let width = bits as usize / 8;
+        let frame_len = width * channels as usize;
+        let count = data.len() / frame_len;
+        if count > MAX_SOUND_SECONDS * sample_rate as usize {
+            return Err(format!("Sound is longer than {} seconds", MAX_SOUND_SECONDS));
+        }
+        let frames = data.chunks_exact(frame_len)
+            .map(|frame| {
+                let left = sample(&frame[..width]);
+                let right = if channels == 2 { sample(&frame[width..]) } else { left };
+                // Float files can hold anything
+                [left.clamp(-1.0, 1.0), right.clamp(-1.0, 1.0)]
+            })
+            .collect();
+        Ok(Self { frames, sample_rate })
+    }
+
+    /// Frame at a fractional position, linearly interpolated (`wrap` reads past the
+    /// end from the start, for loops)
+    fn frame_at(&self, position: f64, wrap: bool) -> [f32; 2] {
+        let len = self.frames.len();
+        let i = position as usize;
+        let t = (position - i as f64) as f32;
+        let a = self.frames[i % len];
+        let b = match (i + 1 < len, wrap) {
+            (true, _) => self.frames[i + 1],
+            (false, true) => self.frames[(i + 1) % len],
+            (false, false) => a,
+        };
+        [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+    }
+}
+
+/// Handle to a voice started on the audio engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VoiceId(pub(super) u64);
+
+/// A sound playing
+pub(super) struct Voice {
+    pub id: VoiceId,
+    sound: Arc<PcmSound>,
+    /// Playback position in the sound's frames
+    position: f64,
+    /// Sound frames per output sample (resamples to SAMPLE_RATE)
+    step: f64,
+    /// Gain at the start of the next buffer
+    gain: f32,
+    /// Gain to reach by the end of the next buffer
+    pub target_gain: f32,
+    looping: bool,
+}
+
+impl Voice {
+    pub fn new(id: VoiceId, sound: Arc<PcmSound>, gain: f32, looping: bool) -> Self {
+        let step = sound.sample_rate as f64 / SAMPLE_RATE as f64;
+        let gain = gain.clamp(0.0, 1.0);
+        Self { id, sound, position: 0.0, step, gain, target_gain: gain, looping }
+    }
+
+    /// Has a one-shot voice played to its end?
+    pub fn finished(&self) -> bool {
+        !self.looping && self.position >= self.sound.frames.len() as f64
+    }
+
+    /// Add the voice to the buffers, ramping from the current to the target gain
+    pub fn mix(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let len = self.sound.frames.len() as f64;
+        if len == 0.0 {
+            self.position = 0.0;
+            self.looping = false;
+            return;
+        }
+        let samples = left.len().min(right.len());
+        let ramp = (self.target_gain - self.gain) / samples.max(1) as f32;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            if self.finished() {
+                break;
+            }
+            self.gain += ramp;
+            let [fl, fr] = self.sound.frame_at(self.position, self.looping);
+            *l += fl * self.gain;
+            *r += fr * self.gain;
+            self.position += self.step;
+            if self.looping && self.position >= len {
+                self.position -= len;
+            }
+        }
+        self.gain = self.target_gain;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A WAV file of 16-bit samples
+    fn wav(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend((36 + data.len() as u32).to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(channels.to_le_bytes());
+        bytes.extend(sample_rate.to_le_bytes());
+        bytes.extend((sample_rate * channels as u32 * 2).to_le_bytes());
+        bytes.extend((channels * 2).to_le_bytes());
+        bytes.extend(16u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn wav_files_decode_to_stereo_frames() {
+        let mono = PcmSound::from_wav(&wav(1, 22050, &[0, 16384, -32768])).unwrap();
+        assert_eq!(mono.frames, [[0.0, 0.0], [0.5, 0.5], [-1.0, -1.0]]);
+        assert_eq!(mono.sample_rate, 22050);
+
+        let stereo = PcmSound::from_wav(&wav(2, SAMPLE_RATE, &[16384, -16384, 0, 8192])).unwrap();
+        assert_eq!(stereo.frames, [[0.5, -0.5], [0.0, 0.25]]);
+
+        assert!(PcmSound::from_wav(b"RIFF\0\0\0\0WAVE").is_err());
+        assert!(PcmSound::from_wav(&wav(3, SAMPLE_RATE, &[0, 0, 0])).is_err());
+        assert!(PcmSound::from_wav(b"not a sound").is_err());
+    }
+
+    #[test]
+    fn voices_loop_ramp_and_finish() {
+        let sound = Arc::new(PcmSound::from_wav(&wav(1, SAMPLE_RATE, &[16384, 16384, 16384])).unwrap());
+        let mix = |voice: &mut Voice, samples: usize| {
+            let (mut left, mut right) = (vec![0.0; samples], vec![0.0; samples]);
+            voice.mix(&mut left, &mut right);
+            left
+        };
+
+        let mut once = Voice::new(VoiceId(0), Arc::clone(&sound), 1.0, false);
+        assert_eq!(mix(&mut once, 5), [0.5, 0.5, 0.5, 0.0, 0.0]);
+        assert!(once.finished());
+
+        let mut looped = Voice::new(VoiceId(1), sound, 1.0, true);
+        assert_eq!(mix(&mut looped, 5), [0.5; 5]);
+        assert!(!looped.finished());
+        // A gain change ramps across the next buffer, then holds
+        looped.target_gain = 0.0;
+        assert_eq!(mix(&mut looped, 4), [0.375, 0.25, 0.125, 0.0]);
+        assert_eq!(mix(&mut looped, 2), [0.0, 0.0]);
+    }
+}
@@ -0,0 +1,388 @@
+//! Row sequencer - plays the song from inside the audio rendering path
+//!
+//! For each output buffer the engine asks how many samples are left until the next
+//! row boundary (from BPM, rows_per_beat and the sample rate), renders up to there,
+//! fires the row and carries on. Rows start on the same sample whatever the buffer
+//! size, so the groove doesn't jitter with the audio device's block size. The UI
+//! thread only sends `SequencerCommand`s through a queue and reads the position
+//! back for display.
+
+use super::mixer::SAMPLE_RATE;
+use super::pattern::{Effect, Song, MAX_CHANNELS};
+
+/// Beats per bar for the metronome accent and count-in (songs are 4/4)
+pub const BEATS_PER_BAR: usize = 4;
+
+/// Request from the UI thread, applied at the start of the next audio buffer
+#[derive(Debug, Clone)]
+pub enum SequencerCommand {
+    /// Start (or restart) at an arrangement position and row, after
+    /// `count_in_rows` rows of metronome clicks
+    Play { pattern_idx: usize, row: usize, count_in_rows: usize },
+    /// Stop and release every note
+    Stop,
+    /// Freeze in place with the notes cut (true) or carry on from there (false)
+    Hold(bool),
+    /// Change the tempo without resending the song
+    SetBpm(u16),
+    /// Song to play from now on (sent when the UI's copy has been edited)
+    SetSong(Box<Song>),
+    Settings(SequencerSettings),
+}
+
+/// Playback options that live in the UI but steer the sequencer
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SequencerSettings {
+    /// Arrangement position to keep replaying (pattern loop)
+    pub pattern_loop: Option<usize>,
+    /// Arrangement positions (start, end, inclusive) that playback wraps within
+    pub loop_region: Option<(usize, usize)>,
+    /// Click on every beat
+    pub metronome: bool,
+    pub metronome_volume: f32,
+}
+
+/// Synth work a row asks for, applied by the engine at the row's sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SequencerEvent {
+    /// Select the instrument, then start the note
+    NoteOn { channel: i32, key: i32, velocity: i32, bank: i32, program: i32 },
+    NoteOff { channel: i32, key: i32 },
+    /// Channel message: status nibble (0xB0 control change, 0xE0 pitch bend) and data
+    Midi { channel: i32, command: i32, data1: i32, data2: i32 },
+    /// Metronome click (higher pitched on the first beat of a bar)
+    Click { accent: bool, volume: f32 },
+    AllNotesOff,
+}
+
+/// Where playback is, as read back by the UI
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlaybackPosition {
+    pub playing: bool,
+    /// Row sounding now
+    pub pattern_idx: usize,
+    pub row: usize,
+    /// How far through that row (0-1)
+    pub fraction: f32,
+    /// Rows of count-in left before the first row plays
+    pub count_in_rows: usize,
+    /// Times playback stopped by itself (arrangement pointing at a missing pattern)
+    pub ends: u32,
+}
+
+/// Song position and timing, owned by the audio thread
+pub struct Sequencer {
+    song: Box<Song>,
+    settings: SequencerSettings,
+    playing: bool,
+    held: bool,
+    /// Next row to play
+    pattern_idx: usize,
+    row: usize,
+    count_in_rows: usize,
+    /// Row sounding now (pattern_idx, row)
+    shown: (usize, usize),
+    /// Samples rendered since Play
+    now: u64,
+    /// Sample (since Play) the next row starts on; fractional so long songs don't drift
+    next_row_at: f64,
+    /// Last note started per channel (the same note again sustains)
+    last_notes: [Option<u8>; MAX_CHANNELS],
+    ends: u32,
+}
+
+impl Sequencer {
+    pub fn new() -> Self {
+        Self {
+            song: Box::default(),
+            settings: SequencerSettings::default(),
+            playing: false,
+            held: false,
+            pattern_idx: 0,
+            row: 0,
+            count_in_rows: 0,
+            shown: (0, 0),
+            now: 0,
+            next_row_at: 0.0,
+            last_notes: [None; MAX_CHANNELS],
+            ends: 0,
+        }
+    }
+
+    /// Length of a row in samples at the current tempo
+    fn row_samples(&self) -> f64 {
+        SAMPLE_RATE as f64 * self.song.tick_duration()
+    }
+
+    /// Apply a command from the UI, pushing the synth work it needs. Returns the song
+    /// a `SetSong` replaced, for the caller to hand back so it isn't freed in the
+    /// audio callback.
+    pub fn apply(&mut self, command: SequencerCommand, events: &mut Vec<SequencerEvent>) -> Option<Box<Song>> {
+        match command {
+            SequencerCommand::Play { pattern_idx, row, count_in_rows } => {
+                events.push(SequencerEvent::AllNotesOff);
+                self.playing = true;
+                self.held = false;
+                self.pattern_idx = pattern_idx;
+                self.row = row;
+                self.count_in_rows = count_in_rows;
+                self.shown = (pattern_idx, row);
+                self.now = 0;
+                self.next_row_at = 0.0;
+                self.last_notes = [None; MAX_CHANNELS];
+            }
+            SequencerCommand::Stop => {
+                events.push(SequencerEvent::AllNotesOff);
+                self.playing = false;
+                self.count_in_rows = 0;
+                self.last_notes = [None; MAX_CHANNELS];
+            }
+            SequencerCommand::Hold(held) => {
+                if held && !self.held {
+                    events.push(SequencerEvent::AllNotesOff);
+                    self.last_notes = [None; MAX_CHANNELS];
+                }
+                self.held = held;
+            }
+            SequencerCommand::SetBpm(bpm) => self.song.bpm = bpm,
+            SequencerCommand::SetSong(song) => return Some(std::mem::replace(&mut self.song, song)),
+            SequencerCommand::Settings(settings) => self.settings = settings,
+        }
+        None
+    }
+
+    /// Samples until the next row starts (None while stopped or held)
+    pub fn samples_to_next_row(&self) -> Option<usize> {
+        if !self.playing || self.held {
+            return None;
+        }
+        Some((self.next_row_at.ceil() as u64).saturating_sub(self.now) as usize)
+    }
+
+    /// Count samples the engine rendered
+    pub fn advance(&mut self, samples: usize) {
+        if self.playing && !self.held {
+            self.now += samples as u64;
+        }
+    }
+
+    /// Play the row that is due (count-in click, notes, effects) and move on
+    pub fn fire_row(&mut self, events: &mut Vec<SequencerEvent>) {
+        self.next_row_at += self.row_samples();
+        if self.count_in_rows > 0 {
+            // Count-in lengths are whole bars, so rows left line up with the bar too
+            self.click_on_beat(self.count_in_rows, events);
+            self.count_in_rows -= 1;
+            return;
+        }
+        if self.settings.metronome {
+            self.click_on_beat(self.row, events);
+        }
+        self.shown = (self.pattern_idx, self.row);
+        self.play_row(events);
+        self.next_row(events);
+    }
+
+    /// Where playback is now
+    pub fn position(&self) -> PlaybackPosition {
+        let row_samples = self.row_samples();
+        let row_start = self.next_row_at - row_samples;
+        PlaybackPosition {
+            playing: self.playing,
+            pattern_idx: self.shown.0,
+            row: self.shown.1,
+            fraction: ((self.now as f64 - row_start) / row_samples).clamp(0.0, 1.0) as f32,
+            count_in_rows: self.count_in_rows,
+            ends: self.ends,
+        }
+    }
+
+    /// Metronome click if `row` (counted from the start of a bar) is on a beat,
+    /// accented on the first beat of the bar
+    fn click_on_beat(&self, row: usize, events: &mut Vec<SequencerEvent>) {
+        let rows_per_beat = self.song.rows_per_beat.max(1) as usize;
+        if row.is_multiple_of(rows_per_beat) {
+            events.push(SequencerEvent::Click {
+                accent: row.is_multiple_of(rows_per_beat * BEATS_PER_BAR),
+                volume: self.settings.metronome_volume.clamp(0.0, 1.0),
+            });
+        }
+    }
+
+    /// Notes and effects of the row at the playback position
+    fn play_row(&mut self, events: &mut Vec<SequencerEvent>) {
+        let Some(pattern) = self.song.arrangement.get(self.pattern_idx)
+            .and_then(|&n| self.song.patterns.get(n)) else {
+            return;
+        };
+
+        let mut tempo = None;
+        for channel in 0..self.song.num_channels().min(MAX_CHANNELS) {
+            let Some(note) = pattern.get(channel, self.row) else {
+                continue;
+            };
+            let ch = channel as i32;
+            match note.pitch {
+                Some(0xFF) => {
+                    // Note off
+                    if let Some(key) = self.last_notes[channel].take() {
+                        events.push(SequencerEvent::NoteOff { channel: ch, key: key as i32 });
+                    }
+                }
+                // Same note as the last one sustains (like Picotron), anything else triggers
+                Some(pitch) if self.last_notes[channel] != Some(pitch) => {
+                    let program = note.instrument.unwrap_or_else(|| self.song.get_channel_instrument(channel));
+                    events.push(SequencerEvent::NoteOn {
+                        channel: ch,
+                        key: pitch as i32,
+                        velocity: note.volume.unwrap_or(100) as i32,
+                        bank: self.song.get_channel_bank(channel) as i32,
+                        program: program as i32,
+                    });
+                    self.last_notes[channel] = Some(pitch);
+                }
+                _ => {}
+            }
+            if let (Some(fx_char), Some(fx_param)) = (note.effect, note.effect_param) {
+                tempo = effect_events(ch, Effect::from_char(fx_char, fx_param), events).or(tempo);
+            }
+        }
+        if let Some(bpm) = tempo {
+            // Tempo change for the rest of this playback
+            self.song.bpm = bpm;
+        }
+    }
+
+    /// Arrangement position to play after the one at `idx` finishes
+    fn next_arrangement_idx(&self, idx: usize) -> usize {
+        let len = self.song.arrangement.len();
+        if let Some(looped) = self.settings.pattern_loop.filter(|&i| i < len) {
+            return looped;
+        }
+        if let Some((start, end)) = self.settings.loop_region {
+            if start < len && idx == end.min(len - 1) {
+                return start;
+            }
+        }
+        if idx + 1 >= len { 0 } else { idx + 1 }
+    }
+
+    /// Step to the next row, wrapping into the next arrangement position. Stops if
+    /// the arrangement no longer points at a pattern.
+    fn next_row(&mut self, events: &mut Vec<SequencerEvent>) {
+        let Some(pattern_len) = self.song.arrangement.get(self.pattern_idx)
+            .and_then(|&n| self.song.patterns.get(n))
+            .map(|p| p.length) else {
+            self.playing = false;
+            self.ends += 1;
+            return;
+        };
+
+        self.row += 1;
+        if self.row >= pattern_len {
+            self.row = 0;
+            let next = self.next_arrangement_idx(self.pattern_idx);
+            if next != self.pattern_idx + 1 {
+                // Jumping back: don't let notes sustain across the wrap
+                for (channel, note) in self.last_notes.iter_mut().enumerate() {
+                    if let Some(key) = note.take() {
+                        events.push(SequencerEvent::NoteOff { channel: channel as i32, key: key as i32 });
+                    }
+                }
+            }
+            self.pattern_idx = next;
+        }
+    }
+}
+
+/// Controller messages for an effect, played at its row's sample. Returns the new
+/// tempo for a speed effect.
+fn effect_events(ch: i32, effect: Effect, events: &mut Vec<SequencerEvent>) -> Option<u16> {
+    let cc = |number: i32, value: i32| SequencerEvent::Midi { channel: ch, command: 0xB0, data1: number, data2: value.clamp(0, 127) };
+    let bend = |value: i32| SequencerEvent::Midi { channel: ch, command: 0xE0, data1: value & 0x7F, data2: (value >> 7) & 0x7F };
+    match effect {
+        Effect::None => {}
+        Effect::SetVolume(v) => events.push(cc(7, v as i32)),
+        Effect::SetPan(p) => events.push(cc(10, p as i32)),
+        Effect::SetPanning(p) => {
+            let pan = Effect::panning_to_pan(p);
+            events.push(cc(10, ((pan.clamp(-1.0, 1.0) + 1.0) * 63.5).round() as i32));
+        }
+        Effect::SetReverb(v) => events.push(cc(91, v as i32)),
+        Effect::SetChorus(v) => events.push(cc(93, v as i32)),
+        Effect::SetExpression(v) => events.push(cc(11, v as i32)),
+        Effect::SetModulation(v) => events.push(cc(1, v as i32)),
+        // Pitch bend around center (8192), amount * 64
+        Effect::SlideUp(amount) => events.push(bend((8192 + amount as i32 * 64).min(16383))),
+        Effect::SlideDown(amount) => events.push(bend((8192 - amount as i32 * 64).max(0))),
+        // Use modulation wheel for vibrato
+        Effect::Vibrato(_, depth) => events.push(cc(1, (depth as i32 * 8).min(127))),
+        Effect::SetSpeed(bpm) if bpm > 0 => return Some(bpm as u16),
+        Effect::SetSpeed(_) => {}
+        // TODO: Implement pattern break properly
+        Effect::PatternBreak(_) => {}
+        // Effects that need per-tick processing (not implemented yet)
+        Effect::Arpeggio(_, _) | Effect::Portamento(_) | Effect::VolumeSlide(_, _) => {}
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_start_on_their_sample() {
+        let mut song = Song::new();
+        song.bpm = 137; // Rows don't fall on whole samples
+        let row_samples = SAMPLE_RATE as f64 * song.tick_duration();
+
+        let mut sequencer = Sequencer::new();
+        let mut events = Vec::new();
+        sequencer.apply(SequencerCommand::SetSong(Box::new(song)), &mut events);
+        sequencer.apply(SequencerCommand::Play { pattern_idx: 0, row: 0, count_in_rows: 0 }, &mut events);
+
+        // Step in odd-sized blocks like an audio callback would
+        let mut now = 0;
+        let mut starts = Vec::new();
+        while starts.len() < 8 {
+            match sequencer.samples_to_next_row() {
+                Some(0) => {
+                    starts.push(now);
+                    sequencer.fire_row(&mut events);
+                }
+                Some(samples) => {
+                    let step = samples.min(333);
+                    sequencer.advance(step);
+                    now += step;
+                }
+                None => panic!("stopped playing"),
+            }
+        }
+        for (k, &start) in starts.iter().enumerate() {
+            assert_eq!(start, (k as f64 * row_samples).ceil() as usize);
+        }
+        assert_eq!(sequencer.position().row, 7);
+
+        // Held playback doesn't move
+        sequencer.apply(SequencerCommand::Hold(true), &mut events);
+        assert_eq!(sequencer.samples_to_next_row(), None);
+        sequencer.advance(10_000);
+        sequencer.apply(SequencerCommand::Hold(false), &mut events);
+        assert!(sequencer.samples_to_next_row().is_some_and(|n| n < row_samples as usize + 1));
+    }
+
+    #[test]
+    fn new_songs_hand_back_the_old_one() {
+        let mut sequencer = Sequencer::new();
+        let mut events = Vec::new();
+        let mut first = Song::new();
+        first.bpm = 90;
+        assert!(sequencer.apply(SequencerCommand::SetSong(Box::new(first)), &mut events).is_some());
+
+        // The song it replaced comes back to be freed by the caller; other commands return nothing
+        let replaced = sequencer.apply(SequencerCommand::SetSong(Box::default()), &mut events);
+        assert_eq!(replaced.map(|song| song.bpm), Some(90));
+        assert!(sequencer.apply(SequencerCommand::SetBpm(100), &mut events).is_none());
+    }
+}
@@ -0,0 +1,119 @@
+//! Tracker undo/redo
+//!
+//! Edits are kept as deltas (a cell before and after, the rows a shortened
+//! pattern lost) rather than song snapshots, since patterns can be large.
+//! Cursor and playback state are never part of an undo step.
+
+use super::pattern::{Note, Song};
+
+/// Maximum number of undo steps kept
+pub const MAX_UNDO: usize = 300;
+
+/// One reversible change to the song
+#[derive(Debug, Clone, PartialEq)]
+pub enum SongEdit {
+    /// A cell of `song.patterns[pattern]`
+    Cell { pattern: usize, channel: usize, row: usize, before: Note, after: Note },
+    /// Pattern resized; `cut` holds the rows a shortening dropped, per channel
+    PatternLength { pattern: usize, before: usize, after: usize, cut: Vec<Vec<Note>> },
+    /// The arrangement (a short list of pattern numbers, so stored whole)
+    Arrangement { before: Vec<usize>, after: Vec<usize> },
+    /// A copy of `song.patterns[source]` appended to the patterns, with the
+    /// arrangement change that uses it (the copy is made again on redo, so the
+    /// notes aren't stored)
+    PatternCopy { source: usize, before: Vec<usize>, after: Vec<usize> },
+    Bpm { before: u16, after: u16 },
+}
+
+impl SongEdit {
+    /// Redo (`forward`) or undo the edit
+    pub fn apply(&self, song: &mut Song, forward: bool) {
+        match self {
+            SongEdit::Cell { pattern, channel, row, before, after } => {
+                if let Some(p) = song.patterns.get_mut(*pattern) {
+                    p.set(*channel, *row, if forward { *after } else { *before });
+                }
+            }
+            SongEdit::PatternLength { pattern, before, after, cut } => {
+                let Some(p) = song.patterns.get_mut(*pattern) else { return };
+                if forward {
+                    p.set_length(*after);
+                    return;
+                }
+                p.set_length(*before);
+                for (rows, lost) in p.channels.iter_mut().zip(cut) {
+                    for (slot, note) in rows.iter_mut().skip(*after).zip(lost) {
+                        *slot = *note;
+                    }
+                }
+            }
+            SongEdit::Arrangement { before, after } => {
+                song.arrangement = if forward { after.clone() } else { before.clone() };
+            }
+            SongEdit::PatternCopy { source, before, after } => {
+                if forward {
+                    if let Some(copy) = song.patterns.get(*source).cloned() {
+                        song.patterns.push(copy);
+                    }
+                    song.arrangement = after.clone();
+                } else {
+                    song.patterns.pop();
+                    song.arrangement = before.clone();
+                }
+            }
+            SongEdit::Bpm { before, after } => {
+                song.bpm = if forward { *after } else { *before };
+            }
+        }
+    }
+}
+
+/// An edit on the undo/redo stack, labeled for the status bar
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    /// Human-readable description (e.g. "note C-4 at 0A/ch2")
+    pub label: String,
+    pub edit: SongEdit,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortening_a_pattern_restores_the_cut_rows() {
+        let mut song = Song::new();
+        song.patterns[0].set(1, 40, Note::new(60, 0));
+        let cut = song.patterns[0].channels.iter().map(|ch| ch[32..].to_vec()).collect();
+        let edit = SongEdit::PatternLength { pattern: 0, before: 64, after: 32, cut };
+
+        edit.apply(&mut song, true);
+        assert_eq!(song.patterns[0].channels[1].len(), 32);
+        edit.apply(&mut song, false);
+        assert_eq!(song.patterns[0].length, 64);
+        assert_eq!(song.patterns[0].get(1, 40), Some(&Note::new(60, 0)));
+    }
+
+    #[test]
+    fn pattern_copies_come_and_go_with_their_arrangement() {
+        let mut song = Song::new();
+        song.patterns[0].set(0, 4, Note::new(48, 2));
+        song.arrangement = vec![0, 0];
+        let edit = SongEdit::PatternCopy { source: 0, before: vec![0, 0], after: vec![0, 1] };
+
+        edit.apply(&mut song, true);
+        assert_eq!(song.patterns.len(), 2);
+        assert_eq!(song.patterns[1], song.patterns[0]);
+        assert_eq!(song.pattern_use_count(0), 1);
+        assert_eq!(song.pattern_use_count(1), 1);
+
+        // Editing the copy leaves the original alone
+        song.patterns[1].set(0, 4, Note::off());
+        assert_eq!(song.patterns[0].get(0, 4), Some(&Note::new(48, 2)));
+
+        edit.apply(&mut song, false);
+        assert_eq!(song.patterns.len(), 1);
+        assert_eq!(song.arrangement, vec![0, 0]);
+        assert_eq!(song.pattern_use_count(0), 2);
+    }
+}
@@ -0,0 +1,205 @@
+//! Indexed (CLUT) textures
+//!
+//! PS1 textures were mostly 4-bit or 8-bit images whose texels index a color lookup
+//! table of 16 or 256 colors. `Texture::quantized` reduces a texture to at most that
+//! many colors with a median-cut quantizer and stores it that way: one byte per texel
+//! plus the palette, a quarter of the direct RGBA size.
+
+use std::collections::HashMap;
+use super::{Color, Texture};
+
+/// Palette and per-texel indices of an indexed texture
+#[derive(Debug, Clone)]
+pub struct Clut {
+    /// At most 256 colors (16 for a 4-bit CLUT)
+    pub colors: Vec<Color>,
+    /// One palette index per texel, row by row
+    pub indices: Vec<u8>,
+}
+
+/// A box of colors in RGBA space, with how often each occurs
+struct ColorBox {
+    colors: Vec<([u8; 4], u32)>,
+}
+
+impl ColorBox {
+    /// (channel, extent) of the channel this box spans the most
+    fn widest_channel(&self) -> (usize, u8) {
+        (0..4)
+            .map(|c| {
+                let (lo, hi) = self.colors.iter()
+                    .fold((u8::MAX, u8::MIN), |(lo, hi), (color, _)| (lo.min(color[c]), hi.max(color[c])));
+                (c, hi.saturating_sub(lo))
+            })
+            .max_by_key(|&(_, extent)| extent)
+            .unwrap_or((0, 0))
+    }
+
+    /// Split at the weighted median of the widest channel (None if it's one color)
+    fn split(mut self) -> Result<(ColorBox, ColorBox), ColorBox> {
+        let (channel, extent) = self.widest_channel();
+        if extent == 0 || self.colors.len() < 2 {
+            return Err(self);
+        }
+        self.colors.sort_unstable_by_key(|(color, _)| color[channel]);
+        let total: u32 = self.colors.iter().map(|(_, n)| n).sum();
+        let mut seen = 0;
+        let mut at = self.colors.len() - 1;
+        for (i, (_, n)) in self.colors.iter().enumerate() {
+            seen += n;
+            if seen * 2 >= total {
+                at = i + 1;
+                break;
+            }
+        }
+        // Both halves keep at least one color
+        let at = at.clamp(1, self.colors.len() - 1);
+        let upper = self.colors.split_off(at);
+        Ok((self, ColorBox { colors: upper }))
+    }
+
+    /// Count-weighted average color
+    fn average(&self) -> Color {
+        let mut sum = [0u64; 4];
+        let mut total = 0u64;
+        for (color, n) in &self.colors {
+            for c in 0..4 {
+                sum[c] += color[c] as u64 * *n as u64;
+            }
+            total += *n as u64;
+        }
+        let avg = sum.map(|s| ((s + total / 2) / total.max(1)) as u8);
+        Color::with_alpha(avg[0], avg[1], avg[2], avg[3])
+    }
+}
+
+/// Squared RGBA distance
+fn distance(a: Color, b: Color) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.r, b.r) + d(a.g, b.g) + d(a.b, b.b) + d(a.a, b.a)
+}
+
+/// Median-cut palette of at most `max_colors` colors for `pixels`. Images that
+/// already fit keep their exact colors.
+pub fn median_cut(pixels: &[Color], max_colors: usize) -> Vec<Color> {
+    let max_colors = max_colors.clamp(1, 256);
+    let mut counts: HashMap<[u8; 4], u32> = HashMap::new();
+    for p in pixels {
+        *counts.entry([p.r, p.g, p.b, p.a]).or_insert(0) += 1;
+    }
+    let mut colors: Vec<([u8; 4], u32)> = counts.into_iter().collect();
+    // Deterministic order regardless of hashing
+    colors.sort_unstable();
+    if colors.len() <= max_colors {
+        return colors.iter().map(|(c, _)| Color::with_alpha(c[0], c[1], c[2], c[3])).collect();
+    }
+
+    // Keep splitting the box with the most texels until there are enough boxes
+    let mut boxes = vec![ColorBox { colors }];
+    let mut done = Vec::new();
+    while boxes.len() + done.len() < max_colors {
+        let Some(i) = (0..boxes.len())
+            .max_by_key(|&i| boxes[i].colors.iter().map(|(_, n)| n).sum::<u32>())
+        else {
+            break;
+        };
+        match boxes.swap_remove(i).split() {
+            Ok((a, b)) => {
+                boxes.push(a);
+                boxes.push(b);
+            }
+            Err(single) => done.push(single),
+        }
+    }
+    boxes.iter().chain(&done).map(ColorBox::average).collect()
+}
+
+impl Clut {
+    /// Index every pixel to its nearest color in `colors`
+    pub fn from_palette(pixels: &[Color], colors: Vec<Color>) -> Self {
+        let mut nearest: HashMap<[u8; 4], u8> = HashMap::new();
+        let indices = pixels.iter()
+            .map(|&p| *nearest.entry([p.r, p.g, p.b, p.a]).or_insert_with(|| {
+                (0..colors.len())
+                    .min_by_key(|&i| distance(p, colors[i]))
+                    .unwrap_or(0) as u8
+            }))
+            .collect();
+        Self { colors, indices }
+    }
+
+    /// Color of texel `i`
+    #[inline]
+    pub fn texel(&self, i: usize) -> Color {
+        self.colors[self.indices[i] as usize]
+    }
+}
+
+impl Texture {
+    /// Indexed copy of this texture with at most `max_colors` colors (16 or 256 for
+    /// the PS1 CLUT depths). Mip levels are rebuilt from the quantized image.
+    pub fn quantized(&self, max_colors: usize) -> Self {
+        let pixels = self.texels();
+        let clut = Clut::from_palette(&pixels, median_cut(&pixels, max_colors));
+        let texture = Self {
+            width: self.width,
+            height: self.height,
+            pixels: Vec::new(),
+            name: self.name.clone(),
+            mips: Vec::new(),
+            clut: Some(clut),
+        };
+        if self.mips.is_empty() { texture } else { texture.with_mips() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16x16 gradient with 256 distinct colors
+    fn gradient() -> Texture {
+        let mut texture = Texture::new(16, 16);
+        for (i, p) in texture.pixels.iter_mut().enumerate() {
+            *p = Color::new((i % 16 * 16) as u8, (i / 16 * 16) as u8, 128);
+        }
+        texture
+    }
+
+    #[test]
+    fn quantize_to_clut4() {
+        let texture = gradient();
+        let indexed = texture.quantized(16);
+        let clut = indexed.clut.as_ref().unwrap();
+        assert_eq!(clut.colors.len(), 16);
+        assert!(clut.indices.iter().all(|&i| (i as usize) < 16));
+        assert!(indexed.pixels.is_empty());
+
+        // Every texel lands near its source color, and sampling goes through the CLUT
+        for y in 0..16 {
+            for x in 0..16 {
+                let (a, b) = (texture.get_pixel(x, y), indexed.get_pixel(x, y));
+                assert!(distance(a, b) < 3 * 40 * 40, "{:?} -> {:?}", a, b);
+            }
+        }
+        let (u, v) = (5.5 / 16.0, 9.5 / 16.0);
+        assert_eq!(indexed.sample(u, v), indexed.get_pixel(5, 9));
+
+        // A byte per texel plus the palette
+        let (bytes, _) = indexed.memory_bytes();
+        assert_eq!(bytes, 256 + 16 * std::mem::size_of::<Color>());
+    }
+
+    #[test]
+    fn few_colors_are_kept_exactly() {
+        let texture = Texture::checkerboard(8, 8, Color::RED, Color::with_alpha(0, 0, 0, 0));
+        let indexed = texture.clone().with_mips().quantized(256);
+        assert_eq!(indexed.clut.as_ref().unwrap().colors.len(), 2);
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(indexed.get_pixel(x, y), texture.get_pixel(x, y));
+            }
+        }
+        assert_eq!(indexed.mips.len(), 3);
+    }
+}
@@ -108,35 +108,57 @@ pub fn perspective_transform(v: Vec3, cam_x: Vec3, cam_y: Vec3, cam_z: Vec3) ->
     }
 }
 
+/// Default field of view in degrees, across the shorter side of the screen
+/// (2·atan(1/3), matching the engine's original fixed projection)
+pub const DEFAULT_FOV_DEGREES: f32 = 36.87;
+
+/// Field of view range accepted from settings and level files
+pub const MIN_FOV_DEGREES: f32 = 10.0;
+pub const MAX_FOV_DEGREES: f32 = 170.0;
+
+/// Default near clipping distance (camera space)
+pub const DEFAULT_NEAR_PLANE: f32 = 0.1;
+
+/// Screen scale for a field of view: pixels per unit of x/z, so a point at
+/// camera-space (x, y, z) lands `x / z * focal` pixels from the screen center.
+/// The FOV spans the shorter side of the screen.
+pub fn focal_length(fov_degrees: f32, width: usize, height: usize) -> f32 {
+    let half_fov = (fov_degrees.clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES) * 0.5).to_radians();
+    (width.min(height) as f32 / 2.0) / half_fov.tan()
+}
+
+/// How camera space maps to the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Perspective divide, with the scale from `focal_length`
+    Perspective(f32),
+    /// Parallel projection: pixels per world unit, whatever the depth
+    Orthographic(f32),
+}
+
 /// Project a 3D point to 2D screen coordinates
 /// If `snap` is true, coordinates are floored to integers (PS1 jitter effect)
 /// Returns Vec3 where x,y are screen coords and z is the ORIGINAL camera-space depth
-/// (needed for perspective-correct texture interpolation)
-pub fn project(v: Vec3, snap: bool, width: usize, height: usize) -> Vec3 {
-    const DISTANCE: f32 = 5.0;
-    const SCALE: f32 = 0.75;
-
-    let ud = DISTANCE;
-    let us = ud - 1.0;
-    let vs = (width.min(height) as f32 / 2.0) * SCALE;
-
-    // Perspective divide
-    let denom = v.z + ud;
-    if denom.abs() < 0.001 {
-        return Vec3::new(width as f32 / 2.0, height as f32 / 2.0, v.z);
-    }
+/// (needed for perspective-correct texture interpolation).
+/// For perspective, `unproject` is the inverse.
+pub fn project(v: Vec3, snap: bool, width: usize, height: usize, projection: Projection) -> Vec3 {
+    let (x, y) = match projection {
+        Projection::Perspective(focal) => {
+            // Perspective divide
+            if v.z.abs() < 0.001 {
+                return Vec3::new(width as f32 / 2.0, height as f32 / 2.0, v.z);
+            }
+            (v.x / v.z * focal, v.y / v.z * focal)
+        }
+        Projection::Orthographic(scale) => (v.x * scale, v.y * scale),
+    };
 
     let mut result = Vec3 {
-        x: (v.x * us) / denom,
-        y: (v.y * us) / denom,
+        x: x + (width as f32 / 2.0),
+        y: y + (height as f32 / 2.0),
         z: v.z, // Store ORIGINAL camera-space Z for perspective-correct interpolation
     };
 
-    // Scale to screen
-    result.x = result.x * vs + (width as f32 / 2.0);
-    result.y = result.y * vs + (height as f32 / 2.0);
-    // z stays as original camera-space depth
-
     // PS1 vertex snapping
     if snap {
         result.x = result.x.floor();
@@ -146,6 +168,15 @@ pub fn project(v: Vec3, snap: bool, width: usize, height: usize) -> Vec3 {
     result
 }
 
+/// Inverse of `project`: camera-space direction (at z = 1) through a screen point
+pub fn unproject(screen_x: f32, screen_y: f32, width: usize, height: usize, focal: f32) -> Vec3 {
+    Vec3::new(
+        (screen_x - width as f32 / 2.0) / focal,
+        (screen_y - height as f32 / 2.0) / focal,
+        1.0,
+    )
+}
+
 /// Calculate barycentric coordinates for point p in triangle (v1, v2, v3)
 /// Returns (u, v, w) where u + v + w = 1 if point is inside triangle
 pub fn barycentric(p: Vec3, v1: Vec3, v2: Vec3, v3: Vec3) -> Vec3 {
@@ -210,49 +241,35 @@ pub fn ray_triangle_intersect(
     }
 }
 
-/// Generate a ray from screen coordinates through the camera
-/// Returns (ray_origin, ray_direction)
-/// screen_x, screen_y: pixel coordinates
-/// screen_width, screen_height: framebuffer dimensions
-/// camera: the camera to cast from
-pub fn screen_to_ray(
-    screen_x: f32,
-    screen_y: f32,
-    screen_width: usize,
-    screen_height: usize,
-    cam_pos: Vec3,
-    cam_x: Vec3,
-    cam_y: Vec3,
-    cam_z: Vec3,
-) -> (Vec3, Vec3) {
-    // Reverse the projection math from project()
-    const DISTANCE: f32 = 5.0;
-    const SCALE: f32 = 0.75;
-
-    let vs = (screen_width.min(screen_height) as f32 / 2.0) * SCALE;
-
-    // Convert screen coordinates to normalized device coordinates
-    let ndc_x = (screen_x - screen_width as f32 / 2.0) / vs;
-    let ndc_y = (screen_y - screen_height as f32 / 2.0) / vs;
-
-    // The ray direction in camera space
-    // At z=1 (unit distance in front of camera), the point would be at (ndc_x, ndc_y, 1)
-    let cam_space_dir = Vec3::new(ndc_x, ndc_y, 1.0).normalize();
-
-    // Transform ray direction from camera space to world space
-    let world_dir = Vec3::new(
-        cam_space_dir.x * cam_x.x + cam_space_dir.y * cam_y.x + cam_space_dir.z * cam_z.x,
-        cam_space_dir.x * cam_x.y + cam_space_dir.y * cam_y.y + cam_space_dir.z * cam_z.y,
-        cam_space_dir.x * cam_x.z + cam_space_dir.y * cam_y.z + cam_space_dir.z * cam_z.z,
-    ).normalize();
-
-    (cam_pos, world_dir)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_unproject_inverts_project() {
+        for fov in [30.0, DEFAULT_FOV_DEGREES, 90.0] {
+            let focal = focal_length(fov, 320, 240);
+            let p = Vec3::new(-300.0, 120.0, 900.0);
+            let screen = project(p, false, 320, 240, Projection::Perspective(focal));
+            let dir = unproject(screen.x, screen.y, 320, 240, focal);
+            assert!((dir.x * p.z - p.x).abs() < 0.01);
+            assert!((dir.y * p.z - p.y).abs() < 0.01);
+        }
+        // Edge of the shorter side is half the FOV off-axis
+        let focal = focal_length(90.0, 320, 240);
+        assert!((project(Vec3::new(0.0, 1.0, 1.0), false, 320, 240, Projection::Perspective(focal)).y - 240.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn orthographic_projection_ignores_depth() {
+        let ortho = Projection::Orthographic(0.5);
+        let near = project(Vec3::new(100.0, -40.0, 10.0), false, 320, 240, ortho);
+        let far = project(Vec3::new(100.0, -40.0, 5000.0), false, 320, 240, ortho);
+        assert_eq!((near.x, near.y), (210.0, 100.0));
+        assert_eq!((far.x, far.y), (near.x, near.y));
+        assert_eq!(far.z, 5000.0);
+    }
+
     #[test]
     fn test_vec3_dot() {
         let a = Vec3::new(1.0, 2.0, 3.0);
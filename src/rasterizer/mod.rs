@@ -6,14 +6,20 @@
 //! - Vertex snapping (integer coords = PS1 jitter)
 //! - Flat and Gouraud shading
 //! - Z-buffer or painter's algorithm
+//! - Bitmap text for in-scene labels
+//! - Indexed (CLUT) textures with a median-cut quantizer
 
 mod math;
 mod types;
 mod render;
+mod text;
+mod clut;
 
 pub use math::*;
 pub use types::*;
 pub use render::*;
+pub use text::*;
+pub use clut::*;
 
 /// Screen dimensions (authentic PS1 resolution)
 pub const WIDTH: usize = 320;
@@ -1,8 +1,11 @@
 //! Core rendering functions
 //! Triangle rasterization with PS1-style effects
 
-use super::math::{barycentric, perspective_transform, project, Vec3};
-use super::types::{BlendMode, Color, Face, RasterSettings, ShadingMode, Texture, Vertex};
+use super::math::{
+    barycentric, focal_length, perspective_transform, project, unproject, Projection, Vec3,
+    DEFAULT_FOV_DEGREES, DEFAULT_NEAR_PLANE,
+};
+use super::types::{BlendMode, Color, DebugView, Face, RasterSettings, ShadingMode, SortKey, Texture, Vertex};
 
 /// Framebuffer for software rendering
 pub struct Framebuffer {
@@ -10,6 +13,15 @@ pub struct Framebuffer {
     pub zbuffer: Vec<f32>,  // Depth buffer
     pub width: usize,
     pub height: usize,
+    /// Bounds (inclusive) of the pixels drawn since the last clear; empty when min > max.
+    /// Only the `set_pixel*` paths are tracked, not writes to `pixels` from outside.
+    dirty_min: (usize, usize),
+    dirty_max: (usize, usize),
+    /// Color of the last full clear, while the rest of the buffer still holds it
+    background: Option<Color>,
+    /// Triangle pixel writes per pixel since the last clear, for the overdraw debug
+    /// view (empty while it's off)
+    overdraw: Vec<u16>,
 }
 
 impl Framebuffer {
@@ -19,6 +31,10 @@ impl Framebuffer {
             zbuffer: vec![f32::MAX; width * height],
             width,
             height,
+            dirty_min: (usize::MAX, usize::MAX),
+            dirty_max: (0, 0),
+            background: None,
+            overdraw: Vec::new(),
         }
     }
 
@@ -28,22 +44,113 @@ impl Framebuffer {
             self.height = height;
             self.pixels = vec![0; width * height * 4];
             self.zbuffer = vec![f32::MAX; width * height];
+            self.background = None;
+            self.overdraw.clear();
         }
     }
 
     pub fn clear(&mut self, color: Color) {
-        for i in 0..(self.width * self.height) {
-            let bytes = color.to_bytes();
-            self.pixels[i * 4] = bytes[0];
-            self.pixels[i * 4 + 1] = bytes[1];
-            self.pixels[i * 4 + 2] = bytes[2];
-            self.pixels[i * 4 + 3] = bytes[3];
-            self.zbuffer[i] = f32::MAX;
+        let bytes = color.to_bytes();
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&bytes);
+        }
+        self.zbuffer.fill(f32::MAX);
+        self.overdraw.fill(0);
+        self.background = Some(color);
+        self.reset_dirty();
+    }
+
+    /// Clear a rectangle of color and depth (clipped to the framebuffer)
+    pub fn clear_region(&mut self, x: usize, y: usize, w: usize, h: usize, color: Color) {
+        let (x1, y1) = ((x + w).min(self.width), (y + h).min(self.height));
+        if x >= x1 || y >= y1 {
+            return;
+        }
+        let bytes = color.to_bytes();
+        for row in y..y1 {
+            let start = row * self.width;
+            for pixel in self.pixels[(start + x) * 4..(start + x1) * 4].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&bytes);
+            }
+            self.zbuffer[start + x..start + x1].fill(f32::MAX);
+            if !self.overdraw.is_empty() {
+                self.overdraw[start + x..start + x1].fill(0);
+            }
+        }
+    }
+
+    /// Clear only what was drawn since the last clear, when the rest of the buffer
+    /// already holds `color`; otherwise clear everything. The result is the same as
+    /// `clear` as long as all drawing went through the framebuffer's own methods.
+    pub fn clear_dirty(&mut self, color: Color) {
+        if self.background != Some(color) {
+            self.clear(color);
+            return;
+        }
+        if self.dirty_min.0 <= self.dirty_max.0 {
+            let (x0, y0) = self.dirty_min;
+            let (x1, y1) = self.dirty_max;
+            self.clear_region(x0, y0, x1 - x0 + 1, y1 - y0 + 1, color);
+        }
+        self.reset_dirty();
+    }
+
+    fn reset_dirty(&mut self) {
+        self.dirty_min = (usize::MAX, usize::MAX);
+        self.dirty_max = (0, 0);
+    }
+
+    #[inline]
+    fn mark_dirty(&mut self, x: usize, y: usize) {
+        self.dirty_min = (self.dirty_min.0.min(x), self.dirty_min.1.min(y));
+        self.dirty_max = (self.dirty_max.0.max(x), self.dirty_max.1.max(y));
+    }
+
+    /// Start counting triangle pixel writes (overdraw debug view), from zero
+    fn count_overdraw(&mut self) {
+        if self.overdraw.is_empty() {
+            self.overdraw = vec![0; self.width * self.height];
+        }
+    }
+
+    /// Replace the picture with `view`'s visualization, once all meshes are drawn:
+    /// overdraw counts or depth. Other views are drawn as the triangles go (or are
+    /// off), and stop the overdraw counting.
+    pub fn apply_debug_view(&mut self, view: DebugView) {
+        match view {
+            DebugView::Overdraw if !self.overdraw.is_empty() => {
+                for (pixel, &count) in self.pixels.chunks_exact_mut(4).zip(&self.overdraw) {
+                    let color = match count {
+                        0 => Color::new(20, 20, 28),
+                        n => heat_color((n - 1) as f32 / 5.0),
+                    };
+                    pixel.copy_from_slice(&color.to_bytes());
+                }
+            }
+            DebugView::Depth => {
+                let drawn = || self.zbuffer.iter().copied().filter(|&z| z < f32::MAX);
+                let near = drawn().fold(f32::MAX, f32::min);
+                let far = drawn().fold(0.0, f32::max);
+                let range = (far - near).max(1.0);
+                for (pixel, &z) in self.pixels.chunks_exact_mut(4).zip(&self.zbuffer) {
+                    let level = if z < f32::MAX { (255.0 - 235.0 * (z - near) / range) as u8 } else { 0 };
+                    pixel.copy_from_slice(&Color::new(level, level, level).to_bytes());
+                }
+            }
+            _ => {
+                self.overdraw = Vec::new();
+                return;
+            }
+        }
+        if self.width > 0 && self.height > 0 {
+            self.mark_dirty(0, 0);
+            self.mark_dirty(self.width - 1, self.height - 1);
         }
     }
 
     pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
         if x < self.width && y < self.height {
+            self.mark_dirty(x, y);
             let idx = (y * self.width + x) * 4;
             let bytes = color.to_bytes();
             self.pixels[idx] = bytes[0];
@@ -56,6 +163,7 @@ impl Framebuffer {
     /// Set pixel with PS1-style blending
     pub fn set_pixel_blended(&mut self, x: usize, y: usize, color: Color, mode: BlendMode) {
         if x < self.width && y < self.height {
+            self.mark_dirty(x, y);
             let idx = (y * self.width + x) * 4;
 
             // Read existing pixel (back)
@@ -81,6 +189,7 @@ impl Framebuffer {
             let idx = y * self.width + x;
             if z < self.zbuffer[idx] {
                 self.zbuffer[idx] = z;
+                self.mark_dirty(x, y);
                 let pixel_idx = idx * 4;
                 let bytes = color.to_bytes();
                 self.pixels[pixel_idx] = bytes[0];
@@ -248,6 +357,14 @@ pub struct Camera {
     pub basis_x: Vec3,
     pub basis_y: Vec3,
     pub basis_z: Vec3,
+
+    /// Field of view in degrees (across the shorter screen side)
+    pub fov_degrees: f32,
+    /// Near clipping distance: anything closer is not drawn or picked
+    pub near_plane: f32,
+    /// Orthographic projection scaled to match the perspective view at this distance
+    /// in front of the camera (None = perspective)
+    pub ortho_distance: Option<f32>,
 }
 
 impl Camera {
@@ -259,14 +376,15 @@ impl Camera {
             basis_x: Vec3::new(1.0, 0.0, 0.0),
             basis_y: Vec3::new(0.0, 1.0, 0.0),
             basis_z: Vec3::new(0.0, 0.0, 1.0),
+            fov_degrees: DEFAULT_FOV_DEGREES,
+            near_plane: DEFAULT_NEAR_PLANE,
+            ortho_distance: None,
         };
         cam.update_basis();
         cam
     }
 
     pub fn update_basis(&mut self) {
-        let upward = Vec3::new(0.0, -1.0, 0.0);  // Use -Y as up to match screen coordinates
-
         // Forward vector based on rotation
         self.basis_z = Vec3 {
             x: self.rotation_x.cos() * self.rotation_y.sin(),
@@ -274,13 +392,60 @@ impl Camera {
             z: self.rotation_x.cos() * self.rotation_y.cos(),
         };
 
-        // Right vector
-        self.basis_x = upward.cross(self.basis_z).normalize();
+        // Right vector: up (-Y, to match screen coordinates) × forward, written out
+        // so it holds looking straight up or down too
+        self.basis_x = Vec3::new(-self.rotation_y.cos(), 0.0, self.rotation_y.sin());
 
         // Up vector
         self.basis_y = self.basis_z.cross(self.basis_x);
     }
 
+    /// Screen scale for this camera's FOV at a framebuffer size
+    pub fn focal_length(&self, width: usize, height: usize) -> f32 {
+        focal_length(self.fov_degrees, width, height)
+    }
+
+    /// Projection at a framebuffer size: orthographic at the scale perspective has
+    /// at `ortho_distance`, if set
+    pub fn projection(&self, width: usize, height: usize) -> Projection {
+        let focal = self.focal_length(width, height);
+        match self.ortho_distance {
+            Some(distance) => Projection::Orthographic(focal / distance.max(1.0)),
+            None => Projection::Perspective(focal),
+        }
+    }
+
+    /// Project a world-space point to framebuffer coordinates (None if in front of
+    /// the near plane). Same projection as `render_mesh`.
+    pub fn world_to_screen(&self, world_pos: Vec3, width: usize, height: usize) -> Option<(f32, f32)> {
+        let cam = perspective_transform(world_pos - self.position, self.basis_x, self.basis_y, self.basis_z);
+        if cam.z < self.near_plane {
+            return None;
+        }
+        let p = project(cam, false, width, height, self.projection(width, height));
+        Some((p.x, p.y))
+    }
+
+    /// World-space ray (origin, direction) through a framebuffer pixel, with the
+    /// projection used to render. Orthographic rays all point straight ahead,
+    /// starting from the point on the camera plane under the pixel.
+    pub fn screen_to_ray(&self, screen_x: f32, screen_y: f32, width: usize, height: usize) -> (Vec3, Vec3) {
+        let focal = match self.projection(width, height) {
+            Projection::Perspective(focal) => focal,
+            Projection::Orthographic(scale) => {
+                let offset_x = (screen_x - width as f32 / 2.0) / scale;
+                let offset_y = (screen_y - height as f32 / 2.0) / scale;
+                let origin = self.position + self.basis_x * offset_x + self.basis_y * offset_y;
+                return (origin, self.basis_z.normalize());
+            }
+        };
+
+        // The ray direction in camera space, transformed to world space
+        let dir = unproject(screen_x, screen_y, width, height, focal);
+        let world_dir = self.basis_x * dir.x + self.basis_y * dir.y + self.basis_z * dir.z;
+        (self.position, world_dir.normalize())
+    }
+
     pub fn rotate(&mut self, dx: f32, dy: f32) {
         self.rotation_y += dy;
         self.rotation_x = (self.rotation_x + dx).clamp(
@@ -308,10 +473,43 @@ struct Surface {
     pub uv1: super::math::Vec2,
     pub uv2: super::math::Vec2,
     pub uv3: super::math::Vec2,
+    pub c1: Color, // Vertex colors (tint)
+    pub c2: Color,
+    pub c3: Color,
     pub normal: Vec3, // Face normal (camera space)
     pub face_idx: usize,
 }
 
+/// Painter's algorithm depth of a surface by `key`, and its farthest depth (tie-break)
+fn sort_depth(surface: &Surface, key: SortKey) -> (f32, f32) {
+    let (z1, z2, z3) = (surface.v1.z, surface.v2.z, surface.v3.z);
+    let max_z = z1.max(z2).max(z3);
+    let depth = match key {
+        SortKey::MaxZ => max_z,
+        SortKey::Centroid => (z1 + z2 + z3) / 3.0,
+        SortKey::MinZ => z1.min(z2).min(z3),
+    };
+    (depth, max_z)
+}
+
+/// Debug view ramp from `t` = 0 (cold: blue) through green and yellow to 1 (hot: red)
+fn heat_color(t: f32) -> Color {
+    const STOPS: [(u8, u8, u8); 4] = [(40, 60, 220), (40, 200, 80), (230, 220, 40), (230, 40, 30)];
+    let t = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let i = (t as usize).min(STOPS.len() - 2);
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    let f = t - i as f32;
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * f).round() as u8;
+    Color::new(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// Triangle density debug color of a triangle covering `area` screen pixels: red
+/// below 8 pixels, a step cooler every 4x larger, blue from 2048
+fn density_color(area: f32) -> Color {
+    let bucket = (area.max(1.0) / 8.0).log(4.0).ceil().clamp(0.0, 5.0);
+    heat_color(1.0 - bucket / 5.0)
+}
+
 /// Calculate shading intensity for a normal
 fn shade_intensity(normal: Vec3, light_dir: Vec3, ambient: f32) -> f32 {
     let diffuse = normal.dot(light_dir).max(0.0);
@@ -347,12 +545,39 @@ fn apply_dither(color: Color, x: usize, y: usize) -> Color {
     Color::with_alpha(r, g, b, color.a)
 }
 
+/// Mip level for a whole triangle: log2 of how many texels one screen pixel steps
+/// over, from the triangle's screen-space UV derivatives (its larger axis)
+fn mip_level(surface: &Surface, texture: &Texture) -> usize {
+    let (e1x, e1y) = (surface.v2.x - surface.v1.x, surface.v2.y - surface.v1.y);
+    let (e2x, e2y) = (surface.v3.x - surface.v1.x, surface.v3.y - surface.v1.y);
+    let det = e1x * e2y - e2x * e1y;
+    if det.abs() < 1e-6 {
+        return 0;
+    }
+    // Texel deltas along the triangle's edges
+    let w = texture.width as f32;
+    let h = texture.height as f32;
+    let (du1, dv1) = ((surface.uv2.x - surface.uv1.x) * w, (surface.uv2.y - surface.uv1.y) * h);
+    let (du2, dv2) = ((surface.uv3.x - surface.uv1.x) * w, (surface.uv3.y - surface.uv1.y) * h);
+    let dudx = (du1 * e2y - du2 * e1y) / det;
+    let dvdx = (dv1 * e2y - dv2 * e1y) / det;
+    let dudy = (du2 * e1x - du1 * e2x) / det;
+    let dvdy = (dv2 * e1x - dv1 * e2x) / det;
+    let rho = (dudx * dudx + dvdx * dvdx).max(dudy * dudy + dvdy * dvdy).sqrt();
+    if rho <= 1.0 {
+        0
+    } else {
+        (rho.log2().floor() as usize).min(texture.mips.len())
+    }
+}
+
 /// Rasterize a single triangle
 fn rasterize_triangle(
     fb: &mut Framebuffer,
     surface: &Surface,
     texture: Option<&Texture>,
     settings: &RasterSettings,
+    light_dir: Vec3,
 ) {
     // Bounding box
     let min_x = surface.v1.x.min(surface.v2.x).min(surface.v3.x).max(0.0) as usize;
@@ -360,9 +585,31 @@ fn rasterize_triangle(
     let min_y = surface.v1.y.min(surface.v2.y).min(surface.v3.y).max(0.0) as usize;
     let max_y = (surface.v1.y.max(surface.v2.y).max(surface.v3.y) + 1.0).min(fb.height as f32) as usize;
 
+    let depth = settings.color_depth;
+
+    // One mip level for the whole triangle (0 = full size)
+    let mip = match texture {
+        Some(tex) if settings.mipmapping && !tex.mips.is_empty() => mip_level(surface, tex),
+        _ => 0,
+    };
+
+    // Skip tint interpolation for untinted triangles (the common case)
+    let tinted = surface.c1 != Color::NEUTRAL || surface.c2 != Color::NEUTRAL || surface.c3 != Color::NEUTRAL;
+
+    // Triangle density view: one color for the triangle, by its screen area
+    let density = (settings.debug_view == DebugView::TriangleDensity).then(|| {
+        let (e1x, e1y) = (surface.v2.x - surface.v1.x, surface.v2.y - surface.v1.y);
+        let (e2x, e2y) = (surface.v3.x - surface.v1.x, surface.v3.y - surface.v1.y);
+        density_color(0.5 * (e1x * e2y - e2x * e1y).abs())
+    });
+    let count_overdraw = settings.debug_view == DebugView::Overdraw;
+    if count_overdraw {
+        fb.count_overdraw();
+    }
+
     // Pre-calculate flat shading if needed
     let flat_shade = if settings.shading == ShadingMode::Flat {
-        shade_intensity(surface.normal, settings.light_dir, settings.ambient)
+        shade_intensity(surface.normal, light_dir, settings.ambient)
     } else {
         1.0
     };
@@ -411,39 +658,91 @@ fn rasterize_triangle(
 
                 // Sample texture or use white
                 let mut color = if let Some(tex) = texture {
-                    tex.sample(u, 1.0 - v)
+                    depth.quantize(tex.sample_mip(u, 1.0 - v, mip))
                 } else {
                     Color::WHITE
                 };
 
+                // Apply vertex color (Gouraud-interpolated tint)
+                if tinted {
+                    let lerp = |a: u8, b: u8, c: u8| (bc.x * a as f32 + bc.y * b as f32 + bc.z * c as f32).clamp(0.0, 255.0) as u8;
+                    let tint = Color::new(
+                        lerp(surface.c1.r, surface.c2.r, surface.c3.r),
+                        lerp(surface.c1.g, surface.c2.g, surface.c3.g),
+                        lerp(surface.c1.b, surface.c2.b, surface.c3.b),
+                    );
+                    color = depth.quantize(color.modulate(tint));
+                }
+
                 // Apply shading
                 let shade = match settings.shading {
                     ShadingMode::None => 1.0,
                     ShadingMode::Flat => flat_shade,
                     ShadingMode::Gouraud => {
                         // Interpolate per-vertex shading
-                        let s1 = shade_intensity(surface.vn1, settings.light_dir, settings.ambient);
-                        let s2 = shade_intensity(surface.vn2, settings.light_dir, settings.ambient);
-                        let s3 = shade_intensity(surface.vn3, settings.light_dir, settings.ambient);
+                        let s1 = shade_intensity(surface.vn1, light_dir, settings.ambient);
+                        let s2 = shade_intensity(surface.vn2, light_dir, settings.ambient);
+                        let s3 = shade_intensity(surface.vn3, light_dir, settings.ambient);
                         bc.x * s1 + bc.y * s2 + bc.z * s3
                     }
                 };
 
-                color = color.shade(shade);
+                color = density.unwrap_or(color).shade(shade);
 
-                // Apply PS1-style ordered dithering
+                // Apply PS1-style ordered dithering (already 15-bit), or truncate
+                // to the frame buffer's depth
                 if settings.dithering {
                     color = apply_dither(color, x, y);
+                } else {
+                    color = depth.quantize(color);
                 }
 
-                // Write pixel
-                fb.set_pixel_with_depth(x, y, z, color);
+                // Write pixel (the painter's algorithm draws over whatever came before)
+                let written = if settings.use_zbuffer {
+                    fb.set_pixel_with_depth(x, y, z, color)
+                } else {
+                    fb.set_pixel(x, y, color);
+                    true
+                };
+                if written && count_overdraw {
+                    let count = &mut fb.overdraw[y * fb.width + x];
+                    *count = count.saturating_add(1);
+                }
             }
         }
     }
 }
 
+/// Per-call buffers of `render_mesh`, kept between calls so rendering many meshes a
+/// frame doesn't allocate once the buffers have grown to fit
+#[derive(Default)]
+pub struct RenderScratch {
+    projected: Vec<Vec3>,
+    cam_space_positions: Vec<Vec3>,
+    cam_space_normals: Vec<Vec3>,
+    surfaces: Vec<Surface>,
+    backface_wireframes: Vec<(Vec3, Vec3, Vec3)>,
+    unique_edges: Vec<(i32, i32, i32, i32)>,
+    /// Calls that had to grow a buffer (a debug counter: stays put once warmed up)
+    pub grows: usize,
+}
+
+impl RenderScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total capacity of all buffers, in elements
+    fn capacity(&self) -> usize {
+        self.projected.capacity() + self.cam_space_positions.capacity() + self.cam_space_normals.capacity()
+            + self.surfaces.capacity() + self.backface_wireframes.capacity() + self.unique_edges.capacity()
+    }
+}
+
 /// Render a mesh to the framebuffer
+///
+/// Allocates its working buffers on every call; use `render_mesh_with_scratch` when
+/// rendering several meshes per frame.
 pub fn render_mesh(
     fb: &mut Framebuffer,
     vertices: &[Vertex],
@@ -452,10 +751,36 @@ pub fn render_mesh(
     camera: &Camera,
     settings: &RasterSettings,
 ) {
+    render_mesh_with_scratch(fb, vertices, faces, textures, camera, settings, &mut RenderScratch::new());
+}
+
+/// Render a mesh to the framebuffer, reusing the buffers in `scratch`
+pub fn render_mesh_with_scratch(
+    fb: &mut Framebuffer,
+    vertices: &[Vertex],
+    faces: &[Face],
+    textures: &[Texture],
+    camera: &Camera,
+    settings: &RasterSettings,
+    scratch: &mut RenderScratch,
+) {
+    let capacity = scratch.capacity();
+    let RenderScratch {
+        projected, cam_space_positions, cam_space_normals, surfaces, backface_wireframes, unique_edges, ..
+    } = scratch;
+    projected.clear();
+    cam_space_positions.clear();
+    cam_space_normals.clear();
+    surfaces.clear();
+    backface_wireframes.clear();
+    unique_edges.clear();
+
     // Transform and project all vertices
-    let mut projected: Vec<Vec3> = Vec::with_capacity(vertices.len());
-    let mut cam_space_positions: Vec<Vec3> = Vec::with_capacity(vertices.len());
-    let mut cam_space_normals: Vec<Vec3> = Vec::with_capacity(vertices.len());
+    let projection = camera.projection(fb.width, fb.height);
+    let ortho = camera.ortho_distance.is_some();
+    projected.reserve(vertices.len());
+    cam_space_positions.reserve(vertices.len());
+    cam_space_normals.reserve(vertices.len());
 
     for v in vertices {
         // Transform position to camera space
@@ -464,7 +789,7 @@ pub fn render_mesh(
         cam_space_positions.push(cam_pos);
 
         // Project to screen
-        let screen_pos = project(cam_pos, settings.vertex_snap, fb.width, fb.height);
+        let screen_pos = project(cam_pos, settings.vertex_snap, fb.width, fb.height, projection);
         projected.push(screen_pos);
 
         // Transform normal to camera space
@@ -473,8 +798,7 @@ pub fn render_mesh(
     }
 
     // Build surfaces for front-faces and collect back-faces for wireframe
-    let mut surfaces: Vec<Surface> = Vec::with_capacity(faces.len());
-    let mut backface_wireframes: Vec<(Vec3, Vec3, Vec3)> = Vec::new();
+    surfaces.reserve(faces.len());
 
     for (face_idx, face) in faces.iter().enumerate() {
         let v1 = projected[face.v0];
@@ -488,7 +812,8 @@ pub fn render_mesh(
 
         // Near plane clipping (skip triangles behind camera)
         // In our coordinate system, +Z is forward, so we check if vertices are in front of camera
-        if cv1.z <= 0.1 || cv2.z <= 0.1 || cv3.z <= 0.1 {
+        let near = camera.near_plane;
+        if cv1.z <= near || cv2.z <= near || cv3.z <= near {
             continue;
         }
 
@@ -510,7 +835,7 @@ pub fn render_mesh(
             (cv1.y + cv2.y + cv3.y) / 3.0,
             (cv1.z + cv2.z + cv3.z) / 3.0,
         );
-        let view_dir = face_center.normalize();
+        let view_dir = if ortho { Vec3::new(0.0, 0.0, 1.0) } else { face_center.normalize() };
 
         // Face is back-facing if its normal points away from us (same direction as view)
         // Dot product > 0 means normal and view direction point the same way = back-facing
@@ -537,6 +862,9 @@ pub fn render_mesh(
                     uv1: vertices[face.v0].uv,
                     uv2: vertices[face.v1].uv,
                     uv3: vertices[face.v2].uv,
+                    c1: vertices[face.v0].color,
+                    c2: vertices[face.v1].color,
+                    c3: vertices[face.v2].color,
                     normal: normal.scale(-1.0),
                     face_idx,
                 });
@@ -553,6 +881,9 @@ pub fn render_mesh(
                 uv1: vertices[face.v0].uv,
                 uv2: vertices[face.v1].uv,
                 uv3: vertices[face.v2].uv,
+                c1: vertices[face.v0].color,
+                c2: vertices[face.v1].color,
+                c3: vertices[face.v2].color,
                 normal,
                 face_idx,
             });
@@ -562,18 +893,31 @@ pub fn render_mesh(
     // Sort by depth if not using Z-buffer (painter's algorithm)
     if !settings.use_zbuffer {
         surfaces.sort_by(|a, b| {
-            let a_max_z = a.v1.z.max(a.v2.z).max(a.v3.z);
-            let b_max_z = b.v1.z.max(b.v2.z).max(b.v3.z);
-            b_max_z.partial_cmp(&a_max_z).unwrap()
+            let (a_key, a_max_z) = sort_depth(a, settings.sort_key);
+            let (b_key, b_max_z) = sort_depth(b, settings.sort_key);
+            b_key.total_cmp(&a_key).then(b_max_z.total_cmp(&a_max_z))
         });
     }
 
+    // Light direction into camera space (normals are already there)
+    let light_dir = perspective_transform(settings.light_dir, camera.basis_x, camera.basis_y, camera.basis_z).normalize();
+
+    // Depth doesn't foreshorten orthographic views, so texture interpolation is
+    // affine there (and exact)
+    let ortho_settings;
+    let settings = if ortho && !settings.affine_textures {
+        ortho_settings = RasterSettings { affine_textures: true, ..settings.clone() };
+        &ortho_settings
+    } else {
+        settings
+    };
+
     // Rasterize each solid surface
-    for surface in &surfaces {
+    for surface in surfaces.iter() {
         let texture = faces[surface.face_idx]
             .texture_id
             .and_then(|id| textures.get(id));
-        rasterize_triangle(fb, surface, texture, settings);
+        rasterize_triangle(fb, surface, texture, settings, light_dir);
     }
 
     // Draw wireframes for back-faces (visible but not solid)
@@ -581,9 +925,7 @@ pub fn render_mesh(
     if settings.backface_cull {
         // Deduplicate edges to avoid drawing shared edges twice (which causes double-line artifacts)
         // Use a Vec to collect unique edges - compare by rounded screen coordinates
-        let mut unique_edges: Vec<(i32, i32, i32, i32)> = Vec::new();
-
-        for (v1, v2, v3) in &backface_wireframes {
+        for (v1, v2, v3) in backface_wireframes.iter() {
             let edges = [
                 (v1.x as i32, v1.y as i32, v2.x as i32, v2.y as i32),
                 (v2.x as i32, v2.y as i32, v3.x as i32, v3.y as i32),
@@ -607,10 +949,14 @@ pub fn render_mesh(
 
         // Draw each unique edge once
         let wireframe_color = Color::new(80, 80, 100);
-        for (x0, y0, x1, y1) in unique_edges {
+        for &(x0, y0, x1, y1) in unique_edges.iter() {
             fb.draw_line(x0, y0, x1, y1, wireframe_color);
         }
     }
+
+    if scratch.capacity() > capacity {
+        scratch.grows += 1;
+    }
 }
 
 /// Create a simple test cube mesh
@@ -676,11 +1022,7 @@ pub fn create_test_cube() -> (Vec<Vertex>, Vec<Face>) {
         let normal = normals[face_idx];
 
         for i in 0..4 {
-            vertices.push(Vertex {
-                pos: positions[base + i],
-                uv: uvs[i],
-                normal,
-            });
+            vertices.push(Vertex::new(positions[base + i], uvs[i], normal));
         }
 
         // Two triangles per face
@@ -691,3 +1033,214 @@ pub fn create_test_cube() -> (Vec<Vertex>, Vec<Face>) {
 
     (vertices, faces)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_clear_matches_full_clear() {
+        let bg = Color::new(30, 30, 40);
+        let mut fb = Framebuffer::new(64, 48);
+        fb.clear(bg);
+        fb.draw_line(3, 5, 40, 20, Color::WHITE);
+        fb.set_pixel_with_depth(50, 40, 10.0, Color::WHITE);
+        fb.clear_dirty(bg);
+        let mut full = Framebuffer::new(64, 48);
+        full.clear(bg);
+        assert!(fb.pixels == full.pixels && fb.zbuffer == full.zbuffer);
+
+        // A new color (or a resize) falls back to a full clear
+        fb.clear_dirty(Color::BLACK);
+        assert!(fb.pixels.chunks_exact(4).all(|p| p == Color::BLACK.to_bytes()));
+    }
+
+    #[test]
+    fn scratch_buffers_stop_growing() {
+        let (vertices, faces) = create_test_cube();
+        let mut camera = Camera::new();
+        camera.position = Vec3::new(0.0, 0.0, -5.0);
+        let settings = RasterSettings::default();
+
+        let mut expected = Framebuffer::new(64, 48);
+        render_mesh(&mut expected, &vertices, &faces, &[], &camera, &settings);
+        assert!(expected.pixels.iter().any(|&b| b != 0));
+
+        let mut scratch = RenderScratch::new();
+        for _ in 0..3 {
+            let mut fb = Framebuffer::new(64, 48);
+            render_mesh_with_scratch(&mut fb, &vertices, &faces, &[], &camera, &settings, &mut scratch);
+            assert!(fb.pixels == expected.pixels);
+        }
+        assert_eq!(scratch.grows, 1);
+    }
+
+    /// Sum of brightness steps between horizontal neighbors (shimmer shows up as noise)
+    fn horizontal_noise(fb: &Framebuffer) -> u32 {
+        fb.pixels.chunks_exact(fb.width * 4)
+            .flat_map(|row| row.chunks_exact(4).zip(row.chunks_exact(4).skip(1)))
+            .map(|(a, b)| (a[0] as i32 - b[0] as i32).unsigned_abs())
+            .sum()
+    }
+
+    #[test]
+    fn overdraw_view_counts_pixel_writes() {
+        // Two overlapping squares facing the camera, the far one drawn first
+        let square = |x: f32, z: f32| [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(dx, dy)| Vertex::from_pos(x + dx, dy, z));
+        let vertices: Vec<Vertex> = square(0.5, 6.0).into_iter().chain(square(-0.5, 5.0)).collect();
+        let faces: Vec<Face> = [0, 4].iter().flat_map(|&b| [Face::new(b, b + 1, b + 2), Face::new(b, b + 2, b + 3)]).collect();
+        let settings = RasterSettings {
+            backface_cull: false,
+            vertex_snap: false,
+            debug_view: DebugView::Overdraw,
+            ..RasterSettings::default()
+        };
+
+        let mut fb = Framebuffer::new(64, 48);
+        render_mesh(&mut fb, &vertices, &faces, &[], &Camera::new(), &settings);
+        // Along the middle row
+        let row = 24 * fb.width;
+        let counts = &fb.overdraw[row..row + fb.width];
+        assert_eq!(counts.iter().max(), Some(&2));
+        assert_eq!(counts[0], 0);
+        let twice = row + counts.iter().position(|&c| c == 2).unwrap();
+
+        fb.apply_debug_view(DebugView::Overdraw);
+        assert_eq!(fb.pixels[twice * 4..twice * 4 + 4], heat_color(0.2).to_bytes());
+
+        // Turning the view off stops counting; a clear resets the counts
+        fb.clear(Color::BLACK);
+        assert!(fb.overdraw.iter().all(|&c| c == 0));
+        fb.apply_debug_view(DebugView::Off);
+        assert!(fb.overdraw.is_empty());
+    }
+
+    #[test]
+    fn density_colors_step_with_screen_area() {
+        assert_eq!(density_color(2.0), heat_color(1.0));
+        assert_eq!(density_color(20.0), heat_color(0.8));
+        assert_eq!(density_color(100.0), heat_color(0.6));
+        assert_eq!(density_color(5000.0), heat_color(0.0));
+    }
+
+    /// Room corner where the floor drops into a pit: the upper floor merged into two
+    /// long thin triangles, the pit's floor and far wall in sector-sized tiles
+    fn ledge_corner() -> (Vec<Vertex>, Vec<Face>) {
+        use super::super::math::Vec2;
+
+        let (length, sector) = (4096.0, 1024.0);
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        let mut quad = |corners: [Vec3; 4], color: Color| {
+            let base = vertices.len();
+            let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]).normalize();
+            for pos in corners {
+                vertices.push(Vertex::with_color(pos, Vec2::new(0.0, 0.0), normal, color));
+            }
+            faces.push(Face::new(base, base + 1, base + 2));
+            faces.push(Face::new(base, base + 2, base + 3));
+        };
+        let corner = Vec3::new;
+        quad([corner(0.0, 0.0, 0.0), corner(0.0, 0.0, sector), corner(length, 0.0, sector), corner(length, 0.0, 0.0)], Color::new(200, 60, 60));
+        let mut x = 0.0;
+        while x < length {
+            let (x0, x1) = (x, x + sector);
+            quad([corner(x0, -sector, sector), corner(x0, -sector, 2.0 * sector), corner(x1, -sector, 2.0 * sector), corner(x1, -sector, sector)], Color::new(200, 200, 60));
+            quad([corner(x0, -sector, 2.0 * sector), corner(x0, 0.0, 2.0 * sector), corner(x1, 0.0, 2.0 * sector), corner(x1, -sector, 2.0 * sector)], Color::new(60, 200, 60));
+            x = x1;
+        }
+        (vertices, faces)
+    }
+
+    #[test]
+    fn centroid_sort_keeps_long_floor_triangles_in_front() {
+        let (vertices, faces) = ledge_corner();
+        // Standing back from the ledge, looking diagonally down into the pit
+        let mut camera = Camera::new();
+        camera.position = Vec3::new(0.0, 600.0, -1000.0);
+        camera.rotation_y = 0.6;
+        camera.rotation_x = 0.3;
+        camera.update_basis();
+        let mut settings = RasterSettings {
+            shading: ShadingMode::None,
+            dithering: false,
+            vertex_snap: false,
+            ..RasterSettings::default()
+        };
+
+        let mut reference = Framebuffer::new(160, 120);
+        render_mesh(&mut reference, &vertices, &faces, &[], &camera, &settings);
+        settings.use_zbuffer = false;
+        let wrong = [SortKey::MaxZ, SortKey::Centroid].map(|key| {
+            settings.sort_key = key;
+            let mut fb = Framebuffer::new(160, 120);
+            render_mesh(&mut fb, &vertices, &faces, &[], &camera, &settings);
+            fb.pixels.chunks_exact(4).zip(reference.pixels.chunks_exact(4)).filter(|(a, b)| a != b).count()
+        });
+        // The pit's far wall sorts in front of the upper floor by its farthest vertex
+        assert!(wrong[0] > 1000, "max z ordering got {} pixels wrong", wrong[0]);
+        assert!(wrong[1] < 20, "centroid ordering got {} pixels wrong", wrong[1]);
+    }
+
+    #[test]
+    fn mipmapping_calms_a_distant_checkerboard() {
+        use super::super::math::Vec2;
+
+        // Long corridor floor with a 1-texel checker repeated along it
+        let mut texture = Texture::new(64, 64);
+        for (i, pixel) in texture.pixels.iter_mut().enumerate() {
+            if (i % 64 + i / 64) % 2 == 1 {
+                *pixel = Color::BLACK;
+            }
+        }
+        let texture = texture.with_mips();
+        assert_eq!(texture.mips.len(), 6);
+        assert_eq!(texture.memory_bytes().1, (32 * 32 + 16 * 16 + 8 * 8 + 4 * 4 + 2 * 2 + 1) * std::mem::size_of::<Color>());
+
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let vertices = vec![
+            Vertex::new(Vec3::new(-400.0, -200.0, 50.0), Vec2::new(0.0, 0.0), up),
+            Vertex::new(Vec3::new(400.0, -200.0, 50.0), Vec2::new(8.0, 0.0), up),
+            Vertex::new(Vec3::new(400.0, -200.0, 20000.0), Vec2::new(8.0, 200.0), up),
+            Vertex::new(Vec3::new(-400.0, -200.0, 20000.0), Vec2::new(0.0, 200.0), up),
+        ];
+        let faces = vec![Face::with_texture(0, 1, 2, 0), Face::with_texture(0, 2, 3, 0)];
+        let mut settings = RasterSettings {
+            affine_textures: false,
+            vertex_snap: false,
+            shading: ShadingMode::None,
+            backface_cull: false,
+            dithering: false,
+            ..RasterSettings::default()
+        };
+
+        let mut noise = [0, 0];
+        for (mipmapping, noise) in [false, true].into_iter().zip(noise.iter_mut()) {
+            settings.mipmapping = mipmapping;
+            let mut fb = Framebuffer::new(160, 120);
+            render_mesh(&mut fb, &vertices, &faces, std::slice::from_ref(&texture), &Camera::new(), &settings);
+            *noise = horizontal_noise(&fb);
+        }
+        assert!(noise[1] * 2 < noise[0], "noise without mips {}, with {}", noise[0], noise[1]);
+    }
+
+    #[test]
+    fn orthographic_rays_go_straight_ahead() {
+        let mut camera = Camera::new();
+        camera.basis_x = Vec3::new(1.0, 0.0, 0.0);
+        camera.basis_y = Vec3::new(0.0, 1.0, 0.0);
+        camera.basis_z = Vec3::new(0.0, 0.0, 1.0);
+
+        // A ray through the center of the view runs along the view direction
+        let (origin, dir) = camera.screen_to_ray(160.0, 120.0, 320, 240);
+        assert_eq!((origin.x, origin.y, origin.z), (0.0, 0.0, 0.0));
+        assert_eq!((dir.x, dir.y, dir.z), (0.0, 0.0, 1.0));
+
+        // Orthographic rays through a pixel start under it on the camera plane
+        camera.ortho_distance = Some(camera.focal_length(320, 240) * 2.0);
+        let (origin, dir) = camera.screen_to_ray(210.0, 100.0, 320, 240);
+        assert_eq!((origin.x, origin.y, origin.z), (100.0, -40.0, 0.0));
+        assert_eq!((dir.x, dir.y, dir.z), (0.0, 0.0, 1.0));
+    }
+}
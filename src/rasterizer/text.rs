@@ -0,0 +1,199 @@
+//! Bitmap text drawn into the framebuffer
+//!
+//! A 3x5 pixel font, so labels go through the same low-res buffer as the scene
+//! instead of being drawn over it. Lowercase is drawn as uppercase; characters
+//! without a glyph show as '?'. The same font stamps names into the placeholder
+//! texture for missing texture references.
+
+use super::{BlendMode, Camera, Color, Framebuffer, RasterSettings, Texture, Vec3};
+
+const GLYPH_W: usize = 3;
+const GLYPH_H: usize = 5;
+/// Horizontal advance per character (glyph plus one pixel of spacing), unscaled
+const ADVANCE: usize = GLYPH_W + 1;
+/// Labels behind something closer than this much (camera-space z) are drawn dimmed
+const LABEL_DEPTH_BIAS: f32 = 16.0;
+
+/// Size of the missing-texture placeholder (square)
+const MISSING_SIZE: usize = 64;
+/// Checker cell size of the missing-texture placeholder
+const MISSING_CELL: usize = 8;
+const MISSING_MAGENTA: Color = Color { r: 255, g: 0, b: 255, a: 255 };
+
+/// Glyph rows, top first, three bits each (leftmost pixel is the high bit)
+fn glyph(c: char) -> u16 {
+    match c.to_ascii_uppercase() {
+        '0' => 0b111_101_101_101_111,
+        '1' => 0b010_110_010_010_111,
+        '2' => 0b111_001_111_100_111,
+        '3' => 0b111_001_111_001_111,
+        '4' => 0b101_101_111_001_001,
+        '5' => 0b111_100_111_001_111,
+        '6' => 0b111_100_111_101_111,
+        '7' => 0b111_001_010_010_010,
+        '8' => 0b111_101_111_101_111,
+        '9' => 0b111_101_111_001_111,
+        'A' => 0b010_101_111_101_101,
+        'B' => 0b110_101_110_101_110,
+        'C' => 0b011_100_100_100_011,
+        'D' => 0b110_101_101_101_110,
+        'E' => 0b111_100_110_100_111,
+        'F' => 0b111_100_110_100_100,
+        'G' => 0b011_100_101_101_011,
+        'H' => 0b101_101_111_101_101,
+        'I' => 0b111_010_010_010_111,
+        'J' => 0b001_001_001_101_010,
+        'K' => 0b101_101_110_101_101,
+        'L' => 0b100_100_100_100_111,
+        'M' => 0b101_111_111_101_101,
+        'N' => 0b110_101_101_101_101,
+        'O' => 0b010_101_101_101_010,
+        'P' => 0b110_101_110_100_100,
+        'Q' => 0b010_101_101_110_011,
+        'R' => 0b110_101_110_101_101,
+        'S' => 0b011_100_010_001_110,
+        'T' => 0b111_010_010_010_010,
+        'U' => 0b101_101_101_101_111,
+        'V' => 0b101_101_101_101_010,
+        'W' => 0b101_101_111_111_101,
+        'X' => 0b101_101_010_101_101,
+        'Y' => 0b101_101_010_010_010,
+        'Z' => 0b111_001_010_100_111,
+        ' ' => 0,
+        '-' => 0b000_000_111_000_000,
+        '.' => 0b000_000_000_000_010,
+        ':' => 0b000_010_000_010_000,
+        '>' => 0b100_010_001_010_100,
+        '<' => 0b001_010_100_010_001,
+        '#' => 0b101_111_101_111_101,
+        '(' => 0b010_100_100_100_010,
+        ')' => 0b010_001_001_001_010,
+        '/' => 0b001_001_010_100_100,
+        '_' => 0b000_000_000_000_111,
+        _ => 0b111_001_010_000_010,
+    }
+}
+
+/// Width in pixels of `text` drawn at `scale` (no trailing spacing)
+pub fn text_width(text: &str, scale: usize) -> usize {
+    let chars = text.chars().count();
+    if chars == 0 { 0 } else { (chars * ADVANCE - 1) * scale }
+}
+
+/// Height in pixels of a line of text at `scale`
+pub fn text_height(scale: usize) -> usize {
+    GLYPH_H * scale
+}
+
+/// Call `f` with the top-left corner of every set font pixel of `text` drawn at (x, y)
+fn for_each_font_pixel(x: i32, y: i32, text: &str, scale: usize, mut f: impl FnMut(i32, i32)) {
+    let s = scale as i32;
+    for (i, c) in text.chars().enumerate() {
+        let bits = glyph(c);
+        let gx = x + (i * ADVANCE) as i32 * s;
+        for row in 0..GLYPH_H {
+            for col in 0..GLYPH_W {
+                let bit = (GLYPH_H - 1 - row) * GLYPH_W + (GLYPH_W - 1 - col);
+                if bits & (1 << bit) != 0 {
+                    f(gx + col as i32 * s, y + row as i32 * s);
+                }
+            }
+        }
+    }
+}
+
+impl Framebuffer {
+    /// Draw `text` with its top-left corner at (x, y), each font pixel `scale` pixels
+    /// square. Pixels outside the framebuffer are skipped.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, scale: usize, color: Color) {
+        let s = scale as i32;
+        for_each_font_pixel(x, y, text, scale, |px, py| {
+            self.draw_filled_rect(px, py, px + s - 1, py + s - 1, color);
+        });
+    }
+}
+
+impl Texture {
+    /// Placeholder for a texture reference that nothing resolves: a magenta/black
+    /// checker with "MISSING" and the expected name (cut to fit) on a black band
+    pub fn missing(expected_name: &str) -> Self {
+        let size = MISSING_SIZE;
+        let mut pixels = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                let magenta = (x / MISSING_CELL + y / MISSING_CELL).is_multiple_of(2);
+                pixels.push(if magenta { MISSING_MAGENTA } else { Color::BLACK });
+            }
+        }
+
+        let max_chars = (size - 2) / ADVANCE;
+        let name: String = expected_name.chars().take(max_chars).collect();
+        let band_h = text_height(1) * 2 + 5;
+        let band_y = (size - band_h) / 2;
+        pixels[band_y * size..(band_y + band_h) * size].fill(Color::BLACK);
+
+        let mut stamp = |text: &str, y: usize, color: Color| {
+            let x = (size - text_width(text, 1)) / 2;
+            for_each_font_pixel(x as i32, y as i32, text, 1, |px, py| {
+                pixels[py as usize * size + px as usize] = color;
+            });
+        };
+        stamp("MISSING", band_y + 2, MISSING_MAGENTA);
+        stamp(&name, band_y + 3 + text_height(1), Color::WHITE);
+
+        Self { width: size, height: size, pixels, name: expected_name.to_string(), mips: Vec::new(), clut: None }
+    }
+}
+
+/// Draw `text` floating just above a world-space point.
+///
+/// Skipped (returns false) when the point is behind the camera or projects outside
+/// the framebuffer. With the z-buffer on, a label whose anchor pixel is covered by
+/// closer geometry is drawn dimmed. The label box is kept inside the framebuffer.
+pub fn render_world_label(
+    fb: &mut Framebuffer,
+    camera: &Camera,
+    world_pos: Vec3,
+    text: &str,
+    color: Color,
+    settings: &RasterSettings,
+) -> bool {
+    let z = (world_pos - camera.position).dot(camera.basis_z);
+    if z <= camera.near_plane {
+        return false;
+    }
+    let Some((sx, sy)) = camera.world_to_screen(world_pos, fb.width, fb.height) else {
+        return false;
+    };
+    if sx < 0.0 || sy < 0.0 || sx >= fb.width as f32 || sy >= fb.height as f32 {
+        return false;
+    }
+
+    let hidden = settings.use_zbuffer && {
+        let idx = sy as usize * fb.width + sx as usize;
+        fb.zbuffer[idx] < z - LABEL_DEPTH_BIAS
+    };
+    let color = if hidden {
+        Color::with_alpha(color.r / 2, color.g / 2, color.b / 2, color.a)
+    } else {
+        color
+    };
+
+    // Box: one font pixel of padding, bottom edge just above the anchor
+    let scale = if settings.low_resolution { 1 } else { 2 };
+    let pad = scale as i32;
+    let w = text_width(text, scale) as i32 + pad * 2;
+    let h = text_height(scale) as i32 + pad * 2;
+    let max_x = (fb.width as i32 - w).max(0);
+    let max_y = (fb.height as i32 - h).max(0);
+    let x0 = (sx as i32 - w / 2).clamp(0, max_x);
+    let y0 = (sy as i32 - h - pad).clamp(0, max_y);
+
+    for y in y0..(y0 + h).min(fb.height as i32) {
+        for x in x0..(x0 + w).min(fb.width as i32) {
+            fb.set_pixel_blended(x as usize, y as usize, Color::BLACK, BlendMode::Average);
+        }
+    }
+    fb.draw_text(x0 + pad, y0 + pad, text, scale, color);
+    true
+}
@@ -1,10 +1,16 @@
 //! Core types for the rasterizer
 
 use super::math::{Vec2, Vec3};
+use super::clut::Clut;
 use serde::{Deserialize, Serialize};
 
+/// 0.0-1.0 channel to 0-255, rounded to nearest
+fn unit_to_u8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
 /// RGBA color (0-255 per channel)
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -18,6 +24,8 @@ impl Color {
     pub const RED: Color = Color { r: 255, g: 0, b: 0, a: 255 };
     pub const GREEN: Color = Color { r: 0, g: 255, b: 0, a: 255 };
     pub const BLUE: Color = Color { r: 0, g: 0, b: 255, a: 255 };
+    /// Neutral vertex tint (PS1 style: 128 = 1.0x, 255 = ~2.0x)
+    pub const NEUTRAL: Color = Color { r: 128, g: 128, b: 128, a: 255 };
 
     pub fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b, a: 255 }
@@ -38,6 +46,28 @@ impl Color {
         }
     }
 
+    /// Truncate to 15-bit color (5 bits per channel), as the PS1 frame buffer stores it
+    #[inline]
+    pub fn to_15bit(self) -> Self {
+        Self {
+            r: self.r & 0xF8,
+            g: self.g & 0xF8,
+            b: self.b & 0xF8,
+            a: self.a,
+        }
+    }
+
+    /// Modulate by a vertex tint, PS1 style: texel * tint / 128 (saturating)
+    pub fn modulate(self, tint: Color) -> Self {
+        let m = |c: u8, t: u8| ((c as u16 * t as u16) / 128).min(255) as u8;
+        Self {
+            r: m(self.r, tint.r),
+            g: m(self.g, tint.g),
+            b: m(self.b, tint.b),
+            a: self.a,
+        }
+    }
+
     /// Convert to u32 (RGBA format for macroquad)
     pub fn to_u32(self) -> u32 {
         ((self.r as u32) << 24) | ((self.g as u32) << 16) | ((self.b as u32) << 8) | (self.a as u32)
@@ -48,6 +78,51 @@ impl Color {
         [self.r, self.g, self.b, self.a]
     }
 
+    /// Interpolate toward `other` (t = 0.0-1.0, all four channels, rounded)
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let l = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Self {
+            r: l(self.r, other.r),
+            g: l(self.g, other.g),
+            b: l(self.b, other.b),
+            a: l(self.a, other.a),
+        }
+    }
+
+    /// Multiply RGB by a factor (alpha unchanged, rounded, saturating)
+    pub fn scale_rgb(self, factor: f32) -> Self {
+        let s = |c: u8| (c as f32 * factor.max(0.0)).round().min(255.0) as u8;
+        Self {
+            r: s(self.r),
+            g: s(self.g),
+            b: s(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Replace alpha with a 0.0-1.0 value
+    pub fn with_alpha_f32(self, alpha: f32) -> Self {
+        Self { a: unit_to_u8(alpha), ..self }
+    }
+
+    /// Convert to macroquad's float color
+    #[cfg(feature = "ui")]
+    pub fn to_mq(self) -> macroquad::color::Color {
+        macroquad::color::Color::from_rgba(self.r, self.g, self.b, self.a)
+    }
+
+    /// Convert from macroquad's float color (rounded to the nearest step, not truncated)
+    #[cfg(feature = "ui")]
+    pub fn from_mq(color: macroquad::color::Color) -> Self {
+        Self {
+            r: unit_to_u8(color.r),
+            g: unit_to_u8(color.g),
+            b: unit_to_u8(color.b),
+            a: unit_to_u8(color.a),
+        }
+    }
+
     /// PS1-style blend: combine this color (front) with back color using blend mode
     pub fn blend(self, back: Color, mode: BlendMode) -> Color {
         match mode {
@@ -92,17 +167,37 @@ impl Color {
     }
 }
 
-/// A vertex with position, texture coordinate, and normal
-#[derive(Debug, Clone, Copy, Default)]
+#[cfg(feature = "ui")]
+impl From<Color> for macroquad::color::Color {
+    fn from(color: Color) -> Self {
+        color.to_mq()
+    }
+}
+
+#[cfg(feature = "ui")]
+impl From<macroquad::color::Color> for Color {
+    fn from(color: macroquad::color::Color) -> Self {
+        Color::from_mq(color)
+    }
+}
+
+/// A vertex with position, texture coordinate, normal and tint
+#[derive(Debug, Clone, Copy)]
 pub struct Vertex {
     pub pos: Vec3,
     pub uv: Vec2,
     pub normal: Vec3,
+    /// Vertex color, modulates the texture (Color::NEUTRAL = unchanged)
+    pub color: Color,
 }
 
 impl Vertex {
     pub fn new(pos: Vec3, uv: Vec2, normal: Vec3) -> Self {
-        Self { pos, uv, normal }
+        Self { pos, uv, normal, color: Color::NEUTRAL }
+    }
+
+    pub fn with_color(pos: Vec3, uv: Vec2, normal: Vec3, color: Color) -> Self {
+        Self { pos, uv, normal, color }
     }
 
     pub fn from_pos(x: f32, y: f32, z: f32) -> Self {
@@ -110,10 +205,17 @@ impl Vertex {
             pos: Vec3::new(x, y, z),
             uv: Vec2::default(),
             normal: Vec3::ZERO,
+            color: Color::NEUTRAL,
         }
     }
 }
 
+impl Default for Vertex {
+    fn default() -> Self {
+        Self::from_pos(0.0, 0.0, 0.0)
+    }
+}
+
 /// A triangle face (indices into vertex array)
 #[derive(Debug, Clone, Copy)]
 pub struct Face {
@@ -143,6 +245,9 @@ impl Face {
     }
 }
 
+/// (file name, error) of each texture file in a directory that didn't load
+pub type TextureLoadErrors = Vec<(String, String)>;
+
 /// Simple texture (array of colors)
 #[derive(Debug, Clone)]
 pub struct Texture {
@@ -150,6 +255,27 @@ pub struct Texture {
     pub height: usize,
     pub pixels: Vec<Color>,
     pub name: String,
+    /// Box-filtered mip levels, each half the size of the one before, down to 1x1
+    /// (empty until `with_mips`)
+    pub mips: Vec<MipLevel>,
+    /// Indexed storage (see `Texture::quantized`): when set, `pixels` is empty and
+    /// texels resolve through the palette
+    pub clut: Option<Clut>,
+}
+
+/// One reduced copy of a texture (see `Texture::with_mips`)
+#[derive(Debug, Clone)]
+pub struct MipLevel {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+/// Nearest texel of a `width` x `height` image at UV coordinates (wrapping)
+fn sample_texels(pixels: &[Color], width: usize, height: usize, u: f32, v: f32) -> Color {
+    let tx = ((u * width as f32) as usize) % width;
+    let ty = ((v * height as f32) as usize) % height;
+    pixels[ty * width + tx]
 }
 
 impl Texture {
@@ -159,6 +285,8 @@ impl Texture {
             height,
             pixels: vec![Color::WHITE; width * height],
             name: String::new(),
+            mips: Vec::new(),
+            clut: None,
         }
     }
 
@@ -188,56 +316,66 @@ impl Texture {
             height: height as usize,
             pixels,
             name,
+            mips: Vec::new(),
+            clut: None,
         })
     }
 
     /// Load all textures from a directory
     #[cfg(not(target_arch = "wasm32"))]
     pub fn load_directory<P: AsRef<std::path::Path>>(dir: P) -> Vec<Self> {
+        Self::load_directory_checked(dir).map_or_else(|_| Vec::new(), |(textures, _)| textures)
+    }
+
+    /// Load all textures from a directory, with the files that didn't load. Errs if
+    /// the directory can't be read at all.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_directory_checked<P: AsRef<std::path::Path>>(dir: P) -> Result<(Vec<Self>, TextureLoadErrors), String> {
         use indicatif::{ProgressBar, ProgressStyle};
 
         let dir = dir.as_ref();
         let mut textures = Vec::new();
+        let mut failures = Vec::new();
+
+        let entries = std::fs::read_dir(dir).map_err(|e| format!("Can't read {}: {}", dir.display(), e))?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension()
+                    .map(|ext| ext.to_ascii_lowercase() == "png")
+                    .unwrap_or(false)
+            })
+            .collect();
 
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            let mut paths: Vec<_> = entries
-                .filter_map(|e| e.ok())
-                .map(|e| e.path())
-                .filter(|p| {
-                    p.extension()
-                        .map(|ext| ext.to_ascii_lowercase() == "png")
-                        .unwrap_or(false)
-                })
-                .collect();
-
-            paths.sort();
-
-            let total = paths.len() as u64;
-            let pb = ProgressBar::new(total);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("Loading textures [{bar:30}] {pos}/{len} {msg}")
-                    .unwrap()
-                    .progress_chars("█▓░"),
-            );
-
-            for path in paths {
-                match Self::from_file(&path) {
-                    Ok(tex) => {
-                        pb.set_message(format!("{} ({}x{})", tex.name, tex.width, tex.height));
-                        textures.push(tex);
-                    }
-                    Err(e) => {
-                        pb.set_message(format!("Error: {}", e));
-                    }
+        paths.sort();
+
+        let total = paths.len() as u64;
+        let pb = ProgressBar::new(total);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("Loading textures [{bar:30}] {pos}/{len} {msg}")
+                .unwrap()
+                .progress_chars("█▓░"),
+        );
+
+        for path in paths {
+            match Self::from_file(&path) {
+                Ok(tex) => {
+                    pb.set_message(format!("{} ({}x{})", tex.name, tex.width, tex.height));
+                    textures.push(tex);
+                }
+                Err(e) => {
+                    pb.set_message(format!("Error: {}", e));
+                    let file = path.file_name().map_or_else(String::new, |f| f.to_string_lossy().to_string());
+                    failures.push((file, e));
                 }
-                pb.inc(1);
             }
-
-            pb.finish_with_message(format!("Loaded {} textures", textures.len()));
+            pb.inc(1);
         }
 
-        textures
+        pb.finish_with_message(format!("Loaded {} textures", textures.len()));
+        Ok((textures, failures))
     }
 
     /// Load all textures from a directory (WASM - no progress bar)
@@ -289,6 +427,8 @@ impl Texture {
             height: height as usize,
             pixels,
             name,
+            mips: Vec::new(),
+            clut: None,
         })
     }
 
@@ -301,20 +441,93 @@ impl Texture {
                 pixels.push(if checker { color1 } else { color2 });
             }
         }
-        Self { width, height, pixels, name: "checkerboard".to_string() }
+        Self { width, height, pixels, name: "checkerboard".to_string(), mips: Vec::new(), clut: None }
+    }
+
+    /// This texture with its mip chain generated: each level averages 2x2 texels of
+    /// the one above (a single row or column averages pairs)
+    pub fn with_mips(mut self) -> Self {
+        self.mips.clear();
+        let base = self.texels().into_owned();
+        let (mut width, mut height) = (self.width, self.height);
+        while width > 1 || height > 1 {
+            let (src, src_w) = match self.mips.last() {
+                Some(level) => (&level.pixels, level.width),
+                None => (&base, self.width),
+            };
+            let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+            let mut pixels = Vec::with_capacity(w * h);
+            for y in 0..h {
+                for x in 0..w {
+                    let xs = [2 * x, (2 * x + 1).min(width - 1)];
+                    let ys = [2 * y, (2 * y + 1).min(height - 1)];
+                    let mut sum = [0u32; 4];
+                    for sy in ys {
+                        for sx in xs {
+                            let c = src[sy * src_w + sx];
+                            sum[0] += c.r as u32;
+                            sum[1] += c.g as u32;
+                            sum[2] += c.b as u32;
+                            sum[3] += c.a as u32;
+                        }
+                    }
+                    let avg = sum.map(|s| ((s + 2) / 4) as u8);
+                    pixels.push(Color::with_alpha(avg[0], avg[1], avg[2], avg[3]));
+                }
+            }
+            self.mips.push(MipLevel { width: w, height: h, pixels });
+            (width, height) = (w, h);
+        }
+        self
+    }
+
+    /// Full-size texels, row by row (resolved through the palette if indexed)
+    pub fn texels(&self) -> std::borrow::Cow<'_, [Color]> {
+        match &self.clut {
+            Some(clut) => clut.indices.iter().map(|&i| clut.colors[i as usize]).collect(),
+            None => std::borrow::Cow::Borrowed(&self.pixels),
+        }
+    }
+
+    /// Bytes of pixel data: the full-size image (indices plus palette if indexed),
+    /// and its mip levels
+    pub fn memory_bytes(&self) -> (usize, usize) {
+        let texel = std::mem::size_of::<Color>();
+        let mips = self.mips.iter().map(|m| m.pixels.len()).sum::<usize>();
+        let base = match &self.clut {
+            Some(clut) => clut.indices.len() + clut.colors.len() * texel,
+            None => self.pixels.len() * texel,
+        };
+        (base, mips * texel)
     }
 
     /// Sample texture at UV coordinates (no filtering - PS1 style)
     pub fn sample(&self, u: f32, v: f32) -> Color {
-        let tx = ((u * self.width as f32) as usize) % self.width;
-        let ty = ((v * self.height as f32) as usize) % self.height;
-        self.pixels[ty * self.width + tx]
+        match &self.clut {
+            Some(clut) => {
+                let tx = ((u * self.width as f32) as usize) % self.width;
+                let ty = ((v * self.height as f32) as usize) % self.height;
+                clut.texel(ty * self.width + tx)
+            }
+            None => sample_texels(&self.pixels, self.width, self.height, u, v),
+        }
+    }
+
+    /// Sample mip `level` (0 = full size; clamped to the smallest generated level)
+    pub fn sample_mip(&self, u: f32, v: f32, level: usize) -> Color {
+        match level.checked_sub(1).and_then(|i| self.mips.get(i).or(self.mips.last())) {
+            Some(mip) => sample_texels(&mip.pixels, mip.width, mip.height, u, v),
+            None => self.sample(u, v),
+        }
     }
 
     /// Get pixel at x,y coordinates
     pub fn get_pixel(&self, x: usize, y: usize) -> Color {
         if x < self.width && y < self.height {
-            self.pixels[y * self.width + x]
+            match &self.clut {
+                Some(clut) => clut.texel(y * self.width + x),
+                None => self.pixels[y * self.width + x],
+            }
         } else {
             Color::BLACK
         }
@@ -322,7 +535,7 @@ impl Texture {
 }
 
 /// Shading mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum ShadingMode {
     None,     // No shading, raw texture/vertex colors
     Flat,     // One light calculation per face
@@ -341,6 +554,103 @@ pub enum BlendMode {
     AddQuarter,// Mode 3: B + 0.25*F (subtle glow)
 }
 
+impl BlendMode {
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Opaque,
+        BlendMode::Average,
+        BlendMode::Add,
+        BlendMode::Subtract,
+        BlendMode::AddQuarter,
+    ];
+
+    /// Short display name
+    pub fn label(&self) -> &'static str {
+        match self {
+            BlendMode::Opaque => "Opaque",
+            BlendMode::Average => "Average",
+            BlendMode::Add => "Add",
+            BlendMode::Subtract => "Subtract",
+            BlendMode::AddQuarter => "Add 1/4",
+        }
+    }
+
+    /// Next mode in cycle order (wraps around)
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+/// Color precision of the rendering pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ColorDepth {
+    /// 24-bit color throughout
+    #[default]
+    Full,
+    /// PS1 15-bit: texels, tinted colors and output pixels truncated to 5 bits per channel
+    Bit15,
+}
+
+impl ColorDepth {
+    /// Apply this depth to a color (no-op for Full)
+    #[inline]
+    pub fn quantize(self, color: Color) -> Color {
+        match self {
+            ColorDepth::Full => color,
+            ColorDepth::Bit15 => color.to_15bit(),
+        }
+    }
+}
+
+/// Depth the painter's algorithm (Z-buffer off) orders triangles by, far to near
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum SortKey {
+    /// Farthest vertex (long floor triangles sort too far back, and walls behind
+    /// them draw over them)
+    MaxZ,
+    /// Average of the three vertices, ties broken by the farthest (like the PS1's
+    /// ordering tables)
+    #[default]
+    Centroid,
+    /// Nearest vertex
+    MinZ,
+}
+
+/// Debug visualization replacing the normal shading, to see where the rasterizer
+/// works hardest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugView {
+    #[default]
+    Off,
+    /// How many times each pixel was written, blue (once) to red (6+)
+    Overdraw,
+    /// The Z-buffer, near bright to far dark
+    Depth,
+    /// Each triangle by its screen area, red (tiny) to blue (large)
+    TriangleDensity,
+}
+
+impl DebugView {
+    /// Next view when cycling (toolbar button)
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Off => DebugView::Overdraw,
+            DebugView::Overdraw => DebugView::Depth,
+            DebugView::Depth => DebugView::TriangleDensity,
+            DebugView::TriangleDensity => DebugView::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DebugView::Off => "Off",
+            DebugView::Overdraw => "Overdraw",
+            DebugView::Depth => "Depth",
+            DebugView::TriangleDensity => "Triangle Density",
+        }
+    }
+}
+
 /// Rasterizer settings
 #[derive(Debug, Clone)]
 pub struct RasterSettings {
@@ -350,11 +660,13 @@ pub struct RasterSettings {
     pub vertex_snap: bool,
     /// Use Z-buffer (false = painter's algorithm)
     pub use_zbuffer: bool,
+    /// Triangle order of the painter's algorithm
+    pub sort_key: SortKey,
     /// Shading mode
     pub shading: ShadingMode,
     /// Backface culling
     pub backface_cull: bool,
-    /// Light direction (for shading)
+    /// Direction toward the light in world space (Y up), for shading
     pub light_dir: Vec3,
     /// Ambient light intensity (0.0-1.0)
     pub ambient: f32,
@@ -362,6 +674,25 @@ pub struct RasterSettings {
     pub low_resolution: bool,
     /// Enable PS1-style ordered dithering (4x4 Bayer matrix)
     pub dithering: bool,
+    /// Color precision (15-bit emulates the PS1 frame buffer even without dithering)
+    pub color_depth: ColorDepth,
+    /// Pulse faces whose texture is missing (editor viewport)
+    pub highlight_missing_textures: bool,
+    /// Draw rectangles of identical flat floors/ceilings as single quads (fewer
+    /// triangles, but more affine warping on the larger polygons)
+    pub merge_coplanar: bool,
+    /// Average vertex normals where faces of the same kind meet (Gouraud shading
+    /// runs smoothly across sloped floors instead of showing each tile)
+    pub smooth_normals: bool,
+    /// Sample a smaller mip level (picked once per triangle) on distant or steep
+    /// surfaces, instead of shimmering. Needs textures loaded `with_mips`.
+    pub mipmapping: bool,
+    /// Light rooms' point lights every frame instead of into their cached render
+    /// data (editor preview: slower, but moving a light doesn't rebuild the room)
+    pub dynamic_lights: bool,
+    /// Debug visualization (Overdraw and Depth need `Framebuffer::apply_debug_view`
+    /// after the meshes are drawn)
+    pub debug_view: DebugView,
 }
 
 impl Default for RasterSettings {
@@ -370,12 +701,132 @@ impl Default for RasterSettings {
             affine_textures: true,  // PS1 default: affine (warpy)
             vertex_snap: true,      // PS1 default: jittery vertices
             use_zbuffer: true,
+            sort_key: SortKey::Centroid,
             shading: ShadingMode::Gouraud,
             backface_cull: true,
-            light_dir: Vec3::new(-1.0, -1.0, -1.0).normalize(),
+            light_dir: Vec3::new(1.0, 1.0, -1.0).normalize(), // Upper left, behind the default camera
             ambient: 0.3,
             low_resolution: true,   // PS1 default: 320x240
             dithering: true,        // PS1 default: ordered dithering enabled
+            color_depth: ColorDepth::Full,
+            highlight_missing_textures: true,
+            merge_coplanar: false,
+            smooth_normals: false,
+            mipmapping: false,      // PS1 had no mipmaps
+            dynamic_lights: false,
+            debug_view: DebugView::Off,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVELS: [u8; 3] = [0, 128, 255];
+
+    fn gray(v: u8) -> Color {
+        Color::new(v, v, v)
+    }
+
+    /// Reference PS1 semi-transparency math, per channel, saturating at 0 and 255
+    fn reference(front: u8, back: u8, mode: BlendMode) -> u8 {
+        let (f, b) = (front as i32, back as i32);
+        let v = match mode {
+            BlendMode::Opaque => f,
+            BlendMode::Average => (b + f) / 2,
+            BlendMode::Add => b + f,
+            BlendMode::Subtract => b - f,
+            BlendMode::AddQuarter => b + f / 4,
+        };
+        v.clamp(0, 255) as u8
+    }
+
+    #[test]
+    fn test_color_helpers_round() {
+        // 127.5 rounds up; truncation would give 127
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, 0.5), gray(128));
+        assert_eq!(Color::new(10, 20, 255).scale_rgb(0.25), Color::new(3, 5, 64));
+        assert_eq!(Color::new(200, 200, 200).scale_rgb(2.0), Color::WHITE);
+        assert_eq!(Color::WHITE.with_alpha_f32(0.5).a, 128);
+    }
+
+    #[cfg(feature = "ui")]
+    #[test]
+    fn test_macroquad_round_trip() {
+        for v in 0..=255u8 {
+            let c = Color::with_alpha(v, 255 - v, v / 2, v);
+            assert_eq!(Color::from(c.to_mq()), c);
+        }
+        // Just under a step still rounds to it
+        let mq = macroquad::color::Color::new(99.6 / 255.0, 0.0, 1.0, 1.0);
+        assert_eq!(Color::from_mq(mq).r, 100);
+    }
+
+    #[test]
+    fn test_15bit_quantize() {
+        let c = Color::with_alpha(255, 7, 130, 200);
+        assert_eq!(ColorDepth::Bit15.quantize(c).to_bytes(), [248, 0, 128, 200]);
+        assert_eq!(ColorDepth::Full.quantize(c).to_bytes(), c.to_bytes());
+    }
+
+    #[test]
+    fn test_blend_boundaries_all_modes() {
+        for mode in BlendMode::ALL {
+            for &f in &LEVELS {
+                for &b in &LEVELS {
+                    let out = gray(f).blend(gray(b), mode);
+                    let expected = reference(f, b, mode);
+                    assert_eq!((out.r, out.g, out.b), (expected, expected, expected),
+                        "{:?}: front {} back {}", mode, f, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_blend_saturates_without_wraparound() {
+        let white = gray(255);
+        let black = gray(0);
+        assert_eq!(white.blend(white, BlendMode::Add).r, 255);
+        assert_eq!(white.blend(black, BlendMode::Subtract).r, 0);
+        assert_eq!(white.blend(white, BlendMode::AddQuarter).r, 255);
+        assert_eq!(gray(128).blend(gray(128), BlendMode::Add).r, 255);
+        assert_eq!(gray(128).blend(gray(128), BlendMode::Subtract).r, 0);
+        assert_eq!(gray(255).blend(gray(128), BlendMode::Subtract).r, 0);
+    }
+
+    #[test]
+    fn test_blend_exact_values() {
+        assert_eq!(gray(255).blend(gray(0), BlendMode::Average).r, 127);
+        assert_eq!(gray(128).blend(gray(255), BlendMode::Average).r, 191);
+        assert_eq!(gray(128).blend(gray(0), BlendMode::AddQuarter).r, 32);
+        assert_eq!(gray(255).blend(gray(128), BlendMode::AddQuarter).r, 191);
+        assert_eq!(gray(128).blend(gray(255), BlendMode::Subtract).r, 127);
+    }
+
+    #[test]
+    fn test_blend_is_per_channel() {
+        let front = Color::with_alpha(255, 128, 0, 200);
+        let back = Color::new(0, 128, 255);
+        assert_eq!(front.blend(back, BlendMode::Add).to_bytes(), [255, 255, 255, 200]);
+        assert_eq!(front.blend(back, BlendMode::Subtract).to_bytes(), [0, 0, 255, 200]);
+        assert_eq!(front.blend(back, BlendMode::AddQuarter).to_bytes(), [63, 160, 255, 200]);
+        assert_eq!(front.blend(back, BlendMode::Average).to_bytes(), [127, 128, 127, 200]);
+        assert_eq!(front.blend(back, BlendMode::Opaque).to_bytes(), front.to_bytes());
+    }
+
+    #[test]
+    fn test_blend_mode_serde_names() {
+        for (mode, name) in [
+            (BlendMode::Opaque, "Opaque"),
+            (BlendMode::Average, "Average"),
+            (BlendMode::Add, "Add"),
+            (BlendMode::Subtract, "Subtract"),
+            (BlendMode::AddQuarter, "AddQuarter"),
+        ] {
+            assert_eq!(ron::to_string(&mode).unwrap(), name);
+            assert_eq!(ron::from_str::<BlendMode>(name).unwrap(), mode);
         }
     }
 }
@@ -1,26 +1,19 @@
-//! Audio engine using rustysynth for SF2 playback
+//! Audio engine: loads SF2 soundfonts into the mixer (`music::AudioState`: the
+//! rustysynth synth with PCM voices for ambient sounds alongside) and feeds it to
+//! the audio device
 //!
 //! Platform-specific audio output:
 //! - Native: cpal for direct audio device access
 //! - WASM: Web Audio API via JavaScript FFI
 
 use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender};
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
 use rustysynth::{SoundFont, Synthesizer, SynthesizerSettings};
-
-/// Sample rate for audio output
-pub const SAMPLE_RATE: u32 = 44100;
-
-/// Audio engine state shared between main thread and audio callback
-struct AudioState {
-    /// The synthesizer
-    synth: Option<Synthesizer>,
-    /// Whether audio is playing
-    playing: bool,
-}
+use crate::music::{all_notes_off, set_program, AudioState, PcmSound, PlaybackPosition, SequencerCommand, Song, VoiceId, REPLACED_SONGS, SAMPLE_RATE};
 
 // =============================================================================
 // Native audio output using cpal
@@ -48,25 +41,18 @@ mod native {
         let stream = device.build_output_stream(
             &config,
             move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                let mut state = state.lock().unwrap();
-
-                if let Some(ref mut synth) = state.synth {
-                    let samples_needed = data.len() / 2;
-                    if left_buffer.len() < samples_needed {
-                        left_buffer.resize(samples_needed, 0.0);
-                        right_buffer.resize(samples_needed, 0.0);
-                    }
-
-                    synth.render(&mut left_buffer[..samples_needed], &mut right_buffer[..samples_needed]);
-
-                    for i in 0..samples_needed {
-                        data[i * 2] = left_buffer[i];
-                        data[i * 2 + 1] = right_buffer[i];
-                    }
-                } else {
-                    for sample in data.iter_mut() {
-                        *sample = 0.0;
-                    }
+                let samples_needed = data.len() / 2;
+                if left_buffer.len() < samples_needed {
+                    left_buffer.resize(samples_needed, 0.0);
+                    right_buffer.resize(samples_needed, 0.0);
+                }
+
+                // The song keeps time here even before a soundfont is loaded
+                state.lock().unwrap().render(&mut left_buffer[..samples_needed], &mut right_buffer[..samples_needed]);
+
+                for i in 0..samples_needed {
+                    data[i * 2] = left_buffer[i];
+                    data[i * 2 + 1] = right_buffer[i];
                 }
             },
             |err| eprintln!("Audio stream error: {}", err),
@@ -136,11 +122,17 @@ pub mod wasm {
 pub struct AudioEngine {
     /// Shared state
     state: Arc<Mutex<AudioState>>,
+    /// Command queue to the sequencer in the audio callback
+    commands: Sender<SequencerCommand>,
+    /// Songs the sequencer replaced, freed by `free_replaced_songs`
+    replaced_songs: Receiver<Box<Song>>,
     /// The audio stream (native only, kept alive)
     #[cfg(not(target_arch = "wasm32"))]
     _stream: Option<cpal::Stream>,
     /// Loaded soundfont info
     soundfont_name: Option<String>,
+    /// Presets in the loaded soundfont as (bank, program, name), sorted by bank then program
+    presets: Vec<(u8, u8, String)>,
     /// Audio render buffers (WASM only - we render on demand)
     #[cfg(target_arch = "wasm32")]
     left_buffer: Vec<f32>,
@@ -154,18 +146,20 @@ pub struct AudioEngine {
 impl AudioEngine {
     /// Create a new audio engine (no soundfont loaded yet)
     pub fn new() -> Self {
-        let state = Arc::new(Mutex::new(AudioState {
-            synth: None,
-            playing: false,
-        }));
+        let (commands, receiver) = channel();
+        let (returns, replaced_songs) = sync_channel(REPLACED_SONGS);
+        let state = Arc::new(Mutex::new(AudioState::new(receiver, returns)));
 
         #[cfg(not(target_arch = "wasm32"))]
         {
             let stream = native::init_audio_stream(Arc::clone(&state));
             Self {
                 state,
+                commands,
+                replaced_songs,
                 _stream: stream,
                 soundfont_name: None,
+                presets: Vec::new(),
             }
         }
 
@@ -174,7 +168,10 @@ impl AudioEngine {
             wasm::init_audio();
             Self {
                 state,
+                commands,
+                replaced_songs,
                 soundfont_name: None,
+                presets: Vec::new(),
                 left_buffer: vec![0.0; 2048],
                 right_buffer: vec![0.0; 2048],
                 sample_accumulator: 0.0,
@@ -199,20 +196,38 @@ impl AudioEngine {
         self.load_soundfont_from_reader(&mut cursor, name)
     }
 
-    /// Internal: Load soundfont from any reader
+    /// Internal: Load soundfont from any reader. Files come from users, so a parser
+    /// panic on a malformed one is caught and reported like any other parse error.
     fn load_soundfont_from_reader<R: std::io::Read>(&mut self, reader: &mut R, name: Option<String>) -> Result<(), String> {
-        let soundfont = SoundFont::new(reader)
+        let soundfont = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| SoundFont::new(reader)))
+            .map_err(|_| "Failed to parse soundfont: malformed file".to_string())?
             .map_err(|e| format!("Failed to parse soundfont: {:?}", e))?;
 
+        let mut presets: Vec<(u8, u8, String)> = soundfont.get_presets().iter()
+            .filter_map(|p| Some((
+                u8::try_from(p.get_bank_number()).ok()?,
+                u8::try_from(p.get_patch_number()).ok()?,
+                p.get_name().to_string(),
+            )))
+            .collect();
+        presets.sort_by_key(|(bank, program, _)| (*bank, *program));
+
         let soundfont = Arc::new(soundfont);
 
         let settings = SynthesizerSettings::new(SAMPLE_RATE as i32);
-        let synth = Synthesizer::new(&soundfont, &settings)
+        let synth = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| Synthesizer::new(&soundfont, &settings)))
+            .map_err(|_| "Failed to create synthesizer: malformed soundfont".to_string())?
             .map_err(|e| format!("Failed to create synthesizer: {:?}", e))?;
 
         self.soundfont_name = name;
+        self.presets = presets;
 
+        // Swapped under the lock: the callback never sees a half-replaced synth, and
+        // the old one's voices are cut rather than left ringing into the new one
         let mut state = self.state.lock().unwrap();
+        if let Some(old) = state.synth.as_mut() {
+            old.note_off_all(true);
+        }
         state.synth = Some(synth);
         state.playing = true;
 
@@ -232,31 +247,44 @@ impl AudioEngine {
     /// Render and output audio (WASM only - must be called each frame with delta time)
     #[cfg(target_arch = "wasm32")]
     pub fn render_audio(&mut self, delta: f64) {
-        let mut state = self.state.lock().unwrap();
-        if let Some(ref mut synth) = state.synth {
-            // Calculate exact samples needed based on actual elapsed time
-            // delta is in seconds, sample_rate is 44100 samples/sec
-            self.sample_accumulator += delta * SAMPLE_RATE as f64;
-
-            // Only render whole samples
-            let samples = self.sample_accumulator as usize;
-            if samples == 0 {
-                return;
-            }
+        // Calculate exact samples needed based on actual elapsed time
+        // delta is in seconds, sample_rate is 44100 samples/sec
+        self.sample_accumulator += delta * SAMPLE_RATE as f64;
+
+        // Only render whole samples
+        let samples = self.sample_accumulator as usize;
+        if samples == 0 {
+            return;
+        }
 
-            // Keep fractional part for next frame
-            self.sample_accumulator -= samples as f64;
+        // Keep fractional part for next frame
+        self.sample_accumulator -= samples as f64;
 
-            // Cap to reasonable max (prevents runaway if tab was backgrounded)
-            let samples = samples.min(4096);
+        // Cap to reasonable max (prevents runaway if tab was backgrounded)
+        let samples = samples.min(4096);
 
-            if self.left_buffer.len() < samples {
-                self.left_buffer.resize(samples, 0.0);
-                self.right_buffer.resize(samples, 0.0);
-            }
-            synth.render(&mut self.left_buffer[..samples], &mut self.right_buffer[..samples]);
-            wasm::write_audio(&self.left_buffer[..samples], &self.right_buffer[..samples]);
+        if self.left_buffer.len() < samples {
+            self.left_buffer.resize(samples, 0.0);
+            self.right_buffer.resize(samples, 0.0);
         }
+        self.state.lock().unwrap().render(&mut self.left_buffer[..samples], &mut self.right_buffer[..samples]);
+        wasm::write_audio(&self.left_buffer[..samples], &self.right_buffer[..samples]);
+    }
+
+    /// Queue a command for the sequencer (applied at the start of the next buffer)
+    pub fn send(&self, command: SequencerCommand) {
+        // The receiver lives as long as the engine's state, so this can't fail
+        let _ = self.commands.send(command);
+    }
+
+    /// Free the songs the sequencer replaced (call once per frame)
+    pub fn free_replaced_songs(&self) {
+        while self.replaced_songs.try_recv().is_ok() {}
+    }
+
+    /// Playback position as of the last rendered buffer
+    pub fn playback_position(&self) -> PlaybackPosition {
+        self.state.lock().unwrap().sequencer.position()
     }
 
     /// Play a note (note on)
@@ -279,27 +307,16 @@ impl AudioEngine {
     pub fn all_notes_off(&self) {
         let mut state = self.state.lock().unwrap();
         if let Some(ref mut synth) = state.synth {
-            for channel in 0..16 {
-                for key in 0..128 {
-                    synth.note_off(channel, key);
-                }
-            }
+            all_notes_off(synth);
         }
     }
 
-    /// Set the instrument (program) for a channel
-    pub fn set_program(&self, channel: i32, program: i32) {
+    /// Set the instrument for a channel: bank select (CC 0) then program change.
+    /// The synth takes the SF2 bank number as-is, so drum kits (bank 128) work on any channel.
+    pub fn set_program(&self, channel: i32, bank: i32, program: i32) {
         let mut state = self.state.lock().unwrap();
         if let Some(ref mut synth) = state.synth {
-            synth.process_midi_message(channel, 0xC0, program, 0);
-        }
-    }
-
-    /// Set channel volume (CC 7)
-    pub fn set_volume(&self, channel: i32, volume: i32) {
-        let mut state = self.state.lock().unwrap();
-        if let Some(ref mut synth) = state.synth {
-            synth.process_midi_message(channel, 0xB0, 7, volume);
+            set_program(synth, channel, bank, program);
         }
     }
 
@@ -311,15 +328,36 @@ impl AudioEngine {
         }
     }
 
-    /// Set pitch bend (0-16383, center = 8192)
-    pub fn set_pitch_bend(&self, channel: i32, value: i32) {
-        let mut state = self.state.lock().unwrap();
-        if let Some(ref mut synth) = state.synth {
-            // Pitch bend is 0xE0, with LSB and MSB as the two data bytes
-            let lsb = value & 0x7F;
-            let msb = (value >> 7) & 0x7F;
-            synth.process_midi_message(channel, 0xE0, lsb, msb);
-        }
+    /// Set channel pan from a normalized value (-1.0 = left, 0.0 = center, 1.0 = right)
+    pub fn set_channel_pan(&self, channel: i32, pan: f32) {
+        let cc = ((pan.clamp(-1.0, 1.0) + 1.0) * 63.5).round() as i32;
+        self.set_pan(channel, cc.clamp(0, 127));
+    }
+
+    /// Start playing a PCM sound at `gain` (0.0-1.0), once or on a loop
+    pub fn play_voice(&self, sound: Arc<PcmSound>, gain: f32, looping: bool) -> VoiceId {
+        self.state.lock().unwrap().play_voice(sound, gain, looping)
+    }
+
+    /// Change a voice's gain (ramped over the next buffer). Does nothing once the
+    /// voice has stopped.
+    pub fn set_voice_gain(&self, id: VoiceId, gain: f32) {
+        self.state.lock().unwrap().set_voice_gain(id, gain);
+    }
+
+    /// Stop a voice
+    pub fn stop_voice(&self, id: VoiceId) {
+        self.state.lock().unwrap().stop_voice(id);
+    }
+
+    /// Set master output volume (0.0-1.0)
+    pub fn set_master_volume(&self, volume: f32) {
+        self.state.lock().unwrap().master_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Enable/disable PS1 SPU 15-bit output quantization
+    pub fn set_spu_quantize(&self, enabled: bool) {
+        self.state.lock().unwrap().spu_quantize = enabled;
     }
 
     /// Set modulation wheel (CC 1)
@@ -362,8 +400,29 @@ impl AudioEngine {
         }
     }
 
-    /// Get list of preset names from the loaded soundfont
+    /// Banks that have presets, in order (just bank 0 before a soundfont is loaded)
+    pub fn banks(&self) -> Vec<u8> {
+        let mut banks: Vec<u8> = self.presets.iter().map(|(bank, _, _)| *bank).collect();
+        banks.dedup();
+        if banks.is_empty() {
+            banks.push(0);
+        }
+        banks
+    }
+
+    /// Name of a preset, if the soundfont has it
+    pub fn preset_name(&self, bank: u8, program: u8) -> Option<String> {
+        self.get_preset_names().into_iter()
+            .find(|(b, p, _)| *b == bank && *p == program)
+            .map(|(_, _, name)| name)
+    }
+
+    /// List of presets as (bank, program, name): the loaded soundfont's, or the
+    /// General MIDI names for bank 0 before one is loaded
     pub fn get_preset_names(&self) -> Vec<(u8, u8, String)> {
+        if !self.presets.is_empty() {
+            return self.presets.clone();
+        }
         let gm_names = [
             "Acoustic Grand Piano", "Bright Acoustic Piano", "Electric Grand Piano",
             "Honky-tonk Piano", "Electric Piano 1", "Electric Piano 2", "Harpsichord",
@@ -1,24 +1,14 @@
 //! Tracker UI layout and rendering
 
 use macroquad::prelude::*;
-use crate::ui::{Rect, UiContext, Toolbar, icon, draw_knob};
+use crate::ui::{Rect, UiContext, Toolbar, icon, icon_button, draw_knob, draw_icon_centered};
 use super::state::{TrackerState, TrackerView};
+use crate::music::DEFAULT_PATTERN_LEN;
 
-// Colors
-const BG_COLOR: Color = Color::new(0.11, 0.11, 0.13, 1.0);
-const HEADER_COLOR: Color = Color::new(0.15, 0.15, 0.18, 1.0);
-const ROW_EVEN: Color = Color::new(0.13, 0.13, 0.15, 1.0);
-const ROW_ODD: Color = Color::new(0.11, 0.11, 0.13, 1.0);
-const ROW_BEAT: Color = Color::new(0.16, 0.14, 0.12, 1.0);
-const ROW_HIGHLIGHT: Color = Color::new(0.2, 0.25, 0.3, 1.0);
-const CURSOR_COLOR: Color = Color::new(0.3, 0.5, 0.8, 0.8);
-const PLAYBACK_ROW_COLOR: Color = Color::new(0.4, 0.2, 0.2, 0.6);
-const TEXT_COLOR: Color = Color::new(0.8, 0.8, 0.85, 1.0);
-const TEXT_DIM: Color = Color::new(0.4, 0.4, 0.45, 1.0);
-const NOTE_COLOR: Color = Color::new(0.9, 0.85, 0.5, 1.0);
-const INST_COLOR: Color = Color::new(0.5, 0.8, 0.5, 1.0);
-const VOL_COLOR: Color = Color::new(0.5, 0.7, 0.9, 1.0);
-const FX_COLOR: Color = Color::new(0.9, 0.5, 0.7, 1.0);
+// Colors (the rest come from `UiTheme::tracker`)
+const HEX_PENDING_COLOR: Color = Color::new(1.0, 0.9, 0.4, 1.0);
+const SONG_PROGRESS_COLOR: Color = Color::new(0.8, 0.4, 0.4, 1.0);
+const LOOP_COLOR: Color = Color::new(0.4, 0.8, 0.9, 1.0);
 
 // Layout constants
 const ROW_HEIGHT: f32 = 18.0;
@@ -32,8 +22,9 @@ const FXPARAM_WIDTH: f32 = 24.0;
 
 /// Draw the tracker interface
 pub fn draw_tracker(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_font: Option<&Font>) {
+    let theme = ctx.theme;
     // Background
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, BG_COLOR);
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.tracker.bg);
 
     // Split into header and main area
     let header_height = 60.0;
@@ -43,20 +34,24 @@ pub fn draw_tracker(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, i
     // Draw header (transport, info)
     draw_header(ctx, header_rect, state, icon_font);
 
+    // Escape goes to a knob being edited first (the instruments view handles it)
+    let escape_taken = state.editing_knob.is_some();
+
     // Draw main content based on view
     match state.view {
         TrackerView::Pattern => draw_pattern_view(ctx, main_rect, state),
-        TrackerView::Arrangement => draw_arrangement_view(ctx, main_rect, state),
-        TrackerView::Instruments => draw_instruments_view(ctx, main_rect, state),
+        TrackerView::Arrangement => draw_arrangement_view(ctx, main_rect, state, icon_font),
+        TrackerView::Instruments => draw_instruments_view(ctx, main_rect, state, icon_font),
     }
 
     // Handle input
-    handle_input(ctx, state);
+    handle_input(ctx, state, escape_taken);
 }
 
 /// Draw the header with transport controls and song info
 fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_font: Option<&Font>) {
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, HEADER_COLOR);
+    let theme = ctx.theme;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.tracker.header);
 
     // First row: toolbar with icons (36.0 height to match World Editor)
     let toolbar_rect = Rect::new(rect.x, rect.y, rect.w, 36.0);
@@ -73,6 +68,7 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
         let is_active = state.view == view;
         if toolbar.icon_button_active(ctx, icon_char, icon_font, tooltip, is_active) {
             state.view = view;
+            state.instrument_return = None;
         }
     }
 
@@ -100,14 +96,66 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
     // BPM controls
     toolbar.label(&format!("BPM:{:3}", state.song.bpm));
     if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Decrease BPM") {
-        state.song.bpm = (state.song.bpm as i32 - 5).clamp(40, 300) as u16;
+        state.set_bpm(state.song.bpm as i32 - 5);
     }
     if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Increase BPM") {
-        state.song.bpm = (state.song.bpm as i32 + 5).clamp(40, 300) as u16;
+        state.set_bpm(state.song.bpm as i32 + 5);
+    }
+
+    toolbar.separator();
+
+    // Master volume controls
+    let master_pct = (state.song.master_volume * 100.0).round() as i32;
+    toolbar.label(&format!("Vol:{:3}", master_pct));
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Decrease Master Volume") {
+        state.set_master_volume((master_pct - 5) as f32 / 100.0);
+    }
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Increase Master Volume") {
+        state.set_master_volume((master_pct + 5) as f32 / 100.0);
+    }
+    if toolbar.icon_button_active(ctx, icon::CPU, icon_font, "SPU 15-bit Quantization (PS1)", state.song.spu_quantize) {
+        state.toggle_spu_quantize();
+    }
+    if toolbar.icon_button_active(ctx, icon::HEADPHONES, icon_font, "Keep Playing in Other Tabs", state.background_playback) {
+        state.toggle_background_playback();
+    }
+    if toolbar.icon_button_active(ctx, icon::LOCATE_FIXED, icon_font, "Follow Playback", state.follow_playback) {
+        state.toggle_follow_playback();
     }
 
     toolbar.separator();
 
+    // Metronome and count-in
+    if toolbar.icon_button_active(ctx, icon::TIMER, icon_font, "Metronome", state.metronome) {
+        state.toggle_metronome();
+    }
+    let click_pct = (state.metronome_volume * 100.0).round() as i32;
+    toolbar.label(&format!("Clk:{:3}", click_pct));
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Decrease Metronome Volume") {
+        state.metronome_volume = ((click_pct - 10) as f32 / 100.0).clamp(0.0, 1.0);
+    }
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Increase Metronome Volume") {
+        state.metronome_volume = ((click_pct + 10) as f32 / 100.0).clamp(0.0, 1.0);
+    }
+    let count_in_tooltip = match state.count_in_bars {
+        0 => "Count-in: Off".to_string(),
+        n => format!("Count-in: {} bar{} before playing in Edit Mode", n, if n == 1 { "" } else { "s" }),
+    };
+    if toolbar.icon_button_active(ctx, icon::HOURGLASS, icon_font, &count_in_tooltip, state.count_in_bars > 0) {
+        state.cycle_count_in();
+    }
+    if toolbar.icon_button_active(ctx, icon::REPEAT, icon_font, "Loop Pattern (F7)", state.pattern_loop) {
+        state.toggle_pattern_loop();
+    }
+
+    toolbar.separator();
+
+    // Edit mode toggle (off = navigate only, keystrokes don't modify the pattern)
+    let edit_tooltip = if state.edit_mode { "Edit Mode (`)" } else { "Navigate Mode (`)" };
+    if toolbar.icon_button_active(ctx, icon::PENCIL, icon_font, edit_tooltip, state.edit_mode) {
+        state.toggle_edit_mode();
+    }
+
     // Octave controls
     toolbar.label(&format!("Oct:{}", state.octave));
     if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Octave Down") {
@@ -139,37 +187,85 @@ fn draw_header(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_f
         state.add_channel();
     }
 
+    // Pattern length controls (16 rows at a time)
+    let length = state.current_pattern().map(|p| p.length).unwrap_or(DEFAULT_PATTERN_LEN);
+    toolbar.label(&format!("Rows:{:3}", length));
+    if toolbar.icon_button(ctx, icon::MINUS, icon_font, "Shorten Pattern") {
+        state.set_pattern_length(length.saturating_sub(16).max(16));
+    }
+    if toolbar.icon_button(ctx, icon::PLUS, icon_font, "Lengthen Pattern") {
+        state.set_pattern_length(length + 16);
+    }
+
+    toolbar.separator();
+
+    if toolbar.icon_button(ctx, icon::FOLDER_OPEN, icon_font, "Load Soundfont...") {
+        state.prompt_load_soundfont();
+    }
+
     // Second row - position info and soundfont status
     let y2 = rect.y + 40.0;
     let pattern_num = state.song.arrangement.get(state.current_pattern_idx).copied().unwrap_or(0);
-    draw_text(
-        &format!("Pos: {:02}/{:02}  Pat: {:02}  Row: {:03}/{:03}  Ch: {}",
+    let pos_dims = draw_text(
+        &format!("Pos: {:02}/{:02}  Pat: {:02}/{:02}  Row: {:03}/{:03}  Ch: {}",
                  state.current_pattern_idx,
                  state.song.arrangement.len(),
                  pattern_num,
+                 state.song.patterns.len(),
                  state.current_row,
                  state.current_pattern().map(|p| p.length).unwrap_or(64),
                  state.current_channel + 1),
-        rect.x + 10.0, y2 + 14.0, 12.0, TEXT_COLOR
+        rect.x + 10.0, y2 + 14.0, 12.0, theme.tracker.text
     );
 
+    // Active loop (the pattern loop wins over the region during playback)
+    let loop_text = if state.pattern_loop {
+        Some(format!("LOOP Pat {:02}", pattern_num))
+    } else {
+        state.active_loop_region().map(|(start, end)| format!("LOOP {:02}-{:02}", start, end))
+    };
+    if let Some(text) = loop_text {
+        draw_text(&text, rect.x + 20.0 + pos_dims.width, y2 + 14.0, 12.0, LOOP_COLOR);
+    }
+
     // Soundfont status
     let sf_status = state.audio.soundfont_name()
         .map(|n| format!("SF: {}", n))
         .unwrap_or_else(|| "No Soundfont".to_string());
-    draw_text(&sf_status, rect.x + 350.0, y2 + 14.0, 12.0, if state.audio.is_loaded() { TEXT_DIM } else { Color::new(0.8, 0.3, 0.3, 1.0) });
+    draw_text(&sf_status, rect.x + 350.0, y2 + 14.0, 12.0, if state.audio.is_loaded() { theme.tracker.text_dim } else { theme.error });
 
     // Status message
-    if let Some(status) = state.get_status() {
-        draw_text(status, rect.x + 550.0, y2 + 14.0, 12.0, Color::new(1.0, 0.8, 0.3, 1.0));
+    if let Some((status, severity)) = state.get_status() {
+        draw_text(status, rect.x + 550.0, y2 + 14.0, 12.0, theme.severity(severity));
     }
+
+    // Position within the whole arrangement
+    let bar_y = rect.bottom() - 2.0;
+    draw_rectangle(rect.x, bar_y, rect.w, 2.0, theme.tracker.bg);
+    draw_rectangle(rect.x, bar_y, rect.w * state.song_progress(), 2.0, SONG_PROGRESS_COLOR);
 }
 
 /// Height of the channel strip header (instrument selector, etc.)
 const CHANNEL_STRIP_HEIGHT: f32 = 36.0;
 
+/// Width of the per-channel pan slider in the channel strip
+const PAN_SLIDER_WIDTH: f32 = 60.0;
+
+/// Format a pan value for display (e.g., "L50", "C", "R100")
+fn format_pan(pan: f32) -> String {
+    let amount = (pan.abs() * 100.0).round() as i32;
+    if amount == 0 {
+        "C".to_string()
+    } else if pan < 0.0 {
+        format!("L{}", amount)
+    } else {
+        format!("R{}", amount)
+    }
+}
+
 /// Draw the pattern editor view
 fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState) {
+    let theme = ctx.theme;
     let num_channels = state.num_channels();
 
     // Calculate visible rows (accounting for channel strip header)
@@ -201,17 +297,32 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
         }
 
         // Channel number
-        let ch_color = if is_current { NOTE_COLOR } else { TEXT_COLOR };
+        let ch_color = if is_current { theme.tracker.note } else { theme.tracker.text };
         draw_text(&format!("Ch {}", ch + 1), ch_x + 4.0, rect.y + 12.0, 11.0, ch_color);
 
+        // Pan slider: click/drag to set, right-click to center
+        let pan = state.song.get_channel_pan(ch);
+        let pan_rect = Rect::new(ch_x + 40.0, rect.y + 4.0, PAN_SLIDER_WIDTH, 8.0);
+        let pan_hover = ctx.mouse.inside(&pan_rect);
+        draw_rectangle(pan_rect.x, pan_rect.y, pan_rect.w, pan_rect.h,
+            if pan_hover { Color::new(0.25, 0.25, 0.3, 1.0) } else { Color::new(0.18, 0.18, 0.22, 1.0) });
+        let center_x = pan_rect.x + pan_rect.w * 0.5;
+        let pan_x = center_x + pan * pan_rect.w * 0.5;
+        draw_rectangle(center_x.min(pan_x), pan_rect.y + 2.0, (pan_x - center_x).abs().max(1.0), pan_rect.h - 4.0, theme.tracker.vol);
+        draw_line(center_x, pan_rect.y, center_x, pan_rect.y + pan_rect.h, 1.0, theme.tracker.text_dim);
+        draw_text(&format_pan(pan), pan_rect.right() + 4.0, rect.y + 12.0, 10.0, theme.tracker.text_dim);
+        if pan_hover && is_mouse_button_down(MouseButton::Left) {
+            let new_pan = ((ctx.mouse.x - center_x) / (pan_rect.w * 0.5)).clamp(-1.0, 1.0);
+            state.set_channel_pan(ch, new_pan);
+        }
+        if pan_hover && is_mouse_button_pressed(MouseButton::Right) {
+            state.set_channel_pan(ch, 0.0);
+        }
+
         // Instrument selector: [-] [instrument name] [+]
         let inst = state.song.get_channel_instrument(ch);
-        let presets = state.audio.get_preset_names();
-        let inst_name = presets
-            .iter()
-            .find(|(_, p, _)| *p == inst)
-            .map(|(_, _, n)| n.as_str())
-            .unwrap_or("---");
+        let bank = state.song.get_channel_bank(ch);
+        let inst_name = state.audio.preset_name(bank, inst).unwrap_or_else(|| "---".to_string());
 
         // Truncate instrument name to fit
         let display_name: String = if inst_name.len() > 12 {
@@ -225,31 +336,25 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
         let minus_hover = ctx.mouse.inside(&minus_rect);
         draw_rectangle(minus_rect.x, minus_rect.y, minus_rect.w, minus_rect.h,
             if minus_hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
-        draw_text("-", minus_rect.x + 5.0, minus_rect.y + 12.0, 12.0, TEXT_COLOR);
+        draw_text("-", minus_rect.x + 5.0, minus_rect.y + 12.0, 12.0, theme.tracker.text);
         if minus_hover && is_mouse_button_pressed(MouseButton::Left) {
             let new_inst = inst.saturating_sub(1);
-            state.song.set_channel_instrument(ch, new_inst);
-            if ch == state.current_channel {
-                state.audio.set_program(ch as i32, new_inst as i32);
-            }
+            state.set_channel_preset(ch, bank, new_inst);
         }
 
         // Instrument name (clickable to open instrument picker)
         let name_x = ch_x + 20.0;
-        draw_text(&format!("{:03}:{}", inst, display_name), name_x, rect.y + 28.0, 10.0, INST_COLOR);
+        draw_text(&format!("{:03}:{}", inst, display_name), name_x, rect.y + 28.0, 10.0, theme.tracker.inst);
 
         // [+] button
         let plus_rect = Rect::new(ch_x + CHANNEL_WIDTH - 20.0, rect.y + 16.0, 16.0, 16.0);
         let plus_hover = ctx.mouse.inside(&plus_rect);
         draw_rectangle(plus_rect.x, plus_rect.y, plus_rect.w, plus_rect.h,
             if plus_hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
-        draw_text("+", plus_rect.x + 4.0, plus_rect.y + 12.0, 12.0, TEXT_COLOR);
+        draw_text("+", plus_rect.x + 4.0, plus_rect.y + 12.0, 12.0, theme.tracker.text);
         if plus_hover && is_mouse_button_pressed(MouseButton::Left) {
             let new_inst = (inst + 1).min(127);
-            state.song.set_channel_instrument(ch, new_inst);
-            if ch == state.current_channel {
-                state.audio.set_program(ch as i32, new_inst as i32);
-            }
+            state.set_channel_preset(ch, bank, new_inst);
         }
 
         x += CHANNEL_WIDTH;
@@ -260,7 +365,7 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
 
     // === Column headers (Note, Inst, Vol, etc.) ===
     let header_y = rect.y + CHANNEL_STRIP_HEIGHT;
-    draw_rectangle(rect.x, header_y, rect.w, ROW_HEIGHT, HEADER_COLOR);
+    draw_rectangle(rect.x, header_y, rect.w, ROW_HEIGHT, theme.tracker.header);
 
     x = rect.x + ROW_NUM_WIDTH;
     for ch in 0..num_channels {
@@ -279,7 +384,7 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
 
         // Column labels
         let is_current = ch == state.current_channel;
-        let label_color = if is_current { NOTE_COLOR } else { TEXT_DIM };
+        let label_color = if is_current { theme.tracker.note } else { theme.tracker.text_dim };
         draw_text("Not", ch_x + 4.0, header_y + 13.0, 10.0, label_color);
         draw_text("In", ch_x + NOTE_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
         draw_text("Vl", ch_x + NOTE_WIDTH + INST_WIDTH + 2.0, header_y + 13.0, 10.0, label_color);
@@ -292,6 +397,17 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
     let grid_y_start = rect.y + CHANNEL_STRIP_HEIGHT + ROW_HEIGHT;
     let grid_rect = Rect::new(rect.x, grid_y_start, rect.w, rect.h - CHANNEL_STRIP_HEIGHT - ROW_HEIGHT);
 
+    // While following, the view is centered on the playback position (smooth, sub-row);
+    // otherwise it sits on scroll_row
+    let following = state.playing && state.follow_playback && state.playback_pattern_idx == state.current_pattern_idx;
+    let scroll = if following {
+        let centered = state.playback_position() - state.visible_rows as f32 * 0.5;
+        state.scroll_row = (centered.round().max(0.0) as usize).min(pattern_length.saturating_sub(state.visible_rows));
+        centered
+    } else {
+        state.scroll_row as f32
+    };
+
     // Mouse wheel scrolling
     if ctx.mouse.inside(&grid_rect) {
         let scroll = mouse_wheel().1;
@@ -306,12 +422,11 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
         let mouse_x = ctx.mouse.x;
         let mouse_y = ctx.mouse.y;
 
-        // Calculate clicked row
-        let clicked_screen_row = ((mouse_y - grid_y_start) / ROW_HEIGHT) as usize;
-        let clicked_row = state.scroll_row + clicked_screen_row;
+        // Calculate clicked row (the view may be scrolled by a fraction of a row)
+        let clicked_row = (scroll + (mouse_y - grid_y_start) / ROW_HEIGHT).floor();
 
-        if clicked_row < pattern_length {
-            state.current_row = clicked_row;
+        if clicked_row >= 0.0 && (clicked_row as usize) < pattern_length {
+            state.current_row = clicked_row as usize;
 
             // Calculate clicked channel and column
             let rel_x = mouse_x - rect.x - ROW_NUM_WIDTH;
@@ -333,43 +448,70 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
                     } else {
                         4 // Effect param
                     };
+
+                    // Double-clicking an instrument opens it in the instruments view
+                    if state.current_column == 1 && ctx.double_clicked(&grid_rect) {
+                        let instrument = state.current_pattern()
+                            .and_then(|p| p.get(clicked_channel, clicked_row as usize))
+                            .and_then(|note| note.instrument);
+                        if let Some(instrument) = instrument {
+                            state.jump_to_instrument(instrument);
+                        }
+                    }
                 }
             }
         }
     }
 
     // Now re-borrow pattern for drawing
+    let presets = state.audio.get_preset_names();
     let pattern = match state.current_pattern() {
         Some(p) => p,
         None => return,
     };
 
-    // Draw rows
-    let start_row = state.scroll_row;
-    let visible_rows = state.visible_rows;
-    let end_row = (start_row + visible_rows).min(pattern.length);
+    // Draw rows, clipped to the grid so partially scrolled rows don't cover the header
+    let first_row = scroll.floor() as i32;
+    let row_offset = (scroll - scroll.floor()) * ROW_HEIGHT;
     let pattern_num_channels = pattern.num_channels();
 
-    for row_idx in start_row..end_row {
-        let screen_row = row_idx - start_row;
-        let y = rect.y + CHANNEL_STRIP_HEIGHT + ROW_HEIGHT + screen_row as f32 * ROW_HEIGHT;
+    let dpi = screen_dpi_scale();
+    gl_use_default_material();
+    unsafe {
+        get_internal_gl().quad_gl.scissor(
+            Some((
+                (grid_rect.x * dpi) as i32,
+                (grid_rect.y * dpi) as i32,
+                (grid_rect.w * dpi) as i32,
+                (grid_rect.h * dpi) as i32
+            ))
+        );
+    }
+
+    let rows = (0..=state.visible_rows).filter_map(|screen_row| {
+        let row_idx = first_row + screen_row as i32;
+        (row_idx >= 0 && (row_idx as usize) < pattern.length).then_some((screen_row, row_idx as usize))
+    });
+
+    for (screen_row, row_idx) in rows {
+        let y = grid_y_start + screen_row as f32 * ROW_HEIGHT - row_offset;
 
         // Row background
         let row_bg = if state.playing && row_idx == state.playback_row && state.playback_pattern_idx == state.current_pattern_idx {
-            PLAYBACK_ROW_COLOR
+            theme.tracker.playback_row
         } else if row_idx == state.current_row {
-            ROW_HIGHLIGHT
+            theme.tracker.row_highlight
         } else if row_idx % (rows_per_beat as usize * 4) == 0 {
-            ROW_BEAT
+            theme.tracker.row_beat
         } else if row_idx % 2 == 0 {
-            ROW_EVEN
+            theme.tracker.row_even
         } else {
-            ROW_ODD
+            theme.tracker.row_odd
         };
         draw_rectangle(rect.x, y, rect.w, ROW_HEIGHT, row_bg);
 
         // Row number
-        let row_color = if row_idx % (rows_per_beat as usize) == 0 { TEXT_COLOR } else { TEXT_DIM };
+        let row_color = if row_idx % (rows_per_beat as usize) == 0 { theme.tracker.text } else { theme.tracker.text_dim };
         draw_text(&format!("{:02X}", row_idx), rect.x + 4.0, y + 14.0, 12.0, row_color);
 
         // Draw each channel
@@ -393,62 +535,154 @@ fn draw_pattern_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState)
                     3 => FX_WIDTH,
                     _ => FXPARAM_WIDTH,
                 };
-                draw_rectangle(col_x, y, col_w, ROW_HEIGHT, CURSOR_COLOR);
+                let cursor_color = if state.edit_mode { theme.tracker.cursor_edit } else { theme.tracker.cursor };
+                draw_rectangle(col_x, y, col_w, ROW_HEIGHT, cursor_color);
+                if state.edit_mode {
+                    draw_rectangle_lines(col_x, y, col_w, ROW_HEIGHT, 1.0, HEX_PENDING_COLOR);
+                }
             }
 
+            // Partially typed hex value in this cell (first nibble entered)
+            let pending = state.hex_entry
+                .filter(|e| e.pattern_idx == state.current_pattern_idx && e.row == row_idx && e.channel == ch)
+                .map(|e| (e.column, format!("{:X}_", e.high)));
+            let pending_str = |column: usize| pending.as_ref().filter(|(c, _)| *c == column).map(|(_, s)| s.clone());
+
             // Note
             let note_str = note.pitch_name().unwrap_or_else(|| "---".to_string());
-            let note_color = if note.pitch.is_some() { NOTE_COLOR } else { TEXT_DIM };
+            let note_color = if note.pitch.is_some() { theme.tracker.note } else { theme.tracker.text_dim };
             draw_text(&note_str, x + 2.0, y + 14.0, 12.0, note_color);
 
             // Instrument
-            let inst_str = note.instrument.map(|i| format!("{:02X}", i)).unwrap_or_else(|| "--".to_string());
-            let inst_color = if note.instrument.is_some() { INST_COLOR } else { TEXT_DIM };
+            let (inst_str, inst_color) = match pending_str(1) {
+                Some(partial) => (partial, HEX_PENDING_COLOR),
+                None => (
+                    note.instrument.map(|i| format!("{:02X}", i)).unwrap_or_else(|| "--".to_string()),
+                    if note.instrument.is_some() { theme.tracker.inst } else { theme.tracker.text_dim },
+                ),
+            };
             draw_text(&inst_str, x + NOTE_WIDTH + 2.0, y + 14.0, 12.0, inst_color);
+            let inst_rect = Rect::new(x + NOTE_WIDTH, y, INST_WIDTH, ROW_HEIGHT);
+            if ctx.mouse.inside(&inst_rect) && ctx.mouse.inside(&grid_rect) {
+                if let Some(program) = note.instrument {
+                    let bank = state.song.get_channel_bank(ch);
+                    let name = presets.iter()
+                        .find(|(b, p, _)| *b == bank && *p == program)
+                        .map_or("---", |(_, _, n)| n.as_str());
+                    ctx.set_tooltip(&format!("Bank {:03}, Program {:03}: {}", bank, program, name), ctx.mouse.x, ctx.mouse.y);
+                }
+            }
 
             // Volume
-            let vol_str = note.volume.map(|v| format!("{:02X}", v)).unwrap_or_else(|| "--".to_string());
-            let vol_color = if note.volume.is_some() { VOL_COLOR } else { TEXT_DIM };
+            let (vol_str, vol_color) = match pending_str(2) {
+                Some(partial) => (partial, HEX_PENDING_COLOR),
+                None => (
+                    note.volume.map(|v| format!("{:02X}", v)).unwrap_or_else(|| "--".to_string()),
+                    if note.volume.is_some() { theme.tracker.vol } else { theme.tracker.text_dim },
+                ),
+            };
             draw_text(&vol_str, x + NOTE_WIDTH + INST_WIDTH + 2.0, y + 14.0, 12.0, vol_color);
 
             // Effect
             let fx_str = note.effect.map(|e| e.to_string()).unwrap_or_else(|| "-".to_string());
-            let fx_color = if note.effect.is_some() { FX_COLOR } else { TEXT_DIM };
+            let fx_color = if note.effect.is_some() { theme.tracker.fx } else { theme.tracker.text_dim };
             draw_text(&fx_str, x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + 2.0, y + 14.0, 12.0, fx_color);
 
             // Effect param
-            let fxp_str = note.effect_param.map(|p| format!("{:02X}", p)).unwrap_or_else(|| "--".to_string());
-            draw_text(&fxp_str, x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH + 2.0, y + 14.0, 12.0, fx_color);
+            let (fxp_str, fxp_color) = match pending_str(4) {
+                Some(partial) => (partial, HEX_PENDING_COLOR),
+                None => (
+                    note.effect_param.map(|p| format!("{:02X}", p)).unwrap_or_else(|| "--".to_string()),
+                    fx_color,
+                ),
+            };
+            draw_text(&fxp_str, x + NOTE_WIDTH + INST_WIDTH + VOL_WIDTH + FX_WIDTH + 2.0, y + 14.0, 12.0, fxp_color);
 
             x += CHANNEL_WIDTH;
         }
     }
-}
 
-/// Draw the arrangement view (placeholder)
-fn draw_arrangement_view(_ctx: &mut UiContext, rect: Rect, state: &TrackerState) {
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, BG_COLOR);
+    unsafe {
+        get_internal_gl().quad_gl.scissor(None);
+    }
+}
 
-    // Header
-    draw_text("Song Arrangement", rect.x + 10.0, rect.y + 24.0, 16.0, TEXT_COLOR);
+/// Draw the arrangement view. Click an entry to set the loop start, Shift+click the end.
+fn draw_arrangement_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_font: Option<&Font>) {
+    let theme = ctx.theme;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.tracker.bg);
+
+    // Header, with the pattern copy buttons for the current position
+    draw_text("Song Arrangement", rect.x + 10.0, rect.y + 24.0, 16.0, theme.tracker.text);
+    let button_x = rect.x + 170.0;
+    if icon_button(ctx, Rect::new(button_x, rect.y + 8.0, 24.0, 24.0), icon::CLIPBOARD_COPY, icon_font,
+        "Duplicate Pattern: copy it to a new pattern after this position (Ctrl+D; Ctrl+Shift+D replaces this position)") {
+        state.duplicate_pattern(false);
+    }
+    if icon_button(ctx, Rect::new(button_x + 28.0, rect.y + 8.0, 24.0, 24.0), icon::UNLINK, icon_font,
+        "Make Unique: give this position its own copy of a pattern used elsewhere (Ctrl+U)") {
+        state.make_pattern_unique();
+    }
 
-    // Draw arrangement as list
+    // Draw arrangement as list, with the loop region bracketed on the left
+    let list_x = rect.x + 20.0;
+    let loop_region = state.active_loop_region();
+    let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+    let mut clicked = None;
     let mut y = rect.y + 50.0;
     for (i, &pattern_idx) in state.song.arrangement.iter().enumerate() {
+        let row_rect = Rect::new(list_x, y, 200.0, 24.0);
         let is_current = i == state.current_pattern_idx;
-        let bg = if is_current { ROW_HIGHLIGHT } else if i % 2 == 0 { ROW_EVEN } else { ROW_ODD };
-        draw_rectangle(rect.x + 10.0, y, 200.0, 24.0, bg);
+        let bg = if is_current { theme.tracker.row_highlight } else if i % 2 == 0 { theme.tracker.row_even } else { theme.tracker.row_odd };
+        draw_rectangle(row_rect.x, row_rect.y, row_rect.w, row_rect.h, bg);
         draw_text(
             &format!("{:02}: Pattern {:02}", i, pattern_idx),
-            rect.x + 20.0, y + 16.0, 14.0,
-            if is_current { NOTE_COLOR } else { TEXT_COLOR }
+            list_x + 10.0, y + 16.0, 14.0,
+            if is_current { theme.tracker.note } else { theme.tracker.text }
         );
+        // Shared patterns: editing one changes every position using it
+        let uses = state.song.pattern_use_count(pattern_idx);
+        if uses > 1 {
+            draw_text(&format!("x{}", uses), row_rect.right() - 30.0, y + 16.0, 12.0, theme.tracker.text_dim);
+        }
+        if let Some((start, end)) = loop_region {
+            if (start..=end).contains(&i) {
+                let bx = list_x - 6.0;
+                draw_rectangle(bx, y, 2.0, if i == end { 24.0 } else { 26.0 }, LOOP_COLOR);
+                if i == start {
+                    draw_rectangle(bx, y, 5.0, 2.0, LOOP_COLOR);
+                }
+                if i == end {
+                    draw_rectangle(bx, y + 22.0, 5.0, 2.0, LOOP_COLOR);
+                }
+            }
+        }
+        if ctx.mouse.clicked(&row_rect) {
+            clicked = Some(i);
+        }
         y += 26.0;
     }
+    if let Some(i) = clicked {
+        if shift {
+            state.set_loop_end(i);
+        } else {
+            state.set_loop_start(i);
+        }
+    }
 
-    draw_text("(Press + to add pattern, - to remove)", rect.x + 10.0, rect.y + rect.h - 30.0, 12.0, TEXT_DIM);
+    draw_text("(Press + to add a pattern after the current one, - to remove it; Ctrl+D duplicates it, Ctrl+U makes a shared one unique; click sets loop start, Shift+click loop end)", rect.x + 10.0, rect.y + rect.h - 30.0, 12.0, theme.tracker.text_dim);
 }
 
+/// Keyboard keys that play notes (two octaves, see `TrackerState::key_to_note`)
+const NOTE_KEYS: [KeyCode; 24] = [
+    KeyCode::Z, KeyCode::S, KeyCode::X, KeyCode::D, KeyCode::C,
+    KeyCode::V, KeyCode::G, KeyCode::B, KeyCode::H, KeyCode::N,
+    KeyCode::J, KeyCode::M,
+    KeyCode::Q, KeyCode::Key2, KeyCode::W, KeyCode::Key3, KeyCode::E,
+    KeyCode::R, KeyCode::Key5, KeyCode::T, KeyCode::Key6, KeyCode::Y,
+    KeyCode::Key7, KeyCode::U,
+];
+
 /// Piano key layout for drawing
 const PIANO_WHITE_KEYS: [(u8, &str); 7] = [
     (0, "C"), (2, "D"), (4, "E"), (5, "F"), (7, "G"), (9, "A"), (11, "B")
@@ -472,21 +706,25 @@ fn get_key_label(offset: u8) -> Option<&'static str> {
 
 /// Check if the keyboard key for a given note offset is currently pressed
 fn is_note_key_down(offset: u8) -> bool {
-    let key = match offset {
-        0 => KeyCode::Z, 1 => KeyCode::S, 2 => KeyCode::X, 3 => KeyCode::D, 4 => KeyCode::C,
-        5 => KeyCode::V, 6 => KeyCode::G, 7 => KeyCode::B, 8 => KeyCode::H, 9 => KeyCode::N,
-        10 => KeyCode::J, 11 => KeyCode::M,
-        12 => KeyCode::Q, 13 => KeyCode::Key2, 14 => KeyCode::W, 15 => KeyCode::Key3, 16 => KeyCode::E,
-        17 => KeyCode::R, 18 => KeyCode::Key5, 19 => KeyCode::T, 20 => KeyCode::Key6, 21 => KeyCode::Y,
-        22 => KeyCode::Key7, 23 => KeyCode::U,
-        _ => return false,
-    };
-    is_key_down(key)
+    NOTE_KEYS.get(offset as usize).is_some_and(|&key| is_key_down(key))
+}
+
+/// Scroll offset of a list showing `visible` items that brings item `index` into
+/// view, moving as little as possible from `scroll`
+fn scroll_to_reveal(scroll: usize, index: usize, visible: usize) -> usize {
+    if index < scroll {
+        index
+    } else if index >= scroll + visible {
+        (index + 1).saturating_sub(visible)
+    } else {
+        scroll
+    }
 }
 
 /// Draw the instruments view with piano keyboard
-fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState) {
-    draw_rectangle(rect.x, rect.y, rect.w, rect.h, BG_COLOR);
+fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerState, icon_font: Option<&Font>) {
+    let theme = ctx.theme;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.tracker.bg);
 
     // Split into left (instrument list) and right (piano + info)
     let list_width = 280.0;
@@ -494,13 +732,79 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
 
     // === LEFT: Instrument List ===
     draw_rectangle(list_rect.x, list_rect.y, list_rect.w, list_rect.h, Color::new(0.09, 0.09, 0.11, 1.0));
-    draw_text("Instruments (GM)", list_rect.x + 10.0, list_rect.y + 20.0, 14.0, TEXT_COLOR);
+    draw_text("Instruments", list_rect.x + 10.0, list_rect.y + 20.0, 14.0, theme.tracker.text);
+
+    // Back to the pattern cell this view was opened from
+    if state.instrument_return.is_some() {
+        let back_rect = Rect::new(list_rect.x + list_rect.w - 60.0, list_rect.y + 6.0, 55.0, 16.0);
+        let hover = ctx.mouse.inside(&back_rect);
+        draw_rectangle(back_rect.x, back_rect.y, back_rect.w, back_rect.h,
+            if hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
+        draw_text("< Back", back_rect.x + 6.0, back_rect.y + 12.0, 12.0, theme.tracker.text);
+        if hover {
+            ctx.set_tooltip("Back to the pattern (Escape)", ctx.mouse.x, ctx.mouse.y);
+            if is_mouse_button_pressed(MouseButton::Left) {
+                state.return_from_instruments();
+                return;
+            }
+        }
+    }
 
-    // Scrollable instrument list
-    let presets = state.audio.get_preset_names();
+    // Bank selector: [<] Bank 000 [>], and the favorites filter
+    let banks = state.audio.banks();
+    let bank_pos = banks.iter().position(|&b| b == state.instrument_bank).unwrap_or(0);
+    let bank_y = list_rect.y + 28.0;
+    let prev_rect = Rect::new(list_rect.x + 5.0, bank_y, 16.0, 16.0);
+    let next_rect = Rect::new(list_rect.x + 95.0, bank_y, 16.0, 16.0);
+    let fav_rect = Rect::new(list_rect.x + list_rect.w - 95.0, bank_y, 90.0, 16.0);
+    for (button, label) in [(prev_rect, "<"), (next_rect, ">")] {
+        let hover = ctx.mouse.inside(&button) && !state.instrument_favorites_only;
+        draw_rectangle(button.x, button.y, button.w, button.h,
+            if hover { Color::new(0.3, 0.3, 0.35, 1.0) } else { Color::new(0.2, 0.2, 0.25, 1.0) });
+        draw_text(label, button.x + 5.0, button.y + 12.0, 12.0, theme.tracker.text);
+    }
+    let bank_label = if state.instrument_favorites_only { "All banks".to_string() } else { format!("Bank {:03}", banks[bank_pos]) };
+    draw_text(&bank_label, list_rect.x + 27.0, bank_y + 12.0, 12.0, theme.tracker.inst);
+    if !state.instrument_favorites_only && is_mouse_button_pressed(MouseButton::Left) {
+        let step = if ctx.mouse.inside(&prev_rect) {
+            banks.len() - 1
+        } else if ctx.mouse.inside(&next_rect) {
+            1
+        } else {
+            0
+        };
+        if step != 0 {
+            state.instrument_bank = banks[(bank_pos + step) % banks.len()];
+            state.instrument_scroll = 0;
+        }
+    }
+    let fav_hover = ctx.mouse.inside(&fav_rect);
+    draw_rectangle(fav_rect.x, fav_rect.y, fav_rect.w, fav_rect.h, if state.instrument_favorites_only {
+        Color::new(0.25, 0.3, 0.35, 1.0)
+    } else if fav_hover {
+        Color::new(0.3, 0.3, 0.35, 1.0)
+    } else {
+        Color::new(0.2, 0.2, 0.25, 1.0)
+    });
+    draw_icon_centered(icon_font, icon::STAR, &Rect::new(fav_rect.x, fav_rect.y, 16.0, 16.0), 11.0, theme.tracker.note);
+    draw_text("Favorites", fav_rect.x + 20.0, fav_rect.y + 12.0, 12.0, theme.tracker.text);
+    if fav_hover && is_mouse_button_pressed(MouseButton::Left) {
+        state.instrument_favorites_only = !state.instrument_favorites_only;
+        state.instrument_scroll = 0;
+    }
+
+    // Scrollable instrument list: the selected bank, or the starred instruments of every bank
+    let all_presets = state.audio.get_preset_names();
+    let presets: Vec<&(u8, u8, String)> = all_presets.iter()
+        .filter(|(bank, program, _)| if state.instrument_favorites_only {
+            state.song.is_favorite(*bank, *program)
+        } else {
+            *bank == banks[bank_pos]
+        })
+        .collect();
     let item_height = 18.0;
-    let list_start_y = list_rect.y + 35.0;
-    let list_height = list_rect.h - 45.0;
+    let list_start_y = list_rect.y + 50.0;
+    let list_height = list_rect.h - 60.0;
     let visible_items = (list_height / item_height) as usize;
     let max_scroll = presets.len().saturating_sub(visible_items);
 
@@ -515,15 +819,24 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
         }
     }
 
-    let current_inst = state.current_instrument();
+    // Scroll a preset picked elsewhere (pattern jump, [ and ]) into view
+    if let Some(target) = state.reveal_instrument.take() {
+        if let Some(index) = presets.iter().position(|(bank, program, _)| (*bank, *program) == target) {
+            state.instrument_scroll = scroll_to_reveal(state.instrument_scroll.min(max_scroll), index, visible_items);
+        }
+    }
+
+    let current = (state.current_bank(), state.current_instrument());
     let scroll_offset = state.instrument_scroll.min(max_scroll);
 
-    for (i, (_, program, name)) in presets.iter().enumerate().skip(scroll_offset).take(visible_items) {
+    for (i, (bank, program, name)) in presets.iter().enumerate().skip(scroll_offset).take(visible_items) {
         let y = list_start_y + (i - scroll_offset) as f32 * item_height;
         let item_rect = Rect::new(list_rect.x + 5.0, y, list_rect.w - 10.0, item_height);
+        let star_rect = Rect::new(item_rect.x, y, item_height, item_height);
 
-        let is_current = *program == current_inst;
+        let is_current = (*bank, *program) == current;
         let is_hovered = ctx.mouse.inside(&item_rect);
+        let is_favorite = state.song.is_favorite(*bank, *program);
 
         // Background
         let bg = if is_current {
@@ -537,14 +850,29 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
         };
         draw_rectangle(item_rect.x, item_rect.y, item_rect.w, item_rect.h, bg);
 
-        // Click to select (sets the current channel's instrument)
+        // Star toggles the favorite; clicking elsewhere selects (bank select + program change)
+        let star_hovered = ctx.mouse.inside(&star_rect);
+        let star_color = if is_favorite { theme.tracker.note } else if star_hovered { theme.tracker.text } else { theme.tracker.text_dim };
+        draw_icon_centered(icon_font, icon::STAR, &star_rect, 11.0, star_color);
         if is_hovered && is_mouse_button_pressed(MouseButton::Left) {
-            state.set_current_instrument(*program);
+            if star_hovered {
+                state.song.toggle_favorite(*bank, *program);
+            } else {
+                state.set_current_preset(*bank, *program);
+            }
         }
 
-        // Text
-        let color = if is_current { NOTE_COLOR } else { TEXT_COLOR };
-        draw_text(&format!("{:03}: {}", program, name), item_rect.x + 5.0, y + 13.0, 12.0, color);
+        // Text (favorites span banks, so they show theirs)
+        let color = if is_current { theme.tracker.note } else { theme.tracker.text };
+        let label = if state.instrument_favorites_only {
+            format!("{:03}:{:03} {}", bank, program, name)
+        } else {
+            format!("{:03}: {}", program, name)
+        };
+        draw_text(&label, item_rect.x + item_height + 2.0, y + 13.0, 12.0, color);
+    }
+    if presets.is_empty() && state.instrument_favorites_only {
+        draw_text("Star instruments to list them here", list_rect.x + 10.0, list_start_y + 13.0, 12.0, theme.tracker.text_dim);
     }
 
     // Draw scrollbar if needed
@@ -567,7 +895,7 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
     let black_key_w = 24.0;
     let black_key_h = 75.0;
 
-    draw_text(&format!("Piano - Octave {} & {}", state.octave, state.octave + 1), piano_x, piano_y - 10.0, 14.0, TEXT_COLOR);
+    draw_text(&format!("Piano - Octave {} & {}", state.octave, state.octave + 1), piano_x, piano_y - 10.0, 14.0, theme.tracker.text);
 
     // Draw two octaves of keys
     for octave_offset in 0..2 {
@@ -652,20 +980,17 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
 
     // Current instrument info below piano
     let info_y = piano_y + white_key_h + 30.0;
-    let current_inst = state.current_instrument();
-    let current_name = presets.iter()
-        .find(|(_, p, _)| *p == current_inst)
-        .map(|(_, _, n)| n.as_str())
-        .unwrap_or("Unknown");
+    let (current_bank, current_inst) = current;
+    let current_name = state.audio.preset_name(current_bank, current_inst).unwrap_or_else(|| "Unknown".to_string());
 
-    draw_text(&format!("Current: {:03} - {}", current_inst, current_name),
-              piano_x, info_y, 16.0, INST_COLOR);
+    draw_text(&format!("Current: {:03}:{:03} - {}", current_bank, current_inst, current_name),
+              piano_x, info_y, 16.0, theme.tracker.inst);
 
     // === EFFECT KNOBS ===
     let effects_y = info_y + 30.0;
     let ch = state.current_channel;
 
-    draw_text("Effects Preview", piano_x, effects_y, 14.0, TEXT_COLOR);
+    draw_text("Effects Preview", piano_x, effects_y, 14.0, theme.tracker.text);
 
     let knob_radius = 28.0;
     let knob_spacing = 70.0;
@@ -773,7 +1098,7 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
 
     draw_rectangle(reset_rect.x, reset_rect.y, reset_rect.w, reset_rect.h,
         if reset_hovered { Color::new(0.25, 0.25, 0.3, 1.0) } else { Color::new(0.18, 0.18, 0.22, 1.0) });
-    draw_text("Reset All", reset_rect.x + 22.0, reset_rect.y + 14.0, 12.0, TEXT_COLOR);
+    draw_text("Reset All", reset_rect.x + 22.0, reset_rect.y + 14.0, 12.0, theme.tracker.text);
 
     if reset_hovered && is_mouse_button_pressed(MouseButton::Left) {
         state.reset_preview_effects();
@@ -783,15 +1108,42 @@ fn draw_instruments_view(ctx: &mut UiContext, rect: Rect, state: &mut TrackerSta
     // Help text
     let help_y = reset_y + 35.0;
     draw_text("Click keys to preview | Use keyboard (Z-M, Q-U) to enter notes",
-              piano_x, help_y, 12.0, TEXT_DIM);
+              piano_x, help_y, 12.0, theme.tracker.text_dim);
     draw_text("[ ] = prev/next instrument | +/- = octave up/down",
-              piano_x, help_y + 17.0, 12.0, TEXT_DIM);
+              piano_x, help_y + 17.0, 12.0, theme.tracker.text_dim);
     draw_text("Drag knobs to adjust | Click value to type",
-              piano_x, help_y + 34.0, 12.0, TEXT_DIM);
+              piano_x, help_y + 34.0, 12.0, theme.tracker.text_dim);
 }
 
 /// Handle keyboard and mouse input
-fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
+fn handle_input(ctx: &mut UiContext, state: &mut TrackerState, escape_taken: bool) {
+    // A focused text field (the help filter) gets the keys
+    if ctx.text_input_focused() {
+        state.release_preview_keys();
+        return;
+    }
+
+    // Moving the cursor away (keys or mouse) commits a half-typed hex value
+    state.sync_hex_entry();
+
+    // Undo/redo (Ctrl+Z, Ctrl+Shift+Z or Ctrl+Y); Ctrl keeps the keys from entering notes
+    let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+        || is_key_down(KeyCode::LeftSuper) || is_key_down(KeyCode::RightSuper);
+    let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+    if ctrl {
+        if is_key_pressed(KeyCode::Z) && !shift {
+            state.undo();
+        } else if is_key_pressed(KeyCode::Y) || is_key_pressed(KeyCode::Z) {
+            state.redo();
+        } else if is_key_pressed(KeyCode::D) {
+            state.duplicate_pattern(shift);
+        } else if is_key_pressed(KeyCode::U) {
+            state.make_pattern_unique();
+        }
+        state.release_preview_keys();
+        return;
+    }
+
     // Navigation
     if is_key_pressed(KeyCode::Up) {
         state.cursor_up();
@@ -806,7 +1158,7 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
         state.cursor_right();
     }
     if is_key_pressed(KeyCode::Tab) {
-        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+        if shift {
             state.prev_channel();
         } else {
             state.next_channel();
@@ -840,29 +1192,53 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
     if is_key_pressed(KeyCode::Space) {
         state.toggle_playback();
     }
-    if is_key_pressed(KeyCode::Escape) {
+    if is_key_pressed(KeyCode::Escape) && !escape_taken && !state.return_from_instruments() && !state.clear_loops() {
         state.stop_playback();
     }
 
-    // Octave
-    if is_key_pressed(KeyCode::KpAdd) || (is_key_down(KeyCode::LeftShift) && is_key_pressed(KeyCode::Equal)) {
-        state.octave = (state.octave + 1).min(9);
-        state.set_status(&format!("Octave: {}", state.octave), 1.0);
+    // Loop region (at the current arrangement position) and pattern loop
+    if is_key_pressed(KeyCode::F5) {
+        state.set_loop_start(state.current_pattern_idx);
     }
-    if is_key_pressed(KeyCode::KpSubtract) || is_key_pressed(KeyCode::Minus) {
-        state.octave = state.octave.saturating_sub(1);
-        state.set_status(&format!("Octave: {}", state.octave), 1.0);
+    if is_key_pressed(KeyCode::F6) {
+        state.set_loop_end(state.current_pattern_idx);
+    }
+    if is_key_pressed(KeyCode::F7) {
+        state.toggle_pattern_loop();
+    }
+
+    // Octave (+/- add and remove patterns in the arrangement view instead)
+    let plus = is_key_pressed(KeyCode::KpAdd) || (shift && is_key_pressed(KeyCode::Equal));
+    let minus = is_key_pressed(KeyCode::KpSubtract) || is_key_pressed(KeyCode::Minus);
+    if state.view == TrackerView::Arrangement {
+        if plus {
+            state.insert_arrangement_entry();
+        }
+        if minus {
+            state.remove_arrangement_entry();
+        }
+    } else {
+        if plus {
+            state.octave = (state.octave + 1).min(9);
+            state.set_status(&format!("Octave: {}", state.octave), 1.0);
+        }
+        if minus {
+            state.octave = state.octave.saturating_sub(1);
+            state.set_status(&format!("Octave: {}", state.octave), 1.0);
+        }
     }
 
     // Instrument selection (for current channel)
     if is_key_pressed(KeyCode::LeftBracket) {
         let new_inst = state.current_instrument().saturating_sub(1);
         state.set_current_instrument(new_inst);
+        state.reveal_instrument = Some((state.current_bank(), new_inst));
         state.set_status(&format!("Instrument: {:02}", new_inst), 1.0);
     }
     if is_key_pressed(KeyCode::RightBracket) {
         let new_inst = (state.current_instrument() + 1).min(127);
         state.set_current_instrument(new_inst);
+        state.reveal_instrument = Some((state.current_bank(), new_inst));
         state.set_status(&format!("Instrument: {:02}", new_inst), 1.0);
     }
 
@@ -876,31 +1252,49 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
         state.set_status(&format!("Edit step: {}", state.edit_step), 1.0);
     }
 
-    // Delete
-    if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace) {
-        state.delete_note();
+    // Edit mode toggle
+    if is_key_pressed(KeyCode::GraveAccent) {
+        state.toggle_edit_mode();
     }
 
-    // Note entry (only in Pattern view, when in edit mode and in note column)
-    if state.view == TrackerView::Pattern && state.edit_mode && state.current_column == 0 {
-        // Check for note keys
-        let note_keys = [
-            KeyCode::Z, KeyCode::S, KeyCode::X, KeyCode::D, KeyCode::C,
-            KeyCode::V, KeyCode::G, KeyCode::B, KeyCode::H, KeyCode::N,
-            KeyCode::J, KeyCode::M,
-            KeyCode::Q, KeyCode::Key2, KeyCode::W, KeyCode::Key3, KeyCode::E,
-            KeyCode::R, KeyCode::Key5, KeyCode::T, KeyCode::Key6, KeyCode::Y,
-            KeyCode::Key7, KeyCode::U,
-        ];
+    // Delete (clears the field under the cursor, or the whole note in the note column)
+    if state.edit_mode && (is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace)) {
+        state.clear_field();
+    }
+
+    // Enter commits a half-typed hex value
+    if state.view == TrackerView::Pattern && is_key_pressed(KeyCode::Enter) {
+        state.commit_hex_entry();
+    }
 
-        for key in note_keys {
+    // Note keys: preview on key-down (Pattern and Instruments views), and in the Pattern
+    // view's note column with edit mode on, also write the note into the pattern.
+    // Hex/effect columns in edit mode use these keys for data entry instead.
+    let in_note_column = state.current_column == 0;
+    let entering_notes = state.view == TrackerView::Pattern && state.edit_mode && in_note_column;
+    let previewing = match state.view {
+        TrackerView::Pattern => !state.edit_mode || in_note_column,
+        TrackerView::Instruments => true,
+        TrackerView::Arrangement => false,
+    };
+
+    if previewing {
+        for key in NOTE_KEYS {
             if is_key_pressed(key) {
                 if let Some(pitch) = TrackerState::key_to_note(key, state.octave) {
-                    state.enter_note(pitch);
+                    if entering_notes {
+                        state.enter_note(pitch);
+                    }
+                    state.preview_key_down(key, pitch);
                 }
             }
         }
+    }
+
+    // Releases are matched to the key that started each note, whatever the view/octave now
+    state.release_preview_keys();
 
+    if entering_notes {
         // Note off with period or backtick
         if is_key_pressed(KeyCode::Period) || is_key_pressed(KeyCode::Apostrophe) {
             state.enter_note_off();
@@ -928,9 +1322,9 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
         }
     }
 
-    // Effect parameter entry (in Pattern view, edit mode, fx_param column = 4)
-    if state.view == TrackerView::Pattern && state.edit_mode && state.current_column == 4 {
-        // Hex digits 0-9, A-F for parameter entry
+    // Hex entry (in Pattern view, edit mode, instrument = 1, volume = 2, fx_param = 4)
+    if state.view == TrackerView::Pattern && state.edit_mode && matches!(state.current_column, 1 | 2 | 4) {
+        // Hex digits 0-9, A-F; two keypresses build the value (e.g. "1", "F" -> 1F)
         let hex_keys = [
             (KeyCode::Key0, 0), (KeyCode::Key1, 1), (KeyCode::Key2, 2),
             (KeyCode::Key3, 3), (KeyCode::Key4, 4), (KeyCode::Key5, 5),
@@ -942,39 +1336,7 @@ fn handle_input(_ctx: &mut UiContext, state: &mut TrackerState) {
 
         for (key, nibble) in hex_keys {
             if is_key_pressed(key) {
-                // Shift left and add new nibble (so you type XX as two keypresses)
-                state.set_effect_param_high(state.current_pattern()
-                    .and_then(|p| p.get(state.current_channel, state.current_row))
-                    .and_then(|n| n.effect_param)
-                    .map(|p| p & 0x0F)
-                    .unwrap_or(0));
-                state.set_effect_param_low(nibble);
-            }
-        }
-    }
-
-    // In Instruments view, allow keyboard to preview sounds without entering notes
-    if state.view == TrackerView::Instruments {
-        let note_keys = [
-            KeyCode::Z, KeyCode::S, KeyCode::X, KeyCode::D, KeyCode::C,
-            KeyCode::V, KeyCode::G, KeyCode::B, KeyCode::H, KeyCode::N,
-            KeyCode::J, KeyCode::M,
-            KeyCode::Q, KeyCode::Key2, KeyCode::W, KeyCode::Key3, KeyCode::E,
-            KeyCode::R, KeyCode::Key5, KeyCode::T, KeyCode::Key6, KeyCode::Y,
-            KeyCode::Key7, KeyCode::U,
-        ];
-
-        for key in note_keys {
-            if is_key_pressed(key) {
-                if let Some(pitch) = TrackerState::key_to_note(key, state.octave) {
-                    // Just preview the sound, don't enter into pattern
-                    state.audio.note_on(state.current_channel as i32, pitch as i32, 100);
-                }
-            }
-            if is_key_released(key) {
-                if let Some(pitch) = TrackerState::key_to_note(key, state.octave) {
-                    state.audio.note_off(state.current_channel as i32, pitch as i32);
-                }
+                state.enter_hex_digit(nibble);
             }
         }
     }
@@ -2,13 +2,15 @@
 //!
 //! A pattern-based music tracker with SF2 soundfont support.
 //! Inspired by Picotron's tracker design.
+//!
+//! Songs, the sequencer and the mixer are in the library's `music` module; this
+//! is the UI and the audio device output.
 
 mod state;
 mod audio;
-mod pattern;
 mod layout;
 
 pub use state::TrackerState;
 pub use audio::AudioEngine;
-pub use pattern::*;
+pub use crate::music::{PcmSound, VoiceId};
 pub use layout::draw_tracker;
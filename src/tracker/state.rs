@@ -1,8 +1,11 @@
 //! Tracker editor state
 
 use super::audio::AudioEngine;
-use super::pattern::{Song, Note, Effect, MAX_CHANNELS};
+use crate::music::{Song, Note, MAX_CHANNELS};
+use crate::music::{SequencerCommand, SequencerSettings, BEATS_PER_BAR};
+use crate::music::{SongEdit, UndoEntry, MAX_UNDO};
 use std::path::PathBuf;
+use crate::ui::{Severity, StatusQueue};
 
 /// Tracker view mode
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -15,6 +18,28 @@ pub enum TrackerView {
     Instruments,
 }
 
+/// A two-digit hex value being typed into the pattern, with only the first nibble entered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexEntry {
+    /// Cell being edited (pattern_idx, row, channel, column)
+    pub pattern_idx: usize,
+    pub row: usize,
+    pub channel: usize,
+    pub column: usize,
+    /// First nibble typed
+    pub high: u8,
+}
+
+/// Pattern cursor to restore after a trip to the instruments view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternCursor {
+    pub pattern_idx: usize,
+    pub row: usize,
+    pub channel: usize,
+    pub column: usize,
+    pub scroll_row: usize,
+}
+
 /// Tracker editor state
 pub struct TrackerState {
     /// The current song being edited
@@ -45,16 +70,43 @@ pub struct TrackerState {
     pub edit_step: usize,
     /// Is editing mode active? (vs. navigation only)
     pub edit_mode: bool,
+    /// Partial hex entry in the instrument/volume/fx param columns (None = nothing pending)
+    pub hex_entry: Option<HexEntry>,
 
-    // Playback state
+    // Playback state (the song is sequenced in the audio callback; these mirror
+    // its position, read back every frame)
     /// Is playback active?
     pub playing: bool,
-    /// Current playback row
+    /// Row sounding now
     pub playback_row: usize,
-    /// Current playback pattern in arrangement
+    /// Arrangement position sounding now
     pub playback_pattern_idx: usize,
-    /// Time accumulator for playback timing
-    pub playback_time: f64,
+    /// How far through the playback row (0-1)
+    pub playback_fraction: f32,
+    /// Keep playing while another tool (World, Assets, Home) is active
+    pub background_playback: bool,
+    /// Keep the playback row centered in the pattern view (and switch patterns with the song)
+    pub follow_playback: bool,
+    /// Click on every beat during playback
+    pub metronome: bool,
+    /// Metronome click volume (0.0-1.0)
+    pub metronome_volume: f32,
+    /// Bars of clicks before playback starts in edit mode (0 = off)
+    pub count_in_bars: u8,
+    /// Rows of count-in left before the first row plays (0 = not counting in)
+    pub count_in_rows: usize,
+    /// Playback frozen (a playtest is paused)
+    playback_held: bool,
+    /// Song generation, tempo and settings last sent to the sequencer
+    synced_generation: Option<u64>,
+    synced_bpm: u16,
+    synced_settings: Option<SequencerSettings>,
+    /// Sequencer's count of playbacks that stopped by themselves, as last seen
+    playback_ends: u32,
+    /// Keep replaying the pattern being edited instead of moving on
+    pub pattern_loop: bool,
+    /// Arrangement positions (start, end, inclusive) that playback wraps within
+    pub loop_region: Option<(usize, usize)>,
 
     // View state
     /// First visible row in pattern view
@@ -70,10 +122,17 @@ pub struct TrackerState {
 
     /// Dirty flag
     pub dirty: bool,
-    /// Status message
-    pub status_message: Option<(String, f64)>,
-    /// Last played note per channel (for sustain detection - same note = no re-trigger)
-    last_played_notes: [Option<u8>; MAX_CHANNELS],
+    /// Bumped by every song edit except tempo changes, so playback only resends
+    /// the song when it changed
+    song_generation: u64,
+    /// Undo/redo stacks (song edits only, never cursor or playback)
+    pub undo_stack: Vec<UndoEntry>,
+    pub redo_stack: Vec<UndoEntry>,
+    /// Status messages
+    pub status: StatusQueue,
+    /// Keyboard preview notes currently held: (key, channel, pitch).
+    /// Stored at key-down so the release matches even if octave/instrument/channel changed.
+    preview_notes: Vec<(macroquad::prelude::KeyCode, usize, u8)>,
 
     // Effect preview values (per channel, for testing in instruments view)
     /// Pan value per channel (0=left, 64=center, 127=right)
@@ -89,6 +148,15 @@ pub struct TrackerState {
 
     /// Instrument list scroll offset
     pub instrument_scroll: usize,
+    /// SF2 bank shown in the instrument list
+    pub instrument_bank: u8,
+    /// Show only starred instruments (from every bank)
+    pub instrument_favorites_only: bool,
+    /// Preset (bank, program) the instrument list scrolls into view next frame
+    pub reveal_instrument: Option<(u8, u8)>,
+    /// Where the pattern view was when an instrument cell was double-clicked
+    /// (Escape or Back in the instruments view goes back there)
+    pub instrument_return: Option<PatternCursor>,
 
     /// Which knob is being edited (for text input)
     /// None = not editing, Some(index) = editing knob at index
@@ -97,21 +165,29 @@ pub struct TrackerState {
     pub knob_edit_text: String,
 }
 
-/// Soundfont filename
+/// Default soundfont filename (for songs that don't name one)
 const SOUNDFONT_NAME: &str = "TimGM6mb.sf2";
 
-/// Find the soundfont in various locations (development, deployed, macOS app bundle)
+/// Directory soundfonts are looked up in
+#[cfg(not(target_arch = "wasm32"))]
+const SOUNDFONT_DIR: &str = "assets/soundfonts";
+
+/// Find a soundfont by file name in various locations (development, deployed, macOS
+/// app bundle). Names with a directory part are rejected, since songs are user files.
 #[cfg(not(target_arch = "wasm32"))]
-fn find_soundfont() -> Option<PathBuf> {
+fn find_soundfont(name: &str) -> Option<PathBuf> {
+    if std::path::Path::new(name).file_name().is_none_or(|n| n != name) {
+        return None;
+    }
     let candidates = [
         // Development: relative to cwd
-        PathBuf::from(format!("assets/soundfonts/{}", SOUNDFONT_NAME)),
+        PathBuf::from(SOUNDFONT_DIR).join(name),
         // Deployed: next to executable
-        std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.join("assets/soundfonts").join(SOUNDFONT_NAME))).unwrap_or_default(),
+        std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.join(SOUNDFONT_DIR).join(name))).unwrap_or_default(),
         // macOS app bundle: Contents/Resources
-        std::env::current_exe().ok().and_then(|p| p.parent().and_then(|d| d.parent()).map(|d| d.join("Resources/assets/soundfonts").join(SOUNDFONT_NAME))).unwrap_or_default(),
+        std::env::current_exe().ok().and_then(|p| p.parent().and_then(|d| d.parent()).map(|d| d.join("Resources").join(SOUNDFONT_DIR).join(name))).unwrap_or_default(),
         // Fallback: just the filename in cwd
-        PathBuf::from(SOUNDFONT_NAME),
+        PathBuf::from(name),
     ];
 
     for path in candidates {
@@ -124,44 +200,9 @@ fn find_soundfont() -> Option<PathBuf> {
 
 impl TrackerState {
     pub fn new() -> Self {
-        let mut audio = AudioEngine::new();
+        let audio = AudioEngine::new();
 
-        // Load soundfont - different strategies for native vs WASM
-        #[cfg(target_arch = "wasm32")]
-        {
-            // On WASM: get from JavaScript cache (prefetched before WASM loaded)
-            if super::audio::wasm::is_soundfont_cached() {
-                if let Some(bytes) = super::audio::wasm::get_cached_soundfont() {
-                    match audio.load_soundfont_from_bytes(&bytes, Some(SOUNDFONT_NAME.to_string())) {
-                        Ok(()) => println!("Loaded soundfont from WASM cache: {}", SOUNDFONT_NAME),
-                        Err(e) => eprintln!("Failed to load soundfont from cache: {}", e),
-                    }
-                }
-            } else {
-                eprintln!("Soundfont not available in WASM cache");
-            }
-        }
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // On native: load from filesystem
-            if let Some(sf_path) = find_soundfont() {
-                match audio.load_soundfont(&sf_path) {
-                    Ok(()) => println!("Loaded soundfont: {:?}", sf_path),
-                    Err(e) => eprintln!("Failed to load soundfont {:?}: {}", sf_path, e),
-                }
-            } else {
-                eprintln!("Soundfont {} not found in any search path", SOUNDFONT_NAME);
-                if let Ok(cwd) = std::env::current_dir() {
-                    eprintln!("Current working directory: {:?}", cwd);
-                }
-                if let Ok(exe) = std::env::current_exe() {
-                    eprintln!("Executable location: {:?}", exe);
-                }
-            }
-        }
-
-        Self {
+        let mut state = Self {
             song: Song::new(),
             current_file: None,
             audio,
@@ -176,11 +217,25 @@ impl TrackerState {
             default_volume: 100,
             edit_step: 1,
             edit_mode: true,
+            hex_entry: None,
 
             playing: false,
             playback_row: 0,
             playback_pattern_idx: 0,
-            playback_time: 0.0,
+            playback_fraction: 0.0,
+            background_playback: false,
+            follow_playback: true,
+            metronome: false,
+            metronome_volume: 0.5,
+            count_in_bars: 1,
+            count_in_rows: 0,
+            playback_held: false,
+            synced_generation: None,
+            synced_bpm: 0,
+            synced_settings: None,
+            playback_ends: 0,
+            pattern_loop: false,
+            loop_region: None,
 
             scroll_row: 0,
             visible_rows: 32,
@@ -189,8 +244,11 @@ impl TrackerState {
             selection_end: None,
 
             dirty: false,
-            status_message: None,
-            last_played_notes: [None; MAX_CHANNELS],
+            song_generation: 0,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            status: StatusQueue::new(),
+            preview_notes: Vec::new(),
 
             // Effect previews - initialize to defaults
             preview_pan: [64; MAX_CHANNELS],        // Center
@@ -199,35 +257,172 @@ impl TrackerState {
             preview_modulation: [0; MAX_CHANNELS],  // No modulation
             preview_expression: [127; MAX_CHANNELS], // Full expression
             instrument_scroll: 0,
+            instrument_bank: 0,
+            instrument_favorites_only: false,
+            reveal_instrument: None,
+            instrument_return: None,
             editing_knob: None,
             knob_edit_text: String::new(),
+        };
+        state.apply_mix_settings();
+        state.load_song_soundfont();
+        state
+    }
+
+    /// Load the soundfont the song names (the default for songs that don't) unless
+    /// it's already loaded. Call after replacing the song. If it can't be found the
+    /// loaded one keeps playing and the status line says how to pick it.
+    pub fn load_song_soundfont(&mut self) {
+        let name = self.song.soundfont.clone().unwrap_or_else(|| SOUNDFONT_NAME.to_string());
+        if self.audio.soundfont_name() == Some(name.as_str()) {
+            self.song.soundfont = Some(name);
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(path) = find_soundfont(&name) else {
+                match self.audio.soundfont_name() {
+                    Some(loaded) => self.set_warning(&format!(
+                        "Song uses soundfont {}, not found in {}: playing with {} (Load Soundfont to pick it)",
+                        name, SOUNDFONT_DIR, loaded)),
+                    None => {
+                        self.set_error(&format!("Soundfont {} not found in any search path", name));
+                        if let Ok(cwd) = std::env::current_dir() {
+                            eprintln!("Current working directory: {:?}", cwd);
+                        }
+                        if let Ok(exe) = std::env::current_exe() {
+                            eprintln!("Executable location: {:?}", exe);
+                        }
+                    }
+                }
+                return;
+            };
+            match self.audio.load_soundfont(&path) {
+                Ok(()) => {
+                    println!("Loaded soundfont: {:?}", path);
+                    self.after_soundfont_change();
+                    self.song.soundfont = Some(name);
+                }
+                Err(e) => self.set_error(&format!("Failed to load soundfont {:?}: {}", path, e)),
+            }
+        }
+
+        // On WASM only the default is at hand, prefetched into the JavaScript cache
+        // before WASM loaded: others have to be uploaded
+        #[cfg(target_arch = "wasm32")]
+        {
+            if name != SOUNDFONT_NAME {
+                if self.audio.is_loaded() {
+                    self.set_warning(&format!("Song uses soundfont {}: upload it with Load Soundfont", name));
+                }
+                return;
+            }
+            let cached = super::audio::wasm::is_soundfont_cached()
+                .then(super::audio::wasm::get_cached_soundfont)
+                .flatten();
+            let Some(bytes) = cached else {
+                self.set_error("Soundfont not available in WASM cache");
+                return;
+            };
+            match self.audio.load_soundfont_from_bytes(&bytes, Some(name.clone())) {
+                Ok(()) => {
+                    println!("Loaded soundfont from WASM cache: {}", name);
+                    self.after_soundfont_change();
+                    self.song.soundfont = Some(name);
+                }
+                Err(e) => self.set_error(&format!("Failed to load soundfont from cache: {}", e)),
+            }
         }
     }
 
-    /// Set status message
-    pub fn set_status(&mut self, message: &str, duration: f64) {
-        let expiry = macroquad::time::get_time() + duration;
-        self.status_message = Some((message.to_string(), expiry));
+    /// Pick an .sf2 file and load it (native file dialog)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn prompt_load_soundfont(&mut self) {
+        let dialog = rfd::FileDialog::new()
+            .add_filter("SoundFont", &["sf2"])
+            .set_directory(SOUNDFONT_DIR);
+        let Some(path) = dialog.pick_file() else { return };
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match std::fs::read(&path) {
+            Ok(bytes) => self.load_soundfont_file(&name, &bytes),
+            Err(e) => self.set_error(&format!("Failed to open soundfont: {}", e)),
+        }
     }
 
-    /// Get current status message if not expired
-    pub fn get_status(&self) -> Option<&str> {
-        if let Some((msg, expiry)) = &self.status_message {
-            if macroquad::time::get_time() < *expiry {
-                return Some(msg);
+    /// Ask the page for an .sf2 upload (arrives through `load_soundfont_file`)
+    #[cfg(target_arch = "wasm32")]
+    pub fn prompt_load_soundfont(&mut self) {
+        extern "C" {
+            fn bonnie_import_file();
+        }
+        unsafe {
+            bonnie_import_file();
+        }
+        self.set_status("Select a .sf2 soundfont to load...", 3.0);
+    }
+
+    /// Load a soundfont the user picked or uploaded and record it in the song.
+    /// A file that doesn't parse leaves the current soundfont playing.
+    pub fn load_soundfont_file(&mut self, name: &str, bytes: &[u8]) {
+        match self.audio.load_soundfont_from_bytes(bytes, Some(name.to_string())) {
+            Ok(()) => {
+                self.after_soundfont_change();
+                if self.song.soundfont.as_deref() != Some(name) {
+                    self.song.soundfont = Some(name.to_string());
+                    self.song_edited();
+                }
+                self.set_status(&format!("Loaded soundfont {}", name), 3.0);
             }
+            Err(e) => self.set_error(&format!("{}: {}", name, e)),
+        }
+    }
+
+    /// A new synth starts with default controllers and no voices: resend the song's
+    /// mix, forget the previews, and keep the instrument list on a bank that exists
+    fn after_soundfont_change(&mut self) {
+        self.preview_notes.clear();
+        self.preview_pan = [64; MAX_CHANNELS];
+        self.preview_reverb = [0; MAX_CHANNELS];
+        self.preview_chorus = [0; MAX_CHANNELS];
+        self.preview_modulation = [0; MAX_CHANNELS];
+        self.preview_expression = [127; MAX_CHANNELS];
+        self.apply_mix_settings();
+        let banks = self.audio.banks();
+        if !banks.contains(&self.instrument_bank) {
+            self.instrument_bank = banks[0];
         }
-        None
+        self.instrument_scroll = 0;
+    }
+
+    /// Queue a status message that will be displayed for a duration
+    pub fn set_status(&mut self, message: &str, duration: f64) {
+        self.status.push(message, Severity::Info, duration);
+    }
+
+    /// Queue a warning (highlighted, stays up longer)
+    pub fn set_warning(&mut self, message: &str) {
+        self.status.push(message, Severity::Warning, Severity::Warning.default_duration());
+    }
+
+    /// Queue an error (highlighted and logged)
+    pub fn set_error(&mut self, message: &str) {
+        self.status.push(message, Severity::Error, Severity::Error.default_duration());
+    }
+
+    /// Status message to display right now, if any
+    pub fn get_status(&self) -> Option<(&str, Severity)> {
+        self.status.current()
     }
 
     /// Get the current pattern being edited
-    pub fn current_pattern(&self) -> Option<&super::pattern::Pattern> {
+    pub fn current_pattern(&self) -> Option<&crate::music::Pattern> {
         let pattern_num = self.song.arrangement.get(self.current_pattern_idx)?;
         self.song.patterns.get(*pattern_num)
     }
 
     /// Get the current pattern mutably
-    pub fn current_pattern_mut(&mut self) -> Option<&mut super::pattern::Pattern> {
+    pub fn current_pattern_mut(&mut self) -> Option<&mut crate::music::Pattern> {
         let pattern_num = *self.song.arrangement.get(self.current_pattern_idx)?;
         self.song.patterns.get_mut(pattern_num)
     }
@@ -237,10 +432,63 @@ impl TrackerState {
         self.song.get_channel_instrument(self.current_channel)
     }
 
-    /// Set the instrument for the current channel
+    /// Get the SF2 bank for the current channel
+    pub fn current_bank(&self) -> u8 {
+        self.song.get_channel_bank(self.current_channel)
+    }
+
+    /// Set the instrument for the current channel (keeps its bank)
     pub fn set_current_instrument(&mut self, instrument: u8) {
-        self.song.set_channel_instrument(self.current_channel, instrument);
-        self.audio.set_program(self.current_channel as i32, instrument as i32);
+        self.set_current_preset(self.current_bank(), instrument);
+    }
+
+    /// Set the bank and instrument for the current channel
+    pub fn set_current_preset(&mut self, bank: u8, instrument: u8) {
+        self.set_channel_preset(self.current_channel, bank, instrument);
+    }
+
+    /// Set the bank and instrument for a channel (the piano plays it if it's the current one)
+    pub fn set_channel_preset(&mut self, channel: usize, bank: u8, instrument: u8) {
+        self.song.set_channel_bank(channel, bank);
+        self.song.set_channel_instrument(channel, instrument);
+        if channel == self.current_channel {
+            self.audio.set_program(channel as i32, bank as i32, instrument as i32);
+        }
+        self.song_edited();
+    }
+
+    /// Show `program` in the instruments view, selected for the cursor's channel (so the
+    /// piano previews it). The pattern cursor is kept for `return_from_instruments`.
+    pub fn jump_to_instrument(&mut self, program: u8) {
+        self.instrument_return = Some(PatternCursor {
+            pattern_idx: self.current_pattern_idx,
+            row: self.current_row,
+            channel: self.current_channel,
+            column: self.current_column,
+            scroll_row: self.scroll_row,
+        });
+        self.set_current_instrument(program);
+        self.instrument_bank = self.current_bank();
+        self.instrument_favorites_only = false;
+        self.reveal_instrument = Some((self.instrument_bank, program));
+        self.view = TrackerView::Instruments;
+    }
+
+    /// Back to the pattern view with the cursor where `jump_to_instrument` found it.
+    /// Returns false if the instruments view wasn't opened that way.
+    pub fn return_from_instruments(&mut self) -> bool {
+        if self.view != TrackerView::Instruments {
+            return false;
+        }
+        let Some(cursor) = self.instrument_return.take() else { return false };
+        self.current_pattern_idx = cursor.pattern_idx.min(self.song.arrangement.len().saturating_sub(1));
+        self.current_channel = cursor.channel;
+        self.current_column = cursor.column;
+        let length = self.current_pattern().map_or(1, |p| p.length);
+        self.current_row = cursor.row.min(length - 1);
+        self.scroll_row = cursor.scroll_row;
+        self.view = TrackerView::Pattern;
+        true
     }
 
     /// Set preview pan for current channel and apply to audio
@@ -284,6 +532,36 @@ impl TrackerState {
         self.audio.reset_controllers(ch as i32);
     }
 
+    /// Set pan for a channel (-1.0 to 1.0) and apply to audio
+    pub fn set_channel_pan(&mut self, channel: usize, pan: f32) {
+        self.song.set_channel_pan(channel, pan);
+        self.audio.set_channel_pan(channel as i32, self.song.get_channel_pan(channel));
+        self.song_edited();
+    }
+
+    /// Set master volume (0.0-1.0) and apply to audio
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.song.master_volume = volume.clamp(0.0, 1.0);
+        self.audio.set_master_volume(self.song.master_volume);
+        self.song_edited();
+    }
+
+    /// Toggle PS1 SPU 15-bit output quantization
+    pub fn toggle_spu_quantize(&mut self) {
+        self.song.spu_quantize = !self.song.spu_quantize;
+        self.audio.set_spu_quantize(self.song.spu_quantize);
+        self.song_edited();
+    }
+
+    /// Push the song's mixer settings (pan, master volume, quantization) to the audio engine
+    pub fn apply_mix_settings(&mut self) {
+        for ch in 0..self.song.num_channels() {
+            self.audio.set_channel_pan(ch as i32, self.song.get_channel_pan(ch));
+        }
+        self.audio.set_master_volume(self.song.master_volume);
+        self.audio.set_spu_quantize(self.song.spu_quantize);
+    }
+
     /// Get the number of channels
     pub fn num_channels(&self) -> usize {
         self.song.num_channels()
@@ -292,11 +570,13 @@ impl TrackerState {
     /// Add a channel
     pub fn add_channel(&mut self) {
         self.song.add_channel();
+        self.song_edited();
     }
 
     /// Remove a channel
     pub fn remove_channel(&mut self) {
         self.song.remove_channel();
+        self.song_edited();
         // Make sure current_channel is still valid
         if self.current_channel >= self.song.num_channels() {
             self.current_channel = self.song.num_channels() - 1;
@@ -366,120 +646,345 @@ impl TrackerState {
         }
     }
 
-    /// Enter a note at cursor position
-    pub fn enter_note(&mut self, pitch: u8) {
-        let channel = self.current_channel;
-        let row = self.current_row;
-        let instrument = self.current_instrument();
+    /// Mark the song dirty and due to be sent to the sequencer again
+    fn song_edited(&mut self) {
+        self.dirty = true;
+        self.song_generation += 1;
+    }
 
-        if let Some(pattern) = self.current_pattern_mut() {
-            let note = Note::new(pitch, instrument);
-            pattern.set(channel, row, note);
+    /// Record an edit for undo (clears the redo stack) and mark the song edited
+    fn push_undo(&mut self, label: String, edit: SongEdit) {
+        // The sequencer picks up a tempo change by itself (SetBpm)
+        if matches!(edit, SongEdit::Bpm { .. }) {
+            self.dirty = true;
+        } else {
+            self.song_edited();
         }
-        self.dirty = true;
+        self.undo_stack.push(UndoEntry { label, edit });
+        self.redo_stack.clear();
+        if self.undo_stack.len() > MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+    }
 
-        // Preview the note (make sure audio engine uses correct instrument for channel)
-        self.audio.set_program(channel as i32, instrument as i32);
-        self.audio.note_on(channel as i32, pitch as i32, 100);
+    /// Change one cell of the pattern at an arrangement position, recording undo
+    /// labeled "<what> at <row>/ch<n>" if anything changed
+    fn edit_note(&mut self, pattern_idx: usize, channel: usize, row: usize, what: &str, change: impl FnOnce(&mut Note)) {
+        let Some(&pattern) = self.song.arrangement.get(pattern_idx) else { return };
+        let Some(note) = self.song.patterns.get_mut(pattern)
+            .and_then(|p| p.channels.get_mut(channel))
+            .and_then(|ch| ch.get_mut(row)) else { return };
+        let before = *note;
+        change(note);
+        let after = *note;
+        if after != before {
+            let label = format!("{} at {:02X}/ch{}", what, row, channel + 1);
+            self.push_undo(label, SongEdit::Cell { pattern, channel, row, before, after });
+        }
+    }
+
+    /// Change the cell under the cursor, recording undo
+    fn edit_cursor_note(&mut self, what: &str, change: impl FnOnce(&mut Note)) {
+        self.edit_note(self.current_pattern_idx, self.current_channel, self.current_row, what, change);
+    }
+
+    /// Undo the last song edit
+    pub fn undo(&mut self) {
+        self.hex_entry = None;
+        match self.undo_stack.pop() {
+            Some(entry) => {
+                entry.edit.apply(&mut self.song, false);
+                self.set_status(&format!("Undo: {}", entry.label), 2.0);
+                self.redo_stack.push(entry);
+                self.after_history_step();
+            }
+            None => self.set_status("Nothing to undo", 1.5),
+        }
+    }
+
+    /// Redo the last undone song edit
+    pub fn redo(&mut self) {
+        self.hex_entry = None;
+        match self.redo_stack.pop() {
+            Some(entry) => {
+                entry.edit.apply(&mut self.song, true);
+                self.set_status(&format!("Redo: {}", entry.label), 2.0);
+                self.undo_stack.push(entry);
+                self.after_history_step();
+            }
+            None => self.set_status("Nothing to redo", 1.5),
+        }
+    }
+
+    /// Keep the cursor inside the song after undo/redo changed its shape
+    fn after_history_step(&mut self) {
+        self.song_edited();
+        self.current_pattern_idx = self.current_pattern_idx.min(self.song.arrangement.len().saturating_sub(1));
+        if let Some(length) = self.current_pattern().map(|p| p.length) {
+            self.current_row = self.current_row.min(length - 1);
+            self.ensure_row_visible();
+        }
+    }
+
+    /// Set the tempo (40-300 BPM, one undo step)
+    pub fn set_bpm(&mut self, bpm: i32) {
+        let (before, after) = (self.song.bpm, bpm.clamp(40, 300) as u16);
+        if after != before {
+            self.song.bpm = after;
+            self.push_undo(format!("BPM {} -> {}", before, after), SongEdit::Bpm { before, after });
+        }
+    }
+
+    /// Resize the pattern being edited (one undo step, keeping the rows it cuts off)
+    pub fn set_pattern_length(&mut self, length: usize) {
+        let Some(&pattern) = self.song.arrangement.get(self.current_pattern_idx) else { return };
+        let Some(p) = self.current_pattern_mut() else { return };
+        let before = p.length;
+        let cut = p.channels.iter().map(|ch| ch.iter().skip(length).copied().collect()).collect();
+        p.set_length(length);
+        let after = p.length;
+        if after != before {
+            let label = format!("Pattern {:02} length {} -> {}", pattern, before, after);
+            self.push_undo(label, SongEdit::PatternLength { pattern, before, after, cut });
+            self.current_row = self.current_row.min(after - 1);
+            self.ensure_row_visible();
+        }
+    }
+
+    /// Insert a new empty pattern into the arrangement after the current position
+    pub fn insert_arrangement_entry(&mut self) {
+        let before = self.song.arrangement.clone();
+        let pattern = self.song.add_pattern();
+        let position = (self.current_pattern_idx + 1).min(self.song.arrangement.len());
+        self.song.arrangement.insert(position, pattern);
+        let after = self.song.arrangement.clone();
+        self.push_undo(format!("Add pattern {:02} at {:02}", pattern, position), SongEdit::Arrangement { before, after });
+        self.current_pattern_idx = position;
+    }
+
+    /// Remove the current position from the arrangement (the pattern itself is kept)
+    pub fn remove_arrangement_entry(&mut self) {
+        if self.song.arrangement.len() <= 1 {
+            self.set_status("The arrangement needs at least one pattern", 1.5);
+            return;
+        }
+        let before = self.song.arrangement.clone();
+        let position = self.current_pattern_idx.min(before.len() - 1);
+        let pattern = self.song.arrangement.remove(position);
+        let after = self.song.arrangement.clone();
+        self.push_undo(format!("Remove pattern {:02} at {:02}", pattern, position), SongEdit::Arrangement { before, after });
+        self.current_pattern_idx = position.min(self.song.arrangement.len() - 1);
+    }
+
+    /// Copy the current pattern into a new one (one undo step). The copy goes
+    /// after the current position, or with `replace` takes its place.
+    pub fn duplicate_pattern(&mut self, replace: bool) {
+        let Some(&source) = self.song.arrangement.get(self.current_pattern_idx) else { return };
+        let copy = self.add_pattern_copy(replace);
+        let message = if replace {
+            format!("Pattern {:02} copied to {:02} at {:02}", source, copy, self.current_pattern_idx)
+        } else {
+            format!("Pattern {:02} copied to {:02}, inserted at {:02}", source, copy, self.current_pattern_idx)
+        };
+        self.set_status(&message, 2.0);
+    }
+
+    /// Give the current position its own copy of its pattern, so editing it
+    /// leaves the other positions playing that pattern alone (one undo step)
+    pub fn make_pattern_unique(&mut self) {
+        let Some(&source) = self.song.arrangement.get(self.current_pattern_idx) else { return };
+        if self.song.pattern_use_count(source) <= 1 {
+            self.set_status(&format!("Pattern {:02} is only used here already", source), 2.0);
+            return;
+        }
+        let copy = self.add_pattern_copy(true);
+        self.set_status(&format!("Position {:02} now plays pattern {:02} (copy of {:02})", self.current_pattern_idx, copy, source), 2.0);
+    }
+
+    /// Append a copy of the current pattern and use it after (or with `replace`, at)
+    /// the current position, moving there. Returns the copy's index.
+    fn add_pattern_copy(&mut self, replace: bool) -> usize {
+        let source = self.song.arrangement[self.current_pattern_idx];
+        let copy = self.song.patterns.len();
+        let before = self.song.arrangement.clone();
+        let mut after = before.clone();
+        let position = if replace {
+            after[self.current_pattern_idx] = copy;
+            self.current_pattern_idx
+        } else {
+            after.insert(self.current_pattern_idx + 1, copy);
+            self.current_pattern_idx + 1
+        };
+        let edit = SongEdit::PatternCopy { source, before, after };
+        edit.apply(&mut self.song, true);
+        self.push_undo(format!("Copy pattern {:02} to {:02} at {:02}", source, copy, position), edit);
+        self.current_pattern_idx = position;
+        copy
+    }
+
+    /// Enter a note at cursor position
+    pub fn enter_note(&mut self, pitch: u8) {
+        let note = Note::new(pitch, self.current_instrument());
+        let what = format!("note {}", note.pitch_name().unwrap_or_default());
+        self.edit_cursor_note(&what, |slot| *slot = note);
 
         // Advance cursor
         self.advance_cursor();
     }
 
-    /// Enter a note-off at cursor position
-    pub fn enter_note_off(&mut self) {
+    /// Start a keyboard preview note on the current channel with its current instrument.
+    /// Each physical key sounds at most one note; holding several keys plays a chord.
+    pub fn preview_key_down(&mut self, key: macroquad::prelude::KeyCode, pitch: u8) {
+        if self.preview_notes.iter().any(|&(k, _, _)| k == key) {
+            return;
+        }
         let channel = self.current_channel;
-        let row = self.current_row;
+        let instrument = self.current_instrument();
+        self.audio.set_program(channel as i32, self.current_bank() as i32, instrument as i32);
+        self.audio.note_on(channel as i32, pitch as i32, 100);
+        self.preview_notes.push((key, channel, pitch));
+    }
 
-        if let Some(pattern) = self.current_pattern_mut() {
-            pattern.set(channel, row, Note::off());
+    /// Release the preview note started by this key (if any)
+    pub fn preview_key_up(&mut self, key: macroquad::prelude::KeyCode) {
+        if let Some(idx) = self.preview_notes.iter().position(|&(k, _, _)| k == key) {
+            let (_, channel, pitch) = self.preview_notes.remove(idx);
+            self.audio.note_off(channel as i32, pitch as i32);
         }
-        self.dirty = true;
+    }
+
+    /// Release any preview note whose key is no longer held
+    pub fn release_preview_keys(&mut self) {
+        let released: Vec<_> = self.preview_notes.iter()
+            .map(|&(k, _, _)| k)
+            .filter(|&k| !macroquad::prelude::is_key_down(k))
+            .collect();
+        for key in released {
+            self.preview_key_up(key);
+        }
+    }
+
+    /// Enter a note-off at cursor position
+    pub fn enter_note_off(&mut self) {
+        self.edit_cursor_note("note off", |slot| *slot = Note::off());
         self.advance_cursor();
     }
 
     /// Delete note at cursor position
     pub fn delete_note(&mut self) {
-        let channel = self.current_channel;
-        let row = self.current_row;
-
-        if let Some(pattern) = self.current_pattern_mut() {
-            pattern.set(channel, row, Note::EMPTY);
-        }
-        self.dirty = true;
+        self.edit_cursor_note("delete note", |slot| *slot = Note::EMPTY);
     }
 
     /// Set effect at cursor position
     pub fn set_effect(&mut self, effect_char: char, param: u8) {
-        let channel = self.current_channel;
-        let row = self.current_row;
-
-        if let Some(pattern) = self.current_pattern_mut() {
-            if let Some(note) = pattern.channels.get_mut(channel).and_then(|ch| ch.get_mut(row)) {
-                note.effect = Some(effect_char);
-                note.effect_param = Some(param);
-            }
-        }
-        self.dirty = true;
+        self.edit_cursor_note(&format!("effect {}{:02X}", effect_char, param), |note| {
+            note.effect = Some(effect_char);
+            note.effect_param = Some(param);
+        });
     }
 
     /// Set only the effect character at cursor (keep existing param)
     pub fn set_effect_char(&mut self, effect_char: char) {
-        let channel = self.current_channel;
-        let row = self.current_row;
-
-        if let Some(pattern) = self.current_pattern_mut() {
-            if let Some(note) = pattern.channels.get_mut(channel).and_then(|ch| ch.get_mut(row)) {
-                note.effect = Some(effect_char);
-                // Initialize param if not set
-                if note.effect_param.is_none() {
-                    note.effect_param = Some(0);
+        self.edit_cursor_note(&format!("effect {}", effect_char), |note| {
+            note.effect = Some(effect_char);
+            // Initialize param if not set
+            if note.effect_param.is_none() {
+                note.effect_param = Some(0);
+            }
+        });
+    }
+
+    /// Toggle between edit mode and navigate-only mode
+    pub fn toggle_edit_mode(&mut self) {
+        self.commit_hex_entry();
+        self.edit_mode = !self.edit_mode;
+        let mode = if self.edit_mode { "Edit" } else { "Navigate" };
+        self.set_status(&format!("{} mode", mode), 1.0);
+    }
+
+    /// Type a hex digit into the instrument/volume/fx param column at the cursor.
+    /// The first digit is held as a partial entry; the second completes the value and advances.
+    pub fn enter_hex_digit(&mut self, nibble: u8) {
+        match self.hex_entry.take() {
+            Some(entry) if self.hex_entry_at_cursor(&entry) => {
+                self.write_hex_value(&entry, (entry.high << 4) | (nibble & 0x0F));
+                self.advance_cursor();
+            }
+            pending => {
+                if let Some(entry) = pending {
+                    self.write_hex_value(&entry, entry.high);
                 }
+                self.hex_entry = Some(HexEntry {
+                    pattern_idx: self.current_pattern_idx,
+                    row: self.current_row,
+                    channel: self.current_channel,
+                    column: self.current_column,
+                    high: nibble & 0x0F,
+                });
             }
         }
-        self.dirty = true;
     }
 
-    /// Set only the effect parameter at cursor (high nibble)
-    pub fn set_effect_param_high(&mut self, nibble: u8) {
-        let channel = self.current_channel;
-        let row = self.current_row;
+    /// Commit a pending single-digit hex entry as its value (e.g. "7" -> 07)
+    pub fn commit_hex_entry(&mut self) {
+        if let Some(entry) = self.hex_entry.take() {
+            self.write_hex_value(&entry, entry.high);
+        }
+    }
 
-        if let Some(pattern) = self.current_pattern_mut() {
-            if let Some(note) = pattern.channels.get_mut(channel).and_then(|ch| ch.get_mut(row)) {
-                let low = note.effect_param.unwrap_or(0) & 0x0F;
-                note.effect_param = Some((nibble << 4) | low);
+    /// Commit a pending hex entry if the cursor has moved away from it
+    pub fn sync_hex_entry(&mut self) {
+        if let Some(entry) = self.hex_entry {
+            if !self.hex_entry_at_cursor(&entry) {
+                self.commit_hex_entry();
             }
         }
-        self.dirty = true;
     }
 
-    /// Set only the effect parameter at cursor (low nibble)
-    pub fn set_effect_param_low(&mut self, nibble: u8) {
-        let channel = self.current_channel;
-        let row = self.current_row;
+    /// Is the pending hex entry at the current cursor position?
+    fn hex_entry_at_cursor(&self, entry: &HexEntry) -> bool {
+        entry.pattern_idx == self.current_pattern_idx
+            && entry.row == self.current_row
+            && entry.channel == self.current_channel
+            && entry.column == self.current_column
+    }
 
-        if let Some(pattern) = self.current_pattern_mut() {
-            if let Some(note) = pattern.channels.get_mut(channel).and_then(|ch| ch.get_mut(row)) {
-                let high = note.effect_param.unwrap_or(0) & 0xF0;
-                note.effect_param = Some(high | (nibble & 0x0F));
+    /// Write a completed hex value into the field a hex entry targets
+    fn write_hex_value(&mut self, entry: &HexEntry, value: u8) {
+        let what = match entry.column {
+            1 => "instrument",
+            2 => "volume",
+            4 => "effect param",
+            _ => return,
+        };
+        let column = entry.column;
+        self.edit_note(entry.pattern_idx, entry.channel, entry.row, &format!("{} {:02X}", what, value), |note| {
+            match column {
+                1 => note.instrument = Some(value.min(127)),
+                2 => note.volume = Some(value.min(127)),
+                _ => note.effect_param = Some(value),
             }
+        });
+    }
+
+    /// Clear the field under the cursor (whole note in the note column)
+    pub fn clear_field(&mut self) {
+        self.hex_entry = None;
+
+        match self.current_column {
+            0 => self.delete_note(),
+            1 => self.edit_cursor_note("clear instrument", |note| note.instrument = None),
+            2 => self.edit_cursor_note("clear volume", |note| note.volume = None),
+            _ => self.clear_effect(),
         }
-        self.dirty = true;
     }
 
     /// Clear effect at cursor position
     pub fn clear_effect(&mut self) {
-        let channel = self.current_channel;
-        let row = self.current_row;
-
-        if let Some(pattern) = self.current_pattern_mut() {
-            if let Some(note) = pattern.channels.get_mut(channel).and_then(|ch| ch.get_mut(row)) {
-                note.effect = None;
-                note.effect_param = None;
-            }
-        }
-        self.dirty = true;
+        self.edit_cursor_note("clear effect", |note| {
+            note.effect = None;
+            note.effect_param = None;
+        });
     }
 
     /// Advance cursor by edit_step rows
@@ -492,218 +997,233 @@ impl TrackerState {
 
     /// Toggle playback from current cursor position
     pub fn toggle_playback(&mut self) {
-        self.playing = !self.playing;
         if self.playing {
-            self.playback_row = self.current_row;
-            self.playback_pattern_idx = self.current_pattern_idx;
-            self.playback_time = 0.0;
-            self.last_played_notes = [None; MAX_CHANNELS];
+            self.pause_playback();
         } else {
-            self.audio.all_notes_off();
-            self.last_played_notes = [None; MAX_CHANNELS];
+            self.start_playback(self.current_pattern_idx, self.current_row);
         }
     }
 
     /// Start playback from the beginning of the song
     pub fn play_from_start(&mut self) {
-        self.audio.all_notes_off();
-        self.playback_row = 0;
-        self.playback_pattern_idx = 0;
-        self.playback_time = 0.0;
+        self.start_playback(0, 0);
+    }
+
+    /// Send the song and start the sequencer at an arrangement position and row
+    /// (after the count-in in edit mode)
+    fn start_playback(&mut self, pattern_idx: usize, row: usize) {
         self.playing = true;
-        self.last_played_notes = [None; MAX_CHANNELS];
+        self.playback_pattern_idx = pattern_idx;
+        self.playback_row = row;
+        self.playback_fraction = 0.0;
+        self.apply_mix_settings();
+        self.sync_sequencer();
+        self.count_in_rows = self.count_in_length();
+        self.audio.send(SequencerCommand::Play { pattern_idx, row, count_in_rows: self.count_in_rows });
+    }
+
+    /// In edit mode, the first row is held back for `count_in_bars` bars of clicks
+    fn count_in_length(&self) -> usize {
+        if self.edit_mode {
+            self.count_in_bars as usize * BEATS_PER_BAR * self.song.rows_per_beat.max(1) as usize
+        } else {
+            0
+        }
+    }
+
+    /// Toggle the metronome click during playback
+    pub fn toggle_metronome(&mut self) {
+        self.metronome = !self.metronome;
+        let status = if self.metronome { "Metronome: On" } else { "Metronome: Off" };
+        self.set_status(status, 1.5);
+    }
+
+    /// Cycle the count-in length: off, 1 bar, 2 bars
+    pub fn cycle_count_in(&mut self) {
+        self.count_in_bars = (self.count_in_bars + 1) % 3;
+        let status = match self.count_in_bars {
+            0 => "Count-in: Off".to_string(),
+            1 => "Count-in: 1 bar".to_string(),
+            n => format!("Count-in: {} bars", n),
+        };
+        self.set_status(&status, 1.5);
     }
 
     /// Stop playback and return cursor to start
     pub fn stop_playback(&mut self) {
-        self.playing = false;
+        self.pause_playback();
         self.playback_row = 0;
         self.playback_pattern_idx = 0;
         self.current_row = 0;
         self.current_pattern_idx = 0;
         self.scroll_row = 0;
-        self.audio.all_notes_off();
-        self.last_played_notes = [None; MAX_CHANNELS];
     }
 
-    /// Update playback (called each frame)
-    pub fn update_playback(&mut self, delta: f64) {
-        // On WASM, we need to render audio each frame to push samples to Web Audio
-        #[cfg(target_arch = "wasm32")]
-        {
-            self.audio.render_audio(delta);
+    /// Stop playback without rewinding (cursor, scroll and position are kept)
+    pub fn pause_playback(&mut self) {
+        self.playing = false;
+        self.count_in_rows = 0;
+        self.audio.send(SequencerCommand::Stop);
+    }
+
+    /// Freeze playback where it is (a paused playtest) or let it carry on
+    pub fn set_playback_held(&mut self, held: bool) {
+        if held != self.playback_held {
+            self.playback_held = held;
+            self.audio.send(SequencerCommand::Hold(held));
         }
+    }
 
-        if !self.playing {
-            return;
+    /// Called when switching away from the tracker to another tool.
+    /// Playback keeps going only if background playback is enabled.
+    pub fn on_leave(&mut self) {
+        self.commit_hex_entry();
+        for (_, channel, pitch) in self.preview_notes.drain(..) {
+            self.audio.note_off(channel as i32, pitch as i32);
+        }
+        if !self.background_playback {
+            self.pause_playback();
         }
+    }
 
-        self.playback_time += delta;
-        let tick_duration = self.song.tick_duration();
+    /// Toggle playing in the background while other tools are active
+    pub fn toggle_background_playback(&mut self) {
+        self.background_playback = !self.background_playback;
+        let status = if self.background_playback { "Background playback: On" } else { "Background playback: Off" };
+        self.set_status(status, 1.5);
+    }
 
-        while self.playback_time >= tick_duration {
-            self.playback_time -= tick_duration;
-            self.play_current_row();
-            self.advance_playback();
-        }
+    /// Toggle keeping the pattern view on the playback position
+    pub fn toggle_follow_playback(&mut self) {
+        self.follow_playback = !self.follow_playback;
+        let status = if self.follow_playback { "Follow playback: On" } else { "Follow playback: Off" };
+        self.set_status(status, 1.5);
     }
 
-    /// Play notes at current playback row
-    fn play_current_row(&mut self) {
-        let pattern_num = match self.song.arrangement.get(self.playback_pattern_idx) {
-            Some(&n) => n,
-            None => return,
-        };
+    /// Toggle looping the pattern being edited
+    pub fn toggle_pattern_loop(&mut self) {
+        self.pattern_loop = !self.pattern_loop;
+        let status = if self.pattern_loop { "Pattern loop: On" } else { "Pattern loop: Off" };
+        self.set_status(status, 1.5);
+    }
 
-        let pattern = match self.song.patterns.get(pattern_num) {
-            Some(p) => p,
-            None => return,
-        };
+    /// Set the start of the arrangement loop region (the end moves along if it's earlier)
+    pub fn set_loop_start(&mut self, idx: usize) {
+        let end = self.loop_region.map(|(_, end)| end.max(idx)).unwrap_or(idx);
+        self.loop_region = Some((idx, end));
+        self.set_status(&format!("Loop: {:02}-{:02}", idx, end), 1.5);
+    }
 
-        // Collect note data first to avoid borrow issues
-        let num_channels = self.song.num_channels();
-        let playback_row = self.playback_row;
-        let mut notes_to_play: Vec<(usize, Option<u8>, Option<u8>, Option<u8>, Option<u8>)> = Vec::new();
-        let mut effects_to_apply: Vec<(usize, Effect)> = Vec::new();
-
-        for channel in 0..num_channels {
-            if let Some(note) = pattern.get(channel, playback_row) {
-                // Collect note data
-                let inst = note.instrument.unwrap_or_else(|| self.song.get_channel_instrument(channel));
-                notes_to_play.push((channel, note.pitch, Some(inst), note.volume, None));
-
-                // Collect effect
-                if let (Some(fx_char), Some(fx_param)) = (note.effect, note.effect_param) {
-                    let effect = Effect::from_char(fx_char, fx_param);
-                    effects_to_apply.push((channel, effect));
-                }
-            }
-        }
+    /// Set the end of the arrangement loop region (the start moves along if it's later)
+    pub fn set_loop_end(&mut self, idx: usize) {
+        let start = self.loop_region.map(|(start, _)| start.min(idx)).unwrap_or(idx);
+        self.loop_region = Some((start, idx));
+        self.set_status(&format!("Loop: {:02}-{:02}", start, idx), 1.5);
+    }
 
-        // Now process notes (pattern borrow is released)
-        for (channel, pitch, inst, volume, _) in notes_to_play {
-            if let Some(p) = pitch {
-                if p == 0xFF {
-                    // Note off
-                    self.audio.note_off(channel as i32, 0);
-                    self.last_played_notes[channel] = None;
-                } else {
-                    // Check if same note is already playing (sustain behavior like Picotron)
-                    let last_note = self.last_played_notes[channel];
-                    if last_note != Some(p) {
-                        // Different note or first note - trigger it
-                        let velocity = volume.unwrap_or(100) as i32;
-                        let instrument = inst.unwrap_or(0);
-                        self.audio.set_program(channel as i32, instrument as i32);
-                        self.audio.note_on(channel as i32, p as i32, velocity);
-                        self.last_played_notes[channel] = Some(p);
-                    }
-                    // Same note = sustain, don't re-trigger
-                }
-            }
+    /// Turn off the pattern loop and loop region. Playback carries on linearly from
+    /// where it is. Returns false if nothing was looping.
+    pub fn clear_loops(&mut self) -> bool {
+        if !self.pattern_loop && self.loop_region.is_none() {
+            return false;
         }
+        self.pattern_loop = false;
+        self.loop_region = None;
+        self.set_status("Loop: Off", 1.5);
+        true
+    }
+
+    /// Loop region clamped to the arrangement (None if it no longer fits)
+    pub fn active_loop_region(&self) -> Option<(usize, usize)> {
+        let (start, end) = self.loop_region?;
+        let last = self.song.arrangement.len().checked_sub(1)?;
+        (start <= last).then_some((start, end.min(last)))
+    }
 
-        // Now apply effects
-        for (channel, effect) in effects_to_apply {
-            self.apply_effect(channel, effect);
+    /// Playback position within the current pattern, in rows (fractional between ticks)
+    pub fn playback_position(&self) -> f32 {
+        if self.count_in_rows > 0 {
+            return self.playback_row as f32;
         }
+        self.playback_row as f32 + self.playback_fraction
     }
 
-    /// Apply an effect to a channel
-    fn apply_effect(&mut self, channel: usize, effect: Effect) {
-        let ch = channel as i32;
-        match effect {
-            Effect::None => {}
-            Effect::SetVolume(v) => {
-                self.audio.set_volume(ch, v as i32);
-            }
-            Effect::SetPan(p) => {
-                self.audio.set_pan(ch, p as i32);
-            }
-            Effect::SetReverb(v) => {
-                self.audio.set_reverb(ch, v as i32);
-            }
-            Effect::SetChorus(v) => {
-                self.audio.set_chorus(ch, v as i32);
-            }
-            Effect::SetExpression(v) => {
-                self.audio.set_expression(ch, v as i32);
-            }
-            Effect::SetModulation(v) => {
-                self.audio.set_modulation(ch, v as i32);
-            }
-            Effect::SlideUp(amount) => {
-                // Pitch bend up: center (8192) + amount * 64
-                let bend = 8192 + (amount as i32 * 64);
-                self.audio.set_pitch_bend(ch, bend.min(16383));
-            }
-            Effect::SlideDown(amount) => {
-                // Pitch bend down: center (8192) - amount * 64
-                let bend = 8192 - (amount as i32 * 64);
-                self.audio.set_pitch_bend(ch, bend.max(0));
-            }
-            Effect::Vibrato(_, depth) => {
-                // Use modulation wheel for vibrato
-                self.audio.set_modulation(ch, (depth as i32 * 8).min(127));
-            }
-            Effect::SetSpeed(bpm) => {
-                // Change song tempo
-                if bpm > 0 {
-                    self.song.bpm = bpm as u16;
-                }
-            }
-            Effect::PatternBreak(row) => {
-                // Jump to next pattern at specified row
-                // This will be handled in advance_playback
-                // For now, just set a flag or target row
-                // TODO: Implement pattern break properly
-                let _ = row;
-            }
-            // Effects that need per-tick processing (not implemented yet)
-            Effect::Arpeggio(_, _) => {
-                // Would need sub-row tick processing
-            }
-            Effect::Portamento(_) => {
-                // Would need note memory and per-tick slide
-            }
-            Effect::VolumeSlide(_, _) => {
-                // Would need per-tick processing
-            }
+    /// Playback position within the whole arrangement (0-1)
+    pub fn song_progress(&self) -> f32 {
+        let pattern_length = |&idx: &usize| self.song.patterns.get(idx).map(|p| p.length).unwrap_or(0);
+        let total: usize = self.song.arrangement.iter().map(pattern_length).sum();
+        if total == 0 {
+            return 0.0;
         }
+        let before: usize = self.song.arrangement.iter().take(self.playback_pattern_idx).map(pattern_length).sum();
+        ((before as f32 + self.playback_position()) / total as f32).min(1.0)
     }
 
-    /// Advance playback to next row
-    fn advance_playback(&mut self) {
-        let pattern_num = match self.song.arrangement.get(self.playback_pattern_idx) {
-            Some(&n) => n,
-            None => {
-                self.stop_playback();
-                return;
-            }
-        };
+    /// Send the sequencer whatever changed in the song or loop/metronome settings
+    fn sync_sequencer(&mut self) {
+        if self.synced_generation != Some(self.song_generation) {
+            self.audio.send(SequencerCommand::SetSong(Box::new(self.song.clone())));
+            self.synced_generation = Some(self.song_generation);
+            self.synced_bpm = self.song.bpm;
+        } else if self.synced_bpm != self.song.bpm {
+            // A tempo drag only needs the new BPM
+            self.audio.send(SequencerCommand::SetBpm(self.song.bpm));
+            self.synced_bpm = self.song.bpm;
+        }
 
-        let pattern_len = match self.song.patterns.get(pattern_num) {
-            Some(p) => p.length,
-            None => {
-                self.stop_playback();
-                return;
-            }
+        let settings = SequencerSettings {
+            pattern_loop: self.pattern_loop.then_some(self.current_pattern_idx),
+            loop_region: self.active_loop_region(),
+            metronome: self.metronome,
+            metronome_volume: self.metronome_volume,
         };
+        if self.synced_settings != Some(settings) {
+            self.audio.send(SequencerCommand::Settings(settings));
+            self.synced_settings = Some(settings);
+        }
+    }
 
-        self.playback_row += 1;
-        if self.playback_row >= pattern_len {
-            self.playback_row = 0;
-            self.playback_pattern_idx += 1;
-            if self.playback_pattern_idx >= self.song.arrangement.len() {
-                // Loop or stop
-                self.playback_pattern_idx = 0; // Loop for now
-            }
+    /// Update playback (called each frame): keep the sequencer in sync with the song
+    /// and read its position back for display
+    pub fn update_playback(&mut self, delta: f64) {
+        // On WASM, we need to render audio each frame to push samples to Web Audio
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.audio.render_audio(delta);
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = delta;
+        self.audio.free_replaced_songs();
 
-        // Update view cursor to follow playback
-        self.current_row = self.playback_row;
-        self.current_pattern_idx = self.playback_pattern_idx;
-        self.ensure_row_visible();
+        if !self.playing {
+            return;
+        }
+        self.sync_sequencer();
+
+        let position = self.audio.playback_position();
+        if position.ends != self.playback_ends {
+            // The arrangement ran into a missing pattern
+            self.playback_ends = position.ends;
+            self.stop_playback();
+            return;
+        }
+        if !position.playing {
+            // Play not picked up by the audio thread yet
+            return;
+        }
+        self.playback_row = position.row;
+        self.playback_pattern_idx = position.pattern_idx;
+        self.playback_fraction = position.fraction;
+        self.count_in_rows = position.count_in_rows;
+
+        // Show the pattern being played; the view centers on the playback row while
+        // the cursor stays where the user put it
+        if self.follow_playback && self.current_pattern_idx != self.playback_pattern_idx {
+            self.current_pattern_idx = self.playback_pattern_idx;
+            if let Some(len) = self.current_pattern().map(|p| p.length) {
+                self.current_row = self.current_row.min(len.saturating_sub(1));
+            }
+        }
     }
 
     /// Convert keyboard key to MIDI note
@@ -756,3 +1276,22 @@ impl Default for TrackerState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn song_soundfonts_are_plain_file_names() {
+        assert!(find_soundfont(SOUNDFONT_NAME).is_some());
+        assert_eq!(find_soundfont("../soundfonts/TimGM6mb.sf2"), None);
+        assert_eq!(find_soundfont("/tmp/TimGM6mb.sf2"), None);
+
+        // Only recorded once a soundfont is loaded; older songs don't have it
+        let mut song = Song::new();
+        assert!(!ron::to_string(&song).unwrap().contains("soundfont"));
+        song.soundfont = Some("Custom.sf2".to_string());
+        let loaded: Song = ron::from_str(&ron::to_string(&song).unwrap()).unwrap();
+        assert_eq!(loaded.soundfont.as_deref(), Some("Custom.sf2"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Drag values: numeric fields changed by dragging or typing
+//!
+//! Drag a field to step its value, or double-click it to type one (Enter commits,
+//! Escape cancels). The drag in progress and the typed text live in `UiContext`,
+//! like text field focus, so callers only own the value. A field with a unit shows
+//! it next to the world units ("768 (3 clk)") and takes typed values in either:
+//! "768" is world units, "3c" three clicks.
+
+use macroquad::prelude::*;
+use super::{text_input, Rect, TextInputEvent, UiContext};
+
+/// A unit a value can also be shown and typed in (e.g. clicks of 256 world units)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueUnit {
+    /// World units per unit
+    pub size: f32,
+    /// Typed after a number to mean this unit ("3c")
+    pub suffix: char,
+    /// Short name in the display ("clk")
+    pub name: &'static str,
+}
+
+/// How a drag value looks and steps
+#[derive(Debug, Clone, Copy)]
+pub struct DragValue<'a> {
+    /// Shown before the value
+    pub label: &'a str,
+    pub tooltip: &'a str,
+    /// Change per drag step
+    pub step: f32,
+    /// Mouse travel per step (pixels)
+    pub pixels_per_step: f32,
+    /// Drag up/down instead of left/right
+    pub vertical: bool,
+    pub min: f32,
+    pub max: f32,
+    /// Round typed values to a whole number of steps
+    pub snap: bool,
+    pub unit: Option<ValueUnit>,
+}
+
+impl<'a> DragValue<'a> {
+    /// A field dragged left/right one `step` per 6 pixels, unbounded
+    pub fn new(label: &'a str, step: f32) -> Self {
+        Self {
+            label,
+            tooltip: "",
+            step,
+            pixels_per_step: 6.0,
+            vertical: false,
+            min: f32::MIN,
+            max: f32::MAX,
+            snap: false,
+            unit: None,
+        }
+    }
+}
+
+/// A change made through a drag value this frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueChange {
+    pub value: f32,
+    /// First change of a drag, or a typed value: record undo before applying it
+    pub first: bool,
+    /// The drag was released, or the value was typed: the edit is complete
+    pub last: bool,
+}
+
+/// Drag value being dragged or typed into (kept across frames)
+#[derive(Debug, Clone)]
+pub(super) enum ValueEdit {
+    Drag { id: u64, anchor: f32, start: f32, last: f32, moved: bool },
+    Typing { id: u64, text: String },
+}
+
+impl ValueEdit {
+    fn id(&self) -> u64 {
+        match self {
+            ValueEdit::Drag { id, .. } | ValueEdit::Typing { id, .. } => *id,
+        }
+    }
+}
+
+/// `value` with up to two decimals, without trailing zeros
+fn format_number(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
+
+/// Display text of a value: world units, then the unit if there is one ("768 (3 clk)")
+pub fn format_value(value: f32, unit: Option<ValueUnit>) -> String {
+    match unit {
+        Some(unit) => format!("{} ({} {})", format_number(value), format_number(value / unit.size), unit.name),
+        None => format_number(value),
+    }
+}
+
+/// Parse a typed value: a number in world units, or followed by the unit's suffix
+/// or name for that unit ("3c", "3 clk"). None if it isn't a finite number.
+pub fn parse_value(text: &str, unit: Option<ValueUnit>) -> Option<f32> {
+    let text = text.trim();
+    let lower = text.to_ascii_lowercase();
+    let (number, scale) = match unit {
+        Some(unit) if lower.ends_with(unit.name) => (&text[..text.len() - unit.name.len()], unit.size),
+        Some(unit) if lower.ends_with(unit.suffix) => (&text[..text.len() - unit.suffix.len_utf8()], unit.size),
+        _ => (text, 1.0),
+    };
+    let value = number.trim().parse::<f32>().ok()? * scale;
+    value.is_finite().then_some(value)
+}
+
+/// Draw a drag value showing `value`. `id` must be the same every frame (like a
+/// text field's). Returns the change made this frame, if any.
+pub fn drag_value(ctx: &mut UiContext, rect: Rect, id: u64, value: f32, spec: &DragValue) -> Option<ValueChange> {
+    if let Some(ValueEdit::Typing { text, .. }) = ctx.value_edit.as_mut().filter(|e| e.id() == id) {
+        let mut text = std::mem::take(text);
+        // Focus can be dropped elsewhere (the field went away for a frame)
+        let event = if ctx.is_text_focused(id) { text_input(ctx, rect, id, &mut text) } else { TextInputEvent::Cancelled };
+        match event {
+            TextInputEvent::Submitted => {
+                ctx.value_edit = None;
+                let typed = parse_value(&text, spec.unit)?;
+                let typed = if spec.snap { (typed / spec.step).round() * spec.step } else { typed };
+                let typed = typed.clamp(spec.min, spec.max);
+                return Some(ValueChange { value: typed, first: true, last: true });
+            }
+            TextInputEvent::Cancelled => ctx.value_edit = None,
+            TextInputEvent::None | TextInputEvent::Changed => {
+                ctx.value_edit = Some(ValueEdit::Typing { id, text });
+            }
+        }
+        return None;
+    }
+
+    let theme = ctx.theme;
+    let hovered = ctx.mouse.inside(&rect);
+    let axis = |ctx: &UiContext| if spec.vertical { -ctx.mouse.y } else { ctx.mouse.x };
+    let mut change = None;
+
+    if hovered {
+        let how = if spec.vertical { "drag up/down" } else { "drag left/right" };
+        let tooltip = if spec.tooltip.is_empty() {
+            format!("{} ({}, double-click to type)", spec.label, how)
+        } else {
+            format!("{} ({}, double-click to type)", spec.tooltip, how)
+        };
+        ctx.set_tooltip(&tooltip, ctx.mouse.x, ctx.mouse.y);
+        if ctx.double_clicked(&rect) {
+            let text = format_number(value);
+            ctx.focus_text(id, &text);
+            ctx.value_edit = Some(ValueEdit::Typing { id, text });
+            return None;
+        }
+        if ctx.mouse.left_pressed {
+            ctx.value_edit = Some(ValueEdit::Drag { id, anchor: axis(ctx), start: value, last: value, moved: false });
+        }
+    }
+
+    let pos = axis(ctx);
+    let left_down = ctx.mouse.left_down;
+    let dragging = match ctx.value_edit.as_mut() {
+        Some(ValueEdit::Drag { id: drag_id, anchor, start, last, moved }) if *drag_id == id => {
+            if left_down {
+                let steps = ((pos - *anchor) / spec.pixels_per_step).round();
+                let new_value = (*start + steps * spec.step).clamp(spec.min, spec.max);
+                if new_value != *last {
+                    change = Some(ValueChange { value: new_value, first: !*moved, last: false });
+                    *last = new_value;
+                    *moved = true;
+                }
+            } else if *moved {
+                change = Some(ValueChange { value: *last, first: false, last: true });
+            }
+            left_down
+        }
+        _ => false,
+    };
+    if !dragging && ctx.value_edit.as_ref().is_some_and(|e| e.id() == id) {
+        ctx.value_edit = None;
+    }
+
+    let bg = if dragging {
+        theme.button_active
+    } else if hovered {
+        theme.button_hover
+    } else {
+        theme.button_bg
+    };
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, bg);
+    let shown = change.map_or(value, |c| c.value);
+    let text = format!("{}: {}", spec.label, format_value(shown, spec.unit));
+    draw_text(&text, (rect.x + 4.0).floor(), (rect.y + (rect.h + 13.0) * 0.5 - 3.0).floor(), 13.0, WHITE);
+
+    change
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLICKS: ValueUnit = ValueUnit { size: 256.0, suffix: 'c', name: "clk" };
+
+    #[test]
+    fn values_show_and_parse_in_either_unit() {
+        assert_eq!(format_value(768.0, Some(CLICKS)), "768 (3 clk)");
+        assert_eq!(format_value(-64.0, Some(CLICKS)), "-64 (-0.25 clk)");
+        assert_eq!(format_value(0.5, None), "0.5");
+
+        assert_eq!(parse_value(" 768 ", Some(CLICKS)), Some(768.0));
+        assert_eq!(parse_value("3c", Some(CLICKS)), Some(768.0));
+        assert_eq!(parse_value("-1.5 CLK", Some(CLICKS)), Some(-384.0));
+        assert_eq!(parse_value("3c", None), None);
+        assert_eq!(parse_value("c", Some(CLICKS)), None);
+        assert_eq!(parse_value("inf", None), None);
+    }
+}
@@ -1,6 +1,8 @@
 //! Lucide icon support
 //!
-//! Uses the Lucide icon font for crisp vector icons at any size.
+//! Uses the Lucide icon font for crisp vector icons at any size. Without the font
+//! (it failed to load) icons are drawn as short text labels instead, which can also
+//! be forced for the toolbars (`UiContext::text_icons`).
 
 use macroquad::prelude::*;
 
@@ -10,11 +12,14 @@ pub mod icon {
     pub const SAVE: char = '\u{e14d}';
     pub const SAVE_AS: char = '\u{e40f}';  // save-all (Save As)
     pub const FOLDER_OPEN: char = '\u{e247}';
+    pub const IMPORT: char = '\u{e22f}';     // Import OBJ blockout
     pub const FILE_PLUS: char = '\u{e0c9}';
+    pub const FILE_DIFF: char = '\u{e317}';  // Compare with file
 
     // Edit operations
     pub const UNDO: char = '\u{e19b}';
     pub const REDO: char = '\u{e143}';
+    pub const HISTORY: char = '\u{e1f5}';
 
     // Playback / Transport
     pub const PLAY: char = '\u{e13c}';
@@ -35,12 +40,22 @@ pub mod icon {
     // Link/Unlink (for vertex mode)
     pub const LINK: char = '\u{e104}';
     pub const UNLINK: char = '\u{e19a}';
+    pub const CROSSHAIR: char = '\u{e0ac}';  // Sync views
+    pub const TAG: char = '\u{e17f}';        // 3D viewport labels
+    pub const EYE: char = '\u{e0ba}';        // 3D viewport layers
 
     // Editor tools
     pub const BOX: char = '\u{e061}';
     pub const LAYERS: char = '\u{e529}';
     pub const GRID: char = '\u{e0e9}';
     pub const DOOR_CLOSED: char = '\u{e09a}';  // Portal (doorway between rooms)
+    pub const HAND_GRAB: char = '\u{e1e6}';    // Move Room tool
+    pub const GEM: char = '\u{e242}';          // Pickup tool
+    pub const VOLUME_2: char = '\u{e1ab}';     // Ambient sound tool
+    pub const LIGHTBULB: char = '\u{e1c2}';    // Point light tool
+    pub const AXIS_3D: char = '\u{e2fe}';      // Isometric grid preview
+    pub const RULER: char = '\u{e14b}';        // Measure tool
+    pub const MOVE_VERTICAL: char = '\u{e1c7}'; // Height snap increment
 
     // PS1 effect toggles
     pub const WAVES: char = '\u{e283}';       // Affine texture mapping (warpy)
@@ -48,11 +63,22 @@ pub mod icon {
     pub const MONITOR: char = '\u{e11d}';     // Low resolution mode
     pub const SUN: char = '\u{e178}';         // Lighting/shading
     pub const BLEND: char = '\u{e59c}';       // Dithering (color blending)
+    pub const PALETTE: char = '\u{e1dd}';     // 15-bit color depth
+    pub const FILE_COG: char = '\u{e316}';    // Store render settings with level
+    pub const CONTRAST: char = '\u{e09d}';    // Bake ambient occlusion
 
     // Music editor
     pub const MUSIC: char = '\u{e122}';       // Music/notes
     pub const PIANO: char = '\u{e2ea}';       // Piano (keyboard icon)
     pub const LIST_MUSIC: char = '\u{e10b}';  // Arrangement/playlist
+    pub const CPU: char = '\u{e0a9}';         // SPU quantization (PS1 hardware)
+    pub const PENCIL: char = '\u{e1f9}';      // Edit mode
+    pub const HEADPHONES: char = '\u{e0f1}';  // Background playback
+    pub const LOCATE_FIXED: char = '\u{e1db}'; // Follow playback
+    pub const STAR: char = '\u{e176}';        // Favorite instrument
+    pub const TIMER: char = '\u{e1e0}';       // Metronome
+    pub const HOURGLASS: char = '\u{e296}';   // Count-in
+    pub const REPEAT: char = '\u{e146}';      // Pattern loop
 
     // Tab bar icons
     pub const HOUSE: char = '\u{e0f5}';           // Home tab
@@ -61,13 +87,128 @@ pub mod icon {
 
     // Properties panel icons
     pub const FOOTPRINTS: char = '\u{e3b9}';      // Walkable surface
+    pub const BRICK_WALL: char = '\u{e581}';      // Solid wall
 
     // Browser / Examples
     pub const BOOK_OPEN: char = '\u{e05f}';       // Examples browser
+    pub const BRUSH_CLEANING: char = '\u{e666}';  // Clean level (texture report)
+    pub const IMAGE_OFF: char = '\u{e1c0}';       // Highlight missing textures
+    pub const SWATCH_BOOK: char = '\u{e59f}';     // UI theme
+    pub const REPLACE: char = '\u{e3db}';         // Replace texture
+    pub const PLUG: char = '\u{e37f}';            // Suggest portals
+    pub const CHART_NO_AXES_COLUMN_INCREASING: char = '\u{e06a}'; // Stairs generator
+    pub const CLIPBOARD_COPY: char = '\u{e225}';  // Copy sectors as text
+    pub const CLIPBOARD_PASTE: char = '\u{e3e8}'; // Paste sectors from text
+    pub const TABLE_CELLS_MERGE: char = '\u{e5c7}'; // Merge coplanar faces
+    pub const IMAGE_DOWN: char = '\u{e53c}';      // Mipmapping
+    pub const LAMP: char = '\u{e2d8}';            // Dynamic lights
+    pub const BUG: char = '\u{e20c}';             // Debug render views
+    pub const TYPE: char = '\u{e198}';            // Text labels instead of icons
+    pub const SEARCH: char = '\u{e151}';          // Find faces
 }
 
-/// Draw a Lucide icon centered in a rect
+/// Text stand-ins for the icons (2-4 characters, to fit a square button)
+const ICON_LABELS: &[(char, &str)] = &[
+    (icon::SAVE, "Save"),
+    (icon::SAVE_AS, "SvAs"),
+    (icon::FOLDER_OPEN, "Open"),
+    (icon::IMPORT, "Imp"),
+    (icon::FILE_PLUS, "New"),
+    (icon::FILE_DIFF, "Diff"),
+    (icon::UNDO, "Undo"),
+    (icon::REDO, "Redo"),
+    (icon::HISTORY, "Hist"),
+    (icon::PLAY, "Play"),
+    (icon::PAUSE, "Paus"),
+    (icon::SQUARE, "Stop"),
+    (icon::SKIP_BACK, "Rew"),
+    (icon::SKIP_FORWARD, "Fwd"),
+    (icon::PLUS, "Add"),
+    (icon::MINUS, "Rem"),
+    (icon::MOVE, "Move"),
+    (icon::CIRCLE_CHEVRON_LEFT, "Prev"),
+    (icon::CIRCLE_CHEVRON_RIGHT, "Next"),
+    (icon::CHEVRON_UP, "Up"),
+    (icon::CHEVRON_DOWN, "Dn"),
+    (icon::LINK, "Link"),
+    (icon::UNLINK, "Unlk"),
+    (icon::CROSSHAIR, "Sync"),
+    (icon::TAG, "Tag"),
+    (icon::EYE, "View"),
+    (icon::BOX, "Box"),
+    (icon::LAYERS, "Lyr"),
+    (icon::GRID, "Grid"),
+    (icon::DOOR_CLOSED, "Port"),
+    (icon::HAND_GRAB, "Grab"),
+    (icon::GEM, "Gem"),
+    (icon::VOLUME_2, "Snd"),
+    (icon::LIGHTBULB, "Lght"),
+    (icon::AXIS_3D, "Iso"),
+    (icon::RULER, "Meas"),
+    (icon::MOVE_VERTICAL, "Snap"),
+    (icon::WAVES, "Afn"),
+    (icon::MAGNET, "Jit"),
+    (icon::MONITOR, "LoRs"),
+    (icon::SUN, "Lit"),
+    (icon::BLEND, "Dith"),
+    (icon::PALETTE, "15b"),
+    (icon::FILE_COG, "RSet"),
+    (icon::CONTRAST, "AO"),
+    (icon::MUSIC, "Mus"),
+    (icon::PIANO, "Pno"),
+    (icon::LIST_MUSIC, "Arr"),
+    (icon::CPU, "SPU"),
+    (icon::PENCIL, "Edit"),
+    (icon::HEADPHONES, "BgPl"),
+    (icon::LOCATE_FIXED, "Flw"),
+    (icon::STAR, "Fav"),
+    (icon::TIMER, "Met"),
+    (icon::HOURGLASS, "Cnt"),
+    (icon::REPEAT, "Loop"),
+    (icon::HOUSE, "Home"),
+    (icon::GLOBE, "Wrld"),
+    (icon::PERSON_STANDING, "Asst"),
+    (icon::FOOTPRINTS, "Walk"),
+    (icon::BRICK_WALL, "Wall"),
+    (icon::BOOK_OPEN, "Exmp"),
+    (icon::BRUSH_CLEANING, "Cln"),
+    (icon::IMAGE_OFF, "Miss"),
+    (icon::SWATCH_BOOK, "Thm"),
+    (icon::REPLACE, "Repl"),
+    (icon::PLUG, "Sugg"),
+    (icon::CHART_NO_AXES_COLUMN_INCREASING, "Strs"),
+    (icon::CLIPBOARD_COPY, "Copy"),
+    (icon::CLIPBOARD_PASTE, "Pste"),
+    (icon::TABLE_CELLS_MERGE, "Mrg"),
+    (icon::IMAGE_DOWN, "Mip"),
+    (icon::LAMP, "DynL"),
+    (icon::BUG, "Dbg"),
+    (icon::TYPE, "Aa"),
+    (icon::SEARCH, "Find"),
+];
+
+/// Text label drawn for `icon` when there's no icon font
+pub fn icon_label(icon: char) -> &'static str {
+    ICON_LABELS.iter().find(|(c, _)| *c == icon).map_or("?", |(_, label)| label)
+}
+
+/// Font size for `label` to fit `width` (pixels), at most `size`
+fn label_size(label: &str, width: f32, size: f32) -> f32 {
+    let natural = measure_text(label, None, size as u16, 1.0).width;
+    if natural <= width || natural <= 0.0 { size } else { (size * width / natural).floor().max(8.0) }
+}
+
+/// Draw a Lucide icon centered in a rect (its text label without a font)
 pub fn draw_icon_centered(font: Option<&Font>, icon: char, rect: &super::Rect, size: f32, color: Color) {
+    if font.is_none() {
+        let label = icon_label(icon);
+        let size = label_size(label, rect.w - 2.0, size.max(12.0));
+        let dims = measure_text(label, None, size as u16, 1.0);
+        let x = rect.x + (rect.w - dims.width) * 0.5;
+        let y = rect.y + (rect.h + dims.offset_y) * 0.5;
+        draw_text(label, x.round(), y.round(), size, color);
+        return;
+    }
     let text = icon.to_string();
 
     // Icon fonts typically have square glyphs where width ≈ height ≈ font size
@@ -95,3 +236,18 @@ pub fn draw_icon_centered(font: Option<&Font>, icon: char, rect: &super::Rect, s
         },
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_label_fits_a_button() {
+        for (i, (icon, label)) in ICON_LABELS.iter().enumerate() {
+            assert!((2..=4).contains(&label.chars().count()), "{:?}", label);
+            assert!(ICON_LABELS[i + 1..].iter().all(|(other, _)| other != icon), "{} listed twice", label);
+        }
+        assert_eq!(icon_label(icon::SAVE), "Save");
+        assert_eq!(icon_label('x'), "?");
+    }
+}
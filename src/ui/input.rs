@@ -1,8 +1,14 @@
 //! Input state for UI interaction
 
-use super::Rect;
+use super::{Rect, TextEdit, UiTheme};
+use super::drag_value::ValueEdit;
 use macroquad::prelude::*;
 
+/// Maximum time between the presses of a double-click (seconds)
+pub const DOUBLE_CLICK_TIME: f64 = 0.3;
+/// Maximum mouse travel between the presses of a double-click (pixels)
+const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
 /// Mouse button state
 #[derive(Debug, Clone, Copy, Default)]
 pub struct MouseState {
@@ -10,6 +16,7 @@ pub struct MouseState {
     pub y: f32,
     pub left_down: bool,
     pub right_down: bool,
+    pub middle_down: bool,
     pub left_pressed: bool,  // Just pressed this frame
     pub left_released: bool, // Just released this frame
     pub scroll: f32,         // Scroll wheel delta
@@ -53,6 +60,19 @@ pub struct UiContext {
     pub tooltip: Option<PendingTooltip>,
     /// Whether a modal dialog is active (blocks input to background)
     modal_active: bool,
+    /// Text field with keyboard focus (kept across frames)
+    pub(super) text_edit: Option<TextEdit>,
+    /// Drag value being dragged or typed into (kept across frames)
+    pub(super) value_edit: Option<ValueEdit>,
+    /// Active color theme
+    pub theme: UiTheme,
+    /// Icon buttons show text labels even with the icon font loaded (without it
+    /// they always do)
+    pub text_icons: bool,
+    /// Time and position of the last left press (for double-clicks)
+    last_press: Option<(f64, f32, f32)>,
+    /// Is this frame's left press the second of a double-click?
+    double_press: bool,
 }
 
 impl UiContext {
@@ -64,6 +84,12 @@ impl UiContext {
             id_counter: 0,
             tooltip: None,
             modal_active: false,
+            text_edit: None,
+            value_edit: None,
+            theme: UiTheme::default(),
+            text_icons: false,
+            last_press: None,
+            double_press: false,
         }
     }
 
@@ -80,6 +106,7 @@ impl UiContext {
             // Block all mouse interactions
             self.mouse.left_down = false;
             self.mouse.right_down = false;
+            self.mouse.middle_down = false;
             self.mouse.left_pressed = false;
             self.mouse.left_released = false;
             self.mouse.scroll = 0.0;
@@ -93,6 +120,23 @@ impl UiContext {
         self.mouse = real_mouse;
     }
 
+    /// Does any text field have keyboard focus? Editor shortcuts check this so
+    /// typing doesn't trigger them.
+    pub fn text_input_focused(&self) -> bool {
+        self.text_edit.is_some()
+    }
+
+    /// Does text field `id` have keyboard focus?
+    pub fn is_text_focused(&self, id: u64) -> bool {
+        self.text_edit.as_ref().is_some_and(|e| e.id == id)
+    }
+
+    /// Did the mouse just double-click inside a rect? (The first click of the pair
+    /// still counts as a plain click.)
+    pub fn double_clicked(&self, rect: &Rect) -> bool {
+        self.double_press && self.mouse.clicked(rect)
+    }
+
     /// Generate a unique ID for a widget
     pub fn next_id(&mut self) -> u64 {
         self.id_counter += 1;
@@ -107,6 +151,27 @@ impl UiContext {
         self.tooltip = None;
         self.modal_active = false;
 
+        // A press soon after the last one, close to it, makes a double-click; a
+        // third press starts a new pair
+        self.double_press = false;
+        if self.mouse.left_pressed {
+            let now = get_time();
+            self.double_press = self.last_press.is_some_and(|(time, x, y)| {
+                now - time < DOUBLE_CLICK_TIME
+                    && (self.mouse.x - x).abs() <= DOUBLE_CLICK_DISTANCE
+                    && (self.mouse.y - y).abs() <= DOUBLE_CLICK_DISTANCE
+            });
+            self.last_press = if self.double_press { None } else { Some((now, self.mouse.x, self.mouse.y)) };
+        }
+
+        // A focused text field that wasn't drawn last frame is gone: drop its focus
+        if self.text_edit.as_ref().is_some_and(|e| !e.seen) {
+            self.text_edit = None;
+        }
+        if let Some(edit) = &mut self.text_edit {
+            edit.seen = false;
+        }
+
         // Clear dragging if mouse released
         if !self.mouse.left_down {
             self.dragging = None;
@@ -16,6 +16,10 @@ mod widgets;
 mod input;
 mod tabbar;
 mod icons;
+mod status;
+mod text_input;
+mod drag_value;
+mod theme;
 
 pub use rect::*;
 pub use panel::*;
@@ -23,3 +27,7 @@ pub use widgets::*;
 pub use input::*;
 pub use tabbar::*;
 pub use icons::*;
+pub use status::*;
+pub use text_input::*;
+pub use drag_value::*;
+pub use theme::*;
@@ -3,7 +3,7 @@
 //! Panels can be split horizontally or vertically with draggable dividers.
 
 use macroquad::prelude::*;
-use super::{Rect, UiContext};
+use super::{Rect, UiContext, DOUBLE_CLICK_TIME};
 
 /// Direction of a split
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -12,13 +12,48 @@ pub enum SplitDir {
     Vertical,   // Top / Bottom
 }
 
+/// Minimum size of each side of a split
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinSize {
+    /// Absolute size in pixels
+    Pixels(f32),
+    /// Fraction of the container (0.0 - 0.5)
+    Fraction(f32),
+}
+
+impl MinSize {
+    /// Resolve to pixels for a container of the given size.
+    /// Never more than 40% of the container, so both sides stay usable in small windows.
+    pub fn resolve(&self, total_size: f32) -> f32 {
+        let size = match *self {
+            MinSize::Pixels(px) => px,
+            MinSize::Fraction(f) => f * total_size,
+        };
+        size.min(total_size * 0.4)
+    }
+}
+
+/// Which side of a split can be collapsed via the arrow on the divider
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollapseSide {
+    First,  // Left / Top
+    Second, // Right / Bottom
+}
+
+/// Length of the collapse arrow along the divider
+const COLLAPSE_ARROW_LENGTH: f32 = 24.0;
+
 /// A split panel that divides space between two children
 pub struct SplitPanel {
     pub id: u64,
     pub dir: SplitDir,
-    pub ratio: f32,        // 0.0 - 1.0
-    pub min_size: f32,     // Minimum size in pixels for each side
-    pub divider_size: f32, // Width/height of the draggable divider
+    pub ratio: f32,          // 0.0 - 1.0 (kept while collapsed, restored on expand)
+    pub default_ratio: f32,  // Ratio restored by double-clicking the divider
+    pub min_size: MinSize,   // Minimum size for each side
+    pub divider_size: f32,   // Width/height of the draggable divider
+    pub collapse_side: Option<CollapseSide>, // Side the arrow collapses (None = no arrow)
+    pub collapsed: bool,
+    last_click_time: f64,
 }
 
 impl SplitPanel {
@@ -27,8 +62,12 @@ impl SplitPanel {
             id,
             dir,
             ratio: 0.5,
-            min_size: 50.0,
-            divider_size: 4.0,
+            default_ratio: 0.5,
+            min_size: MinSize::Pixels(50.0),
+            divider_size: 6.0,
+            collapse_side: None,
+            collapsed: false,
+            last_click_time: f64::NEG_INFINITY,
         }
     }
 
@@ -40,31 +79,81 @@ impl SplitPanel {
         Self::new(id, SplitDir::Vertical)
     }
 
+    /// Set the ratio (also used as the double-click reset ratio)
     pub fn with_ratio(mut self, ratio: f32) -> Self {
         self.ratio = ratio.clamp(0.0, 1.0);
+        self.default_ratio = self.ratio;
         self
     }
 
+    /// Minimum size in pixels for each side
     pub fn with_min_size(mut self, min_size: f32) -> Self {
-        self.min_size = min_size;
+        self.min_size = MinSize::Pixels(min_size);
+        self
+    }
+
+    /// Minimum size as a fraction of the container for each side
+    pub fn with_min_fraction(mut self, fraction: f32) -> Self {
+        self.min_size = MinSize::Fraction(fraction.clamp(0.0, 0.5));
+        self
+    }
+
+    /// Show an arrow on the divider that collapses the given side
+    pub fn with_collapse(mut self, side: CollapseSide) -> Self {
+        self.collapse_side = Some(side);
         self
     }
 
+    /// Reset to the default ratio (and expand if collapsed)
+    pub fn reset(&mut self) {
+        self.ratio = self.default_ratio;
+        self.collapsed = false;
+    }
+
+    /// Toggle the collapsed state (ratio is remembered for expanding)
+    pub fn toggle_collapsed(&mut self) {
+        if self.collapse_side.is_some() {
+            self.collapsed = !self.collapsed;
+        }
+    }
+
     /// Update and render the split panel
     /// Returns (first_rect, second_rect) for the two child areas
     pub fn update(&mut self, ctx: &mut UiContext, bounds: Rect) -> (Rect, Rect) {
+        // Nothing to split (e.g. inside a collapsed parent)
+        if bounds.w <= 1.0 || bounds.h <= 1.0 {
+            let empty = Rect::new(bounds.x, bounds.y, 0.0, 0.0);
+            return (empty, empty);
+        }
+
         let divider_rect = self.divider_rect(bounds);
+        let arrow_rect = self.arrow_rect(divider_rect);
 
         // Handle divider dragging
         if ctx.mouse.inside(&divider_rect) {
             ctx.set_hot(self.id);
         }
 
+        let over_arrow = arrow_rect.is_some_and(|r| ctx.mouse.inside(&r));
         if ctx.is_hot(self.id) && ctx.mouse.left_pressed {
-            ctx.start_drag(self.id);
+            if over_arrow {
+                self.toggle_collapsed();
+            } else {
+                let now = get_time();
+                if now - self.last_click_time < DOUBLE_CLICK_TIME {
+                    self.reset();
+                    self.last_click_time = f64::NEG_INFINITY;
+                } else {
+                    self.last_click_time = now;
+                    ctx.start_drag(self.id);
+                }
+            }
         }
 
         if ctx.is_dragging(self.id) {
+            // Dragging a collapsed divider expands it again
+            self.collapsed = false;
+
             // Update ratio based on mouse position
             match self.dir {
                 SplitDir::Horizontal => {
@@ -87,60 +176,125 @@ impl SplitPanel {
         };
         draw_rectangle(divider_rect.x, divider_rect.y, divider_rect.w, divider_rect.h, color);
 
+        // Collapse/expand arrow (points the way the divider will move)
+        if let (Some(rect), Some(side)) = (arrow_rect, self.collapse_side) {
+            let arrow_color = if over_arrow {
+                WHITE
+            } else {
+                Color::from_rgba(150, 150, 160, 255)
+            };
+            let towards_first = (side == CollapseSide::First) != self.collapsed;
+            let (a, b, c) = match (self.dir, towards_first) {
+                (SplitDir::Horizontal, true) => (
+                    vec2(rect.x, rect.y + rect.h * 0.5),
+                    vec2(rect.right(), rect.y + rect.h * 0.3),
+                    vec2(rect.right(), rect.y + rect.h * 0.7),
+                ),
+                (SplitDir::Horizontal, false) => (
+                    vec2(rect.right(), rect.y + rect.h * 0.5),
+                    vec2(rect.x, rect.y + rect.h * 0.3),
+                    vec2(rect.x, rect.y + rect.h * 0.7),
+                ),
+                (SplitDir::Vertical, true) => (
+                    vec2(rect.x + rect.w * 0.5, rect.y),
+                    vec2(rect.x + rect.w * 0.3, rect.bottom()),
+                    vec2(rect.x + rect.w * 0.7, rect.bottom()),
+                ),
+                (SplitDir::Vertical, false) => (
+                    vec2(rect.x + rect.w * 0.5, rect.bottom()),
+                    vec2(rect.x + rect.w * 0.3, rect.y),
+                    vec2(rect.x + rect.w * 0.7, rect.y),
+                ),
+            };
+            draw_triangle(a, b, c, arrow_color);
+            if over_arrow {
+                let tip = if self.collapsed { "Expand panel" } else { "Collapse panel" };
+                ctx.set_tooltip(tip, ctx.mouse.x, ctx.mouse.y);
+            }
+        }
+
         // Return child rects
         self.child_rects(bounds)
     }
 
     /// Clamp ratio to respect minimum sizes
     fn clamp_ratio(&self, ratio: f32, total_size: f32) -> f32 {
-        let min_ratio = self.min_size / total_size;
+        if total_size <= 0.0 {
+            return ratio;
+        }
+        let min_ratio = self.min_size.resolve(total_size) / total_size;
         let max_ratio = 1.0 - min_ratio;
         ratio.clamp(min_ratio, max_ratio)
     }
 
+    /// Ratio actually used for layout: collapsed sides take no space, and the stored
+    /// ratio is clamped to the current container so a shrunk window keeps both sides usable
+    fn effective_ratio(&self, bounds: Rect) -> f32 {
+        if self.collapsed {
+            match self.collapse_side {
+                Some(CollapseSide::First) => return 0.0,
+                Some(CollapseSide::Second) => return 1.0,
+                None => {}
+            }
+        }
+        let total = match self.dir {
+            SplitDir::Horizontal => bounds.w,
+            SplitDir::Vertical => bounds.h,
+        };
+        self.clamp_ratio(self.ratio, total)
+    }
+
     /// Get the divider rectangle
     fn divider_rect(&self, bounds: Rect) -> Rect {
+        let ratio = self.effective_ratio(bounds);
         match self.dir {
             SplitDir::Horizontal => {
-                let x = bounds.x + bounds.w * self.ratio - self.divider_size * 0.5;
+                let x = (bounds.x + bounds.w * ratio - self.divider_size * 0.5)
+                    .min(bounds.right() - self.divider_size)
+                    .max(bounds.x);
                 Rect::new(x, bounds.y, self.divider_size, bounds.h)
             }
             SplitDir::Vertical => {
-                let y = bounds.y + bounds.h * self.ratio - self.divider_size * 0.5;
+                let y = (bounds.y + bounds.h * ratio - self.divider_size * 0.5)
+                    .min(bounds.bottom() - self.divider_size)
+                    .max(bounds.y);
                 Rect::new(bounds.x, y, bounds.w, self.divider_size)
             }
         }
     }
 
+    /// Get the collapse arrow rectangle (centered on the divider)
+    fn arrow_rect(&self, divider: Rect) -> Option<Rect> {
+        self.collapse_side?;
+        Some(match self.dir {
+            SplitDir::Horizontal => Rect::new(
+                divider.x,
+                divider.y + (divider.h - COLLAPSE_ARROW_LENGTH) * 0.5,
+                divider.w,
+                COLLAPSE_ARROW_LENGTH,
+            ),
+            SplitDir::Vertical => Rect::new(
+                divider.x + (divider.w - COLLAPSE_ARROW_LENGTH) * 0.5,
+                divider.y,
+                COLLAPSE_ARROW_LENGTH,
+                divider.h,
+            ),
+        })
+    }
+
     /// Get the two child rectangles (excluding divider)
     fn child_rects(&self, bounds: Rect) -> (Rect, Rect) {
-        let half_div = self.divider_size * 0.5;
+        let divider = self.divider_rect(bounds);
 
         match self.dir {
-            SplitDir::Horizontal => {
-                let split = bounds.w * self.ratio;
-                (
-                    Rect::new(bounds.x, bounds.y, split - half_div, bounds.h),
-                    Rect::new(
-                        bounds.x + split + half_div,
-                        bounds.y,
-                        bounds.w - split - half_div,
-                        bounds.h,
-                    ),
-                )
-            }
-            SplitDir::Vertical => {
-                let split = bounds.h * self.ratio;
-                (
-                    Rect::new(bounds.x, bounds.y, bounds.w, split - half_div),
-                    Rect::new(
-                        bounds.x,
-                        bounds.y + split + half_div,
-                        bounds.w,
-                        bounds.h - split - half_div,
-                    ),
-                )
-            }
+            SplitDir::Horizontal => (
+                Rect::new(bounds.x, bounds.y, divider.x - bounds.x, bounds.h),
+                Rect::new(divider.right(), bounds.y, bounds.right() - divider.right(), bounds.h),
+            ),
+            SplitDir::Vertical => (
+                Rect::new(bounds.x, bounds.y, bounds.w, divider.y - bounds.y),
+                Rect::new(bounds.x, divider.bottom(), bounds.w, bounds.bottom() - divider.bottom()),
+            ),
         }
     }
 }
@@ -175,3 +329,39 @@ pub fn panel_content_rect(rect: Rect, has_title: bool) -> Rect {
         rect.pad(2.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_fraction_clamps_stored_ratio() {
+        let mut split = SplitPanel::horizontal(1).with_ratio(0.5).with_min_fraction(0.2);
+        split.ratio = 0.02;
+        let (left, right) = split.child_rects(Rect::new(0.0, 0.0, 1000.0, 100.0));
+        assert!(left.w >= 197.0);
+        assert!((left.w + right.w + split.divider_size - 1000.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pixel_min_limited_in_small_container() {
+        let split = SplitPanel::horizontal(1).with_ratio(0.9).with_min_size(300.0);
+        let (_, right) = split.child_rects(Rect::new(0.0, 0.0, 500.0, 100.0));
+        assert!(right.w >= 190.0);
+    }
+
+    #[test]
+    fn test_collapse_keeps_ratio() {
+        let mut split = SplitPanel::vertical(1).with_ratio(0.6).with_collapse(CollapseSide::Second);
+        split.ratio = 0.7;
+        split.toggle_collapsed();
+        let (top, bottom) = split.child_rects(Rect::new(0.0, 0.0, 100.0, 400.0));
+        assert!(bottom.h.abs() < 0.01);
+        assert!((top.h - (400.0 - split.divider_size)).abs() < 0.01);
+
+        split.toggle_collapsed();
+        assert!((split.ratio - 0.7).abs() < 0.001);
+        split.reset();
+        assert!((split.ratio - 0.6).abs() < 0.001);
+    }
+}
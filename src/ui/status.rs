@@ -0,0 +1,154 @@
+//! Status messages - a small timed queue shown in status bars
+//!
+//! Messages are shown one after another instead of overwriting each other, so a
+//! warning raised in the same frame as "Saved" is still seen. Warnings and errors
+//! stay up for a minimum time; errors are also logged (stderr / browser console).
+
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+
+/// How important a status message is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Shortest time a message stays up before a newer one can take over
+    fn min_duration(self) -> f64 {
+        match self {
+            Severity::Info => 0.5,
+            Severity::Warning | Severity::Error => 3.0,
+        }
+    }
+
+    /// Display time used by `set_warning` / `set_error`
+    pub fn default_duration(self) -> f64 {
+        match self {
+            Severity::Info => 2.0,
+            Severity::Warning => 4.0,
+            Severity::Error => 5.0,
+        }
+    }
+
+    /// Status bar text color
+    pub fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::from_rgba(100, 255, 100, 255),
+            Severity::Warning => Color::from_rgba(255, 200, 80, 255),
+            Severity::Error => Color::from_rgba(255, 100, 100, 255),
+        }
+    }
+}
+
+struct StatusEntry {
+    message: String,
+    severity: Severity,
+    /// Display window (from get_time)
+    start: f64,
+    end: f64,
+}
+
+/// Queue of timed status messages
+#[derive(Default)]
+pub struct StatusQueue {
+    entries: VecDeque<StatusEntry>,
+}
+
+impl StatusQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a message to show for `duration` seconds once earlier messages are done
+    pub fn push(&mut self, message: &str, severity: Severity, duration: f64) {
+        if severity == Severity::Error {
+            macroquad::logging::error!("{}", message);
+        }
+        self.push_at(message, severity, duration, get_time());
+    }
+
+    fn push_at(&mut self, message: &str, severity: Severity, duration: f64, now: f64) {
+        while self.entries.front().is_some_and(|e| e.end <= now) {
+            self.entries.pop_front();
+        }
+        let duration = duration.max(severity.min_duration());
+
+        // Same message again (e.g. refreshed every frame while dragging): keep it up longer
+        if let Some(last) = self.entries.back_mut() {
+            if last.message == message && last.severity == severity {
+                last.end = last.end.max(now + duration);
+                return;
+            }
+        }
+
+        // An info message that hasn't been shown yet is stale once a newer one arrives
+        if severity == Severity::Info
+            && self.entries.back().is_some_and(|e| e.severity == Severity::Info && e.start > now)
+        {
+            self.entries.pop_back();
+        }
+
+        // Cut the previous message down to its minimum so this one isn't kept waiting
+        let start = match self.entries.back_mut() {
+            Some(last) => {
+                last.end = last.end.min((last.start + last.severity.min_duration()).max(now));
+                last.end
+            }
+            None => now,
+        };
+        self.entries.push_back(StatusEntry {
+            message: message.to_string(),
+            severity,
+            start,
+            end: start + duration,
+        });
+    }
+
+    /// Message to display right now, if any
+    pub fn current(&self) -> Option<(&str, Severity)> {
+        self.current_at(get_time())
+    }
+
+    fn current_at(&self, now: f64) -> Option<(&str, Severity)> {
+        self.entries.iter()
+            .find(|e| e.start <= now && now < e.end)
+            .map(|e| (e.message.as_str(), e.severity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_show_in_order() {
+        let mut queue = StatusQueue::new();
+        queue.push_at("Saved", Severity::Info, 2.0, 0.0);
+        queue.push_at("Portals misaligned", Severity::Warning, 4.0, 0.0);
+
+        // Info gets its minimum time, then the warning takes over for its full duration
+        assert_eq!(queue.current_at(0.1), Some(("Saved", Severity::Info)));
+        assert_eq!(queue.current_at(1.0), Some(("Portals misaligned", Severity::Warning)));
+        assert_eq!(queue.current_at(4.4), Some(("Portals misaligned", Severity::Warning)));
+        assert_eq!(queue.current_at(5.0), None);
+
+        // Warnings aren't cut short by later info messages
+        queue.push_at("Selected", Severity::Info, 1.0, 1.0);
+        assert_eq!(queue.current_at(3.0), Some(("Portals misaligned", Severity::Warning)));
+        assert_eq!(queue.current_at(3.6), Some(("Selected", Severity::Info)));
+    }
+
+    #[test]
+    fn repeated_and_stale_info_collapse() {
+        let mut queue = StatusQueue::new();
+        queue.push_at("Height: 256", Severity::Info, 0.5, 0.0);
+        queue.push_at("Height: 256", Severity::Info, 0.5, 0.1);
+        queue.push_at("Height: 512", Severity::Info, 0.5, 0.2);
+        queue.push_at("Height: 768", Severity::Info, 0.5, 0.3);
+        assert_eq!(queue.entries.len(), 2);
+        assert_eq!(queue.current_at(0.55), Some(("Height: 768", Severity::Info)));
+    }
+}
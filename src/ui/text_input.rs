@@ -0,0 +1,330 @@
+//! Single-line text input
+//!
+//! One field has keyboard focus at a time. Its editing state (caret, selection and
+//! the value Escape restores) lives in `UiContext`, so callers only own the `String`.
+//! Positions are in chars, not bytes, so text committed by an IME (any multi-byte
+//! char) is edited as a whole. While a field has focus, `UiContext::text_input_focused`
+//! is true and editor shortcuts should stay quiet.
+
+use macroquad::prelude::*;
+use super::{Rect, UiContext, ACCENT_COLOR};
+
+const FONT_SIZE: f32 = 14.0;
+const PADDING: f32 = 4.0;
+
+/// What happened to a text field this frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextInputEvent {
+    None,
+    /// The text was edited
+    Changed,
+    /// Enter, or a click outside the field: focus is released and the text kept
+    Submitted,
+    /// Escape: focus is released and the text restored to its value at focus
+    Cancelled,
+}
+
+/// Editing state of the focused text field
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    pub id: u64,
+    /// Value when the field took focus (restored on Escape)
+    original: String,
+    /// Caret position (chars)
+    caret: usize,
+    /// Other end of the selection (chars); equal to `caret` when nothing is selected
+    anchor: usize,
+    /// Drawn since the last `begin_frame` (focus is dropped once the field goes away)
+    pub(super) seen: bool,
+}
+
+/// Byte offset of char index `i` in `text`
+fn byte_index(text: &str, i: usize) -> usize {
+    text.char_indices().nth(i).map_or(text.len(), |(b, _)| b)
+}
+
+impl TextEdit {
+    fn new(id: u64, text: &str, caret: usize) -> Self {
+        Self { id, original: text.to_string(), caret, anchor: caret, seen: true }
+    }
+
+    /// Selected char range (start, end); empty when start == end
+    fn selection(&self) -> (usize, usize) {
+        (self.caret.min(self.anchor), self.caret.max(self.anchor))
+    }
+
+    fn selected_text<'a>(&self, text: &'a str) -> &'a str {
+        let (start, end) = self.selection();
+        &text[byte_index(text, start)..byte_index(text, end)]
+    }
+
+    /// Move the caret, growing the selection if `extend` (Shift held)
+    fn move_to(&mut self, text: &str, to: usize, extend: bool) {
+        self.caret = to.min(text.chars().count());
+        if !extend {
+            self.anchor = self.caret;
+        }
+    }
+
+    /// Left/Right by one char. Without Shift, a selection collapses to its edge first.
+    fn step(&mut self, text: &str, right: bool, extend: bool) {
+        let (start, end) = self.selection();
+        let to = match (right, extend || start == end) {
+            (false, true) => self.caret.saturating_sub(1),
+            (true, true) => self.caret + 1,
+            (false, false) => start,
+            (true, false) => end,
+        };
+        self.move_to(text, to, extend);
+    }
+
+    fn select_all(&mut self, text: &str) {
+        self.anchor = 0;
+        self.caret = text.chars().count();
+    }
+
+    /// Remove the selection. Returns false if nothing was selected.
+    fn delete_selection(&mut self, text: &mut String) -> bool {
+        let (start, end) = self.selection();
+        if start == end {
+            return false;
+        }
+        text.replace_range(byte_index(text, start)..byte_index(text, end), "");
+        self.caret = start;
+        self.anchor = start;
+        true
+    }
+
+    /// Type or paste `s` over the selection
+    fn insert(&mut self, text: &mut String, s: &str) {
+        self.delete_selection(text);
+        text.insert_str(byte_index(text, self.caret), s);
+        self.caret += s.chars().count();
+        self.anchor = self.caret;
+    }
+
+    /// Backspace (`forward` false) or Delete. Returns true if the text changed.
+    fn erase(&mut self, text: &mut String, forward: bool) -> bool {
+        if self.delete_selection(text) {
+            return true;
+        }
+        let len = text.chars().count();
+        let (start, end) = if forward {
+            (self.caret, (self.caret + 1).min(len))
+        } else {
+            (self.caret.saturating_sub(1), self.caret)
+        };
+        if start == end {
+            return false;
+        }
+        self.anchor = start;
+        self.caret = end;
+        self.delete_selection(text)
+    }
+}
+
+fn text_width(s: &str) -> f32 {
+    measure_text(s, None, FONT_SIZE as u16, 1.0).width
+}
+
+/// First char drawn so the caret stays inside a field `width` wide
+fn scroll_start(text: &str, caret: usize, width: f32) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    (0..caret)
+        .find(|&start| text_width(&chars[start..caret].iter().collect::<String>()) <= width)
+        .unwrap_or(caret)
+}
+
+/// Char index nearest to `x` pixels right of the first drawn char
+fn caret_at(text: &str, start: usize, x: f32) -> usize {
+    let mut prefix = String::new();
+    let mut prev_w = 0.0;
+    for (i, c) in text.chars().enumerate().skip(start) {
+        prefix.push(c);
+        let w = text_width(&prefix);
+        if x < (prev_w + w) * 0.5 {
+            return i;
+        }
+        prev_w = w;
+    }
+    text.chars().count()
+}
+
+/// Clipboard text as a single line (None when the platform has no clipboard)
+fn clipboard_line() -> Option<String> {
+    let text = macroquad::miniquad::window::clipboard_get()?;
+    Some(text.chars().filter(|c| !c.is_control()).collect())
+}
+
+impl UiContext {
+    /// Give text field `id` keyboard focus with the caret at the end of `text`
+    /// (for fields that should take typing without a click first)
+    pub fn focus_text(&mut self, id: u64, text: &str) {
+        if !self.is_text_focused(id) {
+            while get_char_pressed().is_some() {}
+            self.text_edit = Some(TextEdit::new(id, text, text.chars().count()));
+        }
+    }
+}
+
+/// Draw a single-line text field editing `text`.
+///
+/// `id` must be the same every frame (not from `next_id`, which depends on draw
+/// order). Click to focus; Left/Right/Home/End move the caret (Shift selects),
+/// Backspace/Delete erase, Ctrl+A/C/X/V select all and use the clipboard, Enter
+/// or a click elsewhere submits and Escape cancels.
+pub fn text_input(ctx: &mut UiContext, rect: Rect, id: u64, text: &mut String) -> TextInputEvent {
+    let hovered = ctx.mouse.inside(&rect);
+    let inner_w = rect.w - PADDING * 2.0;
+    let mut event = TextInputEvent::None;
+
+    if !ctx.is_text_focused(id) && ctx.mouse.clicked(&rect) {
+        // Drop anything typed before the field had focus
+        while get_char_pressed().is_some() {}
+        let start = scroll_start(text, text.chars().count(), inner_w);
+        ctx.text_edit = Some(TextEdit::new(id, text, caret_at(text, start, ctx.mouse.x - rect.x - PADDING)));
+    }
+
+    if let Some(edit) = ctx.text_edit.as_mut().filter(|e| e.id == id) {
+        edit.seen = true;
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+                 || is_key_down(KeyCode::LeftSuper) || is_key_down(KeyCode::RightSuper);
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let before = text.clone();
+
+        // Mouse: click (Shift+click extends) and drag to select
+        let start = scroll_start(text, edit.caret, inner_w);
+        let mouse_caret = caret_at(text, start, ctx.mouse.x - rect.x - PADDING);
+        if ctx.mouse.clicked(&rect) {
+            edit.move_to(text, mouse_caret, shift);
+            ctx.dragging = Some(id);
+        } else if ctx.dragging == Some(id) && ctx.mouse.left_down {
+            edit.move_to(text, mouse_caret, true);
+        }
+
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && !ctrl {
+                edit.insert(text, &c.to_string());
+            }
+        }
+        if is_key_pressed(KeyCode::Left) {
+            edit.step(text, false, shift);
+        }
+        if is_key_pressed(KeyCode::Right) {
+            edit.step(text, true, shift);
+        }
+        if is_key_pressed(KeyCode::Home) {
+            edit.move_to(text, 0, shift);
+        }
+        if is_key_pressed(KeyCode::End) {
+            edit.move_to(text, usize::MAX, shift);
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            edit.erase(text, false);
+        }
+        if is_key_pressed(KeyCode::Delete) {
+            edit.erase(text, true);
+        }
+        if ctrl && is_key_pressed(KeyCode::A) {
+            edit.select_all(text);
+        }
+        if ctrl && (is_key_pressed(KeyCode::C) || is_key_pressed(KeyCode::X)) {
+            let selected = edit.selected_text(text);
+            if !selected.is_empty() {
+                macroquad::miniquad::window::clipboard_set(selected);
+                if is_key_pressed(KeyCode::X) {
+                    edit.delete_selection(text);
+                }
+            }
+        }
+        if ctrl && is_key_pressed(KeyCode::V) {
+            if let Some(pasted) = clipboard_line() {
+                edit.insert(text, &pasted);
+            }
+        }
+
+        if *text != before {
+            event = TextInputEvent::Changed;
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            *text = edit.original.clone();
+            event = TextInputEvent::Cancelled;
+        } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter)
+            || (ctx.mouse.left_pressed && !hovered)
+        {
+            event = TextInputEvent::Submitted;
+        }
+        if matches!(event, TextInputEvent::Cancelled | TextInputEvent::Submitted) {
+            ctx.text_edit = None;
+        }
+    }
+
+    // Draw
+    let edit = ctx.text_edit.as_ref().filter(|e| e.id == id);
+    let bg = if edit.is_some() {
+        Color::from_rgba(70, 90, 110, 255)
+    } else if hovered {
+        Color::from_rgba(55, 55, 65, 255)
+    } else {
+        Color::from_rgba(40, 40, 48, 255)
+    };
+    draw_rectangle(rect.x.floor(), rect.y.floor(), rect.w, rect.h, bg);
+
+    let caret = edit.map_or(0, |e| e.caret);
+    let start = scroll_start(text, caret, inner_w);
+    let chars: Vec<char> = text.chars().collect();
+    let x_of = |i: usize| rect.x + PADDING + text_width(&chars[start..i.max(start)].iter().collect::<String>());
+    let mut end = start;
+    while end < chars.len() && x_of(end + 1) <= rect.right() - PADDING {
+        end += 1;
+    }
+    let text_y = (rect.y + (rect.h + FONT_SIZE) * 0.5 - 3.0).floor();
+
+    if let Some(edit) = edit {
+        let (sel_start, sel_end) = edit.selection();
+        let (a, b) = (sel_start.clamp(start, end), sel_end.clamp(start, end));
+        if a < b {
+            let selection = Color::new(ACCENT_COLOR.r, ACCENT_COLOR.g, ACCENT_COLOR.b, 0.6);
+            draw_rectangle(x_of(a).floor(), rect.y + 2.0, x_of(b) - x_of(a), rect.h - 4.0, selection);
+        }
+    }
+    let visible: String = chars[start..end].iter().collect();
+    draw_text(&visible, (rect.x + PADDING).floor(), text_y, FONT_SIZE, WHITE);
+    if edit.is_some() && get_time().fract() < 0.5 {
+        let caret_x = x_of(caret).floor() + 0.5;
+        draw_line(caret_x, rect.y + 3.0, caret_x, rect.bottom() - 3.0, 1.0, WHITE);
+    }
+
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editing_works_on_chars() {
+        let mut text = "naïve".to_string();
+        let mut edit = TextEdit::new(1, &text, 3);
+
+        // Shift+Left selects the multi-byte char, typing replaces it
+        edit.step(&text, false, true);
+        assert_eq!(edit.selected_text(&text), "ï");
+        edit.insert(&mut text, "日本");
+        assert_eq!((text.as_str(), edit.caret), ("na日本ve", 4));
+
+        // Left without Shift moves one char; Backspace and Delete erase one each
+        edit.step(&text, false, false);
+        assert!(edit.erase(&mut text, false));
+        assert!(edit.erase(&mut text, true));
+        assert_eq!((text.as_str(), edit.caret), ("nave", 2));
+
+        // Home + Shift+End selects everything; cut leaves an empty field
+        edit.move_to(&text, 0, false);
+        edit.move_to(&text, usize::MAX, true);
+        assert_eq!(edit.selection(), (0, 4));
+        assert!(edit.delete_selection(&mut text));
+        assert!(text.is_empty() && !edit.erase(&mut text, false));
+        assert_eq!(edit.original, "naïve");
+    }
+}
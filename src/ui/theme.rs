@@ -0,0 +1,260 @@
+//! UI color themes
+//!
+//! The palette shared by the editor and tracker draw code. The active theme lives
+//! in `UiContext::theme`; the built-in ones are the original dark palette, a
+//! high-contrast one, and one that swaps red/green cues for blue/orange.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use super::Severity;
+
+/// Built-in themes (saved in the editor prefs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThemeKind {
+    #[default]
+    Default,
+    HighContrast,
+    Deuteranopia,
+}
+
+impl ThemeKind {
+    /// Next theme when cycling (toolbar button)
+    pub fn next(self) -> Self {
+        match self {
+            ThemeKind::Default => ThemeKind::HighContrast,
+            ThemeKind::HighContrast => ThemeKind::Deuteranopia,
+            ThemeKind::Deuteranopia => ThemeKind::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeKind::Default => "Default",
+            ThemeKind::HighContrast => "High Contrast",
+            ThemeKind::Deuteranopia => "Color-blind (blue/orange)",
+        }
+    }
+
+    pub fn theme(self) -> UiTheme {
+        match self {
+            ThemeKind::Default => UiTheme::DEFAULT,
+            ThemeKind::HighContrast => UiTheme::HIGH_CONTRAST,
+            ThemeKind::Deuteranopia => UiTheme::DEUTERANOPIA,
+        }
+    }
+}
+
+/// Tracker pattern editor colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackerColors {
+    pub bg: Color,
+    pub header: Color,
+    pub row_even: Color,
+    pub row_odd: Color,
+    /// Rows on a beat
+    pub row_beat: Color,
+    /// Current row / current arrangement entry
+    pub row_highlight: Color,
+    pub cursor: Color,
+    pub cursor_edit: Color,
+    pub playback_row: Color,
+    pub text: Color,
+    pub text_dim: Color,
+    pub note: Color,
+    pub inst: Color,
+    pub vol: Color,
+    pub fx: Color,
+}
+
+/// Colors used across the editor UI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiTheme {
+    /// Side panels
+    pub panel_bg: Color,
+    /// Sunken areas (3D viewport panel, texture palette)
+    pub inset_bg: Color,
+    /// Status bar and header strips
+    pub bar_bg: Color,
+    pub popup_bg: Color,
+    pub popup_border: Color,
+
+    pub button_bg: Color,
+    pub button_hover: Color,
+    /// Pressed / toggled-on buttons and focused fields
+    pub button_active: Color,
+    pub border: Color,
+    /// Slider fill
+    pub fill: Color,
+
+    pub text: Color,
+    /// Values and secondary labels
+    pub text_secondary: Color,
+    /// Captions and headings
+    pub text_dim: Color,
+    /// Placeholders and hints
+    pub text_faint: Color,
+
+    /// Current item in lists (room list, history)
+    pub selection: Color,
+    pub selection_bg: Color,
+    /// Selected things in the views (sectors, walls, textures)
+    pub highlight: Color,
+    /// Things under the mouse in the views
+    pub hover: Color,
+    pub accent: Color,
+
+    pub info: Color,
+    pub warning: Color,
+    pub error: Color,
+
+    pub grid_bg: Color,
+    pub grid_line: Color,
+    /// Grid lines through the origin (X = 0, Z = 0)
+    pub axis_x: Color,
+    pub axis_z: Color,
+    pub sector_edge: Color,
+    pub sector_edge_active: Color,
+
+    pub tracker: TrackerColors,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::from_rgba(r, g, b, 255)
+}
+
+impl UiTheme {
+    /// Status message color
+    pub fn severity(&self, severity: Severity) -> Color {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Warning => self.warning,
+            Severity::Error => self.error,
+        }
+    }
+
+    /// The original dark palette
+    pub const DEFAULT: UiTheme = UiTheme {
+        panel_bg: rgb(35, 35, 40),
+        inset_bg: rgb(25, 25, 30),
+        bar_bg: rgb(40, 40, 45),
+        popup_bg: Color::from_rgba(30, 30, 36, 240),
+        popup_border: rgb(80, 80, 95),
+        button_bg: rgb(45, 45, 55),
+        button_hover: rgb(60, 60, 75),
+        button_active: rgb(70, 90, 110),
+        border: rgb(70, 70, 85),
+        fill: rgb(80, 90, 110),
+        text: WHITE,
+        text_secondary: rgb(180, 180, 180),
+        text_dim: rgb(150, 150, 150),
+        text_faint: rgb(100, 100, 100),
+        selection: rgb(100, 200, 100),
+        selection_bg: rgb(60, 80, 60),
+        highlight: rgb(255, 200, 100),
+        hover: rgb(150, 200, 255),
+        accent: Color::new(0.0, 0.75, 0.9, 1.0),
+        info: rgb(100, 255, 100),
+        warning: rgb(255, 200, 80),
+        error: rgb(255, 100, 100),
+        grid_bg: rgb(20, 20, 25),
+        grid_line: rgb(40, 40, 45),
+        axis_x: rgb(80, 40, 40),
+        axis_z: rgb(40, 80, 40),
+        sector_edge: rgb(100, 100, 110),
+        sector_edge_active: rgb(200, 200, 220),
+        tracker: TrackerColors {
+            bg: Color::new(0.11, 0.11, 0.13, 1.0),
+            header: Color::new(0.15, 0.15, 0.18, 1.0),
+            row_even: Color::new(0.13, 0.13, 0.15, 1.0),
+            row_odd: Color::new(0.11, 0.11, 0.13, 1.0),
+            row_beat: Color::new(0.16, 0.14, 0.12, 1.0),
+            row_highlight: Color::new(0.2, 0.25, 0.3, 1.0),
+            cursor: Color::new(0.3, 0.5, 0.8, 0.8),
+            cursor_edit: Color::new(0.75, 0.3, 0.3, 0.85),
+            playback_row: Color::new(0.4, 0.2, 0.2, 0.6),
+            text: Color::new(0.8, 0.8, 0.85, 1.0),
+            text_dim: Color::new(0.4, 0.4, 0.45, 1.0),
+            note: Color::new(0.9, 0.85, 0.5, 1.0),
+            inst: Color::new(0.5, 0.8, 0.5, 1.0),
+            vol: Color::new(0.5, 0.7, 0.9, 1.0),
+            fx: Color::new(0.9, 0.5, 0.7, 1.0),
+        },
+    };
+
+    /// Black backgrounds, white text and borders, saturated highlights
+    pub const HIGH_CONTRAST: UiTheme = UiTheme {
+        panel_bg: rgb(10, 10, 10),
+        inset_bg: rgb(0, 0, 0),
+        bar_bg: rgb(20, 20, 20),
+        popup_bg: Color::from_rgba(0, 0, 0, 250),
+        popup_border: WHITE,
+        button_bg: rgb(30, 30, 30),
+        button_hover: rgb(70, 70, 70),
+        button_active: rgb(0, 90, 160),
+        border: rgb(200, 200, 200),
+        fill: rgb(0, 140, 255),
+        text: WHITE,
+        text_secondary: rgb(235, 235, 235),
+        text_dim: rgb(210, 210, 210),
+        text_faint: rgb(170, 170, 170),
+        selection: rgb(255, 255, 0),
+        selection_bg: rgb(90, 90, 0),
+        highlight: rgb(255, 255, 0),
+        hover: rgb(0, 255, 255),
+        accent: rgb(0, 200, 255),
+        info: rgb(0, 255, 0),
+        warning: rgb(255, 220, 0),
+        error: rgb(255, 80, 80),
+        grid_bg: rgb(0, 0, 0),
+        grid_line: rgb(70, 70, 70),
+        axis_x: rgb(200, 60, 60),
+        axis_z: rgb(60, 200, 60),
+        sector_edge: rgb(180, 180, 180),
+        sector_edge_active: WHITE,
+        tracker: TrackerColors {
+            bg: rgb(0, 0, 0),
+            header: rgb(20, 20, 20),
+            row_even: rgb(14, 14, 14),
+            row_odd: rgb(0, 0, 0),
+            row_beat: rgb(45, 45, 20),
+            row_highlight: rgb(0, 60, 110),
+            cursor: Color::from_rgba(0, 140, 255, 220),
+            cursor_edit: Color::from_rgba(230, 40, 40, 230),
+            playback_row: Color::from_rgba(120, 60, 0, 180),
+            text: WHITE,
+            text_dim: rgb(170, 170, 170),
+            note: rgb(255, 255, 80),
+            inst: rgb(80, 255, 80),
+            vol: rgb(80, 200, 255),
+            fx: rgb(255, 110, 220),
+        },
+    };
+
+    /// Default palette with red/green cues moved to blue/orange
+    pub const DEUTERANOPIA: UiTheme = UiTheme {
+        selection: rgb(90, 170, 255),
+        selection_bg: rgb(40, 65, 110),
+        highlight: rgb(255, 160, 30),
+        hover: rgb(210, 210, 255),
+        info: rgb(90, 170, 255),
+        warning: rgb(255, 210, 40),
+        error: rgb(240, 100, 200),
+        axis_x: rgb(110, 80, 20),
+        axis_z: rgb(30, 60, 120),
+        tracker: TrackerColors {
+            cursor_edit: Color::new(0.85, 0.5, 0.1, 0.85),
+            playback_row: Color::new(0.45, 0.3, 0.05, 0.6),
+            inst: Color::new(0.4, 0.65, 1.0, 1.0),
+            vol: Color::new(0.8, 0.8, 0.9, 1.0),
+            fx: Color::new(1.0, 0.65, 0.25, 1.0),
+            ..UiTheme::DEFAULT.tracker
+        },
+        ..UiTheme::DEFAULT
+    };
+}
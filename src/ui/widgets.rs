@@ -1,7 +1,7 @@
 //! Basic UI widgets
 
 use macroquad::prelude::*;
-use super::{Rect, UiContext, draw_icon_centered};
+use super::{Rect, UiContext, draw_icon_centered, icon_label};
 
 // =============================================================================
 // Scrollable List Widget
@@ -58,7 +58,7 @@ pub fn draw_scrollable_list(
     row_height: f32,
     colors: Option<&ListColors>,
 ) -> ListResult {
-    let default_colors = ListColors::default();
+    let default_colors = ListColors { row_selected: ctx.theme.accent, ..ListColors::default() };
     let colors = colors.unwrap_or(&default_colors);
 
     let mut result = ListResult {
@@ -268,25 +268,44 @@ impl Toolbar {
         self.cursor_x += text_dims.width + self.spacing;
     }
 
-    /// Add an icon button (square button with icon)
-    pub fn icon_button(&mut self, ctx: &mut UiContext, icon: char, icon_font: Option<&Font>, tooltip: &str) -> bool {
+    /// Reserve space for a custom widget of the given width, returns its rect
+    pub fn next_rect(&mut self, width: f32) -> Rect {
+        let rect = Rect::new(self.cursor_x.round(), (self.rect.y + 2.0).round(), width, (self.rect.h - 4.0).round());
+        self.cursor_x += width + self.spacing;
+        rect
+    }
+
+    /// Rect of the next icon button: square, or as wide as its text label
+    fn icon_button_rect(&mut self, ctx: &UiContext, icon: char, icon_font: Option<&Font>) -> Rect {
         let size = (self.rect.h - 4.0).round();
+        let width = if icon_font.is_none() || ctx.text_icons {
+            let label = measure_text(icon_label(icon), None, TEXT_ICON_SIZE as u16, 1.0);
+            size.max((label.width + 8.0).ceil())
+        } else {
+            size
+        };
         // Round positions to integer pixels for crisp rendering
-        let btn_rect = Rect::new(self.cursor_x.round(), (self.rect.y + 2.0).round(), size, size);
-        self.cursor_x += size + self.spacing;
+        let btn_rect = Rect::new(self.cursor_x.round(), (self.rect.y + 2.0).round(), width, size);
+        self.cursor_x += width + self.spacing;
+        btn_rect
+    }
+
+    /// Add an icon button (square button with icon)
+    pub fn icon_button(&mut self, ctx: &mut UiContext, icon: char, icon_font: Option<&Font>, tooltip: &str) -> bool {
+        let btn_rect = self.icon_button_rect(ctx, icon, icon_font);
         icon_button(ctx, btn_rect, icon, icon_font, tooltip)
     }
 
     /// Add an icon button with active state
     pub fn icon_button_active(&mut self, ctx: &mut UiContext, icon: char, icon_font: Option<&Font>, tooltip: &str, is_active: bool) -> bool {
-        let size = (self.rect.h - 4.0).round();
-        // Round positions to integer pixels for crisp rendering
-        let btn_rect = Rect::new(self.cursor_x.round(), (self.rect.y + 2.0).round(), size, size);
-        self.cursor_x += size + self.spacing;
+        let btn_rect = self.icon_button_rect(ctx, icon, icon_font);
         icon_button_active(ctx, btn_rect, icon, icon_font, tooltip, is_active)
     }
 }
 
+/// Font size of an icon button's text label (no icon font, or text icons forced)
+const TEXT_ICON_SIZE: f32 = 13.0;
+
 /// Accent color (cyan like MuseScore)
 pub const ACCENT_COLOR: Color = Color::new(0.0, 0.75, 0.9, 1.0);
 
@@ -318,8 +337,8 @@ fn draw_flat_icon_button(ctx: &mut UiContext, rect: Rect, icon: char, icon_font:
 
     // Draw background only when active or hovered
     if is_active {
-        // Cyan rounded rectangle for active state
-        draw_rounded_rect(rect.x, rect.y, rect.w, rect.h, corner_radius, ACCENT_COLOR);
+        // Accent (cyan) rounded rectangle for active state
+        draw_rounded_rect(rect.x, rect.y, rect.w, rect.h, corner_radius, ctx.theme.accent);
     } else if pressed {
         // Slight highlight when pressed
         draw_rounded_rect(rect.x, rect.y, rect.w, rect.h, corner_radius, Color::from_rgba(60, 60, 70, 255));
@@ -335,11 +354,12 @@ fn draw_flat_icon_button(ctx: &mut UiContext, rect: Rect, icon: char, icon_font:
     } else if hovered {
         Color::from_rgba(220, 220, 220, 255)
     } else {
-        Color::from_rgba(180, 180, 180, 255)
+        ctx.theme.text_secondary
     };
 
-    // Draw icon centered
-    let icon_size = (rect.h * 0.55).min(16.0);
+    // Draw icon centered (its text label when there's no font, or text is forced)
+    let icon_font = icon_font.filter(|_| !ctx.text_icons);
+    let icon_size = if icon_font.is_some() { (rect.h * 0.55).min(16.0) } else { TEXT_ICON_SIZE };
     draw_icon_centered(icon_font, icon, &rect, icon_size, icon_color);
 
     clicked
@@ -357,6 +377,26 @@ fn draw_rounded_rect(x: f32, y: f32, w: f32, h: f32, r: f32, color: Color) {
     draw_circle(x + w - r, y + h - r, r, color);
 }
 
+/// Draw a filled text button (dialog style, lighter when hovered), returns true if clicked
+pub fn draw_button(ctx: &mut UiContext, rect: Rect, text: &str, bg_color: Color) -> bool {
+    let hovered = ctx.mouse.inside(&rect);
+    let clicked = hovered && ctx.mouse.left_pressed;
+
+    let color = if hovered {
+        crate::rasterizer::Color::from(bg_color).scale_rgb(1.2).to_mq()
+    } else {
+        bg_color
+    };
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+
+    let dims = measure_text(text, None, 14, 1.0);
+    let tx = rect.x + (rect.w - dims.width) / 2.0;
+    let ty = rect.y + (rect.h + dims.height) / 2.0 - 2.0;
+    draw_text(text, tx, ty, 14.0, WHITE);
+
+    clicked
+}
+
 // =============================================================================
 // Knob / Potentiometer Widget
 // =============================================================================
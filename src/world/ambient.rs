@@ -0,0 +1,215 @@
+//! Ambient sound emitters
+//!
+//! An emitter is a point in a room that plays a sound (dripping water, machinery
+//! hum), usually on a loop. Its gain falls off with the listener's distance, reaching
+//! silence at its radius. In game mode only emitters within `AMBIENT_MAX_HOPS`
+//! portal hops of the player's room are considered at all; the audio side turns the
+//! gains from `ambient_gains` into voices and updates them every frame.
+
+use std::collections::VecDeque;
+use serde::{Serialize, Deserialize};
+use crate::rasterizer::Vec3;
+use super::{Level, Room, SECTOR_SIZE};
+
+/// Emitters further than this many portal hops from the listener's room are culled
+pub const AMBIENT_MAX_HOPS: usize = 2;
+
+/// Default falloff radius of a new emitter (world units)
+pub const AMBIENT_DEFAULT_RADIUS: f32 = SECTOR_SIZE * 4.0;
+
+/// Inverse-square falloff is at full gain within this fraction of the radius
+const INVERSE_SQUARE_NEAR: f32 = 0.25;
+
+/// How an emitter's gain falls off with distance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Falloff {
+    /// Straight line from full gain at the emitter to silence at the radius
+    #[default]
+    Linear,
+    /// Full gain close by, then 1/d² (shifted so it reaches silence at the radius)
+    InverseSquare,
+}
+
+impl Falloff {
+    pub const ALL: [Falloff; 2] = [Falloff::Linear, Falloff::InverseSquare];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Falloff::Linear => "Linear",
+            Falloff::InverseSquare => "Inverse Square",
+        }
+    }
+
+    /// Gain (0..=1) at `distance` from an emitter with the given radius
+    pub fn gain(self, distance: f32, radius: f32) -> f32 {
+        if radius <= 0.0 || distance >= radius {
+            return 0.0;
+        }
+        let gain = match self {
+            Falloff::Linear => 1.0 - distance / radius,
+            Falloff::InverseSquare => {
+                let near = radius * INVERSE_SQUARE_NEAR;
+                let at = |d: f32| (near / d.max(near)).powi(2);
+                let edge = at(radius);
+                (at(distance) - edge) / (1.0 - edge)
+            }
+        };
+        gain.clamp(0.0, 1.0)
+    }
+}
+
+fn default_ambient_volume() -> f32 {
+    1.0
+}
+
+fn default_ambient_radius() -> f32 {
+    AMBIENT_DEFAULT_RADIUS
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A sound placed in a room
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbientEmitter {
+    /// Position relative to the room origin
+    pub position: Vec3,
+    /// Sound asset name (a file in `assets/sounds`, without the extension)
+    #[serde(default, deserialize_with = "super::limits::string")]
+    pub sound: String,
+    /// Gain at the emitter (0..=1)
+    #[serde(default = "default_ambient_volume")]
+    pub volume: f32,
+    /// Distance (world units) at which the sound fades out completely
+    #[serde(default = "default_ambient_radius")]
+    pub radius: f32,
+    #[serde(default)]
+    pub falloff: Falloff,
+    /// Plays on a loop; otherwise once each time the listener comes into range
+    #[serde(default = "default_true")]
+    pub looping: bool,
+}
+
+impl AmbientEmitter {
+    pub fn new(position: Vec3, sound: impl Into<String>) -> Self {
+        Self {
+            position,
+            sound: sound.into(),
+            volume: default_ambient_volume(),
+            radius: AMBIENT_DEFAULT_RADIUS,
+            falloff: Falloff::default(),
+            looping: true,
+        }
+    }
+
+    /// Gain (0..=1) heard by a listener at `listener` (world space)
+    pub fn gain_at(&self, room: &Room, listener: Vec3) -> f32 {
+        let distance = (room.position + self.position - listener).len();
+        self.volume.clamp(0.0, 1.0) * self.falloff.gain(distance, self.radius)
+    }
+}
+
+/// An emitter the listener can hear, and how loud
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientGain {
+    pub room: usize,
+    /// Index into `Room::ambients`
+    pub index: usize,
+    pub gain: f32,
+}
+
+impl Level {
+    /// Portal hops from room `from` to every room (by index), None for rooms more
+    /// than `max_hops` away or not connected at all
+    pub fn portal_hops(&self, from: usize, max_hops: usize) -> Vec<Option<usize>> {
+        let mut hops = vec![None; self.rooms.len()];
+        if from >= self.rooms.len() {
+            return hops;
+        }
+        hops[from] = Some(0);
+        let mut queue = VecDeque::from([from]);
+        while let Some(idx) = queue.pop_front() {
+            let next = hops[idx].unwrap_or(0) + 1;
+            if next > max_hops {
+                continue;
+            }
+            for portal in &self.rooms[idx].portals {
+                let Some(target) = self.room_index(portal.target_room) else { continue };
+                if hops[target].is_none() {
+                    hops[target] = Some(next);
+                    queue.push_back(target);
+                }
+            }
+        }
+        hops
+    }
+}
+
+/// Emitters audible from `listener` (world space), with their gains. Rooms more than
+/// `max_hops` portal hops from `listener_room` are skipped; without a room (the
+/// listener is outside every room) only distance counts.
+pub fn ambient_gains(level: &Level, listener_room: Option<usize>, listener: Vec3, max_hops: usize) -> Vec<AmbientGain> {
+    let hops = listener_room.map(|room| level.portal_hops(room, max_hops));
+    let mut gains = Vec::new();
+    for (room_idx, room) in level.rooms.iter().enumerate() {
+        if hops.as_ref().is_some_and(|hops| hops[room_idx].is_none()) {
+            continue;
+        }
+        for (index, emitter) in room.ambients.iter().enumerate() {
+            let gain = emitter.gain_at(room, listener);
+            if gain > 0.0 && !emitter.sound.is_empty() {
+                gains.push(AmbientGain { room: room_idx, index, gain });
+            }
+        }
+    }
+    gains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::create_two_room_level;
+
+    #[test]
+    fn falloff_is_full_up_close_and_silent_at_the_radius() {
+        for falloff in Falloff::ALL {
+            assert_eq!(falloff.gain(0.0, 1000.0), 1.0);
+            assert_eq!(falloff.gain(1000.0, 1000.0), 0.0);
+            assert_eq!(falloff.gain(5000.0, 1000.0), 0.0);
+            assert_eq!(falloff.gain(0.0, 0.0), 0.0);
+            let mut last = 1.0;
+            for d in (0..1000).step_by(50) {
+                let gain = falloff.gain(d as f32, 1000.0);
+                assert!(gain <= last, "{:?} rises at {}", falloff, d);
+                last = gain;
+            }
+        }
+        assert_eq!(Falloff::Linear.gain(250.0, 1000.0), 0.75);
+        // Inverse square stays loud close by, then drops off faster
+        assert_eq!(Falloff::InverseSquare.gain(200.0, 1000.0), 1.0);
+        assert!(Falloff::InverseSquare.gain(500.0, 1000.0) < Falloff::Linear.gain(500.0, 1000.0));
+    }
+
+    #[test]
+    fn emitters_beyond_the_hop_limit_are_culled() {
+        let mut level = create_two_room_level();
+        assert_eq!(level.portal_hops(0, 2), [Some(0), Some(1)]);
+        assert_eq!(level.portal_hops(0, 0), [Some(0), None]);
+
+        let mut emitter = AmbientEmitter::new(Vec3::new(512.0, 512.0, 512.0), "drip");
+        emitter.radius = 100_000.0;
+        emitter.volume = 0.5;
+        level.rooms[1].ambients.push(emitter.clone());
+        emitter.sound.clear();
+        level.rooms[0].ambients.push(emitter);
+
+        let listener = level.rooms[0].position + Vec3::new(512.0, 512.0, 512.0);
+        let gains = ambient_gains(&level, Some(0), listener, 1);
+        assert_eq!(gains.len(), 1, "emitters without a sound are silent");
+        assert_eq!((gains[0].room, gains[0].index), (1, 0));
+        assert!(gains[0].gain > 0.0 && gains[0].gain <= 0.5);
+        assert!(ambient_gains(&level, Some(0), listener, 0).is_empty());
+        assert_eq!(ambient_gains(&level, None, listener, 0).len(), 1);
+    }
+}
@@ -0,0 +1,303 @@
+//! Ambient occlusion bake
+//!
+//! Cheap corner darkening written into face vertex colors, in the spirit of
+//! voxel AO: each floor/ceiling corner looks at the three sectors sharing that
+//! grid vertex, each wall corner at the floor/ceiling it meets and the wall
+//! next to it. No rays, no lightmaps.
+
+use crate::rasterizer::Color;
+use super::{Direction, Level, Room, Sector, VerticalFace};
+
+/// Height difference that counts as a step (half a click)
+const AO_STEP: f32 = 128.0;
+
+/// Occlusion level of a fully enclosed corner
+const AO_MAX: u8 = 3;
+
+/// Which face of a sector a baked corner belongs to
+#[derive(Clone, Copy)]
+enum AoTarget {
+    Floor,
+    Ceiling,
+    Wall(Direction, usize),
+}
+
+/// Corner index [NW, NE, SE, SW] -> (x side, z side), 0 = -X/-Z, 1 = +X/+Z
+fn corner_sides(corner: usize) -> (i32, i32) {
+    match corner {
+        0 => (0, 0),
+        1 => (1, 0),
+        2 => (1, 1),
+        _ => (0, 1),
+    }
+}
+
+fn corner_from_sides(x_side: i32, z_side: i32) -> usize {
+    match (x_side, z_side) {
+        (0, 0) => 0,
+        (1, 0) => 1,
+        (1, 1) => 2,
+        _ => 3,
+    }
+}
+
+/// Sector edge on the X or Z side of a corner
+fn x_edge(x_side: i32) -> Direction {
+    if x_side == 0 { Direction::West } else { Direction::East }
+}
+
+fn z_edge(z_side: i32) -> Direction {
+    if z_side == 0 { Direction::North } else { Direction::South }
+}
+
+/// Floor/ceiling corners at the left and right end of a wall (as seen from inside the sector)
+fn wall_end_corners(dir: Direction) -> (usize, usize) {
+    match dir {
+        Direction::North => (0, 1),
+        Direction::East => (1, 2),
+        Direction::South => (2, 3),
+        Direction::West => (3, 0),
+    }
+}
+
+/// Edge meeting a wall's left and right end within the same sector
+fn wall_end_edges(dir: Direction) -> (Direction, Direction) {
+    match dir {
+        Direction::North => (Direction::West, Direction::East),
+        Direction::East => (Direction::North, Direction::South),
+        Direction::South => (Direction::East, Direction::West),
+        Direction::West => (Direction::South, Direction::North),
+    }
+}
+
+/// Combine two sides and a diagonal, voxel AO style: two blocked sides fully enclose the corner
+fn combine(side1: bool, side2: bool, diagonal: bool) -> u8 {
+    if side1 && side2 {
+        AO_MAX
+    } else {
+        side1 as u8 + side2 as u8 + diagonal as u8
+    }
+}
+
+/// Darken a tint around the 128-neutral point (keeps existing tints, just scaled)
+fn darken(color: Color, factor: f32) -> Color {
+    let d = |c: u8| (c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    Color::with_alpha(d(color.r), d(color.g), d(color.b), color.a)
+}
+
+/// Does any wall on this edge reach down to `height` (floor) or up to it (ceiling)?
+fn edge_blocks(walls: &[VerticalFace], height: f32, floor: bool) -> bool {
+    walls.iter().any(|w| {
+        if floor {
+            w.heights[0].min(w.heights[1]) <= height + AO_STEP
+        } else {
+            w.heights[2].max(w.heights[3]) >= height - AO_STEP
+        }
+    })
+}
+
+impl Level {
+    /// Sector at grid offset from a room's sector. Cells outside the room's grid are
+    /// looked up in whichever other room covers that spot (rooms joined by portals).
+    fn ao_neighbor(&self, room_idx: usize, gx: usize, gz: usize, dx: i32, dz: i32) -> Option<&Sector> {
+        let room = &self.rooms[room_idx];
+        let nx = gx as i32 + dx;
+        let nz = gz as i32 + dz;
+        if nx >= 0 && nz >= 0 && (nx as usize) < room.width && (nz as usize) < room.depth {
+            return room.get_sector(nx as usize, nz as usize);
+        }
+
+        let world_x = room.position.x + (nx as f32 + 0.5) * super::SECTOR_SIZE;
+        let world_z = room.position.z + (nz as f32 + 0.5) * super::SECTOR_SIZE;
+        self.rooms.iter().enumerate()
+            .filter(|(i, _)| *i != room_idx)
+            .find_map(|(_, other): (usize, &Room)| {
+                let (ox, oz) = other.world_to_grid(world_x, world_z)?;
+                other.get_sector(ox, oz)
+            })
+    }
+
+    /// Occlusion (0..=AO_MAX) at one floor or ceiling corner
+    fn horizontal_corner_ao(&self, room_idx: usize, gx: usize, gz: usize, corner: usize, floor: bool) -> u8 {
+        let sector = match self.rooms[room_idx].get_sector(gx, gz) {
+            Some(s) => s,
+            None => return 0,
+        };
+        let face = if floor { &sector.floor } else { &sector.ceiling };
+        let height = match face {
+            Some(f) => f.heights[corner],
+            None => return 0,
+        };
+
+        let (xs, zs) = corner_sides(corner);
+        let dx = if xs == 0 { -1 } else { 1 };
+        let dz = if zs == 0 { -1 } else { 1 };
+
+        // A neighbor occludes when its face at this vertex steps toward the inside of the room
+        let stepped = |neighbor: Option<&Sector>, corner: usize| -> bool {
+            let face = neighbor.and_then(|n| if floor { n.floor.as_ref() } else { n.ceiling.as_ref() });
+            match face {
+                Some(f) if floor => f.heights[corner] >= height + AO_STEP,
+                Some(f) => f.heights[corner] <= height - AO_STEP,
+                None => false,
+            }
+        };
+
+        // Sides: own wall on the shared edge, the neighbor's wall facing back, or a step
+        let x_neighbor = self.ao_neighbor(room_idx, gx, gz, dx, 0);
+        let z_neighbor = self.ao_neighbor(room_idx, gx, gz, 0, dz);
+        let x_edge_dir = x_edge(xs);
+        let z_edge_dir = z_edge(zs);
+
+        let side_x = edge_blocks(sector.walls(x_edge_dir), height, floor)
+            || x_neighbor.is_some_and(|n| edge_blocks(n.walls(x_edge_dir.opposite()), height, floor))
+            || stepped(x_neighbor, corner_from_sides(1 - xs, zs));
+        let side_z = edge_blocks(sector.walls(z_edge_dir), height, floor)
+            || z_neighbor.is_some_and(|n| edge_blocks(n.walls(z_edge_dir.opposite()), height, floor))
+            || stepped(z_neighbor, corner_from_sides(xs, 1 - zs));
+
+        // Diagonal: a step, or a wall on either edge between it and the side neighbors
+        let diagonal_neighbor = self.ao_neighbor(room_idx, gx, gz, dx, dz);
+        let diagonal = stepped(diagonal_neighbor, corner_from_sides(1 - xs, 1 - zs))
+            || x_neighbor.is_some_and(|n| edge_blocks(n.walls(z_edge_dir), height, floor))
+            || z_neighbor.is_some_and(|n| edge_blocks(n.walls(x_edge_dir), height, floor))
+            || diagonal_neighbor.is_some_and(|n| {
+                edge_blocks(n.walls(x_edge_dir.opposite()), height, floor)
+                    || edge_blocks(n.walls(z_edge_dir.opposite()), height, floor)
+            });
+
+        combine(side_x, side_z, diagonal)
+    }
+
+    /// Occlusion (0..=AO_MAX) at one wall corner: where it meets the floor/ceiling and the adjacent wall
+    fn wall_corner_ao(sector: &Sector, dir: Direction, wall: &VerticalFace, corner: usize) -> u8 {
+        let bottom = corner < 2;
+        let left = corner == 0 || corner == 3;
+
+        let (left_corner, right_corner) = wall_end_corners(dir);
+        let face_corner = if left { left_corner } else { right_corner };
+        let height = wall.heights[corner];
+
+        let face = if bottom { &sector.floor } else { &sector.ceiling };
+        let meets_face = face.as_ref().is_some_and(|f| (f.heights[face_corner] - height).abs() < AO_STEP);
+
+        let (left_edge, right_edge) = wall_end_edges(dir);
+        let end_edge = if left { left_edge } else { right_edge };
+        let meets_wall = sector.walls(end_edge).iter().any(|w| {
+            w.heights[0].min(w.heights[1]) <= height + AO_STEP && w.heights[2].max(w.heights[3]) >= height - AO_STEP
+        });
+
+        combine(meets_face, meets_wall, false)
+    }
+
+    /// Bake ambient-occlusion-style corner darkening into face vertex colors.
+    /// `strength` is how much a fully enclosed corner is darkened (0.0 = none, 1.0 = black).
+    /// Existing tints are scaled, not replaced, so baking twice darkens twice.
+    /// Returns the number of corners that were darkened.
+    pub fn bake_corner_ao(&mut self, strength: f32) -> usize {
+        let strength = strength.clamp(0.0, 1.0);
+
+        // Gather first (reads neighbors), then apply
+        let mut darkened: Vec<(usize, usize, usize, AoTarget, usize, f32)> = Vec::new();
+        for (room_idx, room) in self.rooms.iter().enumerate() {
+            for (gx, gz, sector) in room.iter_sectors() {
+                for corner in 0..4 {
+                    for (floor, target) in [(true, AoTarget::Floor), (false, AoTarget::Ceiling)] {
+                        let ao = self.horizontal_corner_ao(room_idx, gx, gz, corner, floor);
+                        if ao > 0 {
+                            darkened.push((room_idx, gx, gz, target, corner, ao as f32 / AO_MAX as f32));
+                        }
+                    }
+                }
+                for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                    for (i, wall) in sector.walls(dir).iter().enumerate() {
+                        for corner in 0..4 {
+                            let ao = Self::wall_corner_ao(sector, dir, wall, corner);
+                            if ao > 0 {
+                                darkened.push((room_idx, gx, gz, AoTarget::Wall(dir, i), corner, ao as f32 / AO_MAX as f32));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for &(room_idx, gx, gz, target, corner, occlusion) in &darkened {
+            let factor = 1.0 - strength * occlusion;
+            let sector = match self.rooms[room_idx].get_sector_mut(gx, gz) {
+                Some(s) => s,
+                None => continue,
+            };
+            let colors = match target {
+                AoTarget::Floor => sector.floor.as_mut().map(|f| &mut f.colors),
+                AoTarget::Ceiling => sector.ceiling.as_mut().map(|c| &mut c.colors),
+                AoTarget::Wall(dir, i) => sector.walls_mut(dir).get_mut(i).map(|w| &mut w.colors),
+            };
+            if let Some(colors) = colors {
+                colors[corner] = darken(colors[corner], factor);
+            }
+        }
+
+        darkened.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::TextureRef;
+
+    /// 3x3 room with floor and ceiling everywhere and walls around the outside
+    fn walled_room_level() -> Level {
+        let mut room = Room::new(0, Vec3::ZERO, 3, 3);
+        for x in 0..3 {
+            for z in 0..3 {
+                room.set_floor(x, z, 0.0, TextureRef::none());
+                room.set_ceiling(x, z, 1024.0, TextureRef::none());
+            }
+        }
+        for i in 0..3 {
+            room.add_wall(i, 0, Direction::North, 0.0, 1024.0, TextureRef::none());
+            room.add_wall(i, 2, Direction::South, 0.0, 1024.0, TextureRef::none());
+            room.add_wall(0, i, Direction::West, 0.0, 1024.0, TextureRef::none());
+            room.add_wall(2, i, Direction::East, 0.0, 1024.0, TextureRef::none());
+        }
+        let mut level = Level::new();
+        level.add_room(room);
+        level
+    }
+
+    fn floor_color(level: &Level, x: usize, z: usize, corner: usize) -> Color {
+        level.rooms[0].get_sector(x, z).unwrap().floor.as_ref().unwrap().colors[corner]
+    }
+
+    #[test]
+    fn room_corners_darker_than_wall_edges_darker_than_center() {
+        let mut level = walled_room_level();
+        level.bake_corner_ao(0.6);
+
+        let corner = floor_color(&level, 0, 0, 0);    // NW corner of the room
+        let edge = floor_color(&level, 1, 0, 0);      // Along the north wall
+        let center = floor_color(&level, 1, 1, 0);    // Interior vertex
+
+        assert_eq!(center, Color::NEUTRAL);
+        assert!(corner.r < edge.r, "corner {:?} should be darker than edge {:?}", corner, edge);
+        assert!(edge.r < center.r);
+
+        // Wall bottom corner meeting another wall and the floor is darkened too
+        let wall = &level.rooms[0].get_sector(0, 0).unwrap().walls_north[0];
+        assert!(wall.colors[0].r < Color::NEUTRAL.r);
+    }
+
+    #[test]
+    fn composes_with_existing_tint() {
+        let mut level = walled_room_level();
+        let red = Color::new(200, 128, 64);
+        level.rooms[0].get_sector_mut(0, 0).unwrap().floor.as_mut().unwrap().colors[0] = red;
+        level.bake_corner_ao(0.5);
+
+        let baked = floor_color(&level, 0, 0, 0);
+        assert_eq!(baked, Color::new(100, 64, 32));
+    }
+}
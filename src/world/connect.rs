@@ -0,0 +1,192 @@
+//! Portal suggestions
+//!
+//! Finds rooms whose bounding boxes touch on a wall plane (one room's +X or +Z side
+//! against another's -X or -Z side) and proposes the portal pair for the shared
+//! rectangle. Pairs already joined by a portal on that plane aren't suggested again.
+
+use crate::rasterizer::Vec3;
+use super::{Aabb, Direction, Level, Room, VerticalFace, SECTOR_SIZE};
+
+/// Slack (world units) when matching room faces and existing portals
+const CONNECT_EPSILON: f32 = 1.0;
+
+/// A portal pair that would join two touching rooms
+#[derive(Debug, Clone)]
+pub struct PortalSuggestion {
+    /// Room on the low side (-X or -Z) of the shared plane (index)
+    pub room_a: usize,
+    /// Room on the high side (index)
+    pub room_b: usize,
+    /// Direction from `room_a` into `room_b` (East or South)
+    pub direction: Direction,
+    /// World-space corners of the shared rectangle: bottom two, then top two
+    pub corners: [Vec3; 4],
+    /// Solid walls cover the whole shared face, so a portal wouldn't see anything
+    pub blocked: bool,
+}
+
+/// Overlap of two ranges, if longer than the slack
+fn overlap(a: (f32, f32), b: (f32, f32)) -> Option<(f32, f32)> {
+    let (lo, hi) = (a.0.max(b.0), a.1.min(b.1));
+    (hi - lo > CONNECT_EPSILON).then_some((lo, hi))
+}
+
+/// Do solid walls in `walls` cover `lo..hi` without gaps?
+fn walls_cover(walls: &[VerticalFace], lo: f32, hi: f32) -> bool {
+    // Conservative spans: the lower of the top corners, the higher of the bottom ones
+    let mut spans: Vec<(f32, f32)> = walls.iter()
+        .filter(|w| w.solid)
+        .map(|w| (w.heights[0].max(w.heights[1]), w.heights[2].min(w.heights[3])))
+        .collect();
+    spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut covered = lo;
+    for (bottom, top) in spans {
+        if bottom > covered + CONNECT_EPSILON {
+            break;
+        }
+        covered = covered.max(top);
+    }
+    covered >= hi - CONNECT_EPSILON
+}
+
+/// Is `room`'s side of the shared face walled off at the sector containing `(wx, wz)`?
+fn walled(room: &Room, wx: f32, wz: f32, dir: Direction, lo: f32, hi: f32) -> bool {
+    room.world_to_grid(wx, wz)
+        .and_then(|(x, z)| room.get_sector(x, z))
+        .is_some_and(|sector| walls_cover(sector.walls(dir), lo, hi))
+}
+
+impl Level {
+    /// Portal pairs that would join rooms touching on a wall plane, in room order.
+    /// Suggestions whose shared face is entirely behind solid walls are `blocked`.
+    pub fn suggest_portals(&self) -> Vec<PortalSuggestion> {
+        let bounds: Vec<Aabb> = self.rooms.iter().map(Room::world_bounds).collect();
+        let mut suggestions = Vec::new();
+        for a in 0..self.rooms.len() {
+            for b in 0..self.rooms.len() {
+                if a == b {
+                    continue;
+                }
+                for direction in [Direction::East, Direction::South] {
+                    if let Some(s) = self.suggestion(a, b, &bounds, direction) {
+                        suggestions.push(s);
+                    }
+                }
+            }
+        }
+        suggestions.sort_by_key(|s| (s.room_a.min(s.room_b), s.room_a.max(s.room_b)));
+        suggestions
+    }
+
+    /// Suggestion for `a`'s `direction` side (East or South) touching `b`'s opposite side
+    fn suggestion(&self, a: usize, b: usize, bounds: &[Aabb], direction: Direction) -> Option<PortalSuggestion> {
+        let (ba, bb) = (&bounds[a], &bounds[b]);
+        if ba.is_empty() || bb.is_empty() {
+            return None;
+        }
+        let east = direction == Direction::East;
+        // Plane coordinate, and the horizontal extent along it
+        let (plane, other, a_span, b_span) = if east {
+            (ba.max.x, bb.min.x, (ba.min.z, ba.max.z), (bb.min.z, bb.max.z))
+        } else {
+            (ba.max.z, bb.min.z, (ba.min.x, ba.max.x), (bb.min.x, bb.max.x))
+        };
+        if (plane - other).abs() >= CONNECT_EPSILON {
+            return None;
+        }
+        let (h0, h1) = overlap(a_span, b_span)?;
+        let (y0, y1) = overlap((ba.min.y, ba.max.y), (bb.min.y, bb.max.y))?;
+
+        let point = |h: f32, y: f32| if east { Vec3::new(plane, y, h) } else { Vec3::new(h, y, plane) };
+        let corners = [point(h0, y0), point(h1, y0), point(h1, y1), point(h0, y1)];
+
+        let (room_a, room_b) = (&self.rooms[a], &self.rooms[b]);
+        if self.has_portal_on(room_a, room_b, &corners) {
+            return None;
+        }
+
+        // Blocked if every sector along the face is walled off on one side or the other
+        let half = SECTOR_SIZE * 0.5;
+        let mut h = h0 + half.min((h1 - h0) * 0.5);
+        let mut blocked = true;
+        while h < h1 && blocked {
+            let (a_pt, b_pt) = if east {
+                ((plane - half, h), (plane + half, h))
+            } else {
+                ((h, plane - half), (h, plane + half))
+            };
+            blocked = walled(room_a, a_pt.0, a_pt.1, direction, y0, y1)
+                || walled(room_b, b_pt.0, b_pt.1, direction.opposite(), y0, y1);
+            h += SECTOR_SIZE;
+        }
+
+        Some(PortalSuggestion { room_a: a, room_b: b, direction, corners, blocked })
+    }
+
+    /// Does `from` already have a portal into `to` lying on the plane of `corners`?
+    fn has_portal_on(&self, from: &Room, to: &Room, corners: &[Vec3; 4]) -> bool {
+        let center = (corners[0] + corners[2]) * 0.5;
+        let along_x = (corners[0].x - corners[1].x).abs() > CONNECT_EPSILON;
+        from.portals.iter()
+            .filter(|p| p.target_room == to.id && p.normal.y.abs() < 0.5)
+            .any(|p| {
+                let c = p.center() + from.position;
+                if along_x {
+                    (c.z - center.z).abs() < CONNECT_EPSILON
+                } else {
+                    (c.x - center.x).abs() < CONNECT_EPSILON
+                }
+            })
+    }
+
+    /// Create the portal pair for a suggestion (normals point into each room)
+    pub fn accept_portal_suggestion(&mut self, suggestion: &PortalSuggestion) {
+        let (a, b) = (suggestion.room_a, suggestion.room_b);
+        let (id_a, id_b) = (self.rooms[a].id, self.rooms[b].id);
+        let into_b = match suggestion.direction {
+            Direction::East => Vec3::new(1.0, 0.0, 0.0),
+            _ => Vec3::new(0.0, 0.0, 1.0),
+        };
+        let pos_a = self.rooms[a].position;
+        let pos_b = self.rooms[b].position;
+        self.rooms[a].add_portal(id_b, suggestion.corners.map(|v| v - pos_a), into_b * -1.0);
+        self.rooms[b].add_portal(id_a, suggestion.corners.map(|v| v - pos_b), into_b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_two_room_level, TextureRef};
+
+    #[test]
+    fn touching_rooms_get_one_suggestion() {
+        // The template is already connected
+        let mut level = create_two_room_level();
+        assert!(level.suggest_portals().is_empty());
+
+        // Without the portals, the shared edge comes back open
+        let expected = level.rooms[0].portals[0].normal;
+        for room in &mut level.rooms {
+            room.portals.clear();
+        }
+        let suggestions = level.suggest_portals();
+        assert_eq!(suggestions.len(), 1);
+        let s = &suggestions[0];
+        assert_eq!((s.room_a, s.room_b, s.direction, s.blocked), (0, 1, Direction::East, false));
+        level.accept_portal_suggestion(s);
+        assert_eq!(level.misaligned_portals(0), 0);
+        let normal = level.rooms[0].portals[0].normal;
+        assert_eq!((normal.x, normal.z), (expected.x, expected.z));
+        assert!(level.suggest_portals().is_empty());
+
+        // Walling off the shared edge marks it blocked
+        for room in &mut level.rooms {
+            room.portals.clear();
+        }
+        for z in 0..2 {
+            level.rooms[0].add_wall(1, z, Direction::East, 0.0, 1024.0, TextureRef::none());
+        }
+        assert!(level.suggest_portals()[0].blocked);
+    }
+}
@@ -0,0 +1,471 @@
+//! Structural level diff
+//!
+//! RON diffs of level files are unreadable (every nudged height is a changed float
+//! somewhere in thousands of lines), so this compares two levels room by room and
+//! sector by sector and says what changed: "Room 2 sector (3, 4): floor heights".
+//!
+//! Rooms are matched by `Room::id`, so deleting or reordering rooms doesn't show up
+//! as changes to every room after them. Sectors are matched by world position, so
+//! resizing or trimming a room's grid reports the resize rather than every sector.
+
+use std::fmt;
+use serde::Serialize;
+use super::{Direction, HorizontalFace, Level, Room, Sector, VerticalFace, SECTOR_SIZE};
+
+/// Part of a sector that changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectorPart {
+    Floor,
+    Ceiling,
+    /// The wall stack on one edge
+    Walls(Direction),
+}
+
+/// How a sector part changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartChange {
+    /// Faces added (a floor or ceiling is one face, walls count each)
+    Added(usize),
+    Removed(usize),
+    /// Corner heights, detail heights or (walls) span along the edge
+    Heights,
+    Texture,
+    /// Anything else: UVs, colors, blend mode, flags
+    Properties,
+}
+
+/// One difference between two levels
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelChange {
+    /// Level-wide setting (sun direction, game FOV, render settings, room order)
+    Setting(&'static str),
+    RoomAdded,
+    RoomRemoved,
+    RoomMoved,
+    /// Grid size (width, depth) before and after
+    RoomResized { before: (usize, usize), after: (usize, usize) },
+    /// Room setting (name, ambient, light, pickups, ambient sounds, doors)
+    RoomProperty(&'static str),
+    PortalAdded { target: usize },
+    PortalRemoved { target: usize },
+    /// Portals to the same rooms as before, with a different shape
+    PortalsReshaped,
+    SectorAdded,
+    SectorRemoved,
+    SectorChanged(Vec<(SectorPart, PartChange)>),
+}
+
+/// A difference and where it is
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelDiffEntry {
+    /// Room id (None for level-wide settings)
+    pub room: Option<usize>,
+    /// Sector grid position in the new room (in the old room for a removed sector
+    /// outside the new grid)
+    pub sector: Option<(usize, usize)>,
+    pub change: LevelChange,
+}
+
+/// Everything that changed from one level to another (`old.diff(&new)`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelDiff {
+    pub entries: Vec<LevelDiffEntry>,
+}
+
+impl LevelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Compare values with no PartialEq (vectors, colors) by their serialized form
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    ron::to_string(a).ok() == ron::to_string(b).ok()
+}
+
+fn horizontal_changes(part: SectorPart, old: &Option<HorizontalFace>, new: &Option<HorizontalFace>) -> Vec<(SectorPart, PartChange)> {
+    let (old, new) = match (old, new) {
+        (None, None) => return Vec::new(),
+        (None, Some(_)) => return vec![(part, PartChange::Added(1))],
+        (Some(_), None) => return vec![(part, PartChange::Removed(1))],
+        (Some(old), Some(new)) => (old, new),
+    };
+    let mut changes = Vec::new();
+    if old.heights != new.heights || old.subdivision != new.subdivision || old.detail != new.detail {
+        changes.push((part, PartChange::Heights));
+    }
+    if old.texture != new.texture {
+        changes.push((part, PartChange::Texture));
+    }
+    // The rest, with the parts reported above made equal
+    let mut rest = new.clone();
+    rest.heights = old.heights;
+    rest.subdivision = old.subdivision;
+    rest.detail = old.detail.clone();
+    rest.texture = old.texture.clone();
+    if !same(old, &rest) {
+        changes.push((part, PartChange::Properties));
+    }
+    changes
+}
+
+fn wall_changes(dir: Direction, old: &[VerticalFace], new: &[VerticalFace]) -> Vec<(SectorPart, PartChange)> {
+    let part = SectorPart::Walls(dir);
+    let mut changes = Vec::new();
+    if new.len() > old.len() {
+        changes.push((part, PartChange::Added(new.len() - old.len())));
+    } else if old.len() > new.len() {
+        changes.push((part, PartChange::Removed(old.len() - new.len())));
+    }
+    let pairs = || old.iter().zip(new);
+    if pairs().any(|(a, b)| a.heights != b.heights || a.x_start != b.x_start || a.x_end != b.x_end) {
+        changes.push((part, PartChange::Heights));
+    }
+    if pairs().any(|(a, b)| a.texture != b.texture) {
+        changes.push((part, PartChange::Texture));
+    }
+    let properties = pairs().any(|(a, b)| {
+        let mut rest = b.clone();
+        rest.heights = a.heights;
+        rest.x_start = a.x_start;
+        rest.x_end = a.x_end;
+        rest.texture = a.texture.clone();
+        !same(a, &rest)
+    });
+    if properties {
+        changes.push((part, PartChange::Properties));
+    }
+    changes
+}
+
+/// What changed between two versions of a sector (empty if nothing did)
+fn sector_changes(old: &Sector, new: &Sector) -> Vec<(SectorPart, PartChange)> {
+    let mut changes = horizontal_changes(SectorPart::Floor, &old.floor, &new.floor);
+    changes.extend(horizontal_changes(SectorPart::Ceiling, &old.ceiling, &new.ceiling));
+    for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+        changes.extend(wall_changes(dir, old.walls(dir), new.walls(dir)));
+    }
+    changes
+}
+
+/// Portal targets in `a` that aren't matched by one in `b` (counting duplicates)
+fn unmatched_targets(a: &Room, b: &Room) -> Vec<usize> {
+    let mut others: Vec<usize> = b.portals.iter().map(|p| p.target_room).collect();
+    a.portals.iter()
+        .filter_map(|p| match others.iter().position(|&t| t == p.target_room) {
+            Some(i) => {
+                others.swap_remove(i);
+                None
+            }
+            None => Some(p.target_room),
+        })
+        .collect()
+}
+
+fn diff_rooms(old: &Room, new: &Room, entries: &mut Vec<LevelDiffEntry>) {
+    let mut push = |sector: Option<(usize, usize)>, change: LevelChange| {
+        entries.push(LevelDiffEntry { room: Some(new.id), sector, change });
+    };
+
+    let (op, np) = (old.position, new.position);
+    if (op.x, op.y, op.z) != (np.x, np.y, np.z) {
+        push(None, LevelChange::RoomMoved);
+    }
+    if (old.width, old.depth) != (new.width, new.depth) {
+        push(None, LevelChange::RoomResized { before: (old.width, old.depth), after: (new.width, new.depth) });
+    }
+    if old.name != new.name {
+        push(None, LevelChange::RoomProperty("name"));
+    }
+    if old.ambient != new.ambient {
+        push(None, LevelChange::RoomProperty("ambient"));
+    }
+    if !same(&old.light_dir, &new.light_dir) {
+        push(None, LevelChange::RoomProperty("light direction"));
+    }
+    if !same(&old.pickups, &new.pickups) {
+        push(None, LevelChange::RoomProperty("pickups"));
+    }
+    if !same(&old.ambients, &new.ambients) {
+        push(None, LevelChange::RoomProperty("ambient sounds"));
+    }
+    if !same(&old.lights, &new.lights) {
+        push(None, LevelChange::RoomProperty("lights"));
+    }
+    if !same(&old.doors, &new.doors) {
+        push(None, LevelChange::RoomProperty("doors"));
+    }
+
+    let added = unmatched_targets(new, old);
+    let removed = unmatched_targets(old, new);
+    for &target in &added {
+        push(None, LevelChange::PortalAdded { target });
+    }
+    for &target in &removed {
+        push(None, LevelChange::PortalRemoved { target });
+    }
+    if added.is_empty() && removed.is_empty() && !same(&old.portals, &new.portals) {
+        push(None, LevelChange::PortalsReshaped);
+    }
+
+    // Line the grids up by world position when the room moved by whole sectors (a
+    // trim or a resize toward -X/-Z); otherwise compare cell by cell
+    let offset = |a: f32, b: f32| {
+        let cells = (b - a) / SECTOR_SIZE;
+        (cells == cells.round()).then_some(cells as i64)
+    };
+    let (dx, dz) = match (offset(op.x, np.x), offset(op.z, np.z)) {
+        (Some(dx), Some(dz)) => (dx, dz),
+        _ => (0, 0),
+    };
+    // Old grid cell under a new one
+    let old_cell = |x: usize, z: usize| -> Option<&Sector> {
+        let (ox, oz) = (x as i64 + dx, z as i64 + dz);
+        if ox < 0 || oz < 0 {
+            return None;
+        }
+        old.get_sector(ox as usize, oz as usize)
+    };
+
+    for x in 0..new.width {
+        for z in 0..new.depth {
+            match (old_cell(x, z), new.get_sector(x, z)) {
+                (None, None) => {}
+                (None, Some(_)) => push(Some((x, z)), LevelChange::SectorAdded),
+                (Some(_), None) => push(Some((x, z)), LevelChange::SectorRemoved),
+                (Some(a), Some(b)) => {
+                    let changes = sector_changes(a, b);
+                    if !changes.is_empty() {
+                        push(Some((x, z)), LevelChange::SectorChanged(changes));
+                    }
+                }
+            }
+        }
+    }
+
+    // Old sectors the new grid no longer covers
+    for ox in 0..old.width {
+        for oz in 0..old.depth {
+            let (x, z) = (ox as i64 - dx, oz as i64 - dz);
+            let covered = x >= 0 && z >= 0 && (x as usize) < new.width && (z as usize) < new.depth;
+            if !covered && old.get_sector(ox, oz).is_some() {
+                push(Some((ox, oz)), LevelChange::SectorRemoved);
+            }
+        }
+    }
+}
+
+impl Level {
+    /// What changed going from this level to `other`
+    pub fn diff(&self, other: &Level) -> LevelDiff {
+        let mut entries = Vec::new();
+        let mut setting = |changed: bool, name: &'static str| {
+            if changed {
+                entries.push(LevelDiffEntry { room: None, sector: None, change: LevelChange::Setting(name) });
+            }
+        };
+        setting(!same(&self.light_dir, &other.light_dir), "sun direction");
+        setting(self.game_fov != other.game_fov, "game FOV");
+        setting(self.render_settings != other.render_settings, "render settings");
+
+        // Rooms in both levels, in each level's order
+        let kept = |a: &Level, b: &Level| -> Vec<usize> {
+            a.rooms.iter().map(|r| r.id).filter(|&id| b.room_index(id).is_some()).collect()
+        };
+        setting(kept(self, other) != kept(other, self), "room order");
+
+        for old in &self.rooms {
+            match other.room_index(old.id) {
+                Some(i) => diff_rooms(old, &other.rooms[i], &mut entries),
+                None => entries.push(LevelDiffEntry { room: Some(old.id), sector: None, change: LevelChange::RoomRemoved }),
+            }
+        }
+        for new in other.rooms.iter().filter(|r| self.room_index(r.id).is_none()) {
+            entries.push(LevelDiffEntry { room: Some(new.id), sector: None, change: LevelChange::RoomAdded });
+        }
+        LevelDiff { entries }
+    }
+}
+
+impl fmt::Display for SectorPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SectorPart::Floor => write!(f, "floor"),
+            SectorPart::Ceiling => write!(f, "ceiling"),
+            SectorPart::Walls(dir) => write!(f, "{} walls", format!("{:?}", dir).to_lowercase()),
+        }
+    }
+}
+
+impl fmt::Display for LevelChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelChange::Setting(name) => write!(f, "{} changed", name),
+            LevelChange::RoomAdded => write!(f, "added"),
+            LevelChange::RoomRemoved => write!(f, "removed"),
+            LevelChange::RoomMoved => write!(f, "moved"),
+            LevelChange::RoomResized { before, after } => {
+                write!(f, "resized {}x{} -> {}x{}", before.0, before.1, after.0, after.1)
+            }
+            LevelChange::RoomProperty(name) => write!(f, "{} changed", name),
+            LevelChange::PortalAdded { target } => write!(f, "portal to room {} added", target),
+            LevelChange::PortalRemoved { target } => write!(f, "portal to room {} removed", target),
+            LevelChange::PortalsReshaped => write!(f, "portals reshaped"),
+            LevelChange::SectorAdded => write!(f, "added"),
+            LevelChange::SectorRemoved => write!(f, "removed"),
+            LevelChange::SectorChanged(changes) => {
+                for (i, (part, change)) in changes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match change {
+                        PartChange::Added(n) => write!(f, "{} +{}", part, n)?,
+                        PartChange::Removed(n) => write!(f, "{} -{}", part, n)?,
+                        PartChange::Heights => write!(f, "{} heights", part)?,
+                        PartChange::Texture => write!(f, "{} texture", part)?,
+                        PartChange::Properties => write!(f, "{} properties", part)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for LevelDiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.room, self.sector) {
+            (Some(room), Some((x, z))) => write!(f, "Room {} sector ({}, {}): {}", room, x, z, self.change),
+            (Some(room), None) => write!(f, "Room {}: {}", room, self.change),
+            _ => write!(f, "Level: {}", self.change),
+        }
+    }
+}
+
+impl fmt::Display for LevelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::TextureRef;
+
+    fn level() -> Level {
+        let mut level = Level::new();
+        let mut room = Room::new(0, Vec3::ZERO, 3, 3);
+        for x in 0..3 {
+            for z in 0..3 {
+                room.set_floor(x, z, 0.0, TextureRef::new("pack", "FLOOR"));
+            }
+        }
+        room.ensure_sector(1, 1).walls_north.push(VerticalFace::new(0.0, 1024.0, TextureRef::new("pack", "WALL")));
+        level.rooms.push(room);
+        level
+    }
+
+    #[test]
+    fn texture_only_change() {
+        let old = level();
+        let mut new = old.clone();
+        new.rooms[0].get_sector_mut(1, 1).unwrap().walls_north[0].texture = TextureRef::new("pack", "BRICK");
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.entries, vec![LevelDiffEntry {
+            room: Some(0),
+            sector: Some((1, 1)),
+            change: LevelChange::SectorChanged(vec![(SectorPart::Walls(Direction::North), PartChange::Texture)]),
+        }]);
+        assert_eq!(diff.to_string(), "Room 0 sector (1, 1): north walls texture\n");
+        assert!(old.diff(&old.clone()).is_empty());
+    }
+
+    #[test]
+    fn height_only_change() {
+        let old = level();
+        let mut new = old.clone();
+        new.rooms[0].get_sector_mut(2, 0).unwrap().floor.as_mut().unwrap().heights[1] = 256.0;
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.entries[0].sector, Some((2, 0)));
+        assert_eq!(diff.entries[0].change, LevelChange::SectorChanged(vec![(SectorPart::Floor, PartChange::Heights)]));
+    }
+
+    #[test]
+    fn grid_resize_lines_up_sectors() {
+        let old = level();
+
+        // Grow toward -X: the room moves one sector west and gains an empty column
+        let mut new = old.clone();
+        let room = &mut new.rooms[0];
+        room.position.x -= SECTOR_SIZE;
+        room.sectors.insert(0, vec![None; 3]);
+        room.width = 4;
+        room.set_floor(0, 2, 0.0, TextureRef::new("pack", "FLOOR"));
+
+        let changes: Vec<_> = old.diff(&new).entries.into_iter().map(|e| (e.sector, e.change)).collect();
+        assert_eq!(changes, vec![
+            (None, LevelChange::RoomMoved),
+            (None, LevelChange::RoomResized { before: (3, 3), after: (4, 3) }),
+            (Some((0, 2)), LevelChange::SectorAdded),
+        ]);
+
+        // Shrinking from the east drops the last column
+        let mut new = old.clone();
+        new.rooms[0].sectors.pop();
+        new.rooms[0].width = 2;
+        let removed = old.diff(&new).entries.iter()
+            .filter(|e| e.change == LevelChange::SectorRemoved)
+            .map(|e| e.sector)
+            .collect::<Vec<_>>();
+        assert_eq!(removed, vec![Some((2, 0)), Some((2, 1)), Some((2, 2))]);
+    }
+
+    #[test]
+    fn rooms_are_matched_by_id() {
+        let mut old = level();
+        for id in 1..4 {
+            let mut room = old.rooms[0].clone();
+            room.id = id;
+            room.position.x = id as f32 * 3.0 * SECTOR_SIZE;
+            room.add_portal(id - 1, [Vec3::ZERO; 4], Vec3::new(-1.0, 0.0, 0.0));
+            old.rooms.push(room);
+        }
+
+        // Deleting a middle room is one removal, by id; the rooms after it didn't change
+        let mut new = old.clone();
+        new.rooms.remove(1);
+        let diff = old.diff(&new);
+        assert_eq!(diff.entries, vec![LevelDiffEntry { room: Some(1), sector: None, change: LevelChange::RoomRemoved }]);
+        assert_eq!(diff.to_string(), "Room 1: removed\n");
+
+        // Reordering is a level change only; a new room is added under its id
+        let mut new = old.clone();
+        new.rooms.swap(1, 2);
+        let mut room = new.rooms[0].clone();
+        room.id = 7;
+        new.rooms.push(room);
+        new.rooms[3].portals[0].target_room = 0;
+        assert_eq!(old.diff(&new).to_string(), [
+            "Level: room order changed",
+            "Room 3: portal to room 0 added",
+            "Room 3: portal to room 2 removed",
+            "Room 7: added",
+            "",
+        ].join("\n"));
+    }
+}
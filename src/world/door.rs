@@ -0,0 +1,242 @@
+//! Doors
+//!
+//! A door is a wall on a sector edge that slides vertically between a closed and
+//! an open set of corner heights. `DoorState` holds the runtime side (how far each
+//! door is open) and writes the animated heights into the play session's copy of the
+//! level, so rendering and collision both see the door where it actually is.
+//!
+//! A saved door names its wall by its place in the edge's stack. Once loaded it
+//! follows the wall by id instead, so walls coming and going around it don't move it
+//! to another wall; `Room::sync_door_walls` writes the place back for saving.
+
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::rasterizer::Vec3;
+use super::{Direction, Level, Room, VerticalFace, WallId, SECTOR_SIZE};
+
+/// Horizontal (XZ) distance from a door's center within which the player can use it
+pub const DOOR_REACH: f32 = SECTOR_SIZE * 0.75;
+
+fn default_door_duration() -> f32 {
+    1.0
+}
+
+/// A wall that opens and closes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Door {
+    /// Sector (grid coordinates) holding the wall
+    pub x: usize,
+    pub z: usize,
+    /// Edge of the sector the wall is on
+    pub edge: Direction,
+    /// Index into that edge's wall stack (as saved; see `wall_id`)
+    pub wall: usize,
+    /// The wall while the level is loaded, resolved from `wall` on load - not serialized
+    #[serde(skip)]
+    pub wall_id: WallId,
+    /// Corner heights when closed [BL, BR, TR, TL]
+    pub closed: [f32; 4],
+    /// Corner heights when fully open [BL, BR, TR, TL]
+    pub open: [f32; 4],
+    /// Seconds to go from closed to open (and back)
+    #[serde(default = "default_door_duration")]
+    pub duration: f32,
+}
+
+impl Door {
+    /// Door for an existing wall: closed where the wall is now, open sunk to its
+    /// bottom edge (floor level)
+    pub fn from_wall(x: usize, z: usize, edge: Direction, wall: usize, face: &VerticalFace) -> Self {
+        let [bl, br, _, _] = face.heights;
+        Self {
+            x,
+            z,
+            edge,
+            wall,
+            wall_id: face.id,
+            closed: face.heights,
+            open: [bl, br, br, bl],
+            duration: default_door_duration(),
+        }
+    }
+
+    /// Does this door use the given wall?
+    pub fn is_wall(&self, x: usize, z: usize, edge: Direction, wall: WallId) -> bool {
+        self.x == x && self.z == z && self.edge == edge && self.wall_id == wall
+    }
+
+    /// Corner heights when `amount` open (0 = closed, 1 = open)
+    pub fn heights_at(&self, amount: f32) -> [f32; 4] {
+        std::array::from_fn(|i| self.closed[i] + (self.open[i] - self.closed[i]) * amount)
+    }
+
+    /// Center of the door's edge in world space (at the closed bottom height)
+    pub fn center(&self, room: &Room) -> Vec3 {
+        let (dx, dz) = match self.edge {
+            Direction::North => (0.5, 0.0),
+            Direction::East => (1.0, 0.5),
+            Direction::South => (0.5, 1.0),
+            Direction::West => (0.0, 0.5),
+        };
+        Vec3::new(
+            room.position.x + (self.x as f32 + dx) * SECTOR_SIZE,
+            (self.closed[0] + self.closed[1]) * 0.5,
+            room.position.z + (self.z as f32 + dz) * SECTOR_SIZE,
+        )
+    }
+}
+
+impl Room {
+    /// Door using the given wall, if any
+    pub fn door_at(&self, x: usize, z: usize, edge: Direction, wall: WallId) -> Option<usize> {
+        self.doors.iter().position(|d| d.is_wall(x, z, edge, wall))
+    }
+
+    /// Index of a door's wall in its edge's stack (None if the wall is gone)
+    pub fn door_wall_index(&self, door: &Door) -> Option<usize> {
+        self.get_sector(door.x, door.z)?.wall_index(door.edge, door.wall_id)
+    }
+
+    /// Point doors at their wall's id from its saved place in the stack (call once
+    /// wall ids are assigned)
+    pub fn resolve_door_walls(&mut self) {
+        for i in 0..self.doors.len() {
+            let door = &self.doors[i];
+            let id = self.get_sector(door.x, door.z)
+                .and_then(|s| s.walls(door.edge).get(door.wall))
+                .map(|w| w.id);
+            self.doors[i].wall_id = id.unwrap_or_default();
+        }
+    }
+
+    /// Drop doors whose wall is gone (removed, or its sector cleared) and write the
+    /// others' current place in the stack back into `wall`
+    pub fn sync_door_walls(&mut self) {
+        let doors = std::mem::take(&mut self.doors);
+        self.doors = doors.into_iter()
+            .filter_map(|mut door| {
+                door.wall = self.door_wall_index(&door)?;
+                Some(door)
+            })
+            .collect();
+    }
+
+    /// Do all doors' saved places match where their walls are?
+    pub fn door_walls_synced(&self) -> bool {
+        self.doors.iter().all(|d| self.door_wall_index(d) == Some(d.wall))
+    }
+
+    /// Nearest door within `DOOR_REACH` of a world position (XZ)
+    pub fn door_near(&self, pos: Vec3) -> Option<usize> {
+        self.doors.iter().enumerate()
+            .map(|(i, door)| {
+                let c = door.center(self);
+                (i, (c.x - pos.x).hypot(c.z - pos.z))
+            })
+            .filter(|&(_, dist)| dist <= DOOR_REACH)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+}
+
+/// How far one door is open and which way it's moving
+#[derive(Debug, Clone, Copy)]
+struct DoorMotion {
+    amount: f32,
+    opening: bool,
+}
+
+/// Runtime door state for one play session
+#[derive(Debug, Clone, Default)]
+pub struct DoorState {
+    /// (room index, door index) -> motion. Doors not in here are closed.
+    doors: HashMap<(usize, usize), DoorMotion>,
+}
+
+impl DoorState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How far a door is open (0 = closed, 1 = open)
+    pub fn openness(&self, room: usize, door: usize) -> f32 {
+        self.doors.get(&(room, door)).map_or(0.0, |m| m.amount)
+    }
+
+    /// Start opening a closed/closing door, or closing an open/opening one.
+    /// Returns true if the door is now opening.
+    pub fn toggle(&mut self, room: usize, door: usize) -> bool {
+        let motion = self.doors.entry((room, door)).or_insert(DoorMotion { amount: 0.0, opening: false });
+        motion.opening = !motion.opening;
+        motion.opening
+    }
+
+    /// Advance moving doors and write their wall heights into `level`
+    pub fn update(&mut self, level: &mut Level, dt: f32) {
+        for (&(room_idx, door_idx), motion) in &mut self.doors {
+            let Some(room) = level.rooms.get_mut(room_idx) else { continue };
+            let Some(door) = room.doors.get(door_idx) else { continue };
+
+            let target = if motion.opening { 1.0 } else { 0.0 };
+            if motion.amount == target {
+                continue;
+            }
+            let step = dt / door.duration.max(0.01);
+            motion.amount = if motion.opening {
+                (motion.amount + step).min(1.0)
+            } else {
+                (motion.amount - step).max(0.0)
+            };
+
+            let heights = door.heights_at(motion.amount);
+            let (x, z, edge, wall) = (door.x, door.z, door.edge, door.wall_id);
+            if let Some(face) = room.get_sector_mut(x, z).and_then(|s| s.wall_by_id_mut(edge, wall)) {
+                face.heights = heights;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::TextureRef;
+
+    /// 2x1 room with a door on the east edge of sector (0, 0)
+    fn level_with_door() -> Level {
+        let mut room = Room::new(0, Vec3::ZERO, 2, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.set_floor(1, 0, 0.0, TextureRef::none());
+        room.push_wall(0, 0, Direction::East, VerticalFace::new(0.0, 1024.0, TextureRef::none()));
+        let door = Door::from_wall(0, 0, Direction::East, 0, &room.get_sector(0, 0).unwrap().walls_east[0]);
+        room.doors.push(door);
+        let mut level = Level::new();
+        level.add_room(room);
+        level
+    }
+
+    fn door_wall(level: &Level) -> &VerticalFace {
+        &level.rooms[0].get_sector(0, 0).unwrap().walls_east[0]
+    }
+
+    #[test]
+    fn door_slides_open_and_closed() {
+        let mut level = level_with_door();
+        let mut doors = DoorState::new();
+
+        assert_eq!(level.rooms[0].door_near(Vec3::new(900.0, 0.0, 512.0)), Some(0));
+        assert_eq!(level.rooms[0].door_near(Vec3::new(-2000.0, 0.0, 512.0)), None);
+
+        assert!(doors.toggle(0, 0));
+        doors.update(&mut level, 0.5);
+        assert_eq!(doors.openness(0, 0), 0.5);
+        assert_eq!(door_wall(&level).heights, [0.0, 0.0, 512.0, 512.0]);
+
+        doors.update(&mut level, 1.0);
+        assert_eq!(door_wall(&level).heights, [0.0; 4]);
+
+        assert!(!doors.toggle(0, 0));
+        doors.update(&mut level, 0.25);
+        assert_eq!(door_wall(&level).y_top(), 256.0);
+    }
+}
@@ -0,0 +1,241 @@
+//! Level-wide face search
+//!
+//! `Level::find_faces` answers questions like "where did I use the lava texture"
+//! or "which floors are below -2048": every face matching all the criteria of a
+//! `FaceQuery`, in room, sector and face order. `find_faces_in` scans a range of
+//! rooms, so a caller can spread a big level over a few frames.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use crate::rasterizer::{BlendMode, Vec2};
+use super::{Direction, HitFace, Level, Room, TextureRef, SECTOR_SIZE};
+
+/// What to look for. Faces must match every criterion that is set; a query with
+/// none set finds nothing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaceQuery {
+    /// Faces using this texture
+    pub texture: Option<TextureRef>,
+    /// Floors and ceilings that can't be walked on (or bumped into)
+    pub not_walkable: bool,
+    /// Faces drawn with a blend mode other than Opaque
+    pub blended: bool,
+    /// Floors with a corner between these world heights (min, max)
+    pub floor_height: Option<(f32, f32)>,
+    /// Faces of sectors holding something the player sets off: a pickup or a door
+    pub triggers: bool,
+    /// Faces with their own UVs instead of the default mapping
+    pub custom_uv: bool,
+}
+
+impl FaceQuery {
+    /// No criteria set?
+    pub fn is_empty(&self) -> bool {
+        *self == FaceQuery::default()
+    }
+}
+
+/// A face found by a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaceLocation {
+    /// Room index and sector grid coordinates
+    pub room: usize,
+    pub x: usize,
+    pub z: usize,
+    pub face: HitFace,
+}
+
+/// The face properties a query looks at
+struct FaceInfo<'a> {
+    face: HitFace,
+    texture: &'a TextureRef,
+    uv: &'a Option<[Vec2; 4]>,
+    blend_mode: BlendMode,
+    /// None for walls
+    walkable: Option<bool>,
+    heights: [f32; 4],
+}
+
+impl FaceQuery {
+    fn matches(&self, info: &FaceInfo, trigger: bool) -> bool {
+        // Face heights are already world Y (rendering and collision use them as is)
+        let floor_height = |(min, max): (f32, f32)| {
+            info.face == HitFace::Floor && info.heights.iter().any(|h| (min..=max).contains(h))
+        };
+        self.texture.as_ref().is_none_or(|t| t == info.texture)
+            && (!self.not_walkable || info.walkable == Some(false))
+            && (!self.blended || info.blend_mode != BlendMode::Opaque)
+            && self.floor_height.is_none_or(floor_height)
+            && (!self.triggers || trigger)
+            && (!self.custom_uv || info.uv.is_some())
+    }
+}
+
+/// Sectors (x, z) of a room with a pickup in them or a door on one of their edges
+fn trigger_sectors(room: &Room) -> HashSet<(usize, usize)> {
+    let pickups = room.pickups.iter()
+        .filter(|p| p.position.x >= 0.0 && p.position.z >= 0.0)
+        .map(|p| ((p.position.x / SECTOR_SIZE) as usize, (p.position.z / SECTOR_SIZE) as usize));
+    let doors = room.doors.iter().map(|d| (d.x, d.z));
+    pickups.chain(doors).collect()
+}
+
+impl Level {
+    /// Every face matching `query`
+    pub fn find_faces(&self, query: &FaceQuery) -> Vec<FaceLocation> {
+        self.find_faces_in(0..self.rooms.len(), query)
+    }
+
+    /// Faces matching `query` in a range of rooms (by index)
+    pub fn find_faces_in(&self, rooms: Range<usize>, query: &FaceQuery) -> Vec<FaceLocation> {
+        let mut found = Vec::new();
+        if query.is_empty() {
+            return found;
+        }
+        for room_idx in rooms {
+            let Some(room) = self.rooms.get(room_idx) else { break };
+            let triggers = if query.triggers { trigger_sectors(room) } else { HashSet::new() };
+            for (x, z, sector) in room.iter_sectors() {
+                let trigger = triggers.contains(&(x, z));
+                if query.triggers && !trigger {
+                    continue;
+                }
+                let horizontal = [(HitFace::Floor, &sector.floor), (HitFace::Ceiling, &sector.ceiling)]
+                    .into_iter()
+                    .filter_map(|(face, f)| f.as_ref().map(|f| FaceInfo {
+                        face,
+                        texture: &f.texture,
+                        uv: &f.uv,
+                        blend_mode: f.blend_mode,
+                        walkable: Some(f.walkable),
+                        heights: f.heights,
+                    }));
+                let walls = [Direction::North, Direction::East, Direction::South, Direction::West]
+                    .into_iter()
+                    .flat_map(|dir| sector.walls(dir).iter().enumerate().map(move |(i, w)| FaceInfo {
+                        face: HitFace::Wall(dir, i),
+                        texture: &w.texture,
+                        uv: &w.uv,
+                        blend_mode: w.blend_mode,
+                        walkable: None,
+                        heights: w.heights,
+                    }));
+                found.extend(horizontal.chain(walls)
+                    .filter(|info| query.matches(info, trigger))
+                    .map(|info| FaceLocation { room: room_idx, x, z, face: info.face }));
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::{Door, Pickup, VerticalFace};
+
+    /// One room, 3x1: a FLOOR floor at 0, a LAVA floor at -2048 and a floor at 512
+    /// under a ceiling, plus a north wall on the first sector
+    fn level() -> Level {
+        let mut level = Level::new();
+        let mut room = Room::new(0, Vec3::ZERO, 3, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::new("pack", "FLOOR"));
+        room.set_floor(1, 0, -2048.0, TextureRef::new("pack", "LAVA"));
+        room.set_floor(2, 0, 512.0, TextureRef::new("pack", "FLOOR"));
+        room.set_ceiling(2, 0, 2048.0, TextureRef::new("pack", "LAVA"));
+        room.ensure_sector(0, 0).walls_north.push(VerticalFace::new(0.0, 1024.0, TextureRef::new("pack", "WALL")));
+        level.rooms.push(room);
+        level
+    }
+
+    fn at(x: usize, face: HitFace) -> FaceLocation {
+        FaceLocation { room: 0, x, z: 0, face }
+    }
+
+    #[test]
+    fn empty_query_finds_nothing() {
+        assert!(level().find_faces(&FaceQuery::default()).is_empty());
+    }
+
+    #[test]
+    fn by_texture() {
+        let query = FaceQuery { texture: Some(TextureRef::new("pack", "LAVA")), ..Default::default() };
+        assert_eq!(level().find_faces(&query), vec![at(1, HitFace::Floor), at(2, HitFace::Ceiling)]);
+    }
+
+    #[test]
+    fn by_walkable() {
+        let mut level = level();
+        level.rooms[0].get_sector_mut(1, 0).unwrap().floor.as_mut().unwrap().walkable = false;
+        let query = FaceQuery { not_walkable: true, ..Default::default() };
+        // Walls have no walkable flag, so never match
+        assert_eq!(level.find_faces(&query), vec![at(1, HitFace::Floor)]);
+    }
+
+    #[test]
+    fn by_blend_mode() {
+        let mut level = level();
+        level.rooms[0].get_sector_mut(0, 0).unwrap().walls_north[0].blend_mode = BlendMode::Add;
+        let query = FaceQuery { blended: true, ..Default::default() };
+        assert_eq!(level.find_faces(&query), vec![at(0, HitFace::Wall(Direction::North, 0))]);
+    }
+
+    #[test]
+    fn by_floor_height() {
+        let mut level = level();
+        let query = FaceQuery { floor_height: Some((f32::NEG_INFINITY, -2048.0)), ..Default::default() };
+        assert_eq!(level.find_faces(&query), vec![at(1, HitFace::Floor)]);
+
+        // Any corner in range counts: tilt the first floor so one corner dips to -2560
+        level.rooms[0].get_sector_mut(0, 0).unwrap().floor.as_mut().unwrap().heights[2] = -2560.0;
+        assert_eq!(level.find_faces(&query), vec![at(0, HitFace::Floor), at(1, HitFace::Floor)]);
+
+        // Heights are world Y already: the room's position doesn't shift them
+        level.rooms[0].position.y = 1024.0;
+        let query = FaceQuery { floor_height: Some((500.0, 600.0)), ..Default::default() };
+        assert_eq!(level.find_faces(&query), vec![at(2, HitFace::Floor)]);
+    }
+
+    #[test]
+    fn by_triggers() {
+        let mut level = level();
+        let query = FaceQuery { triggers: true, ..Default::default() };
+        assert!(level.find_faces(&query).is_empty());
+
+        let room = &mut level.rooms[0];
+        room.pickups.push(Pickup::new(Vec3::new(SECTOR_SIZE * 2.5, 512.0, SECTOR_SIZE * 0.5), "coin", TextureRef::none()));
+        let door = Door::from_wall(0, 0, Direction::North, 0, &room.get_sector(0, 0).unwrap().walls_north[0]);
+        room.doors.push(door);
+        assert_eq!(level.find_faces(&query), vec![
+            at(0, HitFace::Floor),
+            at(0, HitFace::Wall(Direction::North, 0)),
+            at(2, HitFace::Floor),
+            at(2, HitFace::Ceiling),
+        ]);
+    }
+
+    #[test]
+    fn by_custom_uv_combined_with_texture() {
+        let mut level = level();
+        let uv = Some([Vec2::new(0.0, 0.0), Vec2::new(0.5, 0.0), Vec2::new(0.5, 0.5), Vec2::new(0.0, 0.5)]);
+        level.rooms[0].get_sector_mut(0, 0).unwrap().floor.as_mut().unwrap().uv = uv;
+        level.rooms[0].get_sector_mut(1, 0).unwrap().floor.as_mut().unwrap().uv = uv;
+        let query = FaceQuery { custom_uv: true, ..Default::default() };
+        assert_eq!(level.find_faces(&query), vec![at(0, HitFace::Floor), at(1, HitFace::Floor)]);
+
+        let query = FaceQuery { custom_uv: true, texture: Some(TextureRef::new("pack", "LAVA")), ..Default::default() };
+        assert_eq!(level.find_faces(&query), vec![at(1, HitFace::Floor)]);
+    }
+
+    #[test]
+    fn room_ranges_add_up_to_the_whole_level() {
+        let mut level = level();
+        level.rooms.push(level.rooms[0].clone());
+        let query = FaceQuery { texture: Some(TextureRef::new("pack", "FLOOR")), ..Default::default() };
+        let mut parts = level.find_faces_in(0..1, &query);
+        parts.extend(level.find_faces_in(1..5, &query));
+        assert_eq!(parts, level.find_faces(&query));
+        assert_eq!(parts.len(), 4);
+    }
+}
@@ -3,18 +3,25 @@
 //! Sector-based geometry system inspired by TRLE.
 //! Rooms contain a 2D grid of sectors, each with floor, ceiling, and walls.
 
+use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
-use crate::rasterizer::{Vec3, Vec2, Vertex, Face as RasterFace, BlendMode};
+use crate::rasterizer::{Vec3, Vec2, Vertex, Face as RasterFace, BlendMode, Color, ColorDepth, RasterSettings, ShadingMode};
+use super::PackAliases;
 
 /// TRLE sector size in world units
 pub const SECTOR_SIZE: f32 = 1024.0;
 
+/// TRLE height step ("click"), a quarter sector
+pub const CLICK_HEIGHT: f32 = SECTOR_SIZE / 4.0;
+
 /// Texture reference by pack and name
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TextureRef {
     /// Texture pack name (e.g., "SAMPLE")
+    #[serde(deserialize_with = "super::limits::string")]
     pub pack: String,
     /// Texture name without extension (e.g., "floor_01")
+    #[serde(deserialize_with = "super::limits::string")]
     pub name: String,
 }
 
@@ -48,6 +55,73 @@ impl Default for TextureRef {
 
 fn default_true() -> bool { true }
 
+fn is_true(b: &bool) -> bool { *b }
+
+fn default_colors() -> [Color; 4] { [Color::NEUTRAL; 4] }
+
+fn is_default_colors(colors: &[Color; 4]) -> bool { *colors == default_colors() }
+
+fn default_subdivision() -> u8 { 1 }
+
+fn is_single_cell(subdivision: &u8) -> bool { *subdivision <= 1 }
+
+fn is_edge_start(x: &f32) -> bool { *x <= 0.0 }
+
+fn default_edge_end() -> f32 { 1.0 }
+
+fn is_edge_end(x: &f32) -> bool { *x >= 1.0 }
+
+/// Subdivision levels offered by the editor (cells per side)
+pub const SUBDIVISION_LEVELS: [u8; 3] = [1, 2, 4];
+
+/// Height inside a quad [NW, NE, SE, SW] at (u, v), over the NW-SE triangle split
+fn quad_height_at(heights: [f32; 4], u: f32, v: f32) -> f32 {
+    let [nw, ne, se, sw] = heights;
+    if u >= v {
+        // NW, NE, SE triangle
+        nw + u * (ne - nw) + v * (se - ne)
+    } else {
+        // NW, SE, SW triangle
+        nw + v * (sw - nw) + u * (se - sw)
+    }
+}
+
+/// What a surface is made of (footstep sounds, later splashes/particles)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SurfaceMaterial {
+    #[default]
+    Stone,
+    Metal,
+    Wood,
+    Water,
+    Grass,
+    /// Game-specific material id
+    Custom(u8),
+}
+
+impl SurfaceMaterial {
+    /// Built-in materials, in editor menu order
+    pub const ALL: [SurfaceMaterial; 5] = [
+        SurfaceMaterial::Stone,
+        SurfaceMaterial::Metal,
+        SurfaceMaterial::Wood,
+        SurfaceMaterial::Water,
+        SurfaceMaterial::Grass,
+    ];
+
+    /// Short display name
+    pub fn label(&self) -> String {
+        match self {
+            SurfaceMaterial::Stone => "Stone".to_string(),
+            SurfaceMaterial::Metal => "Metal".to_string(),
+            SurfaceMaterial::Wood => "Wood".to_string(),
+            SurfaceMaterial::Water => "Water".to_string(),
+            SurfaceMaterial::Grass => "Grass".to_string(),
+            SurfaceMaterial::Custom(id) => format!("Custom {}", id),
+        }
+    }
+}
+
 /// A horizontal face (floor or ceiling)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HorizontalFace {
@@ -62,12 +136,44 @@ pub struct HorizontalFace {
     /// Is this surface walkable? (for collision/AI)
     #[serde(default = "default_true")]
     pub walkable: bool,
+    /// Is this surface drawn? Invisible floors still collide if walkable.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub visible: bool,
     /// Transparency/blend mode
     #[serde(default)]
     pub blend_mode: BlendMode,
+    /// Per-corner vertex colors [NW, NE, SE, SW] (128 = neutral)
+    #[serde(default = "default_colors", skip_serializing_if = "is_default_colors")]
+    pub colors: [Color; 4],
+    /// Surface material (footstep sounds)
+    #[serde(default)]
+    pub material: SurfaceMaterial,
+    /// Opening into the room below (floor) or above (ceiling), by `Room::id`.
+    /// Open faces aren't drawn or collided with; see `Level::create_vertical_portal`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opening: Option<usize>,
+    /// Cells per side for detail geometry (1 = a single quad, 2 = 2x2, 4 = 4x4)
+    #[serde(default = "default_subdivision", skip_serializing_if = "is_single_cell")]
+    pub subdivision: u8,
+    /// Height offsets of the subdivided grid points, `(subdivision + 1)^2` of them,
+    /// row by row from the north edge, west to east. They're added to the surface
+    /// the corner heights describe, so the corners stay authoritative (their own
+    /// offsets are ignored). Empty when not subdivided.
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "super::limits::detail_heights")]
+    pub detail: Vec<f32>,
 }
 
 impl HorizontalFace {
+    /// UVs used when `uv` is None: the whole texture, corner to corner
+    pub fn default_uvs() -> [Vec2; 4] {
+        [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)]
+    }
+
+    /// UVs of the corners [NW, NE, SE, SW] (custom or default)
+    pub fn uvs(&self) -> [Vec2; 4] {
+        self.uv.unwrap_or_else(Self::default_uvs)
+    }
+
     /// Create a flat horizontal face at the given height
     pub fn flat(height: f32, texture: TextureRef) -> Self {
         Self {
@@ -75,7 +181,13 @@ impl HorizontalFace {
             texture,
             uv: None,
             walkable: true,
+            visible: true,
             blend_mode: BlendMode::Opaque,
+            colors: default_colors(),
+            material: SurfaceMaterial::Stone,
+            opening: None,
+            subdivision: 1,
+            detail: Vec::new(),
         }
     }
 
@@ -86,7 +198,13 @@ impl HorizontalFace {
             texture,
             uv: None,
             walkable: true,
+            visible: true,
             blend_mode: BlendMode::Opaque,
+            colors: default_colors(),
+            material: SurfaceMaterial::Stone,
+            opening: None,
+            subdivision: 1,
+            detail: Vec::new(),
         }
     }
 
@@ -95,13 +213,133 @@ impl HorizontalFace {
         (self.heights[0] + self.heights[1] + self.heights[2] + self.heights[3]) / 4.0
     }
 
-    /// Check if the face is flat (all corners at same height)
+    /// Height at a point inside the sector (u along +X, v along +Z, both 0-1).
+    /// Interpolates over the same NW-SE triangle split used for rendering, within
+    /// the grid cell the point falls in when the face is subdivided.
+    pub fn height_at(&self, u: f32, v: f32) -> f32 {
+        let (u, v) = (u.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let n = self.cells();
+        if n == 1 {
+            return quad_height_at(self.heights, u, v);
+        }
+        let (gu, gv) = (u * n as f32, v * n as f32);
+        let (cx, cz) = ((gu as usize).min(n - 1), (gv as usize).min(n - 1));
+        quad_height_at(self.cell_heights(cx, cz), gu - cx as f32, gv - cz as f32)
+    }
+
+    /// Check if the face is flat (all corners at same height, no detail offsets)
     pub fn is_flat(&self) -> bool {
         let h = self.heights[0];
         self.heights.iter().all(|&corner| (corner - h).abs() < 0.001)
+            && self.detail.iter().all(|d| d.abs() < 0.001)
+    }
+
+    /// Cells per side (1 when not subdivided)
+    pub fn cells(&self) -> usize {
+        (self.subdivision as usize).max(1)
+    }
+
+    /// Change the subdivision level. Detail offsets are dropped.
+    pub fn set_subdivision(&mut self, subdivision: u8) {
+        self.subdivision = subdivision.max(1);
+        let n = self.cells();
+        self.detail = if n > 1 { vec![0.0; (n + 1) * (n + 1)] } else { Vec::new() };
+    }
+
+    /// Height of grid point (ix, iz), both 0..=cells(), counted from the NW corner
+    pub fn grid_height(&self, ix: usize, iz: usize) -> f32 {
+        let n = self.cells();
+        match (ix, iz) {
+            (0, 0) => return self.heights[0],
+            (x, 0) if x == n => return self.heights[1],
+            (x, z) if x == n && z == n => return self.heights[2],
+            (0, z) if z == n => return self.heights[3],
+            _ => {}
+        }
+        let base = quad_height_at(self.heights, ix as f32 / n as f32, iz as f32 / n as f32);
+        base + self.detail.get(iz * (n + 1) + ix).copied().unwrap_or(0.0)
+    }
+
+    /// Heights [NW, NE, SE, SW] of grid cell (cx, cz)
+    pub fn cell_heights(&self, cx: usize, cz: usize) -> [f32; 4] {
+        [
+            self.grid_height(cx, cz),
+            self.grid_height(cx + 1, cz),
+            self.grid_height(cx + 1, cz + 1),
+            self.grid_height(cx, cz + 1),
+        ]
+    }
+
+    /// Normal of grid cell (cx, cz) as it's drawn: up for a floor, down for a ceiling
+    pub fn cell_normal(&self, cx: usize, cz: usize, is_floor: bool) -> Vec3 {
+        let size = SECTOR_SIZE / self.cells() as f32;
+        let h = self.cell_heights(cx, cz);
+        let corners = [
+            Vec3::new(0.0, h[0], 0.0),
+            Vec3::new(size, h[1], 0.0),
+            Vec3::new(size, h[2], size),
+            Vec3::new(0.0, h[3], size),
+        ];
+        quad_normal(&corners, is_floor)
+    }
+
+    /// Editable vertices beyond the four corners: (vertex index, u, v) of every grid
+    /// point that isn't a corner. Vertex indices 0-3 are the corners [NW, NE, SE, SW];
+    /// grid point (ix, iz) is `4 + iz * (cells + 1) + ix`.
+    pub fn detail_vertices(&self) -> impl Iterator<Item = (usize, f32, f32)> + '_ {
+        let n = self.cells();
+        let side = if n > 1 { n + 1 } else { 0 };
+        (0..side * side).filter_map(move |g| {
+            let (ix, iz) = (g % side, g / side);
+            let corner = (ix == 0 || ix == n) && (iz == 0 || iz == n);
+            (!corner).then(|| (4 + g, ix as f32 / n as f32, iz as f32 / n as f32))
+        })
+    }
+
+    /// Height of a vertex by index (see `detail_vertices`)
+    pub fn vertex_height(&self, vertex: usize) -> Option<f32> {
+        if vertex < 4 {
+            return Some(self.heights[vertex]);
+        }
+        let side = self.cells() + 1;
+        let g = vertex - 4;
+        (g < self.detail.len()).then(|| self.grid_height(g % side, g / side))
+    }
+
+    /// Move a vertex (see `detail_vertices`) to an absolute height
+    pub fn set_vertex_height(&mut self, vertex: usize, height: f32) {
+        if vertex < 4 {
+            self.heights[vertex] = height;
+            return;
+        }
+        let n = self.cells();
+        let g = vertex - 4;
+        if g < self.detail.len() {
+            let (ix, iz) = (g % (n + 1), g / (n + 1));
+            let base = quad_height_at(self.heights, ix as f32 / n as f32, iz as f32 / n as f32);
+            self.detail[g] = height - base;
+        }
+    }
+
+    /// Get the two corner heights along an edge, ordered left-to-right as seen
+    /// from inside the sector (same order as a wall's bottom-left/bottom-right)
+    pub fn edge_heights(&self, direction: Direction) -> (f32, f32) {
+        let (left, right) = match direction {
+            Direction::North => (0, 1), // NW, NE
+            Direction::East => (1, 2),  // NE, SE
+            Direction::South => (2, 3), // SE, SW
+            Direction::West => (3, 0),  // SW, NW
+        };
+        (self.heights[left], self.heights[right])
     }
 }
 
+/// Identity of a wall within its room, kept while the walls around it are added
+/// and removed (the editor selects walls by it, doors follow their wall with it).
+/// Assigned by `Room::assign_wall_ids`, not serialized; 0 = not assigned yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct WallId(pub u32);
+
 /// A vertical face (wall) on a sector edge
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerticalFace {
@@ -115,12 +353,44 @@ pub struct VerticalFace {
     /// Is this a solid wall for collision?
     #[serde(default = "default_true")]
     pub solid: bool,
+    /// Is this wall drawn? Invisible solid walls still block (invisible walls).
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub visible: bool,
     /// Transparency/blend mode
     #[serde(default)]
     pub blend_mode: BlendMode,
+    /// Per-corner vertex colors [bottom-left, bottom-right, top-right, top-left] (128 = neutral)
+    #[serde(default = "default_colors", skip_serializing_if = "is_default_colors")]
+    pub colors: [Color; 4],
+    /// Span of the sector edge the wall covers, 0-1 from its left end (as seen from
+    /// inside the sector). Partial walls frame doorways; the corner heights are at
+    /// the ends of the span.
+    #[serde(default, skip_serializing_if = "is_edge_start")]
+    pub x_start: f32,
+    #[serde(default = "default_edge_end", skip_serializing_if = "is_edge_end")]
+    pub x_end: f32,
+    /// Stable id within the room - not serialized
+    #[serde(skip)]
+    pub id: WallId,
 }
 
 impl VerticalFace {
+    /// UVs used when `uv` is None: a partial wall shows its part of the texture
+    pub fn default_uvs(&self) -> [Vec2; 4] {
+        let (u0, u1) = (self.x_start, self.x_end);
+        [
+            Vec2::new(u0, 1.0), // bottom-left
+            Vec2::new(u1, 1.0), // bottom-right
+            Vec2::new(u1, 0.0), // top-right
+            Vec2::new(u0, 0.0), // top-left
+        ]
+    }
+
+    /// UVs of the corners [bottom-left, bottom-right, top-right, top-left] (custom or default)
+    pub fn uvs(&self) -> [Vec2; 4] {
+        self.uv.unwrap_or_else(|| self.default_uvs())
+    }
+
     /// Create a wall from bottom to top (all corners at same heights)
     pub fn new(y_bottom: f32, y_top: f32, texture: TextureRef) -> Self {
         Self {
@@ -128,10 +398,46 @@ impl VerticalFace {
             texture,
             uv: None,
             solid: true,
+            visible: true,
             blend_mode: BlendMode::Opaque,
+            colors: default_colors(),
+            x_start: 0.0,
+            x_end: 1.0,
+            id: WallId::default(),
         }
     }
 
+    /// Does the wall cover the whole sector edge?
+    pub fn is_full_width(&self) -> bool {
+        is_edge_start(&self.x_start) && is_edge_end(&self.x_end)
+    }
+
+    /// Does the wall cover the point `t` (0-1 from its left end) of its edge?
+    pub fn covers(&self, t: f32) -> bool {
+        (self.x_start..=self.x_end).contains(&t)
+    }
+
+    /// World-space corners [bottom-left, bottom-right, top-right, top-left] (as seen
+    /// from inside the sector) on the `edge` of the sector whose NW corner is at
+    /// (base_x, base_z), over the part of the edge the wall covers
+    pub fn corners(&self, base_x: f32, base_z: f32, edge: Direction) -> [Vec3; 4] {
+        let s = SECTOR_SIZE;
+        let (left, right) = match edge {
+            Direction::North => ((base_x, base_z), (base_x + s, base_z)),
+            Direction::East => ((base_x + s, base_z), (base_x + s, base_z + s)),
+            Direction::South => ((base_x + s, base_z + s), (base_x, base_z + s)),
+            Direction::West => ((base_x, base_z + s), (base_x, base_z)),
+        };
+        let at = |t: f32| (left.0 + (right.0 - left.0) * t, left.1 + (right.1 - left.1) * t);
+        let (start, end) = (at(self.x_start), at(self.x_end));
+        [
+            Vec3::new(start.0, self.heights[0], start.1),
+            Vec3::new(end.0, self.heights[1], end.1),
+            Vec3::new(end.0, self.heights[2], end.1),
+            Vec3::new(start.0, self.heights[3], start.1),
+        ]
+    }
+
     /// Get the average height of this wall
     pub fn height(&self) -> f32 {
         let bottom = (self.heights[0] + self.heights[1]) / 2.0;
@@ -165,16 +471,16 @@ pub struct Sector {
     /// Ceiling face (None = no ceiling / open sky)
     pub ceiling: Option<HorizontalFace>,
     /// Walls on north edge (-Z) - can have multiple stacked
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::limits::walls")]
     pub walls_north: Vec<VerticalFace>,
     /// Walls on east edge (+X)
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::limits::walls")]
     pub walls_east: Vec<VerticalFace>,
     /// Walls on south edge (+Z)
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::limits::walls")]
     pub walls_south: Vec<VerticalFace>,
     /// Walls on west edge (-X)
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::limits::walls")]
     pub walls_west: Vec<VerticalFace>,
 }
 
@@ -230,6 +536,126 @@ impl Sector {
             Direction::West => &mut self.walls_west,
         }
     }
+
+    /// Index of the wall with `id` on an edge
+    pub fn wall_index(&self, direction: Direction, id: WallId) -> Option<usize> {
+        self.walls(direction).iter().position(|w| w.id == id)
+    }
+
+    /// The wall with `id` on an edge
+    pub fn wall_by_id(&self, direction: Direction, id: WallId) -> Option<&VerticalFace> {
+        self.walls(direction).iter().find(|w| w.id == id)
+    }
+
+    /// The wall with `id` on an edge, mutable
+    pub fn wall_by_id_mut(&mut self, direction: Direction, id: WallId) -> Option<&mut VerticalFace> {
+        self.walls_mut(direction).iter_mut().find(|w| w.id == id)
+    }
+
+    /// Texture references of every face (floor, ceiling, walls)
+    pub fn textures(&self) -> impl Iterator<Item = &TextureRef> {
+        self.floor.iter().chain(&self.ceiling).map(|f| &f.texture)
+            .chain(
+                self.walls_north.iter()
+                    .chain(&self.walls_east)
+                    .chain(&self.walls_south)
+                    .chain(&self.walls_west)
+                    .map(|w| &w.texture),
+            )
+    }
+
+    /// Mutable texture references of every face
+    pub fn textures_mut(&mut self) -> impl Iterator<Item = &mut TextureRef> {
+        self.floor.iter_mut().chain(&mut self.ceiling).map(|f| &mut f.texture)
+            .chain(
+                self.walls_north.iter_mut()
+                    .chain(&mut self.walls_east)
+                    .chain(&mut self.walls_south)
+                    .chain(&mut self.walls_west)
+                    .map(|w| &mut w.texture),
+            )
+    }
+}
+
+/// One face of a sector (what the editor selects)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectorFace {
+    Floor,
+    Ceiling,
+    /// Walls by id (not index), so the selection stays on the same wall while
+    /// others on the edge come and go
+    WallNorth(WallId),
+    WallEast(WallId),
+    WallSouth(WallId),
+    WallWest(WallId),
+}
+
+impl SectorFace {
+    /// The wall face with `id` on an edge
+    pub fn for_wall(direction: Direction, id: WallId) -> Self {
+        match direction {
+            Direction::North => SectorFace::WallNorth(id),
+            Direction::East => SectorFace::WallEast(id),
+            Direction::South => SectorFace::WallSouth(id),
+            Direction::West => SectorFace::WallWest(id),
+        }
+    }
+
+    /// The wall face of `sector`'s wall at `index` on an edge
+    pub fn wall_at(sector: &Sector, direction: Direction, index: usize) -> Option<Self> {
+        Some(Self::for_wall(direction, sector.walls(direction).get(index)?.id))
+    }
+
+    /// Edge direction and wall id, if this is a wall face
+    pub fn wall(&self) -> Option<(Direction, WallId)> {
+        match *self {
+            SectorFace::WallNorth(id) => Some((Direction::North, id)),
+            SectorFace::WallEast(id) => Some((Direction::East, id)),
+            SectorFace::WallSouth(id) => Some((Direction::South, id)),
+            SectorFace::WallWest(id) => Some((Direction::West, id)),
+            SectorFace::Floor | SectorFace::Ceiling => None,
+        }
+    }
+
+    /// Edge direction and current index of this wall in `sector`
+    pub fn wall_index_in(&self, sector: &Sector) -> Option<(Direction, usize)> {
+        let (dir, id) = self.wall()?;
+        Some((dir, sector.wall_index(dir, id)?))
+    }
+
+    /// This wall in `sector` (None if it's gone, or not a wall face)
+    pub fn wall_in<'a>(&self, sector: &'a Sector) -> Option<(Direction, &'a VerticalFace)> {
+        let (dir, id) = self.wall()?;
+        Some((dir, sector.wall_by_id(dir, id)?))
+    }
+
+    /// This wall in `sector`, mutable
+    pub fn wall_in_mut<'a>(&self, sector: &'a mut Sector) -> Option<(Direction, &'a mut VerticalFace)> {
+        let (dir, id) = self.wall()?;
+        Some((dir, sector.wall_by_id_mut(dir, id)?))
+    }
+}
+
+/// Which faces a level-wide texture edit applies to
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextureScope {
+    /// Every room
+    Level,
+    /// One room (by index)
+    Room(usize),
+    /// These sectors only: (room index, x, z)
+    Sectors(Vec<(usize, usize, usize)>),
+}
+
+impl TextureScope {
+    /// Does the scope include the sector at (x, z) of the room at `room_idx`?
+    pub fn contains(&self, room_idx: usize, x: usize, z: usize) -> bool {
+        match self {
+            TextureScope::Level => true,
+            TextureScope::Room(r) => *r == room_idx,
+            TextureScope::Sectors(sectors) => sectors.contains(&(room_idx, x, z)),
+        }
+    }
 }
 
 /// Cardinal direction for sector edges
@@ -261,10 +687,76 @@ impl Direction {
             Direction::West => (-1, 0),
         }
     }
+
+    /// Normal of a wall on this edge of a sector (facing into the sector)
+    pub fn wall_normal(self) -> Vec3 {
+        match self {
+            Direction::North => Vec3::new(0.0, 0.0, 1.0),
+            Direction::East => Vec3::new(-1.0, 0.0, 0.0),
+            Direction::South => Vec3::new(0.0, 0.0, -1.0),
+            Direction::West => Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Normal of a floor or ceiling quad from its corners [NW, NE, SE, SW]
+fn quad_normal(corners: &[Vec3; 4], is_floor: bool) -> Vec3 {
+    // For floor (facing up): use edge2 x edge1 to get +Y normal
+    // For ceiling (facing down): use edge1 x edge2 to get -Y normal
+    let edge1 = corners[1] - corners[0]; // NW -> NE (along +X)
+    let edge2 = corners[3] - corners[0]; // NW -> SW (along +Z)
+    if is_floor {
+        edge2.cross(edge1).normalize() // +Z x +X = +Y (up)
+    } else {
+        edge1.cross(edge2).normalize() // +X x +Z = -Y (down)
+    }
+}
+
+/// Surface a render vertex belongs to. Normals are only smoothed between vertices
+/// of the same kind, so the edge where a floor meets a wall stays hard.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum SurfaceKind {
+    Floor,
+    Ceiling,
+    Wall(Direction),
 }
 
-/// Axis-aligned bounding box
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+/// A floor or ceiling to mesh, spanning `span` (width, depth) sectors from the
+/// NW corner at (`base_x`, `base_z`)
+struct HorizontalSpan<'a> {
+    face: &'a HorizontalFace,
+    base_x: f32,
+    base_z: f32,
+    span: (usize, usize),
+    is_floor: bool,
+}
+
+/// Vertices closer than this (world units) count as the same point when smoothing
+const SMOOTH_EPSILON: f32 = 0.5;
+
+/// Average the normals of vertices at the same point and of the same kind, so
+/// Gouraud shading runs on across the edges between faces instead of stepping
+fn smooth_normals(vertices: &mut [Vertex], kinds: &[SurfaceKind]) {
+    let key = |v: &Vertex, kind: SurfaceKind| {
+        let q = |c: f32| (c / SMOOTH_EPSILON).round() as i64;
+        (q(v.pos.x), q(v.pos.y), q(v.pos.z), kind)
+    };
+    let mut sums: HashMap<_, Vec3> = HashMap::new();
+    for (v, &kind) in vertices.iter().zip(kinds) {
+        let sum = sums.entry(key(v, kind)).or_insert(Vec3::ZERO);
+        *sum = *sum + v.normal;
+    }
+    for (v, &kind) in vertices.iter_mut().zip(kinds) {
+        let sum = sums[&key(v, kind)];
+        if sum.len() > 1e-6 {
+            v.normal = sum.normalize();
+        }
+    }
+}
+
+/// Axis-aligned bounding box. The empty box (nothing added yet, see `empty`) has
+/// `min` above `max`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Aabb {
     pub min: Vec3,
     pub max: Vec3,
@@ -275,8 +767,21 @@ impl Aabb {
         Self { min, max }
     }
 
-    /// Check if a point is inside the box
+    /// A box containing nothing; `expand` grows it around the first point
+    pub fn empty() -> Self {
+        Self::new(Vec3::new(f32::MAX, f32::MAX, f32::MAX), Vec3::new(f32::MIN, f32::MIN, f32::MIN))
+    }
+
+    /// Does the box contain nothing (no point added)?
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Check if a point is inside the box (never, for an empty box)
     pub fn contains(&self, point: Vec3) -> bool {
+        if self.is_empty() {
+            return false;
+        }
         point.x >= self.min.x && point.x <= self.max.x
             && point.y >= self.min.y && point.y <= self.max.y
             && point.z >= self.min.z && point.z <= self.max.z
@@ -292,8 +797,20 @@ impl Aabb {
         self.max.z = self.max.z.max(point.z);
     }
 
-    /// Get center of the box
+    /// The box moved by `offset` (an empty box stays empty)
+    pub fn translated(&self, offset: Vec3) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        Self::new(self.min + offset, self.max + offset)
+    }
+
+    /// Get center of the box (the origin for an empty box, so a room's own
+    /// position for empty room-relative bounds)
     pub fn center(&self) -> Vec3 {
+        if self.is_empty() {
+            return Vec3::ZERO;
+        }
         Vec3::new(
             (self.min.x + self.max.x) * 0.5,
             (self.min.y + self.max.y) * 0.5,
@@ -302,10 +819,16 @@ impl Aabb {
     }
 }
 
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 /// Portal connecting two rooms
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Portal {
-    /// Target room ID
+    /// Target room's `Room::id` (not its index, so reordering/deleting rooms keeps portals valid)
     pub target_room: usize,
     /// Portal corners in room-relative coordinates (4 vertices)
     pub vertices: [Vec3; 4],
@@ -335,8 +858,11 @@ impl Portal {
 /// A room in the level - contains a 2D grid of sectors
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
-    /// Unique room identifier
+    /// Unique room identifier, never reused within a level (portals refer to rooms by id)
     pub id: usize,
+    /// Display name (empty = "Room <id>")
+    #[serde(default, skip_serializing_if = "String::is_empty", deserialize_with = "super::limits::string")]
+    pub name: String,
     /// Room position in world space (origin of sector grid)
     pub position: Vec3,
     /// Grid width (number of sectors in X direction)
@@ -344,9 +870,10 @@ pub struct Room {
     /// Grid depth (number of sectors in Z direction)
     pub depth: usize,
     /// 2D array of sectors [x][z], None = no sector at this position
+    #[serde(deserialize_with = "super::limits::sector_grid")]
     pub sectors: Vec<Vec<Option<Sector>>>,
     /// Portals to adjacent rooms
-    #[serde(default)]
+    #[serde(default, deserialize_with = "super::limits::portals")]
     pub portals: Vec<Portal>,
     /// Bounding box (room-relative) - computed from sectors, not serialized
     #[serde(skip)]
@@ -354,6 +881,28 @@ pub struct Room {
     /// Ambient light level (0.0 = dark, 1.0 = bright)
     #[serde(default = "default_ambient")]
     pub ambient: f32,
+    /// Collectible pickups placed in this room
+    #[serde(default, deserialize_with = "super::limits::pickups")]
+    pub pickups: Vec<super::Pickup>,
+    /// Ambient sound emitters placed in this room
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "super::limits::ambients")]
+    pub ambients: Vec<super::AmbientEmitter>,
+    /// Walls that open and close in game mode
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "super::limits::doors")]
+    pub doors: Vec<super::Door>,
+    /// Point lights placed in this room
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "super::limits::lights")]
+    pub lights: Vec<super::PointLight>,
+    /// Light direction override (None = use the level's light)
+    #[serde(default)]
+    pub light_dir: Option<Vec3>,
+    /// Edit generation, stamped by the editor on every change to the room (caches
+    /// rebuild a room whose generation moved) - not serialized
+    #[serde(skip)]
+    pub generation: u64,
+    /// Next `WallId` to hand out - not serialized
+    #[serde(skip)]
+    next_wall_id: u32,
 }
 
 fn default_ambient() -> f32 {
@@ -370,13 +919,30 @@ impl Room {
 
         Self {
             id,
+            name: String::new(),
             position,
             width,
             depth,
             sectors,
             portals: Vec::new(),
-            bounds: Aabb::default(),
+            bounds: Aabb::empty(),
             ambient: 0.5,
+            pickups: Vec::new(),
+            ambients: Vec::new(),
+            doors: Vec::new(),
+            lights: Vec::new(),
+            light_dir: None,
+            generation: 0,
+            next_wall_id: 1,
+        }
+    }
+
+    /// Name shown in the editor
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            format!("Room {}", self.id)
+        } else {
+            self.name.clone()
         }
     }
 
@@ -428,10 +994,30 @@ impl Room {
         sector.ceiling = Some(HorizontalFace::flat(height, texture));
     }
 
-    /// Add a wall on a sector edge
-    pub fn add_wall(&mut self, x: usize, z: usize, direction: Direction, y_bottom: f32, y_top: f32, texture: TextureRef) {
-        let sector = self.ensure_sector(x, z);
-        sector.walls_mut(direction).push(VerticalFace::new(y_bottom, y_top, texture));
+    /// Add a wall on a sector edge. Returns its id.
+    pub fn add_wall(&mut self, x: usize, z: usize, direction: Direction, y_bottom: f32, y_top: f32, texture: TextureRef) -> WallId {
+        self.push_wall(x, z, direction, VerticalFace::new(y_bottom, y_top, texture))
+    }
+
+    /// Put a wall on top of a sector edge's stack, with a fresh id (so a selection
+    /// or door can refer to it right away). Returns the id.
+    pub fn push_wall(&mut self, x: usize, z: usize, direction: Direction, mut wall: VerticalFace) -> WallId {
+        wall.id = WallId(self.next_wall_id.max(1));
+        self.next_wall_id = wall.id.0 + 1;
+        let id = wall.id;
+        self.ensure_sector(x, z).walls_mut(direction).push(wall);
+        id
+    }
+
+    /// Close floor/ceiling openings whose target room id fails `keep`
+    pub fn close_openings(&mut self, keep: impl Fn(usize) -> bool) {
+        for sector in self.sectors.iter_mut().flatten().flatten() {
+            for face in sector.floor.iter_mut().chain(sector.ceiling.iter_mut()) {
+                if face.opening.is_some_and(|target| !keep(target)) {
+                    face.opening = None;
+                }
+            }
+        }
     }
 
     /// Add a portal to another room
@@ -439,6 +1025,114 @@ impl Room {
         self.portals.push(Portal::new(target_room, vertices, normal));
     }
 
+    /// Remove a wall from a sector edge. A door on that wall goes with it, and doors on
+    /// walls further up the stack are re-indexed.
+    pub fn remove_wall(&mut self, x: usize, z: usize, dir: Direction, index: usize) -> Option<VerticalFace> {
+        let walls = self.get_sector_mut(x, z)?.walls_mut(dir);
+        if index >= walls.len() {
+            return None;
+        }
+        let wall = walls.remove(index);
+        self.doors.retain(|d| !d.is_wall(x, z, dir, wall.id));
+        for door in &mut self.doors {
+            if door.x == x && door.z == z && door.edge == dir && door.wall > index {
+                door.wall -= 1;
+            }
+        }
+        Some(wall)
+    }
+
+    /// Cut an opening `width` wide (centered on the wall) and `height` tall (up from the
+    /// wall's bottom edge) into a wall, replacing it with the pieces around the opening:
+    /// left pillar, right pillar and lintel, each only if something is left of it.
+    /// Returns how many pieces the wall became.
+    pub fn cut_doorway(&mut self, x: usize, z: usize, dir: Direction, index: usize, width: f32, height: f32) -> Result<usize, String> {
+        let wall = self.get_sector(x, z)
+            .and_then(|s| s.walls(dir).get(index))
+            .cloned()
+            .ok_or("No wall there")?;
+        if self.door_at(x, z, dir, wall.id).is_some() {
+            return Err("Can't cut a doorway into a door".to_string());
+        }
+        let span = wall.x_end - wall.x_start;
+        let opening = width / SECTOR_SIZE;
+        if width <= 0.0 || height <= 0.0 {
+            return Err("Opening needs a width and a height".to_string());
+        }
+        if opening > span + f32::EPSILON {
+            return Err(format!("Opening is wider than the wall ({:.0})", span * SECTOR_SIZE));
+        }
+
+        // Edge heights at `t` along the edge (0-1), interpolated between the wall's ends
+        let bottom = |t: f32| {
+            let f = (t - wall.x_start) / span;
+            wall.heights[0] + (wall.heights[1] - wall.heights[0]) * f
+        };
+        let top = |t: f32| {
+            let f = (t - wall.x_start) / span;
+            wall.heights[3] + (wall.heights[2] - wall.heights[3]) * f
+        };
+        let piece = |start: f32, end: f32, low: [f32; 2]| {
+            let mut piece = wall.clone();
+            piece.x_start = start;
+            piece.x_end = end;
+            piece.heights = [low[0], low[1], top(end), top(start)];
+            piece.uv = None;
+            piece
+        };
+
+        let open_start = wall.x_start + (span - opening) * 0.5;
+        let open_end = open_start + opening;
+        let mut pieces = Vec::new();
+        if open_start - wall.x_start > f32::EPSILON {
+            pieces.push(piece(wall.x_start, open_start, [bottom(wall.x_start), bottom(open_start)]));
+        }
+        if wall.x_end - open_end > f32::EPSILON {
+            pieces.push(piece(open_end, wall.x_end, [bottom(open_end), bottom(wall.x_end)]));
+        }
+        // The lintel's bottom never goes above the wall's top
+        let lintel_low = [
+            (bottom(open_start) + height).min(top(open_start)),
+            (bottom(open_end) + height).min(top(open_end)),
+        ];
+        if lintel_low[0] < top(open_start) || lintel_low[1] < top(open_end) {
+            pieces.push(piece(open_start, open_end, lintel_low));
+        }
+        if pieces.is_empty() {
+            return Err("Opening would remove the whole wall".to_string());
+        }
+
+        self.remove_wall(x, z, dir, index);
+        let count = pieces.len();
+        if let Some(sector) = self.get_sector_mut(x, z) {
+            sector.walls_mut(dir).extend(pieces);
+        }
+        Ok(count)
+    }
+
+    /// Is the edge of sector (x, z) facing `dir` blocked anywhere between `bottom` and
+    /// `top`, at `along` (0-1 from the edge's left end as seen from inside the sector)?
+    /// Checks solid walls stored on either side of the edge that cover that point, at
+    /// their current heights (so an opening door stops blocking once it's low enough).
+    pub fn edge_blocked(&self, x: usize, z: usize, dir: Direction, along: f32, bottom: f32, top: f32) -> bool {
+        let (dx, dz) = dir.offset();
+        let neighbor = (x as i32 + dx, z as i32 + dz);
+        let here = self.get_sector(x, z).map(|s| s.walls(dir));
+        let there = (neighbor.0 >= 0 && neighbor.1 >= 0)
+            .then(|| self.get_sector(neighbor.0 as usize, neighbor.1 as usize))
+            .flatten()
+            .map(|s| s.walls(dir.opposite()));
+
+        let blocks = |wall: &VerticalFace, t: f32| {
+            let lo = wall.heights.iter().copied().fold(f32::MAX, f32::min);
+            let hi = wall.heights.iter().copied().fold(f32::MIN, f32::max);
+            wall.solid && wall.covers(t) && hi > bottom && lo < top
+        };
+        // The neighbor's wall runs the other way along the shared edge
+        here.into_iter().flatten().any(|wall| blocks(wall, along))
+            || there.into_iter().flatten().any(|wall| blocks(wall, 1.0 - along))
+    }
+
     /// Convert world position to grid coordinates
     pub fn world_to_grid(&self, world_x: f32, world_z: f32) -> Option<(usize, usize)> {
         let local_x = world_x - self.position.x;
@@ -458,6 +1152,35 @@ impl Room {
         }
     }
 
+    /// Floor face and its height (world Y) at a world XZ position, if there's a floor there
+    /// (an opening into the room below or a non-walkable floor doesn't count)
+    pub fn floor_at(&self, world_x: f32, world_z: f32) -> Option<(&HorizontalFace, f32)> {
+        let (gx, gz, u, v) = self.sector_uv(world_x, world_z)?;
+        let floor = self.get_sector(gx, gz)?.floor.as_ref().filter(|f| f.opening.is_none() && f.walkable)?;
+        Some((floor, floor.height_at(u, v)))
+    }
+
+    /// Floor height (world Y) at a world XZ position, if there's a floor there
+    pub fn floor_height_at(&self, world_x: f32, world_z: f32) -> Option<f32> {
+        self.floor_at(world_x, world_z).map(|(_, height)| height)
+    }
+
+    /// Ceiling height (world Y) at a world XZ position, if there's a ceiling there
+    /// that collides
+    pub fn ceiling_height_at(&self, world_x: f32, world_z: f32) -> Option<f32> {
+        let (gx, gz, u, v) = self.sector_uv(world_x, world_z)?;
+        let ceiling = self.get_sector(gx, gz)?.ceiling.as_ref().filter(|c| c.opening.is_none() && c.walkable)?;
+        Some(ceiling.height_at(u, v))
+    }
+
+    /// Grid cell and 0-1 position inside it for a world XZ position
+    fn sector_uv(&self, world_x: f32, world_z: f32) -> Option<(usize, usize, f32, f32)> {
+        let (gx, gz) = self.world_to_grid(world_x, world_z)?;
+        let u = (world_x - self.position.x) / SECTOR_SIZE - gx as f32;
+        let v = (world_z - self.position.z) / SECTOR_SIZE - gz as f32;
+        Some((gx, gz, u, v))
+    }
+
     /// Convert grid coordinates to world position (returns corner of sector)
     pub fn grid_to_world(&self, x: usize, z: usize) -> Vec3 {
         Vec3::new(
@@ -467,12 +1190,36 @@ impl Room {
         )
     }
 
-    /// Recalculate bounds from sectors (call after loading from file)
+    /// Give every wall without an id (new, or loaded from file) one, and fresh ids
+    /// to copies of walls already in the room (the first keeps the id)
+    pub fn assign_wall_ids(&mut self) {
+        const EDGES: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+        // Past every id in use, so a fresh one can't collide with a wall later on
+        let mut next = self.next_wall_id.max(1);
+        for (_, _, sector) in self.iter_sectors() {
+            for dir in EDGES {
+                next = sector.walls(dir).iter().map(|w| w.id.0 + 1).fold(next, u32::max);
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        for sector in self.sectors.iter_mut().flatten().flatten() {
+            for dir in EDGES {
+                for wall in sector.walls_mut(dir) {
+                    if wall.id.0 == 0 || !seen.insert(wall.id) {
+                        wall.id = WallId(next);
+                        seen.insert(wall.id);
+                        next += 1;
+                    }
+                }
+            }
+        }
+        self.next_wall_id = next;
+    }
+
+    /// Recalculate bounds from sectors (call after loading from file). A room with
+    /// no floors, ceilings or walls gets empty bounds.
     pub fn recalculate_bounds(&mut self) {
-        self.bounds = Aabb::new(
-            Vec3::new(f32::MAX, f32::MAX, f32::MAX),
-            Vec3::new(f32::MIN, f32::MIN, f32::MIN),
-        );
+        self.bounds = Aabb::empty();
 
         for x in 0..self.width {
             for z in 0..self.depth {
@@ -544,20 +1291,19 @@ impl Room {
         self.bounds.contains(relative)
     }
 
-    /// Get world-space bounds
+    /// Get world-space bounds (empty for a room without geometry)
     pub fn world_bounds(&self) -> Aabb {
-        Aabb::new(
-            Vec3::new(
-                self.bounds.min.x + self.position.x,
-                self.bounds.min.y + self.position.y,
-                self.bounds.min.z + self.position.z,
-            ),
-            Vec3::new(
-                self.bounds.max.x + self.position.x,
-                self.bounds.max.y + self.position.y,
-                self.bounds.max.z + self.position.z,
-            ),
-        )
+        self.bounds.translated(self.position)
+    }
+
+    /// World-space center of the room's geometry (its position if it has none)
+    pub fn world_center(&self) -> Vec3 {
+        self.position + self.bounds.center()
+    }
+
+    /// Does the room have no floors, ceilings or walls (per its last computed bounds)?
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
     }
 
     /// Iterate over all sectors with their grid coordinates
@@ -572,117 +1318,230 @@ impl Room {
     /// Convert room geometry to rasterizer format (vertices + faces)
     /// Returns world-space vertices ready for rendering
     pub fn to_render_data_with_textures<F>(&self, resolve_texture: F) -> (Vec<Vertex>, Vec<RasterFace>)
+    where
+        F: Fn(&TextureRef) -> Option<usize>,
+    {
+        self.to_render_data_merged(false, resolve_texture)
+    }
+
+    /// Like `to_render_data_with_textures`, optionally drawing rectangles of
+    /// identical flat floors and ceilings as single quads
+    /// (`RasterSettings::merge_coplanar`). Collision always stays per sector.
+    pub fn to_render_data_merged<F>(&self, merge_coplanar: bool, resolve_texture: F) -> (Vec<Vertex>, Vec<RasterFace>)
+    where
+        F: Fn(&TextureRef) -> Option<usize>,
+    {
+        self.build_render_data(merge_coplanar, false, resolve_texture)
+    }
+
+    /// Render data the way `settings` asks for it: merged coplanar faces
+    /// (`merge_coplanar`), normals averaged across shared edges (`smooth_normals`)
+    /// and the room's point lights in the vertex colors, unless the caller lights
+    /// them every frame (`dynamic_lights`, see `light_vertices`)
+    pub fn to_render_data_for<F>(&self, settings: &RasterSettings, resolve_texture: F) -> (Vec<Vertex>, Vec<RasterFace>)
+    where
+        F: Fn(&TextureRef) -> Option<usize>,
+    {
+        let (mut vertices, faces) = self.build_render_data(settings.merge_coplanar, settings.smooth_normals, resolve_texture);
+        if !settings.dynamic_lights {
+            self.light_vertices(&mut vertices);
+        }
+        (vertices, faces)
+    }
+
+    fn build_render_data<F>(&self, merge_coplanar: bool, smooth: bool, resolve_texture: F) -> (Vec<Vertex>, Vec<RasterFace>)
     where
         F: Fn(&TextureRef) -> Option<usize>,
     {
         let mut vertices = Vec::new();
         let mut faces = Vec::new();
+        // Surface kind of every vertex pushed so far (for smoothing)
+        let mut kinds = Vec::new();
+
+        // Merged rectangles first; the sectors they cover are skipped below
+        let mut merged = [vec![false; self.width * self.depth], vec![false; self.width * self.depth]];
+        if merge_coplanar {
+            for (is_floor, covered) in [true, false].into_iter().zip(merged.iter_mut()) {
+                for (x, z, w, d) in self.coplanar_runs(is_floor) {
+                    let Some(face) = self.horizontal_face(x, z, is_floor) else { continue };
+                    for (cx, cz) in (x..x + w).flat_map(|cx| (z..z + d).map(move |cz| (cx, cz))) {
+                        covered[cz * self.width + cx] = true;
+                    }
+                    // Repeat the texture once per sector. v runs up to 1 at the south
+                    // edge so the sampled 1 - v stays positive and every sector gets
+                    // the same texels as when drawn on its own.
+                    let mut quad = face.clone();
+                    quad.uv = Some([
+                        Vec2::new(0.0, 1.0 - d as f32),
+                        Vec2::new(w as f32, 1.0 - d as f32),
+                        Vec2::new(w as f32, 1.0),
+                        Vec2::new(0.0, 1.0),
+                    ]);
+                    quad.subdivision = 1;
+                    quad.detail.clear();
+                    let base_x = self.position.x + (x as f32) * SECTOR_SIZE;
+                    let base_z = self.position.z + (z as f32) * SECTOR_SIZE;
+                    let span = HorizontalSpan { face: &quad, base_x, base_z, span: (w, d), is_floor };
+                    self.add_horizontal_face_to_render_data(&mut vertices, &mut faces, span, &resolve_texture);
+                    kinds.resize(vertices.len(), if is_floor { SurfaceKind::Floor } else { SurfaceKind::Ceiling });
+                }
+            }
+        }
 
         for (grid_x, grid_z, sector) in self.iter_sectors() {
             let base_x = self.position.x + (grid_x as f32) * SECTOR_SIZE;
             let base_z = self.position.z + (grid_z as f32) * SECTOR_SIZE;
+            let [floor_merged, ceiling_merged] = merged.each_ref().map(|m| m[grid_z * self.width + grid_x]);
 
-            // Render floor
-            if let Some(floor) = &sector.floor {
-                self.add_horizontal_face_to_render_data(
-                    &mut vertices,
-                    &mut faces,
-                    floor,
-                    base_x,
-                    base_z,
-                    true, // is_floor
-                    &resolve_texture,
-                );
+            // Render floor (openings to the room below are left as holes)
+            if let Some(floor) = sector.floor.as_ref().filter(|f| f.opening.is_none() && f.visible && !floor_merged) {
+                let span = HorizontalSpan { face: floor, base_x, base_z, span: (1, 1), is_floor: true };
+                self.add_horizontal_face_to_render_data(&mut vertices, &mut faces, span, &resolve_texture);
+                kinds.resize(vertices.len(), SurfaceKind::Floor);
             }
 
             // Render ceiling
-            if let Some(ceiling) = &sector.ceiling {
-                self.add_horizontal_face_to_render_data(
-                    &mut vertices,
-                    &mut faces,
-                    ceiling,
-                    base_x,
-                    base_z,
-                    false, // is_ceiling
-                    &resolve_texture,
-                );
+            if let Some(ceiling) = sector.ceiling.as_ref().filter(|c| c.opening.is_none() && c.visible && !ceiling_merged) {
+                let span = HorizontalSpan { face: ceiling, base_x, base_z, span: (1, 1), is_floor: false };
+                self.add_horizontal_face_to_render_data(&mut vertices, &mut faces, span, &resolve_texture);
+                kinds.resize(vertices.len(), SurfaceKind::Ceiling);
             }
 
-            // Render walls on each edge
-            for wall in &sector.walls_north {
-                self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::North, &resolve_texture);
-            }
-            for wall in &sector.walls_east {
-                self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::East, &resolve_texture);
-            }
-            for wall in &sector.walls_south {
-                self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::South, &resolve_texture);
-            }
-            for wall in &sector.walls_west {
-                self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, Direction::West, &resolve_texture);
+            // Render walls on each edge (invisible walls only collide)
+            for direction in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                for wall in sector.walls(direction).iter().filter(|w| w.visible) {
+                    self.add_wall_to_render_data(&mut vertices, &mut faces, wall, base_x, base_z, direction, &resolve_texture);
+                }
+                kinds.resize(vertices.len(), SurfaceKind::Wall(direction));
             }
         }
 
+        if smooth {
+            smooth_normals(&mut vertices, &kinds);
+        }
         (vertices, faces)
     }
 
+    /// Floor (or ceiling) of a sector, if it's drawn at all
+    fn horizontal_face(&self, x: usize, z: usize, is_floor: bool) -> Option<&HorizontalFace> {
+        let sector = self.get_sector(x, z)?;
+        let face = if is_floor { sector.floor.as_ref() } else { sector.ceiling.as_ref() };
+        face.filter(|f| f.opening.is_none() && f.visible)
+    }
+
+    /// Rectangles (x, z, width, depth) of neighbouring floors (or ceilings) that
+    /// can be drawn as one quad: flat at the same height, same texture, blend mode
+    /// and uniform vertex color, default UVs. Greedy, row by row from the NW
+    /// corner; single sectors that don't merge with anything are left out.
+    fn coplanar_runs(&self, is_floor: bool) -> Vec<(usize, usize, usize, usize)> {
+        let mergeable = |x: usize, z: usize| {
+            self.horizontal_face(x, z, is_floor).filter(|f| {
+                f.is_flat() && f.uv.is_none() && f.colors.iter().all(|&c| c == f.colors[0])
+            })
+        };
+        let same = |a: &HorizontalFace, b: &HorizontalFace| {
+            (a.heights[0] - b.heights[0]).abs() < 0.001
+                && a.texture == b.texture
+                && a.blend_mode == b.blend_mode
+                && a.colors[0] == b.colors[0]
+        };
+
+        let mut used = vec![false; self.width * self.depth];
+        let mut runs = Vec::new();
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                if used[z * self.width + x] {
+                    continue;
+                }
+                let Some(face) = mergeable(x, z) else { continue };
+                let fits = |cx: usize, cz: usize, used: &[bool]| {
+                    !used[cz * self.width + cx] && mergeable(cx, cz).is_some_and(|f| same(face, f))
+                };
+                let mut w = 1;
+                while x + w < self.width && fits(x + w, z, &used) {
+                    w += 1;
+                }
+                let mut d = 1;
+                while z + d < self.depth && (x..x + w).all(|cx| fits(cx, z + d, &used)) {
+                    d += 1;
+                }
+                if w * d > 1 {
+                    for cz in z..z + d {
+                        used[cz * self.width + x..cz * self.width + x + w].fill(true);
+                    }
+                    runs.push((x, z, w, d));
+                }
+            }
+        }
+        runs
+    }
+
     /// Helper to add a horizontal face (floor or ceiling) to render data
     fn add_horizontal_face_to_render_data<F>(
         &self,
         vertices: &mut Vec<Vertex>,
         faces: &mut Vec<RasterFace>,
-        face: &HorizontalFace,
-        base_x: f32,
-        base_z: f32,
-        is_floor: bool,
+        span: HorizontalSpan,
         resolve_texture: &F,
     )
     where
         F: Fn(&TextureRef) -> Option<usize>,
     {
-        let base_idx = vertices.len();
-
-        // Corner positions: NW, NE, SE, SW
-        let corners = [
-            Vec3::new(base_x, face.heights[0], base_z),                         // NW
-            Vec3::new(base_x + SECTOR_SIZE, face.heights[1], base_z),           // NE
-            Vec3::new(base_x + SECTOR_SIZE, face.heights[2], base_z + SECTOR_SIZE), // SE
-            Vec3::new(base_x, face.heights[3], base_z + SECTOR_SIZE),           // SW
-        ];
+        let HorizontalSpan { face, base_x, base_z, span, is_floor } = span;
+        let uvs = face.uvs();
+        let texture_id = resolve_texture(&face.texture).unwrap_or(0);
 
-        // Calculate normal from cross product
-        // For floor (facing up): use edge2 x edge1 to get +Y normal
-        // For ceiling (facing down): use edge1 x edge2 to get -Y normal
-        let edge1 = corners[1] - corners[0]; // NW -> NE (along +X)
-        let edge2 = corners[3] - corners[0]; // NW -> SW (along +Z)
-        let normal = if is_floor {
-            edge2.cross(edge1).normalize() // +Z x +X = +Y (up)
-        } else {
-            edge1.cross(edge2).normalize() // +X x +Z = -Y (down)
+        // UV and vertex color at a point of the face, bilinear across the corners
+        let lerp = |a: f32, b: f32, t: f32| a * (1.0 - t) + b * t;
+        let uv_at = |u: f32, v: f32| {
+            Vec2::new(
+                lerp(lerp(uvs[0].x, uvs[1].x, u), lerp(uvs[3].x, uvs[2].x, u), v),
+                lerp(lerp(uvs[0].y, uvs[1].y, u), lerp(uvs[3].y, uvs[2].y, u), v),
+            )
+        };
+        let color_at = |u: f32, v: f32| {
+            let north = face.colors[0].lerp(face.colors[1], u);
+            let south = face.colors[3].lerp(face.colors[2], u);
+            north.lerp(south, v)
         };
 
-        // Default UVs
-        let uvs = face.uv.unwrap_or([
-            Vec2::new(0.0, 0.0),
-            Vec2::new(1.0, 0.0),
-            Vec2::new(1.0, 1.0),
-            Vec2::new(0.0, 1.0),
-        ]);
+        // One quad per grid cell (a single cell unless subdivided)
+        let n = face.cells();
+        let cell_x = SECTOR_SIZE * span.0 as f32 / n as f32;
+        let cell_z = SECTOR_SIZE * span.1 as f32 / n as f32;
+        for cz in 0..n {
+            for cx in 0..n {
+                let base_idx = vertices.len();
+                let heights = face.cell_heights(cx, cz);
+                let x0 = base_x + cx as f32 * cell_x;
+                let z0 = base_z + cz as f32 * cell_z;
+
+                // Corner positions: NW, NE, SE, SW
+                let corners = [
+                    Vec3::new(x0, heights[0], z0),                   // NW
+                    Vec3::new(x0 + cell_x, heights[1], z0),          // NE
+                    Vec3::new(x0 + cell_x, heights[2], z0 + cell_z), // SE
+                    Vec3::new(x0, heights[3], z0 + cell_z),          // SW
+                ];
+                let grid = [(cx, cz), (cx + 1, cz), (cx + 1, cz + 1), (cx, cz + 1)];
 
-        // Add vertices
-        for i in 0..4 {
-            vertices.push(Vertex::new(corners[i], uvs[i], normal));
-        }
+                let normal = quad_normal(&corners, is_floor);
 
-        let texture_id = resolve_texture(&face.texture).unwrap_or(0);
+                // Add vertices
+                for i in 0..4 {
+                    let (u, v) = (grid[i].0 as f32 / n as f32, grid[i].1 as f32 / n as f32);
+                    vertices.push(Vertex::with_color(corners[i], uv_at(u, v), normal, color_at(u, v)));
+                }
 
-        // Winding order: floor = CCW from above, ceiling = CW from above (so it faces down)
-        if is_floor {
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 1, base_idx + 2, texture_id));
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 3, texture_id));
-        } else {
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 3, base_idx + 2, texture_id));
-            faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 1, texture_id));
+                // Winding order: floor = CCW from above, ceiling = CW from above (so it faces down)
+                if is_floor {
+                    faces.push(RasterFace::with_texture(base_idx, base_idx + 1, base_idx + 2, texture_id));
+                    faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 3, texture_id));
+                } else {
+                    faces.push(RasterFace::with_texture(base_idx, base_idx + 3, base_idx + 2, texture_id));
+                    faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 1, texture_id));
+                }
+            }
         }
     }
 
@@ -702,62 +1561,14 @@ impl Room {
     {
         let base_idx = vertices.len();
 
-        // Wall corners based on direction
-        // Each wall has 4 corners: bottom-left, bottom-right, top-right, top-left (from inside room)
-        // wall.heights = [bottom-left, bottom-right, top-right, top-left]
-        let (corners, normal) = match direction {
-            Direction::North => {
-                // Wall at -Z edge, facing +Z (into room)
-                let corners = [
-                    Vec3::new(base_x, wall.heights[0], base_z),                    // bottom-left
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z),      // bottom-right
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z),      // top-right
-                    Vec3::new(base_x, wall.heights[3], base_z),                    // top-left
-                ];
-                (corners, Vec3::new(0.0, 0.0, 1.0))
-            }
-            Direction::East => {
-                // Wall at +X edge, facing -X (into room)
-                let corners = [
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[1], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[2], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z),
-                ];
-                (corners, Vec3::new(-1.0, 0.0, 0.0))
-            }
-            Direction::South => {
-                // Wall at +Z edge, facing -Z (into room)
-                let corners = [
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[0], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x, wall.heights[1], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x, wall.heights[2], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x + SECTOR_SIZE, wall.heights[3], base_z + SECTOR_SIZE),
-                ];
-                (corners, Vec3::new(0.0, 0.0, -1.0))
-            }
-            Direction::West => {
-                // Wall at -X edge, facing +X (into room)
-                let corners = [
-                    Vec3::new(base_x, wall.heights[0], base_z + SECTOR_SIZE),
-                    Vec3::new(base_x, wall.heights[1], base_z),
-                    Vec3::new(base_x, wall.heights[2], base_z),
-                    Vec3::new(base_x, wall.heights[3], base_z + SECTOR_SIZE),
-                ];
-                (corners, Vec3::new(1.0, 0.0, 0.0))
-            }
-        };
+        // Corners over the wall's span of the edge, facing into the room
+        let corners = wall.corners(base_x, base_z, direction);
+        let normal = direction.wall_normal();
 
-        // Default UVs for wall
-        let uvs = wall.uv.unwrap_or([
-            Vec2::new(0.0, 1.0),  // bottom-left
-            Vec2::new(1.0, 1.0),  // bottom-right
-            Vec2::new(1.0, 0.0),  // top-right
-            Vec2::new(0.0, 0.0),  // top-left
-        ]);
+        let uvs = wall.uvs();
 
         for i in 0..4 {
-            vertices.push(Vertex::new(corners[i], uvs[i], normal));
+            vertices.push(Vertex::with_color(corners[i], uvs[i], normal, wall.colors[i]));
         }
 
         let texture_id = resolve_texture(&wall.texture).unwrap_or(0);
@@ -779,6 +1590,15 @@ pub struct EditorLayoutConfig {
     pub left_split: f32,
     /// Right vertical split ratio (texture palette | properties)
     pub right_panel_split: f32,
+    /// Collapsed panels (left panels, right panels, room properties, properties)
+    #[serde(default)]
+    pub main_collapsed: bool,
+    #[serde(default)]
+    pub right_collapsed: bool,
+    #[serde(default)]
+    pub left_collapsed: bool,
+    #[serde(default)]
+    pub right_panel_collapsed: bool,
 }
 
 impl Default for EditorLayoutConfig {
@@ -788,26 +1608,149 @@ impl Default for EditorLayoutConfig {
             right_split: 0.75,
             left_split: 0.6,
             right_panel_split: 0.6,
+            main_collapsed: false,
+            right_collapsed: false,
+            left_collapsed: false,
+            right_panel_collapsed: false,
         }
     }
 }
 
+/// Camera bookmark slots per level (Ctrl+1..5 to store, 1..5 to recall)
+pub const CAMERA_BOOKMARK_SLOTS: usize = 5;
+
+/// A stored editor 3D camera view (saved with level)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    #[serde(default, deserialize_with = "super::limits::string")]
+    pub name: String,
+    pub position: Vec3,
+    /// Pitch
+    pub rotation_x: f32,
+    /// Yaw
+    pub rotation_y: f32,
+}
+
+fn no_bookmarks(bookmarks: &[Option<CameraBookmark>; CAMERA_BOOKMARK_SLOTS]) -> bool {
+    bookmarks.iter().all(Option::is_none)
+}
+
+/// PS1 effect settings stored with a level, so it looks the way its author saw it.
+/// Applied over the viewer's own settings when the level is opened or played.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelRenderSettings {
+    pub affine_textures: bool,
+    pub vertex_snap: bool,
+    pub shading: ShadingMode,
+    pub low_resolution: bool,
+    pub dithering: bool,
+    #[serde(default)]
+    pub color_depth: ColorDepth,
+    #[serde(default)]
+    pub smooth_normals: bool,
+}
+
+impl LevelRenderSettings {
+    /// The stored parts of `settings`
+    pub fn from_raster(settings: &RasterSettings) -> Self {
+        Self {
+            affine_textures: settings.affine_textures,
+            vertex_snap: settings.vertex_snap,
+            shading: settings.shading,
+            low_resolution: settings.low_resolution,
+            dithering: settings.dithering,
+            color_depth: settings.color_depth,
+            smooth_normals: settings.smooth_normals,
+        }
+    }
+
+    /// Overwrite the stored parts of `settings`
+    pub fn apply(&self, settings: &mut RasterSettings) {
+        settings.affine_textures = self.affine_textures;
+        settings.vertex_snap = self.vertex_snap;
+        settings.shading = self.shading;
+        settings.low_resolution = self.low_resolution;
+        settings.dithering = self.dithering;
+        settings.color_depth = self.color_depth;
+        settings.smooth_normals = self.smooth_normals;
+    }
+}
+
 /// The entire level
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Level {
+    #[serde(deserialize_with = "super::limits::rooms")]
     pub rooms: Vec<Room>,
     /// Editor layout configuration (optional, uses default if missing)
     #[serde(default)]
     pub editor_layout: EditorLayoutConfig,
+    /// Editor camera bookmarks, by slot
+    #[serde(default, skip_serializing_if = "no_bookmarks")]
+    pub camera_bookmarks: [Option<CameraBookmark>; CAMERA_BOOKMARK_SLOTS],
+    /// Sun direction: toward the light, world space (Y up)
+    #[serde(default = "default_light_dir")]
+    pub light_dir: Vec3,
+    /// Field of view in game mode, degrees (the editor viewport has its own)
+    #[serde(default = "default_game_fov")]
+    pub game_fov: f32,
+    /// Render settings to view this level with (None = each viewer's own)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_settings: Option<LevelRenderSettings>,
+    /// Substitute texture pack for each pack this level uses that may be missing
+    /// where it's opened (picked when loading, see `TextureIndex`)
+    #[serde(default, skip_serializing_if = "PackAliases::is_empty", deserialize_with = "super::limits::pack_aliases")]
+    pub pack_aliases: PackAliases,
 }
 
-impl Level {
-    pub fn new() -> Self {
+fn default_light_dir() -> Vec3 {
+    crate::rasterizer::RasterSettings::default().light_dir
+}
+
+fn default_game_fov() -> f32 {
+    crate::rasterizer::DEFAULT_FOV_DEGREES
+}
+
+/// Light direction from azimuth (radians, 0 = +Z, clockwise toward +X as seen
+/// from above) and elevation (radians above the horizon)
+pub fn light_dir_from_angles(azimuth: f32, elevation: f32) -> Vec3 {
+    Vec3::new(
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+        elevation.cos() * azimuth.cos(),
+    )
+}
+
+/// Inverse of `light_dir_from_angles`: (azimuth, elevation) in radians
+pub fn light_dir_angles(dir: Vec3) -> (f32, f32) {
+    let dir = dir.normalize();
+    (dir.x.atan2(dir.z), dir.y.clamp(-1.0, 1.0).asin())
+}
+
+impl Default for Level {
+    fn default() -> Self {
         Self {
             rooms: Vec::new(),
             editor_layout: EditorLayoutConfig::default(),
+            camera_bookmarks: Default::default(),
+            light_dir: default_light_dir(),
+            game_fov: default_game_fov(),
+            render_settings: None,
+            pack_aliases: PackAliases::new(),
         }
     }
+}
+
+impl Level {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Light direction used to shade a room (its override, else the level's)
+    pub fn room_light_dir(&self, room_idx: usize) -> Vec3 {
+        self.rooms.get(room_idx)
+            .and_then(|r| r.light_dir)
+            .unwrap_or(self.light_dir)
+    }
 
     /// Add a room and return its index
     pub fn add_room(&mut self, room: Room) -> usize {
@@ -816,6 +1759,93 @@ impl Level {
         id
     }
 
+    /// Highest floor at or below `pos`: (room index, floor face, floor Y).
+    /// Floor height and material lookups both go through this, so they always
+    /// agree on which face is underfoot.
+    pub fn floor_at(&self, pos: Vec3) -> Option<(usize, &HorizontalFace, f32)> {
+        self.rooms.iter().enumerate()
+            .filter_map(|(i, room)| {
+                let (face, height) = room.floor_at(pos.x, pos.z)?;
+                (height <= pos.y).then_some((i, face, height))
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+    }
+
+    /// Material of the floor under `pos` (see `floor_at`)
+    pub fn floor_material_at(&self, pos: Vec3) -> Option<SurfaceMaterial> {
+        self.floor_at(pos).map(|(_, face, _)| face.material)
+    }
+
+    /// Index of the room with the given id
+    pub fn room_index(&self, id: usize) -> Option<usize> {
+        self.rooms.iter().position(|r| r.id == id)
+    }
+
+    /// Id for a new room (one past the highest in use, so ids are never reused)
+    pub fn next_room_id(&self) -> usize {
+        self.rooms.iter().map(|r| r.id).max().map_or(0, |id| id + 1)
+    }
+
+    /// Remove a room, dropping every portal that led into it (floor/ceiling openings
+    /// into it close again). Returns the removed room and the number of portals dropped.
+    pub fn remove_room(&mut self, room_idx: usize) -> Option<(Room, usize)> {
+        if room_idx >= self.rooms.len() {
+            return None;
+        }
+        let removed = self.rooms.remove(room_idx);
+        let dropped = self.drop_portals_into(&[removed.id]);
+        Some((removed, dropped))
+    }
+
+    /// Remove several rooms at once (indices may repeat or be out of range), dropping
+    /// every portal into any of them. Returns (rooms removed, portals dropped).
+    pub fn remove_rooms(&mut self, room_indices: &[usize]) -> (usize, usize) {
+        let ids: Vec<usize> = room_indices.iter()
+            .filter_map(|&idx| self.rooms.get(idx).map(|r| r.id))
+            .collect();
+        let before = self.rooms.len();
+        self.rooms.retain(|r| !ids.contains(&r.id));
+        let removed = before - self.rooms.len();
+        (removed, self.drop_portals_into(&ids))
+    }
+
+    /// Drop the portals leading into rooms with these ids and close the openings
+    /// into them. Returns the number of portals dropped.
+    fn drop_portals_into(&mut self, ids: &[usize]) -> usize {
+        let mut dropped = 0;
+        for room in &mut self.rooms {
+            let before = room.portals.len();
+            room.portals.retain(|p| !ids.contains(&p.target_room));
+            dropped += before - room.portals.len();
+            room.close_openings(|target| !ids.contains(&target));
+        }
+        dropped
+    }
+
+    /// Move a room to another position in the room list (organization only;
+    /// portals refer to ids so they are unaffected). Returns false if out of range.
+    pub fn move_room(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.rooms.len() || to >= self.rooms.len() {
+            return false;
+        }
+        let room = self.rooms.remove(from);
+        self.rooms.insert(to, room);
+        true
+    }
+
+    /// Fix up levels saved before room ids were unique: if any id repeats, every room
+    /// gets its index as id (older portals stored the target's index). Returns true if changed.
+    pub fn ensure_unique_room_ids(&mut self) -> bool {
+        let mut seen = std::collections::HashSet::new();
+        if self.rooms.iter().all(|r| seen.insert(r.id)) {
+            return false;
+        }
+        for (i, room) in self.rooms.iter_mut().enumerate() {
+            room.id = i;
+        }
+        true
+    }
+
     /// Find which room contains a point
     pub fn find_room_at(&self, point: Vec3) -> Option<usize> {
         for (i, room) in self.rooms.iter().enumerate() {
@@ -840,6 +1870,160 @@ impl Level {
         // Fall back to linear search
         self.find_room_at(point)
     }
+
+    /// Deep-clone a room, placing the copy one room-width along +X.
+    /// The copy gets a fresh id and is appended to the room list.
+    /// Portals to other rooms are dropped (a portal back into the source room is remapped
+    /// to the copy) and floor/ceiling openings are closed. Returns (new room index, portals dropped).
+    pub fn duplicate_room(&mut self, room_idx: usize) -> Option<(usize, usize)> {
+        let new_id = self.next_room_id();
+        let source = self.rooms.get(room_idx)?;
+        let new_idx = self.rooms.len();
+
+        let mut copy = source.clone();
+        copy.id = new_id;
+        copy.position.x += copy.width as f32 * SECTOR_SIZE;
+
+        let before = copy.portals.len();
+        copy.portals.retain(|p| p.target_room == source.id);
+        for portal in &mut copy.portals {
+            portal.target_room = new_id;
+        }
+        let dropped = before - copy.portals.len();
+        copy.close_openings(|_| false);
+
+        copy.recalculate_bounds();
+        self.rooms.push(copy);
+        Some((new_idx, dropped))
+    }
+
+    /// Texture references of every face in the level
+    pub fn texture_refs(&self) -> impl Iterator<Item = &TextureRef> {
+        self.rooms.iter()
+            .flat_map(|room| room.sectors.iter().flatten().flatten())
+            .flat_map(|sector| sector.textures())
+    }
+
+    /// Indices of rooms without any geometry (nothing to draw, stand in or enter).
+    /// Used to warn on save.
+    pub fn empty_rooms(&self) -> Vec<usize> {
+        self.rooms.iter().enumerate().filter(|(_, room)| room.is_empty()).map(|(i, _)| i).collect()
+    }
+
+    /// Count faces that are neither drawn nor collided with, so do nothing.
+    /// Used to warn on save.
+    pub fn inert_faces(&self) -> usize {
+        let sectors = self.rooms.iter().flat_map(|room| room.sectors.iter().flatten().flatten());
+        sectors.map(|sector| {
+            let horizontal = sector.floor.iter().chain(&sector.ceiling)
+                .filter(|f| !f.visible && !f.walkable && f.opening.is_none())
+                .count();
+            let walls = [&sector.walls_north, &sector.walls_east, &sector.walls_south, &sector.walls_west]
+                .into_iter()
+                .flatten()
+                .filter(|w| !w.visible && !w.solid)
+                .count();
+            horizontal + walls
+        }).sum()
+    }
+
+    /// Replace face textures for which `f` returns a new reference. Returns how many
+    /// faces changed.
+    pub fn retarget_textures(&mut self, mut f: impl FnMut(&TextureRef) -> Option<TextureRef>) -> usize {
+        let mut count = 0;
+        for room in &mut self.rooms {
+            for sector in room.sectors.iter_mut().flatten().flatten() {
+                for texture in sector.textures_mut() {
+                    if let Some(new) = f(texture) {
+                        *texture = new;
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Faces in `scope` using each texture, sorted by pack/name
+    pub fn texture_usage(&self, scope: &TextureScope) -> Vec<(TextureRef, usize)> {
+        let mut usage: std::collections::BTreeMap<(&str, &str), (&TextureRef, usize)> = Default::default();
+        for (room_idx, room) in self.rooms.iter().enumerate() {
+            for (x, z, sector) in room.iter_sectors() {
+                if !scope.contains(room_idx, x, z) {
+                    continue;
+                }
+                for tex in sector.textures() {
+                    usage.entry((tex.pack.as_str(), tex.name.as_str())).or_insert((tex, 0)).1 += 1;
+                }
+            }
+        }
+        usage.into_values().map(|(tex, count)| (tex.clone(), count)).collect()
+    }
+
+    /// Point every face in `scope` that uses `from` at `to`. Returns how many faces changed.
+    pub fn replace_texture(&mut self, from: &TextureRef, to: &TextureRef, scope: &TextureScope) -> usize {
+        if from == to {
+            return 0;
+        }
+        let mut count = 0;
+        for (room_idx, room) in self.rooms.iter_mut().enumerate() {
+            for (x, column) in room.sectors.iter_mut().enumerate() {
+                for (z, sector) in column.iter_mut().enumerate() {
+                    let Some(sector) = sector else { continue };
+                    if !scope.contains(room_idx, x, z) {
+                        continue;
+                    }
+                    for texture in sector.textures_mut().filter(|t| *t == from) {
+                        *texture = to.clone();
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Count portals involving a room (its own, and other rooms' portals into it)
+    /// whose world-space vertices no longer line up with a portal leading back.
+    /// Used to warn after a room has been moved.
+    pub fn misaligned_portals(&self, room_idx: usize) -> usize {
+        const EPSILON: f32 = 1.0;
+
+        let world_vertices = |room: &Room, portal: &Portal| -> [Vec3; 4] {
+            portal.vertices.map(|v| v + room.position)
+        };
+
+        let Some(room_id) = self.rooms.get(room_idx).map(|r| r.id) else {
+            return 0;
+        };
+
+        let mut count = 0;
+        for (from_idx, room) in self.rooms.iter().enumerate() {
+            for portal in &room.portals {
+                if from_idx != room_idx && portal.target_room != room_id {
+                    continue;
+                }
+
+                let verts = world_vertices(room, portal);
+                let target = self.room_index(portal.target_room).map(|i| &self.rooms[i]);
+                let aligned = target.is_some_and(|target| {
+                    target.portals.iter()
+                        .filter(|back| back.target_room == room.id)
+                        .any(|back| {
+                            let back_verts = world_vertices(target, back);
+                            verts.iter().all(|v| {
+                                back_verts.iter().any(|b| (*v - *b).len() < EPSILON)
+                            })
+                        })
+                });
+
+                if !aligned {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
 }
 
 /// Create an empty level with a single starter room (floor only)
@@ -887,3 +2071,431 @@ pub fn create_test_level() -> Level {
 
     level
 }
+
+/// Room with floor and ceiling on every sector and walls around the outside,
+/// except along `open_side` (where a portal will go)
+fn box_room(id: usize, position: Vec3, width: usize, depth: usize, height: f32, open_side: Option<Direction>) -> Room {
+    let floor_tex = TextureRef::new("retro-texture-pack", "FLOOR_1A");
+    let wall_tex = TextureRef::new("retro-texture-pack", "WALL_1A");
+
+    let mut room = Room::new(id, position, width, depth);
+    for x in 0..width {
+        for z in 0..depth {
+            room.set_floor(x, z, 0.0, floor_tex.clone());
+            room.set_ceiling(x, z, height, floor_tex.clone());
+
+            let edges = [
+                (Direction::North, z == 0),
+                (Direction::East, x == width - 1),
+                (Direction::South, z == depth - 1),
+                (Direction::West, x == 0),
+            ];
+            for (dir, outside) in edges {
+                if outside && open_side != Some(dir) {
+                    room.add_wall(x, z, dir, 0.0, height, wall_tex.clone());
+                }
+            }
+        }
+    }
+    room.recalculate_bounds();
+    room
+}
+
+/// Template: a 1x8 walled corridor
+pub fn create_corridor_level() -> Level {
+    let mut level = Level::new();
+    level.add_room(box_room(0, Vec3::ZERO, 1, 8, 1024.0, None));
+    level
+}
+
+/// Template: two 2x2 rooms side by side, joined by a portal on their shared edge
+pub fn create_two_room_level() -> Level {
+    let mut level = Level::new();
+    let size = 2.0 * SECTOR_SIZE;
+    let height = 1024.0;
+    level.add_room(box_room(0, Vec3::ZERO, 2, 2, height, Some(Direction::East)));
+    level.add_room(box_room(1, Vec3::new(size, 0.0, 0.0), 2, 2, height, Some(Direction::West)));
+
+    // Shared edge at x = size (world), corners in each room's local space
+    let edge = |x: f32| [
+        Vec3::new(x, 0.0, 0.0),
+        Vec3::new(x, 0.0, size),
+        Vec3::new(x, height, size),
+        Vec3::new(x, height, 0.0),
+    ];
+    level.rooms[0].add_portal(1, edge(size), Vec3::new(-1.0, 0.0, 0.0));
+    level.rooms[1].add_portal(0, edge(0.0), Vec3::new(1.0, 0.0, 0.0));
+    level
+}
+
+/// Template: open 8x8 terrain with gently rolling floors (no walls or ceiling)
+pub fn create_terrain_level() -> Level {
+    const SIZE: usize = 8;
+    let texture = TextureRef::new("retro-texture-pack", "FLOOR_1A");
+
+    // Height at a grid corner, rounded to quarter clicks so corners line up exactly
+    let corner = |x: usize, z: usize| -> f32 {
+        let h = ((x as f32 * 0.9).sin() + (z as f32 * 0.7).cos()) * 256.0;
+        (h / 64.0).round() * 64.0
+    };
+
+    let mut room = Room::new(0, Vec3::ZERO, SIZE, SIZE);
+    for x in 0..SIZE {
+        for z in 0..SIZE {
+            let mut floor = HorizontalFace::flat(0.0, texture.clone());
+            floor.heights = [corner(x, z), corner(x + 1, z), corner(x + 1, z + 1), corner(x, z + 1)];
+            floor.material = SurfaceMaterial::Grass;
+            room.ensure_sector(x, z).floor = Some(floor);
+        }
+    }
+    room.recalculate_bounds();
+
+    let mut level = Level::new();
+    level.add_room(room);
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Single-sector room with floor and ceiling at the given world XZ
+    fn sector_room(id: usize, x: f32, z: f32) -> Room {
+        let mut room = Room::new(id, Vec3::new(x, 0.0, z), 1, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.set_ceiling(0, 0, 1024.0, TextureRef::none());
+        room.recalculate_bounds();
+        room
+    }
+
+    /// Portal pair on the shared edge between `level.rooms[a]` and `level.rooms[b]`,
+    /// given as world-space corners
+    fn connect(level: &mut Level, a: usize, b: usize, corners: [Vec3; 4], normal: Vec3) {
+        let (id_a, id_b) = (level.rooms[a].id, level.rooms[b].id);
+        let pos_a = level.rooms[a].position;
+        let pos_b = level.rooms[b].position;
+        level.rooms[a].add_portal(id_b, corners.map(|v| v - pos_a), normal * -1.0);
+        level.rooms[b].add_portal(id_a, corners.map(|v| v - pos_b), normal);
+    }
+
+    /// A at the origin, B east of it, C north of it; portals A-B and A-C
+    fn three_room_level() -> Level {
+        let mut level = Level::new();
+        level.add_room(sector_room(0, 0.0, 0.0));
+        level.add_room(sector_room(1, SECTOR_SIZE, 0.0));
+        level.add_room(sector_room(2, 0.0, SECTOR_SIZE));
+        let s = SECTOR_SIZE;
+        connect(&mut level, 0, 1, [
+            Vec3::new(s, 0.0, 0.0), Vec3::new(s, 0.0, s), Vec3::new(s, s, s), Vec3::new(s, s, 0.0),
+        ], Vec3::new(-1.0, 0.0, 0.0));
+        connect(&mut level, 0, 2, [
+            Vec3::new(0.0, 0.0, s), Vec3::new(s, 0.0, s), Vec3::new(s, s, s), Vec3::new(0.0, s, s),
+        ], Vec3::new(0.0, 0.0, -1.0));
+        level
+    }
+
+    #[test]
+    fn two_room_template_portals_line_up() {
+        let level = create_two_room_level();
+        assert_eq!(level.rooms.len(), 2);
+        assert_eq!(level.misaligned_portals(0), 0);
+        assert_eq!(level.misaligned_portals(1), 0);
+
+        // No walls across the opening
+        assert!(level.rooms[0].get_sector(1, 0).unwrap().walls_east.is_empty());
+        assert!(level.rooms[1].get_sector(0, 1).unwrap().walls_west.is_empty());
+    }
+
+    #[test]
+    fn portals_survive_deleting_a_middle_room() {
+        let mut level = three_room_level();
+        assert_eq!(level.misaligned_portals(0), 0);
+
+        let (removed, dropped) = level.remove_room(1).unwrap();
+        assert_eq!(removed.id, 1);
+        assert_eq!(dropped, 1);
+
+        // Room A's remaining portal still leads to C, now at index 1
+        let a = &level.rooms[0];
+        assert_eq!(a.portals.len(), 1);
+        let c_idx = level.room_index(a.portals[0].target_room).unwrap();
+        assert_eq!(c_idx, 1);
+        assert_eq!(level.rooms[c_idx].position.z, SECTOR_SIZE);
+        assert_eq!(level.misaligned_portals(0), 0);
+        assert_eq!(level.misaligned_portals(1), 0);
+
+        let inside_c = Vec3::new(512.0, 512.0, SECTOR_SIZE + 512.0);
+        assert_eq!(level.find_room_at(inside_c), Some(1));
+
+        // New rooms never reuse the deleted id
+        assert_eq!(level.next_room_id(), 3);
+
+        // Save/load keeps the connection
+        let ron = ron::to_string(&level).unwrap();
+        let loaded = super::super::load_level_from_str(&ron).unwrap();
+        let target = loaded.room_index(loaded.rooms[0].portals[0].target_room).unwrap();
+        assert_eq!(loaded.rooms[target].id, 2);
+        assert_eq!(loaded.misaligned_portals(0), 0);
+    }
+
+    #[test]
+    fn removing_rooms_together() {
+        let mut level = three_room_level();
+        // Out of range and repeated indices are ignored
+        assert_eq!(level.remove_rooms(&[2, 1, 2, 9]), (2, 2));
+        assert_eq!(level.rooms.len(), 1);
+        assert_eq!(level.rooms[0].id, 0);
+        assert!(level.rooms[0].portals.is_empty());
+    }
+
+    #[test]
+    fn reordering_keeps_portal_targets() {
+        let mut level = three_room_level();
+        assert!(level.move_room(0, 2));
+        assert_eq!(level.rooms.iter().map(|r| r.id).collect::<Vec<_>>(), vec![1, 2, 0]);
+        for idx in 0..3 {
+            assert_eq!(level.misaligned_portals(idx), 0);
+        }
+        let b = &level.rooms[0];
+        assert_eq!(level.room_index(b.portals[0].target_room), Some(2));
+    }
+
+    #[test]
+    fn duplicate_ids_from_old_files_become_indices() {
+        let mut level = Level::new();
+        level.add_room(sector_room(0, 0.0, 0.0));
+        level.add_room(sector_room(0, SECTOR_SIZE, 0.0));
+        assert!(level.ensure_unique_room_ids());
+        assert_eq!(level.rooms[1].id, 1);
+        assert!(!level.ensure_unique_room_ids());
+    }
+
+    #[test]
+    fn replace_texture_respects_scope() {
+        let old = TextureRef::new("pack", "WALL_1A");
+        let new = TextureRef::new("pack", "BRICK_03");
+        let mut level = Level::new();
+        for i in 0..2 {
+            let mut room = Room::new(i, Vec3::new(i as f32 * 2.0 * SECTOR_SIZE, 0.0, 0.0), 2, 1);
+            for x in 0..2 {
+                room.set_floor(x, 0, 0.0, TextureRef::new("pack", "FLOOR"));
+                room.add_wall(x, 0, Direction::North, 0.0, 1024.0, old.clone());
+            }
+            level.add_room(room);
+        }
+        assert_eq!(
+            level.texture_usage(&TextureScope::Level),
+            vec![(TextureRef::new("pack", "FLOOR"), 4), (old.clone(), 4)]
+        );
+
+        // Selection: one sector; room: the rest of room 1; then nothing left but room 0
+        assert_eq!(level.replace_texture(&old, &new, &TextureScope::Sectors(vec![(1, 0, 0)])), 1);
+        assert_eq!(level.replace_texture(&old, &new, &TextureScope::Room(1)), 1);
+        assert_eq!(level.replace_texture(&old, &new, &TextureScope::Room(1)), 0);
+        assert_eq!(level.texture_usage(&TextureScope::Room(0)).iter().find(|(t, _)| *t == old).map(|u| u.1), Some(2));
+        assert_eq!(level.replace_texture(&old, &new, &TextureScope::Level), 2);
+        assert!(level.texture_refs().all(|t| *t != old));
+    }
+
+    #[test]
+    fn subdivided_floor_renders_and_collides_per_cell() {
+        let mut floor = HorizontalFace::flat(0.0, TextureRef::none());
+        floor.set_subdivision(2);
+        // Raise the center grid point; corners stay put
+        floor.set_vertex_height(4 + 4, 256.0);
+        assert_eq!(floor.detail_vertices().count(), 5);
+        assert_eq!(floor.height_at(0.5, 0.5), 256.0);
+        assert_eq!(floor.height_at(0.25, 0.25), 128.0);
+        assert_eq!(floor.height_at(0.0, 0.0), 0.0);
+        assert!(!floor.is_flat());
+
+        let mut room = Room::new(0, Vec3::ZERO, 1, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.get_sector_mut(0, 0).unwrap().floor = Some(floor);
+        let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+        let sector = room.get_sector(0, 0).unwrap();
+        let span = HorizontalSpan { face: sector.floor.as_ref().unwrap(), base_x: 0.0, base_z: 0.0, span: (1, 1), is_floor: true };
+        room.add_horizontal_face_to_render_data(&mut vertices, &mut faces, span, &|_| None);
+        assert_eq!((vertices.len(), faces.len()), (16, 8));
+
+        // Old files without the fields load as a single quad
+        let old: HorizontalFace = ron::from_str("(heights: (0, 0, 0, 0), texture: (pack: \"\", name: \"\"))").unwrap();
+        assert_eq!((old.cells(), old.detail.len()), (1, 0));
+    }
+
+    #[test]
+    fn coplanar_floors_merge_into_rectangles() {
+        let mut room = Room::new(0, Vec3::ZERO, 4, 4);
+        for x in 0..4 {
+            for z in 0..4 {
+                room.set_floor(x, z, 0.0, TextureRef::none());
+                room.set_ceiling(x, z, 1024.0, TextureRef::none());
+            }
+        }
+        room.set_floor(3, 3, 256.0, TextureRef::none());
+
+        let (_, faces) = room.to_render_data_with_textures(|_| None);
+        assert_eq!(faces.len(), 64);
+        // Floors: 4x3, 3x1 and the raised sector on its own; ceilings: one 4x4
+        assert_eq!(room.coplanar_runs(true), vec![(0, 0, 4, 3), (0, 3, 3, 1)]);
+        let (vertices, faces) = room.to_render_data_merged(true, |_| None);
+        assert_eq!(faces.len(), 8);
+        // Texture coordinates stay positive where they're sampled (u, 1 - v)
+        assert!(vertices.iter().all(|v| v.uv.x >= 0.0 && 1.0 - v.uv.y >= 0.0));
+    }
+
+    #[test]
+    fn smooth_normals_join_same_kind_faces_only() {
+        let mut room = create_terrain_level().rooms.remove(0);
+        let floor_y = room.get_sector(0, 0).unwrap().floor.as_ref().unwrap().heights[0];
+        room.ensure_sector(0, 0).walls_north.push(VerticalFace::new(floor_y, floor_y + 1024.0, TextureRef::none()));
+        let at = |vertices: &[Vertex], x: f32, z: f32| -> Vec<Vec3> {
+            vertices.iter().filter(|v| v.pos.x == x && v.pos.z == z).map(|v| v.normal).collect()
+        };
+        let same = |a: Vec3, b: Vec3| (a - b).len() < 1e-4;
+
+        // Four tiles meet at the corner (1024, 1024): flat normals differ, smoothed ones agree
+        let mut settings = RasterSettings::default();
+        let (flat, _) = room.to_render_data_for(&settings, |_| None);
+        let normals = at(&flat, SECTOR_SIZE, SECTOR_SIZE);
+        assert!(normals.len() >= 4 && !normals.iter().all(|&n| same(n, normals[0])));
+        settings.smooth_normals = true;
+        let (smooth, _) = room.to_render_data_for(&settings, |_| None);
+        let normals = at(&smooth, SECTOR_SIZE, SECTOR_SIZE);
+        assert!(normals.iter().all(|&n| same(n, normals[0]) && n.y > 0.0));
+
+        // The wall's bottom corner shares a point with the floor but keeps its own normal
+        let corner = at(&smooth, 0.0, 0.0);
+        assert!(corner.iter().any(|&n| same(n, Direction::North.wall_normal())));
+        assert!(corner.iter().any(|&n| n.y > 0.5));
+        let floor = room.get_sector(0, 0).unwrap().floor.as_ref().unwrap();
+        assert!(same(floor.cell_normal(0, 0, true), at(&flat, 0.0, 0.0).into_iter().find(|n| n.y > 0.5).unwrap()));
+    }
+
+    #[test]
+    fn cut_doorway_leaves_pillars_and_lintel() {
+        let mut room = Room::new(0, Vec3::ZERO, 2, 2);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.ensure_sector(0, 0).walls_north.push(VerticalFace::new(0.0, 1024.0, TextureRef::none()));
+        assert!(room.cut_doorway(0, 0, Direction::North, 0, 2048.0, 768.0).is_err());
+
+        assert_eq!(room.cut_doorway(0, 0, Direction::North, 0, 512.0, 768.0), Ok(3));
+        let walls = &room.get_sector(0, 0).unwrap().walls_north;
+        let spans: Vec<_> = walls.iter().map(|w| (w.x_start, w.x_end, w.heights[0])).collect();
+        assert_eq!(spans, vec![(0.0, 0.25, 0.0), (0.75, 1.0, 0.0), (0.25, 0.75, 768.0)]);
+        let right = walls[0].corners(0.0, 0.0, Direction::North)[1];
+        assert_eq!((right.x, right.z), (256.0, 0.0));
+
+        // Only the pillars block, and only below the lintel in the opening
+        assert!(room.edge_blocked(0, 0, Direction::North, 0.1, 10.0, 700.0));
+        assert!(!room.edge_blocked(0, 0, Direction::North, 0.5, 10.0, 700.0));
+        assert!(room.edge_blocked(0, 0, Direction::North, 0.5, 10.0, 900.0));
+    }
+
+    #[test]
+    fn visibility_and_collision_are_independent() {
+        let mut room = Room::new(0, Vec3::ZERO, 2, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.set_floor(1, 0, 0.0, TextureRef::none());
+        let mut wall = VerticalFace::new(0.0, 1024.0, TextureRef::none());
+        wall.visible = false;
+        room.ensure_sector(0, 0).walls_north.push(wall);
+        room.ensure_sector(1, 0).floor.as_mut().unwrap().walkable = false;
+        let (_, faces) = room.to_render_data_with_textures(|_| Some(0));
+
+        // The invisible wall isn't drawn but still blocks; the decorative floor is drawn but not stood on
+        assert_eq!(faces.len(), 4);
+        assert!(room.edge_blocked(0, 0, Direction::North, 0.5, 10.0, 700.0));
+        assert!(room.floor_at(SECTOR_SIZE * 1.5, 512.0).is_none());
+
+        let mut level = Level::new();
+        level.rooms.push(room);
+        assert_eq!(level.inert_faces(), 0);
+        level.rooms[0].get_sector_mut(1, 0).unwrap().floor.as_mut().unwrap().visible = false;
+        assert_eq!(level.inert_faces(), 1);
+    }
+
+    #[test]
+    fn wall_ids_are_kept_and_made_unique() {
+        let mut room = sector_room(0, 0.0, 0.0);
+        // Added walls have their id right away, and assigning ids keeps it
+        let ids: Vec<WallId> = [256.0, 512.0, 768.0].iter()
+            .map(|&top| room.add_wall(0, 0, Direction::North, top - 256.0, top, TextureRef::none()))
+            .collect();
+        assert!(ids.iter().all(|id| id.0 != 0));
+        room.assign_wall_ids();
+        let assigned: Vec<WallId> = room.sectors[0][0].as_ref().unwrap().walls_north.iter().map(|w| w.id).collect();
+        assert_eq!(assigned, ids);
+
+        // Removing a wall leaves the others' ids alone; a copy gets a fresh one
+        room.remove_wall(0, 0, Direction::North, 0);
+        let sector = room.get_sector_mut(0, 0).unwrap();
+        let copy = sector.walls_north[0].clone();
+        sector.walls_east.push(copy);
+        room.assign_wall_ids();
+        let sector = room.get_sector(0, 0).unwrap();
+        assert_eq!(sector.wall_index(Direction::North, ids[1]), Some(0));
+        assert_eq!(sector.wall_index(Direction::North, ids[2]), Some(1));
+        assert!(!ids.contains(&sector.walls_east[0].id));
+    }
+
+    #[test]
+    fn empty_rooms_have_empty_bounds() {
+        let mut level = Level::new();
+        level.add_room(sector_room(0, 0.0, 0.0));
+        level.add_room(sector_room(1, SECTOR_SIZE, 0.0));
+        let position = level.rooms[1].position;
+        let inside = position + Vec3::new(SECTOR_SIZE * 0.5, 512.0, SECTOR_SIZE * 0.5);
+        assert_eq!(level.find_room_at(inside), Some(1));
+        assert_eq!(level.suggest_portals().len(), 1);
+
+        // Deleting the only sector leaves nothing to contain, center on or connect to
+        level.rooms[1].remove_sector(0, 0);
+        level.rooms[1].recalculate_bounds();
+        let room = &level.rooms[1];
+        assert!(room.is_empty() && room.world_bounds().is_empty());
+        assert!(!room.contains_point(position));
+        assert!((room.world_center() - position).len() < 1e-6);
+        assert_eq!(level.find_room_at(inside), None);
+        assert_eq!(level.empty_rooms(), vec![1]);
+        assert!(level.suggest_portals().is_empty());
+
+        // Same for a file whose grid is all empty
+        let mut file_level = Level::new();
+        file_level.add_room(Room::new(0, Vec3::new(2048.0, 0.0, 0.0), 2, 2));
+        let loaded = super::super::load_level_from_str(&super::super::level_to_string(&file_level).unwrap()).unwrap();
+        assert!(loaded.rooms[0].is_empty());
+        assert!(!loaded.rooms[0].contains_point(Vec3::new(2048.0, 0.0, 0.0)));
+        assert_eq!(loaded.empty_rooms(), vec![0]);
+
+        // Growing the box from empty takes the first point as-is
+        let mut bounds = Aabb::empty();
+        assert_eq!(bounds.center().len(), 0.0);
+        bounds.expand(Vec3::new(1.0, 2.0, 3.0));
+        assert!(!bounds.is_empty() && bounds.contains(Vec3::new(1.0, 2.0, 3.0)));
+    }
+
+    #[test]
+    fn wall_selection_follows_its_wall() {
+        let mut room = Room::new(0, Vec3::ZERO, 1, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        for top in [256.0, 512.0, 768.0] {
+            room.add_wall(0, 0, Direction::North, top - 256.0, top, TextureRef::none());
+        }
+        room.assign_wall_ids();
+        let face = SectorFace::wall_at(room.get_sector(0, 0).unwrap(), Direction::North, 1).unwrap();
+
+        // The wall below the selected one goes away: the selection stays on its wall
+        room.remove_wall(0, 0, Direction::North, 0);
+        room.assign_wall_ids();
+        let sector = room.get_sector(0, 0).unwrap();
+        assert_eq!(face.wall_index_in(sector), Some((Direction::North, 0)));
+        assert_eq!(face.wall_in(sector).map(|(_, w)| w.heights[2]), Some(512.0));
+
+        // A wall added in its place doesn't pick up the selection
+        room.remove_wall(0, 0, Direction::North, 0);
+        room.add_wall(0, 0, Direction::North, 256.0, 512.0, TextureRef::none());
+        room.assign_wall_ids();
+        assert!(face.wall_in(room.get_sector(0, 0).unwrap()).is_none());
+    }
+}
@@ -0,0 +1,192 @@
+//! Undo/redo history of level snapshots
+//!
+//! Each step is a full copy of the level from before (undo stack) or after (redo
+//! stack) one action. Drags and paint strokes edit over many frames but open a
+//! transaction so they undo as a single step.
+
+use super::Level;
+
+/// Maximum number of undo steps kept
+pub const MAX_UNDO: usize = 50;
+
+/// A level snapshot on the undo/redo stack, labeled with the action it belongs to
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    /// Human-readable description (e.g. "Raise floor (3, 2)")
+    pub label: String,
+    /// Level before the action (undo stack) or after it (redo stack)
+    pub level: Level,
+}
+
+/// An open undo transaction: a drag or paint stroke that edits over many frames
+/// but undoes as one step
+#[derive(Debug, Clone)]
+struct UndoTransaction {
+    /// Label of the step it finalizes into
+    label: String,
+    /// Level before the first edit (None until something was edited)
+    before: Option<Level>,
+}
+
+/// Undo and redo stacks for one level
+#[derive(Debug, Clone, Default)]
+pub struct LevelHistory {
+    pub undo_stack: Vec<UndoEntry>,
+    pub redo_stack: Vec<UndoEntry>,
+    /// Drag or stroke collecting its edits into one step (see `begin_transaction`)
+    transaction: Option<UndoTransaction>,
+}
+
+impl LevelHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop every step and any open transaction (e.g. after loading another level)
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.transaction = None;
+    }
+
+    /// Snapshot `level` before an edit. Inside a transaction only the first call
+    /// snapshots (and `label` is ignored): `end_transaction` pushes the step.
+    /// Returns true if a snapshot was taken.
+    pub fn save(&mut self, level: &Level, label: &str) -> bool {
+        if let Some(transaction) = &mut self.transaction {
+            if transaction.before.is_some() {
+                return false;
+            }
+            transaction.before = Some(level.clone());
+            return true;
+        }
+        self.push(UndoEntry { label: label.to_string(), level: level.clone() });
+        true
+    }
+
+    fn push(&mut self, entry: UndoEntry) {
+        self.undo_stack.push(entry);
+        self.redo_stack.clear();
+
+        // Limit undo stack size
+        if self.undo_stack.len() > MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// Rename the most recent undo step (e.g. once a paint stroke knows its face
+    /// count), or the open transaction's
+    pub fn set_label(&mut self, label: &str) {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.label = label.to_string();
+        } else if let Some(entry) = self.undo_stack.last_mut() {
+            entry.label = label.to_string();
+        }
+    }
+
+    /// Start collecting edits into one undo step (a drag or stroke): `save` calls
+    /// until `end_transaction` share the snapshot taken by the first one.
+    /// Ends a transaction that is still open.
+    pub fn begin_transaction(&mut self, level: &Level, label: &str) {
+        self.end_transaction(level);
+        self.transaction = Some(UndoTransaction { label: label.to_string(), before: None });
+    }
+
+    /// Close the open transaction, pushing its undo step unless `level` ended up
+    /// unchanged. Returns true if a step was pushed.
+    pub fn end_transaction(&mut self, level: &Level) -> bool {
+        let Some(UndoTransaction { label, before: Some(before) }) = self.transaction.take() else {
+            return false;
+        };
+        if before.diff(level).is_empty() {
+            return false;
+        }
+        self.push(UndoEntry { label, level: before });
+        true
+    }
+
+    /// Close the open transaction without pushing a step. Returns the level from
+    /// before its edits, if there were any, for the caller to put back.
+    pub fn cancel_transaction(&mut self) -> Option<Level> {
+        self.transaction.take()?.before
+    }
+
+    /// Is a drag or stroke collecting its edits?
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    /// Swap `level` with the most recent undo step. Returns false if the stack is
+    /// empty. An open transaction is closed first, its edits becoming their own step.
+    pub fn undo(&mut self, level: &mut Level) -> bool {
+        self.end_transaction(level);
+        match self.undo_stack.pop() {
+            Some(entry) => {
+                let current = std::mem::replace(level, entry.level);
+                self.redo_stack.push(UndoEntry { label: entry.label, level: current });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Swap `level` with the most recent redo step. Returns false if the stack is empty.
+    pub fn redo(&mut self, level: &mut Level) -> bool {
+        self.end_transaction(level);
+        match self.redo_stack.pop() {
+            Some(entry) => {
+                let current = std::mem::replace(level, entry.level);
+                self.undo_stack.push(UndoEntry { label: entry.label, level: current });
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::{Room, TextureRef};
+
+    #[test]
+    fn drag_is_one_undo_step() {
+        let mut room = Room::new(0, Vec3::ZERO, 1, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        let mut level = Level::new();
+        level.add_room(room);
+        let mut history = LevelHistory::new();
+        let floor_heights = |level: &Level| level.rooms[0].get_sector(0, 0).unwrap().floor.as_ref().unwrap().heights;
+        let original = floor_heights(&level);
+
+        // A 100-frame drag raising one corner a little each frame
+        history.begin_transaction(&level, "Move vertex heights");
+        for step in 1..=100 {
+            history.save(&level, "Move vertex heights");
+            let floor = level.rooms[0].get_sector_mut(0, 0).unwrap().floor.as_mut().unwrap();
+            floor.set_vertex_height(0, step as f32 * 8.0);
+        }
+        history.set_label("Raise floor (0, 0)");
+        assert!(history.end_transaction(&level));
+        assert_eq!(history.undo_stack.len(), 1);
+        assert_eq!(history.undo_stack[0].label, "Raise floor (0, 0)");
+
+        assert!(history.undo(&mut level));
+        assert_eq!(floor_heights(&level), original);
+        assert!(history.redo(&mut level));
+        assert_eq!(floor_heights(&level)[0], 800.0);
+        assert!(history.undo(&mut level));
+
+        // A drag that changes nothing leaves no step; a cancelled one hands back the level
+        history.begin_transaction(&level, "Move vertex heights");
+        history.save(&level, "Move vertex heights");
+        assert!(!history.end_transaction(&level));
+        history.begin_transaction(&level, "Move vertex heights");
+        history.save(&level, "Move vertex heights");
+        level.rooms[0].get_sector_mut(0, 0).unwrap().floor.as_mut().unwrap().set_vertex_height(2, 512.0);
+        level = history.cancel_transaction().unwrap();
+        assert_eq!(floor_heights(&level), original);
+        assert!(history.undo_stack.is_empty() && !history.in_transaction());
+    }
+}
@@ -1,10 +1,17 @@
 //! Level loading and saving
 //!
-//! Uses RON (Rusty Object Notation) for human-readable level files.
+//! Uses RON (Rusty Object Notation) for human-readable level files. Loading
+//! enforces `limits` so a hostile or corrupt file fails fast instead of
+//! exhausting memory.
+
+pub mod limits;
 
 use std::fs;
 use std::path::Path;
-use super::Level;
+use serde::{Deserialize, Serialize};
+use crate::rasterizer::Vec3;
+use super::{Level, Room, Sector};
+use limits::LimitExceeded;
 
 /// Error type for level loading
 #[derive(Debug)]
@@ -12,6 +19,12 @@ pub enum LevelError {
     IoError(std::io::Error),
     ParseError(ron::error::SpannedError),
     SerializeError(ron::Error),
+    /// File is bigger than `limits::MAX_FILE_SIZE` (size in bytes)
+    FileTooLarge(usize),
+    /// A count or size in the file is over its limit
+    LimitExceeded(LimitExceeded),
+    /// NaN, infinite or out-of-range number, or an inconsistent room grid
+    InvalidValue(String),
 }
 
 impl From<std::io::Error> for LevelError {
@@ -22,7 +35,17 @@ impl From<std::io::Error> for LevelError {
 
 impl From<ron::error::SpannedError> for LevelError {
     fn from(e: ron::error::SpannedError) -> Self {
-        LevelError::ParseError(e)
+        // Bounded deserializers leave the limit they hit behind; report that instead
+        if let Some(limit) = limits::take_violation() {
+            return LevelError::LimitExceeded(limit);
+        }
+        match e.code {
+            ron::Error::ExceededRecursionLimit => LevelError::LimitExceeded(LimitExceeded {
+                what: "levels of nesting",
+                max: limits::MAX_NESTING,
+            }),
+            _ => LevelError::ParseError(e),
+        }
     }
 }
 
@@ -38,41 +61,450 @@ impl std::fmt::Display for LevelError {
             LevelError::IoError(e) => write!(f, "IO error: {}", e),
             LevelError::ParseError(e) => write!(f, "Parse error: {}", e),
             LevelError::SerializeError(e) => write!(f, "Serialize error: {}", e),
+            LevelError::FileTooLarge(size) => write!(
+                f, "File too large: {} bytes (max {})", size, limits::MAX_FILE_SIZE
+            ),
+            LevelError::LimitExceeded(limit) => write!(f, "Level too large: {}", limit),
+            LevelError::InvalidValue(msg) => write!(f, "Invalid value: {}", msg),
         }
     }
 }
 
 /// Load a level from a RON file
 pub fn load_level<P: AsRef<Path>>(path: P) -> Result<Level, LevelError> {
+    // Check the size before reading anything
+    let size = fs::metadata(&path)?.len() as usize;
+    if size > limits::MAX_FILE_SIZE {
+        return Err(LevelError::FileTooLarge(size));
+    }
     let contents = fs::read_to_string(path)?;
-    let mut level: Level = ron::from_str(&contents)?;
+    load_level_from_str(&contents)
+}
+
+/// Save a level to a RON file
+pub fn save_level<P: AsRef<Path>>(level: &Level, path: P) -> Result<(), LevelError> {
+    fs::write(path, level_to_string(level)?)?;
+    Ok(())
+}
+
+/// A level as `save_level` writes it
+pub fn level_to_string(level: &Level) -> Result<String, LevelError> {
+    // Doors are saved by their wall's place in its stack: write back where each
+    // door's wall is now (on a copy, only if one moved since the last sync)
+    let synced;
+    let level = if level.rooms.iter().all(Room::door_walls_synced) {
+        level
+    } else {
+        let mut copy = level.clone();
+        copy.rooms.iter_mut().for_each(Room::sync_door_walls);
+        synced = copy;
+        &synced
+    };
+    let config = ron::ser::PrettyConfig::new()
+        .depth_limit(4)
+        .indentor("  ".to_string());
+    Ok(ron::ser::to_string_pretty(level, config)?)
+}
+
+/// Load a level from a RON string (for embedded levels, uploads or testing)
+pub fn load_level_from_str(s: &str) -> Result<Level, LevelError> {
+    if s.len() > limits::MAX_FILE_SIZE {
+        return Err(LevelError::FileTooLarge(s.len()));
+    }
 
-    // Recalculate bounds for all rooms (not serialized)
+    // Collections and names are bounded while parsing; numbers are checked after
+    limits::take_violation();
+    let mut level: Level = limits::ron_options().from_str(s)?;
+    limits::validate(&level)?;
+    level.ensure_unique_room_ids();
+
+    // Recalculate bounds and wall ids for all rooms (not serialized), then point
+    // doors at their wall's id
     for room in &mut level.rooms {
         room.recalculate_bounds();
+        room.assign_wall_ids();
+        room.resolve_door_walls();
     }
 
     Ok(level)
 }
 
-/// Save a level to a RON file
-pub fn save_level<P: AsRef<Path>>(level: &Level, path: P) -> Result<(), LevelError> {
+/// Sectors copied out of a room, shared as text (clipboard) and pasted into another
+/// room or level. Offsets are relative to the north-west corner of the copied area.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorSnippet {
+    /// (x offset, z offset, sector)
+    #[serde(deserialize_with = "limits::snippet_sectors")]
+    pub sectors: Vec<(usize, usize, Sector)>,
+}
+
+impl SectorSnippet {
+    /// Copy sectors (grid coordinates) out of a room. Floor/ceiling openings are
+    /// dropped, since the rooms they lead to aren't part of the snippet.
+    pub fn copy(room: &Room, coords: &[(usize, usize)]) -> Option<Self> {
+        let min_x = coords.iter().map(|c| c.0).min()?;
+        let min_z = coords.iter().map(|c| c.1).min()?;
+        let mut sectors: Vec<_> = coords.iter()
+            .filter_map(|&(x, z)| {
+                let mut sector = room.get_sector(x, z)?.clone();
+                for face in sector.floor.iter_mut().chain(sector.ceiling.iter_mut()) {
+                    face.opening = None;
+                }
+                Some((x - min_x, z - min_z, sector))
+            })
+            .collect();
+        sectors.sort_by_key(|(x, z, _)| (*x, *z));
+        sectors.dedup_by_key(|(x, z, _)| (*x, *z));
+        (!sectors.is_empty()).then_some(Self { sectors })
+    }
+
+    /// Paste into a room with the snippet's north-west corner at (x, z), replacing
+    /// the sectors there. Returns the grid coordinates written; sectors that would
+    /// land past the room's edge are skipped.
+    pub fn paste(&self, room: &mut Room, x: usize, z: usize) -> Vec<(usize, usize)> {
+        let mut placed = Vec::new();
+        for (dx, dz, sector) in &self.sectors {
+            let (gx, gz) = (x + dx, z + dz);
+            if gx < room.width && gz < room.depth {
+                room.sectors[gx][gz] = Some(sector.clone());
+                placed.push((gx, gz));
+            }
+        }
+        room.recalculate_bounds();
+        placed
+    }
+
+    /// Compact RON text for the clipboard
+    pub fn to_text(&self) -> Result<String, LevelError> {
+        Ok(ron::ser::to_string(self)?)
+    }
+
+    /// Parse snippet text, with the same limits and checks as level files
+    pub fn from_text(s: &str) -> Result<Self, LevelError> {
+        if s.len() > limits::MAX_FILE_SIZE {
+            return Err(LevelError::FileTooLarge(s.len()));
+        }
+        limits::take_violation();
+        let snippet: SectorSnippet = limits::ron_options().from_str(s.trim())?;
+        limits::validate_snippet(&snippet)?;
+        if snippet.sectors.is_empty() {
+            return Err(LevelError::InvalidValue("no sectors in snippet".to_string()));
+        }
+        Ok(snippet)
+    }
+}
+
+/// A room saved on its own, to stamp into other levels. Only its geometry is kept:
+/// portals, floor/ceiling openings, pickups, lights and ambient sounds are stripped
+/// and the room sits at the origin. Texture references are kept as they are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomPrefab {
+    pub room: Room,
+}
+
+impl RoomPrefab {
+    /// Prefab of a room (the room is copied)
+    pub fn from_room(room: &Room) -> Self {
+        let mut room = room.clone();
+        room.id = 0;
+        room.position = Vec3::ZERO;
+        room.portals.clear();
+        room.pickups.clear();
+        room.ambients.clear();
+        room.lights.clear();
+        room.close_openings(|_| false);
+        room.sync_door_walls();
+        room.generation = 0;
+        Self { room }
+    }
+
+    /// Add a copy of the room to a level with a fresh id and its grid origin at
+    /// `position`. Returns the new room's index.
+    pub fn stamp(&self, level: &mut Level, position: Vec3) -> usize {
+        let mut room = Self::from_room(&self.room).room;
+        room.id = level.next_room_id();
+        room.position = position;
+        room.recalculate_bounds();
+        level.rooms.push(room);
+        level.rooms.len() - 1
+    }
+}
+
+/// Save a room prefab to a RON file, creating its directory if needed
+pub fn save_prefab<P: AsRef<Path>>(prefab: &RoomPrefab, path: P) -> Result<(), LevelError> {
     let config = ron::ser::PrettyConfig::new()
         .depth_limit(4)
         .indentor("  ".to_string());
 
-    let contents = ron::ser::to_string_pretty(level, config)?;
+    let contents = ron::ser::to_string_pretty(prefab, config)?;
+    if let Some(dir) = path.as_ref().parent() {
+        fs::create_dir_all(dir)?;
+    }
     fs::write(path, contents)?;
     Ok(())
 }
 
-/// Load a level from a RON string (for embedded levels or testing)
-pub fn load_level_from_str(s: &str) -> Result<Level, LevelError> {
-    let mut level: Level = ron::from_str(s)?;
+/// Load a room prefab from a RON file
+pub fn load_prefab<P: AsRef<Path>>(path: P) -> Result<RoomPrefab, LevelError> {
+    let size = fs::metadata(&path)?.len() as usize;
+    if size > limits::MAX_FILE_SIZE {
+        return Err(LevelError::FileTooLarge(size));
+    }
+    let contents = fs::read_to_string(path)?;
+    load_prefab_from_str(&contents)
+}
 
-    for room in &mut level.rooms {
-        room.recalculate_bounds();
+/// Load a room prefab from a RON string, with the same limits and checks as level files
+pub fn load_prefab_from_str(s: &str) -> Result<RoomPrefab, LevelError> {
+    if s.len() > limits::MAX_FILE_SIZE {
+        return Err(LevelError::FileTooLarge(s.len()));
     }
+    limits::take_violation();
+    let mut prefab: RoomPrefab = limits::ron_options().from_str(s)?;
+    limits::validate_prefab(&prefab)?;
+    prefab.room.recalculate_bounds();
+    prefab.room.assign_wall_ids();
+    prefab.room.resolve_door_walls();
+    Ok(prefab)
+}
 
-    Ok(level)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Direction;
+
+    fn room(width: usize, depth: usize, sectors: &str) -> String {
+        format!("(id: 0, position: (x: 0.0, y: 0.0, z: 0.0), width: {}, depth: {}, sectors: {})", width, depth, sectors)
+    }
+
+    fn level(rooms: &[String]) -> String {
+        format!("(rooms: [{}])", rooms.join(", "))
+    }
+
+    /// A sector with a floor at `height` and `walls` walls on its north edge
+    fn sector(height: &str, walls: usize) -> String {
+        let texture = r#"texture: (pack: "p", name: "t")"#;
+        let wall = format!("(heights: (0.0, 0.0, 1024.0, 1024.0), {})", texture);
+        format!(
+            "Some((floor: Some((heights: ({0}, {0}, {0}, {0}), {1})), ceiling: None, walls_north: [{2}]))",
+            height, texture, vec![wall; walls].join(", ")
+        )
+    }
+
+    fn limit(result: Result<Level, LevelError>) -> LimitExceeded {
+        match result {
+            Err(LevelError::LimitExceeded(limit)) => limit,
+            other => panic!("expected a limit error, got {:?}", other.map(|l| l.rooms.len())),
+        }
+    }
+
+    fn invalid(result: Result<Level, LevelError>) -> String {
+        match result {
+            Err(LevelError::InvalidValue(msg)) => msg,
+            other => panic!("expected an invalid value error, got {:?}", other.map(|l| l.rooms.len())),
+        }
+    }
+
+    #[test]
+    fn bundled_levels_are_within_limits() {
+        load_level_from_str(include_str!("../../assets/levels/level_001.ron")).unwrap();
+        load_level_from_str(include_str!("../../assets/levels/level_002.ron")).unwrap();
+        load_level_from_str(include_str!("../../assets/levels/mip_corridor.ron")).unwrap();
+        let small = level(&[room(1, 1, &format!("[[{}]]", sector("0.0", 2)))]);
+        assert_eq!(load_level_from_str(&small).unwrap().rooms[0].sectors[0][0].as_ref().unwrap().walls_north.len(), 2);
+    }
+
+    #[test]
+    fn oversized_collections_are_rejected_while_parsing() {
+        let none_column = |n: usize| format!("[{}]", vec!["None"; n].join(", "));
+
+        let wide = format!("[{}]", vec![none_column(1); limits::MAX_ROOM_SIZE + 1].join(", "));
+        assert_eq!(limit(load_level_from_str(&level(&[room(1, 1, &wide)]))).what, "sectors along room width");
+
+        let deep = format!("[{}]", none_column(limits::MAX_ROOM_SIZE + 1));
+        assert_eq!(limit(load_level_from_str(&level(&[room(1, 1, &deep)]))).what, "sectors along room depth");
+
+        let walls = format!("[[{}]]", sector("0.0", limits::MAX_WALLS_PER_EDGE + 1));
+        assert_eq!(limit(load_level_from_str(&level(&[room(1, 1, &walls)]))).what, "walls per edge");
+
+        let rooms = vec![room(0, 0, "[]"); limits::MAX_ROOMS + 1];
+        assert_eq!(limit(load_level_from_str(&level(&rooms))).what, "rooms");
+
+        let name = format!(r#"(id: 0, name: "{}", position: (x: 0.0, y: 0.0, z: 0.0), width: 0, depth: 0, sectors: [])"#,
+            "x".repeat(limits::MAX_STRING_LEN + 1));
+        assert_eq!(limit(load_level_from_str(&level(&[name]))).what, "bytes in a name");
+
+        // A size claimed in the header can't make us allocate either
+        assert_eq!(limit(load_level_from_str(&level(&[room(usize::MAX, 1, "[]")]))).what, "sectors along a room side");
+    }
+
+    #[test]
+    fn bad_numbers_and_mismatched_grids_are_rejected() {
+        for height in ["NaN", "inf", "-inf", "1e30"] {
+            let msg = invalid(load_level_from_str(&level(&[room(1, 1, &format!("[[{}]]", sector(height, 0)))])));
+            assert!(msg.contains("sector (0, 0)"), "{}", msg);
+        }
+
+        let far = level(&[room(0, 0, "[]").replace("x: 0.0", "x: 1e12")]);
+        assert!(invalid(load_level_from_str(&far)).contains("position"));
+
+        let mismatched = level(&[room(2, 1, "[[None]]")]);
+        assert!(invalid(load_level_from_str(&mismatched)).contains("doesn't match"));
+
+        let detail = sector("0.0", 0).replace(r#"name: "t")"#, r#"name: "t"), subdivision: 2, detail: [0.0]"#);
+        assert!(invalid(load_level_from_str(&level(&[room(1, 1, &format!("[[{}]]", detail))]))).contains("subdivision 2"));
+
+        let span = sector("0.0", 1).replace(r#"name: "t"))]"#, r#"name: "t"), x_start: 0.75, x_end: 0.25)]"#);
+        assert!(invalid(load_level_from_str(&level(&[room(1, 1, &format!("[[{}]]", span))]))).contains("wall span"));
+    }
+
+    #[test]
+    fn doors_must_point_at_a_wall() {
+        let with_door = |x: usize, wall: usize| {
+            let room = room(1, 1, &format!("[[{}]]", sector("0.0", 2)));
+            let door = format!("(x: {}, z: 0, edge: North, wall: {}, closed: (0.0, 0.0, 1024.0, 1024.0), open: (0.0, 0.0, 0.0, 0.0))", x, wall);
+            level(&[format!("{}, doors: [{}])", room.trim_end_matches(')'), door)])
+        };
+        assert_eq!(load_level_from_str(&with_door(0, 1)).unwrap().rooms[0].doors[0].wall, 1);
+        assert!(invalid(load_level_from_str(&with_door(0, 2))).contains("door"));
+        assert!(invalid(load_level_from_str(&with_door(3, 0))).contains("door"));
+    }
+
+    #[test]
+    fn doors_follow_their_wall_and_save_its_place() {
+        let room = room(1, 1, &format!("[[{}]]", sector("0.0", 2)));
+        let door = "(x: 0, z: 0, edge: North, wall: 1, closed: (0.0, 0.0, 1024.0, 1024.0), open: (0.0, 0.0, 0.0, 0.0))";
+        let mut level = load_level_from_str(&level(&[format!("{}, doors: [{}])", room.trim_end_matches(')'), door)])).unwrap();
+        let room = &mut level.rooms[0];
+        assert_eq!(room.doors[0].wall_id, room.get_sector(0, 0).unwrap().walls_north[1].id);
+
+        // The wall below goes: the door stays on its wall, and is saved at its new place
+        room.get_sector_mut(0, 0).unwrap().walls_north.remove(0);
+        assert_eq!(room.door_wall_index(&room.doors[0]), Some(0));
+        let saved = level_to_string(&level).unwrap();
+        assert!(!saved.contains("wall_id"));
+        assert_eq!(load_level_from_str(&saved).unwrap().rooms[0].doors[0].wall, 0);
+
+        // Its wall goes: so does the door
+        level.rooms[0].remove_wall(0, 0, Direction::North, 0);
+        assert!(level.rooms[0].doors.is_empty());
+    }
+
+    #[test]
+    fn render_settings_are_optional() {
+        let mut level = Level::new();
+        let plain = ron::ser::to_string(&level).unwrap();
+        assert!(!plain.contains("render_settings"));
+        assert!(load_level_from_str(&plain).unwrap().render_settings.is_none());
+
+        let mut settings = crate::rasterizer::RasterSettings::default();
+        settings.dithering = !settings.dithering;
+        settings.shading = crate::rasterizer::ShadingMode::None;
+        level.render_settings = Some(crate::world::LevelRenderSettings::from_raster(&settings));
+        let stored = load_level_from_str(&ron::ser::to_string(&level).unwrap()).unwrap();
+        assert_eq!(stored.render_settings, level.render_settings);
+    }
+
+    #[test]
+    fn pack_aliases_round_trip_within_limits() {
+        let mut level = Level::new();
+        assert!(!ron::ser::to_string(&level).unwrap().contains("pack_aliases"));
+        level.pack_aliases.insert("GOTHIC".to_string(), "retro".to_string());
+        let stored = load_level_from_str(&ron::ser::to_string(&level).unwrap()).unwrap();
+        assert_eq!(stored.pack_aliases, level.pack_aliases);
+
+        let aliases: Vec<String> = (0..=limits::MAX_PACK_ALIASES).map(|i| format!(r#""p{}": "q""#, i)).collect();
+        let many = format!("(rooms: [], pack_aliases: {{{}}})", aliases.join(", "));
+        assert_eq!(limit(load_level_from_str(&many)).what, "pack aliases");
+        let long = format!(r#"(rooms: [], pack_aliases: {{"p": "{}"}})"#, "x".repeat(limits::MAX_STRING_LEN + 1));
+        assert_eq!(limit(load_level_from_str(&long)).what, "bytes in a name");
+    }
+
+    #[test]
+    fn camera_bookmarks_round_trip() {
+        let mut level = Level::new();
+        assert!(!ron::ser::to_string(&level).unwrap().contains("camera_bookmarks"));
+
+        level.camera_bookmarks[2] = Some(crate::world::CameraBookmark {
+            name: "Vault".to_string(),
+            position: crate::rasterizer::Vec3::new(1024.0, 512.0, -2048.0),
+            rotation_x: 0.3,
+            rotation_y: -1.5,
+        });
+        let stored = load_level_from_str(&ron::ser::to_string(&level).unwrap()).unwrap();
+        let bookmark = stored.camera_bookmarks[2].as_ref().unwrap();
+        assert_eq!((bookmark.name.as_str(), bookmark.position.z, bookmark.rotation_y), ("Vault", -2048.0, -1.5));
+        assert_eq!(stored.camera_bookmarks.iter().flatten().count(), 1);
+    }
+
+    #[test]
+    fn sector_snippets_round_trip_and_reject_bad_text() {
+        use crate::world::{TextureRef, SECTOR_SIZE};
+        let mut room = Room::new(0, crate::rasterizer::Vec3::ZERO, 4, 4);
+        room.set_floor(1, 1, 256.0, TextureRef::new("pack", "STEP"));
+        room.set_floor(2, 1, 512.0, TextureRef::new("pack", "STEP"));
+        let snippet = SectorSnippet::copy(&room, &[(2, 1), (1, 1)]).unwrap();
+        let pasted = SectorSnippet::from_text(&snippet.to_text().unwrap()).unwrap();
+        assert_eq!(pasted.sectors.iter().map(|(x, z, _)| (*x, *z)).collect::<Vec<_>>(), vec![(0, 0), (1, 0)]);
+
+        // The second sector lands past the east edge
+        let mut target = Room::new(0, crate::rasterizer::Vec3::ZERO, 2, 2);
+        assert_eq!(pasted.paste(&mut target, 1, 1), vec![(1, 1)]);
+        assert_eq!(target.bounds.max.x, 2.0 * SECTOR_SIZE);
+
+        assert!(SectorSnippet::from_text("not a snippet").is_err());
+        assert!(matches!(SectorSnippet::from_text("(sectors: [])"), Err(LevelError::InvalidValue(_))));
+        let far = format!("(sectors: [({}, 0, (floor: None, ceiling: None))])", limits::MAX_ROOM_SIZE);
+        assert!(matches!(SectorSnippet::from_text(&far), Err(LevelError::LimitExceeded(_))));
+        let nan = r#"(sectors: [(0, 0, (floor: Some((heights: (NaN, 0, 0, 0), texture: (pack: "p", name: "t"))), ceiling: None))])"#;
+        assert!(matches!(SectorSnippet::from_text(nan), Err(LevelError::InvalidValue(_))));
+    }
+
+    #[test]
+    fn prefabs_keep_geometry_and_stamp_with_a_fresh_id() {
+        use crate::world::{Pickup, TextureRef, SECTOR_SIZE};
+        let mut source = Room::new(3, Vec3::new(4096.0, 0.0, -1024.0), 2, 2);
+        source.set_floor(0, 0, 0.0, TextureRef::new("pack", "FLOOR"));
+        source.set_floor(1, 0, 0.0, TextureRef::new("pack", "FLOOR"));
+        source.get_sector_mut(1, 0).unwrap().floor.as_mut().unwrap().opening = Some(7);
+        source.add_portal(7, [Vec3::ZERO; 4], Vec3::new(1.0, 0.0, 0.0));
+        source.pickups.push(Pickup::new(Vec3::ZERO, "coin", TextureRef::new("pack", "COIN")));
+
+        let prefab = RoomPrefab::from_room(&source);
+        let text = ron::ser::to_string(&prefab).unwrap();
+        let loaded = load_prefab_from_str(&text).unwrap();
+        assert!(loaded.room.portals.is_empty() && loaded.room.pickups.is_empty());
+        assert_eq!((loaded.room.position.x, loaded.room.position.z), (0.0, 0.0));
+        assert!(loaded.room.get_sector(1, 0).unwrap().floor.as_ref().unwrap().opening.is_none());
+        assert_eq!(loaded.room.get_sector(0, 0).unwrap().floor.as_ref().unwrap().texture.name, "FLOOR");
+
+        let mut level = Level::new();
+        level.rooms.push(source);
+        let idx = loaded.stamp(&mut level, Vec3::new(2.0 * SECTOR_SIZE, 0.0, 0.0));
+        assert_eq!((idx, level.rooms[idx].id), (1, 4));
+        assert_eq!(level.rooms[idx].bounds.max.x, 2.0 * SECTOR_SIZE);
+        assert_eq!(level.rooms[idx].world_bounds().min.x, 2.0 * SECTOR_SIZE);
+
+        // Same limits and checks as levels
+        let nan = text.replacen("0.0", "NaN", 1);
+        assert!(matches!(load_prefab_from_str(&nan), Err(LevelError::InvalidValue(_))));
+        let huge = " ".repeat(limits::MAX_FILE_SIZE + 1);
+        assert!(matches!(load_prefab_from_str(&huge), Err(LevelError::FileTooLarge(_))));
+        let wide = format!("(room: {})", room(limits::MAX_ROOM_SIZE + 1, 1, "[]"));
+        assert!(matches!(load_prefab_from_str(&wide), Err(LevelError::LimitExceeded(_))));
+    }
+
+    #[test]
+    fn garbage_fails_fast() {
+        let huge = " ".repeat(limits::MAX_FILE_SIZE + 1);
+        assert!(matches!(load_level_from_str(&huge), Err(LevelError::FileTooLarge(_))));
+
+        let nested = format!("(rooms: [{}", "[".repeat(100_000));
+        assert!(matches!(load_level_from_str(&nested), Err(LevelError::ParseError(_))));
+
+        // A limit hit by an earlier parse doesn't leak into the next error
+        let rooms = vec![room(0, 0, "[]"); limits::MAX_ROOMS + 1];
+        limit(load_level_from_str(&level(&rooms)));
+        assert!(matches!(load_level_from_str("(rooms: 5)"), Err(LevelError::ParseError(_))));
+    }
 }
@@ -0,0 +1,411 @@
+//! Level file limits
+//!
+//! Level files come from users (uploads, shared zips), so loading never trusts the
+//! sizes inside them. Collections and strings are checked while they're being
+//! deserialized, before anything oversized gets allocated; numbers are checked in a
+//! cheap pass over the (already bounded) result.
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use crate::rasterizer::Vec3;
+use super::super::{HorizontalFace, Level, Room, Sector, VerticalFace, SUBDIVISION_LEVELS};
+use super::{LevelError, RoomPrefab, SectorSnippet};
+
+/// Largest level file accepted (bytes)
+pub const MAX_FILE_SIZE: usize = 16 * 1024 * 1024;
+/// Rooms per level
+pub const MAX_ROOMS: usize = 1024;
+/// Sectors along either side of a room
+pub const MAX_ROOM_SIZE: usize = 256;
+/// Stacked walls on one sector edge
+pub const MAX_WALLS_PER_EDGE: usize = 64;
+/// Portals per room
+pub const MAX_PORTALS_PER_ROOM: usize = 1024;
+/// Pickups per room
+pub const MAX_PICKUPS_PER_ROOM: usize = 1024;
+/// Ambient sound emitters per room
+pub const MAX_AMBIENTS_PER_ROOM: usize = 1024;
+/// Doors per room
+pub const MAX_DOORS_PER_ROOM: usize = 1024;
+/// Point lights per room
+pub const MAX_LIGHTS_PER_ROOM: usize = 1024;
+/// Detail heights of a subdivided floor or ceiling (a 5x5 grid at the finest level)
+pub const MAX_DETAIL_HEIGHTS: usize = 25;
+/// Texture pack substitutes per level
+pub const MAX_PACK_ALIASES: usize = 256;
+/// Names (rooms, textures, pickup kinds), in bytes
+pub const MAX_STRING_LEN: usize = 256;
+/// Largest absolute coordinate or height (1024 sectors from the origin)
+pub const MAX_COORDINATE: f32 = 1024.0 * super::super::SECTOR_SIZE;
+/// Deepest nesting the parser will follow
+pub const MAX_NESTING: usize = 32;
+
+/// A count or size in a level file over its limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// What was too big (e.g. "rooms", "walls per edge")
+    pub what: &'static str,
+    pub max: usize,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "too many {} (max {})", self.what, self.max)
+    }
+}
+
+thread_local! {
+    /// Set when a bounded deserializer gives up, so the loader can report the limit
+    /// instead of a generic parse error
+    static VIOLATION: Cell<Option<LimitExceeded>> = const { Cell::new(None) };
+}
+
+/// Take the limit hit by the last failed parse on this thread (if any)
+pub(super) fn take_violation() -> Option<LimitExceeded> {
+    VIOLATION.with(|v| v.take())
+}
+
+fn exceeded<E: de::Error>(what: &'static str, max: usize) -> E {
+    let limit = LimitExceeded { what, max };
+    VIOLATION.with(|v| v.set(Some(limit)));
+    E::custom(limit)
+}
+
+/// Sequence that fails as soon as it has more than `max` elements
+struct BoundedSeq<T> {
+    what: &'static str,
+    max: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T> BoundedSeq<T> {
+    fn new(what: &'static str, max: usize) -> Self {
+        Self { what, max, marker: PhantomData }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for BoundedSeq<T> {
+    type Value = Vec<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for BoundedSeq<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a list of at most {} {}", self.max, self.what)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(self.max));
+        while let Some(item) = seq.next_element()? {
+            if items.len() == self.max {
+                return Err(exceeded(self.what, self.max));
+            }
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+pub(crate) fn rooms<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    BoundedSeq::new("rooms", MAX_ROOMS).deserialize(d)
+}
+
+pub(crate) fn walls<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<VerticalFace>, D::Error> {
+    BoundedSeq::new("walls per edge", MAX_WALLS_PER_EDGE).deserialize(d)
+}
+
+pub(crate) fn portals<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    BoundedSeq::new("portals per room", MAX_PORTALS_PER_ROOM).deserialize(d)
+}
+
+pub(crate) fn pickups<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    BoundedSeq::new("pickups per room", MAX_PICKUPS_PER_ROOM).deserialize(d)
+}
+
+pub(crate) fn ambients<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    BoundedSeq::new("ambient sounds per room", MAX_AMBIENTS_PER_ROOM).deserialize(d)
+}
+
+pub(crate) fn doors<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    BoundedSeq::new("doors per room", MAX_DOORS_PER_ROOM).deserialize(d)
+}
+
+pub(crate) fn lights<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    BoundedSeq::new("lights per room", MAX_LIGHTS_PER_ROOM).deserialize(d)
+}
+
+pub(crate) fn snippet_sectors<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    BoundedSeq::new("sectors in a snippet", MAX_ROOM_SIZE * MAX_ROOM_SIZE).deserialize(d)
+}
+
+pub(crate) fn detail_heights<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<f32>, D::Error> {
+    BoundedSeq::new("detail heights per face", MAX_DETAIL_HEIGHTS).deserialize(d)
+}
+
+/// Room sector grid: at most MAX_ROOM_SIZE columns of at most MAX_ROOM_SIZE sectors
+pub(crate) fn sector_grid<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Vec<Option<Sector>>>, D::Error> {
+    struct GridVisitor;
+
+    impl<'de> Visitor<'de> for GridVisitor {
+        type Value = Vec<Vec<Option<Sector>>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a sector grid of at most {0}x{0}", MAX_ROOM_SIZE)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut columns = Vec::new();
+            while let Some(column) = seq.next_element_seed(BoundedSeq::new("sectors along room depth", MAX_ROOM_SIZE))? {
+                if columns.len() == MAX_ROOM_SIZE {
+                    return Err(exceeded("sectors along room width", MAX_ROOM_SIZE));
+                }
+                columns.push(column);
+            }
+            Ok(columns)
+        }
+    }
+
+    d.deserialize_seq(GridVisitor)
+}
+
+/// String of at most MAX_STRING_LEN bytes (checked before it's copied)
+pub(crate) fn string<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    struct StringVisitor;
+
+    impl<'de> Visitor<'de> for StringVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a string of at most {} bytes", MAX_STRING_LEN)
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<String, E> {
+            if s.len() > MAX_STRING_LEN {
+                return Err(exceeded("bytes in a name", MAX_STRING_LEN));
+            }
+            Ok(s.to_string())
+        }
+    }
+
+    d.deserialize_str(StringVisitor)
+}
+
+/// A name inside a collection, with the same limit as `string`
+struct Name;
+
+impl<'de> DeserializeSeed<'de> for Name {
+    type Value = String;
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<String, D::Error> {
+        string(d)
+    }
+}
+
+/// Pack aliases: at most MAX_PACK_ALIASES, names bounded like `string`
+pub(crate) fn pack_aliases<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<String, String>, D::Error> {
+    struct AliasVisitor;
+
+    impl<'de> Visitor<'de> for AliasVisitor {
+        type Value = BTreeMap<String, String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a map of at most {} pack aliases", MAX_PACK_ALIASES)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut aliases = BTreeMap::new();
+            while let Some(pack) = map.next_key_seed(Name)? {
+                if aliases.len() == MAX_PACK_ALIASES {
+                    return Err(exceeded("pack aliases", MAX_PACK_ALIASES));
+                }
+                aliases.insert(pack, map.next_value_seed(Name)?);
+            }
+            Ok(aliases)
+        }
+    }
+
+    d.deserialize_map(AliasVisitor)
+}
+
+/// Options for parsing level files (nesting capped at MAX_NESTING)
+pub(super) fn ron_options() -> ron::Options {
+    ron::Options::default().with_recursion_limit(MAX_NESTING)
+}
+
+fn check_value(value: f32, what: impl FnOnce() -> String) -> Result<(), LevelError> {
+    if value.is_finite() && value.abs() <= MAX_COORDINATE {
+        Ok(())
+    } else {
+        Err(LevelError::InvalidValue(format!("{}: {}", what(), value)))
+    }
+}
+
+fn check_vec3(v: Vec3, what: impl Fn() -> String) -> Result<(), LevelError> {
+    check_value(v.x, &what)?;
+    check_value(v.y, &what)?;
+    check_value(v.z, &what)
+}
+
+fn check_heights(heights: &[f32; 4], what: impl Fn() -> String) -> Result<(), LevelError> {
+    heights.iter().try_for_each(|&h| check_value(h, &what))
+}
+
+fn check_uv(uv: &Option<[crate::rasterizer::Vec2; 4]>, what: impl Fn() -> String) -> Result<(), LevelError> {
+    uv.iter().flatten().try_for_each(|c| {
+        check_value(c.x, &what)?;
+        check_value(c.y, &what)
+    })
+}
+
+fn check_horizontal(face: &HorizontalFace, what: impl Fn() -> String) -> Result<(), LevelError> {
+    check_heights(&face.heights, &what)?;
+    check_uv(&face.uv, &what)?;
+    let side = face.cells() + 1;
+    let detail_len_ok = face.detail.is_empty() || face.detail.len() == side * side;
+    if !SUBDIVISION_LEVELS.contains(&face.subdivision) || !detail_len_ok {
+        return Err(LevelError::InvalidValue(format!(
+            "{}: subdivision {} with {} detail heights", what(), face.subdivision, face.detail.len()
+        )));
+    }
+    face.detail.iter().try_for_each(|&h| check_value(h, &what))
+}
+
+fn check_vertical(face: &VerticalFace, what: impl Fn() -> String) -> Result<(), LevelError> {
+    check_heights(&face.heights, &what)?;
+    check_uv(&face.uv, &what)?;
+    // NaN fails both comparisons, so it's caught here too
+    if !(0.0 <= face.x_start && face.x_start < face.x_end && face.x_end <= 1.0) {
+        return Err(LevelError::InvalidValue(format!(
+            "{}: wall span {}-{}", what(), face.x_start, face.x_end
+        )));
+    }
+    Ok(())
+}
+
+fn check_sector(sector: &Sector, what: impl Fn() -> String) -> Result<(), LevelError> {
+    if let Some(floor) = &sector.floor {
+        check_horizontal(floor, &what)?;
+    }
+    if let Some(ceiling) = &sector.ceiling {
+        check_horizontal(ceiling, &what)?;
+    }
+    let walls = sector.walls_north.iter()
+        .chain(&sector.walls_east)
+        .chain(&sector.walls_south)
+        .chain(&sector.walls_west);
+    for wall in walls {
+        check_vertical(wall, &what)?;
+    }
+    Ok(())
+}
+
+/// Check a pasted sector snippet like the sectors of a level file, and that it
+/// fits in a room
+pub(super) fn validate_snippet(snippet: &SectorSnippet) -> Result<(), LevelError> {
+    for (x, z, sector) in &snippet.sectors {
+        if *x >= MAX_ROOM_SIZE || *z >= MAX_ROOM_SIZE {
+            return Err(LevelError::LimitExceeded(LimitExceeded { what: "sectors along a room side", max: MAX_ROOM_SIZE }));
+        }
+        check_sector(sector, || format!("sector ({}, {})", x, z))?;
+    }
+    Ok(())
+}
+
+/// Check what the bounded parse can't: finite, sane numbers and grids that match
+/// their declared size
+pub(super) fn validate(level: &Level) -> Result<(), LevelError> {
+    check_vec3(level.light_dir, || "level light direction".to_string())?;
+    let fov_range = crate::rasterizer::MIN_FOV_DEGREES..=crate::rasterizer::MAX_FOV_DEGREES;
+    if !fov_range.contains(&level.game_fov) {
+        return Err(LevelError::InvalidValue(format!("level game FOV: {}", level.game_fov)));
+    }
+
+    for (slot, bookmark) in level.camera_bookmarks.iter().enumerate() {
+        let Some(bookmark) = bookmark else { continue };
+        let what = || format!("camera bookmark {}", slot + 1);
+        check_vec3(bookmark.position, what)?;
+        check_value(bookmark.rotation_x, what)?;
+        check_value(bookmark.rotation_y, what)?;
+    }
+
+    for (i, room) in level.rooms.iter().enumerate() {
+        validate_room(i, room)?;
+    }
+
+    Ok(())
+}
+
+/// Check one room of a level (`i` is its index, for the error message)
+fn validate_room(i: usize, room: &Room) -> Result<(), LevelError> {
+    if room.width > MAX_ROOM_SIZE || room.depth > MAX_ROOM_SIZE {
+        return Err(LevelError::LimitExceeded(LimitExceeded { what: "sectors along a room side", max: MAX_ROOM_SIZE }));
+    }
+    if room.sectors.len() != room.width || room.sectors.iter().any(|column| column.len() != room.depth) {
+        return Err(LevelError::InvalidValue(format!(
+            "room {}: sector grid doesn't match its {}x{} size", i, room.width, room.depth
+        )));
+    }
+
+    check_vec3(room.position, || format!("room {} position", i))?;
+    check_value(room.ambient, || format!("room {} ambient", i))?;
+    if let Some(dir) = room.light_dir {
+        check_vec3(dir, || format!("room {} light direction", i))?;
+    }
+
+    for (x, column) in room.sectors.iter().enumerate() {
+        for (z, sector) in column.iter().enumerate() {
+            let Some(sector) = sector else { continue };
+            check_sector(sector, || format!("room {} sector ({}, {})", i, x, z))?;
+        }
+    }
+
+    for portal in &room.portals {
+        for &v in &portal.vertices {
+            check_vec3(v, || format!("room {} portal", i))?;
+        }
+        check_vec3(portal.normal, || format!("room {} portal normal", i))?;
+    }
+    for pickup in &room.pickups {
+        check_vec3(pickup.position, || format!("room {} pickup", i))?;
+    }
+    for ambient in &room.ambients {
+        let what = || format!("room {} ambient sound", i);
+        check_vec3(ambient.position, what)?;
+        check_value(ambient.volume, what)?;
+        check_value(ambient.radius, what)?;
+    }
+    for light in &room.lights {
+        let what = || format!("room {} light", i);
+        check_vec3(light.position, what)?;
+        check_value(light.intensity, what)?;
+        check_value(light.radius, what)?;
+    }
+    for door in &room.doors {
+        let what = || format!("room {} door", i);
+        check_heights(&door.closed, what)?;
+        check_heights(&door.open, what)?;
+        check_value(door.duration, what)?;
+        // The door's wall has to be there: in the grid, on a sector, in the edge's stack
+        let found = room.get_sector(door.x, door.z).is_some_and(|s| door.wall < s.walls(door.edge).len());
+        if !found {
+            return Err(LevelError::InvalidValue(format!(
+                "{}: no {:?} wall {} at sector ({}, {})", what(), door.edge, door.wall, door.x, door.z
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a room prefab like a room of a level file
+pub(super) fn validate_prefab(prefab: &RoomPrefab) -> Result<(), LevelError> {
+    validate_room(0, &prefab.room)
+}
@@ -0,0 +1,158 @@
+//! Point lights
+//!
+//! A point light brightens the faces around it, fading out at its radius and only
+//! on faces turned toward it. Lights are added to vertex colors: normally when a
+//! room's render data is built (`Room::to_render_data_for`), or with
+//! `RasterSettings::dynamic_lights` (an editor preview) to the finished vertices
+//! every frame, so moving a light doesn't rebuild the room. Both paths light each
+//! vertex with `lit_color`, so they look the same.
+
+use serde::{Serialize, Deserialize};
+use crate::rasterizer::{Color, Vec3, Vertex};
+use super::{Falloff, Level, Room, SECTOR_SIZE};
+
+/// Default radius of a new light (world units)
+pub const LIGHT_DEFAULT_RADIUS: f32 = SECTOR_SIZE * 3.0;
+
+/// Above this many lights in a level, dynamic lighting switches itself off
+pub const MAX_DYNAMIC_LIGHTS: usize = 64;
+
+fn default_light_color() -> Color {
+    Color::WHITE
+}
+
+fn default_light_intensity() -> f32 {
+    1.0
+}
+
+fn default_light_radius() -> f32 {
+    LIGHT_DEFAULT_RADIUS
+}
+
+/// A light placed in a room
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointLight {
+    /// Position relative to the room origin
+    pub position: Vec3,
+    #[serde(default = "default_light_color")]
+    pub color: Color,
+    /// How much a white light brightens a face right at it (1.0 = twice as bright)
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+    /// Distance (world units) at which the light fades out completely
+    #[serde(default = "default_light_radius")]
+    pub radius: f32,
+    #[serde(default)]
+    pub falloff: Falloff,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            color: default_light_color(),
+            intensity: default_light_intensity(),
+            radius: LIGHT_DEFAULT_RADIUS,
+            falloff: Falloff::default(),
+        }
+    }
+
+    /// Brightness added at `point` (world space) on a surface facing `normal`, for a
+    /// light in a room whose origin is `origin`
+    pub fn amount_at(&self, origin: Vec3, point: Vec3, normal: Vec3) -> f32 {
+        let to_light = origin + self.position - point;
+        let distance = to_light.len();
+        let gain = self.falloff.gain(distance, self.radius);
+        if gain <= 0.0 {
+            return 0.0;
+        }
+        // Full on faces looking straight at the light, nothing on faces turned away
+        let facing = if distance > 0.0 { normal.dot(to_light) / distance } else { 1.0 };
+        self.intensity.max(0.0) * gain * facing.max(0.0)
+    }
+}
+
+/// Vertex color at `point` (world space) facing `normal`, lit by `lights` placed in
+/// a room whose origin is `origin`. Each light scales the tint up by its color times
+/// its brightness there, so darkened (AO) corners stay darker.
+pub fn lit_color(color: Color, point: Vec3, normal: Vec3, origin: Vec3, lights: &[PointLight]) -> Color {
+    let mut gain = [1.0f32; 3];
+    for light in lights {
+        let amount = light.amount_at(origin, point, normal);
+        if amount > 0.0 {
+            for (g, c) in gain.iter_mut().zip([light.color.r, light.color.g, light.color.b]) {
+                *g += amount * c as f32 / 255.0;
+            }
+        }
+    }
+    let scale = |c: u8, g: f32| (c as f32 * g).round().min(255.0) as u8;
+    Color::with_alpha(scale(color.r, gain[0]), scale(color.g, gain[1]), scale(color.b, gain[2]), color.a)
+}
+
+impl Room {
+    /// Light render data of this room (world space vertices) with its point lights
+    pub fn light_vertices(&self, vertices: &mut [Vertex]) {
+        if self.lights.is_empty() {
+            return;
+        }
+        for vertex in vertices {
+            vertex.color = lit_color(vertex.color, vertex.pos, vertex.normal, self.position, &self.lights);
+        }
+    }
+}
+
+impl Level {
+    /// Point lights in every room
+    pub fn light_count(&self) -> usize {
+        self.rooms.iter().map(|r| r.lights.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::RasterSettings;
+    use crate::world::TextureRef;
+
+    /// One floor at 0 in a room away from the origin, lit from above its NW corner
+    fn lit_room() -> Room {
+        let mut room = Room::new(0, Vec3::new(2048.0, 0.0, -1024.0), 1, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.lights.push(PointLight::new(Vec3::new(0.0, 512.0, 0.0)));
+        room
+    }
+
+    #[test]
+    fn falls_off_with_distance_and_skips_faces_turned_away() {
+        let light = PointLight::new(Vec3::new(0.0, 512.0, 0.0));
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let below = light.amount_at(Vec3::ZERO, Vec3::ZERO, up);
+        let further = light.amount_at(Vec3::ZERO, Vec3::new(1024.0, 0.0, 0.0), up);
+        assert!(below > further && further > 0.0);
+        assert_eq!(light.amount_at(Vec3::ZERO, Vec3::ZERO, Vec3::new(0.0, -1.0, 0.0)), 0.0);
+        assert_eq!(light.amount_at(Vec3::ZERO, Vec3::new(LIGHT_DEFAULT_RADIUS, 0.0, 0.0), up), 0.0);
+
+        // Scales the tint: a darkened corner gets brighter but stays darker
+        let dark = Color::new(64, 64, 64);
+        let lit = lit_color(dark, Vec3::ZERO, up, Vec3::ZERO, std::slice::from_ref(&light));
+        assert!(lit.r > dark.r && lit.r < lit_color(Color::NEUTRAL, Vec3::ZERO, up, Vec3::ZERO, &[light]).r);
+    }
+
+    #[test]
+    fn dynamic_lighting_matches_static() {
+        let room = lit_room();
+        let resolve = |_: &TextureRef| None;
+        let static_settings = RasterSettings::default();
+        let dynamic_settings = RasterSettings { dynamic_lights: true, ..RasterSettings::default() };
+
+        let (baked, _) = room.to_render_data_for(&static_settings, resolve);
+        let (mut dynamic, _) = room.to_render_data_for(&dynamic_settings, resolve);
+        assert!(dynamic.iter().all(|v| v.color == Color::NEUTRAL), "dynamic meshes are built unlit");
+        room.light_vertices(&mut dynamic);
+
+        let colors = |vertices: &[Vertex]| vertices.iter().map(|v| v.color).collect::<Vec<_>>();
+        assert_eq!(colors(&baked), colors(&dynamic));
+        // The NW corner is right under the light
+        assert!(baked[0].color.r > baked[2].color.r && baked[2].color.r >= Color::NEUTRAL.r);
+    }
+}
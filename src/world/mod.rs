@@ -2,11 +2,48 @@
 //!
 //! Clean architecture for PS1-style 3D environments:
 //! - Room-based geometry with portal connectivity
+//! - Portal suggestions for rooms that touch
+//! - Floor/ceiling openings between stacked rooms
 //! - Visibility culling through portals
 //! - Tile-based collision detection
+//! - Collectible pickups
+//! - Ambient sound emitters with distance falloff
+//! - Sliding doors
+//! - Point lights, baked into vertex colors or lit per frame
+//! - Line-of-sight ray casts
+//! - Baked corner ambient occlusion (vertex colors)
+//! - Blockout import from Wavefront OBJ
+//! - Structural diffs between two versions of a level
+//! - Undo/redo history of level snapshots
+//! - Level-wide face search by texture, flags, height and more
+//! - Texture ids for texture references, with substitutes for missing packs
 
+mod ambient;
+mod ao;
+mod connect;
+mod diff;
+mod door;
+mod find;
 mod geometry;
+mod history;
 mod level;
+mod light;
+mod obj;
+mod opening;
+mod pickup;
+mod raycast;
+mod texture_index;
 
+pub use ambient::*;
+pub use connect::*;
+pub use diff::*;
+pub use door::*;
+pub use find::*;
 pub use geometry::*;
+pub use history::*;
 pub use level::*;
+pub use light::*;
+pub use obj::*;
+pub use pickup::*;
+pub use raycast::*;
+pub use texture_index::*;
@@ -0,0 +1,278 @@
+//! Wavefront OBJ import
+//!
+//! Turns a blockout mesh into a best-effort room. Near-horizontal triangles become
+//! floors and ceilings (per sector, the lowest surface is the floor and the highest
+//! the ceiling), near-vertical ones become walls on sector edges. Extents snap to the
+//! sector grid and heights to clicks. Sloped, oblique and off-grid triangles are
+//! skipped and counted, so the caller can say how much cleanup to expect.
+
+use std::collections::HashSet;
+use crate::rasterizer::Vec3;
+use super::limits::{MAX_ROOM_SIZE, MAX_WALLS_PER_EDGE};
+use super::{Direction, Level, Room, TextureRef, CLICK_HEIGHT, SECTOR_SIZE};
+
+/// Triangles within this many degrees of horizontal/vertical count as floors/walls
+const AXIS_TOLERANCE_DEG: f32 = 10.0;
+/// How far a wall may sit off a grid line (fraction of a sector) and still snap to it
+const WALL_SNAP: f32 = 0.25;
+
+/// A room built from an OBJ mesh, with how many triangles made it in
+#[derive(Debug, Clone)]
+pub struct ObjImport {
+    pub room: Room,
+    /// Triangles that became (part of) a floor, ceiling or wall
+    pub converted: usize,
+    /// Triangles left out: sloped, oblique, off the grid, degenerate, or between a
+    /// sector's floor and ceiling
+    pub skipped: usize,
+}
+
+impl ObjImport {
+    /// One-line report for the status bar
+    pub fn summary(&self) -> String {
+        format!(
+            "{} of {} triangles converted, {} skipped",
+            self.converted, self.converted + self.skipped, self.skipped
+        )
+    }
+}
+
+/// 0-based vertex index of a face element ("7", "7/1", "7//3"; negative = from the end)
+fn vertex_index(element: &str, count: usize) -> Option<usize> {
+    let i: i64 = element.split('/').next()?.parse().ok()?;
+    let index = if i < 0 { count as i64 + i } else { i - 1 };
+    (0..count as i64).contains(&index).then_some(index as usize)
+}
+
+/// Vertices and (fan-triangulated) faces of an OBJ file; everything else is ignored
+fn parse_obj(text: &str) -> Result<(Vec<Vec3>, Vec<[usize; 3]>), String> {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => {
+                let coords: Vec<f32> = parts.take(3).filter_map(|p| p.parse().ok()).collect();
+                let [x, y, z] = coords[..] else {
+                    return Err(format!("line {}: bad vertex", line_no + 1));
+                };
+                vertices.push(Vec3::new(x, y, z));
+            }
+            Some("f") => {
+                let indices = parts.map(|p| vertex_index(p, vertices.len()))
+                    .collect::<Option<Vec<usize>>>()
+                    .ok_or_else(|| format!("line {}: bad face", line_no + 1))?;
+                for i in 1..indices.len().saturating_sub(1) {
+                    triangles.push([indices[0], indices[i], indices[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((vertices, triangles))
+}
+
+fn snap(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
+
+/// Is (x, z) inside the triangle's XZ projection (edges included)?
+fn covers_xz(tri: &[Vec3; 3], x: f32, z: f32) -> bool {
+    let edge = |p: Vec3, q: Vec3| (q.x - p.x) * (z - p.z) - (q.z - p.z) * (x - p.x);
+    let d = [edge(tri[0], tri[1]), edge(tri[1], tri[2]), edge(tri[2], tri[0])];
+    // Cell centers on a quad's diagonal belong to both halves
+    let eps = SECTOR_SIZE;
+    d.iter().all(|&e| e >= -eps) || d.iter().all(|&e| e <= eps)
+}
+
+/// A wall triangle snapped to a grid line
+struct WallCandidate {
+    /// Wall on a line of constant X (else constant Z)
+    along_z: bool,
+    /// Grid line index (sector boundary)
+    line: usize,
+    /// Sectors spanned along the line
+    cells: std::ops::Range<usize>,
+    bottom: f32,
+    top: f32,
+    /// Normal points toward +X/+Z
+    faces_positive: bool,
+    triangle: usize,
+}
+
+impl Level {
+    /// Build a room from an OBJ mesh. `scale` converts OBJ units to world units
+    /// (`SECTOR_SIZE` makes one unit one sector). The room gets the next free id
+    /// but isn't added to the level.
+    pub fn import_obj(&self, data: &[u8], scale: f32) -> Result<ObjImport, String> {
+        let (vertices, triangles) = parse_obj(&String::from_utf8_lossy(data))?;
+        if triangles.is_empty() {
+            return Err("no faces in OBJ".to_string());
+        }
+        let vertices: Vec<Vec3> = vertices.iter().map(|&v| v * scale).collect();
+
+        // Grid origin: the mesh's minimum corner, snapped down to the sector grid
+        let (mut min, mut max) = (vertices[triangles[0][0]], vertices[triangles[0][0]]);
+        for &v in triangles.iter().flatten().map(|&i| &vertices[i]) {
+            min = Vec3::new(min.x.min(v.x), min.y.min(v.y), min.z.min(v.z));
+            max = Vec3::new(max.x.max(v.x), max.y.max(v.y), max.z.max(v.z));
+        }
+        let origin = Vec3::new(
+            (min.x / SECTOR_SIZE).floor() * SECTOR_SIZE,
+            0.0,
+            (min.z / SECTOR_SIZE).floor() * SECTOR_SIZE,
+        );
+        let width = (((max.x - origin.x) / SECTOR_SIZE).ceil() as usize).max(1);
+        let depth = (((max.z - origin.z) / SECTOR_SIZE).ceil() as usize).max(1);
+        if width > MAX_ROOM_SIZE || depth > MAX_ROOM_SIZE {
+            return Err(format!("mesh spans {}x{} sectors (max {})", width, depth, MAX_ROOM_SIZE));
+        }
+
+        let cos_tol = AXIS_TOLERANCE_DEG.to_radians().cos();
+        let sin_tol = AXIS_TOLERANCE_DEG.to_radians().sin();
+        // Horizontal surfaces per sector as (height, triangle), indexed x * depth + z
+        let mut surfaces: Vec<Vec<(f32, usize)>> = vec![Vec::new(); width * depth];
+        let mut walls = Vec::new();
+        let mut used = vec![false; triangles.len()];
+
+        for (t, indices) in triangles.iter().enumerate() {
+            let tri = indices.map(|i| vertices[i] - origin);
+            let normal = (tri[1] - tri[0]).cross(tri[2] - tri[0]);
+            if normal.len() < 1e-3 {
+                continue; // Degenerate
+            }
+            let n = normal.normalize();
+
+            if n.y.abs() >= cos_tol {
+                let height = snap((tri[0].y + tri[1].y + tri[2].y) / 3.0, CLICK_HEIGHT);
+                let cell = |v: f32, len: usize| ((v / SECTOR_SIZE).floor().max(0.0) as usize).min(len - 1);
+                let lo = tri.iter().fold(Vec3::new(f32::MAX, 0.0, f32::MAX), |a, v| Vec3::new(a.x.min(v.x), 0.0, a.z.min(v.z)));
+                let hi = tri.iter().fold(Vec3::new(f32::MIN, 0.0, f32::MIN), |a, v| Vec3::new(a.x.max(v.x), 0.0, a.z.max(v.z)));
+                for x in cell(lo.x, width)..=cell(hi.x, width) {
+                    for z in cell(lo.z, depth)..=cell(hi.z, depth) {
+                        if covers_xz(&tri, (x as f32 + 0.5) * SECTOR_SIZE, (z as f32 + 0.5) * SECTOR_SIZE) {
+                            surfaces[x * depth + z].push((height, t));
+                        }
+                    }
+                }
+            } else if n.y.abs() <= sin_tol {
+                let along_z = if n.x.abs() >= cos_tol {
+                    true
+                } else if n.z.abs() >= cos_tol {
+                    false
+                } else {
+                    continue; // Oblique
+                };
+                let (plane, span): (Vec<f32>, Vec<f32>) = if along_z {
+                    (tri.iter().map(|v| v.x).collect(), tri.iter().map(|v| v.z).collect())
+                } else {
+                    (tri.iter().map(|v| v.z).collect(), tri.iter().map(|v| v.x).collect())
+                };
+                let plane = plane.iter().sum::<f32>() / 3.0;
+                let line = snap(plane, SECTOR_SIZE);
+                if (plane - line).abs() > WALL_SNAP * SECTOR_SIZE {
+                    continue; // Off the grid
+                }
+                let start = snap(span.iter().copied().fold(f32::MAX, f32::min), SECTOR_SIZE);
+                let end = snap(span.iter().copied().fold(f32::MIN, f32::max), SECTOR_SIZE);
+                let bottom = snap(tri.iter().map(|v| v.y).fold(f32::MAX, f32::min), CLICK_HEIGHT);
+                let top = snap(tri.iter().map(|v| v.y).fold(f32::MIN, f32::max), CLICK_HEIGHT);
+                if end <= start || top <= bottom {
+                    continue; // Too small to snap
+                }
+                walls.push(WallCandidate {
+                    along_z,
+                    line: (line / SECTOR_SIZE) as usize,
+                    cells: (start / SECTOR_SIZE) as usize..(end / SECTOR_SIZE) as usize,
+                    bottom,
+                    top,
+                    faces_positive: if along_z { n.x > 0.0 } else { n.z > 0.0 },
+                    triangle: t,
+                });
+            }
+        }
+
+        let mut room = Room::new(self.next_room_id(), origin, width, depth);
+
+        // Lowest surface over a sector is its floor, the highest its ceiling
+        for x in 0..width {
+            for z in 0..depth {
+                let cell = &surfaces[x * depth + z];
+                let Some(floor) = cell.iter().map(|s| s.0).reduce(f32::min) else { continue };
+                let ceiling = cell.iter().map(|s| s.0).fold(floor, f32::max);
+                room.set_floor(x, z, floor, TextureRef::none());
+                if ceiling > floor {
+                    room.set_ceiling(x, z, ceiling, TextureRef::none());
+                }
+                for &(height, t) in cell {
+                    if height == floor || height == ceiling {
+                        used[t] = true;
+                    }
+                }
+            }
+        }
+
+        // Each wall goes in the sector on the side with a floor, else the side it faces
+        let has_floor = |room: &Room, x: usize, z: usize| room.get_sector(x, z).is_some_and(|s| s.floor.is_some());
+        let mut placed = HashSet::new();
+        for wall in &walls {
+            for c in wall.cells.clone() {
+                // (x, z, direction) of the sector on each side of the line at this cell
+                let (low, high) = if wall.along_z {
+                    (wall.line.checked_sub(1).map(|x| (x, c, Direction::East)), (wall.line, c, Direction::West))
+                } else {
+                    (wall.line.checked_sub(1).map(|z| (c, z, Direction::South)), (c, wall.line, Direction::North))
+                };
+                let in_grid = |&(x, z, _): &(usize, usize, Direction)| x < width && z < depth;
+                let low = low.filter(in_grid);
+                let high = Some(high).filter(in_grid);
+                let floored = |side: Option<(usize, usize, Direction)>| side.filter(|&(x, z, _)| has_floor(&room, x, z));
+                let side = match (floored(low), floored(high)) {
+                    (Some(s), None) | (None, Some(s)) => Some(s),
+                    _ if wall.faces_positive => high.or(low),
+                    _ => low.or(high),
+                };
+                let Some((x, z, dir)) = side else { continue };
+                let key = (x, z, dir, wall.bottom.to_bits(), wall.top.to_bits());
+                let full = room.get_sector(x, z).is_some_and(|s| s.walls(dir).len() >= MAX_WALLS_PER_EDGE);
+                if !full && placed.insert(key) {
+                    room.add_wall(x, z, dir, wall.bottom, wall.top, TextureRef::none());
+                }
+                used[wall.triangle] = true;
+            }
+        }
+
+        room.recalculate_bounds();
+        let converted = used.iter().filter(|&&u| u).count();
+        Ok(ObjImport { room, converted, skipped: triangles.len() - converted })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_becomes_a_room() {
+        // A 2x1x1 box (one unit = one sector) and one sloped triangle
+        let obj = "\
+v 0 0 0\nv 2 0 0\nv 2 0 1\nv 0 0 1\nv 0 1 0\nv 2 1 0\nv 2 1 1\nv 0 1 1\n\
+v 0 0 3\nv 1 1 3\nv 0 1 4\n\
+f 1 2 3 4\nf 5/1 8/1 7/1 6/1\nf 1 5 6 2\nf 2 6 7 3\nf 3 7 8 4\nf 4 8 5 1\nf -3 -2 -1\n";
+        let import = Level::new().import_obj(obj.as_bytes(), SECTOR_SIZE).unwrap();
+        assert_eq!((import.converted, import.skipped), (12, 1));
+
+        let room = &import.room;
+        assert_eq!((room.width, room.depth), (2, 4));
+        for x in 0..2 {
+            let sector = room.get_sector(x, 0).unwrap();
+            assert_eq!(sector.floor.as_ref().unwrap().avg_height(), 0.0);
+            assert_eq!(sector.ceiling.as_ref().unwrap().avg_height(), SECTOR_SIZE);
+            assert_eq!(sector.walls(Direction::North).len(), 1);
+            assert_eq!(sector.walls(Direction::South).len(), 1);
+        }
+        assert_eq!(room.get_sector(0, 0).unwrap().walls(Direction::West)[0].y_top(), SECTOR_SIZE);
+        assert_eq!(room.get_sector(1, 0).unwrap().walls(Direction::East).len(), 1);
+        assert!(room.get_sector(0, 3).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+//! Vertical portals
+//!
+//! TR-style holes in a floor or ceiling that lead into a room stacked below or
+//! above. The face stays in the sector, marked with `HorizontalFace::opening`, so
+//! closing the hole restores it; it isn't drawn or collided with while open. Each
+//! opening also gets an ordinary `Portal` lying on the face (and one back from the
+//! other room), so anything that walks portals crosses it like any other.
+
+use crate::rasterizer::Vec3;
+use super::{HorizontalFace, Level, Portal, Room, SECTOR_SIZE};
+
+/// Slack (world units) when checking that a room covers an opening or sits below/above it
+const OPENING_EPSILON: f32 = 1.0;
+
+/// World XZ square of a sector: (min x, min z)
+fn sector_origin(room: &Room, x: usize, z: usize) -> (f32, f32) {
+    (room.position.x + x as f32 * SECTOR_SIZE, room.position.z + z as f32 * SECTOR_SIZE)
+}
+
+/// Does `room`'s world bounding box cover the sector square at `origin` on XZ?
+fn covers(room: &Room, origin: (f32, f32)) -> bool {
+    let b = room.world_bounds();
+    !b.is_empty()
+        && b.min.x <= origin.0 + OPENING_EPSILON
+        && b.max.x >= origin.0 + SECTOR_SIZE - OPENING_EPSILON
+        && b.min.z <= origin.1 + OPENING_EPSILON
+        && b.max.z >= origin.1 + SECTOR_SIZE - OPENING_EPSILON
+}
+
+/// World-space corners of a floor or ceiling (NW, NE, SE, SW)
+fn face_corners(face: &HorizontalFace, origin: (f32, f32)) -> [Vec3; 4] {
+    let (x, z) = origin;
+    [
+        Vec3::new(x, face.heights[0], z),
+        Vec3::new(x + SECTOR_SIZE, face.heights[1], z),
+        Vec3::new(x + SECTOR_SIZE, face.heights[2], z + SECTOR_SIZE),
+        Vec3::new(x, face.heights[3], z + SECTOR_SIZE),
+    ]
+}
+
+/// Index of the portal in `room` leading to `target` through the horizontal square at `origin`
+fn opening_portal(room: &Room, target: usize, origin: (f32, f32)) -> Option<usize> {
+    let center = (origin.0 + SECTOR_SIZE * 0.5, origin.1 + SECTOR_SIZE * 0.5);
+    room.portals.iter().position(|p| {
+        let c = p.center() + room.position;
+        p.target_room == target
+            && p.normal.y.abs() > 0.5
+            && (c.x - center.0).abs() < OPENING_EPSILON
+            && (c.z - center.1).abs() < OPENING_EPSILON
+    })
+}
+
+/// Floor (false) or ceiling (true) of a sector
+fn horizontal_mut(room: &mut Room, x: usize, z: usize, ceiling: bool) -> Option<&mut HorizontalFace> {
+    let sector = room.get_sector_mut(x, z)?;
+    if ceiling { sector.ceiling.as_mut() } else { sector.floor.as_mut() }
+}
+
+impl Level {
+    /// Room directly below a sector's floor (or above its ceiling) that covers the whole
+    /// sector on XZ, judged by world bounds. The nearest one wins.
+    pub fn vertical_neighbor(&self, room_idx: usize, x: usize, z: usize, ceiling: bool) -> Option<usize> {
+        let room = self.rooms.get(room_idx)?;
+        let sector = room.get_sector(x, z)?;
+        let face = if ceiling { sector.ceiling.as_ref()? } else { sector.floor.as_ref()? };
+        let origin = sector_origin(room, x, z);
+        let low = face.heights.iter().copied().fold(f32::MAX, f32::min);
+        let high = face.heights.iter().copied().fold(f32::MIN, f32::max);
+
+        self.rooms.iter().enumerate()
+            .filter(|&(i, other)| i != room_idx && covers(other, origin))
+            .filter_map(|(i, other)| {
+                let b = other.world_bounds();
+                if ceiling {
+                    (b.min.y >= high - OPENING_EPSILON).then_some((i, b.min.y - high))
+                } else {
+                    (b.max.y <= low + OPENING_EPSILON).then_some((i, low - b.max.y))
+                }
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Open a sector's floor (or ceiling) into the room below (above) it: marks the
+    /// face as an opening, adds a portal on it, and a portal back from the other room
+    /// (opening that room's matching ceiling/floor too, if it has one there).
+    /// Returns the other room's index, or None if no room is there.
+    pub fn create_vertical_portal(&mut self, room_idx: usize, x: usize, z: usize, ceiling: bool) -> Option<usize> {
+        let target_idx = self.vertical_neighbor(room_idx, x, z, ceiling)?;
+        let (room_id, target_id) = (self.rooms[room_idx].id, self.rooms[target_idx].id);
+        let origin = sector_origin(&self.rooms[room_idx], x, z);
+
+        let face = horizontal_mut(&mut self.rooms[room_idx], x, z, ceiling)?;
+        face.opening = Some(target_id);
+        let corners = face_corners(face, origin);
+        // Normals point into the room: up from a floor, down from a ceiling
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let (normal, back_normal) = if ceiling { (up * -1.0, up) } else { (up, up * -1.0) };
+
+        let room = &mut self.rooms[room_idx];
+        if opening_portal(room, target_id, origin).is_none() {
+            let position = room.position;
+            room.portals.push(Portal::new(target_id, corners.map(|v| v - position), normal));
+        }
+
+        let target = &mut self.rooms[target_idx];
+        let center = (origin.0 + SECTOR_SIZE * 0.5, origin.1 + SECTOR_SIZE * 0.5);
+        if let Some((tx, tz)) = target.world_to_grid(center.0, center.1) {
+            if let Some(back) = horizontal_mut(target, tx, tz, !ceiling) {
+                back.opening = Some(room_id);
+            }
+        }
+        if opening_portal(target, room_id, origin).is_none() {
+            let position = target.position;
+            target.portals.push(Portal::new(room_id, corners.map(|v| v - position), back_normal));
+        }
+        Some(target_idx)
+    }
+
+    /// Close a floor (or ceiling) opening: restores the face on both sides and removes
+    /// the portal pair. Returns false if the face wasn't open.
+    pub fn remove_vertical_portal(&mut self, room_idx: usize, x: usize, z: usize, ceiling: bool) -> bool {
+        let Some(room) = self.rooms.get_mut(room_idx) else { return false };
+        let room_id = room.id;
+        let origin = sector_origin(room, x, z);
+        let Some(target_id) = horizontal_mut(room, x, z, ceiling).and_then(|f| f.opening.take()) else {
+            return false;
+        };
+        if let Some(i) = opening_portal(room, target_id, origin) {
+            room.portals.remove(i);
+        }
+
+        if let Some(target) = self.room_index(target_id).map(|i| &mut self.rooms[i]) {
+            let center = (origin.0 + SECTOR_SIZE * 0.5, origin.1 + SECTOR_SIZE * 0.5);
+            if let Some((tx, tz)) = target.world_to_grid(center.0, center.1) {
+                if let Some(back) = horizontal_mut(target, tx, tz, !ceiling) {
+                    if back.opening == Some(room_id) {
+                        back.opening = None;
+                    }
+                }
+            }
+            if let Some(i) = opening_portal(target, room_id, origin) {
+                target.portals.remove(i);
+            }
+        }
+        true
+    }
+
+    /// Count floor/ceiling openings whose target room is gone or no longer covers the
+    /// opening on XZ (e.g. after moving a room). Used to warn on save and after moves.
+    pub fn misplaced_openings(&self) -> usize {
+        let mut count = 0;
+        for room in &self.rooms {
+            for (x, z, sector) in room.iter_sectors() {
+                let origin = sector_origin(room, x, z);
+                for face in sector.floor.iter().chain(&sector.ceiling) {
+                    let Some(target) = face.opening else { continue };
+                    let covered = self.room_index(target).is_some_and(|t| covers(&self.rooms[t], origin));
+                    if !covered {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{TextureRef, CLICK_HEIGHT};
+
+    /// 2x2 room at `y` with floor and ceiling
+    fn slab(id: usize, position: Vec3, y: f32) -> Room {
+        let mut room = Room::new(id, position, 2, 2);
+        for x in 0..2 {
+            for z in 0..2 {
+                room.set_floor(x, z, y, TextureRef::none());
+                room.set_ceiling(x, z, y + 4.0 * CLICK_HEIGHT, TextureRef::none());
+            }
+        }
+        room.recalculate_bounds();
+        room
+    }
+
+    #[test]
+    fn floor_opening_drops_into_room_below() {
+        let mut level = Level::new();
+        level.add_room(slab(0, Vec3::ZERO, 2048.0));
+        level.add_room(slab(1, Vec3::ZERO, 0.0));
+
+        assert_eq!(level.vertical_neighbor(0, 1, 1, false), Some(1));
+        assert_eq!(level.vertical_neighbor(1, 1, 1, false), None);
+        assert_eq!(level.create_vertical_portal(0, 1, 1, false), Some(1));
+
+        // Both faces are open and the portals pair up like wall portals do
+        assert_eq!(level.rooms[0].get_sector(1, 1).unwrap().floor.as_ref().unwrap().opening, Some(1));
+        assert_eq!(level.rooms[1].get_sector(1, 1).unwrap().ceiling.as_ref().unwrap().opening, Some(0));
+        assert_eq!(level.misaligned_portals(0), 0);
+        assert_eq!(level.misplaced_openings(), 0);
+
+        // Nothing to stand on in the open sector of the upper room: the floor below is next
+        let above = Vec3::new(1536.0, 2100.0, 1536.0);
+        assert_eq!(level.floor_at(above).map(|(room, _, y)| (room, y)), Some((1, 0.0)));
+        assert_eq!(level.floor_at(Vec3::new(512.0, 2100.0, 512.0)).map(|(room, _, _)| room), Some(0));
+
+        // A ray straight down passes through the portal and hits the lower floor
+        let hit = level.raycast(above, Vec3::new(1536.0, -100.0, 1536.0)).unwrap();
+        assert_eq!((hit.room, hit.face), (1, crate::world::HitFace::Floor));
+
+        // Moving the lower room away leaves the opening hanging
+        level.rooms[1].position.x += 4096.0;
+        assert_eq!(level.misplaced_openings(), 2);
+        level.rooms[1].position.x -= 4096.0;
+
+        assert!(level.remove_vertical_portal(0, 1, 1, false));
+        assert!(level.rooms.iter().all(|r| r.portals.is_empty()));
+        assert_eq!(level.rooms[1].get_sector(1, 1).unwrap().ceiling.as_ref().unwrap().opening, None);
+    }
+}
@@ -0,0 +1,244 @@
+//! Collectible pickups
+//!
+//! Pickups are placed per room and rendered as bobbing billboards.
+//! `PickupState` holds the runtime side (what's been collected, per-kind counters)
+//! and is not saved with the level - create a fresh one whenever a level is (re)loaded.
+
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
+use crate::rasterizer::{Camera, Vec2, Vec3, Vertex, Face as RasterFace};
+use super::{Level, Room, TextureRef, SECTOR_SIZE};
+
+/// Horizontal (XZ) distance within which the player collects a pickup
+pub const PICKUP_RADIUS: f32 = SECTOR_SIZE * 0.4;
+
+/// Billboard size (width and height) in world units
+pub const PICKUP_SIZE: f32 = SECTOR_SIZE * 0.4;
+
+/// Vertical bob amplitude in world units
+pub const PICKUP_BOB_HEIGHT: f32 = 48.0;
+
+/// Bob speed (radians per second)
+pub const PICKUP_BOB_SPEED: f32 = 2.5;
+
+/// Seconds before a respawning pickup comes back
+pub const PICKUP_RESPAWN_SECS: f64 = 30.0;
+
+/// Suggested kinds (any string is valid; counters are kept per kind)
+pub const PICKUP_KINDS: [&str; 5] = ["coin", "key", "health", "ammo", "secret"];
+
+fn default_pickup_kind() -> String {
+    PICKUP_KINDS[0].to_string()
+}
+
+/// A collectible item placed in a room
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pickup {
+    /// Position of the sprite's bottom center, relative to the room origin
+    pub position: Vec3,
+    /// Item kind (e.g. "coin", "key")
+    #[serde(default = "default_pickup_kind", deserialize_with = "super::limits::string")]
+    pub kind: String,
+    /// Billboard sprite
+    #[serde(default)]
+    pub sprite: TextureRef,
+    /// Comes back PICKUP_RESPAWN_SECS after being collected
+    #[serde(default)]
+    pub respawn: bool,
+}
+
+impl Pickup {
+    pub fn new(position: Vec3, kind: impl Into<String>, sprite: TextureRef) -> Self {
+        Self {
+            position,
+            kind: kind.into(),
+            sprite,
+            respawn: false,
+        }
+    }
+
+    /// Vertical bob offset at the given time (phase varies by position so pickups don't bob in sync)
+    pub fn bob_offset(&self, time: f64) -> f32 {
+        let phase = (self.position.x + self.position.z) / SECTOR_SIZE;
+        (time as f32 * PICKUP_BOB_SPEED + phase).sin() * PICKUP_BOB_HEIGHT
+    }
+
+    /// Can a player standing at `feet` (world space), `height` tall, reach this pickup?
+    /// The XZ distance must be under PICKUP_RADIUS and the player's vertical span must
+    /// overlap the sprite's.
+    pub fn in_reach(&self, room: &Room, feet: Vec3, height: f32) -> bool {
+        let world = room.position + self.position;
+        let dx = feet.x - world.x;
+        let dz = feet.z - world.z;
+        if dx * dx + dz * dz > PICKUP_RADIUS * PICKUP_RADIUS {
+            return false;
+        }
+        let (bottom, top) = (world.y, world.y + PICKUP_SIZE);
+        feet.y <= top && feet.y + height >= bottom
+    }
+
+    /// Append a camera-facing quad (rotates around Y only) to render data
+    pub fn add_billboard_to_render_data(
+        &self,
+        room: &Room,
+        time: f64,
+        camera: &Camera,
+        texture_id: usize,
+        vertices: &mut Vec<Vertex>,
+        faces: &mut Vec<RasterFace>,
+    ) {
+        let base_idx = vertices.len();
+        // basis_x is always horizontal; the normal faces back at the camera
+        let right = camera.basis_x;
+        let normal = Vec3::new(-camera.basis_z.x, 0.0, -camera.basis_z.z).normalize();
+
+        let mut center = room.position + self.position;
+        center.y += self.bob_offset(time);
+
+        let half = right * (PICKUP_SIZE * 0.5);
+        let up = Vec3::new(0.0, PICKUP_SIZE, 0.0);
+
+        // Same corner order and UVs as walls: bottom-left, bottom-right, top-right, top-left
+        let corners = [center - half, center + half, center + half + up, center - half + up];
+        let uvs = [Vec2::new(0.0, 1.0), Vec2::new(1.0, 1.0), Vec2::new(1.0, 0.0), Vec2::new(0.0, 0.0)];
+        for i in 0..4 {
+            vertices.push(Vertex::new(corners[i], uvs[i], normal));
+        }
+
+        faces.push(RasterFace::with_texture(base_idx, base_idx + 2, base_idx + 1, texture_id));
+        faces.push(RasterFace::with_texture(base_idx, base_idx + 3, base_idx + 2, texture_id));
+    }
+}
+
+/// A pickup collected this update
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickupEvent {
+    pub room: usize,
+    pub index: usize,
+    pub kind: String,
+}
+
+/// Runtime pickup state for one play session
+#[derive(Debug, Clone, Default)]
+pub struct PickupState {
+    /// (room index, pickup index) -> time collected
+    collected: HashMap<(usize, usize), f64>,
+    /// Number collected per kind
+    counters: BTreeMap<String, u32>,
+}
+
+impl PickupState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget everything collected (level reload)
+    pub fn reset(&mut self) {
+        self.collected.clear();
+        self.counters.clear();
+    }
+
+    /// Is this pickup currently collected (hidden)?
+    pub fn is_collected(&self, room: usize, index: usize) -> bool {
+        self.collected.contains_key(&(room, index))
+    }
+
+    /// Number of pickups of this kind collected so far
+    pub fn count(&self, kind: &str) -> u32 {
+        self.counters.get(kind).copied().unwrap_or(0)
+    }
+
+    /// All counters, sorted by kind (for HUD display)
+    pub fn counters(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counters.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+
+    /// Respawn expired pickups, then collect everything the player can reach.
+    /// Returns the pickups collected this call (callers hook sounds/effects to these).
+    pub fn update(&mut self, level: &Level, feet: Vec3, height: f32, time: f64) -> Vec<PickupEvent> {
+        self.collected.retain(|&(room, index), collected_at| {
+            let respawns = level.rooms.get(room)
+                .and_then(|r| r.pickups.get(index))
+                .is_some_and(|p| p.respawn);
+            !(respawns && time - *collected_at >= PICKUP_RESPAWN_SECS)
+        });
+
+        let mut events = Vec::new();
+        for (room_idx, room) in level.rooms.iter().enumerate() {
+            for (index, pickup) in room.pickups.iter().enumerate() {
+                if self.is_collected(room_idx, index) || !pickup.in_reach(room, feet, height) {
+                    continue;
+                }
+                self.collected.insert((room_idx, index), time);
+                *self.counters.entry(pickup.kind.clone()).or_insert(0) += 1;
+                events.push(PickupEvent { room: room_idx, index, kind: pickup.kind.clone() });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYER_HEIGHT: f32 = 762.0;
+
+    fn level_with_pickup(pickup: Pickup) -> Level {
+        let mut room = Room::new(0, Vec3::new(2048.0, 0.0, 0.0), 4, 4);
+        room.pickups.push(pickup);
+        let mut level = Level::new();
+        level.add_room(room);
+        level
+    }
+
+    #[test]
+    fn collects_within_radius_and_counts_by_kind() {
+        let level = level_with_pickup(Pickup::new(Vec3::new(512.0, 0.0, 512.0), "key", TextureRef::none()));
+        let mut state = PickupState::new();
+
+        // Too far away on XZ
+        let far = Vec3::new(2048.0 + 512.0 + PICKUP_RADIUS + 10.0, 0.0, 512.0);
+        assert!(state.update(&level, far, PLAYER_HEIGHT, 0.0).is_empty());
+
+        let near = Vec3::new(2048.0 + 600.0, 0.0, 512.0);
+        let events = state.update(&level, near, PLAYER_HEIGHT, 0.1);
+        assert_eq!(events, vec![PickupEvent { room: 0, index: 0, kind: "key".to_string() }]);
+        assert_eq!(state.count("key"), 1);
+
+        // Already collected
+        assert!(state.update(&level, near, PLAYER_HEIGHT, 0.2).is_empty());
+        assert!(state.is_collected(0, 0));
+
+        state.reset();
+        assert_eq!(state.count("key"), 0);
+        assert!(!state.is_collected(0, 0));
+    }
+
+    #[test]
+    fn requires_vertical_overlap() {
+        let level = level_with_pickup(Pickup::new(Vec3::new(512.0, 1024.0, 512.0), "coin", TextureRef::none()));
+        let mut state = PickupState::new();
+        let below = Vec3::new(2048.0 + 512.0, 0.0, 512.0);
+        assert!(state.update(&level, below, 200.0, 0.0).is_empty());
+        assert_eq!(state.update(&level, below, PLAYER_HEIGHT * 2.0, 0.0).len(), 1);
+    }
+
+    #[test]
+    fn respawning_pickups_come_back() {
+        let mut pickup = Pickup::new(Vec3::new(512.0, 0.0, 512.0), "health", TextureRef::none());
+        pickup.respawn = true;
+        let level = level_with_pickup(pickup);
+        let mut state = PickupState::new();
+        let here = Vec3::new(2048.0 + 512.0, 0.0, 512.0);
+        let away = Vec3::new(0.0, 0.0, 0.0);
+
+        assert_eq!(state.update(&level, here, PLAYER_HEIGHT, 0.0).len(), 1);
+        state.update(&level, away, PLAYER_HEIGHT, PICKUP_RESPAWN_SECS - 1.0);
+        assert!(state.is_collected(0, 0));
+        state.update(&level, away, PLAYER_HEIGHT, PICKUP_RESPAWN_SECS);
+        assert!(!state.is_collected(0, 0));
+        assert_eq!(state.update(&level, here, PLAYER_HEIGHT, PICKUP_RESPAWN_SECS + 1.0).len(), 1);
+        assert_eq!(state.count("health"), 2);
+    }
+}
@@ -0,0 +1,335 @@
+//! Line-of-sight queries
+//!
+//! `Level::raycast` walks the sector grid of the room the ray starts in, testing
+//! only the faces of the sectors it passes over, and follows portals into
+//! neighboring rooms. Nothing is allocated per call, so it's cheap enough for AI
+//! sight checks and triggers every frame.
+
+use crate::rasterizer::{ray_triangle_intersect, Vec3};
+use super::{Direction, HorizontalFace, Level, Room, Sector, SECTOR_SIZE};
+
+/// Most portals a single ray will cross (guards against portal loops)
+const MAX_PORTAL_HOPS: usize = 32;
+
+/// Which face of a sector a ray hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitFace {
+    Floor,
+    Ceiling,
+    /// Wall stack on an edge of the sector, and the index within that stack
+    Wall(Direction, usize),
+}
+
+/// Where a ray hit level geometry
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    /// World-space hit position
+    pub point: Vec3,
+    /// Distance from the ray start
+    pub distance: f32,
+    /// Normal of the face, on the side the ray came from
+    pub normal: Vec3,
+    /// Room index, sector grid coordinates and face that was hit
+    pub room: usize,
+    pub x: usize,
+    pub z: usize,
+    pub face: HitFace,
+}
+
+/// Nearest hit so far, as a fraction of the segment
+#[derive(Clone, Copy)]
+struct Candidate {
+    t: f32,
+    normal: Vec3,
+    x: usize,
+    z: usize,
+    face: HitFace,
+}
+
+/// Segment from `from` by `dir` (t in 0..=1)
+#[derive(Clone, Copy)]
+struct Segment {
+    from: Vec3,
+    dir: Vec3,
+}
+
+impl Segment {
+    fn at(&self, t: f32) -> Vec3 {
+        self.from + self.dir * t
+    }
+
+    /// Nearest hit (t) on a quad split into triangles (0, 1, 2) and (0, 2, 3),
+    /// with the quad's normal facing back along the segment
+    fn hit_quad(&self, corners: [Vec3; 4], min_t: f32) -> Option<(f32, Vec3)> {
+        let (t, normal) = [(0, 1, 2), (0, 2, 3)].into_iter()
+            .filter_map(|(a, b, c)| {
+                let t = ray_triangle_intersect(self.from, self.dir, corners[a], corners[b], corners[c])?;
+                (t >= min_t && t <= 1.0).then_some((t, (corners[b] - corners[a]).cross(corners[c] - corners[a])))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))?;
+        let normal = normal.normalize();
+        Some((t, if normal.dot(self.dir) > 0.0 { normal * -1.0 } else { normal }))
+    }
+}
+
+/// World-space corners (NW, NE, SE, SW) of each grid cell of a floor or ceiling
+/// (a single quad unless subdivided)
+fn horizontal_cells(face: &HorizontalFace, base_x: f32, base_z: f32) -> impl Iterator<Item = [Vec3; 4]> + '_ {
+    let n = face.cells();
+    let s = SECTOR_SIZE / n as f32;
+    (0..n * n).map(move |i| {
+        let (cx, cz) = (i % n, i / n);
+        let (x, z) = (base_x + cx as f32 * s, base_z + cz as f32 * s);
+        let h = face.cell_heights(cx, cz);
+        [
+            Vec3::new(x, h[0], z),
+            Vec3::new(x + s, h[1], z),
+            Vec3::new(x + s, h[2], z + s),
+            Vec3::new(x, h[3], z + s),
+        ]
+    })
+}
+
+/// Test every face of one sector, keeping the nearest hit
+fn hit_sector(seg: &Segment, room: &Room, x: usize, z: usize, sector: &Sector, min_t: f32, best: &mut Option<Candidate>) {
+    let base_x = room.position.x + x as f32 * SECTOR_SIZE;
+    let base_z = room.position.z + z as f32 * SECTOR_SIZE;
+    let mut consider = |hit: Option<(f32, Vec3)>, face: HitFace| {
+        if let Some((t, normal)) = hit {
+            if best.is_none_or(|b| t < b.t) {
+                *best = Some(Candidate { t, normal, x, z, face });
+            }
+        }
+    };
+
+    // Openings into rooms below/above are crossed through their portals
+    if let Some(floor) = sector.floor.as_ref().filter(|f| f.opening.is_none()) {
+        for cell in horizontal_cells(floor, base_x, base_z) {
+            consider(seg.hit_quad(cell, min_t), HitFace::Floor);
+        }
+    }
+    if let Some(ceiling) = sector.ceiling.as_ref().filter(|c| c.opening.is_none()) {
+        for cell in horizontal_cells(ceiling, base_x, base_z) {
+            consider(seg.hit_quad(cell, min_t), HitFace::Ceiling);
+        }
+    }
+    for edge in [Direction::North, Direction::East, Direction::South, Direction::West] {
+        for (i, wall) in sector.walls(edge).iter().enumerate() {
+            // Non-solid walls (grates, glass, foliage) don't block sight
+            if wall.solid {
+                consider(seg.hit_quad(wall.corners(base_x, base_z, edge), min_t), HitFace::Wall(edge, i));
+            }
+        }
+    }
+}
+
+/// Walk the room's sector grid from `t_start`; nearest geometry hit, if any
+fn hit_room(seg: &Segment, room: &Room, t_start: f32) -> Option<Candidate> {
+    // Clip the segment to the room's XZ footprint
+    let min = (room.position.x, room.position.z);
+    let max = (
+        room.position.x + room.width as f32 * SECTOR_SIZE,
+        room.position.z + room.depth as f32 * SECTOR_SIZE,
+    );
+    let (mut t0, mut t1) = (t_start, 1.0f32);
+    for (origin, dir, lo, hi) in [(seg.from.x, seg.dir.x, min.0, max.0), (seg.from.z, seg.dir.z, min.1, max.1)] {
+        if dir.abs() < 1e-9 {
+            if origin < lo || origin > hi {
+                return None;
+            }
+        } else {
+            let (a, b) = ((lo - origin) / dir, (hi - origin) / dir);
+            t0 = t0.max(a.min(b));
+            t1 = t1.min(a.max(b));
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+
+    // Grid walk (one cell at a time, in the order the segment crosses them)
+    let start = seg.at(t0);
+    let cell = |v: f32, origin: f32, count: usize| (((v - origin) / SECTOR_SIZE).floor().max(0.0) as usize).min(count - 1);
+    let (mut x, mut z) = (cell(start.x, min.0, room.width), cell(start.z, min.1, room.depth));
+    let step = |dir: f32| if dir > 0.0 { 1i32 } else { -1 };
+    let (step_x, step_z) = (step(seg.dir.x), step(seg.dir.z));
+    // t at which the segment crosses the next cell boundary on each axis
+    let next_t = |pos: usize, dir: f32, origin: f32, from: f32| -> f32 {
+        if dir.abs() < 1e-9 {
+            return f32::INFINITY;
+        }
+        let edge = origin + (pos as f32 + if dir > 0.0 { 1.0 } else { 0.0 }) * SECTOR_SIZE;
+        (edge - from) / dir
+    };
+    let (mut tx, mut tz) = (next_t(x, seg.dir.x, min.0, seg.from.x), next_t(z, seg.dir.z, min.1, seg.from.z));
+    let (dtx, dtz) = (SECTOR_SIZE / seg.dir.x.abs(), SECTOR_SIZE / seg.dir.z.abs());
+
+    let mut best = None;
+    loop {
+        if let Some(sector) = room.get_sector(x, z) {
+            hit_sector(seg, room, x, z, sector, t_start, &mut best);
+        }
+        let exit = tx.min(tz);
+        // Faces on this cell's edges can be hit a hair past the crossing point
+        if best.is_some_and(|b| b.t <= exit + 1e-4) || exit >= t1 {
+            return best;
+        }
+        if tx < tz {
+            let nx = x as i32 + step_x;
+            if nx < 0 || nx >= room.width as i32 {
+                return best;
+            }
+            x = nx as usize;
+            tx += dtx;
+        } else {
+            let nz = z as i32 + step_z;
+            if nz < 0 || nz >= room.depth as i32 {
+                return best;
+            }
+            z = nz as usize;
+            tz += dtz;
+        }
+    }
+}
+
+/// First portal of `room` the segment passes through after `t_start`:
+/// (t, target room index)
+fn hit_portal(level: &Level, seg: &Segment, room: &Room, t_start: f32) -> Option<(f32, usize)> {
+    room.portals.iter()
+        .filter_map(|portal| {
+            let corners = portal.vertices.map(|v| v + room.position);
+            let (t, _) = seg.hit_quad(corners, t_start)?;
+            Some((t, level.room_index(portal.target_room)?))
+        })
+        .filter(|&(t, _)| t > t_start + 1e-5)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+impl Level {
+    /// First level geometry hit on the segment from `from` to `to`, or None if the
+    /// way is clear. Starts in the room containing `from` and follows portals; solid
+    /// walls, floors and ceilings block, non-solid walls don't. A segment that starts
+    /// outside every room, or leaves through an opening with no portal, hits nothing.
+    pub fn raycast(&self, from: Vec3, to: Vec3) -> Option<RayHit> {
+        let seg = Segment { from, dir: to - from };
+        let length = seg.dir.len();
+        if length < 1e-6 {
+            return None;
+        }
+
+        let mut room_idx = self.find_room_at(from)?;
+        let mut t_start = 0.0;
+        for _ in 0..MAX_PORTAL_HOPS {
+            let room = &self.rooms[room_idx];
+            let hit = hit_room(&seg, room, t_start);
+            let portal = hit_portal(self, &seg, room, t_start);
+
+            match (hit, portal) {
+                // Geometry in front of the portal (or no portal): that's the hit
+                (Some(hit), p) if p.is_none_or(|(pt, _)| hit.t <= pt) => {
+                    return Some(RayHit {
+                        point: seg.at(hit.t),
+                        distance: hit.t * length,
+                        normal: hit.normal,
+                        room: room_idx,
+                        x: hit.x,
+                        z: hit.z,
+                        face: hit.face,
+                    });
+                }
+                (_, Some((pt, target))) => {
+                    room_idx = target;
+                    t_start = pt;
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Nearest hit on the segment in any room, ignoring portals. For editor picking,
+    /// where the camera usually starts outside every room.
+    pub fn pick(&self, from: Vec3, to: Vec3) -> Option<RayHit> {
+        let seg = Segment { from, dir: to - from };
+        let length = seg.dir.len();
+        if length < 1e-6 {
+            return None;
+        }
+        self.rooms.iter().enumerate()
+            .filter_map(|(room_idx, room)| Some((room_idx, hit_room(&seg, room, 0.0)?)))
+            .min_by(|a, b| a.1.t.total_cmp(&b.1.t))
+            .map(|(room_idx, hit)| RayHit {
+                point: seg.at(hit.t),
+                distance: hit.t * length,
+                normal: hit.normal,
+                room: room_idx,
+                x: hit.x,
+                z: hit.z,
+                face: hit.face,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{create_two_room_level, TextureRef};
+
+    #[test]
+    fn straight_down_onto_sloped_floor() {
+        let mut room = Room::new(0, Vec3::ZERO, 1, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::none());
+        room.set_ceiling(0, 0, 2048.0, TextureRef::none());
+        // Rises 512 from west to east
+        room.get_sector_mut(0, 0).unwrap().floor.as_mut().unwrap().heights = [0.0, 512.0, 512.0, 0.0];
+        room.recalculate_bounds();
+        let mut level = Level::new();
+        level.add_room(room);
+
+        let hit = level.raycast(Vec3::new(768.0, 1500.0, 256.0), Vec3::new(768.0, -100.0, 256.0)).unwrap();
+        assert_eq!(hit.face, HitFace::Floor);
+        assert!((hit.point.y - 384.0).abs() < 0.5);
+        assert!((hit.distance - (1500.0 - 384.0)).abs() < 0.5);
+        // Tilted toward the west, facing up
+        assert!(hit.normal.y > 0.8 && hit.normal.x < -0.1);
+    }
+
+    #[test]
+    fn crosses_open_portal_into_next_room() {
+        let level = create_two_room_level();
+        let from = Vec3::new(512.0, 512.0, 700.0);
+
+        // Ends inside the second room: nothing in the way
+        assert!(level.raycast(from, Vec3::new(3500.0, 512.0, 700.0)).is_none());
+
+        // Runs on to the far wall of the second room
+        let hit = level.raycast(from, Vec3::new(6000.0, 512.0, 700.0)).unwrap();
+        assert_eq!((hit.room, hit.x, hit.z, hit.face), (1, 1, 0, HitFace::Wall(Direction::East, 0)));
+        assert!((hit.point.x - 4096.0).abs() < 0.5);
+        assert!(hit.normal.x < -0.99);
+    }
+
+    #[test]
+    fn solid_walls_block_and_non_solid_walls_dont() {
+        let mut room = Room::new(0, Vec3::ZERO, 3, 1);
+        for x in 0..3 {
+            room.set_floor(x, 0, 0.0, TextureRef::none());
+            room.set_ceiling(x, 0, 1024.0, TextureRef::none());
+        }
+        // A grate between sectors 0 and 1, a solid wall between 1 and 2
+        room.add_wall(0, 0, Direction::East, 0.0, 1024.0, TextureRef::none());
+        room.get_sector_mut(0, 0).unwrap().walls_east[0].solid = false;
+        room.add_wall(2, 0, Direction::West, 0.0, 1024.0, TextureRef::none());
+        room.recalculate_bounds();
+        let mut level = Level::new();
+        level.add_room(room);
+
+        let from = Vec3::new(512.0, 512.0, 512.0);
+        let hit = level.raycast(from, Vec3::new(2800.0, 512.0, 512.0)).unwrap();
+        assert_eq!((hit.x, hit.face), (2, HitFace::Wall(Direction::West, 0)));
+        assert!((hit.distance - 1536.0).abs() < 0.5);
+
+        // Short of the solid wall: clear
+        assert!(level.raycast(from, Vec3::new(1800.0, 512.0, 512.0)).is_none());
+    }
+}
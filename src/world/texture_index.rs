@@ -0,0 +1,136 @@
+//! Texture references to texture ids
+//!
+//! Faces name their texture by pack and name; the rasterizer wants an index into one
+//! flat texture list. `TextureIndex` maps between the two with the level's pack
+//! aliases applied, so a level made with a pack the viewer doesn't have can borrow
+//! textures from another one. The editor viewport, thumbnails, the game view and
+//! headless rendering all resolve through it.
+
+use std::collections::{BTreeMap, HashMap};
+use super::{Level, TextureRef};
+
+/// Substitute pack by missing pack name (see `Level::pack_aliases`)
+pub type PackAliases = BTreeMap<String, String>;
+
+/// Pack to look `pack`'s textures up in: its substitute, or itself
+pub fn aliased_pack<'a>(aliases: &'a PackAliases, pack: &'a str) -> &'a str {
+    aliases.get(pack).map_or(pack, String::as_str)
+}
+
+/// Texture ids by pack, then texture name
+#[derive(Debug, Clone, Default)]
+pub struct TextureIndex {
+    ids: HashMap<String, HashMap<String, usize>>,
+    len: usize,
+}
+
+impl TextureIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give the next id to texture `name` of `pack` (textures are pushed in the
+    /// order of the flat list they index)
+    pub fn push(&mut self, pack: &str, name: &str) -> usize {
+        let id = self.len;
+        self.insert(pack, name, id);
+        id
+    }
+
+    /// Map a reference to a given id (e.g. a placeholder appended after the packs)
+    pub fn insert(&mut self, pack: &str, name: &str, id: usize) {
+        self.ids.entry(pack.to_string()).or_default().insert(name.to_string(), id);
+        self.len = self.len.max(id + 1);
+    }
+
+    /// Number of ids handed out (one past the largest)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Does any indexed texture come from `pack`?
+    pub fn has_pack(&self, pack: &str) -> bool {
+        self.ids.contains_key(pack)
+    }
+
+    /// Id of the texture `tex_ref` names, looked up in its pack's substitute when
+    /// there is one, else as written (where placeholders are keyed). None for empty
+    /// references and textures nothing provides.
+    pub fn get(&self, tex_ref: &TextureRef, aliases: &PackAliases) -> Option<usize> {
+        if !tex_ref.is_valid() {
+            return None;
+        }
+        let pack = aliased_pack(aliases, &tex_ref.pack);
+        self.lookup(pack, &tex_ref.name)
+            .or_else(|| (pack != tex_ref.pack).then(|| self.lookup(&tex_ref.pack, &tex_ref.name)).flatten())
+    }
+
+    /// Like `get`, but empty references get the first texture (how untextured faces
+    /// are drawn)
+    pub fn resolve(&self, tex_ref: &TextureRef, aliases: &PackAliases) -> Option<usize> {
+        if !tex_ref.is_valid() {
+            return if self.is_empty() { None } else { Some(0) };
+        }
+        self.get(tex_ref, aliases)
+    }
+
+    fn lookup(&self, pack: &str, name: &str) -> Option<usize> {
+        self.ids.get(pack)?.get(name).copied()
+    }
+}
+
+impl Level {
+    /// Packs the level's faces use that `has_pack` rejects (after aliases), with
+    /// how many faces use each, sorted by name
+    pub fn missing_packs(&self, has_pack: impl Fn(&str) -> bool) -> Vec<(String, usize)> {
+        let mut missing: BTreeMap<&str, usize> = BTreeMap::new();
+        for tex in self.texture_refs().filter(|t| t.is_valid()) {
+            if !has_pack(aliased_pack(&self.pack_aliases, &tex.pack)) {
+                *missing.entry(tex.pack.as_str()).or_default() += 1;
+            }
+        }
+        missing.into_iter().map(|(pack, faces)| (pack.to_string(), faces)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rasterizer::Vec3;
+    use crate::world::{Direction, Room};
+
+    #[test]
+    fn aliases_send_missing_packs_to_a_substitute() {
+        let mut index = TextureIndex::new();
+        assert_eq!(index.push("retro", "FLOOR"), 0);
+        assert_eq!(index.push("retro", "WALL"), 1);
+        index.insert("GOTHIC", "ARCH", 2);
+
+        let mut room = Room::new(0, Vec3::ZERO, 2, 1);
+        room.set_floor(0, 0, 0.0, TextureRef::new("GOTHIC", "FLOOR"));
+        room.set_floor(1, 0, 0.0, TextureRef::new("retro", "WALL"));
+        room.add_wall(0, 0, Direction::North, 0.0, 1024.0, TextureRef::new("GOTHIC", "ARCH"));
+        room.add_wall(0, 0, Direction::West, 0.0, 1024.0, TextureRef::none());
+        let mut level = Level::new();
+        level.add_room(room);
+
+        let real_packs = |pack: &str| pack == "retro";
+        assert_eq!(level.missing_packs(real_packs), vec![("GOTHIC".to_string(), 2)]);
+        let floor = TextureRef::new("GOTHIC", "FLOOR");
+        assert_eq!(index.get(&floor, &level.pack_aliases), None);
+        assert_eq!(index.resolve(&TextureRef::none(), &level.pack_aliases), Some(0));
+
+        // Substitute: same-named textures come from "retro", others still reach
+        // their placeholder
+        level.pack_aliases.insert("GOTHIC".to_string(), "retro".to_string());
+        assert!(level.missing_packs(real_packs).is_empty());
+        assert_eq!(index.get(&floor, &level.pack_aliases), Some(0));
+        assert_eq!(index.get(&TextureRef::new("GOTHIC", "ARCH"), &level.pack_aliases), Some(2));
+        assert_eq!(index.get(&TextureRef::new("GOTHIC", "GONE"), &level.pack_aliases), None);
+        assert!(index.has_pack("retro") && !index.has_pack("MISSING") && index.len() == 3);
+    }
+}